@@ -4,12 +4,21 @@
 
 use async_trait::async_trait;
 use std::sync::Arc;
-use zed42_core::{AgentBehavior, AgentId, Artifact, Result, Task};
-use zed42_llm::{ConstrainedGen, LlmClient};
+use zed42_core::types::Confidence;
+use zed42_core::{AgentBehavior, AgentId, Artifact, ArtifactType, Result, Task, TraceKind, TraceRecord};
+use zed42_llm::{ConstrainedGen, LlmClient, ModelConfig};
+use zed42_memory::feedback::FeedbackDigest;
+use zed42_toolboxes::docsite::{DocsGenerator, DocsLayout, DocsModel};
+use zed42_toolboxes::languages::Language;
+use zed42_toolboxes::policy::PolicyEnforcer;
+use zed42_toolboxes::standards::StandardsProfile;
+use zed42_toolboxes::vcs::OwnershipMap;
 use serde::{Deserialize, Serialize};
 use schemars::JsonSchema;
 use uuid::Uuid;
 
+use crate::strategy::ReasoningStrategy;
+
 /// Agent state machine - enforces valid transitions
 #[derive(Debug, Clone)]
 pub enum AgentState {
@@ -65,6 +74,9 @@ pub struct CodeGenerationResponse {
     pub tests: Option<String>,
     /// Brief explanation of the implementation
     pub explanation: String,
+    /// Self-assessed confidence (0.0-1.0) that the code is correct and complete
+    #[serde(default)]
+    pub confidence: Option<f32>,
 }
 
 /// LLM response schema for self-critique
@@ -78,12 +90,27 @@ pub struct CritiqueResponse {
     pub suggestions: Vec<String>,
 }
 
+/// LLM response schema for plan-then-execute planning
+#[derive(Debug, Clone, JsonSchema, Serialize, Deserialize)]
+pub struct PlanStepsResponse {
+    /// Implementation steps in order
+    pub steps: Vec<String>,
+}
+
+/// Confidence of code no critique looked at, unless the model rated it
+const UNREVIEWED_CONFIDENCE: Confidence = 0.5;
+
 /// FeatureImplementer agent - implements new functionality with tests
 pub struct FeatureImplementer {
     id: AgentId,
     llm_client: Arc<dyn LlmClient>,
     state: AgentState,
-    max_reflexion_iterations: u8,
+    strategy: ReasoningStrategy,
+    standards: Option<Arc<StandardsProfile>>,
+    /// Past user corrections of FeatureImplementer output
+    feedback: Option<FeedbackDigest>,
+    /// Prompts, critiques and artifacts of processed tasks, until taken
+    trace: Vec<TraceRecord>,
 }
 
 impl FeatureImplementer {
@@ -93,10 +120,39 @@ impl FeatureImplementer {
             id: Uuid::new_v4(),
             llm_client,
             state: AgentState::Idle,
-            max_reflexion_iterations: 3,
+            strategy: ReasoningStrategy::default(),
+            standards: None,
+            feedback: None,
+            trace: Vec::new(),
         }
     }
 
+    /// Reason with `strategy` instead of the default reflexion loop
+    pub fn with_strategy(mut self, strategy: ReasoningStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Hold generated code to the project standards profile
+    ///
+    /// The profile's rubric is added to the generation and critique prompts,
+    /// and its static checks run on approval.
+    pub fn with_standards(mut self, standards: Arc<StandardsProfile>) -> Self {
+        self.standards = Some(standards);
+        self
+    }
+
+    /// Show past user corrections in the generation prompt
+    pub fn with_feedback(mut self, feedback: FeedbackDigest) -> Self {
+        self.feedback = (!feedback.is_empty()).then_some(feedback);
+        self
+    }
+
+    /// Trace records gathered since the last call, for the blackboard
+    pub fn take_trace(&mut self) -> Vec<TraceRecord> {
+        std::mem::take(&mut self.trace)
+    }
+
     /// Process a task and return an artifact
     pub async fn process_task(&mut self, task: Task) -> Result<Artifact> {
         // Transition to Processing state
@@ -106,18 +162,35 @@ impl FeatureImplementer {
 
         tracing::info!(agent_id = %self.id, task_id = %task.id, "Starting task processing");
 
+        let strategy = self.strategy.clone();
+        let mut base_prompt = self.build_prompt(&task);
+        // Plan-then-execute spends round 0 on the plan
+        let first_round = if strategy == ReasoningStrategy::PlanThenExecute {
+            let steps = self.plan_steps(&task, &base_prompt).await?;
+            base_prompt.push_str("\n\nImplement this plan:\n");
+            for (n, step) in steps.iter().enumerate() {
+                base_prompt.push_str(&format!("{}. {}\n", n + 1, step));
+            }
+            1
+        } else {
+            0
+        };
+
         let mut current_code: Option<CodeGenerationResponse> = None;
         let mut feedback: Option<String> = None;
+        // (passed, iteration, open issues) of the latest critique
+        let mut outcome: Option<(bool, u8, usize)> = None;
 
-        // --- Phase 2.4: Reflexion Loop ---
-        for i in 0..self.max_reflexion_iterations {
-            tracing::info!(agent_id = %self.id, iteration = i, "Reflexion loop: Generating implementation");
+        // --- Phase 2.4: Reasoning loop, shaped by the strategy ---
+        for i in first_round..first_round + strategy.max_rounds() {
+            tracing::info!(agent_id = %self.id, iteration = i, strategy = %strategy.label(), "Generating implementation");
             
             // 1. Generate Proposal
-            let mut prompt = self.build_prompt(&task);
+            let mut prompt = base_prompt.clone();
             if let Some(ref fb) = feedback {
                 prompt.push_str(&format!("\n\nPrevious attempt had the following issues:\n{}\nPlease fix these and provide a new implementation.", fb));
             }
+            self.trace.push(TraceRecord::new(task.id.clone(), self.id, TraceKind::Prompt { iteration: i }, prompt.clone()));
 
             let response: CodeGenerationResponse = ConstrainedGen::new(self.llm_client.as_ref())
                 .system(self.system_prompt())
@@ -125,30 +198,53 @@ impl FeatureImplementer {
                 .generate()
                 .await
                 .map_err(|e| zed42_core::Error::Llm(e.to_string()))?;
+            self.trace.push(TraceRecord::new(task.id.clone(), self.id, TraceKind::Response { iteration: i }, response.code.clone()));
+
+            if !strategy.critiques() {
+                current_code = Some(response);
+                break;
+            }
 
             // 2. Critique Proposal
-            tracing::info!(agent_id = %self.id, iteration = i, "Reflexion loop: Critiquing implementation");
-            let critique_prompt = format!(
+            tracing::info!(agent_id = %self.id, iteration = i, "Critiquing implementation");
+            let language = task_language(&task).unwrap_or(Language::Rust);
+            let mut critique_prompt = format!(
                 "Critique the following code implementation based on the task and context.\n\
                 Task: {}\n\
-                Implementation:\n```rust\n{}\n```\n\
+                Implementation:\n```{}\n{}\n```\n\
                 Evaluate for: correctness, safety (no unwrap), adherence to constraints, and test coverage.",
-                task.description, response.code
+                task.description, language.fence(), response.code
             );
+            if let Some(rubric) = self.standards_rubric() {
+                critique_prompt.push_str(&format!("\nFail the review if any of these are not met.\n{}", rubric));
+            }
 
-            let critique: CritiqueResponse = ConstrainedGen::new(self.llm_client.as_ref())
-                .system("You are a senior security and quality reviewer. Be strict. Reject any code with unhandled results, inadequate comments, or missing edge cases.")
-                .prompt(critique_prompt)
+            let mut critic = ConstrainedGen::new(self.llm_client.as_ref()).prompt(critique_prompt);
+            critic = match &strategy {
+                // The challenger model argues against the proposal
+                ReasoningStrategy::Debate { challenger, .. } => critic
+                    .system("You are debating another engineer's implementation. Argue against it: find every way it is wrong, unsafe or incomplete. Pass it only if you cannot.")
+                    .model_config(ModelConfig { model: challenger.clone(), ..ModelConfig::default() }),
+                _ => critic.system("You are a senior security and quality reviewer. Be strict. Reject any code with unhandled results, inadequate comments, or missing edge cases."),
+            };
+            let critique: CritiqueResponse = critic
                 .generate()
                 .await
                 .map_err(|e| zed42_core::Error::Llm(e.to_string()))?;
 
+            outcome = Some((critique.pass, i - first_round, critique.issues.len()));
+            self.trace.push(TraceRecord::new(
+                task.id.clone(),
+                self.id,
+                TraceKind::Critique { iteration: i, passed: critique.pass, issues: critique.issues.clone() },
+                critique.suggestions.join("; "),
+            ));
             if critique.pass {
-                tracing::info!(agent_id = %self.id, iteration = i, "Reflexion loop: Critique passed");
+                tracing::info!(agent_id = %self.id, iteration = i, "Critique passed");
                 current_code = Some(response);
                 break;
             } else {
-                tracing::warn!(agent_id = %self.id, iteration = i, "Reflexion loop: Critique failed, retrying");
+                tracing::warn!(agent_id = %self.id, iteration = i, "Critique failed, retrying");
                 feedback = Some(critique.issues.join("\n"));
                 current_code = Some(response); // Keep latest as fallback
             }
@@ -158,12 +254,27 @@ impl FeatureImplementer {
             zed42_core::Error::Agent("Exhausted reflexion iterations without generating code".to_string())
         })?;
 
+        let confidence = match outcome {
+            Some((passed, iteration, open_issues)) => {
+                derive_confidence(passed, iteration, open_issues, final_response.confidence)
+            }
+            None => final_response.confidence.map_or(UNREVIEWED_CONFIDENCE, |own| own.clamp(0.0, 1.0)),
+        };
+
         // Create artifact
         let artifact = Artifact::code(
             task.id.clone(),
             final_response.code,
             None, // File path will be set by toolbox
-        );
+        )
+        .with_confidence(confidence)
+        .with_strategy(strategy.label());
+        self.trace.push(TraceRecord::new(
+            task.id.clone(),
+            self.id,
+            TraceKind::Artifact { artifact_id: artifact.id.clone(), file_path: artifact.file_path.clone() },
+            final_response.explanation,
+        ));
 
         // Transition to AwaitingReview
         self.state = self.state.clone()
@@ -187,6 +298,29 @@ impl FeatureImplementer {
         }
     }
 
+    /// Approve the current artifact only if it passes the project policy
+    ///
+    /// Violations leave the agent in AwaitingReview so the artifact can be fixed.
+    pub fn approve_with_policy(&mut self, enforcer: &PolicyEnforcer) -> Result<Artifact> {
+        if let AgentState::AwaitingReview(ref artifact) = self.state {
+            let mut violations = enforcer.check_artifact(artifact);
+            // Standards already applied by the enforcer are not checked twice
+            if let (Some(standards), None) = (&self.standards, enforcer.standards()) {
+                let path = artifact.file_path.as_deref().unwrap_or(&artifact.id);
+                violations.extend(standards.check_source(path, &artifact.content));
+            }
+            if !violations.is_empty() {
+                let details: Vec<String> = violations.iter().map(|v| v.to_string()).collect();
+                return Err(zed42_core::Error::Agent(format!(
+                    "Artifact blocked by {} policy violation(s):\n{}",
+                    violations.len(),
+                    details.join("\n")
+                )));
+            }
+        }
+        self.approve()
+    }
+
     /// Get current agent state
     pub fn state(&self) -> &AgentState {
         &self.state
@@ -206,6 +340,10 @@ impl FeatureImplementer {
             prompt.push_str(&format!("\nContext: {}\n", context));
         }
 
+        if let Some(ref pack) = task.context_pack {
+            prompt.push_str(&format!("\n{}", pack.render()));
+        }
+
         if !task.constraints.is_empty() {
             prompt.push_str("\nConstraints:\n");
             for constraint in &task.constraints {
@@ -213,9 +351,66 @@ impl FeatureImplementer {
             }
         }
 
+        if let Some(language) = task_language(task) {
+            prompt.push_str(&format!("\n{}\n", language.prompt_hint()));
+        }
+
+        if let Some(rubric) = self.standards_rubric() {
+            prompt.push_str(&format!("\n{}\n", rubric));
+        }
+
+        if let Some(ref feedback) = self.feedback {
+            prompt.push_str(&format!("\n{}", feedback.render()));
+        }
+
         prompt.push_str("\nGenerate code that fulfills this task.");
         prompt
     }
+
+    /// Ordered implementation steps for the task, traced as round 0
+    async fn plan_steps(&mut self, task: &Task, task_prompt: &str) -> Result<Vec<String>> {
+        let prompt = format!("{}\n\nBefore writing any code, list the implementation steps in order.", task_prompt);
+        self.trace.push(TraceRecord::new(task.id.clone(), self.id, TraceKind::Prompt { iteration: 0 }, prompt.clone()));
+        let plan: PlanStepsResponse = ConstrainedGen::new(self.llm_client.as_ref())
+            .system(self.system_prompt())
+            .prompt(prompt)
+            .generate()
+            .await
+            .map_err(|e| zed42_core::Error::Llm(e.to_string()))?;
+        self.trace.push(TraceRecord::new(task.id.clone(), self.id, TraceKind::Response { iteration: 0 }, plan.steps.join("; ")));
+        Ok(plan.steps)
+    }
+
+    fn standards_rubric(&self) -> Option<String> {
+        self.standards
+            .as_ref()
+            .map(|s| s.critique_rubric())
+            .filter(|rubric| !rubric.is_empty())
+    }
+}
+
+/// Language of the files a task names, if it names any
+fn task_language(task: &Task) -> Option<Language> {
+    Language::detect_in_text(&task.description)
+        .or_else(|| task.context.as_deref().and_then(Language::detect_in_text))
+}
+
+/// Confidence from the critique outcome and the model's self-assessment
+///
+/// Passing critique on the first round scores 1.0 and each further round
+/// costs 0.15; code that never passed scores 0.3 less 0.05 per open issue.
+/// A self-assessment, when the model gave one, is averaged in.
+fn derive_confidence(passed: bool, iteration: u8, open_issues: usize, self_assessment: Option<f32>) -> Confidence {
+    let critique = if passed {
+        1.0 - 0.15 * f32::from(iteration)
+    } else {
+        0.3 - 0.05 * open_issues as f32
+    }
+    .clamp(0.05, 1.0);
+    match self_assessment {
+        Some(own) => (critique + own.clamp(0.0, 1.0)) / 2.0,
+        None => critique,
+    }
 }
 
 #[async_trait]
@@ -241,6 +436,97 @@ impl AgentBehavior for FeatureImplementer {
     }
 }
 
+/// Where suggested code owners are written
+pub const CODEOWNERS_PATH: &str = ".github/CODEOWNERS";
+
+/// DocumentationWriter agent - keeps project documentation in step with the code
+pub struct DocumentationWriter {
+    id: AgentId,
+}
+
+impl DocumentationWriter {
+    pub fn new() -> Self {
+        Self { id: Uuid::new_v4() }
+    }
+
+    /// CODEOWNERS suggestion from the git ownership map
+    ///
+    /// Directories are assigned to authors holding at least `min_share` of
+    /// their history; the artifact goes through review like any other.
+    pub fn suggest_codeowners(&self, task: &Task, ownership: &OwnershipMap, min_share: f64) -> Artifact {
+        let mut artifact = Artifact::code(
+            task.id.clone(),
+            ownership.suggest_codeowners(min_share),
+            Some(CODEOWNERS_PATH.to_string()),
+        );
+        artifact.artifact_type = ArtifactType::Documentation;
+        tracing::info!(agent_id = %self.id, task_id = %task.id, "Suggested CODEOWNERS from git history");
+        artifact
+    }
+
+    /// Docs site pages whose knowledge-graph nodes changed since the last refresh
+    ///
+    /// API pages, the architecture diagram and new ADRs are rendered in the
+    /// given layout against the manifest under `project_root`; each page
+    /// (and the updated manifest) is returned as a documentation artifact.
+    pub async fn refresh_docs(
+        &self,
+        task: &Task,
+        graph: &zed42_memory::knowledge_graph::KnowledgeGraphMemory,
+        project_root: &std::path::Path,
+        layout: DocsLayout,
+    ) -> Result<Vec<Artifact>> {
+        let generator = DocsGenerator::new(project_root, layout);
+        let model = DocsModel::load(graph).await?;
+        let update = generator.plan(&model, &generator.load_manifest()?)?;
+        tracing::info!(
+            agent_id = %self.id,
+            task_id = %task.id,
+            pages = update.pages.len(),
+            unchanged = update.unchanged.len(),
+            "Planned documentation refresh"
+        );
+        Ok(update
+            .pages
+            .into_iter()
+            .map(|page| {
+                let mut artifact =
+                    Artifact::code(task.id.clone(), page.content, Some(page.path.to_string_lossy().into_owned()));
+                artifact.artifact_type = ArtifactType::Documentation;
+                artifact
+            })
+            .collect())
+    }
+}
+
+impl Default for DocumentationWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl AgentBehavior for DocumentationWriter {
+    fn id(&self) -> AgentId {
+        self.id
+    }
+
+    async fn initialize(&mut self) -> Result<()> {
+        tracing::info!(agent_id = %self.id, "DocumentationWriter initialized");
+        Ok(())
+    }
+
+    async fn run(&mut self) -> Result<()> {
+        tracing::info!(agent_id = %self.id, "DocumentationWriter running");
+        Ok(())
+    }
+
+    async fn shutdown(&mut self) -> Result<()> {
+        tracing::info!(agent_id = %self.id, "DocumentationWriter shutting down");
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -289,6 +575,7 @@ mod tests {
         let artifact = agent.process_task(task).await.expect("Should succeed");
 
         assert!(artifact.content.contains("add"));
+        assert_eq!(artifact.confidence, Some(1.0));
         assert!(matches!(agent.state(), AgentState::AwaitingReview(_)));
     }
 
@@ -296,7 +583,7 @@ mod tests {
     async fn test_feature_implementer_with_reflexion() {
         let code_1 = r#"{"code": "fn add(a: i32, b: i32) { a + b }", "tests": null, "explanation": "Missing return type"}"#;
         let critique_1 = r#"{"issues": ["Missing return type"], "pass": false, "suggestions": ["Add -> i32"]}"#;
-        let code_2 = r#"{"code": "fn add(a: i32, b: i32) -> i32 { a + b }", "tests": null, "explanation": "Fixed version"}"#;
+        let code_2 = r#"{"code": "fn add(a: i32, b: i32) -> i32 { a + b }", "tests": null, "explanation": "Fixed version", "confidence": 0.75}"#;
         let critique_2 = r#"{"issues": [], "pass": true, "suggestions": []}"#;
 
         let client = Arc::new(MockLlmClient::with_responses(vec![
@@ -312,6 +599,166 @@ mod tests {
         let artifact = agent.process_task(task).await.expect("Should succeed after reflexion");
 
         assert!(artifact.content.contains("-> i32"));
+        // Second-round pass (0.85) averaged with the model's own 0.75
+        assert!((artifact.confidence.unwrap() - 0.8).abs() < 1e-6);
+        assert!(matches!(agent.state(), AgentState::AwaitingReview(_)));
+
+        // Prompt, reply and critique per attempt, then the artifact
+        let trace = agent.take_trace();
+        assert_eq!(trace.len(), 7);
+        assert!(matches!(trace[1].kind, TraceKind::Response { iteration: 0 }));
+        assert!(matches!(trace[2].kind, TraceKind::Critique { iteration: 0, passed: false, .. }));
+        assert_eq!(trace[6].artifact_id(), Some(artifact.id.as_str()));
+        assert!(agent.take_trace().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_strategies_shape_the_loop() {
+        let code = r#"{"code": "fn add(a: i32, b: i32) -> i32 { a + b }", "tests": null, "explanation": "Adds"}"#;
+        let client = Arc::new(MockLlmClient::with_responses(vec![code.to_string()]));
+        let mut agent = FeatureImplementer::new(client).with_strategy(ReasoningStrategy::SingleShot);
+        let artifact = agent.process_task(Task::new("Add two numbers")).await.unwrap();
+        assert_eq!(artifact.strategy.as_deref(), Some("single_shot"));
+        assert_eq!(artifact.confidence, Some(UNREVIEWED_CONFIDENCE));
+        assert_eq!(agent.take_trace().len(), 3);
+
+        let plan = r#"{"steps": ["Define the signature", "Return the sum"]}"#;
+        let critique = r#"{"issues": [], "pass": true, "suggestions": []}"#;
+        let client = Arc::new(MockLlmClient::with_responses(vec![plan.to_string(), code.to_string(), critique.to_string()]));
+        let mut agent = FeatureImplementer::new(client).with_strategy(ReasoningStrategy::PlanThenExecute);
+        let artifact = agent.process_task(Task::new("Add two numbers")).await.unwrap();
+        assert_eq!(artifact.strategy.as_deref(), Some("plan_then_execute"));
+        assert_eq!(artifact.confidence, Some(1.0));
+
+        let trace = agent.take_trace();
+        assert!(matches!(trace[1].kind, TraceKind::Response { iteration: 0 }));
+        assert!(trace[2].summary.contains("1. Define the signature"));
+        assert!(matches!(trace[4].kind, TraceKind::Critique { iteration: 1, passed: true, .. }));
+    }
+
+    #[tokio::test]
+    async fn test_policy_violation_blocks_approval() {
+        use zed42_toolboxes::policy::{BannedApi, PolicyConfig};
+
+        let code_response = r#"{"code": "fn parse(s: &str) -> i32 { s.parse().unwrap() }", "tests": null, "explanation": "Parses input"}"#;
+        let critique_response = r#"{"issues": [], "pass": true, "suggestions": []}"#;
+        let client = Arc::new(MockLlmClient::with_responses(vec![
+            code_response.to_string(),
+            critique_response.to_string(),
+        ]));
+
+        let mut agent = FeatureImplementer::new(client);
+        agent.process_task(Task::new("Parse an integer")).await.unwrap();
+
+        let enforcer = PolicyEnforcer::new(PolicyConfig {
+            banned_apis: vec![BannedApi {
+                pattern: ".unwrap()".to_string(),
+                reason: "Handle errors explicitly".to_string(),
+                extensions: vec![],
+            }],
+            ..Default::default()
+        });
+
+        assert!(agent.approve_with_policy(&enforcer).is_err());
+        assert!(matches!(agent.state(), AgentState::AwaitingReview(_)));
+    }
+
+    #[test]
+    fn test_feedback_in_prompt() {
+        let client = Arc::new(MockLlmClient::new(String::new()));
+        let digest = FeedbackDigest {
+            agent_type: "FeatureImplementer".to_string(),
+            edited: 0,
+            rejected: 1,
+            lessons: vec!["Output rejected (reason: Use the existing retry helper)".to_string()],
+        };
+        let agent = FeatureImplementer::new(client.clone()).with_feedback(digest);
+        assert!(agent.build_prompt(&Task::new("Add retries")).contains("- Output rejected (reason: Use the existing retry helper)"));
+
+        let agent = FeatureImplementer::new(client).with_feedback(FeedbackDigest::default());
+        assert!(!agent.build_prompt(&Task::new("Add retries")).contains("Reviewers corrected"));
+    }
+
+    #[tokio::test]
+    async fn test_project_standards_block_approval() {
+        use zed42_toolboxes::policy::PolicyConfig;
+
+        let code_response = r#"{"code": "pub fn parseInput(s: &str) -> usize { s.len() }", "tests": null, "explanation": "Measures input"}"#;
+        let critique_response = r#"{"issues": [], "pass": true, "suggestions": []}"#;
+        let client = Arc::new(MockLlmClient::with_responses(vec![
+            code_response.to_string(),
+            critique_response.to_string(),
+        ]));
+        let standards = StandardsProfile::from_toml_str("[naming]\nfunctions = \"snake_case\"\n").unwrap();
+
+        let mut agent = FeatureImplementer::new(client).with_standards(Arc::new(standards));
+        assert!(!agent.build_prompt(&Task::new("Measure input")).contains("Language:"));
+        let python = Task::new("Add retries").with_context("Edit client/http.py");
+        assert!(agent.build_prompt(&python).contains("Language: Python."));
+        assert!(agent.build_prompt(&Task::new("Measure input")).contains("Names of functions are snake_case"));
+        agent.process_task(Task::new("Measure input")).await.unwrap();
+
+        let err = agent.approve_with_policy(&PolicyEnforcer::new(PolicyConfig::default())).unwrap_err();
+        assert!(err.to_string().contains("'parseInput' should be snake_case"));
         assert!(matches!(agent.state(), AgentState::AwaitingReview(_)));
     }
+
+    #[test]
+    fn test_documentation_writer_suggests_codeowners() {
+        use zed42_toolboxes::vcs::OwnerShare;
+
+        let owner = |email: &str, share: f64| OwnerShare {
+            author: email.to_string(),
+            email: email.to_string(),
+            commits: 1,
+            share,
+        };
+        let ownership = OwnershipMap {
+            modules: [
+                (".".to_string(), vec![owner("lead@example.com", 0.6)]),
+                ("src/net".to_string(), vec![owner("net@example.com", 0.9)]),
+            ]
+            .into_iter()
+            .collect(),
+        };
+
+        let artifact = DocumentationWriter::new().suggest_codeowners(&Task::new("Suggest owners"), &ownership, 0.5);
+        assert_eq!(artifact.artifact_type, ArtifactType::Documentation);
+        assert_eq!(artifact.file_path.as_deref(), Some(CODEOWNERS_PATH));
+        assert!(artifact.content.contains("* lead@example.com\n/src/net/ net@example.com\n"));
+    }
+
+    #[tokio::test]
+    async fn test_documentation_writer_refreshes_docs() {
+        use zed42_memory::knowledge_graph::{KnowledgeGraphMemory, KnowledgeNode};
+
+        let dir = tempfile::tempdir().unwrap();
+        let graph = KnowledgeGraphMemory::new(dir.path(), "docs", None).await.unwrap();
+        graph
+            .insert_node(KnowledgeNode {
+                id: "module:parser".to_string(),
+                node_type: "module".to_string(),
+                name: "parser".to_string(),
+                content: "Turns source text into syntax trees.".to_string(),
+                embedding: None,
+                metadata: "{}".to_string(),
+                created_at: 0,
+                updated_at: 0,
+            })
+            .await
+            .unwrap();
+
+        let writer = DocumentationWriter::new();
+        let artifacts = writer
+            .refresh_docs(&Task::new("Refresh docs"), &graph, dir.path(), DocsLayout::MdBook)
+            .await
+            .unwrap();
+        let page = artifacts
+            .iter()
+            .find(|a| a.file_path.as_deref() == Some("docs/src/api/parser.md"))
+            .unwrap();
+        assert_eq!(page.artifact_type, ArtifactType::Documentation);
+        assert!(page.content.contains("Turns source text into syntax trees."));
+        assert!(artifacts.iter().any(|a| a.file_path.as_deref() == Some("docs/src/SUMMARY.md")));
+    }
 }
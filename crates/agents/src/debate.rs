@@ -0,0 +1,393 @@
+//! Debate mode: adversarial review of Blue artifacts
+//!
+//! A single reviewer pass misses what nobody argued for. In a debate a Red
+//! agent attacks a Blue artifact, the Blue agent answers each point, and
+//! after a bounded number of rounds (or once Red runs out of objections) a
+//! Green agent judges which weaknesses stand. Every turn and the verdict are
+//! stored as a chain of decisions, and the verdict adjusts the artifact's
+//! confidence.
+
+use anyhow::{bail, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
+use uuid::Uuid;
+use zed42_blackboard::{BlackboardDb, DecisionNode};
+use zed42_core::types::{Confidence, Team};
+use zed42_core::{AgentId, Artifact, Task};
+use zed42_llm::{ConstrainedGen, LlmClient};
+
+use crate::AgentType;
+
+/// Red/Blue exchanges unless the caller sets a bound
+pub const DEFAULT_DEBATE_ROUNDS: u8 = 2;
+
+/// Confidence of an artifact that never had one, before the verdict
+const UNRATED_CONFIDENCE: Confidence = 0.5;
+
+/// Confidence lost per weakness the judge upholds on a revise verdict
+const UPHELD_PENALTY: Confidence = 0.1;
+
+/// Confidence left to a rejected artifact
+const REJECTED_CONFIDENCE: Confidence = 0.05;
+
+/// One participant: an agent of some type and the model it argues with
+#[derive(Clone)]
+pub struct Debater {
+    pub agent_id: AgentId,
+    pub agent_type: AgentType,
+    client: Arc<dyn LlmClient>,
+}
+
+impl Debater {
+    pub fn new(agent_type: AgentType, client: Arc<dyn LlmClient>) -> Self {
+        Self { agent_id: Uuid::new_v4(), agent_type, client }
+    }
+
+    pub fn with_id(mut self, agent_id: AgentId) -> Self {
+        self.agent_id = agent_id;
+        self
+    }
+}
+
+/// Which side spoke
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DebateSide {
+    Red,
+    Blue,
+}
+
+/// One argument in the transcript
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DebateTurn {
+    /// 1-based round
+    pub round: u8,
+    pub side: DebateSide,
+    pub agent_id: AgentId,
+    /// Weaknesses raised (Red) or answers to them (Blue)
+    pub points: Vec<String>,
+    /// Points the other side's previous turn won (Blue concessions)
+    #[serde(default)]
+    pub conceded: Vec<String>,
+}
+
+/// Green's ruling on the artifact
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DebateVerdict {
+    /// The defence held; the artifact can proceed
+    Approve,
+    /// Upheld weaknesses must be fixed first
+    Revise,
+    /// The approach is wrong; the artifact should be redone
+    Reject,
+}
+
+/// LLM response schema for Red's attack
+#[derive(Debug, Clone, JsonSchema, Serialize, Deserialize)]
+pub struct ChallengeResponse {
+    /// Weaknesses still standing; empty when Red has nothing left to argue
+    pub weaknesses: Vec<String>,
+}
+
+/// LLM response schema for Blue's defence
+#[derive(Debug, Clone, JsonSchema, Serialize, Deserialize)]
+pub struct RebuttalResponse {
+    /// An answer to each weakness, in order
+    pub responses: Vec<String>,
+    /// Weaknesses Blue accepts as valid
+    pub conceded: Vec<String>,
+}
+
+/// LLM response schema for Green's judgment
+#[derive(Debug, Clone, JsonSchema, Serialize, Deserialize)]
+pub struct JudgmentResponse {
+    pub verdict: DebateVerdict,
+    /// Weaknesses the defence did not answer
+    pub upheld: Vec<String>,
+    pub reasoning: String,
+    /// Judge's confidence (0.0-1.0) in the artifact after the debate
+    pub confidence: f32,
+}
+
+/// Transcript and ruling of one debate
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DebateOutcome {
+    pub debate_id: String,
+    pub artifact_id: String,
+    pub task_id: String,
+    pub turns: Vec<DebateTurn>,
+    pub verdict: DebateVerdict,
+    pub upheld: Vec<String>,
+    pub reasoning: String,
+    pub judge: AgentId,
+    pub judge_confidence: Confidence,
+}
+
+impl DebateOutcome {
+    /// Artifact confidence after the verdict
+    ///
+    /// Approval averages the prior confidence with the judge's; a revise
+    /// verdict costs [`UPHELD_PENALTY`] per upheld weakness on top of the
+    /// judge's doubt; rejection leaves almost nothing.
+    pub fn adjusted_confidence(&self, prior: Option<Confidence>) -> Confidence {
+        let prior = prior.unwrap_or(UNRATED_CONFIDENCE).clamp(0.0, 1.0);
+        let adjusted = match self.verdict {
+            DebateVerdict::Approve => (prior + self.judge_confidence) / 2.0,
+            DebateVerdict::Revise => {
+                prior.min(self.judge_confidence) - UPHELD_PENALTY * self.upheld.len() as Confidence
+            }
+            DebateVerdict::Reject => REJECTED_CONFIDENCE,
+        };
+        adjusted.clamp(REJECTED_CONFIDENCE, 1.0)
+    }
+
+    /// Factor the verdict into the artifact's confidence
+    pub fn apply(&self, artifact: &mut Artifact) {
+        artifact.confidence = Some(self.adjusted_confidence(artifact.confidence));
+    }
+
+    /// The debate as a chain of decisions: one per turn, then the verdict
+    pub fn decisions(&self) -> Vec<DecisionNode> {
+        let now = chrono::Utc::now().timestamp();
+        let mut decisions = Vec::with_capacity(self.turns.len() + 1);
+        let mut parent: Option<String> = None;
+        for turn in &self.turns {
+            let id = format!("{}-r{}-{}", self.debate_id, turn.round, side_name(turn.side));
+            decisions.push(DecisionNode {
+                id: id.clone(),
+                decision_type: "debate_argument".to_string(),
+                description: format!(
+                    "{} round {} on artifact {}",
+                    side_name(turn.side),
+                    turn.round,
+                    self.artifact_id
+                ),
+                made_by: turn.agent_id,
+                rationale: json!({ "points": turn.points, "conceded": turn.conceded }),
+                alternatives_considered: Vec::new(),
+                timestamp: now,
+                parent_decision: parent.replace(id),
+                confidence: None,
+            });
+        }
+        decisions.push(DecisionNode {
+            id: format!("{}-verdict", self.debate_id),
+            decision_type: "debate_verdict".to_string(),
+            description: format!("{:?} artifact {} for task {}", self.verdict, self.artifact_id, self.task_id),
+            made_by: self.judge,
+            rationale: json!({ "reasoning": self.reasoning, "upheld": self.upheld }),
+            alternatives_considered: Vec::new(),
+            timestamp: now,
+            parent_decision: parent,
+            confidence: Some(self.judge_confidence),
+        });
+        decisions
+    }
+
+    /// Store the transcript and verdict on the blackboard
+    pub async fn record(&self, blackboard: &BlackboardDb) -> Result<()> {
+        for decision in self.decisions() {
+            blackboard.record_decision(decision).await?;
+        }
+        Ok(())
+    }
+}
+
+fn side_name(side: DebateSide) -> &'static str {
+    match side {
+        DebateSide::Red => "red",
+        DebateSide::Blue => "blue",
+    }
+}
+
+/// A Red attacker, Blue defender and Green judge
+pub struct DebateProtocol {
+    red: Debater,
+    blue: Debater,
+    judge: Debater,
+    rounds: u8,
+}
+
+impl DebateProtocol {
+    /// Fails unless each debater belongs to the team of its role
+    pub fn new(red: Debater, blue: Debater, judge: Debater) -> Result<Self> {
+        for (debater, team) in [(&red, Team::Red), (&blue, Team::Blue), (&judge, Team::Green)] {
+            if debater.agent_type.team() != team {
+                bail!("{:?} cannot debate for the {:?} team", debater.agent_type, team);
+            }
+        }
+        Ok(Self { red, blue, judge, rounds: DEFAULT_DEBATE_ROUNDS })
+    }
+
+    /// Bound the number of Red/Blue exchanges (at least one)
+    pub fn with_rounds(mut self, rounds: u8) -> Self {
+        self.rounds = rounds.max(1);
+        self
+    }
+
+    /// Debate `artifact`, produced for `task`, and judge it
+    pub async fn run(&self, task: &Task, artifact: &Artifact) -> Result<DebateOutcome> {
+        let subject = format!(
+            "Task: {}\n\nArtifact {}:\n```\n{}\n```",
+            task.description, artifact.id, artifact.content
+        );
+        let mut turns: Vec<DebateTurn> = Vec::new();
+
+        for round in 1..=self.rounds {
+            let challenge: ChallengeResponse = ConstrainedGen::new(self.red.client.as_ref())
+                .system(
+                    "You are a Red Team reviewer. Argue the weaknesses of this artifact: bugs, \
+                     security holes, unhandled edge cases and performance traps. Drop points the \
+                     defence has answered convincingly; return none if nothing is left.",
+                )
+                .prompt(format!("{}\n\n{}", subject, transcript(&turns)))
+                .max_retries(1)
+                .generate()
+                .await?;
+            if challenge.weaknesses.is_empty() {
+                break;
+            }
+            turns.push(DebateTurn {
+                round,
+                side: DebateSide::Red,
+                agent_id: self.red.agent_id,
+                points: challenge.weaknesses,
+                conceded: Vec::new(),
+            });
+
+            let rebuttal: RebuttalResponse = ConstrainedGen::new(self.blue.client.as_ref())
+                .system(
+                    "You are the Blue Team author of this artifact. Answer each weakness raised: \
+                     show why it does not apply or how the artifact handles it. Concede the \
+                     points that are valid.",
+                )
+                .prompt(format!("{}\n\n{}", subject, transcript(&turns)))
+                .max_retries(1)
+                .generate()
+                .await?;
+            turns.push(DebateTurn {
+                round,
+                side: DebateSide::Blue,
+                agent_id: self.blue.agent_id,
+                points: rebuttal.responses,
+                conceded: rebuttal.conceded,
+            });
+        }
+
+        let judgment: JudgmentResponse = ConstrainedGen::new(self.judge.client.as_ref())
+            .system(
+                "You are a Green Team judge. Decide whether the artifact should be approved, \
+                 revised or rejected, based on which weaknesses the defence failed to answer.",
+            )
+            .prompt(format!("{}\n\n{}", subject, transcript(&turns)))
+            .max_retries(1)
+            .generate()
+            .await?;
+
+        Ok(DebateOutcome {
+            debate_id: format!("debate-{}", Uuid::new_v4()),
+            artifact_id: artifact.id.clone(),
+            task_id: task.id.clone(),
+            turns,
+            verdict: judgment.verdict,
+            upheld: judgment.upheld,
+            reasoning: judgment.reasoning,
+            judge: self.judge.agent_id,
+            judge_confidence: judgment.confidence.clamp(0.0, 1.0),
+        })
+    }
+}
+
+/// The debate so far, as shown to the next speaker
+fn transcript(turns: &[DebateTurn]) -> String {
+    if turns.is_empty() {
+        return "No arguments yet.".to_string();
+    }
+    let mut text = String::from("Debate so far:\n");
+    for turn in turns {
+        let speaker = match turn.side {
+            DebateSide::Red => "Red",
+            DebateSide::Blue => "Blue",
+        };
+        text.push_str(&format!("\nRound {} - {}:\n", turn.round, speaker));
+        for point in &turn.points {
+            text.push_str(&format!("- {}\n", point));
+        }
+        for point in &turn.conceded {
+            text.push_str(&format!("- Conceded: {}\n", point));
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zed42_llm::MockLlmClient;
+
+    fn mock(responses: &[&str]) -> Arc<dyn LlmClient> {
+        Arc::new(MockLlmClient::with_responses(responses.iter().map(|r| r.to_string()).collect()))
+    }
+
+    #[tokio::test]
+    async fn test_debate_rounds_verdict_and_decisions() {
+        let red = Debater::new(
+            AgentType::PenetrationTester,
+            mock(&[r#"{"weaknesses": ["No bound on input length"]}"#, r#"{"weaknesses": []}"#]),
+        );
+        let blue = Debater::new(
+            AgentType::FeatureImplementer,
+            mock(&[r#"{"responses": ["The caller caps input at 4 KiB"], "conceded": []}"#]),
+        );
+        let judge = Debater::new(
+            AgentType::SecurityReviewer,
+            mock(&[r#"{"verdict": "approve", "upheld": [], "reasoning": "The cap is enforced upstream", "confidence": 0.9}"#]),
+        );
+        let protocol = DebateProtocol::new(red, blue, judge).unwrap().with_rounds(3);
+
+        let task = Task::new("Parse user names");
+        let mut artifact = Artifact::code(task.id.clone(), "fn parse(s: &str) {}".to_string(), None).with_confidence(0.7);
+        let outcome = protocol.run(&task, &artifact).await.unwrap();
+
+        // Red ran out of objections in round 2
+        assert_eq!(outcome.turns.len(), 2);
+        assert_eq!(outcome.verdict, DebateVerdict::Approve);
+        outcome.apply(&mut artifact);
+        assert!((artifact.confidence.unwrap() - 0.8).abs() < 1e-6);
+
+        let decisions = outcome.decisions();
+        assert_eq!(decisions.len(), 3);
+        assert_eq!(decisions[1].parent_decision.as_deref(), Some(decisions[0].id.as_str()));
+        assert_eq!(decisions[2].decision_type, "debate_verdict");
+        assert_eq!(decisions[2].parent_decision.as_deref(), Some(decisions[1].id.as_str()));
+
+        let dir = tempfile::tempdir().unwrap();
+        let blackboard = BlackboardDb::new(dir.path(), "debate", "ws://localhost:8000").await.unwrap();
+        outcome.record(&blackboard).await.unwrap();
+        assert_eq!(blackboard.get_decisions(Some(outcome.judge)).await.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_roles_must_match_teams() {
+        let client = mock(&[]);
+        let blue = || Debater::new(AgentType::Refactorer, client.clone());
+        let judge = Debater::new(AgentType::Architect, client.clone());
+        assert!(DebateProtocol::new(blue(), blue(), judge).is_err());
+
+        let outcome = DebateOutcome {
+            debate_id: "debate-x".to_string(),
+            artifact_id: "a".to_string(),
+            task_id: "t".to_string(),
+            turns: Vec::new(),
+            verdict: DebateVerdict::Revise,
+            upheld: vec!["race on shutdown".to_string(), "no timeout".to_string()],
+            reasoning: String::new(),
+            judge: Uuid::new_v4(),
+            judge_confidence: 0.6,
+        };
+        assert!((outcome.adjusted_confidence(Some(0.9)) - 0.4).abs() < 1e-6);
+    }
+}
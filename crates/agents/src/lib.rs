@@ -8,14 +8,17 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use zed42_core::types::{AgentId, Team, AgentStatus};
+use zed42_core::titan::placement::ResourceRequirements;
 
 
 pub mod red;
 pub mod blue;
 pub mod green;
 pub mod base;
+pub mod debate;
 pub mod orchestrator;
 pub mod state;
+pub mod strategy;
 
 /// Agent type enumeration
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -63,6 +66,24 @@ impl AgentType {
         }
     }
 
+    /// CPU and memory this agent type needs to be placed on a machine
+    ///
+    /// Analysis-heavy agents (fuzzing, profiling, whole-repo review) need
+    /// more; none needs a GPU unless it runs a local model, which the
+    /// caller adds with `ResourceRequirements::with_gpu`.
+    pub fn resource_requirements(&self) -> ResourceRequirements {
+        match self {
+            AgentType::PenetrationTester | AgentType::PerformanceAnalyst => ResourceRequirements::new(2.0, 2048),
+            AgentType::ChaosEngineer | AgentType::EdgeCaseMiner => ResourceRequirements::new(1.0, 1024),
+            AgentType::Architect | AgentType::SecurityReviewer => ResourceRequirements::new(1.0, 1024),
+            AgentType::MigrationSpecialist | AgentType::Refactorer => ResourceRequirements::new(1.0, 768),
+            AgentType::FeatureImplementer | AgentType::TestEngineer => ResourceRequirements::new(1.0, 512),
+            AgentType::TechnicalDebtor
+            | AgentType::DocumentationWriter
+            | AgentType::StandardsEnforcer => ResourceRequirements::new(0.5, 256),
+        }
+    }
+
     /// Returns the default toolbox assignment for this agent type
     pub fn default_toolbox(&self) -> Vec<String> {
         match self {
@@ -87,6 +108,7 @@ impl AgentType {
                 "Fuzzing".to_string(),
                 "Testing".to_string(),
                 "StaticAnalysis".to_string(),
+                "WasmSandbox".to_string(),
             ],
             AgentType::TechnicalDebtor => vec![
                 "MetricsAnalysis".to_string(),
@@ -112,10 +134,12 @@ impl AgentType {
                 "Fuzzing".to_string(),
                 "CodeGeneration".to_string(),
                 "PerformanceProfiling".to_string(),
+                "WasmSandbox".to_string(),
             ],
             AgentType::DocumentationWriter => vec![
                 "FileManipulation".to_string(),
                 "GitOperations".to_string(),
+                "GitHistory".to_string(),
                 "DiagramGeneration".to_string(),
             ],
             AgentType::MigrationSpecialist => vec![
@@ -135,12 +159,14 @@ impl AgentType {
             AgentType::StandardsEnforcer => vec![
                 "StaticAnalysis".to_string(),
                 "MetricsAnalysis".to_string(),
+                "PolicyEnforcement".to_string(),
             ],
             AgentType::SecurityReviewer => vec![
                 "StaticAnalysis".to_string(),
                 "DependencyScanning".to_string(),
                 "GraphQuery".to_string(),
                 "GitHistory".to_string(),
+                "InjectionScreening".to_string(),
             ],
         }
     }
@@ -185,6 +211,14 @@ mod tests {
         assert_eq!(AgentType::Architect.team(), Team::Green);
     }
 
+    #[test]
+    fn test_heavy_agents_need_more_resources() {
+        let fuzzer = AgentType::PenetrationTester.resource_requirements();
+        let writer = AgentType::DocumentationWriter.resource_requirements();
+        assert!(fuzzer.memory_bytes > writer.memory_bytes);
+        assert!(!fuzzer.gpu && !writer.gpu);
+    }
+
     #[test]
     fn test_agent_creation() {
         let agent = Agent::new(AgentType::FeatureImplementer, None);
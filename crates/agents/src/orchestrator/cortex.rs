@@ -42,6 +42,11 @@ impl Cortex {
 
     /// Primary execution loop
     pub async fn run(&mut self) -> Result<()> {
+        self.run_until(zed42_core::CancellationToken::new()).await
+    }
+
+    /// Run the loop until `shutdown` is cancelled, then drain the mailbox
+    pub async fn run_until(&mut self, shutdown: zed42_core::CancellationToken) -> Result<()> {
         info!(agent_id = %self.agent_id, team = ?self.team, "Starting SAGA Cortex Loop");
         
         let blackboard_lock = self.substrate.get_blackboard_handle()?;
@@ -65,6 +70,18 @@ impl Cortex {
             }
 
             tokio::select! {
+                // SHUTDOWN: Stop observing, finish what is already queued
+                _ = shutdown.cancelled() => {
+                    let pending = self.mailbox.len();
+                    info!(agent_id = %self.agent_id, pending, "Shutdown requested, draining mailbox");
+                    while let Some(msg) = self.mailbox.pop() {
+                        if let Err(e) = self.cycle_step(msg).await {
+                            error!("Error while draining mailbox: {}", e);
+                        }
+                    }
+                    return Ok(());
+                }
+
                 // OBSERVE: Incoming real-time VOX updates via MOM
                 Ok(msg) = mom_rx.recv() => {
                     debug!(sender = %msg.sender, "Observed VOX message via Titan-linked MOM substrate");
@@ -98,6 +115,32 @@ impl Cortex {
     #[instrument(skip(self, msg), fields(correlation_id = %msg.correlation_id))]
     async fn cycle_step(&mut self, msg: VoxMessage) -> Result<()> {
         let blackboard_lock = self.substrate.get_blackboard_handle()?;
+
+        // 0. DEDUP: Re-posted messages (e.g. after a reconnect) are handled once
+        let fresh = {
+            let blackboard = blackboard_lock.read();
+            blackboard.claim_vox(self.agent_id, &msg).await
+                .context("Cortex Dedup: Failed to claim VOX message")?
+        };
+        if !fresh {
+            debug!(key = ?msg.dedup_key(), "Skipping already processed VOX message");
+            return Ok(());
+        }
+
+        // A failed cycle hands the claim back, so the next delivery is handled instead of skipped
+        let handled = self.handle_vox(&msg).await;
+        if handled.is_err() {
+            let blackboard = blackboard_lock.read();
+            if let Err(e) = blackboard.release_vox(self.agent_id, &msg).await {
+                warn!(key = ?msg.dedup_key(), "Cortex Dedup: failed to release VOX message claim: {}", e);
+            }
+        }
+        handled
+    }
+
+    /// Orient, decide, act and reflect on a claimed VOX message
+    async fn handle_vox(&self, msg: &VoxMessage) -> Result<()> {
+        let blackboard_lock = self.substrate.get_blackboard_handle()?;
         let memory_lock = self.substrate.get_memory_handle()?;
         let llm_lock = self.substrate.get_llm_handle()?;
 
@@ -112,11 +155,11 @@ impl Cortex {
         // RAG: Background memory retrieval
         let memory_context = {
             let memory = memory_lock.read();
-            self.retrieve_memories(&msg, Arc::new(memory.clone())).await.unwrap_or_default()
+            self.retrieve_memories(msg, Arc::new(memory.clone())).await.unwrap_or_default()
         };
 
         // 2. DECIDE
-        let action = self.decide(&msg, &consensus, &memory_context).await?;
+        let action = self.decide(msg, &consensus, &memory_context).await?;
 
         // 3. ACT
         if let Some(act_msg) = action {
@@ -126,7 +169,7 @@ impl Cortex {
         }
 
         // 4. REFLECT
-        if self.is_milestone(&msg) {
+        if self.is_milestone(msg) {
             let memory = memory_lock.read().clone();
             let llm = llm_lock.read().clone();
             let agent_id = self.agent_id;
@@ -154,9 +197,13 @@ impl Cortex {
         match &msg.payload {
             zed42_core::vox::VoxPayload::TaskAssignment { task_id, .. } => {
                 info!(%task_id, "Decided: Acknowledging VOX task assignment");
+                // Senders that aren't agents (system, MOM) get the ack on the thread instead
+                let target = uuid::Uuid::parse_str(&msg.sender.id.to_string())
+                    .map(zed42_core::messages::MessageTarget::Agent)
+                    .unwrap_or(zed42_core::messages::MessageTarget::All);
                 let mut ack = zed42_core::Message::new(
                     self.agent_id,
-                    zed42_core::messages::MessageTarget::Agent(uuid::Uuid::parse_str(&msg.sender.id.to_string()).unwrap_or_default()),
+                    target,
                     zed42_core::MessageType::TaskComplete { 
                         task_id: task_id.clone(), 
                         result: "Acknowledged via VOX".to_string() 
@@ -258,3 +305,49 @@ impl Cortex {
         self.agent_id
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parking_lot::RwLock;
+
+    fn assignment(task_id: &str) -> VoxMessage {
+        VoxMessage {
+            sender: surrealdb::sql::Thing::from(("agent", "cortex")),
+            target_team: "blue".to_string(),
+            priority: 1,
+            correlation_id: Uuid::new_v4(),
+            payload: zed42_core::vox::VoxPayload::TaskAssignment {
+                task_id: task_id.to_string(),
+                description: "add pagination".to_string(),
+            },
+            created_at: chrono::Utc::now(),
+            ttl_secs: None,
+            idempotency_key: Some(format!("assign-{}", task_id)),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failed_cycle_releases_dedup_claim() {
+        let dir = tempfile::tempdir().unwrap();
+        let blackboard = BlackboardDb::new(dir.path(), "cortex", "ws://localhost:8000").await.unwrap();
+        let titan = TitanSubstrate::new();
+        titan.register("Blackboard", Arc::new(RwLock::new(blackboard)));
+        titan.register("Memory", Arc::new(RwLock::new(MemorySubstrate::default())));
+        let llm: Arc<dyn LlmClient> = Arc::new(zed42_llm::MockLlmClient::new(String::new()));
+        titan.register("LlmClient", Arc::new(RwLock::new(llm)));
+        let agent_id = Uuid::new_v4();
+        let mut cortex = Cortex::new(agent_id, Team::Blue, Arc::new(titan));
+        let blackboard = cortex.substrate.get_blackboard_handle().unwrap();
+
+        // The ack for a task id with a space fails schema validation
+        let failing = assignment("bad id");
+        assert!(cortex.cycle_step(failing.clone()).await.is_err());
+        assert!(blackboard.read().claim_vox(agent_id, &failing).await.unwrap());
+
+        // A handled message stays claimed, so its redelivery is skipped
+        let handled = assignment("t1");
+        cortex.cycle_step(handled.clone()).await.unwrap();
+        assert!(!blackboard.read().claim_vox(agent_id, &handled).await.unwrap());
+    }
+}
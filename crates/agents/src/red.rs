@@ -1,3 +1,143 @@
 //! Red Team agents - Offensive Security & Optimization
 
 // Placeholder for Red Team agent implementations
+
+#[cfg(feature = "chaos")]
+pub use chaos::{ChaosEngineer, ChaosExperiment, ChaosReport};
+
+/// ChaosEngineer control over the core fault points (`chaos` feature)
+#[cfg(feature = "chaos")]
+mod chaos {
+    use chrono::{DateTime, Utc};
+    use serde::{Deserialize, Serialize};
+    use std::collections::HashMap;
+    use std::future::Future;
+    use zed42_core::chaos::{chaos, FaultPoint, FaultSpec};
+    use zed42_core::types::AgentId;
+
+    /// Faults to arm while a workload runs
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct ChaosExperiment {
+        pub name: String,
+        pub faults: Vec<(FaultPoint, FaultSpec)>,
+    }
+
+    impl ChaosExperiment {
+        pub fn new(name: impl Into<String>) -> Self {
+            Self {
+                name: name.into(),
+                faults: Vec::new(),
+            }
+        }
+
+        pub fn inject(mut self, point: FaultPoint, spec: FaultSpec) -> Self {
+            self.faults.push((point, spec));
+            self
+        }
+    }
+
+    /// Outcome of one experiment
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct ChaosReport {
+        pub experiment: String,
+        pub run_by: AgentId,
+        pub seed: u64,
+        pub started_at: DateTime<Utc>,
+        pub finished_at: DateTime<Utc>,
+        /// How often each fault fired
+        pub triggered: HashMap<FaultPoint, u64>,
+        /// Error the workload surfaced, if the failure wasn't contained
+        pub error: Option<String>,
+    }
+
+    impl ChaosReport {
+        /// The workload succeeded despite the injected faults
+        pub fn contained(&self) -> bool {
+            self.error.is_none()
+        }
+
+        /// Faults that were armed but never exercised by the workload
+        pub fn unexercised(&self, experiment: &ChaosExperiment) -> Vec<FaultPoint> {
+            experiment
+                .faults
+                .iter()
+                .map(|(point, _)| *point)
+                .filter(|point| self.triggered.get(point).copied().unwrap_or(0) == 0)
+                .collect()
+        }
+    }
+
+    /// Runs chaos experiments against live subsystems
+    pub struct ChaosEngineer {
+        id: AgentId,
+        seed: u64,
+    }
+
+    impl ChaosEngineer {
+        pub fn new(id: AgentId) -> Self {
+            Self { id, seed: 42 }
+        }
+
+        /// Seed for probabilistic faults, so a failing run can be replayed
+        pub fn with_seed(mut self, seed: u64) -> Self {
+            self.seed = seed;
+            self
+        }
+
+        /// Arm the experiment's faults, run `workload`, then disarm everything
+        ///
+        /// Experiments are serialized process-wide; faults never outlive the run.
+        pub async fn run<F, Fut, T>(&self, experiment: &ChaosExperiment, workload: F) -> ChaosReport
+        where
+            F: FnOnce() -> Fut,
+            Fut: Future<Output = anyhow::Result<T>>,
+        {
+            let session = chaos().session(self.seed).await;
+            let started_at = Utc::now();
+            tracing::warn!(agent = %self.id, experiment = %experiment.name, "Starting chaos experiment");
+            for (point, spec) in &experiment.faults {
+                chaos().arm(*point, spec.clone());
+            }
+
+            let error = workload().await.err().map(|e| format!("{:#}", e));
+            let triggered = chaos().triggered_counts();
+            drop(session);
+
+            ChaosReport {
+                experiment: experiment.name.clone(),
+                run_by: self.id,
+                seed: self.seed,
+                started_at,
+                finished_at: Utc::now(),
+                triggered,
+                error,
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "chaos"))]
+mod tests {
+    use super::*;
+    use zed42_core::chaos::{chaos, fail_point, FaultPoint, FaultSpec};
+
+    #[tokio::test]
+    async fn test_experiment_reports_and_disarms() {
+        let experiment = ChaosExperiment::new("flaky-db")
+            .inject(FaultPoint::Database, FaultSpec::always().times(1))
+            .inject(FaultPoint::ToolIo, FaultSpec::always());
+        let engineer = ChaosEngineer::new(uuid::Uuid::new_v4());
+
+        // A workload that retries once contains the single database fault
+        let report = engineer
+            .run(&experiment, || async {
+                fail_point(FaultPoint::Database).or_else(|_| fail_point(FaultPoint::Database))
+            })
+            .await;
+
+        assert!(report.contained());
+        assert_eq!(report.triggered.get(&FaultPoint::Database), Some(&1));
+        assert_eq!(report.unexercised(&experiment), vec![FaultPoint::ToolIo]);
+        assert!(!chaos().is_armed(FaultPoint::ToolIo));
+    }
+}
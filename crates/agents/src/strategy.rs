@@ -0,0 +1,172 @@
+//! Reasoning strategies per agent type
+//!
+//! A generate→critique loop suits implementation work but wastes calls on
+//! boilerplate and is too weak for risky changes. The strategy an agent
+//! reasons with is chosen per agent type in `.zed42/strategies.toml`:
+//!
+//! ```toml
+//! default = { kind = "reflexion", iterations = 3 }
+//!
+//! [agents.documentation_writer]
+//! kind = "single_shot"
+//!
+//! [agents.feature_implementer]
+//! kind = "debate"
+//! challenger = "openai/gpt-4o"
+//! rounds = 2
+//! ```
+//!
+//! Artifacts record the [`ReasoningStrategy::label`] they were produced
+//! with, so strategies can be compared on review outcomes later.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::AgentType;
+
+/// Location of the strategy config relative to the project root
+pub const STRATEGY_CONFIG_PATH: &str = ".zed42/strategies.toml";
+
+/// How an agent turns a task into an artifact
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ReasoningStrategy {
+    /// One generation, no critique
+    SingleShot,
+    /// Generate and self-critique, revising up to `iterations` times
+    Reflexion { iterations: u8 },
+    /// A second model argues against each proposal for up to `rounds` rounds
+    Debate { challenger: String, rounds: u8 },
+    /// Plan the steps first, then implement the plan with one critique
+    PlanThenExecute,
+}
+
+impl Default for ReasoningStrategy {
+    fn default() -> Self {
+        ReasoningStrategy::Reflexion { iterations: 3 }
+    }
+}
+
+impl ReasoningStrategy {
+    /// Short name recorded on artifacts (`single_shot`, `reflexion-3`, ...)
+    pub fn label(&self) -> String {
+        match self {
+            ReasoningStrategy::SingleShot => "single_shot".to_string(),
+            ReasoningStrategy::Reflexion { iterations } => format!("reflexion-{}", iterations),
+            ReasoningStrategy::Debate { challenger, rounds } => format!("debate-{}:{}", rounds, challenger),
+            ReasoningStrategy::PlanThenExecute => "plan_then_execute".to_string(),
+        }
+    }
+
+    /// Generation rounds the strategy allows
+    pub fn max_rounds(&self) -> u8 {
+        match self {
+            ReasoningStrategy::SingleShot | ReasoningStrategy::PlanThenExecute => 1,
+            ReasoningStrategy::Reflexion { iterations } => *iterations,
+            ReasoningStrategy::Debate { rounds, .. } => *rounds,
+        }
+    }
+
+    /// Whether each proposal is critiqued
+    pub fn critiques(&self) -> bool {
+        !matches!(self, ReasoningStrategy::SingleShot)
+    }
+
+    fn validate(&self) -> Result<()> {
+        match self {
+            ReasoningStrategy::Reflexion { iterations: 0 } => bail!("reflexion needs at least one iteration"),
+            ReasoningStrategy::Debate { rounds: 0, .. } => bail!("debate needs at least one round"),
+            ReasoningStrategy::Debate { challenger, .. } if challenger.trim().is_empty() => {
+                bail!("debate needs a challenger model")
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Strategy per agent type, with a default for the rest
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StrategyConfig {
+    #[serde(default)]
+    pub default: ReasoningStrategy,
+    /// Keyed by snake_case agent type (`feature_implementer`)
+    #[serde(default)]
+    pub agents: BTreeMap<String, ReasoningStrategy>,
+}
+
+impl StrategyConfig {
+    pub fn from_toml_str(content: &str) -> Result<Self> {
+        let config: Self = toml::from_str(content).map_err(|e| anyhow::anyhow!("Invalid strategy config: {}", e))?;
+        config.default.validate().context("Invalid default strategy")?;
+        for (agent, strategy) in &config.agents {
+            if agent_type_from_key(agent).is_none() {
+                bail!("Unknown agent type '{}' in strategy config", agent);
+            }
+            strategy.validate().with_context(|| format!("Invalid strategy for {}", agent))?;
+        }
+        Ok(config)
+    }
+
+    /// Config from `.zed42/strategies.toml` under `project_root`, or the default
+    pub fn load(project_root: &Path) -> Result<Self> {
+        let path = project_root.join(STRATEGY_CONFIG_PATH);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::from_toml_str(&content)
+    }
+
+    /// Choose `strategy` for `agent_type`
+    pub fn set(&mut self, agent_type: &AgentType, strategy: ReasoningStrategy) {
+        self.agents.insert(config_key(agent_type), strategy);
+    }
+
+    /// Strategy for `agent_type`
+    pub fn for_agent(&self, agent_type: &AgentType) -> &ReasoningStrategy {
+        self.agents.get(&config_key(agent_type)).unwrap_or(&self.default)
+    }
+}
+
+/// The agent type's serde tag, e.g. `feature_implementer`
+fn config_key(agent_type: &AgentType) -> String {
+    serde_json::to_value(agent_type)
+        .ok()
+        .and_then(|value| value.get("type").and_then(|t| t.as_str()).map(str::to_string))
+        .unwrap_or_else(|| format!("{:?}", agent_type))
+}
+
+fn agent_type_from_key(key: &str) -> Option<AgentType> {
+    serde_json::from_value(serde_json::json!({ "type": key })).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_selects_per_agent_type() {
+        let config = StrategyConfig::from_toml_str(
+            r#"
+            default = { kind = "single_shot" }
+
+            [agents.feature_implementer]
+            kind = "debate"
+            challenger = "openai/gpt-4o"
+            rounds = 2
+            "#,
+        )
+        .unwrap();
+        let debate = config.for_agent(&AgentType::FeatureImplementer);
+        assert_eq!(debate.label(), "debate-2:openai/gpt-4o");
+        assert_eq!(debate.max_rounds(), 2);
+        assert_eq!(config.for_agent(&AgentType::Architect), &ReasoningStrategy::SingleShot);
+
+        assert_eq!(StrategyConfig::default().for_agent(&AgentType::Refactorer).label(), "reflexion-3");
+        assert!(StrategyConfig::from_toml_str("[agents.wizard]\nkind = \"single_shot\"").is_err());
+        assert!(StrategyConfig::from_toml_str("default = { kind = \"reflexion\", iterations = 0 }").is_err());
+    }
+}
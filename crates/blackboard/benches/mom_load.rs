@@ -0,0 +1,69 @@
+//! MOM substrate capacity sweep
+//!
+//! Run with `cargo bench -p zed42-blackboard --bench mom_load`. Each rate
+//! runs against a fresh in-memory SurrealDB; reports are printed and
+//! appended as JSON lines to `target/mom-load/runs.jsonl` so runs can be
+//! compared over time.
+//!
+//! Environment:
+//! - `ZED42_LOAD_RATES`: comma-separated messages/second (default 100,500,1000,2000)
+//! - `ZED42_LOAD_SECS`: seconds per rate (default 5)
+//! - `ZED42_LOAD_SUBSCRIBERS`: subscribers per team (default 1)
+//! - `ZED42_LOAD_SUBSCRIBER_DELAY_US`: simulated work per received message
+
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+use zed42_blackboard::load::{LoadConfig, LoadGenerator};
+
+fn env_or<T: std::str::FromStr>(name: &str, default: T) -> T {
+    std::env::var(name).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let rates: Vec<u32> = std::env::var("ZED42_LOAD_RATES")
+        .unwrap_or_else(|_| "100,500,1000,2000".to_string())
+        .split(',')
+        .filter_map(|r| r.trim().parse().ok())
+        .collect();
+    let base = LoadConfig {
+        duration: Duration::from_secs(env_or("ZED42_LOAD_SECS", 5)),
+        subscribers_per_team: env_or("ZED42_LOAD_SUBSCRIBERS", 1),
+        subscriber_delay: std::env::var("ZED42_LOAD_SUBSCRIBER_DELAY_US")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .map(Duration::from_micros),
+        ..Default::default()
+    };
+
+    let out_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../target/mom-load");
+    std::fs::create_dir_all(&out_dir)?;
+    let mut log = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(out_dir.join("runs.jsonl"))?;
+
+    println!(
+        "{:>8} {:>10} {:>12} {:>12} {:>12} {:>10} {:>8} {:>8}",
+        "rate/s", "writes/s", "write p99", "deliv p50", "deliv p99", "delivered", "lagged", "lost"
+    );
+    for rate in rates {
+        let config = LoadConfig { rate_per_sec: rate, ..base.clone() };
+        let generator = LoadGenerator::in_memory(config.channel_capacity).await?;
+        let report = generator.run(&config).await?;
+        println!(
+            "{:>8} {:>10.0} {:>10}us {:>10}us {:>10}us {:>9.1}% {:>8} {:>8}",
+            rate,
+            report.write_throughput,
+            report.write_latency.p99_us,
+            report.delivery_latency.p50_us,
+            report.delivery_latency.p99_us,
+            report.delivery_ratio() * 100.0,
+            report.lagged,
+            report.lost
+        );
+        writeln!(log, "{}", serde_json::to_string(&report)?)?;
+    }
+    Ok(())
+}
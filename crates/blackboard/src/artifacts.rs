@@ -0,0 +1,61 @@
+//! ArtifactStore - blobs too large for the VOX bus
+//!
+//! Payloads past the codec's inline cap are written here and replaced on
+//! the bus by a `VoxPayload::ArtifactRef`. Subscribers load them back with
+//! [`ArtifactStore::resolve`].
+
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+use zed42_core::types::ArtifactId;
+use zed42_core::vox::{VoxMessage, VoxPayload};
+
+/// File-backed store, one file per artifact
+#[derive(Debug, Clone)]
+pub struct ArtifactStore {
+    dir: PathBuf,
+}
+
+impl ArtifactStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create artifact directory {}", dir.display()))?;
+        Ok(Self { dir })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Store a blob and return its id
+    pub fn put(&self, bytes: &[u8]) -> Result<ArtifactId> {
+        let id = Uuid::new_v4().to_string();
+        let path = self.dir.join(&id);
+        // Write then rename so readers never see a partial artifact
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, bytes).context("Failed to write artifact")?;
+        std::fs::rename(&tmp, &path).context("Failed to commit artifact")?;
+        Ok(id)
+    }
+
+    pub fn get(&self, id: &str) -> Result<Vec<u8>> {
+        anyhow::ensure!(Uuid::parse_str(id).is_ok(), "Invalid artifact id: {}", id);
+        std::fs::read(self.dir.join(id)).with_context(|| format!("Artifact {} not found", id))
+    }
+
+    pub fn delete(&self, id: &str) -> Result<()> {
+        anyhow::ensure!(Uuid::parse_str(id).is_ok(), "Invalid artifact id: {}", id);
+        std::fs::remove_file(self.dir.join(id)).with_context(|| format!("Failed to delete artifact {}", id))
+    }
+
+    /// Replace an `ArtifactRef` payload with the payload it points to
+    pub fn resolve(&self, mut msg: VoxMessage) -> Result<VoxMessage> {
+        if let VoxPayload::ArtifactRef { artifact_id, .. } = &msg.payload {
+            let bytes = self.get(artifact_id)?;
+            msg.payload = serde_json::from_slice(&bytes)
+                .with_context(|| format!("Artifact {} is not a VOX payload", artifact_id))?;
+        }
+        Ok(msg)
+    }
+}
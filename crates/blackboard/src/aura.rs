@@ -21,8 +21,13 @@ pub struct AuraSentinel {
     check_interval: Duration,
     laggard_threshold: Duration,
     ghost_threshold: Duration,
+    /// Time without task progress before a pulsing agent counts as stalled
+    stall_threshold: Duration,
 }
 
+/// AURA vitals source holding every agent's task progress
+pub const PROGRESS_VITALS_SOURCE: &str = "task_progress";
+
 impl AuraSentinel {
     /// Create a new AuraSentinel
     pub fn new(blackboard: Arc<BlackboardDb>, ledger: Arc<IntelligenceLedger>) -> Self {
@@ -32,18 +37,37 @@ impl AuraSentinel {
             check_interval: Duration::from_secs(30),
             laggard_threshold: Duration::from_secs(60),
             ghost_threshold: Duration::from_secs(300), // 5 minutes
+            stall_threshold: Duration::from_secs(600),
         }
     }
 
+    /// Time without task progress before an agent is reported as stalled
+    pub fn with_stall_threshold(mut self, threshold: Duration) -> Self {
+        self.stall_threshold = threshold;
+        self
+    }
+
     /// Start the AURA monitor loop
     pub async fn run(&self) {
+        self.run_until(zed42_core::CancellationToken::new()).await
+    }
+
+    /// Run the monitor loop until `shutdown` is cancelled
+    pub async fn run_until(&self, shutdown: zed42_core::CancellationToken) {
         info!("AURA Substrate: starting vitality monitor loop...");
         loop {
             if let Err(e) = self.monitor_pulse_health().await {
                 error!("AURA Substrate: error during pulse health check: {}", e);
             }
-            sleep(self.check_interval).await;
+            if let Err(e) = self.monitor_progress().await {
+                error!("AURA Substrate: error during task progress check: {}", e);
+            }
+            tokio::select! {
+                _ = shutdown.cancelled() => break,
+                _ = sleep(self.check_interval) => {}
+            }
         }
+        info!("AURA Substrate: vitality monitor stopped");
     }
 
     /// Primary monitoring logic
@@ -71,6 +95,56 @@ impl AuraSentinel {
         Ok(())
     }
 
+    /// Publish task progress as vitals and alert on newly stalled tasks
+    ///
+    /// Unlike ghost detection this looks at progress, not pulses: a stalled
+    /// agent is still reporting, it just isn't getting anywhere.
+    #[instrument(skip(self))]
+    pub async fn monitor_progress(&self) -> Result<()> {
+        let now = Utc::now();
+        let threshold = chrono::Duration::from_std(self.stall_threshold).unwrap_or_else(|_| chrono::Duration::days(365));
+        let progress = self.blackboard.list_progress().await?;
+
+        let vitals: Vec<serde_json::Value> = progress
+            .iter()
+            .map(|p| {
+                serde_json::json!({
+                    "agent_id": p.agent_id,
+                    "task_id": p.task_id,
+                    "step": p.step,
+                    "percent": p.percent,
+                    "last_tool": p.last_tool,
+                    "idle_secs": p.idle_for(now).num_seconds(),
+                    "stalled": p.idle_for(now) > threshold,
+                })
+            })
+            .collect();
+        self.blackboard
+            .record_vitals(PROGRESS_VITALS_SOURCE, serde_json::Value::Array(vitals))
+            .await?;
+
+        for p in progress.iter().filter(|p| !p.stalled && p.idle_for(now) > threshold) {
+            warn!(agent_id = %p.agent_id, task_id = %p.task_id, step = %p.step, "Task progress STALLED");
+            self.blackboard.mark_stalled(p.agent_id).await?;
+            let alert = VoxMessage {
+                sender: surrealdb::sql::Thing::from(("system", "aura")),
+                target_team: "all".to_string(),
+                priority: 200,
+                correlation_id: uuid::Uuid::new_v4(),
+                payload: zed42_core::vox::VoxPayload::SystemAlert {
+                    action: "task_stalled".to_string(),
+                    agent_id: Some(p.agent_id),
+                    reason: format!("no progress on task {} at step '{}'", p.task_id, p.step),
+                },
+                created_at: Utc::now(),
+                ttl_secs: None,
+                idempotency_key: None,
+            };
+            self.blackboard.mom().broadcast_system_message(alert).await;
+        }
+        Ok(())
+    }
+
     #[instrument(skip(self))]
     async fn mark_laggard(&self, agent_id_str: String, last_pulse: DateTime<Utc>) -> Result<()> {
         warn!(agent_id = %agent_id_str, %last_pulse, "Agent flagged as LAGGARD");
@@ -116,6 +190,8 @@ impl AuraSentinel {
                 reason: "ghost_threshold_exceeded".to_string(),
             },
             created_at: Utc::now(),
+            ttl_secs: None,
+            idempotency_key: None,
         };
 
         // Notify via MOM Reactive Substrate (bypass DB for latency)
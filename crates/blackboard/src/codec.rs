@@ -0,0 +1,151 @@
+//! Transport encoding for large VOX payloads
+//!
+//! Payloads whose JSON exceeds `compress_above` bytes are zstd-compressed
+//! into a `VoxPayload::Compressed` before they are written to the
+//! blackboard, and the MOM watcher restores them with [`decode`] before
+//! broadcasting. If the compressed form is still over `max_inline`, the
+//! payload goes to the [`ArtifactStore`] and only a reference travels.
+
+use crate::artifacts::ArtifactStore;
+use anyhow::{Context, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use zed42_core::vox::{VoxMessage, VoxPayload};
+
+/// `encoding` marker of zstd-compressed payloads
+pub const ZSTD: &str = "zstd";
+
+/// Size thresholds for payload encoding
+#[derive(Debug, Clone)]
+pub struct PayloadCodec {
+    /// Compress payloads whose JSON is larger than this
+    pub compress_above: usize,
+    /// Largest compressed payload kept on the bus
+    pub max_inline: usize,
+    /// zstd level
+    pub level: i32,
+}
+
+impl Default for PayloadCodec {
+    fn default() -> Self {
+        Self {
+            compress_above: 8 * 1024,
+            max_inline: 256 * 1024,
+            level: 3,
+        }
+    }
+}
+
+impl PayloadCodec {
+    /// Encode a message for the blackboard, offloading to `store` past the cap
+    pub fn encode(&self, mut msg: VoxMessage, store: &ArtifactStore) -> Result<VoxMessage> {
+        if matches!(msg.payload, VoxPayload::Compressed { .. } | VoxPayload::ArtifactRef { .. }) {
+            return Ok(msg);
+        }
+        let json = serde_json::to_vec(&msg.payload).context("Failed to serialize VOX payload")?;
+        if json.len() <= self.compress_above {
+            return Ok(msg);
+        }
+
+        let compressed = zstd::encode_all(json.as_slice(), self.level).context("Failed to compress VOX payload")?;
+        msg.payload = if compressed.len() > self.max_inline {
+            let artifact_id = store.put(&json)?;
+            tracing::debug!(%artifact_id, bytes = json.len(), "VOX payload over inline cap, stored as artifact");
+            VoxPayload::ArtifactRef {
+                artifact_id,
+                original_bytes: json.len(),
+            }
+        } else {
+            VoxPayload::Compressed {
+                encoding: ZSTD.to_string(),
+                original_bytes: json.len(),
+                data: STANDARD.encode(compressed),
+            }
+        };
+        Ok(msg)
+    }
+}
+
+/// Restore a compressed payload; other payloads pass through unchanged
+pub fn decode(mut msg: VoxMessage) -> Result<VoxMessage> {
+    if let VoxPayload::Compressed { encoding, original_bytes, data } = &msg.payload {
+        anyhow::ensure!(encoding == ZSTD, "Unsupported VOX payload encoding: {}", encoding);
+        let compressed = STANDARD.decode(data).context("Compressed VOX payload is not valid base64")?;
+        let json = zstd::decode_all(compressed.as_slice()).context("Failed to decompress VOX payload")?;
+        anyhow::ensure!(
+            json.len() == *original_bytes,
+            "Decompressed VOX payload is {} bytes, expected {}",
+            json.len(),
+            original_bytes
+        );
+        msg.payload = serde_json::from_slice(&json).context("Decompressed VOX payload is invalid")?;
+    }
+    Ok(msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn observation(content: String) -> VoxMessage {
+        VoxMessage {
+            sender: surrealdb::sql::Thing::from(("agent", "test")),
+            target_team: "blue".to_string(),
+            priority: 1,
+            correlation_id: uuid::Uuid::new_v4(),
+            payload: VoxPayload::Observation { content },
+            created_at: chrono::Utc::now(),
+            ttl_secs: None,
+            idempotency_key: None,
+        }
+    }
+
+    fn content(msg: &VoxMessage) -> &str {
+        match &msg.payload {
+            VoxPayload::Observation { content } => content,
+            other => panic!("Expected observation, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_small_payloads_pass_through() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ArtifactStore::new(dir.path()).unwrap();
+        let msg = PayloadCodec::default().encode(observation("small".into()), &store).unwrap();
+        assert_eq!(content(&msg), "small");
+        assert_eq!(content(&decode(msg).unwrap()), "small");
+    }
+
+    #[test]
+    fn test_large_payload_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ArtifactStore::new(dir.path()).unwrap();
+        let diff = "+ let x = 1;\n".repeat(2_000);
+
+        let encoded = PayloadCodec::default().encode(observation(diff.clone()), &store).unwrap();
+        let VoxPayload::Compressed { encoding, data, .. } = &encoded.payload else {
+            panic!("Expected compressed payload, got {:?}", encoded.payload);
+        };
+        assert_eq!(encoding, ZSTD);
+        assert!(data.len() < diff.len() / 10);
+        assert_eq!(content(&decode(encoded).unwrap()), diff);
+    }
+
+    #[test]
+    fn test_oversized_payload_goes_to_artifact_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ArtifactStore::new(dir.path()).unwrap();
+        let codec = PayloadCodec {
+            compress_above: 16,
+            max_inline: 16,
+            ..Default::default()
+        };
+        let report = (0..500).map(|i| format!("finding {} ", i)).collect::<String>();
+
+        let encoded = codec.encode(observation(report.clone()), &store).unwrap();
+        assert!(matches!(encoded.payload, VoxPayload::ArtifactRef { .. }));
+        // Decoding leaves references alone; the store resolves them
+        let decoded = decode(encoded).unwrap();
+        assert_eq!(content(&store.resolve(decoded).unwrap()), report);
+    }
+}
@@ -5,14 +5,16 @@ use std::path::Path;
 use std::sync::Arc;
 use surrealdb::engine::local::{Db, Mem};
 use surrealdb::{Surreal, Response};
-use tokio::sync::broadcast;
 
 use crate::{
     DecisionNode, StateEntry, StateKey, 
     MessageFilter, MOMWatcher, VoxMessage
 };
 use crate::types::BlackboardStats;
+use crate::direct::DirectRegistry;
+use crate::{ArtifactStore, PayloadCodec, SchemaMode, SchemaRegistry};
 use zed42_core::{Message, AgentId, Team};
+use zed42_core::chaos::{fail_point, FaultPoint};
 
 
 /// Blackboard - Living communication substrate
@@ -22,6 +24,20 @@ pub struct BlackboardDb {
     db: Surreal<Db>,
     mom: Arc<MOMWatcher>,
     db_path: std::path::PathBuf,
+    /// Large VOX payloads offloaded from the bus
+    artifacts: ArtifactStore,
+    codec: PayloadCodec,
+    /// In-process ends of direct agent channels
+    direct: DirectRegistry,
+    /// Stops the MOM watcher task
+    watcher_token: zed42_core::CancellationToken,
+    /// How long processed idempotency keys are remembered
+    dedup_window: std::time::Duration,
+    /// Schemas messages are validated against before they are written
+    schemas: SchemaRegistry,
+    schema_mode: SchemaMode,
+    /// In-process feed of `set_state` writes, for watches without LIVE SELECT
+    state_changes: tokio::sync::broadcast::Sender<StateEntry>,
 }
 
 impl BlackboardDb {
@@ -36,6 +52,7 @@ impl BlackboardDb {
             .context("Failed to create blackboard data directory")?;
 
         let db_path = data_dir.join(format!("blackboard_{}.db", project_name));
+        let artifacts = ArtifactStore::new(data_dir.join("artifacts"))?;
 
         // Use Mem for local state, but the watcher connects via WS to the authoritative DB
         let db: Surreal<Db> = Surreal::new::<Mem>(())
@@ -55,11 +72,25 @@ impl BlackboardDb {
 
         // Start the MOM reactive loop in a background task
         let mom_clone = mom.clone();
+        let watcher_token = zed42_core::CancellationToken::new();
+        let token = watcher_token.clone();
         tokio::spawn(async move {
-            mom_clone.run_loop().await;
+            mom_clone.run_until(token).await;
         });
 
-        let blackboard = Self { db, mom, db_path };
+        let blackboard = Self {
+            db,
+            mom,
+            db_path,
+            artifacts,
+            codec: PayloadCodec::default(),
+            direct: DirectRegistry::new(),
+            watcher_token,
+            dedup_window: crate::DEFAULT_DEDUP_WINDOW,
+            schemas: SchemaRegistry::vox(),
+            schema_mode: SchemaMode::Strict,
+            state_changes: tokio::sync::broadcast::channel(256).0,
+        };
 
         blackboard.initialize_schema().await?;
 
@@ -78,6 +109,7 @@ impl BlackboardDb {
                  DEFINE FIELD IF NOT EXISTS correlation_id ON blackboard TYPE uuid;
                  DEFINE FIELD IF NOT EXISTS payload ON blackboard TYPE object;
                  DEFINE FIELD IF NOT EXISTS created_at ON blackboard TYPE datetime VALUE $before OR time::now();
+                 DEFINE FIELD IF NOT EXISTS ttl_secs ON blackboard TYPE option<int>;
                  DEFINE INDEX IF NOT EXISTS target_team_idx ON blackboard FIELDS target_team;",
             )
             .await
@@ -101,6 +133,57 @@ impl BlackboardDb {
             .await
             .context("Failed to create AURA pulse schema")?;
 
+        self.db
+            .query("DEFINE TABLE IF NOT EXISTS aura_vitals SCHEMALESS;")
+            .await
+            .context("Failed to create AURA vitals schema")?;
+
+        // Latest intra-task progress per agent
+        self.db
+            .query("DEFINE TABLE IF NOT EXISTS task_progress SCHEMALESS;")
+            .await
+            .context("Failed to create task progress schema")?;
+
+        // Planned tasks and their claims, shared by every Cortex instance
+        self.db
+            .query(
+                "DEFINE TABLE IF NOT EXISTS task_queue SCHEMALESS;
+                 DEFINE INDEX IF NOT EXISTS task_queue_status_idx ON task_queue FIELDS status;",
+            )
+            .await
+            .context("Failed to create task queue schema")?;
+
+        self.db
+            .query("DEFINE TABLE IF NOT EXISTS direct_endpoints SCHEMALESS;")
+            .await
+            .context("Failed to create direct endpoints schema")?;
+
+        // Idempotency keys consumers have already processed
+        self.db
+            .query(
+                "DEFINE TABLE IF NOT EXISTS processed SCHEMALESS;
+                 DEFINE INDEX IF NOT EXISTS processed_seen_idx ON processed FIELDS seen_at;",
+            )
+            .await
+            .context("Failed to create processed schema")?;
+
+        // Expired VOX and blackboard messages, kept for audit
+        self.db
+            .query(
+                "DEFINE TABLE IF NOT EXISTS vox_archive SCHEMALESS;
+                 DEFINE TABLE IF NOT EXISTS message_archive SCHEMALESS;",
+            )
+            .await
+            .context("Failed to create archive tables")?;
+
+        self.db
+            .query(
+                "DEFINE TABLE IF NOT EXISTS traces SCHEMALESS;
+                 DEFINE INDEX IF NOT EXISTS trace_task_idx ON traces FIELDS task_id;",
+            )
+            .await
+            .context("Failed to create traces schema")?;
+
         // Maintain legacy tables for compatibility during transition
         self.db
             .query(
@@ -113,6 +196,7 @@ impl BlackboardDb {
                  DEFINE FIELD IF NOT EXISTS metadata ON messages TYPE object;
                  DEFINE FIELD IF NOT EXISTS timestamp ON messages TYPE int;
                  DEFINE FIELD IF NOT EXISTS reply_to ON messages TYPE option<string>;
+                 DEFINE FIELD IF NOT EXISTS ttl_secs ON messages TYPE option<int>;
                  DEFINE INDEX IF NOT EXISTS message_type_idx ON messages FIELDS message_type;
                  DEFINE INDEX IF NOT EXISTS from_agent_idx ON messages FIELDS from_agent;
                  DEFINE INDEX IF NOT EXISTS timestamp_idx ON messages FIELDS timestamp;",
@@ -153,11 +237,101 @@ impl BlackboardDb {
         Ok(())
     }
 
+    /// Override the compression threshold and inline cap for VOX payloads
+    pub fn with_payload_codec(mut self, codec: PayloadCodec) -> Self {
+        self.codec = codec;
+        self
+    }
+
+    /// Override how long processed idempotency keys are remembered
+    pub fn with_dedup_window(mut self, window: std::time::Duration) -> Self {
+        self.dedup_window = window;
+        self
+    }
+
+    /// Validate posted messages against `schemas` instead of the VOX defaults
+    pub fn with_schema_registry(mut self, schemas: SchemaRegistry) -> Self {
+        self.schemas = schemas;
+        self
+    }
+
+    /// Reject (strict) or only log (lenient) messages that fail validation
+    pub fn with_schema_mode(mut self, mode: SchemaMode) -> Self {
+        self.schema_mode = mode;
+        self
+    }
+
+    pub(crate) fn dedup_window(&self) -> std::time::Duration {
+        self.dedup_window
+    }
+
+    /// Store holding VOX payloads too large for the bus
+    pub fn artifacts(&self) -> &ArtifactStore {
+        &self.artifacts
+    }
+
+    /// Write a VOX message to the MOM substrate
+    ///
+    /// Large payloads are compressed, and payloads over the inline cap are
+    /// replaced by an `ArtifactRef` (see [`PayloadCodec`]).
+    pub async fn post_vox(&self, message: VoxMessage) -> Result<()> {
+        fail_point(FaultPoint::Database)?;
+        let message = self.codec.encode(message, &self.artifacts)?;
+        let _: Option<VoxMessage> = self
+            .db
+            .create("blackboard")
+            .content(message)
+            .await
+            .context("Failed to post VOX message")?;
+
+        Ok(())
+    }
+
+    /// Token that stops the MOM watcher (link it to a `ShutdownController`)
+    pub fn watcher_token(&self) -> zed42_core::CancellationToken {
+        self.watcher_token.clone()
+    }
+
     /// Subscribe to real-time VOX messages for a specific team via MOM
-    pub fn subscribe(&self, team: Team) -> broadcast::Receiver<VoxMessage> {
+    pub fn subscribe(&self, team: Team) -> zed42_core::vox::VoxReceiver {
         self.mom.subscribe(team)
     }
 
+    /// Subscribe to a team's VOX messages, replaying those created since `since`
+    ///
+    /// Messages already on the blackboard for the team (or for "all") are
+    /// delivered oldest first, then live traffic. The live subscription
+    /// opens before the history is read, so nothing posted in between is
+    /// missed or delivered twice. Expired messages are not replayed.
+    pub async fn subscribe_from(&self, team: Team, since: chrono::DateTime<chrono::Utc>) -> Result<zed42_core::vox::VoxReceiver> {
+        fail_point(FaultPoint::Database)?;
+        let receiver = self.mom.subscribe(team);
+        let team_key = format!("{:?}", team).to_lowercase();
+        let mut response = self
+            .db
+            .query(
+                "SELECT * FROM blackboard
+                 WHERE created_at >= $since AND string::lowercase(target_team) IN [$team, 'all']
+                 ORDER BY created_at ASC",
+            )
+            .bind(("since", since))
+            .bind(("team", team_key))
+            .await
+            .context("Failed to query VOX history for replay")?;
+        let stored: Vec<VoxMessage> = response.take(0)?;
+
+        let now = chrono::Utc::now();
+        let mut history = Vec::with_capacity(stored.len());
+        for msg in stored {
+            match crate::codec::decode(msg) {
+                Ok(msg) if !msg.is_expired_at(now) => history.push(msg),
+                Ok(_) => {}
+                Err(e) => tracing::warn!("Skipping undecodable VOX message in replay: {}", e),
+            }
+        }
+        Ok(receiver.with_replay(history))
+    }
+
     /// Update AURA vitality pulse
     /// 
     /// Uses SurrealDB time::now() to prevent clock skew issues.
@@ -175,8 +349,58 @@ impl BlackboardDb {
         Ok(())
     }
 
+    /// Record resource vitals (memory, queue depth, ...) reported by a subsystem
+    pub async fn record_vitals(&self, source: &str, vitals: serde_json::Value) -> Result<()> {
+        self.db.query("UPSERT type::thing('aura_vitals', $source) SET reported_at = time::now(), vitals = $vitals")
+            .bind(("source", source.to_string()))
+            .bind(("vitals", vitals))
+            .await
+            .context("Failed to record AURA vitals")?;
+
+        Ok(())
+    }
+
+    /// Broadcast a system alert to every team via MOM
+    pub async fn broadcast_alert(&self, action: &str, reason: &str) {
+        let alert = VoxMessage {
+            sender: surrealdb::sql::Thing::from(("system", "aura")),
+            target_team: "all".to_string(),
+            priority: 255,
+            correlation_id: uuid::Uuid::new_v4(),
+            payload: zed42_core::vox::VoxPayload::SystemAlert {
+                action: action.to_string(),
+                agent_id: None,
+                reason: reason.to_string(),
+            },
+            created_at: chrono::Utc::now(),
+            ttl_secs: None,
+            idempotency_key: None,
+        };
+        self.mom.broadcast_system_message(alert).await;
+    }
+
+    /// Latest vitals reported by a subsystem
+    pub async fn get_vitals(&self, source: &str) -> Result<Option<serde_json::Value>> {
+        let mut response = self.db.query("SELECT VALUE vitals FROM type::thing('aura_vitals', $source)")
+            .bind(("source", source.to_string()))
+            .await?;
+        let vitals: Option<serde_json::Value> = response.take(0)?;
+        Ok(vitals)
+    }
+
     /// Post a message to the blackboard
+    ///
+    /// The message is validated against the schema registry first. In strict
+    /// mode a malformed message is rejected with a [`crate::SchemaViolation`]
+    /// listing every problem; in lenient mode it is logged and posted.
     pub async fn post_message(&self, message: Message) -> Result<()> {
+        fail_point(FaultPoint::Database)?;
+        if let Err(violation) = self.schemas.validate(&message) {
+            match self.schema_mode {
+                SchemaMode::Strict => return Err(violation.into()),
+                SchemaMode::Lenient => tracing::warn!(%violation, "Posting message that fails schema validation"),
+            }
+        }
         let _: Message = self
             .db
             .create("messages")
@@ -190,23 +414,8 @@ impl BlackboardDb {
 
     /// Get messages matching filter
     pub async fn get_messages(&self, filter: MessageFilter) -> Result<Vec<Message>> {
-        let mut conditions = Vec::new();
-
-        if let Some(msg_type) = filter.message_type {
-            conditions.push(format!("message_type = '{:?}'", msg_type));
-        }
-
-        if let Some(from) = filter.from_agent {
-            conditions.push(format!("from_agent = '{}'", from));
-        }
-
-        if let Some(to) = filter.to_agent {
-            conditions.push(format!("to_agent = '{}'", to));
-        }
-
-        if let Some(since) = filter.since_timestamp {
-            conditions.push(format!("timestamp >= {}", since));
-        }
+        fail_point(FaultPoint::Database)?;
+        let conditions = message_conditions(&filter);
 
         let where_clause = if conditions.is_empty() {
             String::new()
@@ -225,28 +434,80 @@ impl BlackboardDb {
             where_clause, limit_clause
         );
 
-        let mut response: Response = self.db.query(query).await?;
+        let mut response: Response = self
+            .db
+            .query(query)
+            .bind(("tenant", filter.tenant_id.clone()))
+            .await?;
         let messages: Vec<Message> = response.take(0)?;
 
-        Ok(messages)
+        let now = chrono::Utc::now();
+        let (expired, live): (Vec<Message>, Vec<Message>) =
+            messages.into_iter().partition(|m| m.is_expired_at(now));
+        for message in expired {
+            self.archive_message(message).await?;
+        }
+
+        Ok(live)
+    }
+
+    /// Move an expired message to `message_archive`
+    async fn archive_message(&self, message: Message) -> Result<()> {
+        let id = message.id.to_string();
+        self.db
+            .query(
+                "CREATE message_archive CONTENT { message: $message, reason: 'expired', archived_at: time::now() };
+                 DELETE type::thing('messages', $id);",
+            )
+            .bind(("message", message))
+            .bind(("id", id))
+            .await
+            .context("Failed to archive expired message")?
+            .check()
+            .context("Failed to archive expired message")?;
+        Ok(())
+    }
+
+    /// Messages that expired before they were read, oldest first
+    pub async fn archived_messages(&self) -> Result<Vec<Message>> {
+        let mut response = self
+            .db
+            .query("SELECT VALUE message FROM message_archive ORDER BY archived_at ASC")
+            .await
+            .context("Failed to query archived messages")?;
+        Ok(response.take(0)?)
+    }
+
+    /// VOX messages that expired before the MOM watcher delivered them
+    pub async fn archived_vox(&self) -> Result<Vec<VoxMessage>> {
+        let mut response = self
+            .db
+            .query("SELECT VALUE message FROM vox_archive ORDER BY archived_at ASC")
+            .await
+            .context("Failed to query archived VOX messages")?;
+        Ok(response.take(0)?)
     }
 
     /// Set state entry
     pub async fn set_state(&self, entry: StateEntry) -> Result<()> {
+        fail_point(FaultPoint::Database)?;
         // Update or insert
-        let _: StateEntry = self
+        let entry: StateEntry = self
             .db
             .create("state")
             .content(entry)
             .await
             .context("Failed to set state")?
             .context("Failed to create state record")?;
+        // Nobody listening is fine
+        let _ = self.state_changes.send(entry);
 
         Ok(())
     }
 
     /// Get state entry
     pub async fn get_state(&self, key: &StateKey) -> Result<Option<StateEntry>> {
+        fail_point(FaultPoint::Database)?;
         let query = format!("SELECT * FROM state WHERE key = '{}'", key);
 
         let mut response: Response = self.db.query(query).await?;
@@ -255,8 +516,20 @@ impl BlackboardDb {
         Ok(entries.into_iter().next())
     }
 
+    /// Get state entry on behalf of a tenant
+    ///
+    /// Fails if the entry belongs to a different tenant.
+    pub async fn get_state_for_tenant(&self, tenant_id: &str, key: &StateKey) -> Result<Option<StateEntry>> {
+        let entry = self.get_state(key).await?;
+        if let Some(ref entry) = entry {
+            zed42_core::tenant::ensure_tenant(Some(tenant_id), entry.tenant_id.as_deref())?;
+        }
+        Ok(entry)
+    }
+
     /// Record a decision
     pub async fn record_decision(&self, decision: DecisionNode) -> Result<()> {
+        fail_point(FaultPoint::Database)?;
         let _: DecisionNode = self
             .db
             .create("decisions")
@@ -270,6 +543,7 @@ impl BlackboardDb {
 
     /// Get decision history for an agent
     pub async fn get_decisions(&self, agent_id: Option<AgentId>) -> Result<Vec<DecisionNode>> {
+        fail_point(FaultPoint::Database)?;
         let query = if let Some(agent) = agent_id {
             format!(
                 "SELECT * FROM decisions WHERE made_by = '{}' ORDER BY timestamp DESC",
@@ -285,6 +559,44 @@ impl BlackboardDb {
         Ok(decisions)
     }
 
+    /// Decisions recorded with a confidence below `threshold`, most recent first
+    ///
+    /// Decisions without a confidence score are left out.
+    pub async fn get_low_confidence_decisions(&self, threshold: zed42_core::types::Confidence) -> Result<Vec<DecisionNode>> {
+        let decisions = self.get_decisions(None).await?;
+        Ok(decisions
+            .into_iter()
+            .filter(|d| d.confidence.is_some_and(|c| c < threshold))
+            .collect())
+    }
+
+    /// Record a step an agent took on a task (see [`zed42_core::TraceRecord`])
+    pub async fn record_trace(&self, record: zed42_core::TraceRecord) -> Result<()> {
+        fail_point(FaultPoint::Database)?;
+        self.db
+            .query("CREATE traces CONTENT $record")
+            .bind(("record", record))
+            .await
+            .context("Failed to record trace")?;
+        Ok(())
+    }
+
+    /// Trace records of a task, or of every task, oldest first
+    pub async fn get_traces(&self, task_id: Option<&str>) -> Result<Vec<zed42_core::TraceRecord>> {
+        fail_point(FaultPoint::Database)?;
+        let mut response: Response = match task_id {
+            Some(task_id) => {
+                self.db
+                    .query("SELECT * FROM traces WHERE task_id = $task ORDER BY timestamp ASC")
+                    .bind(("task", task_id.to_string()))
+                    .await?
+            }
+            None => self.db.query("SELECT * FROM traces ORDER BY timestamp ASC").await?,
+        };
+        let records: Vec<zed42_core::TraceRecord> = response.take(0)?;
+        Ok(records)
+    }
+
     /// Get blackboard statistics
     pub async fn stats(&self) -> Result<BlackboardStats> {
         let mut msg_count_response: Response = self
@@ -318,9 +630,45 @@ impl BlackboardDb {
         &self.db
     }
 
+    pub(crate) fn state_changes(&self) -> &tokio::sync::broadcast::Sender<StateEntry> {
+        &self.state_changes
+    }
+
+    pub(crate) fn direct(&self) -> &DirectRegistry {
+        &self.direct
+    }
+
     /// Internal access to the MOM Watcher
     pub(crate) fn mom(&self) -> &crate::MOMWatcher {
         &self.mom
     }
 }
 
+/// SurrealQL conditions selecting the messages `filter` matches
+///
+/// The tenant is referenced as `$tenant`; callers bind it from `filter.tenant_id`.
+pub(crate) fn message_conditions(filter: &MessageFilter) -> Vec<String> {
+    let mut conditions = Vec::new();
+
+    if let Some(ref msg_type) = filter.message_type {
+        conditions.push(format!("message_type = '{:?}'", msg_type));
+    }
+
+    if let Some(from) = filter.from_agent {
+        conditions.push(format!("from_agent = '{}'", from));
+    }
+
+    if let Some(to) = filter.to_agent {
+        conditions.push(format!("to_agent = '{}'", to));
+    }
+
+    if let Some(since) = filter.since_timestamp {
+        conditions.push(format!("timestamp >= {}", since));
+    }
+
+    if filter.tenant_id.is_some() {
+        conditions.push("(tenant_id = $tenant OR tenant_id = NONE OR tenant_id = NULL)".to_string());
+    }
+
+    conditions
+}
@@ -0,0 +1,91 @@
+//! Consumer-side message deduplication
+//!
+//! Producers re-post messages after a reconnect, so the same VOX message can
+//! reach an agent twice. Messages carrying an idempotency key are claimed in
+//! the `processed` table before they are handled; a second claim of the same
+//! `(consumer, correlation, key)` within the dedup window is refused. A
+//! consumer that fails to handle a message releases its claim, so the next
+//! delivery is handled instead of skipped. Claims older than the window are
+//! purged, so the table stays small.
+
+use crate::BlackboardDb;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::time::Duration;
+use zed42_core::vox::VoxMessage;
+use zed42_core::{AgentId, Message};
+
+/// How long a processed key is remembered by default
+pub const DEFAULT_DEDUP_WINDOW: Duration = Duration::from_secs(10 * 60);
+
+/// Earlier claim returned by the upsert (only its presence matters)
+#[derive(Debug, Deserialize)]
+struct Claim {}
+
+impl BlackboardDb {
+    /// Record that `consumer` processed `key`
+    ///
+    /// Returns `false` if the key was already claimed within the dedup
+    /// window, in which case the caller should skip the message.
+    pub async fn mark_processed(&self, consumer: AgentId, key: &str) -> Result<bool> {
+        let mut response = self
+            .db()
+            .query(
+                "DELETE processed WHERE seen_at < time::now() - type::duration($window);
+                 UPSERT type::thing('processed', $id) SET key = $key, consumer = $consumer, seen_at = time::now() RETURN BEFORE;",
+            )
+            .bind(("window", format!("{}ms", self.dedup_window().as_millis())))
+            .bind(("id", format!("{}/{}", consumer, key)))
+            .bind(("key", key.to_string()))
+            .bind(("consumer", consumer.to_string()))
+            .await
+            .context("Failed to record processed message")?;
+        let before: Option<Claim> = response.take(1).context("Failed to read processed message claim")?;
+        Ok(before.is_none())
+    }
+
+    /// Forget `consumer`'s claim of `key`, e.g. after handling it failed
+    pub async fn release_processed(&self, consumer: AgentId, key: &str) -> Result<()> {
+        self.db()
+            .query("DELETE type::thing('processed', $id)")
+            .bind(("id", format!("{}/{}", consumer, key)))
+            .await
+            .and_then(|response| response.check())
+            .context("Failed to release processed message claim")?;
+        Ok(())
+    }
+
+    /// Claim a VOX message for `consumer`; `false` means it is a duplicate
+    ///
+    /// Messages without an idempotency key are always claimed.
+    pub async fn claim_vox(&self, consumer: AgentId, msg: &VoxMessage) -> Result<bool> {
+        match msg.dedup_key() {
+            Some(key) => self.mark_processed(consumer, &key).await,
+            None => Ok(true),
+        }
+    }
+
+    /// Release the claim [`claim_vox`](Self::claim_vox) took, so a redelivery is handled
+    pub async fn release_vox(&self, consumer: AgentId, msg: &VoxMessage) -> Result<()> {
+        match msg.dedup_key() {
+            Some(key) => self.release_processed(consumer, &key).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Claim a blackboard message for `consumer`; `false` means it is a duplicate
+    pub async fn claim_message(&self, consumer: AgentId, msg: &Message) -> Result<bool> {
+        match msg.dedup_key() {
+            Some(key) => self.mark_processed(consumer, &key).await,
+            None => Ok(true),
+        }
+    }
+
+    /// Release the claim [`claim_message`](Self::claim_message) took, so a redelivery is handled
+    pub async fn release_message(&self, consumer: AgentId, msg: &Message) -> Result<()> {
+        match msg.dedup_key() {
+            Some(key) => self.release_processed(consumer, &key).await,
+            None => Ok(()),
+        }
+    }
+}
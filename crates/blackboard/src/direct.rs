@@ -0,0 +1,204 @@
+//! Direct agent-to-agent channels
+//!
+//! High-frequency exchanges (implementer ↔ tester ping-pong) skip the
+//! database when both agents run in this process: each pair shares a pair
+//! of bounded mpsc queues. Agents advertise which process they live in via
+//! the `direct_endpoints` table; when the peer is elsewhere, unregistered,
+//! or has gone away, the channel falls back to ordinary blackboard messages.
+
+use crate::BlackboardDb;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tokio::time::Duration;
+use uuid::Uuid;
+use zed42_core::messages::MessageTarget;
+use zed42_core::{AgentId, Message};
+
+/// Messages buffered per direction before `send` waits
+const DIRECT_CHANNEL_CAPACITY: usize = 256;
+
+/// How often a fallback channel polls the blackboard
+const FALLBACK_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Where an agent can be reached, as recorded on the blackboard
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirectEndpoint {
+    pub agent_id: AgentId,
+    /// Registry the agent's channels live in (one per process)
+    pub process_id: Uuid,
+    pub registered_at: DateTime<Utc>,
+}
+
+/// One side of a pair's queues, waiting for the peer to claim it
+struct PendingEnd {
+    tx: mpsc::Sender<Message>,
+    rx: mpsc::Receiver<Message>,
+}
+
+/// In-process half of the negotiation: queues not yet claimed by the peer
+pub(crate) struct DirectRegistry {
+    process_id: Uuid,
+    /// Keyed by (owner, peer)
+    pending: DashMap<(AgentId, AgentId), PendingEnd>,
+}
+
+impl DirectRegistry {
+    pub(crate) fn new() -> Self {
+        Self {
+            process_id: Uuid::new_v4(),
+            pending: DashMap::new(),
+        }
+    }
+
+    pub(crate) fn process_id(&self) -> Uuid {
+        self.process_id
+    }
+
+    /// Claim the end the peer left for `me`, or create the pair and leave
+    /// the peer's end behind
+    fn connect(&self, me: AgentId, peer: AgentId) -> (mpsc::Sender<Message>, mpsc::Receiver<Message>) {
+        if let Some((_, end)) = self.pending.remove(&(me, peer)) {
+            if !end.tx.is_closed() {
+                return (end.tx, end.rx);
+            }
+        }
+        let (to_peer, from_me) = mpsc::channel(DIRECT_CHANNEL_CAPACITY);
+        let (to_me, from_peer) = mpsc::channel(DIRECT_CHANNEL_CAPACITY);
+        self.pending.insert((peer, me), PendingEnd { tx: to_me, rx: from_me });
+        (to_peer, from_peer)
+    }
+}
+
+enum Transport {
+    Local {
+        tx: mpsc::Sender<Message>,
+        rx: mpsc::Receiver<Message>,
+    },
+    Blackboard,
+}
+
+/// A negotiated channel between two agents
+pub struct DirectChannel {
+    me: AgentId,
+    peer: AgentId,
+    transport: Transport,
+    /// Newest fallback message already returned by `recv`
+    seen_until: DateTime<Utc>,
+}
+
+impl DirectChannel {
+    pub fn peer(&self) -> AgentId {
+        self.peer
+    }
+
+    /// Messages bypass the database
+    pub fn is_local(&self) -> bool {
+        matches!(self.transport, Transport::Local { .. })
+    }
+
+    /// Send to the peer, falling back to the blackboard if it went away
+    pub async fn send(&mut self, blackboard: &BlackboardDb, mut message: Message) -> Result<()> {
+        message.from_agent = self.me;
+        message.to_team = MessageTarget::Agent(self.peer);
+        if let Transport::Local { tx, .. } = &self.transport {
+            match tx.send(message).await {
+                Ok(()) => return Ok(()),
+                Err(mpsc::error::SendError(returned)) => {
+                    tracing::debug!(me = %self.me, peer = %self.peer, "Direct peer closed, falling back to blackboard");
+                    message = returned;
+                    self.transport = Transport::Blackboard;
+                }
+            }
+        }
+        blackboard.post_message(message).await
+    }
+
+    /// Next message from the peer
+    pub async fn recv(&mut self, blackboard: &BlackboardDb) -> Result<Message> {
+        if let Transport::Local { rx, .. } = &mut self.transport {
+            if let Some(message) = rx.recv().await {
+                return Ok(message);
+            }
+            self.transport = Transport::Blackboard;
+        }
+        loop {
+            if let Some(message) = self.poll_blackboard(blackboard).await? {
+                return Ok(message);
+            }
+            tokio::time::sleep(FALLBACK_POLL_INTERVAL).await;
+        }
+    }
+
+    async fn poll_blackboard(&mut self, blackboard: &BlackboardDb) -> Result<Option<Message>> {
+        let filter = crate::MessageFilter {
+            from_agent: Some(self.peer),
+            ..Default::default()
+        };
+        let next = blackboard
+            .get_messages(filter)
+            .await?
+            .into_iter()
+            .filter(|m| matches!(m.to_team, MessageTarget::Agent(id) if id == self.me))
+            .filter(|m| m.timestamp > self.seen_until)
+            .min_by_key(|m| m.timestamp);
+        if let Some(message) = &next {
+            self.seen_until = message.timestamp;
+        }
+        Ok(next)
+    }
+}
+
+impl BlackboardDb {
+    /// Advertise that `agent_id` can take direct channels in this process
+    ///
+    /// Agents register at startup so peers can negotiate a local channel.
+    pub async fn register_direct_endpoint(&self, agent_id: AgentId) -> Result<()> {
+        self.db()
+            .query("UPSERT type::thing('direct_endpoints', $agent) SET agent_id = $agent_id, process_id = $process, registered_at = time::now()")
+            .bind(("agent", agent_id.to_string()))
+            .bind(("agent_id", agent_id))
+            .bind(("process", self.direct().process_id()))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    pub async fn direct_endpoint(&self, agent_id: AgentId) -> Result<Option<DirectEndpoint>> {
+        let mut response = self.db()
+            .query("SELECT agent_id, process_id, registered_at FROM type::thing('direct_endpoints', $agent)")
+            .bind(("agent", agent_id.to_string()))
+            .await?;
+        let endpoint: Option<DirectEndpoint> = response.take(0)?;
+        Ok(endpoint)
+    }
+
+    /// Open a channel from `me` to `peer`
+    ///
+    /// Local when the peer registered from this process, otherwise backed
+    /// by blackboard messages. Both agents call this with their own id
+    /// first; the second caller claims the queues the first one created.
+    pub async fn open_direct(&self, me: AgentId, peer: AgentId) -> Result<DirectChannel> {
+        self.register_direct_endpoint(me).await?;
+        let local = self
+            .direct_endpoint(peer)
+            .await?
+            .is_some_and(|endpoint| endpoint.process_id == self.direct().process_id());
+
+        let transport = if local {
+            let (tx, rx) = self.direct().connect(me, peer);
+            Transport::Local { tx, rx }
+        } else {
+            tracing::debug!(%me, %peer, "Peer not in this process, direct channel uses the blackboard");
+            Transport::Blackboard
+        };
+        Ok(DirectChannel {
+            me,
+            peer,
+            transport,
+            seen_until: Utc::now(),
+        })
+    }
+}
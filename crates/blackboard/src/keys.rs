@@ -0,0 +1,244 @@
+//! Namespaced state keys
+//!
+//! `StateKey` is a free-form string, so two agents can pick the same key
+//! for different things and a typo silently reads nothing. A
+//! [`NamespacedKey`] is `domain/scope/name`: the domain says what the value
+//! is about (`plan`, `review`, `ledger`), the scope whose it is (a session,
+//! task or agent, or [`GLOBAL_SCOPE`]), and the name which value. Segments
+//! are lowercase ASCII letters, digits, `_`, `-` and `.`.
+//!
+//! Keys written before namespacing can be moved with
+//! [`BlackboardDb::migrate_state_keys`], and every key in a domain or scope
+//! listed with [`BlackboardDb::get_state_by_prefix`].
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{BlackboardDb, StateEntry, StateKey};
+
+/// Scope of keys shared by the whole project
+pub const GLOBAL_SCOPE: &str = "global";
+
+/// Domain free-form keys are migrated to by [`NamespacedKey::legacy`]
+pub const LEGACY_DOMAIN: &str = "legacy";
+
+const SEPARATOR: char = '/';
+
+/// A `domain/scope/name` state key
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct NamespacedKey {
+    domain: String,
+    scope: String,
+    name: String,
+}
+
+impl NamespacedKey {
+    pub fn new(domain: &str, scope: &str, name: &str) -> Result<Self> {
+        for (segment, value) in [("domain", domain), ("scope", scope), ("name", name)] {
+            validate_segment(value).with_context(|| format!("Invalid state key {}: '{}'", segment, value))?;
+        }
+        Ok(Self { domain: domain.to_string(), scope: scope.to_string(), name: name.to_string() })
+    }
+
+    /// Start a key in `domain`; scope defaults to [`GLOBAL_SCOPE`]
+    pub fn in_domain(domain: impl Into<String>) -> KeyBuilder {
+        KeyBuilder { domain: domain.into(), scope: GLOBAL_SCOPE.to_string() }
+    }
+
+    /// Parse `domain/scope/name`
+    pub fn parse(key: &str) -> Result<Self> {
+        let segments: Vec<&str> = key.split(SEPARATOR).collect();
+        let [domain, scope, name] = segments.as_slice() else {
+            anyhow::bail!("State key '{}' is not domain/scope/name", key);
+        };
+        Self::new(domain, scope, name)
+    }
+
+    /// Namespaced form of a free-form key: `legacy/global/<key>`
+    ///
+    /// Characters not allowed in a segment become `_`, and uppercase is lowered.
+    pub fn legacy(key: &str) -> Self {
+        let mut name: String = key
+            .chars()
+            .map(|c| c.to_ascii_lowercase())
+            .map(|c| if is_segment_char(c) { c } else { '_' })
+            .collect();
+        if name.is_empty() {
+            name.push('_');
+        }
+        Self { domain: LEGACY_DOMAIN.to_string(), scope: GLOBAL_SCOPE.to_string(), name }
+    }
+
+    pub fn domain(&self) -> &str {
+        &self.domain
+    }
+
+    pub fn scope(&self) -> &str {
+        &self.scope
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Prefix shared by every key in `domain`
+    pub fn domain_prefix(domain: &str) -> String {
+        format!("{}{}", domain, SEPARATOR)
+    }
+
+    /// Prefix shared by every key in `domain` and `scope`
+    pub fn scope_prefix(domain: &str, scope: &str) -> String {
+        format!("{}{}{}{}", domain, SEPARATOR, scope, SEPARATOR)
+    }
+}
+
+/// Builder returned by [`NamespacedKey::in_domain`]
+#[derive(Debug, Clone)]
+pub struct KeyBuilder {
+    domain: String,
+    scope: String,
+}
+
+impl KeyBuilder {
+    pub fn scope(mut self, scope: impl Into<String>) -> Self {
+        self.scope = scope.into();
+        self
+    }
+
+    pub fn name(self, name: &str) -> Result<NamespacedKey> {
+        NamespacedKey::new(&self.domain, &self.scope, name)
+    }
+}
+
+impl fmt::Display for NamespacedKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}{}{}{}", self.domain, SEPARATOR, self.scope, SEPARATOR, self.name)
+    }
+}
+
+impl FromStr for NamespacedKey {
+    type Err = anyhow::Error;
+
+    fn from_str(key: &str) -> Result<Self> {
+        Self::parse(key)
+    }
+}
+
+impl TryFrom<String> for NamespacedKey {
+    type Error = anyhow::Error;
+
+    fn try_from(key: String) -> Result<Self> {
+        Self::parse(&key)
+    }
+}
+
+impl From<NamespacedKey> for String {
+    fn from(key: NamespacedKey) -> Self {
+        key.to_string()
+    }
+}
+
+fn is_segment_char(c: char) -> bool {
+    c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '_' | '-' | '.')
+}
+
+fn validate_segment(segment: &str) -> Result<()> {
+    anyhow::ensure!(!segment.is_empty(), "segment is empty");
+    if let Some(c) = segment.chars().find(|c| !is_segment_char(*c)) {
+        anyhow::bail!("'{}' is not allowed (use lowercase letters, digits, '_', '-' or '.')", c);
+    }
+    Ok(())
+}
+
+/// Outcome of [`BlackboardDb::migrate_state_keys`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyMigrationReport {
+    /// `(old, new)` for every key renamed
+    pub migrated: Vec<(StateKey, StateKey)>,
+    /// Keys already namespaced
+    pub unchanged: usize,
+    /// Keys whose new name is taken, left as they were
+    pub conflicts: Vec<StateKey>,
+}
+
+impl BlackboardDb {
+    /// State entries whose key starts with `prefix`, in key order
+    ///
+    /// Use [`NamespacedKey::domain_prefix`] or [`NamespacedKey::scope_prefix`]
+    /// to list a domain or one scope of it.
+    pub async fn get_state_by_prefix(&self, prefix: &str) -> Result<Vec<StateEntry>> {
+        zed42_core::chaos::fail_point(zed42_core::chaos::FaultPoint::Database)?;
+        let mut response = self
+            .db()
+            .query("SELECT * FROM state WHERE string::starts_with(key, $prefix) ORDER BY key ASC")
+            .bind(("prefix", prefix.to_string()))
+            .await
+            .context("Failed to query state by prefix")?;
+        Ok(response.take(0)?)
+    }
+
+    /// Rename free-form state keys to namespaced ones
+    ///
+    /// `rename` maps a key that doesn't parse as a [`NamespacedKey`] to its
+    /// new name; `None` uses [`NamespacedKey::legacy`]. Keys whose new name
+    /// is already taken are reported and left alone.
+    pub async fn migrate_state_keys<F>(&self, rename: F) -> Result<KeyMigrationReport>
+    where
+        F: Fn(&str) -> Option<NamespacedKey>,
+    {
+        let mut response = self
+            .db()
+            .query("SELECT VALUE key FROM state")
+            .await
+            .context("Failed to list state keys")?;
+        let keys: Vec<StateKey> = response.take(0)?;
+        let mut existing: std::collections::HashSet<StateKey> = keys.iter().cloned().collect();
+
+        let mut report = KeyMigrationReport::default();
+        for key in keys {
+            if NamespacedKey::parse(&key).is_ok() {
+                report.unchanged += 1;
+                continue;
+            }
+            let new_key = rename(&key).unwrap_or_else(|| NamespacedKey::legacy(&key)).to_string();
+            if existing.contains(&new_key) {
+                report.conflicts.push(key);
+                continue;
+            }
+            self.db()
+                .query("UPDATE state SET key = $new WHERE key = $old")
+                .bind(("new", new_key.clone()))
+                .bind(("old", key.clone()))
+                .await
+                .and_then(|response| response.check())
+                .with_context(|| format!("Failed to migrate state key '{}'", key))?;
+            existing.remove(&key);
+            existing.insert(new_key.clone());
+            report.migrated.push((key, new_key));
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_parse_and_legacy_keys() {
+        let key = NamespacedKey::in_domain("plan").scope("session-42").name("current_phase").unwrap();
+        assert_eq!(key.to_string(), "plan/session-42/current_phase");
+        assert_eq!("plan/session-42/current_phase".parse::<NamespacedKey>().unwrap(), key);
+        assert_eq!(NamespacedKey::in_domain("review").name("queue").unwrap().scope(), GLOBAL_SCOPE);
+
+        assert!(NamespacedKey::parse("plan/current_phase").is_err());
+        let err = NamespacedKey::in_domain("Plan").name("phase").unwrap_err();
+        assert!(format!("{:#}", err).contains("domain"));
+
+        assert_eq!(NamespacedKey::legacy("Current Phase").to_string(), "legacy/global/current_phase");
+        assert!(key.to_string().starts_with(&NamespacedKey::scope_prefix("plan", "session-42")));
+    }
+}
@@ -1,23 +1,46 @@
 //! Blackboard system for agent coordination and state management
 
+mod artifacts;
+pub mod codec;
 mod database;
+mod dedup;
+mod direct;
 mod graph;
+pub mod keys;
+mod paging;
+pub mod progress;
+pub mod task_queue;
 mod state;
 mod types;
+mod watch;
 mod mom;
 mod aura;
 mod resolver;
+pub mod load;
+pub mod schema;
+pub mod transcript;
 
 #[cfg(test)]
 mod tests;
 
+pub use artifacts::ArtifactStore;
+pub use codec::PayloadCodec;
 pub use database::BlackboardDb;
+pub use dedup::DEFAULT_DEDUP_WINDOW;
+pub use schema::{SchemaMode, SchemaRegistry, SchemaViolation};
+pub use direct::{DirectChannel, DirectEndpoint};
 pub use graph::{DecisionGraph, EdgeType};
-pub use mom::MOMWatcher;
+pub use keys::{KeyMigrationReport, NamespacedKey};
+pub use watch::StateWatch;
+pub use progress::TaskProgress;
+pub use task_queue::{QueueStatus, QueuedTask};
+pub use mom::{MOMWatcher, MomStats};
 pub use aura::AuraSentinel as Aura;
+pub use aura::PROGRESS_VITALS_SOURCE;
 
 // Re-export core types used by blackboard via root re-exports
 pub use zed42_core::{Result, Error};
+pub use zed42_core::page::{Cursor, Page, PageRequest};
 pub use zed42_core::{
     AgentId, Priority, Team, ThreadId, MessageId,
     Message, MessageType
@@ -35,6 +58,7 @@ pub use types::{
 pub use zed42_core::AgentStatus;
 pub use state::{BlackboardState};
 pub use resolver::{StateResolver, ConsensusState};
+pub use transcript::{Transcript, TranscriptEntry, TranscriptSource};
 
 // Backwards compatibility
 pub use zed42_core::messages::MessageTarget;
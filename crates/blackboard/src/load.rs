@@ -0,0 +1,342 @@
+//! Load test mode for the MOM substrate
+//!
+//! Floods the `blackboard` table with synthetic VOX messages at a fixed
+//! rate while a [`MOMWatcher`] routes the LIVE SELECT notifications to team
+//! subscribers. Each run reports SurrealDB write throughput and latency,
+//! end-to-end delivery latency (write start to subscriber receive), and how
+//! many messages subscribers lost to broadcast lag or never received.
+//!
+//! `cargo bench -p zed42-blackboard --bench mom_load` sweeps a range of
+//! rates against an in-memory database.
+
+use crate::mom::{MOMWatcher, MomStats};
+use crate::types::VoxMessage;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use surrealdb::engine::any::{connect, Any};
+use surrealdb::{Connection, Surreal};
+use tokio::sync::broadcast::error::RecvError;
+use uuid::Uuid;
+use zed42_core::vox::VoxPayload;
+use zed42_core::{CancellationToken, Team};
+
+/// Shape of one load run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoadConfig {
+    /// Target VOX messages written per second
+    pub rate_per_sec: u32,
+    pub duration: Duration,
+    /// Messages are spread round-robin over these teams
+    pub teams: Vec<Team>,
+    pub subscribers_per_team: usize,
+    /// Size of each message's observation payload
+    pub payload_bytes: usize,
+    /// Broadcast buffer per team channel (applied by `LoadGenerator::in_memory`;
+    /// a watcher passed to `LoadGenerator::new` keeps its own)
+    pub channel_capacity: usize,
+    /// Simulated work per received message, to find where subscribers lag
+    pub subscriber_delay: Option<Duration>,
+    /// How long to wait for in-flight notifications after the last write
+    pub drain_timeout: Duration,
+}
+
+impl Default for LoadConfig {
+    fn default() -> Self {
+        Self {
+            rate_per_sec: 200,
+            duration: Duration::from_secs(10),
+            teams: vec![Team::Red, Team::Blue, Team::Green],
+            subscribers_per_team: 1,
+            payload_bytes: 256,
+            channel_capacity: 1024,
+            subscriber_delay: None,
+            drain_timeout: Duration::from_secs(2),
+        }
+    }
+}
+
+/// Percentiles of a set of latency samples, in microseconds
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LatencySummary {
+    pub samples: usize,
+    pub mean_us: u64,
+    pub p50_us: u64,
+    pub p95_us: u64,
+    pub p99_us: u64,
+    pub max_us: u64,
+}
+
+impl LatencySummary {
+    pub fn from_micros(mut samples: Vec<u64>) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        samples.sort_unstable();
+        let at = |q: f64| samples[((samples.len() - 1) as f64 * q).round() as usize];
+        Self {
+            samples: samples.len(),
+            mean_us: samples.iter().sum::<u64>() / samples.len() as u64,
+            p50_us: at(0.50),
+            p95_us: at(0.95),
+            p99_us: at(0.99),
+            max_us: *samples.last().unwrap_or(&0),
+        }
+    }
+}
+
+/// Results of one load run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoadReport {
+    pub run_id: Uuid,
+    pub started_at: DateTime<Utc>,
+    pub config: LoadConfig,
+    /// Messages written successfully
+    pub sent: u64,
+    pub write_errors: u64,
+    /// Achieved SurrealDB writes per second
+    pub write_throughput: f64,
+    pub write_latency: LatencySummary,
+    /// `sent` times the subscribers of each message's team
+    pub expected_deliveries: u64,
+    pub delivered: u64,
+    pub delivery_latency: LatencySummary,
+    /// Messages subscribers skipped because they fell behind the buffer
+    pub lagged: u64,
+    /// Messages that never arrived within the drain timeout
+    pub lost: u64,
+    /// Watcher counters accumulated during the run
+    pub watcher: MomStats,
+}
+
+impl LoadReport {
+    /// Share of expected deliveries that arrived
+    pub fn delivery_ratio(&self) -> f64 {
+        if self.expected_deliveries == 0 {
+            return 1.0;
+        }
+        self.delivered as f64 / self.expected_deliveries as f64
+    }
+
+    /// One-line summary for logs and bench output
+    pub fn summary(&self) -> String {
+        format!(
+            "{}/s target: {:.0} writes/s, write p99 {}us, delivery p50/p99 {}/{}us, {:.1}% delivered ({} lagged, {} lost, {} write errors)",
+            self.config.rate_per_sec,
+            self.write_throughput,
+            self.write_latency.p99_us,
+            self.delivery_latency.p50_us,
+            self.delivery_latency.p99_us,
+            self.delivery_ratio() * 100.0,
+            self.lagged,
+            self.lost,
+            self.write_errors,
+        )
+    }
+}
+
+#[derive(Default)]
+struct Delivery {
+    delivered: AtomicU64,
+    lagged: AtomicU64,
+    latencies: Mutex<Vec<u64>>,
+}
+
+/// Drives a load run against a database and the watcher routing it
+pub struct LoadGenerator<C: Connection> {
+    db: Surreal<C>,
+    watcher: Arc<MOMWatcher>,
+}
+
+impl LoadGenerator<Any> {
+    /// Fresh in-memory database with its own watcher
+    pub async fn in_memory(channel_capacity: usize) -> Result<Self> {
+        let db = connect("mem://").await?;
+        db.use_ns("zed42").use_db("mom_load").await?;
+        let watcher = MOMWatcher::new("mem://".to_string(), "zed42".to_string(), "mom_load".to_string())
+            .with_channel_capacity(channel_capacity);
+        Ok(Self::new(db, Arc::new(watcher)))
+    }
+}
+
+impl<C: Connection> LoadGenerator<C> {
+    pub fn new(db: Surreal<C>, watcher: Arc<MOMWatcher>) -> Self {
+        Self { db, watcher }
+    }
+
+    /// Run one load test
+    ///
+    /// Writes go to the `blackboard` table of `db`; don't point this at a
+    /// database real agents are subscribed to.
+    pub async fn run(&self, config: &LoadConfig) -> Result<LoadReport> {
+        anyhow::ensure!(config.rate_per_sec > 0, "Load rate must be positive");
+        anyhow::ensure!(!config.teams.is_empty(), "Load test needs at least one team");
+
+        let run_id = Uuid::new_v4();
+        let started_at = Utc::now();
+        let stats_before = self.watcher.stats();
+        let sent_at: Arc<DashMap<Uuid, Instant>> = Arc::new(DashMap::new());
+        let delivery = Arc::new(Delivery::default());
+        let stop = CancellationToken::new();
+
+        let mut subscribers = Vec::new();
+        for team in &config.teams {
+            for _ in 0..config.subscribers_per_team {
+                let mut rx = self.watcher.subscribe(*team);
+                let (sent_at, delivery, stop) = (sent_at.clone(), delivery.clone(), stop.clone());
+                let delay = config.subscriber_delay;
+                subscribers.push(tokio::spawn(async move {
+                    loop {
+                        let received = tokio::select! {
+                            _ = stop.cancelled() => break,
+                            received = rx.recv() => received,
+                        };
+                        match received {
+                            Ok(msg) => {
+                                if let Some(start) = sent_at.get(&msg.correlation_id) {
+                                    delivery.latencies.lock().push(start.elapsed().as_micros() as u64);
+                                }
+                                delivery.delivered.fetch_add(1, Ordering::Relaxed);
+                                if let Some(delay) = delay {
+                                    tokio::time::sleep(delay).await;
+                                }
+                            }
+                            Err(RecvError::Lagged(skipped)) => {
+                                delivery.lagged.fetch_add(skipped, Ordering::Relaxed);
+                            }
+                            Err(RecvError::Closed) => break,
+                        }
+                    }
+                }));
+            }
+        }
+
+        let watcher = self.watcher.clone();
+        let db = self.db.clone();
+        let watcher_task = tokio::spawn(async move { watcher.run_on(&db).await });
+        // Give the LIVE SELECT time to register before the first write
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        let payload = "x".repeat(config.payload_bytes);
+        let mut ticker = tokio::time::interval(Duration::from_secs(1) / config.rate_per_sec);
+        let mut write_latencies = Vec::new();
+        let (mut sent, mut write_errors) = (0u64, 0u64);
+        let write_start = Instant::now();
+        let mut index = 0usize;
+        while write_start.elapsed() < config.duration {
+            ticker.tick().await;
+            let team = config.teams[index % config.teams.len()];
+            index += 1;
+            let message = VoxMessage {
+                sender: surrealdb::sql::Thing::from(("agent", "load_generator")),
+                target_team: format!("{:?}", team),
+                priority: 1,
+                correlation_id: Uuid::new_v4(),
+                payload: VoxPayload::Observation { content: payload.clone() },
+                created_at: Utc::now(),
+                ttl_secs: None,
+                idempotency_key: None,
+            };
+            let start = Instant::now();
+            sent_at.insert(message.correlation_id, start);
+            let result: surrealdb::Result<Option<VoxMessage>> =
+                self.db.create("blackboard").content(message.clone()).await;
+            match result {
+                Ok(_) => {
+                    sent += 1;
+                    write_latencies.push(start.elapsed().as_micros() as u64);
+                }
+                Err(e) => {
+                    write_errors += 1;
+                    sent_at.remove(&message.correlation_id);
+                    tracing::debug!(error = %e, "Load test write failed");
+                }
+            }
+        }
+        let write_elapsed = write_start.elapsed();
+
+        let expected = sent * config.subscribers_per_team as u64;
+        let drain_deadline = Instant::now() + config.drain_timeout;
+        while Instant::now() < drain_deadline
+            && delivery.delivered.load(Ordering::Relaxed) + delivery.lagged.load(Ordering::Relaxed) < expected
+        {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        stop.cancel();
+        for subscriber in subscribers {
+            let _ = subscriber.await;
+        }
+        watcher_task.abort();
+        // Leave the table empty for the next run
+        if let Err(e) = self.db.query("DELETE blackboard").await {
+            tracing::warn!(error = %e, "Failed to clear load test messages");
+        }
+
+        let delivered = delivery.delivered.load(Ordering::Relaxed);
+        let lagged = delivery.lagged.load(Ordering::Relaxed);
+        let stats_after = self.watcher.stats();
+        let latencies = std::mem::take(&mut *delivery.latencies.lock());
+
+        Ok(LoadReport {
+            run_id,
+            started_at,
+            config: config.clone(),
+            sent,
+            write_errors,
+            write_throughput: sent as f64 / write_elapsed.as_secs_f64().max(f64::EPSILON),
+            write_latency: LatencySummary::from_micros(write_latencies),
+            expected_deliveries: expected,
+            delivered,
+            delivery_latency: LatencySummary::from_micros(latencies),
+            lagged,
+            lost: expected.saturating_sub(delivered + lagged),
+            watcher: MomStats {
+                delivered: stats_after.delivered - stats_before.delivered,
+                unrouted: stats_after.unrouted - stats_before.unrouted,
+                decode_errors: stats_after.decode_errors - stats_before.decode_errors,
+                priority_delivered: stats_after.priority_delivered - stats_before.priority_delivered,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latency_summary() {
+        let summary = LatencySummary::from_micros((1..=100).collect());
+        assert_eq!(summary.samples, 100);
+        assert_eq!(summary.p50_us, 51);
+        assert_eq!(summary.p99_us, 99);
+        assert_eq!(summary.max_us, 100);
+        assert_eq!(LatencySummary::from_micros(Vec::new()), LatencySummary::default());
+    }
+
+    #[tokio::test]
+    async fn test_short_run_accounts_for_every_message() {
+        let config = LoadConfig {
+            rate_per_sec: 100,
+            duration: Duration::from_millis(300),
+            teams: vec![Team::Blue],
+            channel_capacity: 64,
+            drain_timeout: Duration::from_millis(500),
+            ..Default::default()
+        };
+        let generator = LoadGenerator::in_memory(config.channel_capacity).await.unwrap();
+        let report = generator.run(&config).await.unwrap();
+
+        assert!(report.sent > 0);
+        assert_eq!(report.write_errors, 0);
+        assert_eq!(report.write_latency.samples as u64, report.sent);
+        assert_eq!(report.delivered + report.lagged + report.lost, report.expected_deliveries);
+        assert_eq!(report.delivery_latency.samples as u64, report.delivered);
+    }
+}
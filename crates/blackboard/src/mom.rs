@@ -2,30 +2,72 @@
 //!
 //! Maintains a resilient WebSocket connection to SurrealDB and
 //! routes LIVE SELECT notifications to agents via MOM broadcast channels.
+//!
+//! Each subscriber has two lanes. Ordinary traffic shares a bounded
+//! broadcast channel per team, where a lagging subscriber loses the oldest
+//! messages. Messages at or above [`PRIORITY_LANE_THRESHOLD`] (AURA
+//! SystemAlerts and the like) go through a per-subscriber queue instead, so
+//! they are never dropped and are received ahead of ordinary traffic.
 
+use crate::codec;
 use crate::types::VoxMessage;
 use surrealdb::Action;
 use surrealdb::engine::remote::ws::Ws;
-use surrealdb::Surreal;
-use tokio::sync::broadcast;
+use surrealdb::{Connection, Surreal};
+use tokio::sync::{broadcast, mpsc};
 use tokio::time::{sleep, Duration};
 use tracing::{warn, info, error};
+use zed42_core::vox::{VoxReceiver, PRIORITY_LANE_THRESHOLD};
 use zed42_core::Team;
 use futures_util::StreamExt;
 use anyhow::{Result, Context};
 use dashmap::DashMap;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
+/// Default per-team broadcast buffer
+const DEFAULT_CHANNEL_CAPACITY: usize = 1024;
+
+/// Delivery counters since the watcher was created
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct MomStats {
+    /// Notifications broadcast to at least one subscriber
+    pub delivered: u64,
+    /// Notifications for a team nobody subscribed to
+    pub unrouted: u64,
+    /// Notifications whose data wasn't a valid (or decompressible) VOX message
+    pub decode_errors: u64,
+    /// Of `delivered`, messages that went through the priority lane
+    pub priority_delivered: u64,
+    /// Messages past their TTL, archived instead of delivered
+    pub expired: u64,
+}
+
+#[derive(Default)]
+struct Counters {
+    delivered: AtomicU64,
+    unrouted: AtomicU64,
+    decode_errors: AtomicU64,
+    priority_delivered: AtomicU64,
+    expired: AtomicU64,
+}
+
 /// Authoritative source for real-time coordination via the MOM Reactive Substrate
 pub struct MOMWatcher {
     /// Team-based broadcast channels
     senders: Arc<DashMap<String, broadcast::Sender<VoxMessage>>>,
+    /// Per-subscriber priority queues, by team
+    priority_lanes: Arc<DashMap<String, Vec<mpsc::UnboundedSender<VoxMessage>>>>,
     /// Connection address
     addr: String,
     /// Namespace
     ns: String,
     /// Database name
     db: String,
+    /// Buffer size of each team channel; slower subscribers lag past it
+    channel_capacity: usize,
+    counters: Arc<Counters>,
 }
 
 impl MOMWatcher {
@@ -33,38 +75,106 @@ impl MOMWatcher {
     pub fn new(addr: String, ns: String, db: String) -> Self {
         Self {
             senders: Arc::new(DashMap::new()),
+            priority_lanes: Arc::new(DashMap::new()),
             addr,
             ns,
             db,
+            channel_capacity: DEFAULT_CHANNEL_CAPACITY,
+            counters: Arc::new(Counters::default()),
+        }
+    }
+
+    /// Set the per-team buffer size (applies to channels created afterwards)
+    pub fn with_channel_capacity(mut self, capacity: usize) -> Self {
+        self.channel_capacity = capacity.max(1);
+        self
+    }
+
+    pub fn stats(&self) -> MomStats {
+        MomStats {
+            delivered: self.counters.delivered.load(Ordering::Relaxed),
+            unrouted: self.counters.unrouted.load(Ordering::Relaxed),
+            decode_errors: self.counters.decode_errors.load(Ordering::Relaxed),
+            priority_delivered: self.counters.priority_delivered.load(Ordering::Relaxed),
+            expired: self.counters.expired.load(Ordering::Relaxed),
         }
     }
 
     /// Subscribe to VOX messages for a specific team via MOM
-    pub fn subscribe(&self, team: Team) -> broadcast::Receiver<VoxMessage> {
+    pub fn subscribe(&self, team: Team) -> VoxReceiver {
         let team_key = format!("{:?}", team).to_lowercase();
         self.subscribe_raw(team_key)
     }
 
     /// Internal subscription by team string (supports "all")
-    fn subscribe_raw(&self, team_key: String) -> broadcast::Receiver<VoxMessage> {
-        let tx = self.senders.entry(team_key).or_insert_with(|| {
-            let (tx, _) = broadcast::channel(1024);
+    fn subscribe_raw(&self, team_key: String) -> VoxReceiver {
+        let capacity = self.channel_capacity;
+        let normal = self.senders.entry(team_key.clone()).or_insert_with(|| {
+            let (tx, _) = broadcast::channel(capacity);
             tx
-        });
-        tx.subscribe()
+        }).subscribe();
+        let (lane, priority) = mpsc::unbounded_channel();
+        self.priority_lanes.entry(team_key).or_default().push(lane);
+        VoxReceiver::new(priority, normal)
     }
 
     /// Broadcast a system message immediately via MOM (e.g., AURA pulse alerts)
+    ///
+    /// Messages past their TTL are dropped; they were never stored, so there
+    /// is nothing to archive.
     pub async fn broadcast_system_message(&self, msg: VoxMessage) {
+        if msg.is_expired() {
+            self.counters.expired.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
         let team_key = msg.target_team.to_lowercase();
-        
+
         if team_key == "all" {
             // Send to everyone
-            for entry in self.senders.iter() {
-                let _ = entry.value().send(msg.clone());
+            let teams: Vec<String> = self.senders.iter().map(|entry| entry.key().clone()).collect();
+            for team in teams {
+                self.route(&team, msg.clone());
             }
-        } else if let Some(tx) = self.senders.get(&team_key) {
-            let _ = tx.send(msg);
+        } else {
+            self.route(&team_key, msg);
+        }
+    }
+
+    /// Hand a message to one team's subscribers, returning whether anyone got it
+    fn route(&self, team_key: &str, msg: VoxMessage) -> bool {
+        if msg.priority >= PRIORITY_LANE_THRESHOLD {
+            let Some(mut lanes) = self.priority_lanes.get_mut(team_key) else {
+                return false;
+            };
+            // Sending fails only once the receiver is gone; forget those lanes
+            lanes.retain(|lane| lane.send(msg.clone()).is_ok());
+            if !lanes.is_empty() {
+                self.counters.priority_delivered.fetch_add(1, Ordering::Relaxed);
+                return true;
+            }
+            return false;
+        }
+
+        match self.senders.get(team_key) {
+            // Lagging subscribers lose the oldest messages (RecvError::Lagged)
+            Some(tx) => match tx.send(msg) {
+                Ok(_) => true,
+                Err(e) => {
+                    warn!("MOM Substrate: broadcast drop/error for team {}: {}", team_key, e);
+                    false
+                }
+            },
+            None => false,
+        }
+    }
+
+    /// Run the watcher until `shutdown` is cancelled
+    pub async fn run_until(&self, shutdown: zed42_core::CancellationToken) {
+        tokio::select! {
+            _ = shutdown.cancelled() => {
+                info!("MOM Substrate: shutdown requested, closing LIVE SELECT");
+            }
+            _ = self.run_loop() => {}
         }
     }
 
@@ -83,6 +193,7 @@ impl MOMWatcher {
                     backoff = (backoff * 2).min(Duration::from_secs(60));
                 }
             }
+            zed42_core::chaos::delay_point(zed42_core::chaos::FaultPoint::WsReconnect).await;
         }
     }
 
@@ -95,7 +206,14 @@ impl MOMWatcher {
             .context("Failed to select MOM namespace/database")?;
 
         info!("MOM Substrate: connected to {}/{}, starting LIVE SELECT...", self.ns, self.db);
+        self.run_on(&db).await
+    }
 
+    /// Route LIVE SELECT notifications from an already connected database
+    ///
+    /// Returns when the stream ends. `run` uses this over its WebSocket
+    /// connection; load tests and embedded setups pass their own database.
+    pub async fn run_on<C: Connection>(&self, db: &Surreal<C>) -> Result<()> {
         // Native MOM Reactive Substrate LIVE SELECT
         let mut stream = db.select("blackboard").live().await
             .context("Failed to start MOM LIVE SELECT on blackboard")?;
@@ -108,21 +226,30 @@ impl MOMWatcher {
                 Ok(notification) => {
                     match notification.action {
                         Action::Create | Action::Update => {
-                            let msg: VoxMessage = match serde_json::from_value(notification.data) {
+                            let msg: VoxMessage = match serde_json::from_value(notification.data)
+                                .map_err(anyhow::Error::from)
+                                .and_then(codec::decode)
+                            {
                                 Ok(m) => m,
                                 Err(e) => {
-                                    warn!("MOM Substrate: failed to deserialize VOX notification data: {}", e);
+                                    warn!("MOM Substrate: failed to decode VOX notification data: {}", e);
+                                    self.counters.decode_errors.fetch_add(1, Ordering::Relaxed);
                                     continue;
                                 }
                             };
 
-                            let team_key = msg.target_team.to_lowercase();
-                            if let Some(tx) = self.senders.get(&team_key) {
-                                // If an agent lags, drop the message and warn
-                                if let Err(e) = tx.send(msg) {
-                                    warn!("MOM Substrate: broadcast drop/error for team {}: {}", team_key, e);
+                            if msg.is_expired() {
+                                self.counters.expired.fetch_add(1, Ordering::Relaxed);
+                                if let Err(e) = archive_expired(db, &msg).await {
+                                    warn!("MOM Substrate: failed to archive expired VOX message: {}", e);
                                 }
+                                continue;
                             }
+
+                            let team_key = msg.target_team.to_lowercase();
+                            let sent = self.route(&team_key, msg);
+                            let counter = if sent { &self.counters.delivered } else { &self.counters.unrouted };
+                            counter.fetch_add(1, Ordering::Relaxed);
                         }
                         _ => {
                             // Ignored actions (Delete, etc.)
@@ -139,3 +266,114 @@ impl MOMWatcher {
         Ok(())
     }
 }
+
+/// Table holding VOX messages that expired before delivery
+pub const VOX_ARCHIVE_TABLE: &str = "vox_archive";
+
+/// Move an expired message from the bus to the archive
+async fn archive_expired<C: Connection>(db: &Surreal<C>, msg: &VoxMessage) -> Result<()> {
+    db.query(
+        "CREATE vox_archive CONTENT { message: $message, reason: 'expired', archived_at: time::now() };
+         DELETE blackboard WHERE correlation_id = $correlation AND created_at = $created;",
+    )
+    .bind(("message", msg.clone()))
+    .bind(("correlation", msg.correlation_id))
+    .bind(("created", msg.created_at))
+    .await
+    .context("Failed to archive expired VOX message")?
+    .check()
+    .context("Failed to archive expired VOX message")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::broadcast::error::RecvError;
+    use zed42_core::vox::VoxPayload;
+
+    fn message(priority: u8, content: &str) -> VoxMessage {
+        VoxMessage {
+            sender: surrealdb::sql::Thing::from(("agent", "test")),
+            target_team: "blue".to_string(),
+            priority,
+            correlation_id: uuid::Uuid::new_v4(),
+            payload: VoxPayload::Observation { content: content.to_string() },
+            created_at: chrono::Utc::now(),
+            ttl_secs: None,
+            idempotency_key: None,
+        }
+    }
+
+    fn content(msg: &VoxMessage) -> &str {
+        match &msg.payload {
+            VoxPayload::Observation { content } => content,
+            _ => "",
+        }
+    }
+
+    #[tokio::test]
+    async fn test_priority_lane_survives_lagging_subscriber() {
+        let watcher = MOMWatcher::new(String::new(), String::new(), String::new()).with_channel_capacity(2);
+        let mut rx = watcher.subscribe(Team::Blue);
+
+        watcher.broadcast_system_message(message(1, "first")).await;
+        watcher.broadcast_system_message(message(PRIORITY_LANE_THRESHOLD, "alert")).await;
+        for i in 0..5 {
+            watcher.broadcast_system_message(message(1, &format!("filler {}", i))).await;
+        }
+
+        // The alert jumps the queue even though ordinary traffic overflowed
+        assert_eq!(content(&rx.recv().await.unwrap()), "alert");
+        assert!(matches!(rx.recv().await, Err(RecvError::Lagged(4))));
+        assert_eq!(content(&rx.recv().await.unwrap()), "filler 3");
+        assert_eq!(watcher.stats().priority_delivered, 1);
+    }
+
+    #[tokio::test]
+    async fn test_priority_lane_drops_closed_subscribers() {
+        let watcher = MOMWatcher::new(String::new(), String::new(), String::new());
+        let rx = watcher.subscribe(Team::Blue);
+        let mut kept = watcher.subscribe(Team::Blue);
+        drop(rx);
+
+        watcher.broadcast_system_message(message(255, "alert")).await;
+        assert_eq!(content(&kept.try_recv().unwrap()), "alert");
+        assert_eq!(watcher.priority_lanes.get("blue").unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_expired_messages_are_not_delivered() {
+        let watcher = MOMWatcher::new(String::new(), String::new(), String::new());
+        let mut rx = watcher.subscribe(Team::Blue);
+
+        let mut stale = message(PRIORITY_LANE_THRESHOLD, "stale").with_ttl(Duration::from_secs(30));
+        stale.created_at -= chrono::Duration::seconds(60);
+        assert!(stale.is_expired());
+        watcher.broadcast_system_message(stale).await;
+        watcher
+            .broadcast_system_message(message(1, "fresh").with_ttl(Duration::from_secs(30)))
+            .await;
+
+        assert_eq!(content(&rx.recv().await.unwrap()), "fresh");
+        assert_eq!(watcher.stats().expired, 1);
+        assert_eq!(watcher.stats().priority_delivered, 0);
+    }
+
+    #[tokio::test]
+    async fn test_replay_precedes_live_and_skips_live_copies() {
+        let watcher = MOMWatcher::new(String::new(), String::new(), String::new());
+        let (mut older, newer) = (message(1, "older"), message(1, "newer"));
+        older.created_at -= chrono::Duration::seconds(1);
+        let mut rx = watcher.subscribe(Team::Blue).with_replay(vec![newer.clone(), older.clone()]);
+
+        // Posted while the history was being read: arrives live as well
+        watcher.broadcast_system_message(newer).await;
+        watcher.broadcast_system_message(message(1, "live")).await;
+
+        assert_eq!(content(&rx.recv().await.unwrap()), "older");
+        assert_eq!(content(&rx.recv().await.unwrap()), "newer");
+        assert_eq!(content(&rx.recv().await.unwrap()), "live");
+        assert!(rx.try_recv().is_err());
+    }
+}
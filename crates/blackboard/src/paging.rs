@@ -0,0 +1,89 @@
+//! Paged reads of message and decision history
+//!
+//! `get_messages` and `get_decisions` return every match, which the UI and
+//! API server cannot afford on a long-running project. The paged variants
+//! return newest first, at most one page at a time, with a cursor for the
+//! next page (see [`zed42_core::page`]).
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use zed42_core::page::{Page, PageRequest, Position};
+use zed42_core::{AgentId, Message};
+
+use crate::database::message_conditions;
+use crate::{BlackboardDb, DecisionNode, MessageFilter};
+
+impl BlackboardDb {
+    /// One page of the messages matching `filter`, newest first
+    ///
+    /// `filter.limit` is ignored in favour of the page size. Expired messages
+    /// are left out of the page; `get_messages` archives them.
+    pub async fn get_messages_page(&self, filter: MessageFilter, page: PageRequest) -> Result<Page<Message>> {
+        zed42_core::chaos::fail_point(zed42_core::chaos::FaultPoint::Database)?;
+        let limit = page.limit();
+        let position: Option<Position<DateTime<Utc>>> = page.position()?;
+
+        let mut conditions = message_conditions(&filter);
+        if position.is_some() {
+            conditions.push("timestamp <= $key".to_string());
+        }
+        let query = format!(
+            "SELECT * FROM messages {} ORDER BY timestamp DESC, id DESC LIMIT $limit START $ties",
+            where_clause(&conditions)
+        );
+        let mut response = self
+            .db()
+            .query(query)
+            .bind(("tenant", filter.tenant_id.clone()))
+            .bind(("key", position.as_ref().map(|p| p.key)))
+            .bind(("ties", position.as_ref().map_or(0, |p| p.ties)))
+            .bind(("limit", limit + 1))
+            .await
+            .context("Failed to query message page")?;
+        let rows: Vec<Message> = response.take(0)?;
+
+        let mut page = Page::from_rows(rows, limit, position.as_ref(), |m| m.timestamp);
+        let now = Utc::now();
+        page.items.retain(|m| !m.is_expired_at(now));
+        Ok(page)
+    }
+
+    /// One page of decision history, newest first
+    pub async fn get_decisions_page(&self, agent_id: Option<AgentId>, page: PageRequest) -> Result<Page<DecisionNode>> {
+        zed42_core::chaos::fail_point(zed42_core::chaos::FaultPoint::Database)?;
+        let limit = page.limit();
+        let position: Option<Position<i64>> = page.position()?;
+
+        let mut conditions = Vec::new();
+        if agent_id.is_some() {
+            conditions.push("made_by = $agent".to_string());
+        }
+        if position.is_some() {
+            conditions.push("timestamp <= $key".to_string());
+        }
+        let query = format!(
+            "SELECT * FROM decisions {} ORDER BY timestamp DESC, id DESC LIMIT $limit START $ties",
+            where_clause(&conditions)
+        );
+        let mut response = self
+            .db()
+            .query(query)
+            .bind(("agent", agent_id.map(|a| a.to_string())))
+            .bind(("key", position.as_ref().map(|p| p.key)))
+            .bind(("ties", position.as_ref().map_or(0, |p| p.ties)))
+            .bind(("limit", limit + 1))
+            .await
+            .context("Failed to query decision page")?;
+        let rows: Vec<DecisionNode> = response.take(0)?;
+
+        Ok(Page::from_rows(rows, limit, position.as_ref(), |d| d.timestamp))
+    }
+}
+
+fn where_clause(conditions: &[String]) -> String {
+    if conditions.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", conditions.join(" AND "))
+    }
+}
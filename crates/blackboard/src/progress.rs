@@ -0,0 +1,161 @@
+//! Intra-task progress and stall detection
+//!
+//! An agent several minutes into a task pulses exactly like a hung one. While
+//! it works, an agent reports [`TaskProgress`]: the step it is on, how far
+//! along it is and the last tool it ran. Each report doubles as a heartbeat;
+//! only a change of step, percent or tool counts as progress. An agent that
+//! keeps reporting without moving is *stalled* — alive by its pulse, so not a
+//! ghost, but stuck. AURA publishes every agent's progress as vitals and
+//! alerts on stalls.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use zed42_core::types::{AgentId, TaskId};
+
+use crate::BlackboardDb;
+
+/// Latest progress of the task an agent is working on
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskProgress {
+    pub agent_id: AgentId,
+    pub task_id: TaskId,
+    /// What the agent is doing, e.g. `running tests`
+    pub step: String,
+    /// Share of the task done, 0-100, when the agent can tell
+    #[serde(default)]
+    pub percent: Option<u8>,
+    /// Tool the agent ran most recently
+    #[serde(default)]
+    pub last_tool: Option<String>,
+    /// Last report, progress or not
+    pub reported_at: DateTime<Utc>,
+    /// Last report that moved the step, percent or tool
+    pub changed_at: DateTime<Utc>,
+    /// AURA has alerted that this task stopped moving
+    #[serde(default)]
+    pub stalled: bool,
+}
+
+impl TaskProgress {
+    pub fn new(agent_id: AgentId, task_id: impl Into<TaskId>, step: impl Into<String>) -> Self {
+        let now = Utc::now();
+        Self {
+            agent_id,
+            task_id: task_id.into(),
+            step: step.into(),
+            percent: None,
+            last_tool: None,
+            reported_at: now,
+            changed_at: now,
+            stalled: false,
+        }
+    }
+
+    /// Share of the task done, capped at 100
+    pub fn with_percent(mut self, percent: u8) -> Self {
+        self.percent = Some(percent.min(100));
+        self
+    }
+
+    pub fn with_tool(mut self, tool: impl Into<String>) -> Self {
+        self.last_tool = Some(tool.into());
+        self
+    }
+
+    /// How long the task has gone without progress at `now`
+    pub fn idle_for(&self, now: DateTime<Utc>) -> Duration {
+        now - self.changed_at
+    }
+
+    fn same_position(&self, other: &TaskProgress) -> bool {
+        self.task_id == other.task_id
+            && self.step == other.step
+            && self.percent == other.percent
+            && self.last_tool == other.last_tool
+    }
+}
+
+impl BlackboardDb {
+    /// Record an agent's progress; a report that doesn't move keeps `changed_at`
+    pub async fn report_progress(&self, mut progress: TaskProgress) -> Result<TaskProgress> {
+        zed42_core::chaos::fail_point(zed42_core::chaos::FaultPoint::Database)?;
+        let now = Utc::now();
+        progress.reported_at = now;
+        match self.get_progress(progress.agent_id).await? {
+            Some(previous) if previous.same_position(&progress) => {
+                progress.changed_at = previous.changed_at;
+                progress.stalled = previous.stalled;
+            }
+            _ => {
+                progress.changed_at = now;
+                progress.stalled = false;
+            }
+        }
+        self.db()
+            .query("UPSERT type::thing('task_progress', $agent) CONTENT $progress")
+            .bind(("agent", progress.agent_id.to_string()))
+            .bind(("progress", progress.clone()))
+            .await
+            .and_then(|response| response.check())
+            .context("Failed to record task progress")?;
+        Ok(progress)
+    }
+
+    /// Latest progress of the task `agent_id` is working on
+    pub async fn get_progress(&self, agent_id: AgentId) -> Result<Option<TaskProgress>> {
+        let mut response = self
+            .db()
+            .query("SELECT * FROM type::thing('task_progress', $agent)")
+            .bind(("agent", agent_id.to_string()))
+            .await
+            .context("Failed to query task progress")?;
+        let rows: Vec<TaskProgress> = response.take(0)?;
+        Ok(rows.into_iter().next())
+    }
+
+    /// Progress of every agent with a task in flight
+    pub async fn list_progress(&self) -> Result<Vec<TaskProgress>> {
+        let mut response = self
+            .db()
+            .query("SELECT * FROM task_progress")
+            .await
+            .context("Failed to list task progress")?;
+        Ok(response.take(0)?)
+    }
+
+    /// Forget an agent's progress once its task is done
+    pub async fn clear_progress(&self, agent_id: AgentId) -> Result<()> {
+        self.db()
+            .query("DELETE type::thing('task_progress', $agent)")
+            .bind(("agent", agent_id.to_string()))
+            .await
+            .and_then(|response| response.check())
+            .context("Failed to clear task progress")?;
+        Ok(())
+    }
+
+    /// Tasks without progress for longer than `threshold`
+    pub async fn stalled_tasks(&self, threshold: Duration) -> Result<Vec<TaskProgress>> {
+        let now = Utc::now();
+        let mut stalled: Vec<TaskProgress> = self
+            .list_progress()
+            .await?
+            .into_iter()
+            .filter(|progress| progress.idle_for(now) > threshold)
+            .collect();
+        stalled.sort_by_key(|progress| progress.changed_at);
+        Ok(stalled)
+    }
+
+    /// Mark a task as alerted on, so AURA raises each stall once
+    pub(crate) async fn mark_stalled(&self, agent_id: AgentId) -> Result<()> {
+        self.db()
+            .query("UPDATE type::thing('task_progress', $agent) SET stalled = true")
+            .bind(("agent", agent_id.to_string()))
+            .await
+            .and_then(|response| response.check())
+            .context("Failed to mark task stalled")?;
+        Ok(())
+    }
+}
@@ -62,6 +62,11 @@ impl StateResolver {
                 zed42_core::vox::VoxPayload::Ack { result } => {
                     values.insert("ack_result".to_string(), Value::String(result.clone()));
                 }
+                zed42_core::vox::VoxPayload::ArtifactRef { artifact_id, .. } => {
+                    values.insert("artifact_id".to_string(), Value::String(artifact_id.clone()));
+                }
+                // Decoded by the MOM watcher before it reaches consensus
+                zed42_core::vox::VoxPayload::Compressed { .. } => {}
             }
             if msg.created_at > last_updated {
                 last_updated = msg.created_at;
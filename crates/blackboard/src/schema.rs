@@ -0,0 +1,233 @@
+//! Write-time validation of blackboard messages
+//!
+//! `Message` is typed, but nothing stops an agent from posting a task with
+//! an empty description or a verdict without the id it refers to; such
+//! messages only fail once another agent reads them. The [`SchemaRegistry`]
+//! holds a [`MessageSchema`] per VOX message type and checks every message
+//! before it is written. In [`SchemaMode::Strict`] a violation is returned
+//! to the posting agent; [`SchemaMode::Lenient`] logs it and posts anyway,
+//! for migrating producers that don't conform yet.
+
+use serde_json::Value;
+use std::collections::HashMap;
+use thiserror::Error;
+use zed42_core::{AgentId, Message, MessageTarget};
+
+/// Largest text field accepted inline; bigger content belongs in an artifact
+pub const DEFAULT_MAX_TEXT_BYTES: usize = 64 * 1024;
+
+/// What `post_message` does with a message that fails validation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SchemaMode {
+    /// Reject the message
+    #[default]
+    Strict,
+    /// Log the violation and post the message anyway
+    Lenient,
+}
+
+/// Shape of one field of a message type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldRule {
+    /// Non-empty text
+    Text(&'static str),
+    /// Non-empty identifier without whitespace
+    Id(&'static str),
+    /// List of non-empty strings (the list itself may be empty)
+    List(&'static str),
+}
+
+impl FieldRule {
+    fn name(&self) -> &'static str {
+        match self {
+            FieldRule::Text(name) | FieldRule::Id(name) | FieldRule::List(name) => name,
+        }
+    }
+}
+
+/// Fields a message type must carry
+#[derive(Debug, Clone)]
+pub struct MessageSchema {
+    /// `type` tag of the message (e.g. `execute_task`)
+    pub message_type: String,
+    pub fields: Vec<FieldRule>,
+}
+
+impl MessageSchema {
+    pub fn new(message_type: impl Into<String>, fields: Vec<FieldRule>) -> Self {
+        Self { message_type: message_type.into(), fields }
+    }
+}
+
+/// Why a message was refused
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("Invalid {message_type} message from agent {from_agent}: {}", problems.join("; "))]
+pub struct SchemaViolation {
+    pub message_type: String,
+    pub from_agent: AgentId,
+    pub problems: Vec<String>,
+}
+
+/// Schemas of the VOX message types, by `type` tag
+#[derive(Debug, Clone)]
+pub struct SchemaRegistry {
+    schemas: HashMap<String, MessageSchema>,
+    pub max_text_bytes: usize,
+}
+
+impl Default for SchemaRegistry {
+    fn default() -> Self {
+        Self::vox()
+    }
+}
+
+impl SchemaRegistry {
+    /// A registry without any schemas; every message type is unknown
+    pub fn empty() -> Self {
+        Self { schemas: HashMap::new(), max_text_bytes: DEFAULT_MAX_TEXT_BYTES }
+    }
+
+    /// Schemas for every built-in `MessageType`
+    pub fn vox() -> Self {
+        use FieldRule::{Id, List, Text};
+
+        let mut registry = Self::empty();
+        for schema in [
+            MessageSchema::new("execute_task", vec![Text("task_description")]),
+            MessageSchema::new("spawn_agent", vec![Id("agent_type"), List("toolbox")]),
+            MessageSchema::new("dissolve_agent", vec![Id("agent_id")]),
+            MessageSchema::new("request_context", vec![Text("query")]),
+            MessageSchema::new("query_knowledge_graph", vec![Text("query")]),
+            MessageSchema::new("get_constraints", vec![Text("scope")]),
+            MessageSchema::new("propose_solution", vec![Text("solution")]),
+            MessageSchema::new("suggest_refactor", vec![Text("refactor_plan")]),
+            MessageSchema::new("identify_risk", vec![Text("risk_description")]),
+            MessageSchema::new("approve_change", vec![Id("change_id"), Text("rationale")]),
+            MessageSchema::new("reject_proposal", vec![Id("proposal_id"), Text("reason")]),
+            MessageSchema::new("request_revision", vec![Id("target_id"), Text("requested_changes")]),
+            MessageSchema::new("task_complete", vec![Id("task_id"), Text("result")]),
+            MessageSchema::new("error_occurred", vec![Text("error"), Text("context")]),
+            MessageSchema::new("milestone_reached", vec![Text("milestone")]),
+        ] {
+            registry.register(schema);
+        }
+        registry
+    }
+
+    /// Add or replace the schema of a message type
+    pub fn register(&mut self, schema: MessageSchema) {
+        self.schemas.insert(schema.message_type.clone(), schema);
+    }
+
+    pub fn get(&self, message_type: &str) -> Option<&MessageSchema> {
+        self.schemas.get(message_type)
+    }
+
+    /// Check a message against its type's schema and the envelope rules
+    pub fn validate(&self, message: &Message) -> Result<(), SchemaViolation> {
+        let body = serde_json::to_value(&message.message_type).unwrap_or(Value::Null);
+        let message_type = body.get("type").and_then(Value::as_str).unwrap_or("unknown").to_string();
+        let mut problems = envelope_problems(message);
+
+        match self.schemas.get(&message_type) {
+            Some(schema) => {
+                for rule in &schema.fields {
+                    if let Some(problem) = self.check_field(rule, body.get(rule.name())) {
+                        problems.push(problem);
+                    }
+                }
+            }
+            None => problems.push(format!("no schema registered for message type '{}'", message_type)),
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(SchemaViolation { message_type, from_agent: message.from_agent, problems })
+        }
+    }
+
+    fn check_field(&self, rule: &FieldRule, value: Option<&Value>) -> Option<String> {
+        let name = rule.name();
+        let Some(value) = value else {
+            return Some(format!("'{}' is missing", name));
+        };
+        match rule {
+            FieldRule::Text(_) | FieldRule::Id(_) => {
+                let Some(text) = value.as_str() else {
+                    return Some(format!("'{}' must be a string", name));
+                };
+                if text.trim().is_empty() {
+                    Some(format!("'{}' is empty", name))
+                } else if matches!(rule, FieldRule::Id(_)) && text.chars().any(char::is_whitespace) {
+                    Some(format!("'{}' must be an identifier, got '{}'", name, text))
+                } else if text.len() > self.max_text_bytes {
+                    Some(format!(
+                        "'{}' is {} bytes, over the {} byte limit; store it as an artifact",
+                        name,
+                        text.len(),
+                        self.max_text_bytes
+                    ))
+                } else {
+                    None
+                }
+            }
+            FieldRule::List(_) => {
+                let Some(items) = value.as_array() else {
+                    return Some(format!("'{}' must be a list", name));
+                };
+                items
+                    .iter()
+                    .position(|item| !item.as_str().is_some_and(|text| !text.trim().is_empty()))
+                    .map(|idx| format!("'{}' entry {} is empty", name, idx))
+            }
+        }
+    }
+}
+
+fn envelope_problems(message: &Message) -> Vec<String> {
+    let mut problems = Vec::new();
+    if message.from_agent.is_nil() {
+        problems.push("'from_agent' is the nil id".to_string());
+    }
+    if message.to_team == MessageTarget::Agent(AgentId::nil()) {
+        problems.push("target agent is the nil id".to_string());
+    }
+    if message.ttl_secs == Some(0) {
+        problems.push("'ttl_secs' of 0 expires the message on arrival".to_string());
+    }
+    if message.idempotency_key.as_deref().is_some_and(|k| k.trim().is_empty()) {
+        problems.push("'idempotency_key' is empty".to_string());
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zed42_core::MessageType;
+
+    fn message(message_type: MessageType) -> Message {
+        Message::new(uuid::Uuid::new_v4(), MessageTarget::All, message_type, 1)
+    }
+
+    #[test]
+    fn test_validate_reports_every_problem() {
+        let registry = SchemaRegistry::vox();
+        let ok = message(MessageType::ApproveChange { change_id: "chg-7".into(), rationale: "tests pass".into() });
+        assert!(registry.validate(&ok).is_ok());
+
+        let bad = message(MessageType::ApproveChange { change_id: "chg 7".into(), rationale: "  ".into() })
+            .with_ttl(std::time::Duration::ZERO);
+        let violation = registry.validate(&bad).unwrap_err();
+        assert_eq!(violation.message_type, "approve_change");
+        assert_eq!(violation.problems.len(), 3);
+        assert!(violation.to_string().contains("'rationale' is empty"));
+
+        let spawn = message(MessageType::SpawnAgent { agent_type: "coder".into(), toolbox: vec!["fs".into(), "".into()] });
+        assert_eq!(registry.validate(&spawn).unwrap_err().problems, vec!["'toolbox' entry 1 is empty"]);
+
+        let unknown = SchemaRegistry::empty().validate(&ok).unwrap_err();
+        assert!(unknown.problems[0].contains("no schema registered"));
+    }
+}
@@ -0,0 +1,285 @@
+//! Persistent task queue
+//!
+//! Planned tasks live in the `task_queue` table rather than in one Cortex's
+//! memory, so a restarted Cortex picks up where the last one stopped and
+//! several instances can share the work. A task is *pending* until an agent
+//! claims it, *assigned* while the agent works and *done* or *failed*
+//! afterwards. Claims are compare-and-set updates on the task's status, so
+//! two instances racing for a task never both get it. Each claim counts as
+//! an attempt; a failed attempt puts the task back in the queue until it
+//! runs out of attempts.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use zed42_core::types::{AgentId, SessionId, TaskId};
+
+use crate::BlackboardDb;
+
+/// Attempts a task gets unless it is enqueued with another limit
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+
+/// Where a queued task is in its lifecycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueueStatus {
+    Pending,
+    Assigned,
+    Done,
+    Failed,
+}
+
+/// A planned task and its scheduling state
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueuedTask {
+    pub session_id: SessionId,
+    pub task_id: TaskId,
+    pub description: String,
+    /// Tasks of the same session that must be done first
+    #[serde(default)]
+    pub depends_on: Vec<TaskId>,
+    /// Agent the plan gave the task to; `None` lets any agent claim it
+    #[serde(default)]
+    pub planned_agent: Option<AgentId>,
+    pub status: QueueStatus,
+    /// Agent holding the current or last claim
+    #[serde(default)]
+    pub assigned_to: Option<AgentId>,
+    /// Claims so far; also identifies the current claim
+    pub attempts: u32,
+    pub max_attempts: u32,
+    #[serde(default)]
+    pub last_error: Option<String>,
+    pub enqueued_at: DateTime<Utc>,
+    #[serde(default)]
+    pub claimed_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+impl QueuedTask {
+    pub fn new(session_id: SessionId, task_id: impl Into<TaskId>, description: impl Into<String>) -> Self {
+        Self {
+            session_id,
+            task_id: task_id.into(),
+            description: description.into(),
+            depends_on: Vec::new(),
+            planned_agent: None,
+            status: QueueStatus::Pending,
+            assigned_to: None,
+            attempts: 0,
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            last_error: None,
+            enqueued_at: Utc::now(),
+            claimed_at: None,
+            finished_at: None,
+        }
+    }
+
+    pub fn with_dependencies(mut self, depends_on: Vec<TaskId>) -> Self {
+        self.depends_on = depends_on;
+        self
+    }
+
+    pub fn with_planned_agent(mut self, agent_id: AgentId) -> Self {
+        self.planned_agent = Some(agent_id);
+        self
+    }
+
+    /// Attempts before the task is marked failed (at least one)
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    fn key(&self) -> String {
+        queue_key(self.session_id, &self.task_id)
+    }
+
+    fn claimable_by(&self, agent_id: AgentId) -> bool {
+        self.status == QueueStatus::Pending && self.planned_agent.is_none_or(|planned| planned == agent_id)
+    }
+
+    /// Where a failed or abandoned claim leaves the task
+    fn requeued(mut self, error: String) -> Self {
+        self.status = if self.attempts >= self.max_attempts {
+            QueueStatus::Failed
+        } else {
+            QueueStatus::Pending
+        };
+        self.finished_at = (self.status == QueueStatus::Failed).then(Utc::now);
+        self.last_error = Some(error);
+        self
+    }
+}
+
+fn queue_key(session_id: SessionId, task_id: &str) -> String {
+    format!("{}/{}", session_id, task_id)
+}
+
+impl BlackboardDb {
+    /// Add a task to the queue; re-enqueueing keeps its status and attempts
+    pub async fn enqueue_task(&self, mut task: QueuedTask) -> Result<QueuedTask> {
+        zed42_core::chaos::fail_point(zed42_core::chaos::FaultPoint::Database)?;
+        if let Some(existing) = self.queued_task(task.session_id, &task.task_id).await? {
+            task.status = existing.status;
+            task.assigned_to = existing.assigned_to;
+            task.attempts = existing.attempts;
+            task.last_error = existing.last_error;
+            task.enqueued_at = existing.enqueued_at;
+            task.claimed_at = existing.claimed_at;
+            task.finished_at = existing.finished_at;
+        }
+        self.db()
+            .query("UPSERT type::thing('task_queue', $key) CONTENT $task")
+            .bind(("key", task.key()))
+            .bind(("task", task.clone()))
+            .await
+            .and_then(|response| response.check())
+            .context("Failed to enqueue task")?;
+        Ok(task)
+    }
+
+    pub async fn queued_task(&self, session_id: SessionId, task_id: &str) -> Result<Option<QueuedTask>> {
+        let mut response = self
+            .db()
+            .query("SELECT * FROM type::thing('task_queue', $key)")
+            .bind(("key", queue_key(session_id, task_id)))
+            .await
+            .context("Failed to query queued task")?;
+        let rows: Vec<QueuedTask> = response.take(0)?;
+        Ok(rows.into_iter().next())
+    }
+
+    /// Queued tasks in claim order, optionally of one session
+    pub async fn list_queue(&self, session_id: Option<SessionId>) -> Result<Vec<QueuedTask>> {
+        let mut response = self
+            .db()
+            .query("SELECT * FROM task_queue")
+            .await
+            .context("Failed to list task queue")?;
+        let mut tasks: Vec<QueuedTask> = response.take(0)?;
+        tasks.retain(|task| session_id.is_none_or(|session| task.session_id == session));
+        tasks.sort_by(|a, b| a.enqueued_at.cmp(&b.enqueued_at).then_with(|| a.task_id.cmp(&b.task_id)));
+        Ok(tasks)
+    }
+
+    /// Claim the oldest pending task `agent_id` may run whose dependencies are done
+    ///
+    /// Returns `None` when nothing is claimable. A task claimed concurrently
+    /// by another instance is skipped rather than handed out twice.
+    pub async fn claim_next_task(&self, agent_id: AgentId) -> Result<Option<QueuedTask>> {
+        zed42_core::chaos::fail_point(zed42_core::chaos::FaultPoint::Database)?;
+        let queue = self.list_queue(None).await?;
+        let done: std::collections::HashSet<(SessionId, &str)> = queue
+            .iter()
+            .filter(|task| task.status == QueueStatus::Done)
+            .map(|task| (task.session_id, task.task_id.as_str()))
+            .collect();
+
+        for candidate in queue.iter().filter(|task| task.claimable_by(agent_id)) {
+            if !candidate.depends_on.iter().all(|dep| done.contains(&(candidate.session_id, dep.as_str()))) {
+                continue;
+            }
+            let mut claimed = candidate.clone();
+            claimed.status = QueueStatus::Assigned;
+            claimed.assigned_to = Some(agent_id);
+            claimed.attempts += 1;
+            claimed.claimed_at = Some(Utc::now());
+            if self.swap_queued(candidate, &claimed).await? {
+                return Ok(Some(claimed));
+            }
+            tracing::debug!(task = %candidate.task_id, "Task claimed elsewhere; trying the next one");
+        }
+        Ok(None)
+    }
+
+    /// Mark a task `agent_id` ran done; `false` if someone else holds the task
+    ///
+    /// Tasks dispatched without a claim can be completed straight from pending.
+    pub async fn complete_queued_task(&self, session_id: SessionId, task_id: &str, agent_id: AgentId) -> Result<bool> {
+        let task = self.queued_task(session_id, task_id).await?.filter(|task| {
+            task.claimable_by(agent_id) || (task.status == QueueStatus::Assigned && task.assigned_to == Some(agent_id))
+        });
+        let Some(task) = task else {
+            return Ok(false);
+        };
+        let mut done = task.clone();
+        done.status = QueueStatus::Done;
+        done.finished_at = Some(Utc::now());
+        self.swap_queued(&task, &done).await
+    }
+
+    /// Record a failed attempt; the task is retried until it runs out of attempts
+    ///
+    /// Returns the task's new status, or `None` if the claim is no longer `agent_id`'s.
+    pub async fn fail_queued_task(
+        &self,
+        session_id: SessionId,
+        task_id: &str,
+        agent_id: AgentId,
+        error: &str,
+    ) -> Result<Option<QueueStatus>> {
+        let Some(task) = self.held_by(session_id, task_id, agent_id).await? else {
+            return Ok(None);
+        };
+        let requeued = task.clone().requeued(error.to_string());
+        Ok(self.swap_queued(&task, &requeued).await?.then_some(requeued.status))
+    }
+
+    /// Put back every task `agent_id` holds, e.g. when the agent is dissolved
+    pub async fn release_agent_tasks(&self, agent_id: AgentId) -> Result<Vec<QueuedTask>> {
+        self.requeue_where(|task| task.assigned_to == Some(agent_id), "agent released").await
+    }
+
+    /// Put back tasks claimed longer than `older_than` ago, e.g. by a crashed instance
+    pub async fn requeue_stale_tasks(&self, older_than: Duration) -> Result<Vec<QueuedTask>> {
+        let cutoff = Utc::now() - older_than;
+        self.requeue_where(|task| task.claimed_at.is_some_and(|at| at < cutoff), "claim expired").await
+    }
+
+    async fn requeue_where(&self, matches: impl Fn(&QueuedTask) -> bool, reason: &str) -> Result<Vec<QueuedTask>> {
+        let mut requeued = Vec::new();
+        for task in self.list_queue(None).await? {
+            if task.status != QueueStatus::Assigned || !matches(&task) {
+                continue;
+            }
+            let next = task.clone().requeued(reason.to_string());
+            if self.swap_queued(&task, &next).await? {
+                tracing::info!(task = %task.task_id, status = ?next.status, reason, "Requeued task");
+                requeued.push(next);
+            }
+        }
+        Ok(requeued)
+    }
+
+    async fn held_by(&self, session_id: SessionId, task_id: &str, agent_id: AgentId) -> Result<Option<QueuedTask>> {
+        Ok(self
+            .queued_task(session_id, task_id)
+            .await?
+            .filter(|task| task.status == QueueStatus::Assigned && task.assigned_to == Some(agent_id)))
+    }
+
+    /// Replace `current` with `next` unless the task moved on meanwhile
+    ///
+    /// Status and attempts together identify a claim, so the update only
+    /// applies if nobody claimed, finished or requeued the task since
+    /// `current` was read.
+    async fn swap_queued(&self, current: &QueuedTask, next: &QueuedTask) -> Result<bool> {
+        let mut response = self
+            .db()
+            .query(
+                "UPDATE type::thing('task_queue', $key) CONTENT $next
+                 WHERE status = $status AND attempts = $attempts
+                 RETURN AFTER",
+            )
+            .bind(("key", current.key()))
+            .bind(("next", next.clone()))
+            .bind(("status", current.status))
+            .bind(("attempts", current.attempts))
+            .await
+            .context("Failed to update queued task")?;
+        let rows: Vec<QueuedTask> = response.take(0)?;
+        Ok(!rows.is_empty())
+    }
+}
@@ -29,6 +29,7 @@ async fn test_state_management() {
         owner_agent: uuid::Uuid::new_v4(),
         timestamp: chrono::Utc::now().timestamp(),
         version: 1,
+        tenant_id: None,
     };
 
     blackboard.set_state(entry.clone()).await.unwrap();
@@ -53,6 +54,7 @@ async fn test_stats() {
         owner_agent: uuid::Uuid::new_v4(),
         timestamp: chrono::Utc::now().timestamp(),
         version: 1,
+        tenant_id: None,
     };
     blackboard.set_state(entry).await.unwrap();
 
@@ -60,6 +62,25 @@ async fn test_stats() {
     assert_eq!(stats.total_state_entries, 1);
 }
 
+#[tokio::test]
+async fn test_state_tenant_isolation() {
+    let (blackboard, _temp) = create_test_blackboard().await;
+
+    let entry = StateEntry {
+        key: "acme_secret".to_string(),
+        value: json!("s3cr3t"),
+        owner_agent: uuid::Uuid::new_v4(),
+        timestamp: chrono::Utc::now().timestamp(),
+        version: 1,
+        tenant_id: Some("acme".to_string()),
+    };
+    blackboard.set_state(entry).await.unwrap();
+
+    let key = "acme_secret".to_string();
+    assert!(blackboard.get_state_for_tenant("acme", &key).await.unwrap().is_some());
+    assert!(blackboard.get_state_for_tenant("globex", &key).await.is_err());
+}
+
 #[tokio::test]
 async fn test_vox_message_serialization() {
     let msg = VoxMessage {
@@ -72,6 +93,8 @@ async fn test_vox_message_serialization() {
             description: "research".to_string(),
         },
         created_at: chrono::Utc::now(),
+        ttl_secs: None,
+        idempotency_key: None,
     };
 
     let serialized = serde_json::to_string(&msg).unwrap();
@@ -93,3 +116,470 @@ async fn test_agent_pulse() {
     // For now, verification that the query didn't error is the baseline.
 }
 
+
+#[tokio::test]
+async fn test_record_vitals() {
+    let (blackboard, _temp) = create_test_blackboard().await;
+
+    assert!(blackboard.get_vitals("memory").await.unwrap().is_none());
+    blackboard.record_vitals("memory", json!({"level": "normal", "rss_bytes": 1024})).await.unwrap();
+    blackboard.record_vitals("memory", json!({"level": "elevated", "rss_bytes": 4096})).await.unwrap();
+
+    let vitals = blackboard.get_vitals("memory").await.unwrap().unwrap();
+    assert_eq!(vitals["level"], "elevated");
+}
+
+#[tokio::test]
+async fn test_direct_channel_ping_pong() {
+    let (blackboard, _temp) = create_test_blackboard().await;
+    let (implementer, tester) = (uuid::Uuid::new_v4(), uuid::Uuid::new_v4());
+    blackboard.register_direct_endpoint(tester).await.unwrap();
+
+    let mut to_tester = blackboard.open_direct(implementer, tester).await.unwrap();
+    let mut to_implementer = blackboard.open_direct(tester, implementer).await.unwrap();
+    assert!(to_tester.is_local() && to_implementer.is_local());
+
+    let ping = Message::new(
+        implementer,
+        MessageTarget::Agent(tester),
+        MessageType::ProposeSolution { solution: "fn sub(a: i32, b: i32) -> i32 { a - b }".into() },
+        1,
+    );
+    to_tester.send(&blackboard, ping.clone()).await.unwrap();
+    let received = to_implementer.recv(&blackboard).await.unwrap();
+    assert_eq!(received.id, ping.id);
+
+    let pong = Message::new(
+        tester,
+        MessageTarget::Agent(implementer),
+        MessageType::RequestRevision { target_id: ping.id.to_string(), requested_changes: "add a test".into() },
+        1,
+    );
+    to_implementer.send(&blackboard, pong.clone()).await.unwrap();
+    assert_eq!(to_tester.recv(&blackboard).await.unwrap().id, pong.id);
+}
+
+#[tokio::test]
+async fn test_direct_channel_falls_back_for_remote_peer() {
+    let (blackboard, _temp) = create_test_blackboard().await;
+    let (me, remote, unknown) = (uuid::Uuid::new_v4(), uuid::Uuid::new_v4(), uuid::Uuid::new_v4());
+    // A peer registered by another process
+    blackboard
+        .db()
+        .query("UPSERT type::thing('direct_endpoints', $agent) SET agent_id = $agent_id, process_id = $process, registered_at = time::now()")
+        .bind(("agent", remote.to_string()))
+        .bind(("agent_id", remote))
+        .bind(("process", uuid::Uuid::new_v4()))
+        .await
+        .unwrap();
+
+    assert!(!blackboard.open_direct(me, remote).await.unwrap().is_local());
+    assert!(!blackboard.open_direct(me, unknown).await.unwrap().is_local());
+    let endpoint = blackboard.direct_endpoint(me).await.unwrap().unwrap();
+    assert_eq!(endpoint.agent_id, me);
+}
+
+#[tokio::test]
+async fn test_trace_records() {
+    let (blackboard, _temp) = create_test_blackboard().await;
+    let agent = uuid::Uuid::new_v4();
+
+    blackboard
+        .record_trace(zed42_core::TraceRecord::new(
+            "t1",
+            agent,
+            zed42_core::TraceKind::Prompt { iteration: 0 },
+            "Implement pagination",
+        ))
+        .await
+        .unwrap();
+    blackboard
+        .record_trace(zed42_core::TraceRecord::new(
+            "t2",
+            agent,
+            zed42_core::TraceKind::Artifact { artifact_id: "a1".to_string(), file_path: Some("src/api.rs".to_string()) },
+            "code",
+        ))
+        .await
+        .unwrap();
+
+    let traces = blackboard.get_traces(Some("t2")).await.unwrap();
+    assert_eq!(traces.len(), 1);
+    assert_eq!(traces[0].artifact_id(), Some("a1"));
+    assert_eq!(blackboard.get_traces(None).await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_thread_transcript() {
+    let (blackboard, _temp) = create_test_blackboard().await;
+    let agent = uuid::Uuid::new_v4();
+    let thread = uuid::Uuid::new_v4();
+
+    blackboard
+        .post_vox(VoxMessage {
+            sender: surrealdb::sql::Thing::from(("agent", "cortex")),
+            target_team: "blue".to_string(),
+            priority: 1,
+            correlation_id: thread,
+            payload: zed42_core::vox::VoxPayload::TaskAssignment {
+                task_id: "t1".to_string(),
+                description: "Add pagination".to_string(),
+            },
+            created_at: chrono::Utc::now(),
+            ttl_secs: None,
+            idempotency_key: None,
+        })
+        .await
+        .unwrap();
+    blackboard
+        .record_trace(zed42_core::TraceRecord::new("t1", agent, zed42_core::TraceKind::Prompt { iteration: 0 }, "Implement pagination"))
+        .await
+        .unwrap();
+    blackboard
+        .record_trace(zed42_core::TraceRecord::new("t2", agent, zed42_core::TraceKind::Prompt { iteration: 0 }, "Other task"))
+        .await
+        .unwrap();
+    let done = Message::new(
+        agent,
+        MessageTarget::All,
+        MessageType::TaskComplete { task_id: "t1".to_string(), result: "done".to_string() },
+        1,
+    )
+    .with_thread(thread);
+    blackboard.post_message(done).await.unwrap();
+
+    let transcript = blackboard.transcript(thread).await.unwrap();
+    assert_eq!(transcript.task_ids, vec!["t1".to_string()]);
+    let sources: Vec<TranscriptSource> = transcript.entries.iter().map(|e| e.source).collect();
+    assert_eq!(sources, vec![TranscriptSource::Vox, TranscriptSource::Trace, TranscriptSource::Message]);
+    assert!(blackboard.thread_ids().await.unwrap().contains(&thread));
+}
+
+#[tokio::test]
+async fn test_duplicate_vox_messages_are_claimed_once() {
+    let (blackboard, _temp) = create_test_blackboard().await;
+    let (consumer, other) = (uuid::Uuid::new_v4(), uuid::Uuid::new_v4());
+    let msg = VoxMessage {
+        sender: surrealdb::sql::Thing::from(("agent", "cortex")),
+        target_team: "blue".to_string(),
+        priority: 1,
+        correlation_id: uuid::Uuid::new_v4(),
+        payload: zed42_core::vox::VoxPayload::Observation { content: "build green".to_string() },
+        created_at: chrono::Utc::now(),
+        ttl_secs: None,
+        idempotency_key: None,
+    };
+
+    // No key: never deduplicated
+    assert!(blackboard.claim_vox(consumer, &msg).await.unwrap());
+    assert!(blackboard.claim_vox(consumer, &msg).await.unwrap());
+
+    let keyed = msg.with_idempotency_key("build-42");
+    assert!(blackboard.claim_vox(consumer, &keyed).await.unwrap());
+    assert!(!blackboard.claim_vox(consumer, &keyed).await.unwrap());
+    assert!(blackboard.claim_vox(other, &keyed).await.unwrap());
+}
+
+#[tokio::test]
+async fn test_dedup_window_expires_claims() {
+    let (blackboard, _temp) = create_test_blackboard().await;
+    let blackboard = blackboard.with_dedup_window(std::time::Duration::ZERO);
+    let consumer = uuid::Uuid::new_v4();
+
+    assert!(blackboard.mark_processed(consumer, "k").await.unwrap());
+    tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+    assert!(blackboard.mark_processed(consumer, "k").await.unwrap());
+}
+
+#[tokio::test]
+async fn test_post_message_validates_schema() {
+    let (blackboard, _temp) = create_test_blackboard().await;
+    let agent = uuid::Uuid::new_v4();
+    let malformed = Message::new(
+        agent,
+        MessageTarget::All,
+        MessageType::ExecuteTask { task_description: String::new() },
+        1,
+    );
+
+    let err = blackboard.post_message(malformed.clone()).await.unwrap_err();
+    let violation = err.downcast_ref::<SchemaViolation>().expect("schema violation");
+    assert_eq!(violation.problems, vec!["'task_description' is empty"]);
+    assert!(blackboard.get_messages(MessageFilter::default()).await.unwrap().is_empty());
+
+    // Lenient mode keeps unconverted producers working during migration
+    let blackboard = blackboard.with_schema_mode(SchemaMode::Lenient);
+    blackboard.post_message(malformed).await.unwrap();
+    assert_eq!(blackboard.get_messages(MessageFilter::default()).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_tenant_filter_is_bound_not_spliced() {
+    let (blackboard, _temp) = create_test_blackboard().await;
+    let mut secret = Message::new(
+        uuid::Uuid::new_v4(),
+        MessageTarget::All,
+        MessageType::TaskComplete { task_id: "t1".to_string(), result: "acme only".to_string() },
+        1,
+    );
+    secret.tenant_id = Some("acme".to_string());
+    blackboard.post_message(secret).await.unwrap();
+
+    let filter = |tenant: &str| MessageFilter { tenant_id: Some(tenant.to_string()), ..Default::default() };
+    assert_eq!(blackboard.get_messages(filter("acme")).await.unwrap().len(), 1);
+    assert!(blackboard.get_messages(filter("globex")).await.unwrap().is_empty());
+    assert!(blackboard.get_messages(filter("x' OR true OR '")).await.unwrap().is_empty());
+    let page = blackboard.get_messages_page(filter("x' OR true OR '"), PageRequest::first(10)).await.unwrap();
+    assert!(page.items.is_empty());
+}
+
+#[tokio::test]
+async fn test_subscribe_from_replays_team_history() {
+    let (blackboard, _temp) = create_test_blackboard().await;
+    let since = chrono::Utc::now();
+    let vox = |team: &str, content: &str, created_at| VoxMessage {
+        sender: surrealdb::sql::Thing::from(("agent", "cortex")),
+        target_team: team.to_string(),
+        priority: 1,
+        correlation_id: uuid::Uuid::new_v4(),
+        payload: zed42_core::vox::VoxPayload::Observation { content: content.to_string() },
+        created_at,
+        ttl_secs: None,
+        idempotency_key: None,
+    };
+    let before = since - chrono::Duration::minutes(5);
+    for msg in [
+        vox("blue", "before the cutoff", before),
+        vox("blue", "second", since + chrono::Duration::seconds(2)),
+        vox("red", "other team", since + chrono::Duration::seconds(1)),
+        vox("all", "first", since + chrono::Duration::seconds(1)),
+    ] {
+        blackboard.post_vox(msg).await.unwrap();
+    }
+
+    let mut rx = blackboard.subscribe_from(Team::Blue, since).await.unwrap();
+    let observed = |msg: VoxMessage| match msg.payload {
+        zed42_core::vox::VoxPayload::Observation { content } => content,
+        other => panic!("unexpected payload {:?}", other),
+    };
+    assert_eq!(observed(rx.try_recv().unwrap()), "first");
+    assert_eq!(observed(rx.try_recv().unwrap()), "second");
+
+    // Live delivery takes over once the history is drained
+    blackboard.broadcast_alert("notice", "replay done").await;
+    assert!(matches!(
+        rx.try_recv().unwrap().payload,
+        zed42_core::vox::VoxPayload::SystemAlert { .. }
+    ));
+}
+
+#[tokio::test]
+async fn test_message_and_decision_pages() {
+    let (blackboard, _temp) = create_test_blackboard().await;
+    let agent = uuid::Uuid::new_v4();
+    let at = chrono::Utc::now();
+    // Three messages share a timestamp, so ties straddle page boundaries
+    for (i, offset) in [0, 1, 1, 1, 2].into_iter().enumerate() {
+        let mut message = Message::new(
+            agent,
+            MessageTarget::All,
+            MessageType::MilestoneReached { milestone: format!("m{}", i) },
+            1,
+        );
+        message.timestamp = at + chrono::Duration::seconds(offset);
+        blackboard.post_message(message).await.unwrap();
+    }
+
+    let mut request = PageRequest::first(2);
+    let mut timestamps = Vec::new();
+    let mut pages = 0;
+    loop {
+        let page = blackboard.get_messages_page(MessageFilter::default(), request.clone()).await.unwrap();
+        pages += 1;
+        timestamps.extend(page.items.iter().map(|m| m.timestamp));
+        match page.next_cursor {
+            Some(cursor) => request = PageRequest::first(2).after(cursor),
+            None => break,
+        }
+    }
+    assert_eq!(pages, 3);
+    assert_eq!(timestamps.len(), 5);
+    assert!(timestamps.windows(2).all(|w| w[0] >= w[1]));
+
+    for i in 0..3 {
+        blackboard
+            .record_decision(DecisionNode {
+                id: format!("d{}", i),
+                decision_type: "architecture".to_string(),
+                description: format!("decision {}", i),
+                made_by: agent,
+                rationale: json!({}),
+                alternatives_considered: vec![],
+                timestamp: 100 + i,
+                parent_decision: None,
+                confidence: None,
+            })
+            .await
+            .unwrap();
+    }
+    let first = blackboard.get_decisions_page(Some(agent), PageRequest::first(2)).await.unwrap();
+    assert_eq!(first.items.iter().map(|d| d.timestamp).collect::<Vec<_>>(), vec![102, 101]);
+    let rest = blackboard
+        .get_decisions_page(Some(agent), PageRequest::first(2).after(first.next_cursor.unwrap()))
+        .await
+        .unwrap();
+    assert_eq!(rest.items.len(), 1);
+    assert!(!rest.has_more());
+}
+
+#[tokio::test]
+async fn test_state_prefix_listing_and_key_migration() {
+    let (blackboard, _temp) = create_test_blackboard().await;
+    let owner = uuid::Uuid::new_v4();
+    let entry = |key: String| StateEntry {
+        key,
+        value: json!(1),
+        owner_agent: owner,
+        timestamp: chrono::Utc::now().timestamp(),
+        version: 1,
+        tenant_id: None,
+    };
+    let plan = NamespacedKey::in_domain("plan").scope("s1");
+    for key in [
+        plan.clone().name("phase").unwrap().to_string(),
+        plan.clone().name("goal").unwrap().to_string(),
+        NamespacedKey::in_domain("plan").scope("s2").name("phase").unwrap().to_string(),
+        NamespacedKey::in_domain("review").name("queue").unwrap().to_string(),
+        "Current Phase".to_string(),
+        "current_phase".to_string(),
+    ] {
+        blackboard.set_state(entry(key)).await.unwrap();
+    }
+
+    let s1 = blackboard.get_state_by_prefix(&NamespacedKey::scope_prefix("plan", "s1")).await.unwrap();
+    assert_eq!(s1.iter().map(|e| e.key.as_str()).collect::<Vec<_>>(), vec!["plan/s1/goal", "plan/s1/phase"]);
+    assert_eq!(blackboard.get_state_by_prefix(&NamespacedKey::domain_prefix("plan")).await.unwrap().len(), 3);
+
+    let report = blackboard
+        .migrate_state_keys(|key| (key == "current_phase").then(|| plan.clone().name("current").unwrap()))
+        .await
+        .unwrap();
+    assert_eq!(report.unchanged, 4);
+    assert_eq!(report.migrated.len(), 2);
+    assert!(blackboard.get_state(&"plan/s1/current".to_string()).await.unwrap().is_some());
+    assert!(blackboard.get_state(&"legacy/global/current_phase".to_string()).await.unwrap().is_some());
+    assert!(blackboard.get_state(&"current_phase".to_string()).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_watch_state_streams_updates_to_one_key() {
+    let (blackboard, _temp) = create_test_blackboard().await;
+    let key = NamespacedKey::in_domain("config").name("max_agents").unwrap().to_string();
+    let mut watch = blackboard.watch_state(&key).await.unwrap();
+
+    let owner = uuid::Uuid::new_v4();
+    for (key, value) in [("config/global/other", 1), (key.as_str(), 8)] {
+        blackboard
+            .set_state(StateEntry {
+                key: key.to_string(),
+                value: json!(value),
+                owner_agent: owner,
+                timestamp: chrono::Utc::now().timestamp(),
+                version: 1,
+                tenant_id: None,
+            })
+            .await
+            .unwrap();
+    }
+
+    let update = tokio::time::timeout(std::time::Duration::from_secs(5), watch.recv())
+        .await
+        .expect("watched key update")
+        .unwrap();
+    assert_eq!(update.key, key);
+    assert_eq!(update.value, json!(8));
+}
+
+#[tokio::test]
+async fn test_progress_heartbeats_and_stall_detection() {
+    let (blackboard, _temp) = create_test_blackboard().await;
+    let blackboard = std::sync::Arc::new(blackboard);
+    let agent = uuid::Uuid::new_v4();
+
+    let first = blackboard
+        .report_progress(TaskProgress::new(agent, "t1", "running tests").with_percent(40).with_tool("cargo_test"))
+        .await
+        .unwrap();
+    // Same position: a heartbeat, not progress
+    let beat = blackboard
+        .report_progress(TaskProgress::new(agent, "t1", "running tests").with_percent(40).with_tool("cargo_test"))
+        .await
+        .unwrap();
+    assert_eq!(beat.changed_at, first.changed_at);
+    assert!(beat.reported_at >= first.reported_at);
+
+    let moved = blackboard
+        .report_progress(TaskProgress::new(agent, "t1", "fixing failures").with_percent(60))
+        .await
+        .unwrap();
+    assert!(moved.changed_at > first.changed_at);
+    assert!(blackboard.stalled_tasks(chrono::Duration::hours(1)).await.unwrap().is_empty());
+
+    let db = surrealdb::engine::any::connect("mem://").await.unwrap();
+    db.use_ns("zed42").use_db("aura").await.unwrap();
+    let ledger = std::sync::Arc::new(zed42_ledger::IntelligenceLedger::new(db));
+    let aura = Aura::new(blackboard.clone(), ledger).with_stall_threshold(std::time::Duration::ZERO);
+    aura.monitor_progress().await.unwrap();
+
+    let vitals = blackboard.get_vitals(PROGRESS_VITALS_SOURCE).await.unwrap().unwrap();
+    assert_eq!(vitals[0]["step"], "fixing failures");
+    assert_eq!(vitals[0]["stalled"], true);
+    assert!(blackboard.get_progress(agent).await.unwrap().unwrap().stalled);
+
+    blackboard.clear_progress(agent).await.unwrap();
+    assert!(blackboard.list_progress().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_task_queue_claims_in_dependency_order_with_retries() {
+    let (blackboard, _temp) = create_test_blackboard().await;
+    let session = uuid::Uuid::new_v4();
+    let (agent, other) = (uuid::Uuid::new_v4(), uuid::Uuid::new_v4());
+
+    blackboard
+        .enqueue_task(QueuedTask::new(session, "t1", "Add the parser").with_planned_agent(agent).with_max_attempts(2))
+        .await
+        .unwrap();
+    blackboard
+        .enqueue_task(QueuedTask::new(session, "t2", "Test the parser").with_dependencies(vec!["t1".to_string()]))
+        .await
+        .unwrap();
+
+    // t1 is planned for `agent`; t2 waits on t1
+    assert!(blackboard.claim_next_task(other).await.unwrap().is_none());
+    let claimed = blackboard.claim_next_task(agent).await.unwrap().unwrap();
+    assert_eq!((claimed.task_id.as_str(), claimed.attempts), ("t1", 1));
+    assert!(blackboard.claim_next_task(agent).await.unwrap().is_none());
+
+    // A failed attempt is retried; only the claim holder reports on a task
+    assert!(blackboard.fail_queued_task(session, "t1", other, "not mine").await.unwrap().is_none());
+    let status = blackboard.fail_queued_task(session, "t1", agent, "tests failed").await.unwrap();
+    assert_eq!(status, Some(QueueStatus::Pending));
+    assert!(!blackboard.complete_queued_task(session, "t1", other).await.unwrap());
+
+    // Re-enqueueing after a restart keeps the scheduling state
+    blackboard.enqueue_task(QueuedTask::new(session, "t1", "Add the parser").with_planned_agent(agent)).await.unwrap();
+    let retry = blackboard.claim_next_task(agent).await.unwrap().unwrap();
+    assert_eq!(retry.attempts, 2);
+    assert!(blackboard.complete_queued_task(session, "t1", agent).await.unwrap());
+
+    // t2 is open to any agent, and handed out once
+    let t2 = blackboard.claim_next_task(other).await.unwrap().unwrap();
+    assert_eq!(t2.task_id, "t2");
+    assert!(blackboard.claim_next_task(agent).await.unwrap().is_none());
+
+    let released = blackboard.release_agent_tasks(other).await.unwrap();
+    assert_eq!(released[0].status, QueueStatus::Pending);
+    let statuses: Vec<QueueStatus> = blackboard.list_queue(Some(session)).await.unwrap().iter().map(|t| t.status).collect();
+    assert_eq!(statuses, vec![QueueStatus::Done, QueueStatus::Pending]);
+}
@@ -0,0 +1,386 @@
+//! Per-thread conversation transcripts
+//!
+//! A [`Transcript`] puts everything said on one thread in time order: the
+//! VOX messages correlated with it, the blackboard messages threaded on it,
+//! and the prompts, replies, tool calls, critiques and artifacts traced for
+//! the tasks the thread assigned. It renders as Markdown or standalone HTML,
+//! with each entry's raw payload folded into a `<details>` block, and is
+//! written next to its JSON form so the CLI can re-render it later.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use zed42_core::vox::VoxPayload;
+use zed42_core::{Message, MessageTarget, ThreadId, TraceKind, TraceRecord};
+
+use crate::{BlackboardDb, VoxMessage};
+
+/// Where a transcript entry came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptSource {
+    Vox,
+    Message,
+    Trace,
+}
+
+impl TranscriptSource {
+    fn label(self) -> &'static str {
+        match self {
+            TranscriptSource::Vox => "vox",
+            TranscriptSource::Message => "message",
+            TranscriptSource::Trace => "trace",
+        }
+    }
+}
+
+/// One step of a conversation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptEntry {
+    pub at: DateTime<Utc>,
+    pub source: TranscriptSource,
+    /// Who spoke, and to whom where known
+    pub actor: String,
+    pub title: String,
+    pub body: String,
+    /// The stored record, as JSON
+    pub raw: Value,
+}
+
+/// Everything said on one thread, oldest first
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Transcript {
+    pub thread_id: ThreadId,
+    /// Tasks assigned or completed on the thread, whose traces are included
+    pub task_ids: Vec<String>,
+    pub entries: Vec<TranscriptEntry>,
+}
+
+impl Transcript {
+    /// Transcript of `thread_id` from records already fetched
+    ///
+    /// Records of other threads are ignored; traces are kept when their
+    /// task was assigned or completed on this thread.
+    pub fn build(thread_id: ThreadId, vox: &[VoxMessage], messages: &[Message], traces: &[TraceRecord]) -> Self {
+        let vox: Vec<&VoxMessage> = vox.iter().filter(|m| m.correlation_id == thread_id).collect();
+        let messages: Vec<&Message> = messages.iter().filter(|m| m.thread_id == thread_id).collect();
+        let task_ids = task_ids(&vox, &messages);
+
+        let mut entries: Vec<TranscriptEntry> = vox.iter().map(|m| vox_entry(m)).collect();
+        entries.extend(messages.iter().map(|m| message_entry(m)));
+        entries.extend(traces.iter().filter(|t| task_ids.contains(&t.task_id)).map(trace_entry));
+        // Stable: equal timestamps keep VOX, message, trace order
+        entries.sort_by_key(|entry| entry.at);
+
+        Self {
+            thread_id,
+            task_ids: task_ids.into_iter().collect(),
+            entries,
+        }
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut md = format!("# Transcript {}\n\n", self.thread_id);
+        if !self.task_ids.is_empty() {
+            let _ = writeln!(md, "Tasks: {}\n", self.task_ids.join(", "));
+        }
+        let _ = writeln!(md, "{} entries", self.entries.len());
+        for entry in &self.entries {
+            let _ = write!(
+                md,
+                "\n### {} · {} · {}\n\n**{}**\n\n",
+                entry.at.format("%Y-%m-%d %H:%M:%S"),
+                entry.source.label(),
+                entry.actor,
+                entry.title
+            );
+            if !entry.body.is_empty() {
+                let fence = fence_for(&entry.body);
+                let _ = write!(md, "{fence}text\n{}\n{fence}\n\n", entry.body);
+            }
+            let raw = serde_json::to_string_pretty(&entry.raw).unwrap_or_default();
+            let fence = fence_for(&raw);
+            let _ = writeln!(md, "<details><summary>Raw payload</summary>\n\n{fence}json\n{}\n{fence}\n\n</details>", raw);
+        }
+        md
+    }
+
+    /// Standalone HTML page
+    pub fn to_html(&self) -> String {
+        let mut html = format!(
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>Transcript {id}</title>\n<style>\n\
+             body {{ font-family: system-ui, sans-serif; max-width: 60rem; margin: 2rem auto; }}\n\
+             .entry {{ border-left: 3px solid #ccc; padding: 0 1rem; margin: 1.5rem 0; }}\n\
+             .entry.vox {{ border-color: #4a7; }} .entry.message {{ border-color: #47a; }} .entry.trace {{ border-color: #a74; }}\n\
+             .meta {{ color: #666; font-size: 0.9em; }}\n\
+             pre {{ white-space: pre-wrap; background: #f6f6f6; padding: 0.5rem; }}\n\
+             </style>\n</head>\n<body>\n<h1>Transcript {id}</h1>\n",
+            id = self.thread_id
+        );
+        if !self.task_ids.is_empty() {
+            let _ = writeln!(html, "<p>Tasks: {}</p>", escape_html(&self.task_ids.join(", ")));
+        }
+        for entry in &self.entries {
+            let _ = write!(
+                html,
+                "<div class=\"entry {source}\">\n<p class=\"meta\">{at} · {source} · {actor}</p>\n<p><strong>{title}</strong></p>\n",
+                source = entry.source.label(),
+                at = entry.at.format("%Y-%m-%d %H:%M:%S"),
+                actor = escape_html(&entry.actor),
+                title = escape_html(&entry.title)
+            );
+            if !entry.body.is_empty() {
+                let _ = writeln!(html, "<pre>{}</pre>", escape_html(&entry.body));
+            }
+            let raw = serde_json::to_string_pretty(&entry.raw).unwrap_or_default();
+            let _ = writeln!(html, "<details><summary>Raw payload</summary><pre>{}</pre></details>\n</div>", escape_html(&raw));
+        }
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+
+    /// Write `<thread>.json`, `<thread>.md` and `<thread>.html` into `dir`
+    ///
+    /// Returns the Markdown path.
+    pub fn write_to(&self, dir: &Path) -> Result<PathBuf> {
+        std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        let base = dir.join(self.thread_id.to_string());
+        let json = serde_json::to_string_pretty(self)?;
+        for (extension, content) in [("json", json), ("md", self.to_markdown()), ("html", self.to_html())] {
+            let path = base.with_extension(extension);
+            std::fs::write(&path, content).with_context(|| format!("Failed to write transcript to {}", path.display()))?;
+        }
+        Ok(base.with_extension("md"))
+    }
+
+    /// Read a transcript written by [`Transcript::write_to`]
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&json).with_context(|| format!("{} is not a transcript", path.display()))
+    }
+}
+
+impl BlackboardDb {
+    /// Transcript of one thread
+    pub async fn transcript(&self, thread_id: ThreadId) -> Result<Transcript> {
+        let mut response = self
+            .db()
+            .query("SELECT * FROM blackboard WHERE correlation_id = $thread ORDER BY created_at ASC")
+            .query("SELECT * FROM messages WHERE thread_id = $thread ORDER BY timestamp ASC")
+            .bind(("thread", thread_id))
+            .await
+            .context("Failed to query thread messages")?;
+        let vox: Vec<VoxMessage> = response.take(0)?;
+        let messages: Vec<Message> = response.take(1)?;
+        // Compressed payloads are shown restored; ones that fail to decode as stored
+        let vox: Vec<VoxMessage> = vox
+            .into_iter()
+            .map(|m| crate::codec::decode(m.clone()).unwrap_or(m))
+            .collect();
+
+        let vox_refs: Vec<&VoxMessage> = vox.iter().collect();
+        let message_refs: Vec<&Message> = messages.iter().collect();
+        let mut traces = Vec::new();
+        for task_id in task_ids(&vox_refs, &message_refs) {
+            traces.extend(self.get_traces(Some(&task_id)).await?);
+        }
+        Ok(Transcript::build(thread_id, &vox, &messages, &traces))
+    }
+
+    /// Threads with at least one VOX or blackboard message
+    pub async fn thread_ids(&self) -> Result<Vec<ThreadId>> {
+        let mut response = self
+            .db()
+            .query("SELECT VALUE correlation_id FROM blackboard")
+            .query("SELECT VALUE thread_id FROM messages")
+            .await
+            .context("Failed to query thread ids")?;
+        let mut ids: BTreeSet<ThreadId> = response.take::<Vec<ThreadId>>(0)?.into_iter().collect();
+        ids.extend(response.take::<Vec<ThreadId>>(1)?);
+        Ok(ids.into_iter().collect())
+    }
+}
+
+fn task_ids(vox: &[&VoxMessage], messages: &[&Message]) -> BTreeSet<String> {
+    let assigned = vox.iter().filter_map(|m| match &m.payload {
+        VoxPayload::TaskAssignment { task_id, .. } => Some(task_id.clone()),
+        _ => None,
+    });
+    let completed = messages.iter().filter_map(|m| match &m.message_type {
+        zed42_core::MessageType::TaskComplete { task_id, .. } => Some(task_id.clone()),
+        _ => None,
+    });
+    assigned.chain(completed).collect()
+}
+
+fn vox_entry(message: &VoxMessage) -> TranscriptEntry {
+    let (title, body) = match &message.payload {
+        VoxPayload::TaskAssignment { task_id, description } => (format!("Task assignment {}", task_id), description.clone()),
+        VoxPayload::Proposal { content } => ("Proposal".to_string(), content.clone()),
+        VoxPayload::ConsensusUpdate { state, .. } => (format!("Consensus: {}", state), String::new()),
+        VoxPayload::Observation { content } => ("Observation".to_string(), content.clone()),
+        VoxPayload::SystemAlert { action, reason, .. } => (format!("Alert: {}", action), reason.clone()),
+        VoxPayload::Ack { result } => ("Ack".to_string(), result.clone()),
+        VoxPayload::Compressed { encoding, original_bytes, .. } => {
+            (format!("Compressed payload ({}, {} bytes)", encoding, original_bytes), String::new())
+        }
+        VoxPayload::ArtifactRef { artifact_id, original_bytes } => {
+            (format!("Payload stored as artifact {} ({} bytes)", artifact_id, original_bytes), String::new())
+        }
+    };
+    TranscriptEntry {
+        at: message.created_at,
+        source: TranscriptSource::Vox,
+        actor: format!("{} → {}", message.sender, message.target_team),
+        title,
+        body,
+        raw: serde_json::to_value(message).unwrap_or(Value::Null),
+    }
+}
+
+fn message_entry(message: &Message) -> TranscriptEntry {
+    let target = match &message.to_team {
+        MessageTarget::Team(team) => format!("{:?}", team),
+        MessageTarget::All => "all".to_string(),
+        MessageTarget::Agent(agent) => agent.to_string(),
+    };
+    // Title from the type tag, body from its text fields
+    let fields = serde_json::to_value(&message.message_type).unwrap_or(Value::Null);
+    let title = fields["type"].as_str().unwrap_or("message").replace('_', " ");
+    let body = fields
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter(|(key, _)| key.as_str() != "type")
+        .map(|(key, value)| match value {
+            Value::String(text) => format!("{}: {}", key, text),
+            other => format!("{}: {}", key, other),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    TranscriptEntry {
+        at: message.timestamp,
+        source: TranscriptSource::Message,
+        actor: format!("{} → {}", message.from_agent, target),
+        title,
+        body,
+        raw: serde_json::to_value(message).unwrap_or(Value::Null),
+    }
+}
+
+fn trace_entry(trace: &TraceRecord) -> TranscriptEntry {
+    let (title, body) = match &trace.kind {
+        TraceKind::Prompt { iteration } => (format!("Prompt (attempt {})", iteration + 1), trace.summary.clone()),
+        TraceKind::Response { iteration } => (format!("Reply (attempt {})", iteration + 1), trace.summary.clone()),
+        TraceKind::ToolCall { tool, ok } => (
+            format!("Tool call {}{}", tool, if *ok { "" } else { " (failed)" }),
+            trace.summary.clone(),
+        ),
+        TraceKind::Critique { iteration, passed, issues } => {
+            let mut lines: Vec<String> = issues.iter().map(|issue| format!("- {}", issue)).collect();
+            if !trace.summary.is_empty() {
+                lines.push(format!("Suggestions: {}", trace.summary));
+            }
+            (
+                format!("Critique (attempt {}): {}", iteration + 1, if *passed { "passed" } else { "failed" }),
+                lines.join("\n"),
+            )
+        }
+        TraceKind::Artifact { artifact_id, file_path } => (
+            match file_path {
+                Some(path) => format!("Artifact {} ({})", artifact_id, path),
+                None => format!("Artifact {}", artifact_id),
+            },
+            trace.summary.clone(),
+        ),
+    };
+    TranscriptEntry {
+        at: trace.timestamp,
+        source: TranscriptSource::Trace,
+        actor: format!("{} on {}", trace.agent_id, trace.task_id),
+        title,
+        body,
+        raw: serde_json::to_value(trace).unwrap_or(Value::Null),
+    }
+}
+
+/// Backtick fence longer than any run of backticks in `text`
+fn fence_for(text: &str) -> String {
+    let longest = text
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or(0);
+    "`".repeat(longest.max(2) + 1)
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zed42_core::MessageType;
+
+    #[test]
+    fn test_build_and_render() {
+        let thread = uuid::Uuid::new_v4();
+        let agent = uuid::Uuid::new_v4();
+        let start = Utc::now();
+        let at = |secs: i64| start + chrono::Duration::seconds(secs);
+
+        let assignment = VoxMessage {
+            sender: surrealdb::sql::Thing::from(("agent", "cortex")),
+            target_team: "blue".to_string(),
+            priority: 1,
+            correlation_id: thread,
+            payload: VoxPayload::TaskAssignment { task_id: "t1".to_string(), description: "Add <pagination>".to_string() },
+            created_at: at(0),
+            ttl_secs: None,
+            idempotency_key: None,
+        };
+        let elsewhere = VoxMessage { correlation_id: uuid::Uuid::new_v4(), ..assignment.clone() };
+        let mut done = Message::new(
+            agent,
+            MessageTarget::All,
+            MessageType::TaskComplete { task_id: "t1".to_string(), result: "Added ```rust fences```".to_string() },
+            1,
+        )
+        .with_thread(thread);
+        done.timestamp = at(5);
+        let mut traces = vec![
+            TraceRecord::new("t1", agent, TraceKind::Prompt { iteration: 0 }, "Implement pagination"),
+            TraceRecord::new("t1", agent, TraceKind::ToolCall { tool: "read_file".to_string(), ok: false }, "params: {}"),
+            TraceRecord::new("t2", agent, TraceKind::Prompt { iteration: 0 }, "Unrelated"),
+        ];
+        for (i, trace) in traces.iter_mut().enumerate() {
+            trace.timestamp = at(i as i64 + 1);
+        }
+
+        let transcript = Transcript::build(thread, &[assignment, elsewhere], &[done], &traces);
+        assert_eq!(transcript.task_ids, vec!["t1".to_string()]);
+        let titles: Vec<&str> = transcript.entries.iter().map(|e| e.title.as_str()).collect();
+        assert_eq!(titles, vec!["Task assignment t1", "Prompt (attempt 1)", "Tool call read_file (failed)", "task complete"]);
+
+        let md = transcript.to_markdown();
+        assert!(md.contains("**Task assignment t1**"));
+        // The body holds a triple-backtick run, so its fence is longer
+        assert!(md.contains("````text\n"));
+        assert!(md.contains("result: Added ```rust fences```"));
+        assert!(md.contains("<details><summary>Raw payload</summary>"));
+
+        let html = transcript.to_html();
+        assert!(html.contains("<pre>Add &lt;pagination&gt;</pre>"));
+        assert_eq!(html.matches("<details>").count(), 4);
+
+        let dir = tempfile::tempdir().unwrap();
+        let md_path = transcript.write_to(dir.path()).unwrap();
+        assert_eq!(std::fs::read_to_string(&md_path).unwrap(), md);
+        assert_eq!(Transcript::load(&md_path.with_extension("json")).unwrap(), transcript);
+    }
+}
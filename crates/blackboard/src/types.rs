@@ -4,8 +4,9 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use uuid::Uuid;
+use zed42_core::types::Confidence;
 
-pub use zed42_core::AgentId;
+pub use zed42_core::{AgentId, TenantId};
 pub use zed42_core::MessageType;
 
 // Re-export VOX Protocol from Core
@@ -27,7 +28,7 @@ pub struct BlackboardStats {
     pub db_path: PathBuf,
 }
 
-/// Key for state storage
+/// Key for state storage (see [`crate::NamespacedKey`] for the `domain/scope/name` convention)
 pub type StateKey = String;
 
 /// Entry in the blackboard state
@@ -38,6 +39,9 @@ pub struct StateEntry {
     pub owner_agent: AgentId,
     pub timestamp: i64,
     pub version: i32,
+    /// Owning tenant (None = shared)
+    #[serde(default)]
+    pub tenant_id: Option<TenantId>,
 }
 
 /// Node representing a decision in the graph
@@ -51,6 +55,9 @@ pub struct DecisionNode {
     pub alternatives_considered: Vec<String>,
     pub timestamp: i64,
     pub parent_decision: Option<String>,
+    /// How sure the deciding agent was (0.0-1.0), when it assessed itself
+    #[serde(default)]
+    pub confidence: Option<Confidence>,
 }
 
 /// Filter for message queries
@@ -60,5 +67,7 @@ pub struct MessageFilter {
     pub from_agent: Option<AgentId>,
     pub to_agent: Option<AgentId>,
     pub since_timestamp: Option<i64>,
+    /// Only messages of this tenant (plus shared ones)
+    pub tenant_id: Option<TenantId>,
     pub limit: Option<usize>,
 }
@@ -0,0 +1,121 @@
+//! Watching individual state keys
+//!
+//! Agents that poll `get_state` for configuration or status changes react
+//! late and waste queries. [`BlackboardDb::watch_state`] streams every new
+//! value of one key instead, from a LIVE SELECT on the `state` table. Where
+//! the database can't serve live queries, the stream falls back to the
+//! blackboard's in-process feed of `set_state` writes.
+
+use anyhow::Result;
+use futures_util::{Stream, StreamExt};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use surrealdb::Action;
+use tokio::sync::{broadcast, mpsc};
+use tracing::warn;
+
+use crate::{BlackboardDb, StateEntry, StateKey};
+
+/// Updates buffered per watcher before the forwarding task waits
+const WATCH_BUFFER: usize = 64;
+
+/// Stream of new values of one state key
+pub struct StateWatch {
+    key: StateKey,
+    live: bool,
+    rx: mpsc::Receiver<StateEntry>,
+}
+
+impl StateWatch {
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Whether updates come from a LIVE SELECT rather than the in-process fallback
+    pub fn is_live(&self) -> bool {
+        self.live
+    }
+
+    /// Next value of the key; `None` once the watch can no longer deliver
+    pub async fn recv(&mut self) -> Option<StateEntry> {
+        self.rx.recv().await
+    }
+}
+
+impl Stream for StateWatch {
+    type Item = StateEntry;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<StateEntry>> {
+        self.rx.poll_recv(cx)
+    }
+}
+
+impl BlackboardDb {
+    /// Stream every value written to `key` from now on
+    ///
+    /// Dropping the returned [`StateWatch`] stops the watch.
+    pub async fn watch_state(&self, key: &StateKey) -> Result<StateWatch> {
+        let (tx, rx) = mpsc::channel(WATCH_BUFFER);
+        let live = match self.db().select("state").live().await {
+            Ok(mut stream) => {
+                let key = key.clone();
+                tokio::spawn(async move {
+                    loop {
+                        let notification = tokio::select! {
+                            _ = tx.closed() => break,
+                            next = stream.next() => next,
+                        };
+                        let notification = match notification {
+                            Some(Ok(notification)) => notification,
+                            Some(Err(e)) => {
+                                warn!(%key, "State watch LIVE SELECT failed: {}", e);
+                                break;
+                            }
+                            None => break,
+                        };
+                        if !matches!(notification.action, Action::Create | Action::Update) {
+                            continue;
+                        }
+                        match serde_json::from_value::<StateEntry>(notification.data) {
+                            Ok(entry) if entry.key == key => {
+                                if tx.send(entry).await.is_err() {
+                                    break;
+                                }
+                            }
+                            Ok(_) => {}
+                            Err(e) => warn!(%key, "Skipping undecodable state notification: {}", e),
+                        }
+                    }
+                });
+                true
+            }
+            Err(e) => {
+                warn!(%key, "LIVE SELECT unavailable, watching in-process state writes: {}", e);
+                let mut changes = self.state_changes().subscribe();
+                let key = key.clone();
+                tokio::spawn(async move {
+                    loop {
+                        let change = tokio::select! {
+                            _ = tx.closed() => break,
+                            change = changes.recv() => change,
+                        };
+                        match change {
+                            Ok(entry) if entry.key == key => {
+                                if tx.send(entry).await.is_err() {
+                                    break;
+                                }
+                            }
+                            Ok(_) => {}
+                            Err(broadcast::error::RecvError::Lagged(missed)) => {
+                                warn!(%key, missed, "State watch fell behind in-process writes");
+                            }
+                            Err(broadcast::error::RecvError::Closed) => break,
+                        }
+                    }
+                });
+                false
+            }
+        };
+        Ok(StateWatch { key: key.clone(), live, rx })
+    }
+}
@@ -33,6 +33,7 @@ async fn test_state_persistence() {
         owner_agent: uuid::Uuid::new_v4(),
         timestamp: chrono::Utc::now().timestamp(),
         version: 1,
+        tenant_id: None,
     };
     
     blackboard.set_state(entry).await.unwrap();
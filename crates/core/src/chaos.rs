@@ -0,0 +1,226 @@
+//! Fault injection for chaos testing
+//!
+//! Subsystems call [`fail_point`] and [`delay_point`] where production
+//! failures happen: blackboard and ledger database calls, MOM watcher
+//! reconnects, tool filesystem commits and Router leases. Without the
+//! `chaos` feature both are no-ops the compiler removes.
+//!
+//! With the feature, faults are armed on the process-wide [`chaos()`]
+//! controller by tests or by the ChaosEngineer agent. Tests that arm faults
+//! should hold a [`ChaosSession`] so they don't leak faults into each other.
+
+use serde::{Deserialize, Serialize};
+
+/// Places where a fault can be injected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FaultPoint {
+    /// Blackboard and ledger SurrealDB operations fail
+    Database,
+    /// MOM watcher waits before reconnecting its WebSocket
+    WsReconnect,
+    /// Tool file commits fail before the atomic rename
+    ToolIo,
+    /// The Router abandons a lease without settling it
+    PoisonedLease,
+}
+
+/// Error returned by a fault point that fired
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("chaos: injected fault at {0:?}")]
+pub struct InjectedFault(pub FaultPoint);
+
+#[cfg(not(feature = "chaos"))]
+#[inline(always)]
+pub fn fail_point(_point: FaultPoint) -> anyhow::Result<()> {
+    Ok(())
+}
+
+#[cfg(not(feature = "chaos"))]
+#[inline(always)]
+pub async fn delay_point(_point: FaultPoint) {}
+
+#[cfg(feature = "chaos")]
+pub use controller::*;
+
+#[cfg(feature = "chaos")]
+mod controller {
+    use super::{FaultPoint, InjectedFault};
+    use parking_lot::Mutex;
+    use serde::{Deserialize, Serialize};
+    use std::collections::HashMap;
+    use std::sync::OnceLock;
+    use std::time::Duration;
+
+    /// How an armed fault behaves
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct FaultSpec {
+        /// Chance each pass through the point fires, 0.0..=1.0
+        pub probability: f64,
+        /// How long `delay_point` sleeps when the fault fires
+        pub delay: Option<Duration>,
+        /// Disarm after firing this many times
+        pub max_triggers: Option<u64>,
+    }
+
+    impl FaultSpec {
+        /// Fire on every pass
+        pub fn always() -> Self {
+            Self::with_probability(1.0)
+        }
+
+        pub fn with_probability(probability: f64) -> Self {
+            Self {
+                probability: probability.clamp(0.0, 1.0),
+                delay: None,
+                max_triggers: None,
+            }
+        }
+
+        pub fn with_delay(mut self, delay: Duration) -> Self {
+            self.delay = Some(delay);
+            self
+        }
+
+        /// Fire at most `n` times
+        pub fn times(mut self, n: u64) -> Self {
+            self.max_triggers = Some(n);
+            self
+        }
+    }
+
+    /// Process-wide fault registry
+    pub struct Chaos {
+        armed: Mutex<HashMap<FaultPoint, FaultSpec>>,
+        triggered: Mutex<HashMap<FaultPoint, u64>>,
+        rng: Mutex<u64>,
+        session: tokio::sync::Mutex<()>,
+    }
+
+    /// Exclusive use of the controller; disarms everything when dropped
+    pub struct ChaosSession {
+        _lock: tokio::sync::MutexGuard<'static, ()>,
+    }
+
+    impl Drop for ChaosSession {
+        fn drop(&mut self) {
+            chaos().disarm_all();
+        }
+    }
+
+    static CHAOS: OnceLock<Chaos> = OnceLock::new();
+
+    /// The process-wide controller
+    pub fn chaos() -> &'static Chaos {
+        CHAOS.get_or_init(|| Chaos {
+            armed: Mutex::new(HashMap::new()),
+            triggered: Mutex::new(HashMap::new()),
+            rng: Mutex::new(0x2545_f491_4f6c_dd1d),
+            session: tokio::sync::Mutex::new(()),
+        })
+    }
+
+    impl Chaos {
+        /// Wait for exclusive use, starting from a clean, reseeded state
+        pub async fn session(&'static self, seed: u64) -> ChaosSession {
+            let lock = self.session.lock().await;
+            self.disarm_all();
+            self.triggered.lock().clear();
+            self.seed(seed);
+            ChaosSession { _lock: lock }
+        }
+
+        /// Make probabilistic faults reproducible
+        pub fn seed(&self, seed: u64) {
+            *self.rng.lock() = seed | 1;
+        }
+
+        pub fn arm(&self, point: FaultPoint, spec: FaultSpec) {
+            tracing::warn!(?point, ?spec, "Chaos: fault armed");
+            self.armed.lock().insert(point, spec);
+        }
+
+        pub fn disarm(&self, point: FaultPoint) {
+            self.armed.lock().remove(&point);
+        }
+
+        pub fn disarm_all(&self) {
+            self.armed.lock().clear();
+        }
+
+        pub fn is_armed(&self, point: FaultPoint) -> bool {
+            self.armed.lock().contains_key(&point)
+        }
+
+        /// How often a point has fired since the last session started
+        pub fn triggered(&self, point: FaultPoint) -> u64 {
+            self.triggered.lock().get(&point).copied().unwrap_or(0)
+        }
+
+        pub fn triggered_counts(&self) -> HashMap<FaultPoint, u64> {
+            self.triggered.lock().clone()
+        }
+
+        fn roll(&self) -> f64 {
+            let mut state = self.rng.lock();
+            *state ^= *state << 13;
+            *state ^= *state >> 7;
+            *state ^= *state << 17;
+            (*state >> 11) as f64 / (1u64 << 53) as f64
+        }
+
+        /// Decide whether `point` fires now, returning its spec if it does
+        fn fire(&self, point: FaultPoint) -> Option<FaultSpec> {
+            let mut armed = self.armed.lock();
+            let spec = armed.get(&point)?.clone();
+            if self.roll() >= spec.probability {
+                return None;
+            }
+            let mut triggered = self.triggered.lock();
+            let count = triggered.entry(point).or_insert(0);
+            *count += 1;
+            if spec.max_triggers.is_some_and(|max| *count >= max) {
+                armed.remove(&point);
+            }
+            tracing::warn!(?point, "Chaos: fault fired");
+            Some(spec)
+        }
+    }
+
+    /// Fail with [`InjectedFault`] if a fault is armed at `point` and fires
+    pub fn fail_point(point: FaultPoint) -> anyhow::Result<()> {
+        match chaos().fire(point) {
+            Some(_) => Err(InjectedFault(point).into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Sleep for the armed delay if a fault at `point` fires
+    pub async fn delay_point(point: FaultPoint) {
+        if let Some(spec) = chaos().fire(point) {
+            tokio::time::sleep(spec.delay.unwrap_or_default()).await;
+        }
+    }
+}
+
+#[cfg(all(test, feature = "chaos"))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_fault_points() {
+        let _session = chaos().session(42).await;
+        assert!(fail_point(FaultPoint::Database).is_ok());
+
+        chaos().arm(FaultPoint::Database, FaultSpec::always().times(2));
+        let err = fail_point(FaultPoint::Database).unwrap_err();
+        assert_eq!(err.downcast_ref::<InjectedFault>(), Some(&InjectedFault(FaultPoint::Database)));
+        assert!(fail_point(FaultPoint::Database).is_err());
+        assert!(fail_point(FaultPoint::Database).is_ok(), "Disarmed after max triggers");
+        assert_eq!(chaos().triggered(FaultPoint::Database), 2);
+
+        chaos().arm(FaultPoint::ToolIo, FaultSpec::with_probability(0.5));
+        let failures = (0..1000).filter(|_| fail_point(FaultPoint::ToolIo).is_err()).count();
+        assert!((400..600).contains(&failures), "{} failures", failures);
+    }
+}
@@ -1,6 +1,7 @@
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
 
 /// Unique identifier for an entity (Agent, Project, User)
 pub type EntityId = String;
@@ -8,19 +9,28 @@ pub type EntityId = String;
 /// Unique identifier for a temporary lease
 pub type LeaseId = String;
 
+/// Cost center tags (e.g. `feature`, `customer`, `environment`) by key
+pub type CostTags = BTreeMap<String, String>;
+
 /// Usage report from an inference call
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Usage {
-    /// Number of input (prompt) tokens
+    /// Number of input (prompt) tokens, cached ones included
     pub input_tokens: u32,
     /// Number of output (completion) tokens
     pub output_tokens: u32,
     /// Model identifier (e.g., "gpt-4")
     pub model: String,
+    /// Input tokens read from the provider's prompt cache
+    #[serde(default)]
+    pub cache_read_tokens: u32,
+    /// Input tokens written to the provider's prompt cache
+    #[serde(default)]
+    pub cache_write_tokens: u32,
 }
 
 /// Cost rate for a specific model
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct RateTableEntry {
     /// Model identifier
     pub model: String,
@@ -28,6 +38,37 @@ pub struct RateTableEntry {
     pub input_cost_per_1k: Decimal,
     /// Cost per 1000 output tokens
     pub output_cost_per_1k: Decimal,
+    /// Cost per 1000 input tokens read from the prompt cache (None: input rate)
+    #[serde(default)]
+    pub cache_read_cost_per_1k: Option<Decimal>,
+    /// Cost per 1000 input tokens written to the prompt cache (None: input rate)
+    #[serde(default)]
+    pub cache_write_cost_per_1k: Option<Decimal>,
+}
+
+impl RateTableEntry {
+    /// Cost of `usage` at these rates
+    pub fn cost(&self, usage: &Usage) -> Decimal {
+        let per_1k = |tokens: u32, rate: Decimal| Decimal::from(tokens) / Decimal::from(1000) * rate;
+        let cache_read = usage.cache_read_tokens.min(usage.input_tokens);
+        let cache_write = usage.cache_write_tokens.min(usage.input_tokens - cache_read);
+        let uncached = usage.input_tokens - cache_read - cache_write;
+
+        per_1k(uncached, self.input_cost_per_1k)
+            + per_1k(cache_read, self.cache_read_cost_per_1k.unwrap_or(self.input_cost_per_1k))
+            + per_1k(cache_write, self.cache_write_cost_per_1k.unwrap_or(self.input_cost_per_1k))
+            + per_1k(usage.output_tokens, self.output_cost_per_1k)
+    }
+
+    /// What prompt caching saved on `usage`; negative when cache writes cost more than reads saved
+    pub fn cache_savings(&self, usage: &Usage) -> Decimal {
+        let uncached = Usage {
+            cache_read_tokens: 0,
+            cache_write_tokens: 0,
+            ..usage.clone()
+        };
+        self.cost(&uncached) - self.cost(usage)
+    }
 }
 
 /// Status of the budget
@@ -57,6 +98,16 @@ pub struct Budget {
     pub status: BudgetStatus,
     /// Last updated timestamp
     pub updated_at: DateTime<Utc>,
+    /// Cost center tags copied onto this budget's ledger entries and receipts
+    #[serde(default)]
+    pub tags: CostTags,
+}
+
+impl Budget {
+    pub fn with_tag(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.tags.insert(key.into(), value.into());
+        self
+    }
 }
 
 /// Type of ledger transaction
@@ -88,8 +139,23 @@ pub struct LedgerEntry {
     pub transaction_type: TransactionType,
     /// Amount reserved or spent
     pub amount: Decimal,
+    /// Model, or metered resource key, billed for this entry (Settlements only)
+    #[serde(default)]
+    pub model: Option<String>,
     /// Description or metadata
     pub details: String,
+    /// Position in the hash chain (1-based)
+    #[serde(default)]
+    pub sequence: u64,
+    /// Hash of the preceding entry
+    #[serde(default)]
+    pub prev_hash: Option<String>,
+    /// SHA-256 hash of this entry's contents and `prev_hash`
+    #[serde(default)]
+    pub hash: Option<String>,
+    /// Cost center tags of the entity's budget when the entry was recorded
+    #[serde(default)]
+    pub tags: CostTags,
 }
 
 /// Receipt returned after committing usage
@@ -103,6 +169,27 @@ pub struct Receipt {
     pub timestamp: DateTime<Utc>,
     /// Warning message if soft limit exceeded
     pub warning: Option<String>,
+    /// Hash of the Settlement entry backing this receipt
+    #[serde(default)]
+    pub entry_hash: Option<String>,
+    /// HMAC signature, when the ledger has a signing key
+    #[serde(default)]
+    pub signature: Option<String>,
+    /// Amount prompt caching saved versus billing every input token at the input rate
+    #[serde(default)]
+    pub cache_savings: Decimal,
+    /// Cost center tags the settlement was booked under
+    #[serde(default)]
+    pub tags: CostTags,
+}
+
+/// Who may draw on a reserve pool once the main budget is exhausted
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum LeasePriority {
+    #[default]
+    Normal,
+    /// Governance work (reviews, safety checks, cleanup) that must not be starved
+    Critical,
 }
 
 /// A temporary reservation of funds
@@ -114,4 +201,7 @@ pub struct Lease {
     pub estimated_cost: Decimal,
     pub created_at: DateTime<Utc>,
     pub expires_at: DateTime<Utc>,
+    /// Reserve pool the lease was granted from, when the budget couldn't cover it
+    #[serde(default)]
+    pub reserve_pool: Option<EntityId>,
 }
@@ -5,9 +5,20 @@ pub mod result;
 pub mod vox;
 pub mod titan;
 pub mod ledger;
+pub mod tenant;
+pub mod chaos;
+pub mod shutdown;
+pub mod workspace;
+pub mod trace;
+pub mod page;
 
 pub use result::{Result, Error};
-pub use types::{AgentId, Priority, Team, ThreadId, MessageId, AgentStatus, Task, Artifact, ArtifactType, TaskId, ArtifactId};
+pub use types::{AgentId, Priority, Team, ThreadId, MessageId, AgentStatus, Task, Artifact, ArtifactType, TaskId, ArtifactId, ContextPack, ContextSnippet, ContextSource};
 pub use messages::{Message, MessageType, MessageTarget};
 pub use traits::AgentBehavior;
+pub use tenant::TenantId;
+pub use shutdown::{CancellationToken, ShutdownController, ShutdownReport, ShutdownStage};
+pub use workspace::{Package, PackageKind, ProjectWorkspace};
+pub use trace::{TraceKind, TraceRecord};
+pub use page::{Cursor, Page, PageRequest};
 
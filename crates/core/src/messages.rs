@@ -5,7 +5,7 @@
 
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
-use crate::{AgentId, MessageId, Priority, Team, ThreadId};
+use crate::{AgentId, MessageId, Priority, Team, TenantId, ThreadId};
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -54,6 +54,15 @@ pub struct Message {
     pub thread_id: ThreadId,
     pub priority: Priority,
     pub requires_response: bool,
+    /// Tenant the message belongs to (None = shared)
+    #[serde(default)]
+    pub tenant_id: Option<TenantId>,
+    /// Seconds after `timestamp` the message is archived instead of read
+    #[serde(default)]
+    pub ttl_secs: Option<u64>,
+    /// Producer-chosen key that stays the same when a message is re-posted
+    #[serde(default)]
+    pub idempotency_key: Option<String>,
 }
 
 impl Message {
@@ -72,6 +81,9 @@ impl Message {
             thread_id: uuid::Uuid::new_v4(),
             priority,
             requires_response: false,
+            tenant_id: None,
+            ttl_secs: None,
+            idempotency_key: None,
         }
     }
 
@@ -80,8 +92,32 @@ impl Message {
         self
     }
 
+    pub fn with_tenant(mut self, tenant_id: impl Into<TenantId>) -> Self {
+        self.tenant_id = Some(tenant_id.into());
+        self
+    }
+
     pub fn requires_response(mut self) -> Self {
         self.requires_response = true;
         self
     }
+
+    pub fn with_ttl(mut self, ttl: std::time::Duration) -> Self {
+        self.ttl_secs = Some(ttl.as_secs());
+        self
+    }
+
+    pub fn is_expired_at(&self, now: DateTime<Utc>) -> bool {
+        crate::vox::expiry(self.timestamp, self.ttl_secs).is_some_and(|expires| now >= expires)
+    }
+
+    pub fn with_idempotency_key(mut self, key: impl Into<String>) -> Self {
+        self.idempotency_key = Some(key.into());
+        self
+    }
+
+    /// Key consumers deduplicate on (thread + idempotency key), if any
+    pub fn dedup_key(&self) -> Option<String> {
+        crate::vox::dedup_key(self.thread_id, self.idempotency_key.as_deref())
+    }
 }
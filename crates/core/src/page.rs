@@ -0,0 +1,175 @@
+//! Cursor-based pagination for history reads
+//!
+//! Message, decision, archive and graph listings can grow without bound.
+//! Paged reads return at most [`MAX_PAGE_SIZE`] items plus an opaque
+//! [`Cursor`] for the next page. The cursor holds the sort key of the last
+//! item returned and how many returned items share it, so pages stay
+//! stable when items with equal keys straddle a page boundary.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Page size when the request doesn't give one
+pub const DEFAULT_PAGE_SIZE: usize = 100;
+
+/// Largest page a request may ask for
+pub const MAX_PAGE_SIZE: usize = 1000;
+
+/// Opaque position in a listing, handed back to fetch the next page
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Cursor(String);
+
+impl Cursor {
+    fn encode<K: Serialize>(position: &Position<K>) -> Self {
+        let json = serde_json::to_vec(position).unwrap_or_default();
+        Self(json.iter().map(|b| format!("{:02x}", b)).collect())
+    }
+
+    fn decode<K: DeserializeOwned>(&self) -> crate::Result<Position<K>> {
+        let invalid = || crate::Error::Other(anyhow::anyhow!("Invalid page cursor: {}", self.0));
+        if self.0.len() % 2 != 0 {
+            return Err(invalid());
+        }
+        let bytes = (0..self.0.len())
+            .step_by(2)
+            .map(|i| self.0.get(i..i + 2).and_then(|hex| u8::from_str_radix(hex, 16).ok()))
+            .collect::<Option<Vec<u8>>>()
+            .ok_or_else(invalid)?;
+        serde_json::from_slice(&bytes).map_err(|_| invalid())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for Cursor {
+    fn from(cursor: String) -> Self {
+        Self(cursor)
+    }
+}
+
+impl fmt::Display for Cursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Sort key of the last item returned, and how many returned items share it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Position<K> {
+    pub key: K,
+    pub ties: usize,
+}
+
+/// Which page to read
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PageRequest {
+    /// `None` for the first page
+    pub cursor: Option<Cursor>,
+    /// Items per page (default [`DEFAULT_PAGE_SIZE`], at most [`MAX_PAGE_SIZE`])
+    pub limit: Option<usize>,
+}
+
+impl PageRequest {
+    pub fn first(limit: usize) -> Self {
+        Self { cursor: None, limit: Some(limit) }
+    }
+
+    pub fn after(mut self, cursor: Cursor) -> Self {
+        self.cursor = Some(cursor);
+        self
+    }
+
+    /// Page size with the default and bounds applied
+    pub fn limit(&self) -> usize {
+        self.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE)
+    }
+
+    /// Where the previous page ended, if this isn't the first page
+    pub fn position<K: DeserializeOwned>(&self) -> crate::Result<Option<Position<K>>> {
+        self.cursor.as_ref().map(Cursor::decode).transpose()
+    }
+}
+
+/// One page of a listing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Cursor for the next page; `None` on the last one
+    pub next_cursor: Option<Cursor>,
+}
+
+impl<T> Page<T> {
+    /// Build a page from up to `limit + 1` rows sorted by `key`, descending
+    ///
+    /// The rows are those with a key at or below `previous.key`, with the
+    /// first `previous.ties` skipped. The row past `limit` only signals that
+    /// another page exists.
+    pub fn from_rows<K, F>(mut rows: Vec<T>, limit: usize, previous: Option<&Position<K>>, key: F) -> Self
+    where
+        K: Serialize + PartialEq,
+        F: Fn(&T) -> K,
+    {
+        let more = rows.len() > limit;
+        rows.truncate(limit);
+        let next_cursor = match rows.last() {
+            Some(last) if more => {
+                let last_key = key(last);
+                let mut ties = rows.iter().rev().take_while(|row| key(row) == last_key).count();
+                // The whole page shares the previous page's last key
+                if let Some(previous) = previous.filter(|p| p.key == last_key) {
+                    ties += previous.ties;
+                }
+                Some(Cursor::encode(&Position { key: last_key, ties }))
+            }
+            _ => None,
+        };
+        Self { items: rows, next_cursor }
+    }
+
+    pub fn has_more(&self) -> bool {
+        self.next_cursor.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Rows at or below the position's key, descending, ties skipped
+    fn read(rows: &[i64], request: &PageRequest) -> Page<i64> {
+        let position: Option<Position<i64>> = request.position().unwrap();
+        let (max, skip) = position.as_ref().map(|p| (p.key, p.ties)).unwrap_or((i64::MAX, 0));
+        let matching: Vec<i64> = rows.iter().copied().filter(|&r| r <= max).skip(skip).take(request.limit() + 1).collect();
+        Page::from_rows(matching, request.limit(), position.as_ref(), |r| *r)
+    }
+
+    #[test]
+    fn test_pages_cover_ties_across_boundaries() {
+        let rows = [9, 7, 7, 7, 7, 5, 3];
+        let mut request = PageRequest::first(2);
+        let mut seen = Vec::new();
+        loop {
+            let page = read(&rows, &request);
+            seen.extend(page.items.iter().copied());
+            match page.next_cursor {
+                Some(cursor) => request = PageRequest::first(2).after(cursor),
+                None => break,
+            }
+        }
+        assert_eq!(seen, rows);
+    }
+
+    #[test]
+    fn test_limits_and_invalid_cursors() {
+        assert_eq!(PageRequest::default().limit(), DEFAULT_PAGE_SIZE);
+        assert_eq!(PageRequest::first(0).limit(), 1);
+        assert_eq!(PageRequest::first(1_000_000).limit(), MAX_PAGE_SIZE);
+
+        let bogus = PageRequest::default().after(Cursor::from("zz".to_string()));
+        assert!(bogus.position::<i64>().is_err());
+    }
+}
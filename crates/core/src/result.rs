@@ -8,6 +8,8 @@ pub enum Error {
     Agent(String),
     #[error("LLM error: {0}")]
     Llm(String),
+    #[error("Tenant isolation violation: {0}")]
+    TenantIsolation(String),
     #[error("I/O error: {0}")]
     Io(#[from] std::io::Error),
     #[error("Serialization error: {0}")]
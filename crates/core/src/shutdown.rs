@@ -0,0 +1,190 @@
+//! Coordinated shutdown
+//!
+//! Background loops (MOM watcher, AURA sentinel, memory monitor, agent OODA
+//! loops) take a [`CancellationToken`] from the [`ShutdownController`] and
+//! exit when it fires. Shutdown then runs in a fixed order:
+//!
+//! 1. Cancel every token; agent loops drain their mailboxes and return.
+//! 2. Wait for tracked tasks up to the grace period, aborting stragglers.
+//! 3. Run registered hooks stage by stage: settle/release open leases,
+//!    then checkpoint persistent stores (SQLite WAL, ANN snapshots).
+
+use parking_lot::Mutex;
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+pub use tokio_util::sync::CancellationToken;
+
+type Hook = Box<dyn FnOnce() -> Pin<Box<dyn Future<Output = anyhow::Result<()>> + Send>> + Send>;
+
+/// When a shutdown hook runs, in order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ShutdownStage {
+    /// Settle or release budget leases still held by this process
+    ReleaseLeases,
+    /// Flush and checkpoint persistent stores
+    Checkpoint,
+}
+
+/// What happened during shutdown
+#[derive(Debug, Default, Clone)]
+pub struct ShutdownReport {
+    /// Tasks that exited on their own within the grace period
+    pub tasks_stopped: Vec<String>,
+    /// Tasks that had to be aborted
+    pub tasks_aborted: Vec<String>,
+    pub hooks_run: Vec<String>,
+    /// Hooks that returned an error, with the error message
+    pub hook_failures: Vec<(String, String)>,
+}
+
+impl ShutdownReport {
+    pub fn is_clean(&self) -> bool {
+        self.tasks_aborted.is_empty() && self.hook_failures.is_empty()
+    }
+}
+
+/// Owns the root cancellation token and everything that must stop with it
+#[derive(Default)]
+pub struct ShutdownController {
+    token: CancellationToken,
+    tasks: Mutex<Vec<(String, JoinHandle<()>)>>,
+    hooks: Mutex<Vec<(ShutdownStage, String, Hook)>>,
+}
+
+impl ShutdownController {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Token for a background loop; cancelled when shutdown starts
+    pub fn token(&self) -> CancellationToken {
+        self.token.child_token()
+    }
+
+    pub fn is_shutting_down(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    /// Track an already spawned task so shutdown waits for it
+    pub fn track(&self, name: impl Into<String>, handle: JoinHandle<()>) {
+        self.tasks.lock().push((name.into(), handle));
+    }
+
+    /// Spawn a tracked task that receives its own cancellation token
+    pub fn spawn<F, Fut>(&self, name: impl Into<String>, task: F)
+    where
+        F: FnOnce(CancellationToken) -> Fut,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let handle = tokio::spawn(task(self.token()));
+        self.track(name, handle);
+    }
+
+    /// Cancel an externally owned token (e.g. a subsystem's watcher) on shutdown
+    pub fn link(&self, token: CancellationToken) {
+        let root = self.token.clone();
+        tokio::spawn(async move {
+            root.cancelled().await;
+            token.cancel();
+        });
+    }
+
+    /// Register work to run after all tasks have stopped
+    pub fn on_shutdown<F, Fut>(&self, stage: ShutdownStage, name: impl Into<String>, hook: F)
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = anyhow::Result<()>> + Send + 'static,
+    {
+        let hook: Hook = Box::new(move || Box::pin(hook()));
+        self.hooks.lock().push((stage, name.into(), hook));
+    }
+
+    /// Stop everything, giving tasks `grace` to exit before aborting them
+    pub async fn shutdown(&self, grace: Duration) -> ShutdownReport {
+        let mut report = ShutdownReport::default();
+        tracing::info!("Shutdown requested, stopping background tasks");
+        self.token.cancel();
+
+        let tasks = std::mem::take(&mut *self.tasks.lock());
+        let deadline = tokio::time::Instant::now() + grace;
+        for (name, mut handle) in tasks {
+            match tokio::time::timeout_at(deadline, &mut handle).await {
+                Ok(_) => report.tasks_stopped.push(name),
+                Err(_) => {
+                    tracing::warn!(task = %name, "Task did not stop within the grace period, aborting");
+                    handle.abort();
+                    report.tasks_aborted.push(name);
+                }
+            }
+        }
+
+        let mut hooks = std::mem::take(&mut *self.hooks.lock());
+        hooks.sort_by_key(|(stage, _, _)| *stage);
+        for (stage, name, hook) in hooks {
+            tracing::debug!(?stage, hook = %name, "Running shutdown hook");
+            match hook().await {
+                Ok(()) => report.hooks_run.push(name),
+                Err(e) => {
+                    tracing::error!(hook = %name, error = %e, "Shutdown hook failed");
+                    report.hook_failures.push((name, e.to_string()));
+                }
+            }
+        }
+
+        tracing::info!(
+            stopped = report.tasks_stopped.len(),
+            aborted = report.tasks_aborted.len(),
+            hooks = report.hooks_run.len(),
+            "Shutdown complete"
+        );
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_shutdown_order() {
+        let controller = ShutdownController::new();
+        let log = Arc::new(Mutex::new(Vec::new()));
+
+        let task_log = log.clone();
+        controller.spawn("watcher", |token| async move {
+            token.cancelled().await;
+            task_log.lock().push("watcher stopped");
+        });
+        controller.spawn("stuck", |_token| async move {
+            std::future::pending::<()>().await;
+        });
+
+        let hook_log = log.clone();
+        controller.on_shutdown(ShutdownStage::Checkpoint, "sqlite", move || async move {
+            hook_log.lock().push("checkpoint");
+            Ok(())
+        });
+        let hook_log = log.clone();
+        controller.on_shutdown(ShutdownStage::ReleaseLeases, "leases", move || async move {
+            hook_log.lock().push("leases");
+            anyhow::bail!("ledger unavailable")
+        });
+
+        let linked = CancellationToken::new();
+        controller.link(linked.clone());
+
+        let report = controller.shutdown(Duration::from_millis(50)).await;
+        assert_eq!(*log.lock(), vec!["watcher stopped", "leases", "checkpoint"]);
+        assert_eq!(report.tasks_stopped, vec!["watcher".to_string()]);
+        assert_eq!(report.tasks_aborted, vec!["stuck".to_string()]);
+        assert_eq!(report.hook_failures.len(), 1);
+        assert!(!report.is_clean());
+        assert!(controller.is_shutting_down());
+
+        tokio::time::timeout(Duration::from_secs(1), linked.cancelled()).await.unwrap();
+    }
+}
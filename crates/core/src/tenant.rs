@@ -0,0 +1,79 @@
+//! Tenant attribution and isolation
+//!
+//! When the control plane serves several users or organisations, every
+//! request, ledger entity and blackboard record carries a tenant id. Ledger
+//! entities are scoped as `<tenant>/<entity>` so each tenant's budget root is
+//! simply the budget stored under the bare tenant id.
+
+use crate::{Error, Result};
+
+pub type TenantId = String;
+
+/// Separator between tenant and entity in a scoped id
+pub const TENANT_SEPARATOR: char = '/';
+
+/// Check that `tenant` can scope ids
+///
+/// Empty ids and ids containing [`TENANT_SEPARATOR`] are rejected: `acme/x`
+/// would scope entities that [`tenant_of`] attributes to `acme`.
+pub fn validate_tenant_id(tenant: &str) -> Result<()> {
+    if tenant.is_empty() || tenant.contains(TENANT_SEPARATOR) {
+        return Err(Error::TenantIsolation(format!(
+            "invalid tenant id {:?}: must be non-empty and not contain '{}'",
+            tenant, TENANT_SEPARATOR
+        )));
+    }
+    Ok(())
+}
+
+/// Scope an entity id to a tenant (`acme` + `agent-1` -> `acme/agent-1`)
+pub fn scoped_id(tenant: &str, id: &str) -> Result<String> {
+    validate_tenant_id(tenant)?;
+    Ok(format!("{}{}{}", tenant, TENANT_SEPARATOR, id))
+}
+
+/// Tenant a scoped id belongs to, if it is scoped
+pub fn tenant_of(scoped: &str) -> Option<&str> {
+    scoped.split_once(TENANT_SEPARATOR).map(|(tenant, _)| tenant)
+}
+
+/// Reject access to a record owned by another tenant
+///
+/// Records without a tenant are shared; a caller without a tenant may only
+/// see shared records.
+pub fn ensure_tenant(caller: Option<&str>, owner: Option<&str>) -> Result<()> {
+    match (caller, owner) {
+        (_, None) => Ok(()),
+        (Some(caller), Some(owner)) if caller == owner => Ok(()),
+        (caller, Some(owner)) => Err(Error::TenantIsolation(format!(
+            "{} may not access records of tenant {}",
+            caller.unwrap_or("unscoped caller"),
+            owner
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scoping_and_isolation() {
+        let id = scoped_id("acme", "agent-1").unwrap();
+        assert_eq!(id, "acme/agent-1");
+        assert_eq!(tenant_of(&id), Some("acme"));
+        assert_eq!(tenant_of("agent-1"), None);
+
+        assert!(ensure_tenant(Some("acme"), Some("acme")).is_ok());
+        assert!(ensure_tenant(Some("acme"), None).is_ok());
+        assert!(ensure_tenant(Some("acme"), Some("globex")).is_err());
+        assert!(ensure_tenant(None, Some("globex")).is_err());
+    }
+
+    #[test]
+    fn test_tenant_ids_cannot_contain_the_separator() {
+        assert!(matches!(scoped_id("acme/x", "agent-1"), Err(Error::TenantIsolation(_))));
+        assert!(scoped_id("", "agent-1").is_err());
+        assert!(validate_tenant_id("acme-x").is_ok());
+    }
+}
@@ -8,6 +8,7 @@ use std::sync::Arc;
 use std::any::Any;
 
 pub mod monitor;
+pub mod placement;
 
 /// The Titan Substrate Registry
 ///
@@ -19,6 +20,8 @@ pub struct TitanSubstrate {
     subsystems: RwLock<std::collections::HashMap<String, Arc<dyn Any + Send + Sync>>>,
     /// Hardware Monitor
     pub sentry: Arc<monitor::SpaceSentry>,
+    /// Reservations for agents placed on this machine
+    pub placement: Arc<placement::PlacementPlanner>,
 }
 
 impl Default for TitanSubstrate {
@@ -33,9 +36,35 @@ impl TitanSubstrate {
         Self {
             subsystems: RwLock::new(std::collections::HashMap::new()),
             sentry: Arc::new(monitor::SpaceSentry::new()),
+            placement: Arc::new(placement::PlacementPlanner::default()),
         }
     }
 
+    /// Use a placement policy other than the defaults (e.g. to enable GPU slots)
+    pub fn with_placement_policy(mut self, policy: placement::PlacementPolicy) -> Self {
+        self.placement = Arc::new(placement::PlacementPlanner::new(policy));
+        self
+    }
+
+    /// Decide whether an agent with `requirements` can be spawned now
+    ///
+    /// Refreshes disk state first so a full disk defers spawns.
+    pub fn place(
+        &self,
+        agent_id: uuid::Uuid,
+        requirements: placement::ResourceRequirements,
+    ) -> placement::PlacementDecision {
+        if let Err(e) = self.sentry.refresh_and_verify() {
+            tracing::warn!(error = %e, "SpaceSentry: disk refresh failed before placement");
+        }
+        let vitals = self.sentry.hardware_vitals();
+        let decision = self.placement.try_place(agent_id, requirements, &vitals);
+        if decision != placement::PlacementDecision::Place {
+            tracing::warn!(agent = %agent_id, ?decision, ?vitals, "SpaceSentry: agent placement held back");
+        }
+        decision
+    }
+
     /// Check system health via SpaceSentry
     pub fn list_vital_signs(&self) -> anyhow::Result<()> {
         self.sentry.refresh_and_verify()?; // Force update
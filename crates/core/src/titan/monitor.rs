@@ -12,6 +12,18 @@ use std::time::Duration;
 /// Critical disk space threshold (500MB)
 const CRITICAL_THRESHOLD_BYTES: u64 = 500 * 1024 * 1024; 
 
+/// Snapshot of machine capacity used for agent placement
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct HardwareVitals {
+    pub cpu_cores: usize,
+    /// Global CPU usage, 0-100
+    pub cpu_usage_percent: f32,
+    pub total_memory_bytes: u64,
+    pub available_memory_bytes: u64,
+    /// Disk below the critical threshold
+    pub disk_critical: bool,
+}
+
 pub struct SpaceSentry {
     /// Global system state
     system: RwLock<System>,
@@ -49,6 +61,20 @@ impl SpaceSentry {
         Ok(())
     }
 
+    /// Current CPU, memory and disk state
+    pub fn hardware_vitals(&self) -> HardwareVitals {
+        let mut system = self.system.write();
+        system.refresh_memory();
+        system.refresh_cpu_usage();
+        HardwareVitals {
+            cpu_cores: system.cpus().len().max(1),
+            cpu_usage_percent: system.global_cpu_usage(),
+            total_memory_bytes: system.total_memory(),
+            available_memory_bytes: system.available_memory(),
+            disk_critical: self.critical_state.load(Ordering::Relaxed),
+        }
+    }
+
     /// Force refresh and status update
     pub fn refresh_and_verify(&self) -> Result<()> {
          let mut disks = self.disks.write();
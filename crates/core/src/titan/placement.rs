@@ -0,0 +1,268 @@
+//! Resource-aware agent placement
+//!
+//! Before an agent is spawned its [`ResourceRequirements`] are checked
+//! against the current [`HardwareVitals`] and the reservations of agents
+//! placed before it. The outcome is a [`PlacementDecision`]:
+//!
+//! - `Place`: enough headroom now; the requirements are reserved.
+//! - `Defer`: the machine could run it, but not right now (memory or CPU
+//!   pressure, GPU slots busy, disk critical). Callers queue and retry.
+//! - `Reject`: the machine can never run it (more memory than installed,
+//!   a GPU agent without GPU slots).
+//!
+//! Memory reservations only count during a warm-up window: after that the
+//! agent's real usage shows up in the available memory reading, and
+//! counting it twice would starve later spawns. CPU cores and GPU slots
+//! stay reserved until released.
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+use super::monitor::HardwareVitals;
+
+/// What one agent needs from the machine
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ResourceRequirements {
+    /// Sustained CPU cores
+    pub cpu_cores: f32,
+    pub memory_bytes: u64,
+    /// Needs a GPU slot (local model inference)
+    pub gpu: bool,
+}
+
+impl ResourceRequirements {
+    pub const fn new(cpu_cores: f32, memory_mb: u64) -> Self {
+        Self {
+            cpu_cores,
+            memory_bytes: memory_mb * 1024 * 1024,
+            gpu: false,
+        }
+    }
+
+    pub const fn with_gpu(mut self) -> Self {
+        self.gpu = true;
+        self
+    }
+}
+
+/// Outcome of a placement check
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "decision", rename_all = "snake_case")]
+pub enum PlacementDecision {
+    Place,
+    Defer { reason: String },
+    Reject { reason: String },
+}
+
+/// Limits placement keeps to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlacementPolicy {
+    /// Memory always left free for the OS and the orchestrator
+    pub memory_headroom_bytes: u64,
+    /// Defer spawns while global CPU usage is above this percentage
+    pub max_cpu_usage: f32,
+    /// GPU agents that may run at once; 0 means no GPU placement
+    pub gpu_slots: usize,
+    /// How long a memory reservation counts against available memory
+    pub memory_warmup: Duration,
+}
+
+impl Default for PlacementPolicy {
+    fn default() -> Self {
+        Self {
+            memory_headroom_bytes: 512 * 1024 * 1024,
+            max_cpu_usage: 90.0,
+            gpu_slots: 0,
+            memory_warmup: Duration::from_secs(30),
+        }
+    }
+}
+
+struct Reservation {
+    requirements: ResourceRequirements,
+    placed_at: Instant,
+}
+
+/// Tracks reservations and decides where new agents fit
+pub struct PlacementPlanner {
+    policy: PlacementPolicy,
+    reserved: Mutex<HashMap<Uuid, Reservation>>,
+}
+
+impl Default for PlacementPlanner {
+    fn default() -> Self {
+        Self::new(PlacementPolicy::default())
+    }
+}
+
+fn mb(bytes: u64) -> u64 {
+    bytes / (1024 * 1024)
+}
+
+impl PlacementPlanner {
+    pub fn new(policy: PlacementPolicy) -> Self {
+        Self {
+            policy,
+            reserved: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn policy(&self) -> &PlacementPolicy {
+        &self.policy
+    }
+
+    /// Check `requirements` against `vitals` and, if they fit, reserve them for `agent_id`
+    pub fn try_place(&self, agent_id: Uuid, requirements: ResourceRequirements, vitals: &HardwareVitals) -> PlacementDecision {
+        let mut reserved = self.reserved.lock();
+        let decision = self.evaluate(&reserved, &requirements, vitals);
+        if decision == PlacementDecision::Place {
+            reserved.insert(
+                agent_id,
+                Reservation {
+                    requirements,
+                    placed_at: Instant::now(),
+                },
+            );
+        }
+        decision
+    }
+
+    /// Drop an agent's reservation (on dissolve or failed spawn)
+    pub fn release(&self, agent_id: Uuid) -> bool {
+        self.reserved.lock().remove(&agent_id).is_some()
+    }
+
+    pub fn reservations(&self) -> usize {
+        self.reserved.lock().len()
+    }
+
+    fn evaluate(
+        &self,
+        reserved: &HashMap<Uuid, Reservation>,
+        req: &ResourceRequirements,
+        vitals: &HardwareVitals,
+    ) -> PlacementDecision {
+        let defer = |reason: String| PlacementDecision::Defer { reason };
+        let reject = |reason: String| PlacementDecision::Reject { reason };
+
+        // Requirements the machine can never satisfy
+        if req.gpu && self.policy.gpu_slots == 0 {
+            return reject("needs a GPU but no GPU slots are configured".to_string());
+        }
+        let usable_memory = vitals.total_memory_bytes.saturating_sub(self.policy.memory_headroom_bytes);
+        if req.memory_bytes > usable_memory {
+            return reject(format!(
+                "needs {} MB memory, machine has {} MB usable",
+                mb(req.memory_bytes),
+                mb(usable_memory)
+            ));
+        }
+        if req.cpu_cores > vitals.cpu_cores as f32 {
+            return reject(format!("needs {} CPU cores, machine has {}", req.cpu_cores, vitals.cpu_cores));
+        }
+
+        // Requirements that don't fit right now
+        if vitals.disk_critical {
+            return defer("disk space critical".to_string());
+        }
+        if req.gpu {
+            let gpus_in_use = reserved.values().filter(|r| r.requirements.gpu).count();
+            if gpus_in_use >= self.policy.gpu_slots {
+                return defer(format!("all {} GPU slots in use", self.policy.gpu_slots));
+            }
+        }
+        let warming: u64 = reserved
+            .values()
+            .filter(|r| r.placed_at.elapsed() < self.policy.memory_warmup)
+            .map(|r| r.requirements.memory_bytes)
+            .sum();
+        let free_memory = vitals
+            .available_memory_bytes
+            .saturating_sub(warming)
+            .saturating_sub(self.policy.memory_headroom_bytes);
+        if req.memory_bytes > free_memory {
+            return defer(format!(
+                "memory pressure: needs {} MB, {} MB free after headroom and pending spawns",
+                mb(req.memory_bytes),
+                mb(free_memory)
+            ));
+        }
+        let cores_reserved: f32 = reserved.values().map(|r| r.requirements.cpu_cores).sum();
+        if cores_reserved + req.cpu_cores > vitals.cpu_cores as f32 {
+            return defer(format!(
+                "CPU oversubscribed: {:.1} of {} cores reserved",
+                cores_reserved, vitals.cpu_cores
+            ));
+        }
+        if vitals.cpu_usage_percent > self.policy.max_cpu_usage {
+            return defer(format!(
+                "CPU pressure: {:.0}% busy (limit {:.0}%)",
+                vitals.cpu_usage_percent, self.policy.max_cpu_usage
+            ));
+        }
+        PlacementDecision::Place
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GB: u64 = 1024 * 1024 * 1024;
+
+    fn vitals(available_gb: u64) -> HardwareVitals {
+        HardwareVitals {
+            cpu_cores: 8,
+            cpu_usage_percent: 20.0,
+            total_memory_bytes: 16 * GB,
+            available_memory_bytes: available_gb * GB,
+            disk_critical: false,
+        }
+    }
+
+    #[test]
+    fn test_places_until_memory_runs_out() {
+        let planner = PlacementPlanner::default();
+        let analyst = ResourceRequirements::new(1.0, 2048);
+
+        assert_eq!(planner.try_place(Uuid::new_v4(), analyst, &vitals(5)), PlacementDecision::Place);
+        assert_eq!(planner.try_place(Uuid::new_v4(), analyst, &vitals(5)), PlacementDecision::Place);
+        // 5 GB available - 4 GB warming - 0.5 GB headroom
+        assert!(matches!(
+            planner.try_place(Uuid::new_v4(), analyst, &vitals(5)),
+            PlacementDecision::Defer { reason } if reason.starts_with("memory pressure")
+        ));
+        assert_eq!(planner.reservations(), 2);
+    }
+
+    #[test]
+    fn test_rejects_impossible_and_defers_busy() {
+        let planner = PlacementPlanner::new(PlacementPolicy {
+            gpu_slots: 1,
+            ..Default::default()
+        });
+        let huge = ResourceRequirements::new(1.0, 32 * 1024);
+        assert!(matches!(planner.try_place(Uuid::new_v4(), huge, &vitals(12)), PlacementDecision::Reject { .. }));
+
+        let local_model = ResourceRequirements::new(2.0, 1024).with_gpu();
+        let first = Uuid::new_v4();
+        assert_eq!(planner.try_place(first, local_model, &vitals(12)), PlacementDecision::Place);
+        assert!(matches!(planner.try_place(Uuid::new_v4(), local_model, &vitals(12)), PlacementDecision::Defer { .. }));
+        planner.release(first);
+        assert_eq!(planner.try_place(Uuid::new_v4(), local_model, &vitals(12)), PlacementDecision::Place);
+
+        let mut full_disk = vitals(12);
+        full_disk.disk_critical = true;
+        assert_eq!(
+            planner.try_place(Uuid::new_v4(), ResourceRequirements::new(0.5, 256), &full_disk),
+            PlacementDecision::Defer { reason: "disk space critical".to_string() }
+        );
+        assert!(matches!(
+            PlacementPlanner::default().try_place(Uuid::new_v4(), local_model, &vitals(12)),
+            PlacementDecision::Reject { .. }
+        ));
+    }
+}
@@ -0,0 +1,70 @@
+//! Trace records for explaining agent output
+//!
+//! Agents record the prompts they sent, the replies and critiques they
+//! received, the tools they ran and the artifacts they produced, each tagged
+//! with the task id (and artifact id once there is one). Stored on the
+//! blackboard, these records tie an artifact or file change back to the
+//! task, intent and model calls that produced it.
+
+use crate::types::{AgentId, ArtifactId, TaskId};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Longest prompt excerpt kept in a trace record
+pub const MAX_TRACE_EXCERPT: usize = 500;
+
+/// What an agent did
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TraceKind {
+    /// A prompt sent to a model
+    Prompt { iteration: u8 },
+    /// The model's reply to the prompt of the same iteration
+    Response { iteration: u8 },
+    /// A tool the agent ran; the summary holds its parameters and result
+    ToolCall { tool: String, ok: bool },
+    /// A review of the previous attempt
+    Critique { iteration: u8, passed: bool, issues: Vec<String> },
+    /// An artifact handed in for review
+    Artifact { artifact_id: ArtifactId, file_path: Option<String> },
+}
+
+/// One step an agent took on a task
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TraceRecord {
+    pub timestamp: DateTime<Utc>,
+    pub task_id: TaskId,
+    pub agent_id: AgentId,
+    #[serde(flatten)]
+    pub kind: TraceKind,
+    /// Prompt excerpt, critique summary or artifact description
+    pub summary: String,
+}
+
+impl TraceRecord {
+    pub fn new(task_id: impl Into<TaskId>, agent_id: AgentId, kind: TraceKind, summary: impl Into<String>) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            task_id: task_id.into(),
+            agent_id,
+            kind,
+            summary: excerpt(&summary.into()),
+        }
+    }
+
+    /// Artifact id, for artifact records
+    pub fn artifact_id(&self) -> Option<&str> {
+        match &self.kind {
+            TraceKind::Artifact { artifact_id, .. } => Some(artifact_id),
+            _ => None,
+        }
+    }
+}
+
+/// First [`MAX_TRACE_EXCERPT`] characters of `text`
+fn excerpt(text: &str) -> String {
+    match text.char_indices().nth(MAX_TRACE_EXCERPT) {
+        Some((idx, _)) => format!("{}...", &text[..idx]),
+        None => text.to_string(),
+    }
+}
@@ -41,6 +41,9 @@ pub struct Task {
     pub context: Option<String>,
     pub constraints: Vec<String>,
     pub created_at: chrono::DateTime<chrono::Utc>,
+    /// Retrieval done for the task before dispatch
+    #[serde(default)]
+    pub context_pack: Option<ContextPack>,
 }
 
 impl Task {
@@ -52,6 +55,7 @@ impl Task {
             context: None,
             constraints: Vec::new(),
             created_at: chrono::Utc::now(),
+            context_pack: None,
         }
     }
 
@@ -66,6 +70,70 @@ impl Task {
         self.constraints.push(constraint.into());
         self
     }
+
+    /// Attach curated context; empty packs are dropped
+    pub fn with_context_pack(mut self, pack: ContextPack) -> Self {
+        self.context_pack = (!pack.is_empty()).then_some(pack);
+        self
+    }
+}
+
+/// Where a piece of task context came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContextSource {
+    KnowledgeGraph,
+    Decision,
+    File,
+}
+
+/// One piece of task context
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContextSnippet {
+    pub source: ContextSource,
+    /// Node name, decision summary or file path
+    pub title: String,
+    pub content: String,
+}
+
+/// Context assembled for a task under a token budget
+///
+/// Built by the Cortex before dispatch so agents start from the same curated
+/// knowledge-graph hits, decisions and files instead of each retrieving them.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ContextPack {
+    pub snippets: Vec<ContextSnippet>,
+    pub token_budget: usize,
+    pub estimated_tokens: usize,
+}
+
+impl ContextPack {
+    pub fn is_empty(&self) -> bool {
+        self.snippets.is_empty()
+    }
+
+    /// Prompt section listing the snippets by source
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for (source, heading) in [
+            (ContextSource::KnowledgeGraph, "Relevant code"),
+            (ContextSource::Decision, "Earlier decisions"),
+            (ContextSource::File, "Impacted files"),
+        ] {
+            let snippets: Vec<&ContextSnippet> = self.snippets.iter().filter(|s| s.source == source).collect();
+            if snippets.is_empty() {
+                continue;
+            }
+            out.push_str(&format!("{}:\n", heading));
+            for snippet in snippets {
+                match source {
+                    ContextSource::File => out.push_str(&format!("--- {}\n```\n{}\n```\n", snippet.title, snippet.content)),
+                    _ => out.push_str(&format!("- {}: {}\n", snippet.title, snippet.content)),
+                }
+            }
+        }
+        out
+    }
 }
 
 /// An artifact produced by an agent
@@ -77,6 +145,12 @@ pub struct Artifact {
     pub content: String,
     pub file_path: Option<String>,
     pub created_at: chrono::DateTime<chrono::Utc>,
+    /// How sure the producing agent is of the artifact (0.0-1.0)
+    #[serde(default)]
+    pub confidence: Option<Confidence>,
+    /// Reasoning strategy that produced the artifact (e.g. `reflexion-3`)
+    #[serde(default)]
+    pub strategy: Option<String>,
 }
 
 /// Type of artifact produced
@@ -101,6 +175,20 @@ impl Artifact {
             content,
             file_path,
             created_at: chrono::Utc::now(),
+            confidence: None,
+            strategy: None,
         }
     }
+
+    /// Attach a confidence score, clamped to 0.0-1.0
+    pub fn with_confidence(mut self, confidence: Confidence) -> Self {
+        self.confidence = Some(confidence.clamp(0.0, 1.0));
+        self
+    }
+
+    /// Record the reasoning strategy that produced the artifact
+    pub fn with_strategy(mut self, strategy: impl Into<String>) -> Self {
+        self.strategy = Some(strategy.into());
+        self
+    }
 }
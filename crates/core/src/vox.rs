@@ -1,7 +1,13 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
+use tokio::sync::broadcast::error::{RecvError, TryRecvError};
+use tokio::sync::{broadcast, mpsc};
 use uuid::Uuid;
 
+/// VOX priority from which messages take the guaranteed lane
+pub const PRIORITY_LANE_THRESHOLD: u8 = 200;
+
 /// Strictly typed SAGA message schemas to eliminate "Payload-Agnostic" risks.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -34,6 +40,21 @@ pub enum VoxPayload {
     Ack {
         result: String,
     },
+    /// Another payload, serialized and compressed for transport
+    ///
+    /// Produced for large payloads on the way into the blackboard; the MOM
+    /// watcher restores the original before delivery.
+    Compressed {
+        encoding: String,
+        original_bytes: usize,
+        /// Base64 of the compressed JSON payload
+        data: String,
+    },
+    /// Payload too large for the bus, stored in the blackboard ArtifactStore
+    ArtifactRef {
+        artifact_id: String,
+        original_bytes: usize,
+    },
 }
 
 /// VOX (Versatile Orchestration eXchange) Protocol Message
@@ -45,4 +66,131 @@ pub struct VoxMessage {
     pub correlation_id: Uuid,
     pub payload: VoxPayload,
     pub created_at: DateTime<Utc>,
+    /// Seconds after `created_at` the message stops being worth delivering
+    ///
+    /// Expired messages are archived instead of delivered. `None` never expires.
+    #[serde(default)]
+    pub ttl_secs: Option<u64>,
+    /// Producer-chosen key that stays the same when a message is re-posted
+    ///
+    /// Consumers skip a `(correlation_id, idempotency_key)` pair they have
+    /// already processed. `None` disables deduplication.
+    #[serde(default)]
+    pub idempotency_key: Option<String>,
+}
+
+impl VoxMessage {
+    pub fn with_ttl(mut self, ttl: std::time::Duration) -> Self {
+        self.ttl_secs = Some(ttl.as_secs());
+        self
+    }
+
+    /// When the message expires, if it has a TTL
+    pub fn expires_at(&self) -> Option<DateTime<Utc>> {
+        expiry(self.created_at, self.ttl_secs)
+    }
+
+    pub fn is_expired_at(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at().is_some_and(|expires| now >= expires)
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.is_expired_at(Utc::now())
+    }
+
+    pub fn with_idempotency_key(mut self, key: impl Into<String>) -> Self {
+        self.idempotency_key = Some(key.into());
+        self
+    }
+
+    /// Key consumers deduplicate on, if the message has an idempotency key
+    pub fn dedup_key(&self) -> Option<String> {
+        dedup_key(self.correlation_id, self.idempotency_key.as_deref())
+    }
+}
+
+/// `<correlation>/<idempotency key>`
+pub(crate) fn dedup_key(correlation: Uuid, idempotency_key: Option<&str>) -> Option<String> {
+    idempotency_key.map(|key| format!("{}/{}", correlation, key))
+}
+
+/// `created_at + ttl_secs`, saturating at the end of representable time
+pub(crate) fn expiry(created_at: DateTime<Utc>, ttl_secs: Option<u64>) -> Option<DateTime<Utc>> {
+    let ttl = chrono::Duration::try_seconds(i64::try_from(ttl_secs?).unwrap_or(i64::MAX)).unwrap_or(chrono::TimeDelta::MAX);
+    Some(created_at.checked_add_signed(ttl).unwrap_or(DateTime::<Utc>::MAX_UTC))
+}
+
+/// Two-lane subscription to a team's VOX traffic
+///
+/// Ordinary messages arrive over a bounded broadcast channel and can be lost
+/// to lag. Messages at or above [`PRIORITY_LANE_THRESHOLD`] arrive over a
+/// per-subscriber queue that never drops, and `recv` returns them first.
+///
+/// A receiver built [`with_replay`](Self::with_replay) hands out its
+/// historical messages, oldest first, before any live one.
+pub struct VoxReceiver {
+    priority: mpsc::UnboundedReceiver<VoxMessage>,
+    normal: broadcast::Receiver<VoxMessage>,
+    /// Historical messages not yet handed out
+    backlog: VecDeque<VoxMessage>,
+    /// `(correlation_id, created_at)` of replayed messages, so their live copies are skipped
+    replayed: HashSet<(Uuid, DateTime<Utc>)>,
+}
+
+impl VoxReceiver {
+    pub fn new(priority: mpsc::UnboundedReceiver<VoxMessage>, normal: broadcast::Receiver<VoxMessage>) -> Self {
+        Self { priority, normal, backlog: VecDeque::new(), replayed: HashSet::new() }
+    }
+
+    /// Deliver `history` before live traffic
+    ///
+    /// Subscribe before reading the history: a message posted in between is
+    /// then both replayed and received live, and the live copy is dropped.
+    pub fn with_replay(mut self, mut history: Vec<VoxMessage>) -> Self {
+        history.sort_by_key(|msg| msg.created_at);
+        self.replayed.extend(history.iter().map(replay_key));
+        self.backlog.extend(history);
+        self
+    }
+
+    /// Receive the next message, replay first, then priority lane (cancel safe)
+    ///
+    /// Only the ordinary lane reports `RecvError::Lagged`.
+    pub async fn recv(&mut self) -> Result<VoxMessage, RecvError> {
+        if let Some(msg) = self.backlog.pop_front() {
+            return Ok(msg);
+        }
+        loop {
+            let msg = match self.priority.try_recv() {
+                Ok(msg) => msg,
+                Err(_) => tokio::select! {
+                    biased;
+                    Some(msg) = self.priority.recv() => msg,
+                    result = self.normal.recv() => result?,
+                },
+            };
+            if !self.replayed.remove(&replay_key(&msg)) {
+                return Ok(msg);
+            }
+        }
+    }
+
+    pub fn try_recv(&mut self) -> Result<VoxMessage, TryRecvError> {
+        if let Some(msg) = self.backlog.pop_front() {
+            return Ok(msg);
+        }
+        loop {
+            let msg = match self.priority.try_recv() {
+                Ok(msg) => msg,
+                Err(_) => self.normal.try_recv()?,
+            };
+            if !self.replayed.remove(&replay_key(&msg)) {
+                return Ok(msg);
+            }
+        }
+    }
+}
+
+fn replay_key(msg: &VoxMessage) -> (Uuid, DateTime<Utc>) {
+    (msg.correlation_id, msg.created_at)
 }
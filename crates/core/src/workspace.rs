@@ -0,0 +1,328 @@
+//! Project workspace model
+//!
+//! Describes the packages of a project so work can be scoped to one member:
+//! Cargo workspaces (`[workspace] members`) and npm/yarn workspaces
+//! (`"workspaces"` in package.json). A plain single-package project is a
+//! workspace with one member at the root.
+//!
+//! Dependencies are kept only between members (inter-crate edges); external
+//! crates are not part of the model. Member globs support a trailing `*`
+//! (`crates/*`), which covers the common layouts.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, VecDeque};
+use std::path::{Component, Path, PathBuf};
+
+/// Build system a package belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PackageKind {
+    Cargo,
+    Npm,
+}
+
+/// One member package
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Package {
+    pub name: String,
+    /// Directory relative to the workspace root (empty for a root package)
+    pub path: PathBuf,
+    pub kind: PackageKind,
+    /// Other members this package depends on
+    pub dependencies: Vec<String>,
+    pub build_command: Vec<String>,
+    pub test_command: Vec<String>,
+}
+
+/// Packages of a project rooted at one directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectWorkspace {
+    pub root: PathBuf,
+    pub packages: Vec<Package>,
+}
+
+impl ProjectWorkspace {
+    /// Read the manifests under `root`; a project without any has no packages
+    pub fn discover(root: impl Into<PathBuf>) -> Result<Self> {
+        let root = root.into();
+        let mut packages = Vec::new();
+        if root.join("Cargo.toml").exists() {
+            packages.extend(discover_cargo(&root)?);
+        }
+        if root.join("package.json").exists() {
+            packages.extend(discover_npm(&root)?);
+        }
+        packages.sort_by(|a, b| a.path.cmp(&b.path).then_with(|| a.name.cmp(&b.name)));
+        Ok(Self { root, packages })
+    }
+
+    /// More than one member package
+    pub fn is_multi_package(&self) -> bool {
+        self.packages.len() > 1
+    }
+
+    pub fn package(&self, name: &str) -> Option<&Package> {
+        self.packages.iter().find(|p| p.name == name)
+    }
+
+    /// Member owning `path` (absolute or relative to the root); the deepest match wins
+    pub fn package_for_path(&self, path: impl AsRef<Path>) -> Option<&Package> {
+        let path = path.as_ref();
+        let relative = path.strip_prefix(&self.root).unwrap_or(path);
+        self.packages
+            .iter()
+            .filter(|p| relative.starts_with(&p.path))
+            .max_by_key(|p| p.path.components().count())
+    }
+
+    /// Members that depend on `name`, directly or transitively
+    pub fn dependents(&self, name: &str) -> Vec<&Package> {
+        let mut seen = BTreeSet::new();
+        let mut queue = VecDeque::from([name]);
+        while let Some(current) = queue.pop_front() {
+            for package in &self.packages {
+                if package.dependencies.iter().any(|d| d == current) && seen.insert(package.name.as_str()) {
+                    queue.push_back(package.name.as_str());
+                }
+            }
+        }
+        self.packages
+            .iter()
+            .filter(|p| seen.contains(p.name.as_str()) && p.name != name)
+            .collect()
+    }
+}
+
+fn read(path: &Path) -> Result<String> {
+    std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))
+}
+
+fn discover_cargo(root: &Path) -> Result<Vec<Package>> {
+    let manifest: toml::Value = read(&root.join("Cargo.toml"))?
+        .parse()
+        .with_context(|| format!("Invalid manifest {}", root.join("Cargo.toml").display()))?;
+
+    let mut dirs = Vec::new();
+    if manifest.get("package").is_some() {
+        dirs.push(PathBuf::new());
+    }
+    if let Some(workspace) = manifest.get("workspace") {
+        let excluded: Vec<PathBuf> = string_list(workspace.get("exclude")).into_iter().map(normalize).collect();
+        for pattern in string_list(workspace.get("members")) {
+            for dir in expand_member(root, &pattern, "Cargo.toml")? {
+                if !excluded.contains(&dir) && !dirs.contains(&dir) {
+                    dirs.push(dir);
+                }
+            }
+        }
+    }
+
+    let mut members = Vec::new();
+    for dir in dirs {
+        let path = root.join(&dir).join("Cargo.toml");
+        let manifest: toml::Value = read(&path)?
+            .parse()
+            .with_context(|| format!("Invalid manifest {}", path.display()))?;
+        let Some(name) = manifest.get("package").and_then(|p| p.get("name")).and_then(|n| n.as_str()) else {
+            continue;
+        };
+        let mut deps = Vec::new();
+        for table in ["dependencies", "dev-dependencies", "build-dependencies"] {
+            if let Some(entries) = manifest.get(table).and_then(|t| t.as_table()) {
+                for (key, spec) in entries {
+                    // `foo = { package = "bar" }` depends on `bar`
+                    let dep = spec.get("package").and_then(|p| p.as_str()).unwrap_or(key);
+                    deps.push(dep.to_string());
+                }
+            }
+        }
+        members.push((name.to_string(), dir, deps));
+    }
+    Ok(link(members, PackageKind::Cargo))
+}
+
+fn discover_npm(root: &Path) -> Result<Vec<Package>> {
+    let manifest: serde_json::Value = serde_json::from_str(&read(&root.join("package.json"))?)
+        .with_context(|| format!("Invalid manifest {}", root.join("package.json").display()))?;
+
+    let patterns = match manifest.get("workspaces") {
+        Some(serde_json::Value::Array(list)) => list.iter().filter_map(|v| v.as_str()).map(String::from).collect(),
+        // Yarn's `{ "packages": [...] }` form
+        Some(obj) => obj
+            .get("packages")
+            .and_then(|p| p.as_array())
+            .map(|list| list.iter().filter_map(|v| v.as_str()).map(String::from).collect())
+            .unwrap_or_default(),
+        None => Vec::new(),
+    };
+
+    let mut dirs = vec![PathBuf::new()];
+    for pattern in patterns {
+        for dir in expand_member(root, &pattern, "package.json")? {
+            if !dirs.contains(&dir) {
+                dirs.push(dir);
+            }
+        }
+    }
+
+    let mut members = Vec::new();
+    for dir in dirs {
+        let path = root.join(&dir).join("package.json");
+        let manifest: serde_json::Value =
+            serde_json::from_str(&read(&path)?).with_context(|| format!("Invalid manifest {}", path.display()))?;
+        let Some(name) = manifest.get("name").and_then(|n| n.as_str()) else {
+            continue;
+        };
+        let deps = ["dependencies", "devDependencies", "peerDependencies"]
+            .iter()
+            .filter_map(|table| manifest.get(*table).and_then(|t| t.as_object()))
+            .flat_map(|entries| entries.keys().cloned())
+            .collect();
+        members.push((name.to_string(), dir, deps));
+    }
+    Ok(link(members, PackageKind::Npm))
+}
+
+/// Keep only dependencies on other members and attach build/test commands
+fn link(members: Vec<(String, PathBuf, Vec<String>)>, kind: PackageKind) -> Vec<Package> {
+    let names: BTreeSet<String> = members.iter().map(|(name, _, _)| name.clone()).collect();
+    let multi = members.len() > 1;
+    members
+        .into_iter()
+        .map(|(name, path, deps)| {
+            let mut dependencies: Vec<String> =
+                deps.into_iter().filter(|d| names.contains(d) && *d != name).collect();
+            dependencies.sort();
+            dependencies.dedup();
+            let (build_command, test_command) = commands(kind, &name, &path, multi);
+            Package {
+                name,
+                path,
+                kind,
+                dependencies,
+                build_command,
+                test_command,
+            }
+        })
+        .collect()
+}
+
+fn commands(kind: PackageKind, name: &str, path: &Path, multi: bool) -> (Vec<String>, Vec<String>) {
+    let argv = |args: &[&str]| args.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+    match kind {
+        PackageKind::Cargo => (argv(&["cargo", "build", "-p", name]), argv(&["cargo", "test", "-p", name])),
+        PackageKind::Npm if multi && !path.as_os_str().is_empty() => {
+            let dir = path.to_string_lossy();
+            (
+                argv(&["npm", "run", "build", "--workspace", dir.as_ref()]),
+                argv(&["npm", "test", "--workspace", dir.as_ref()]),
+            )
+        }
+        PackageKind::Npm => (argv(&["npm", "run", "build"]), argv(&["npm", "test"])),
+    }
+}
+
+fn string_list(value: Option<&toml::Value>) -> Vec<String> {
+    value
+        .and_then(|v| v.as_array())
+        .map(|list| list.iter().filter_map(|v| v.as_str()).map(String::from).collect())
+        .unwrap_or_default()
+}
+
+/// Drop `./` and trailing separators so member paths compare equal
+fn normalize(path: impl AsRef<Path>) -> PathBuf {
+    path.as_ref()
+        .components()
+        .filter(|c| !matches!(c, Component::CurDir))
+        .collect()
+}
+
+/// Member directories for `pattern` that contain `manifest`
+fn expand_member(root: &Path, pattern: &str, manifest: &str) -> Result<Vec<PathBuf>> {
+    let Some(parent) = pattern.strip_suffix('*') else {
+        let dir = normalize(pattern);
+        return Ok(if root.join(&dir).join(manifest).exists() { vec![dir] } else { Vec::new() });
+    };
+
+    let base = root.join(parent);
+    if !base.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut dirs = Vec::new();
+    for entry in std::fs::read_dir(&base).with_context(|| format!("Failed to list {}", base.display()))? {
+        let entry = entry?;
+        if entry.path().join(manifest).exists() {
+            dirs.push(normalize(Path::new(parent).join(entry.file_name())));
+        }
+    }
+    dirs.sort();
+    Ok(dirs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(root: &Path, path: &str, content: &str) {
+        let path = root.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_cargo_workspace_members_and_edges() {
+        let root = std::env::temp_dir().join(format!("zed42-ws-{}", uuid::Uuid::new_v4()));
+        write(&root, "Cargo.toml", "[workspace]\nmembers = [\"crates/*\", \"tools/cli\"]\nexclude = [\"crates/old\"]\n");
+        write(&root, "crates/core/Cargo.toml", "[package]\nname = \"demo-core\"\n[dependencies]\nserde = \"1\"\n");
+        write(
+            &root,
+            "crates/api/Cargo.toml",
+            "[package]\nname = \"demo-api\"\n[dependencies]\ncore = { package = \"demo-core\", path = \"../core\" }\n",
+        );
+        write(&root, "crates/old/Cargo.toml", "[package]\nname = \"demo-old\"\n");
+        write(
+            &root,
+            "tools/cli/Cargo.toml",
+            "[package]\nname = \"demo-cli\"\n[dev-dependencies]\ndemo-api = { path = \"../../crates/api\" }\n",
+        );
+
+        let workspace = ProjectWorkspace::discover(&root).unwrap();
+        let names: Vec<&str> = workspace.packages.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["demo-api", "demo-core", "demo-cli"]);
+        assert_eq!(workspace.package("demo-api").unwrap().dependencies, vec!["demo-core"]);
+        assert_eq!(workspace.package("demo-core").unwrap().test_command, vec!["cargo", "test", "-p", "demo-core"]);
+
+        let owner = workspace.package_for_path(root.join("crates/api/src/lib.rs")).unwrap();
+        assert_eq!(owner.name, "demo-api");
+        assert!(workspace.package_for_path("README.md").is_none());
+
+        let dependents: Vec<&str> = workspace.dependents("demo-core").iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(dependents, vec!["demo-api", "demo-cli"]);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_npm_workspaces_and_single_package() {
+        let root = std::env::temp_dir().join(format!("zed42-ws-{}", uuid::Uuid::new_v4()));
+        write(&root, "package.json", "{\"name\": \"mono\", \"workspaces\": [\"packages/*\"]}");
+        write(&root, "packages/ui/package.json", "{\"name\": \"@mono/ui\", \"dependencies\": {\"@mono/utils\": \"*\", \"react\": \"18\"}}");
+        write(&root, "packages/utils/package.json", "{\"name\": \"@mono/utils\"}");
+
+        let workspace = ProjectWorkspace::discover(&root).unwrap();
+        assert!(workspace.is_multi_package());
+        let ui = workspace.package("@mono/ui").unwrap();
+        assert_eq!(ui.dependencies, vec!["@mono/utils"]);
+        assert_eq!(ui.test_command, vec!["npm", "test", "--workspace", "packages/ui"]);
+        // Files outside any member belong to the root package
+        assert_eq!(workspace.package_for_path("scripts/release.js").unwrap().name, "mono");
+        std::fs::remove_dir_all(&root).unwrap();
+
+        let single = std::env::temp_dir().join(format!("zed42-ws-{}", uuid::Uuid::new_v4()));
+        write(&single, "Cargo.toml", "[package]\nname = \"solo\"\n");
+        let workspace = ProjectWorkspace::discover(&single).unwrap();
+        assert!(!workspace.is_multi_package());
+        assert_eq!(workspace.package_for_path("src/main.rs").unwrap().name, "solo");
+        std::fs::remove_dir_all(&single).unwrap();
+    }
+}
@@ -0,0 +1,473 @@
+//! Architecture Decision Record sync
+//!
+//! Blackboard decisions significant enough to outlive a session are written
+//! to the repository as numbered ADRs (`docs/adr/0001-use-surrealdb.md`).
+//! The files are the source of truth from then on: on re-index every ADR,
+//! including ones written or edited by hand, is parsed back into a decision
+//! node in the knowledge graph.
+//!
+//! Significance comes from `rationale.significance` (0.0-1.0) when the
+//! deciding agent set it, otherwise from the decision type.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use zed42_blackboard::DecisionNode;
+use zed42_memory::knowledge_graph::{KnowledgeEdge, KnowledgeGraphMemory, KnowledgeNode};
+
+/// ADR directory relative to the repository root
+pub const ADR_DIR: &str = "docs/adr";
+
+/// Decisions at or above this significance become ADRs
+pub const DEFAULT_MIN_SIGNIFICANCE: f64 = 0.7;
+
+/// Significance of a blackboard decision
+pub fn significance(decision: &DecisionNode) -> f64 {
+    if let Some(value) = decision.rationale.get("significance").and_then(|v| v.as_f64()) {
+        return value.clamp(0.0, 1.0);
+    }
+    match decision.decision_type.as_str() {
+        "architecture" | "architectural" | "design" | "technology" => 0.8,
+        "plan_revision" | "model_recommendation" => 0.2,
+        _ => 0.5,
+    }
+}
+
+/// One ADR file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Adr {
+    pub number: u32,
+    pub title: String,
+    /// "Accepted", "Proposed", "Superseded by 0004", ...
+    pub status: String,
+    pub date: String,
+    /// Blackboard decision this ADR was exported from; empty for hand-written ADRs
+    pub decision_id: Option<String>,
+    /// Number of the ADR this one replaces
+    pub supersedes: Option<u32>,
+    pub context: String,
+    pub decision: String,
+    pub alternatives: Vec<String>,
+}
+
+impl Adr {
+    /// ADR for a blackboard decision
+    pub fn from_decision(number: u32, decision: &DecisionNode) -> Self {
+        let context = match &decision.rationale {
+            serde_json::Value::String(text) => text.clone(),
+            serde_json::Value::Object(fields) => fields
+                .iter()
+                .filter(|(key, _)| key.as_str() != "significance")
+                .map(|(key, value)| match value {
+                    serde_json::Value::String(text) => format!("- {}: {}", key, text),
+                    other => format!("- {}: {}", key, other),
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            serde_json::Value::Null => String::new(),
+            other => other.to_string(),
+        };
+        let title = decision.description.lines().next().unwrap_or_default().trim_end_matches('.').to_string();
+        Self {
+            number,
+            title,
+            status: "Accepted".to_string(),
+            date: chrono::DateTime::from_timestamp(decision.timestamp, 0)
+                .map(|d| d.format("%Y-%m-%d").to_string())
+                .unwrap_or_default(),
+            decision_id: Some(decision.id.clone()),
+            supersedes: None,
+            context,
+            decision: decision.description.clone(),
+            alternatives: decision.alternatives_considered.clone(),
+        }
+    }
+
+    /// `0003-use-surrealdb.md`
+    pub fn file_name(&self) -> String {
+        let mut slug = String::new();
+        for c in self.title.chars() {
+            if c.is_ascii_alphanumeric() {
+                slug.push(c.to_ascii_lowercase());
+            } else if !slug.ends_with('-') {
+                slug.push('-');
+            }
+        }
+        format!("{:04}-{}.md", self.number, slug.trim_matches('-'))
+    }
+
+    pub fn render(&self) -> String {
+        let mut md = String::new();
+        let _ = writeln!(md, "# {}. {}\n", self.number, self.title);
+        let _ = writeln!(md, "- Status: {}", self.status);
+        let _ = writeln!(md, "- Date: {}", self.date);
+        if let Some(ref id) = self.decision_id {
+            let _ = writeln!(md, "- Decision-Id: {}", id);
+        }
+        if let Some(number) = self.supersedes {
+            let _ = writeln!(md, "- Supersedes: {:04}", number);
+        }
+        let _ = writeln!(md, "\n## Context\n\n{}\n", or_none(&self.context));
+        let _ = writeln!(md, "## Decision\n\n{}\n", or_none(&self.decision));
+        if !self.alternatives.is_empty() {
+            let _ = writeln!(md, "## Alternatives Considered\n");
+            for alternative in &self.alternatives {
+                let _ = writeln!(md, "- {}", alternative);
+            }
+            md.push('\n');
+        }
+        md.truncate(md.trim_end().len());
+        md.push('\n');
+        md
+    }
+
+    /// Parse an ADR in the format [`Adr::render`] writes
+    ///
+    /// Unknown sections are kept in the context so hand edits are not lost.
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut lines = text.lines();
+        let heading = lines
+            .by_ref()
+            .find(|l| !l.trim().is_empty())
+            .context("ADR is empty")?;
+        let heading = heading.trim_start_matches('#').trim();
+        let (number, title) = heading
+            .split_once(". ")
+            .or_else(|| heading.split_once(": "))
+            .with_context(|| format!("ADR heading '{}' has no number", heading))?;
+        let number: u32 = number
+            .trim_start_matches(|c: char| !c.is_ascii_digit())
+            .parse()
+            .with_context(|| format!("ADR heading '{}' has no number", heading))?;
+
+        let mut adr = Adr {
+            number,
+            title: title.trim().to_string(),
+            status: "Accepted".to_string(),
+            date: String::new(),
+            decision_id: None,
+            supersedes: None,
+            context: String::new(),
+            decision: String::new(),
+            alternatives: Vec::new(),
+        };
+        let mut section: Option<String> = None;
+        let mut sections: Vec<(String, String)> = Vec::new();
+        for line in lines {
+            if let Some(name) = line.strip_prefix("## ") {
+                section = Some(name.trim().to_string());
+                sections.push((name.trim().to_string(), String::new()));
+                continue;
+            }
+            match section {
+                None => {
+                    let Some((key, value)) = line.trim_start_matches("- ").split_once(':') else {
+                        continue;
+                    };
+                    let value = value.trim();
+                    match key.trim().to_ascii_lowercase().as_str() {
+                        "status" => adr.status = value.to_string(),
+                        "date" => adr.date = value.to_string(),
+                        "decision-id" => adr.decision_id = Some(value.to_string()).filter(|v| !v.is_empty()),
+                        "supersedes" => adr.supersedes = value.parse().ok(),
+                        _ => {}
+                    }
+                }
+                Some(_) => {
+                    if let Some((_, body)) = sections.last_mut() {
+                        body.push_str(line);
+                        body.push('\n');
+                    }
+                }
+            }
+        }
+
+        let mut extra = Vec::new();
+        for (name, body) in sections {
+            let body = body.trim().to_string();
+            match name.to_ascii_lowercase().as_str() {
+                "context" => adr.context = body,
+                "decision" => adr.decision = body,
+                "alternatives considered" | "alternatives" => {
+                    adr.alternatives = body
+                        .lines()
+                        .filter_map(|l| l.trim().strip_prefix("- "))
+                        .map(str::to_string)
+                        .collect()
+                }
+                _ => extra.push(format!("## {}\n\n{}", name, body)),
+            }
+        }
+        if !extra.is_empty() {
+            adr.context = std::iter::once(adr.context).chain(extra).filter(|s| !s.is_empty()).collect::<Vec<_>>().join("\n\n");
+        }
+        if adr.context == "None." {
+            adr.context.clear();
+        }
+        if adr.decision.is_empty() || adr.decision == "None." {
+            bail!("ADR {:04} has no Decision section", adr.number);
+        }
+        Ok(adr)
+    }
+
+    /// Knowledge-graph node id of this ADR
+    pub fn node_id(&self) -> String {
+        format!("adr:{:04}", self.number)
+    }
+}
+
+fn or_none(text: &str) -> &str {
+    if text.trim().is_empty() {
+        "None."
+    } else {
+        text.trim()
+    }
+}
+
+/// What a sync changed
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AdrSyncReport {
+    /// ADR files written for new decisions
+    pub exported: Vec<PathBuf>,
+    /// ADRs whose decision node was created or refreshed
+    pub imported: usize,
+}
+
+/// Keeps blackboard decisions, ADR files and decision nodes in step
+pub struct AdrSync {
+    dir: PathBuf,
+    min_significance: f64,
+}
+
+impl AdrSync {
+    pub fn new(repo_root: &Path) -> Self {
+        Self {
+            dir: repo_root.join(ADR_DIR),
+            min_significance: DEFAULT_MIN_SIGNIFICANCE,
+        }
+    }
+
+    pub fn with_min_significance(mut self, min_significance: f64) -> Self {
+        self.min_significance = min_significance;
+        self
+    }
+
+    /// Every parseable ADR in the directory, by number
+    pub fn read_all(&self) -> Result<Vec<(PathBuf, Adr)>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut adrs = Vec::new();
+        for entry in std::fs::read_dir(&self.dir).with_context(|| format!("Failed to list {}", self.dir.display()))? {
+            let path = entry?.path();
+            let is_adr = path.extension().is_some_and(|e| e == "md")
+                && path.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.starts_with(|c: char| c.is_ascii_digit()));
+            if !is_adr {
+                continue;
+            }
+            let text = std::fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+            match Adr::parse(&text) {
+                Ok(adr) => adrs.push((path, adr)),
+                Err(e) => tracing::warn!(path = %path.display(), error = %e, "Skipping unparseable ADR"),
+            }
+        }
+        adrs.sort_by_key(|(_, adr)| adr.number);
+        Ok(adrs)
+    }
+
+    /// Write an ADR for each significant decision that has none yet
+    ///
+    /// A decision whose parent already has an ADR supersedes it; the older
+    /// ADR's status is updated in place.
+    pub fn export(&self, decisions: &[DecisionNode]) -> Result<Vec<PathBuf>> {
+        let mut existing = self.read_all()?;
+        let mut by_decision: HashMap<String, u32> = existing
+            .iter()
+            .filter_map(|(_, adr)| adr.decision_id.clone().map(|id| (id, adr.number)))
+            .collect();
+        let mut next = existing.iter().map(|(_, adr)| adr.number).max().unwrap_or(0) + 1;
+
+        let mut decisions: Vec<&DecisionNode> = decisions
+            .iter()
+            .filter(|d| significance(d) >= self.min_significance && !by_decision.contains_key(&d.id))
+            .collect();
+        decisions.sort_by_key(|d| d.timestamp);
+        if decisions.is_empty() {
+            return Ok(Vec::new());
+        }
+        std::fs::create_dir_all(&self.dir).with_context(|| format!("Failed to create {}", self.dir.display()))?;
+
+        let mut written = Vec::new();
+        for decision in decisions {
+            let mut adr = Adr::from_decision(next, decision);
+            adr.supersedes = decision.parent_decision.as_ref().and_then(|p| by_decision.get(p)).copied();
+            if let Some(old) = adr.supersedes {
+                if let Some((path, old_adr)) = existing.iter_mut().find(|(_, a)| a.number == old) {
+                    old_adr.status = format!("Superseded by {:04}", adr.number);
+                    std::fs::write(&*path, old_adr.render())
+                        .with_context(|| format!("Failed to write {}", path.display()))?;
+                }
+            }
+            let path = self.dir.join(adr.file_name());
+            std::fs::write(&path, adr.render()).with_context(|| format!("Failed to write {}", path.display()))?;
+            tracing::info!(adr = adr.number, decision = %decision.id, "Exported decision as ADR");
+            by_decision.insert(decision.id.clone(), adr.number);
+            existing.push((path.clone(), adr));
+            written.push(path);
+            next += 1;
+        }
+        Ok(written)
+    }
+
+    /// Parse every ADR back into a decision node, replacing nodes whose ADR changed
+    pub async fn import(&self, graph: &KnowledgeGraphMemory) -> Result<usize> {
+        let adrs = self.read_all()?;
+        let now = chrono::Utc::now().timestamp();
+        let mut imported = 0;
+        for (path, adr) in &adrs {
+            let content = adr.render();
+            let id = adr.node_id();
+            let created_at = match graph.get_node(&id).await? {
+                Some(node) if node.content == content => continue,
+                Some(node) => {
+                    graph.delete_node(&id).await?;
+                    node.created_at
+                }
+                None => now,
+            };
+            let metadata = serde_json::json!({
+                "adr": adr.number,
+                "status": adr.status,
+                "path": path.file_name().and_then(|n| n.to_str()).map(|n| format!("{}/{}", ADR_DIR, n)),
+                "decision_id": adr.decision_id,
+            });
+            graph
+                .insert_node(KnowledgeNode {
+                    id: id.clone(),
+                    node_type: "decision".to_string(),
+                    name: adr.title.clone(),
+                    content,
+                    embedding: None,
+                    metadata: metadata.to_string(),
+                    created_at,
+                    updated_at: now,
+                })
+                .await?;
+            imported += 1;
+        }
+
+        // Supersedes edges are rebuilt for replaced nodes (deletion drops them)
+        for (_, adr) in &adrs {
+            let Some(old) = adr.supersedes else {
+                continue;
+            };
+            let (from_id, to_id) = (adr.node_id(), format!("adr:{:04}", old));
+            let edge_id = format!("{}->{}", from_id, to_id);
+            let exists = graph
+                .get_edges_by_type(zed42_memory::knowledge_graph::EdgeType::Supersedes)
+                .await?
+                .iter()
+                .any(|e| e.from_id == from_id && e.to_id == to_id);
+            if !exists && graph.get_node(&to_id).await?.is_some() {
+                graph
+                    .insert_edge(KnowledgeEdge {
+                        id: edge_id,
+                        edge_type: "supersedes".to_string(),
+                        from_id,
+                        to_id,
+                        metadata: None,
+                        created_at: now,
+                    })
+                    .await?;
+            }
+        }
+        Ok(imported)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decision(id: &str, description: &str, parent: Option<&str>, timestamp: i64) -> DecisionNode {
+        DecisionNode {
+            id: id.to_string(),
+            decision_type: "architecture".to_string(),
+            description: description.to_string(),
+            made_by: uuid::Uuid::nil(),
+            rationale: serde_json::json!({ "reason": "embedded and multi-model" }),
+            alternatives_considered: vec!["Postgres".to_string()],
+            timestamp,
+            parent_decision: parent.map(str::to_string),
+            confidence: None,
+        }
+    }
+
+    #[test]
+    fn test_render_parse_round_trip() {
+        let mut adr = Adr::from_decision(3, &decision("d1", "Use SurrealDB for the blackboard", None, 0));
+        adr.supersedes = Some(1);
+        assert_eq!(adr.file_name(), "0003-use-surrealdb-for-the-blackboard.md");
+        let text = adr.render();
+        assert!(text.starts_with("# 3. Use SurrealDB for the blackboard\n\n- Status: Accepted\n- Date: 1970-01-01\n"));
+        assert_eq!(Adr::parse(&text).unwrap(), adr);
+
+        // Human edits: extra sections are kept, missing metadata defaults
+        let edited = "# 7. Cache embeddings\n\n- Status: Proposed\n\n## Decision\n\nCache on disk.\n\n## Consequences\n\nFaster restarts.\n";
+        let parsed = Adr::parse(edited).unwrap();
+        assert_eq!(parsed.number, 7);
+        assert_eq!(parsed.status, "Proposed");
+        assert_eq!(parsed.decision_id, None);
+        assert_eq!(parsed.context, "## Consequences\n\nFaster restarts.");
+        assert!(Adr::parse("# 8. Nothing decided\n").is_err());
+    }
+
+    #[test]
+    fn test_export_filters_and_supersedes() {
+        let dir = tempfile::tempdir().unwrap();
+        let sync = AdrSync::new(dir.path());
+        let mut minor = decision("d0", "Rename a variable", None, 0);
+        minor.decision_type = "refactor".to_string();
+
+        let written = sync
+            .export(&[minor, decision("d1", "Use SurrealDB", None, 1)])
+            .unwrap();
+        assert_eq!(written.len(), 1);
+        assert!(written[0].ends_with("0001-use-surrealdb.md"));
+
+        let written = sync
+            .export(&[decision("d1", "Use SurrealDB", None, 1), decision("d2", "Use SQLite", Some("d1"), 2)])
+            .unwrap();
+        assert_eq!(written.len(), 1);
+        let adrs = sync.read_all().unwrap();
+        assert_eq!(adrs[0].1.status, "Superseded by 0002");
+        assert_eq!(adrs[1].1.supersedes, Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_import_picks_up_human_edits() {
+        let dir = tempfile::tempdir().unwrap();
+        let sync = AdrSync::new(dir.path());
+        sync.export(&[decision("d1", "Use SurrealDB", None, 1), decision("d2", "Use SQLite", Some("d1"), 2)])
+            .unwrap();
+        let graph = KnowledgeGraphMemory::new(&dir.path().join("kg"), "adr", None).await.unwrap();
+        assert_eq!(sync.import(&graph).await.unwrap(), 2);
+        assert_eq!(sync.import(&graph).await.unwrap(), 0);
+
+        let path = dir.path().join(ADR_DIR).join("0002-use-sqlite.md");
+        let text = std::fs::read_to_string(&path).unwrap().replace("Use SQLite", "Use SQLite in WAL mode");
+        std::fs::write(&path, text).unwrap();
+        assert_eq!(sync.import(&graph).await.unwrap(), 1);
+
+        let node = graph.get_node("adr:0002").await.unwrap().unwrap();
+        assert_eq!(node.name, "Use SQLite in WAL mode");
+        let edges = graph
+            .get_edges_by_type(zed42_memory::knowledge_graph::EdgeType::Supersedes)
+            .await
+            .unwrap();
+        assert_eq!(edges.len(), 1);
+        assert_eq!(edges[0].to_id, "adr:0001");
+    }
+}
@@ -0,0 +1,168 @@
+//! Affinity-based task assignment
+//!
+//! With several agents of the right type running, a task goes to the one
+//! that already knows its ground: the agent that produced a parent task's
+//! artifact, or the one that touched the files the task is about. That agent
+//! has the context loaded already, and keeping a file with one agent avoids
+//! two agents editing it concurrently. The files a task is about are the
+//! knowledge-graph hits for its description, plus any touched path the
+//! description names. Without any affinity the lowest agent id wins, as
+//! before.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use zed42_core::types::{AgentId, Artifact};
+use zed42_memory::knowledge_graph::{KnowledgeGraphMemory, SearchQuery};
+
+/// Weight of having produced the artifact of a task this one depends on
+pub const PARENT_AFFINITY: f32 = 2.0;
+
+/// Weight of each relevant file the agent touched
+pub const FILE_AFFINITY: f32 = 1.0;
+
+/// Knowledge-graph hits used to find a task's files
+const GRAPH_HITS: usize = 8;
+
+/// Files each agent has touched this session
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AgentHistory {
+    files: BTreeMap<AgentId, BTreeSet<String>>,
+}
+
+impl AgentHistory {
+    /// Remember the file an artifact of `agent_id` writes, if any
+    pub fn record_artifact(&mut self, agent_id: AgentId, artifact: &Artifact) {
+        if let Some(path) = &artifact.file_path {
+            self.record_file(agent_id, path);
+        }
+    }
+
+    pub fn record_file(&mut self, agent_id: AgentId, path: &str) {
+        self.files.entry(agent_id).or_default().insert(path.to_string());
+    }
+
+    pub fn files(&self, agent_id: AgentId) -> impl Iterator<Item = &String> {
+        self.files.get(&agent_id).into_iter().flatten()
+    }
+
+    /// Drop a dissolved agent's history
+    pub fn forget(&mut self, agent_id: AgentId) {
+        self.files.remove(&agent_id);
+    }
+}
+
+/// What a task is about, for matching against agent history
+#[derive(Debug, Clone, Default)]
+pub struct TaskFootprint {
+    pub description: String,
+    /// Agents that handled the tasks this one depends on
+    pub parent_agents: Vec<AgentId>,
+    /// Files behind the knowledge-graph hits for the description
+    pub files: BTreeSet<String>,
+}
+
+impl TaskFootprint {
+    pub fn new(description: impl Into<String>) -> Self {
+        Self {
+            description: description.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_parent_agents(mut self, parent_agents: Vec<AgentId>) -> Self {
+        self.parent_agents = parent_agents;
+        self
+    }
+
+    /// Add the files of the knowledge-graph nodes matching the description
+    pub async fn with_graph_files(mut self, graph: &KnowledgeGraphMemory) -> Self {
+        let query = SearchQuery::Semantic {
+            query_text: self.description.clone(),
+            top_k: GRAPH_HITS,
+            node_types: None,
+        };
+        match graph.search(query).await {
+            Ok(hits) => self
+                .files
+                .extend(hits.iter().filter_map(|hit| zed42_toolboxes::graph::node_path(&hit.node))),
+            Err(e) => tracing::warn!("Affinity: knowledge graph search failed: {:#}", e),
+        }
+        self
+    }
+
+    fn is_about(&self, path: &str) -> bool {
+        self.files.contains(path) || self.description.contains(path)
+    }
+}
+
+/// Why an agent was picked for a task
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Affinity {
+    pub agent_id: AgentId,
+    pub score: f32,
+    /// Parent tasks' producer and touched files behind the score
+    pub reasons: Vec<String>,
+}
+
+/// Affinity of `agent_id` for the task
+pub fn score(agent_id: AgentId, footprint: &TaskFootprint, history: &AgentHistory) -> Affinity {
+    let mut affinity = Affinity { agent_id, score: 0.0, reasons: Vec::new() };
+    if footprint.parent_agents.contains(&agent_id) {
+        affinity.score += PARENT_AFFINITY;
+        affinity.reasons.push("produced a parent artifact".to_string());
+    }
+    for path in history.files(agent_id).filter(|path| footprint.is_about(path)) {
+        affinity.score += FILE_AFFINITY;
+        affinity.reasons.push(format!("touched {}", path));
+    }
+    affinity
+}
+
+/// The candidate with the highest affinity; ties go to the lowest agent id
+pub fn choose(candidates: &[AgentId], footprint: &TaskFootprint, history: &AgentHistory) -> Option<Affinity> {
+    let mut sorted = candidates.to_vec();
+    sorted.sort();
+    sorted
+        .into_iter()
+        .map(|agent_id| score(agent_id, footprint, history))
+        .fold(None, |best: Option<Affinity>, next| match best {
+            Some(best) if best.score >= next.score => Some(best),
+            _ => Some(next),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_parent_producer_and_file_history_win() {
+        let mut ids = [Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4()];
+        ids.sort();
+        let [first, second, third] = ids;
+
+        let mut history = AgentHistory::default();
+        history.record_artifact(third, &Artifact::code("t1".to_string(), String::new(), Some("src/auth.rs".to_string())));
+        history.record_file(third, "src/session.rs");
+        history.record_file(second, "src/db.rs");
+
+        // No affinity: lowest id, as before
+        let plain = TaskFootprint::new("Write the changelog");
+        assert_eq!(choose(&ids, &plain, &history).unwrap().agent_id, first);
+
+        // Files named in the description or found in the graph
+        let mut footprint = TaskFootprint::new("Harden token checks in src/auth.rs");
+        footprint.files.insert("src/session.rs".to_string());
+        let chosen = choose(&ids, &footprint, &history).unwrap();
+        assert_eq!(chosen.agent_id, third);
+        assert_eq!(chosen.score, 2.0 * FILE_AFFINITY);
+
+        // Producing the parent artifact outweighs a single touched file
+        let footprint = TaskFootprint::new("Migrate src/db.rs").with_parent_agents(vec![first]);
+        assert_eq!(choose(&ids, &footprint, &history).unwrap().agent_id, first);
+
+        history.forget(third);
+        assert_eq!(history.files(third).count(), 0);
+    }
+}
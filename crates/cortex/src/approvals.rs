@@ -0,0 +1,301 @@
+//! Approval prompts for dangerous tool calls
+//!
+//! Runs whose preset has the `dangerous_tools` gate hand their tool
+//! executors the session's [`ApprovalDesk`]: executors come from
+//! [`Cortex::tool_executor`](crate::Cortex::tool_executor), which attaches
+//! it through [`Cortex::approver_for`](crate::Cortex::approver_for). Each request is
+//! published on the event bus as `approval_requested`, with an
+//! `approval_required` alert, and to [`ApprovalDesk::subscribe`] receivers
+//! such as the desktop dialog. Whoever answers calls [`ApprovalDesk::resolve`].
+//! Unanswered requests are denied after the desk's timeout.
+//!
+//! Every decision is kept in the desk's history and, with a ledger
+//! attached, recorded as an audit entry. "Always allow for this session"
+//! adds a [`SessionRule`] that answers later matching calls without asking.
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, oneshot};
+use zed42_core::types::SessionId;
+use zed42_ledger::IntelligenceLedger;
+
+pub use zed42_toolboxes::approval::{ApprovalDecision, ApprovalRequest};
+use zed42_toolboxes::Approver;
+
+use crate::dashboard::{AlertView, DashboardEvent, EventBus};
+
+/// How long a request waits before it is denied
+pub const DEFAULT_APPROVAL_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Actor recorded for decisions made by a session rule
+pub const SESSION_RULE_ACTOR: &str = "session_rule";
+
+/// Actor recorded for requests nobody answered in time
+pub const TIMEOUT_ACTOR: &str = "timeout";
+
+/// Requests buffered per subscriber
+const SUBSCRIBER_CAPACITY: usize = 64;
+
+/// Calls allowed without asking for the rest of the session
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionRule {
+    pub tool: String,
+    pub scope: String,
+}
+
+impl SessionRule {
+    pub fn matches(&self, request: &ApprovalRequest) -> bool {
+        self.tool == request.tool && self.scope == request.scope
+    }
+}
+
+/// A decision on a request, and who made it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApprovalRecord {
+    pub request: ApprovalRequest,
+    pub decision: ApprovalDecision,
+    pub actor: String,
+    pub decided_at: DateTime<Utc>,
+}
+
+struct PendingApproval {
+    request: ApprovalRequest,
+    reply: oneshot::Sender<ApprovalDecision>,
+}
+
+/// Open approval requests of a session, shared between its agents and whoever answers
+pub struct ApprovalDesk {
+    session_id: SessionId,
+    pending: Mutex<HashMap<String, PendingApproval>>,
+    rules: Mutex<Vec<SessionRule>>,
+    history: Mutex<Vec<ApprovalRecord>>,
+    requests: broadcast::Sender<ApprovalRequest>,
+    timeout: Duration,
+    events: Option<Arc<EventBus>>,
+    ledger: Option<Arc<IntelligenceLedger>>,
+}
+
+impl ApprovalDesk {
+    pub fn new(session_id: SessionId) -> Self {
+        let (requests, _) = broadcast::channel(SUBSCRIBER_CAPACITY);
+        Self {
+            session_id,
+            pending: Mutex::new(HashMap::new()),
+            rules: Mutex::new(Vec::new()),
+            history: Mutex::new(Vec::new()),
+            requests,
+            timeout: DEFAULT_APPROVAL_TIMEOUT,
+            events: None,
+            ledger: None,
+        }
+    }
+
+    /// Deny requests nobody answers within `timeout`
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Publish requests and decisions on the dashboard event bus
+    pub fn with_events(mut self, events: Arc<EventBus>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Record decisions as ledger audit entries
+    pub fn with_ledger(mut self, ledger: Arc<IntelligenceLedger>) -> Self {
+        self.ledger = Some(ledger);
+        self
+    }
+
+    /// Ledger entity decisions are audited under
+    pub fn audit_entity(&self) -> String {
+        format!("approvals:{}", self.session_id)
+    }
+
+    /// Requests as they are opened
+    pub fn subscribe(&self) -> broadcast::Receiver<ApprovalRequest> {
+        self.requests.subscribe()
+    }
+
+    /// Requests waiting for an answer, oldest first
+    pub fn pending(&self) -> Vec<ApprovalRequest> {
+        let mut pending: Vec<ApprovalRequest> =
+            self.pending.lock().values().map(|p| p.request.clone()).collect();
+        pending.sort_by_key(|request| request.requested_at);
+        pending
+    }
+
+    pub fn rules(&self) -> Vec<SessionRule> {
+        self.rules.lock().clone()
+    }
+
+    /// Stop allowing calls under `rule` without asking; false if there was no such rule
+    pub fn revoke_rule(&self, rule: &SessionRule) -> bool {
+        let mut rules = self.rules.lock();
+        let before = rules.len();
+        rules.retain(|r| r != rule);
+        rules.len() != before
+    }
+
+    /// Decisions made this session, oldest first
+    pub fn history(&self) -> Vec<ApprovalRecord> {
+        self.history.lock().clone()
+    }
+
+    /// Answer an open request
+    pub async fn resolve(&self, request_id: &str, decision: ApprovalDecision, actor: &str) -> Result<ApprovalRecord> {
+        let pending = self
+            .pending
+            .lock()
+            .remove(request_id)
+            .ok_or_else(|| anyhow!("No open approval request {}", request_id))?;
+        if decision == ApprovalDecision::AllowForSession {
+            let rule = SessionRule { tool: pending.request.tool.clone(), scope: pending.request.scope.clone() };
+            let mut rules = self.rules.lock();
+            if !rules.contains(&rule) {
+                rules.push(rule);
+            }
+        }
+        let record = self.record(pending.request, decision, actor).await;
+        pending
+            .reply
+            .send(decision)
+            .map_err(|_| anyhow!("Approval request {} is no longer waiting", request_id))?;
+        Ok(record)
+    }
+
+    async fn record(&self, request: ApprovalRequest, decision: ApprovalDecision, actor: &str) -> ApprovalRecord {
+        let record = ApprovalRecord { request, decision, actor: actor.to_string(), decided_at: Utc::now() };
+        tracing::info!(
+            request = %record.request.id,
+            tool = %record.request.tool,
+            decision = ?decision,
+            actor,
+            "Approval decided"
+        );
+        if let Some(events) = &self.events {
+            events.publish(DashboardEvent::ApprovalResolved {
+                request_id: record.request.id.clone(),
+                decision,
+                actor: actor.to_string(),
+            });
+        }
+        if let Some(ledger) = &self.ledger {
+            let verb = match decision {
+                ApprovalDecision::Allow => "allowed",
+                ApprovalDecision::AllowForSession => "allowed for the session",
+                ApprovalDecision::Deny => "denied",
+            };
+            let details = format!(
+                "{} call {} by {}: {} {}",
+                record.request.tool, verb, actor, record.request.reason, record.request.params
+            );
+            if let Err(e) = ledger.record_audit(&self.audit_entity(), details).await {
+                tracing::warn!(error = %e, "Failed to record approval decision in the ledger");
+            }
+        }
+        self.history.lock().push(record.clone());
+        record
+    }
+}
+
+#[async_trait]
+impl Approver for ApprovalDesk {
+    async fn decide(&self, request: ApprovalRequest) -> ApprovalDecision {
+        if self.rules.lock().iter().any(|rule| rule.matches(&request)) {
+            self.record(request, ApprovalDecision::Allow, SESSION_RULE_ACTOR).await;
+            return ApprovalDecision::Allow;
+        }
+
+        let request_id = request.id.clone();
+        let (reply, rx) = oneshot::channel();
+        self.pending
+            .lock()
+            .insert(request_id.clone(), PendingApproval { request: request.clone(), reply });
+        if let Some(events) = &self.events {
+            events.publish(DashboardEvent::Alert {
+                alert: AlertView {
+                    action: "approval_required".to_string(),
+                    agent_id: None,
+                    reason: request.reason.clone(),
+                    at: request.requested_at,
+                },
+            });
+            events.publish(DashboardEvent::ApprovalRequested { request: request.clone() });
+        }
+        // No subscriber is fine: the window can still answer from the event bus
+        let _ = self.requests.send(request);
+
+        match tokio::time::timeout(self.timeout, rx).await {
+            Ok(Ok(decision)) => decision,
+            _ => {
+                let expired = self.pending.lock().remove(&request_id);
+                if let Some(pending) = expired {
+                    tracing::warn!(request = %request_id, "Approval request timed out; denying");
+                    drop(pending.reply);
+                    self.record(pending.request, ApprovalDecision::Deny, TIMEOUT_ACTOR).await;
+                }
+                ApprovalDecision::Deny
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use zed42_toolboxes::ApprovalNeed;
+
+    fn push() -> ApprovalRequest {
+        ApprovalRequest::new(
+            "execute_command",
+            json!({ "command": "git", "args": ["push"] }),
+            ApprovalNeed::new("Runs `git push`", "git push"),
+            None,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_decisions_rules_and_timeouts() {
+        let events = Arc::new(EventBus::new());
+        let desk = Arc::new(
+            ApprovalDesk::new(uuid::Uuid::new_v4())
+                .with_events(events.clone())
+                .with_timeout(Duration::from_millis(50)),
+        );
+        let mut requests = desk.subscribe();
+
+        let asking = desk.clone();
+        let decision = tokio::spawn(async move { asking.decide(push()).await });
+        let request = requests.recv().await.unwrap();
+        assert_eq!(desk.pending().len(), 1);
+        assert!(events.snapshot().approvals.contains_key(&request.id));
+
+        let record = desk.resolve(&request.id, ApprovalDecision::AllowForSession, "alice").await.unwrap();
+        assert_eq!(record.actor, "alice");
+        assert_eq!(decision.await.unwrap(), ApprovalDecision::AllowForSession);
+        assert!(desk.pending().is_empty());
+        assert!(events.snapshot().approvals.is_empty());
+        assert!(desk.resolve(&request.id, ApprovalDecision::Deny, "alice").await.is_err());
+
+        // The session rule answers the next push without asking
+        assert_eq!(desk.decide(push()).await, ApprovalDecision::Allow);
+        assert_eq!(desk.history().last().unwrap().actor, SESSION_RULE_ACTOR);
+
+        // Without the rule, nobody answers and the call is denied
+        let rule = SessionRule { tool: "execute_command".to_string(), scope: "git push".to_string() };
+        assert!(desk.revoke_rule(&rule));
+        assert_eq!(desk.decide(push()).await, ApprovalDecision::Deny);
+        assert_eq!(desk.history().last().unwrap().actor, TIMEOUT_ACTOR);
+        assert_eq!(desk.history().len(), 3);
+        assert!(desk.pending().is_empty());
+    }
+}
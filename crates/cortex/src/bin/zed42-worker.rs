@@ -0,0 +1,32 @@
+//! Agent worker process
+//!
+//! Started by the Cortex in worker mode; configuration arrives through the
+//! `ZED42_WORKER_*` environment variables (see `zed42_cortex::workers`).
+//!
+//! With `ZED42_REMOTE_WORKER_CONFIG` pointing at a TOML file the process
+//! instead joins a remote Cortex as a worker host (see
+//! `zed42_cortex::remote`). Exits cleanly on SIGTERM or Ctrl-C.
+
+use zed42_core::CancellationToken;
+use zed42_cortex::remote::{RemoteWorker, RemoteWorkerConfig};
+use zed42_cortex::workers::{run_worker, WorkerEnv};
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .init();
+
+    let shutdown = CancellationToken::new();
+    let token = shutdown.clone();
+    tokio::spawn(async move {
+        zed42_cortex::interrupt::signal().await;
+        token.cancel();
+    });
+
+    if let Ok(path) = std::env::var("ZED42_REMOTE_WORKER_CONFIG") {
+        let config = RemoteWorkerConfig::load(path.as_ref())?;
+        return RemoteWorker::connect(config).await?.run(shutdown).await;
+    }
+    run_worker(WorkerEnv::from_env()?, shutdown).await
+}
@@ -0,0 +1,409 @@
+//! ZED42 command line
+//!
+//! `zed42 top` is a live terminal dashboard of a running Cortex: agents with
+//! their status and current task, message throughput, circuit breaker
+//! states and budget burn-down. It reads the dashboard stream the Cortex
+//! serves (see `zed42_cortex::dashboard`), at `--addr`, `ZED42_DASHBOARD_ADDR`
+//! or `127.0.0.1:4242`.
+//!
+//! `zed42 init` inspects the repository at `PATH` (default: the current
+//! directory) and writes an editable starting profile to `.zed42/` (see
+//! `zed42_cortex::bootstrap`). Existing files are kept unless `--force`.
+//!
+//! `zed42 run` plans `INTENT` in the repository at `--root` (default: the
+//! current directory) with the OpenRouter planner (`OPENROUTER_API_KEY`) and
+//! runs the session until every task is done or failed. Ctrl-C (or SIGTERM)
+//! checkpoints the session instead (see `zed42_cortex::interrupt`) and prints
+//! the command that resumes it.
+//!
+//! `zed42 resume` continues the session a Ctrl-C interrupted (the latest one,
+//! or `SESSION`) from its snapshot under `--root`: its plan is restored, its
+//! unfinished tasks are queued again and agents are started for them. It
+//! runs, and can be interrupted, like `zed42 run`.
+//!
+//! Both keep the blackboard under `<root>/.zed42/blackboard`, note the
+//! session in `<root>/.zed42/last_session` and reach the MOM substrate at
+//! `ZED42_MOM_ADDR` (default `ws://localhost:8000`). With a
+//! `<root>/config/notify.toml`, its webhook and email channels are notified
+//! of the run finishing, budget thresholds and approval requests.
+//!
+//! `zed42 recommend` suggests model profile changes from the routing logs
+//! and critiques of the last `--days` (default 30, 0 for all), read straight
+//! from the router's database, `ZED42_MOM_NAMESPACE` (default `zed42`) /
+//! `ZED42_MOM_DATABASE` (default `mom`) at the MOM address. It prints the
+//! suggestions and records each as a `model_recommendation` decision on the
+//! blackboard of `--session` (default: the last session run under
+//! `--root`); before any session they are only printed.
+//!
+//! `zed42 transcript` lists the thread transcripts a session exported under
+//! `--root`, or prints one (a thread id or a prefix of it) as Markdown, or
+//! as HTML with `--html`.
+//!
+//! Usage: `zed42 top [--addr HOST:PORT]`, `zed42 init [--force] [PATH]`,
+//! `zed42 run [--root PATH] INTENT...`, `zed42 resume [--root PATH] [SESSION]`,
+//! `zed42 recommend [--root PATH] [--session SESSION] [--days N] [--min-samples N] [--max-failure-rate R] [--json]`,
+//! `zed42 transcript [--root PATH] [--html] SESSION [THREAD]`
+
+use anyhow::{bail, Context};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use zed42_blackboard::{BlackboardDb, QueueStatus, QueuedTask};
+use zed42_cortex::bootstrap::bootstrap;
+use zed42_cortex::dashboard::{tui, DashboardClient, DEFAULT_DASHBOARD_ADDR};
+use zed42_cortex::interrupt::{self, InterruptSnapshot};
+use zed42_cortex::Cortex;
+use zed42_mom::analytics::RoutingHistory;
+use zed42_mom::recommend::RecommendationPolicy;
+use zed42_notify::{ChannelConfig, Notifier, NotifyConfig};
+
+enum Command {
+    Top { addr: String },
+    Init { root: PathBuf, force: bool },
+    Run { root: PathBuf, intent: String },
+    Resume { root: PathBuf, session: Option<uuid::Uuid> },
+    Recommend { root: PathBuf, session: Option<uuid::Uuid>, days: Option<i64>, policy: RecommendationPolicy, json: bool },
+    Transcript { root: PathBuf, session: uuid::Uuid, thread: Option<String>, html: bool },
+}
+
+const USAGE: &str = "usage: zed42 top [--addr HOST:PORT] | zed42 init [--force] [PATH] | zed42 run [--root PATH] INTENT... | zed42 resume [--root PATH] [SESSION] | zed42 recommend [--root PATH] [--session SESSION] [--days N] [--min-samples N] [--max-failure-rate R] [--json] | zed42 transcript [--root PATH] [--html] SESSION [THREAD]";
+
+/// Blackboard directory relative to the project root
+const BLACKBOARD_DIR: &str = ".zed42/blackboard";
+
+/// File naming the last session run, relative to the project root
+const LAST_SESSION: &str = ".zed42/last_session";
+
+/// Notification channels relative to the project root
+const NOTIFY_CONFIG: &str = "config/notify.toml";
+
+const DEFAULT_MOM_ADDR: &str = "ws://localhost:8000";
+
+/// How often a run checks whether its tasks are finished
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Time agents get to stop when a run ends or is interrupted
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(10);
+
+fn parse_args() -> anyhow::Result<Command> {
+    let mut iter = std::env::args().skip(1);
+    let command = iter.next().context(USAGE)?;
+    match command.as_str() {
+        "top" => {
+            let mut addr = std::env::var("ZED42_DASHBOARD_ADDR").unwrap_or_else(|_| DEFAULT_DASHBOARD_ADDR.to_string());
+            while let Some(arg) = iter.next() {
+                match arg.as_str() {
+                    "--addr" => addr = iter.next().context("--addr needs a value")?,
+                    other => bail!("unknown argument {}", other),
+                }
+            }
+            Ok(Command::Top { addr })
+        }
+        "init" => {
+            let mut root = PathBuf::from(".");
+            let mut force = false;
+            for arg in iter {
+                match arg.as_str() {
+                    "--force" => force = true,
+                    other if other.starts_with("--") => bail!("unknown argument {}", other),
+                    path => root = PathBuf::from(path),
+                }
+            }
+            Ok(Command::Init { root, force })
+        }
+        "run" => {
+            let mut root = PathBuf::from(".");
+            let mut words = Vec::new();
+            while let Some(arg) = iter.next() {
+                match arg.as_str() {
+                    "--root" => root = PathBuf::from(iter.next().context("--root needs a value")?),
+                    other if other.starts_with("--") => bail!("unknown argument {}", other),
+                    word => words.push(word.to_string()),
+                }
+            }
+            if words.is_empty() {
+                bail!(USAGE);
+            }
+            Ok(Command::Run { root, intent: words.join(" ") })
+        }
+        "resume" => {
+            let mut root = PathBuf::from(".");
+            let mut session = None;
+            while let Some(arg) = iter.next() {
+                match arg.as_str() {
+                    "--root" => root = PathBuf::from(iter.next().context("--root needs a value")?),
+                    other if other.starts_with("--") => bail!("unknown argument {}", other),
+                    id => session = Some(id.parse().with_context(|| format!("invalid session id {}", id))?),
+                }
+            }
+            Ok(Command::Resume { root, session })
+        }
+        "recommend" => {
+            let mut root = PathBuf::from(".");
+            let mut session = None;
+            let mut days = Some(30);
+            let mut policy = RecommendationPolicy::default();
+            let mut json = false;
+            while let Some(arg) = iter.next() {
+                let mut value = |name: &str| iter.next().with_context(|| format!("{} needs a value", name));
+                match arg.as_str() {
+                    "--root" => root = PathBuf::from(value("--root")?),
+                    "--session" => {
+                        let id = value("--session")?;
+                        session = Some(id.parse().with_context(|| format!("invalid session id {}", id))?);
+                    }
+                    "--days" => {
+                        let n: i64 = value("--days")?.parse()?;
+                        days = (n > 0).then_some(n);
+                    }
+                    "--min-samples" => policy.min_samples = value("--min-samples")?.parse()?,
+                    "--max-failure-rate" => policy.max_failure_rate = value("--max-failure-rate")?.parse()?,
+                    "--json" => json = true,
+                    other => bail!("unknown argument {}", other),
+                }
+            }
+            Ok(Command::Recommend { root, session, days, policy, json })
+        }
+        "transcript" => {
+            let mut root = PathBuf::from(".");
+            let mut session = None;
+            let mut thread = None;
+            let mut html = false;
+            while let Some(arg) = iter.next() {
+                match arg.as_str() {
+                    "--root" => root = PathBuf::from(iter.next().context("--root needs a value")?),
+                    "--html" => html = true,
+                    other if other.starts_with("--") => bail!("unknown argument {}", other),
+                    id if session.is_none() => {
+                        session = Some(id.parse().with_context(|| format!("invalid session id {}", id))?)
+                    }
+                    other if thread.is_none() => thread = Some(other.to_string()),
+                    other => bail!("unexpected argument {}", other),
+                }
+            }
+            Ok(Command::Transcript { root, session: session.context(USAGE)?, thread, html })
+        }
+        other => bail!("unknown command {}", other),
+    }
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    match parse_args()? {
+        Command::Top { addr } => {
+            let client = DashboardClient::connect(&addr).await?;
+            tui::run(client, &addr).await
+        }
+        Command::Init { root, force } => {
+            let report = bootstrap(&root, force)?;
+            let detected = &report.profile.detected;
+            let languages: Vec<String> = detected
+                .languages
+                .iter()
+                .map(|share| format!("{} ({} files)", share.language.name(), share.files))
+                .collect();
+            println!("Languages:       {}", languages.join(", "));
+            println!("Build systems:   {}", detected.build_systems.join(", "));
+            println!("Test frameworks: {}", detected.test_frameworks.join(", "));
+            println!("CI:              {}", detected.ci.join(", "));
+            for path in &report.written {
+                println!("Wrote {}", path.display());
+            }
+            for path in &report.kept {
+                println!("Kept {} (use --force to regenerate)", path.display());
+            }
+            Ok(())
+        }
+        Command::Run { root, intent } => {
+            let planner = zed42_llm::OpenRouterClient::from_env().context("zed42 run needs a planner")?;
+            let mut cortex = open_cortex(&root, uuid::Uuid::new_v4())
+                .await?
+                .with_planner(Arc::new(planner));
+            println!("Session {}", cortex.session_id());
+            remember_session(&root, cortex.session_id())?;
+            drive(&mut cortex, Some(&intent)).await
+        }
+        Command::Resume { root, session } => {
+            let snapshot = match session {
+                Some(session) => InterruptSnapshot::load(&root, session)?,
+                None => InterruptSnapshot::latest(&root)?.context("no interrupted session to resume")?,
+            };
+            println!("Resuming session {} interrupted at {}", snapshot.session_id, snapshot.interrupted_at);
+            if !snapshot.clean {
+                println!("warning: shutdown was not clean; check the task queue before resuming");
+            }
+            for task in snapshot.unfinished() {
+                println!("  {} ({} attempt(s)): {}", task.task_id, task.attempts, task.description);
+            }
+            let mut cortex = open_cortex(&root, snapshot.session_id).await?;
+            remember_session(&root, snapshot.session_id)?;
+            cortex.resume(snapshot).await?;
+            drive(&mut cortex, None).await
+        }
+        Command::Recommend { root, session, days, policy, json } => {
+            let history = open_history().await?;
+            let since = days.map(|days| chrono::Utc::now() - chrono::Duration::days(days));
+            let session = match session {
+                Some(session) => Some(session),
+                None => last_session(&root)?,
+            };
+            let recommended: Vec<(Option<String>, _)> = match session {
+                Some(session) => {
+                    let mut cortex = open_cortex(&root, session).await?;
+                    let recommended = cortex.recommend_models(&history, since, &policy).await;
+                    cortex.shutdown(SHUTDOWN_GRACE).await;
+                    recommended?.into_iter().map(|(decision, rec)| (Some(decision), rec)).collect()
+                }
+                None => history
+                    .model_recommendations(since, &policy)
+                    .await?
+                    .into_iter()
+                    .map(|rec| (None, rec))
+                    .collect(),
+            };
+            if json {
+                let recommendations: Vec<_> = recommended.iter().map(|(_, rec)| rec).collect();
+                println!("{}", serde_json::to_string_pretty(&recommendations)?);
+            } else if recommended.is_empty() {
+                println!("No recommendations");
+            } else {
+                for (decision, rec) in &recommended {
+                    match decision {
+                        Some(decision) => println!("- {} (decision {})", rec.message, decision),
+                        None => println!("- {}", rec.message),
+                    }
+                }
+                if session.is_none() {
+                    println!("Not recorded: no session has run under {}; pass --session", root.display());
+                }
+            }
+            Ok(())
+        }
+        Command::Transcript { root, session, thread, html } => {
+            let Some(thread) = thread else {
+                let transcripts = Cortex::exported_transcripts(&root, session)?;
+                if transcripts.is_empty() {
+                    println!("No transcripts for session {}", session);
+                }
+                for transcript in &transcripts {
+                    let started = transcript.entries.first().map(|e| e.at.to_rfc3339()).unwrap_or_default();
+                    println!("{}  {:>4} entries  {}", transcript.thread_id, transcript.entries.len(), started);
+                }
+                return Ok(());
+            };
+            let transcript = Cortex::exported_transcript(&root, session, &thread)?;
+            if html {
+                println!("{}", transcript.to_html());
+            } else {
+                println!("{}", transcript.to_markdown());
+            }
+            Ok(())
+        }
+    }
+}
+
+fn mom_addr() -> String {
+    std::env::var("ZED42_MOM_ADDR").unwrap_or_else(|_| DEFAULT_MOM_ADDR.to_string())
+}
+
+/// The router's logs in the MOM database
+async fn open_history() -> anyhow::Result<RoutingHistory> {
+    let addr = mom_addr();
+    let namespace = std::env::var("ZED42_MOM_NAMESPACE").unwrap_or_else(|_| "zed42".to_string());
+    let database = std::env::var("ZED42_MOM_DATABASE").unwrap_or_else(|_| "mom".to_string());
+    let db = surrealdb::engine::any::connect(addr.as_str())
+        .await
+        .with_context(|| format!("Failed to connect to {}", addr))?;
+    db.use_ns(namespace).use_db(database).await?;
+    Ok(RoutingHistory::new(db))
+}
+
+/// Note `session_id` as the last session run under `root`
+fn remember_session(root: &Path, session_id: uuid::Uuid) -> anyhow::Result<()> {
+    let path = root.join(LAST_SESSION);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    std::fs::write(&path, session_id.to_string()).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// The last session run under `root`, if any
+fn last_session(root: &Path) -> anyhow::Result<Option<uuid::Uuid>> {
+    let path = root.join(LAST_SESSION);
+    match std::fs::read_to_string(&path) {
+        Ok(id) => Ok(Some(id.trim().parse().with_context(|| format!("Invalid session id in {}", path.display()))?)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    }
+}
+
+/// A Cortex for the project at `root` with its blackboard attached
+async fn open_cortex(root: &Path, session_id: uuid::Uuid) -> anyhow::Result<Cortex> {
+    let blackboard = BlackboardDb::new(&root.join(BLACKBOARD_DIR), &session_id.to_string(), &mom_addr()).await?;
+    let mut cortex = Cortex::new(session_id).with_repo_root(root);
+    cortex.initialize(blackboard).await?;
+    if let Some(notifier) = load_notifier(root)? {
+        cortex.start_notifications(Arc::new(notifier));
+    }
+    Ok(cortex)
+}
+
+/// Notifier for the project's channels; desktop ones need the desktop app
+fn load_notifier(root: &Path) -> anyhow::Result<Option<Notifier>> {
+    let path = root.join(NOTIFY_CONFIG);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let mut config = NotifyConfig::from_file(&path)?;
+    config.channels.retain(|entry| !matches!(entry.channel, ChannelConfig::Desktop));
+    if config.channels.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(Notifier::from_config(config, None)?))
+}
+
+/// Run the session until its tasks finish, or checkpoint it on Ctrl-C
+///
+/// With an intent the session is planned first; without one (a resumed
+/// session) agents are started for the unfinished tasks.
+async fn drive(cortex: &mut Cortex, intent: Option<&str>) -> anyhow::Result<()> {
+    let finished = tokio::select! {
+        queue = run_session(cortex, intent) => Some(queue),
+        _ = interrupt::signal() => None,
+    };
+    let Some(queue) = finished else {
+        let report = cortex.checkpoint_on_interrupt(SHUTDOWN_GRACE).await?;
+        println!("{}", report);
+        return Ok(());
+    };
+    let queue = match queue {
+        Ok(queue) => queue,
+        Err(e) => {
+            // Keep what the session got done; the snapshot makes it resumable
+            let report = cortex.checkpoint_on_interrupt(SHUTDOWN_GRACE).await?;
+            println!("{}", report);
+            return Err(e);
+        }
+    };
+
+    let report = cortex.shutdown(SHUTDOWN_GRACE).await;
+    let failed: Vec<&QueuedTask> = queue.iter().filter(|task| task.status == QueueStatus::Failed).collect();
+    println!("Session {} finished: {} task(s), {} failed", cortex.session_id(), queue.len(), failed.len());
+    for task in failed {
+        println!("  {}: {}", task.task_id, task.last_error.as_deref().unwrap_or("no error recorded"));
+    }
+    if !report.is_clean() {
+        println!("warning: shutdown was not clean: {:?}", report.hook_failures);
+    }
+    Ok(())
+}
+
+async fn run_session(cortex: &mut Cortex, intent: Option<&str>) -> anyhow::Result<Vec<QueuedTask>> {
+    match intent {
+        Some(intent) => cortex.process_intent(intent).await?,
+        None => {
+            cortex.restart_agents().await?;
+        }
+    }
+    cortex.wait_for_session(POLL_INTERVAL).await
+}
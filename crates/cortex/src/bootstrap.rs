@@ -0,0 +1,396 @@
+//! Cold-start project profile
+//!
+//! `zed42 init` inspects a repository ZED42 has not seen before — language
+//! mix, build system, test framework and CI — and writes a starting
+//! configuration the user can edit:
+//!
+//! - `.zed42/profile.toml`: what was detected, plus a `project` run preset
+//!   (team, recommended toolboxes, model ladder, budget and approval gates)
+//!   that [`PresetRegistry::from_file`](crate::presets::PresetRegistry::from_file) loads as-is
+//! - `.zed42/standards.toml`: a standards rubric for the main languages
+//!
+//! Existing files are left alone unless the wizard is forced, so running it
+//! again never clobbers the user's edits.
+
+use anyhow::{Context, Result};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use zed42_agents::AgentType;
+use zed42_toolboxes::languages::Language;
+use zed42_toolboxes::standards::{Case, StandardsProfile, STANDARDS_PATH};
+
+use crate::presets::{ApprovalGate, RunPreset, ToolboxPolicy};
+
+/// Location of the generated profile relative to the project root
+pub const PROFILE_PATH: &str = ".zed42/profile.toml";
+
+/// Name of the generated run preset
+pub const PROJECT_PRESET: &str = "project";
+
+/// Model ladder of the generated preset, cheapest first
+const DEFAULT_MODELS: &[&str] = &["anthropic/claude-3-haiku", "anthropic/claude-3.5-sonnet", "openai/gpt-4o"];
+
+/// How many files of a language the repository has
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LanguageShare {
+    pub language: Language,
+    pub files: usize,
+}
+
+/// What the wizard found in the repository
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RepoInspection {
+    /// Source files not excluded by ignore files
+    pub source_files: usize,
+    /// Most files first
+    pub languages: Vec<LanguageShare>,
+    /// e.g. `cargo`, `npm`, `poetry`, `go`
+    pub build_systems: Vec<String>,
+    /// e.g. `cargo test`, `vitest`, `pytest`
+    pub test_frameworks: Vec<String>,
+    /// e.g. `github_actions`, `gitlab_ci`
+    pub ci: Vec<String>,
+}
+
+impl RepoInspection {
+    /// Inspect the repository at `root`
+    pub fn inspect(root: &Path) -> Result<Self> {
+        let files = zed42_toolboxes::walk::files(root, false);
+        let mut counts: HashMap<Language, usize> = HashMap::new();
+        for path in &files {
+            if let Some(language) = Language::detect(path) {
+                *counts.entry(language).or_default() += 1;
+            }
+        }
+        let mut languages: Vec<LanguageShare> =
+            counts.into_iter().map(|(language, files)| LanguageShare { language, files }).collect();
+        languages.sort_by(|a, b| b.files.cmp(&a.files).then_with(|| a.language.name().cmp(b.language.name())));
+
+        let relative: Vec<String> = files
+            .iter()
+            .filter_map(|path| path.strip_prefix(root).ok())
+            .map(|path| path.to_string_lossy().replace('\\', "/"))
+            .collect();
+        let package_json = read_optional(&root.join("package.json"))?;
+        let pyproject = read_optional(&root.join("pyproject.toml"))?;
+
+        Ok(Self {
+            source_files: languages.iter().map(|share| share.files).sum(),
+            build_systems: build_systems(root),
+            test_frameworks: test_frameworks(&languages, &relative, package_json.as_deref(), pyproject.as_deref()),
+            ci: ci_systems(root),
+            languages,
+        })
+    }
+
+    pub fn has(&self, language: Language) -> bool {
+        self.languages.iter().any(|share| share.language == language)
+    }
+
+    pub fn primary_language(&self) -> Option<Language> {
+        self.languages.first().map(|share| share.language)
+    }
+}
+
+/// Contents of `.zed42/profile.toml`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProjectProfile {
+    pub detected: RepoInspection,
+    /// Holds the `project` preset, in the presets file layout
+    pub presets: BTreeMap<String, RunPreset>,
+}
+
+impl ProjectProfile {
+    /// Profile recommended for what `inspection` found
+    pub fn recommend(inspection: RepoInspection) -> Self {
+        let mut presets = BTreeMap::new();
+        presets.insert(PROJECT_PRESET.to_string(), recommend_preset(&inspection));
+        Self { detected: inspection, presets }
+    }
+
+    pub fn preset(&self) -> Option<&RunPreset> {
+        self.presets.get(PROJECT_PRESET)
+    }
+
+    pub fn from_toml_str(content: &str) -> Result<Self> {
+        toml::from_str(content).map_err(|e| anyhow::anyhow!("Invalid project profile: {}", e))
+    }
+
+    pub fn to_toml(&self) -> Result<String> {
+        toml::to_string_pretty(self).context("Failed to serialize project profile")
+    }
+}
+
+/// What [`bootstrap`] did
+#[derive(Debug, Clone)]
+pub struct BootstrapReport {
+    pub profile: ProjectProfile,
+    pub written: Vec<PathBuf>,
+    /// Files that already existed and were kept
+    pub kept: Vec<PathBuf>,
+}
+
+/// Inspect `root` and write the profile and standards under `.zed42/`
+///
+/// Files that already exist are kept unless `force` is set.
+pub fn bootstrap(root: &Path, force: bool) -> Result<BootstrapReport> {
+    let inspection = RepoInspection::inspect(root)?;
+    let standards = recommend_standards(&inspection);
+    let profile = ProjectProfile::recommend(inspection);
+
+    let mut report = BootstrapReport { profile, written: Vec::new(), kept: Vec::new() };
+    let standards = toml::to_string_pretty(&standards).context("Failed to serialize standards")?;
+    for (relative, content) in [(PROFILE_PATH, report.profile.to_toml()?), (STANDARDS_PATH, standards)] {
+        let path = root.join(relative);
+        if path.exists() && !force {
+            report.kept.push(path);
+            continue;
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        std::fs::write(&path, content).with_context(|| format!("Failed to write {}", path.display()))?;
+        report.written.push(path);
+    }
+    Ok(report)
+}
+
+fn recommend_preset(inspection: &RepoInspection) -> RunPreset {
+    let has_tests = !inspection.test_frameworks.is_empty();
+    let has_manifests = !inspection.build_systems.is_empty();
+    let web = inspection.has(Language::TypeScript) || inspection.has(Language::JavaScript);
+
+    let mut team = vec![AgentType::FeatureImplementer];
+    if has_tests {
+        team.push(AgentType::TestEngineer);
+    }
+    team.push(AgentType::StandardsEnforcer);
+    if has_manifests {
+        team.push(AgentType::SecurityReviewer);
+    }
+
+    let mut allow: Vec<&str> = vec![
+        "CodeGeneration",
+        "FileManipulation",
+        "Refactoring",
+        "StaticAnalysis",
+        "PolicyEnforcement",
+        "GraphQuery",
+        "GitOperations",
+        "GitHistory",
+        "Shell",
+    ];
+    if has_tests {
+        allow.push("Testing");
+    }
+    if has_manifests {
+        allow.extend(["BuildSystem", "DependencyScanning"]);
+    }
+    if web {
+        allow.extend(["ApiTesting", "BrowserAutomation"]);
+    }
+    if !inspection.ci.is_empty() {
+        allow.push("Forge");
+    }
+
+    // Without tests nothing catches a bad plan before it lands
+    let mut approval_gates = vec![ApprovalGate::Commit];
+    if !has_tests {
+        approval_gates.insert(0, ApprovalGate::PlanReview);
+    }
+
+    RunPreset {
+        description: "Generated by zed42 init; edit to taste".to_string(),
+        team,
+        budget: default_budget(inspection.source_files),
+        models: DEFAULT_MODELS.iter().map(|model| model.to_string()).collect(),
+        toolbox_policy: ToolboxPolicy {
+            allow: allow.into_iter().map(str::to_string).collect(),
+            deny: Vec::new(),
+        },
+        approval_gates,
+    }
+}
+
+/// Run budget scaled to the size of the codebase
+fn default_budget(source_files: usize) -> Decimal {
+    match source_files {
+        0..=200 => Decimal::new(2, 0),
+        201..=2_000 => Decimal::new(5, 0),
+        _ => Decimal::new(10, 0),
+    }
+}
+
+fn recommend_standards(inspection: &RepoInspection) -> StandardsProfile {
+    let mut standards = StandardsProfile::default();
+    if inspection.has(Language::Rust) {
+        standards.naming.functions = Some(Case::SnakeCase);
+        standards.naming.types = Some(Case::UpperCamelCase);
+        standards.naming.constants = Some(Case::ScreamingSnakeCase);
+        standards.error_handling.guidance.push("Propagate errors with ? and add context instead of unwrapping".to_string());
+    }
+    // One review point per language, from the same conventions agents are prompted with
+    for share in &inspection.languages {
+        standards.rubric.push(share.language.prompt_hint());
+    }
+    if !inspection.test_frameworks.is_empty() {
+        standards.rubric.push(format!("New behaviour comes with tests ({})", inspection.test_frameworks.join(", ")));
+    }
+    standards
+}
+
+fn build_systems(root: &Path) -> Vec<String> {
+    let exists = |name: &str| root.join(name).exists();
+    let mut systems = Vec::new();
+    if exists("Cargo.toml") {
+        systems.push("cargo");
+    }
+    if exists("package.json") {
+        systems.push(if exists("pnpm-lock.yaml") {
+            "pnpm"
+        } else if exists("yarn.lock") {
+            "yarn"
+        } else {
+            "npm"
+        });
+    }
+    if exists("poetry.lock") {
+        systems.push("poetry");
+    } else if exists("pyproject.toml") || exists("setup.py") || exists("requirements.txt") {
+        systems.push("pip");
+    }
+    if exists("go.mod") {
+        systems.push("go");
+    }
+    if exists("CMakeLists.txt") {
+        systems.push("cmake");
+    }
+    if exists("Makefile") {
+        systems.push("make");
+    }
+    systems.into_iter().map(str::to_string).collect()
+}
+
+fn test_frameworks(
+    languages: &[LanguageShare],
+    files: &[String],
+    package_json: Option<&str>,
+    pyproject: Option<&str>,
+) -> Vec<String> {
+    let has = |language: Language| languages.iter().any(|share| share.language == language);
+    let file_named = |matches: &dyn Fn(&str) -> bool| {
+        files.iter().any(|path| matches(path.rsplit('/').next().unwrap_or(path)))
+    };
+    let mut frameworks = Vec::new();
+    if has(Language::Rust) {
+        frameworks.push("cargo test".to_string());
+    }
+    if let Some(manifest) = package_json {
+        let manifest: serde_json::Value = serde_json::from_str(manifest).unwrap_or_default();
+        for framework in ["jest", "vitest", "mocha", "@playwright/test"] {
+            let declared = ["dependencies", "devDependencies"]
+                .iter()
+                .any(|section| manifest[*section].get(framework).is_some());
+            if declared {
+                frameworks.push(framework.to_string());
+            }
+        }
+    }
+    if has(Language::Python) {
+        let pytest = pyproject.is_some_and(|content| content.contains("pytest"))
+            || file_named(&|name| name == "conftest.py" || name == "pytest.ini");
+        if pytest {
+            frameworks.push("pytest".to_string());
+        } else if file_named(&|name| name.starts_with("test_") && name.ends_with(".py")) {
+            frameworks.push("unittest".to_string());
+        }
+    }
+    if has(Language::Go) && file_named(&|name| name.ends_with("_test.go")) {
+        frameworks.push("go test".to_string());
+    }
+    frameworks
+}
+
+fn ci_systems(root: &Path) -> Vec<String> {
+    let workflows = root.join(".github/workflows");
+    let github = std::fs::read_dir(&workflows).is_ok_and(|entries| {
+        entries
+            .filter_map(|entry| entry.ok())
+            .any(|entry| entry.path().extension().is_some_and(|ext| ext == "yml" || ext == "yaml"))
+    });
+    let mut ci = Vec::new();
+    if github {
+        ci.push("github_actions");
+    }
+    for (file, name) in [
+        (".gitlab-ci.yml", "gitlab_ci"),
+        (".circleci/config.yml", "circleci"),
+        ("Jenkinsfile", "jenkins"),
+        ("azure-pipelines.yml", "azure_pipelines"),
+    ] {
+        if root.join(file).exists() {
+            ci.push(name);
+        }
+    }
+    ci.into_iter().map(str::to_string).collect()
+}
+
+fn read_optional(path: &Path) -> Result<Option<String>> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => Ok(Some(content)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::presets::PresetRegistry;
+
+    fn write(root: &Path, path: &str, content: &str) {
+        let path = root.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_bootstrap_detects_stack_and_keeps_user_edits() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(root, "Cargo.toml", "[package]\nname = \"svc\"\n");
+        write(root, "src/lib.rs", "pub fn a() {}\n");
+        write(root, "src/main.rs", "fn main() {}\n");
+        write(root, "web/app.ts", "export const x = 1;\n");
+        write(root, "package.json", r#"{ "devDependencies": { "vitest": "^1.0.0" } }"#);
+        write(root, ".github/workflows/ci.yml", "on: push\n");
+        write(root, "target/debug/build.rs", "fn ignored() {}\n");
+
+        let report = bootstrap(root, false).unwrap();
+        let detected = &report.profile.detected;
+        assert_eq!(detected.primary_language(), Some(Language::Rust));
+        assert_eq!(detected.source_files, 3);
+        assert_eq!(detected.build_systems, vec!["cargo", "npm"]);
+        assert_eq!(detected.test_frameworks, vec!["cargo test", "vitest"]);
+        assert_eq!(detected.ci, vec!["github_actions"]);
+        assert_eq!(report.written.len(), 2);
+
+        // The profile doubles as a presets file, and the standards load as usual
+        let registry = PresetRegistry::from_file(root.join(PROFILE_PATH)).unwrap();
+        let preset = registry.get(PROJECT_PRESET).unwrap();
+        assert!(preset.team.contains(&AgentType::TestEngineer));
+        assert!(preset.toolbox_policy.allow.contains(&"BrowserAutomation".to_string()));
+        assert!(!preset.requires(ApprovalGate::PlanReview));
+        let standards = StandardsProfile::load(root).unwrap().unwrap();
+        assert_eq!(standards.naming.functions, Some(Case::SnakeCase));
+
+        // A second run keeps edited files unless forced
+        write(root, STANDARDS_PATH, "rubric = [\"Ours\"]\n");
+        let again = bootstrap(root, false).unwrap();
+        assert_eq!(again.kept.len(), 2);
+        assert_eq!(StandardsProfile::load(root).unwrap().unwrap().rubric, vec!["Ours"]);
+        assert_eq!(bootstrap(root, true).unwrap().written.len(), 2);
+    }
+}
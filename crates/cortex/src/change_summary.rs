@@ -0,0 +1,492 @@
+//! Structured summaries of large changesets
+//!
+//! Reading a thousand-line changeset hunk by hunk buries what matters. A
+//! [`ChangeSummary`] gives reviewers the overview first:
+//!
+//! 1. Public APIs added, removed or changed, read from the definitions the
+//!    diff touches
+//! 2. Behavior changes, summarized by the model one review chunk at a time
+//! 3. Risk hotspots: touched symbols ranked by the impact analysis of the
+//!    knowledge graph
+//!
+//! Summaries are keyed by a fingerprint of the changeset and cached under
+//! `.zed42/change_summaries/`, so every Green reviewer of the same change
+//! reads the same summary without paying for it again.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use zed42_core::types::{Artifact, ArtifactType, TaskId};
+use zed42_llm::{ConstrainedGen, LlmClient};
+use zed42_memory::knowledge_graph::{ImpactRisk, KnowledgeGraphMemory, KnowledgeNode, NodeType, DEFAULT_IMPACT_DEPTH};
+
+use crate::review::{ReviewChangeset, DEFAULT_CHUNK_CHARS};
+
+/// Hotspots kept in a summary, riskiest first
+const MAX_HOTSPOTS: usize = 10;
+
+const SUMMARY_SYSTEM_PROMPT: &str = "You summarize code changes for reviewers. Given part of a \
+unified diff, list each change in runtime behavior in one sentence: what now happens differently \
+and when. Skip formatting, renames and comments. Return an empty list if behavior is unchanged.";
+
+/// How a public API changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiChangeKind {
+    Added,
+    Removed,
+    /// Both removed and re-added lines define it: the signature changed
+    Changed,
+}
+
+/// A public definition the changeset adds, removes or changes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiChange {
+    pub file: String,
+    pub name: String,
+    /// Definition keyword (`fn`, `struct`, `class`, ...)
+    pub kind: String,
+    pub change: ApiChangeKind,
+}
+
+/// A touched symbol whose blast radius calls for a closer look
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskHotspot {
+    pub file: String,
+    pub symbol: String,
+    pub risk: ImpactRisk,
+    pub dependents: usize,
+    /// The impact report's one-line summary
+    pub detail: String,
+}
+
+/// Overview of a changeset for reviewers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangeSummary {
+    pub task_id: TaskId,
+    /// Hash of the diffed files; the cache key
+    pub fingerprint: String,
+    pub files_changed: usize,
+    pub additions: usize,
+    pub deletions: usize,
+    pub api_changes: Vec<ApiChange>,
+    pub behavior_changes: Vec<String>,
+    pub hotspots: Vec<RiskHotspot>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ChangeSummary {
+    pub fn apis(&self, change: ApiChangeKind) -> impl Iterator<Item = &ApiChange> {
+        self.api_changes.iter().filter(move |api| api.change == change)
+    }
+
+    /// The summary as a documentation artifact of its task
+    pub fn to_artifact(&self) -> Result<Artifact> {
+        let mut artifact = Artifact::code(self.task_id.clone(), serde_json::to_string_pretty(self)?, None);
+        artifact.artifact_type = ArtifactType::Documentation;
+        Ok(artifact)
+    }
+
+    /// Render the summary as Markdown
+    pub fn to_markdown(&self) -> String {
+        let mut md = String::new();
+        let _ = writeln!(md, "## Change summary for task `{}`\n", self.task_id);
+        let _ = writeln!(
+            md,
+            "{} file(s) changed, +{} -{}\n",
+            self.files_changed, self.additions, self.deletions
+        );
+
+        let _ = writeln!(md, "### API changes\n");
+        if self.api_changes.is_empty() {
+            let _ = writeln!(md, "No public APIs touched.\n");
+        } else {
+            for (change, label) in [
+                (ApiChangeKind::Added, "Added"),
+                (ApiChangeKind::Removed, "Removed"),
+                (ApiChangeKind::Changed, "Changed"),
+            ] {
+                for api in self.apis(change) {
+                    let _ = writeln!(md, "- {} `{} {}` in `{}`", label, api.kind, api.name, api.file);
+                }
+            }
+            md.push('\n');
+        }
+
+        let _ = writeln!(md, "### Behavior changes\n");
+        if self.behavior_changes.is_empty() {
+            let _ = writeln!(md, "None reported.\n");
+        } else {
+            for change in &self.behavior_changes {
+                let _ = writeln!(md, "- {}", change);
+            }
+            md.push('\n');
+        }
+
+        let _ = writeln!(md, "### Risk hotspots\n");
+        if self.hotspots.is_empty() {
+            let _ = writeln!(md, "None found in the knowledge graph.\n");
+        } else {
+            for hotspot in &self.hotspots {
+                let _ = writeln!(md, "- **{:?}** `{}` in `{}`: {}", hotspot.risk, hotspot.symbol, hotspot.file, hotspot.detail);
+            }
+            md.push('\n');
+        }
+        md
+    }
+}
+
+/// Model output for one review chunk
+#[derive(Debug, Clone, JsonSchema, Serialize, Deserialize)]
+struct ChunkSummary {
+    behavior_changes: Vec<String>,
+}
+
+/// Builds [`ChangeSummary`]s, consulting the cache and knowledge graph when set
+pub struct ChangeSummarizer<'a> {
+    client: &'a dyn LlmClient,
+    knowledge_graph: Option<&'a KnowledgeGraphMemory>,
+    cache_dir: Option<PathBuf>,
+    chunk_chars: usize,
+}
+
+impl<'a> ChangeSummarizer<'a> {
+    pub fn new(client: &'a dyn LlmClient) -> Self {
+        Self { client, knowledge_graph: None, cache_dir: None, chunk_chars: DEFAULT_CHUNK_CHARS }
+    }
+
+    /// Rank touched symbols by impact analysis in `graph`
+    pub fn with_knowledge_graph(mut self, graph: &'a KnowledgeGraphMemory) -> Self {
+        self.knowledge_graph = Some(graph);
+        self
+    }
+
+    /// Cache summaries in `<workspace>/.zed42/change_summaries/`
+    pub fn with_cache(mut self, workspace: &Path) -> Self {
+        self.cache_dir = Some(workspace.join(".zed42").join("change_summaries"));
+        self
+    }
+
+    /// Diff characters sent to the model per call
+    pub fn with_chunk_chars(mut self, chunk_chars: usize) -> Self {
+        self.chunk_chars = chunk_chars;
+        self
+    }
+
+    /// Summarize `changeset`, or return the cached summary of the same changes
+    pub async fn summarize(&self, changeset: &ReviewChangeset) -> Result<ChangeSummary> {
+        let fingerprint = fingerprint(changeset);
+        if let Some(cached) = self.cached(&fingerprint) {
+            return Ok(cached);
+        }
+
+        let symbols = touched_symbols(changeset);
+        let mut behavior_changes: Vec<String> = Vec::new();
+        for chunk in changeset.chunks(self.chunk_chars) {
+            let summary: ChunkSummary = ConstrainedGen::new(self.client)
+                .system(SUMMARY_SYSTEM_PROMPT)
+                .prompt(format!("{}\n\n{}", chunk.heading(), chunk.diff))
+                .max_retries(1)
+                .generate()
+                .await
+                .with_context(|| format!("Failed to summarize changeset part {}", chunk.index))?;
+            for change in summary.behavior_changes {
+                let change = change.trim().to_string();
+                if !change.is_empty() && !behavior_changes.contains(&change) {
+                    behavior_changes.push(change);
+                }
+            }
+        }
+
+        let summary = ChangeSummary {
+            task_id: changeset.task_id.clone(),
+            fingerprint,
+            files_changed: changeset.changed().count(),
+            additions: changeset.additions(),
+            deletions: changeset.deletions(),
+            api_changes: api_changes(&symbols),
+            behavior_changes,
+            hotspots: self.hotspots(&symbols).await,
+            created_at: Utc::now(),
+        };
+        self.store(&summary);
+        Ok(summary)
+    }
+
+    fn cache_path(&self, fingerprint: &str) -> Option<PathBuf> {
+        self.cache_dir.as_ref().map(|dir| dir.join(format!("{}.json", fingerprint)))
+    }
+
+    fn cached(&self, fingerprint: &str) -> Option<ChangeSummary> {
+        let path = self.cache_path(fingerprint)?;
+        let bytes = std::fs::read(&path).ok()?;
+        serde_json::from_slice(&bytes)
+            .map_err(|e| tracing::warn!("Ignoring unreadable change summary {}: {}", path.display(), e))
+            .ok()
+    }
+
+    /// Best effort: a summary that can't be cached is still returned
+    fn store(&self, summary: &ChangeSummary) {
+        let Some(path) = self.cache_path(&summary.fingerprint) else { return };
+        let result = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(&path, serde_json::to_vec_pretty(summary).unwrap_or_default()));
+        if let Err(e) = result {
+            tracing::warn!("Failed to cache change summary {}: {}", path.display(), e);
+        }
+    }
+
+    async fn hotspots(&self, symbols: &[TouchedSymbol]) -> Vec<RiskHotspot> {
+        let Some(graph) = self.knowledge_graph else {
+            return Vec::new();
+        };
+        let mut nodes: Vec<KnowledgeNode> = Vec::new();
+        for node_type in [NodeType::Function, NodeType::Type] {
+            match graph.get_nodes_by_type(node_type).await {
+                Ok(found) => nodes.extend(found),
+                Err(e) => tracing::warn!("Change summary: knowledge graph lookup failed: {:#}", e),
+            }
+        }
+
+        let mut hotspots = Vec::new();
+        let mut seen = BTreeSet::new();
+        for symbol in symbols {
+            if !seen.insert((symbol.file.as_str(), symbol.name.as_str())) {
+                continue;
+            }
+            let Some(node) = nodes.iter().find(|node| {
+                node.name == symbol.name
+                    && zed42_toolboxes::graph::node_path(node).map_or(true, |path| path == symbol.file)
+            }) else {
+                continue;
+            };
+            match graph.analyze_impact(&node.id, DEFAULT_IMPACT_DEPTH).await {
+                Ok(report) => hotspots.push(RiskHotspot {
+                    file: symbol.file.clone(),
+                    symbol: symbol.name.clone(),
+                    risk: report.risk,
+                    dependents: report.dependents.len(),
+                    detail: report.summary(),
+                }),
+                Err(e) => tracing::warn!("Change summary: impact analysis of {} failed: {:#}", node.id, e),
+            }
+        }
+        hotspots.sort_by(|a, b| b.risk.cmp(&a.risk).then(b.dependents.cmp(&a.dependents)));
+        hotspots.truncate(MAX_HOTSPOTS);
+        hotspots
+    }
+}
+
+/// Hash of the task and every diffed file
+fn fingerprint(changeset: &ReviewChangeset) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(changeset.task_id.as_bytes());
+    for file in changeset.changed() {
+        hasher.update([0]);
+        hasher.update(file.path.as_bytes());
+        hasher.update([0]);
+        hasher.update(file.patch().as_bytes());
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// Where a touched definition appears in the diff
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Side {
+    Removed,
+    Added,
+    /// Named in a hunk header: its body changed
+    Context,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TouchedSymbol {
+    file: String,
+    name: String,
+    kind: String,
+    public: bool,
+    side: Side,
+}
+
+/// Definitions on changed lines and in hunk headers, in diff order
+fn touched_symbols(changeset: &ReviewChangeset) -> Vec<TouchedSymbol> {
+    let mut symbols = Vec::new();
+    for file in changeset.changed() {
+        for hunk in &file.hunks {
+            for line in hunk.lines() {
+                let (side, text) = if let Some(header) = line.strip_prefix("@@") {
+                    // `@@ -1,3 +1,4 @@ fn enclosing()`
+                    (Side::Context, header.split_once("@@").map_or("", |(_, tail)| tail))
+                } else if let Some(text) = line.strip_prefix('+') {
+                    (Side::Added, text)
+                } else if let Some(text) = line.strip_prefix('-') {
+                    (Side::Removed, text)
+                } else {
+                    continue;
+                };
+                if let Some((kind, name, public)) = definition(text) {
+                    symbols.push(TouchedSymbol { file: file.path.clone(), name, kind, public, side });
+                }
+            }
+        }
+    }
+    symbols
+}
+
+/// Public definitions by how they changed, in file and name order
+fn api_changes(symbols: &[TouchedSymbol]) -> Vec<ApiChange> {
+    let mut sides: BTreeMap<(&str, &str), (&str, bool, bool)> = BTreeMap::new();
+    for symbol in symbols.iter().filter(|s| s.public && s.side != Side::Context) {
+        let entry = sides.entry((symbol.file.as_str(), symbol.name.as_str())).or_insert((&symbol.kind, false, false));
+        match symbol.side {
+            Side::Removed => entry.1 = true,
+            Side::Added => entry.2 = true,
+            Side::Context => {}
+        }
+    }
+    sides
+        .into_iter()
+        .map(|((file, name), (kind, removed, added))| ApiChange {
+            file: file.to_string(),
+            name: name.to_string(),
+            kind: kind.to_string(),
+            change: match (removed, added) {
+                (true, true) => ApiChangeKind::Changed,
+                (true, false) => ApiChangeKind::Removed,
+                _ => ApiChangeKind::Added,
+            },
+        })
+        .collect()
+}
+
+/// `(keyword, name, public)` of a line that opens a definition
+///
+/// Understands Rust, Python and JavaScript/TypeScript definitions. Rust
+/// items are public when marked `pub`, JavaScript ones when exported, and
+/// Python ones unless their name starts with `_`.
+fn definition(line: &str) -> Option<(String, String, bool)> {
+    const KEYWORDS: [&str; 12] =
+        ["fn", "struct", "enum", "trait", "type", "mod", "const", "static", "def", "function", "class", "interface"];
+    const MODIFIERS: [&str; 5] = ["async", "unsafe", "default", "extern", "abstract"];
+
+    let mut public = None;
+    let mut words = line.split_whitespace().peekable();
+    while let Some(&word) = words.peek() {
+        match word {
+            "pub" | "export" => public = Some(true),
+            w if w.starts_with("pub(") => public = Some(false),
+            w if MODIFIERS.contains(&w) || w.starts_with('"') => {}
+            _ => break,
+        }
+        words.next();
+    }
+    let mut keyword = words.next().filter(|w| KEYWORDS.contains(w))?;
+    if keyword == "const" && words.peek() == Some(&"fn") {
+        keyword = "fn";
+        words.next();
+    }
+    let name: String = words
+        .next()?
+        .chars()
+        .take_while(|c| c.is_alphanumeric() || *c == '_')
+        .collect();
+    if name.is_empty() {
+        return None;
+    }
+    let public = match public {
+        Some(public) => public,
+        None => matches!(keyword, "def" | "class") && !name.starts_with('_'),
+    };
+    Some((keyword.to_string(), name, public))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zed42_llm::MockLlmClient;
+    use zed42_memory::knowledge_graph::KnowledgeEdge;
+
+    #[test]
+    fn test_definition_parsing() {
+        assert_eq!(definition("pub async fn users(page: u32) {"), Some(("fn".into(), "users".into(), true)));
+        assert_eq!(definition("    pub(crate) struct Cache;"), Some(("struct".into(), "Cache".into(), false)));
+        assert_eq!(definition("export function login() {"), Some(("function".into(), "login".into(), true)));
+        assert_eq!(definition("def _helper(x):"), Some(("def".into(), "_helper".into(), false)));
+        assert_eq!(definition("pub const fn empty() -> Self {"), Some(("fn".into(), "empty".into(), true)));
+        assert_eq!(definition("let fn_name = 3;"), None);
+    }
+
+    #[tokio::test]
+    async fn test_summary_reports_apis_hotspots_and_caches() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::write(
+            dir.path().join("src/auth.rs"),
+            "pub fn check(token: &str) -> bool {\n    !token.is_empty()\n}\n\npub fn legacy_login() {}\n",
+        )
+        .unwrap();
+        let new = "pub fn check(token: &str, scope: &str) -> bool {\n    !token.is_empty() && scope == \"admin\"\n}\n\npub fn logout() {}\n";
+        let changeset = ReviewChangeset::build(
+            &"t1".to_string(),
+            &[Artifact::code("t1".to_string(), new.to_string(), Some("src/auth.rs".to_string()))],
+            dir.path(),
+        )
+        .unwrap();
+
+        let graph = KnowledgeGraphMemory::new(dir.path(), "summary", None).await.unwrap();
+        for (id, name, metadata) in [
+            ("fn-check", "check", r#"{"path": "src/auth.rs", "public": true}"#),
+            ("fn-handler", "handler", r#"{"path": "src/api.rs"}"#),
+        ] {
+            graph
+                .insert_node(KnowledgeNode {
+                    id: id.to_string(),
+                    node_type: "function".to_string(),
+                    name: name.to_string(),
+                    content: String::new(),
+                    embedding: None,
+                    metadata: metadata.to_string(),
+                    created_at: 0,
+                    updated_at: 0,
+                })
+                .await
+                .unwrap();
+        }
+        graph
+            .insert_edge(KnowledgeEdge {
+                id: "handler-check".to_string(),
+                edge_type: "calls".to_string(),
+                from_id: "fn-handler".to_string(),
+                to_id: "fn-check".to_string(),
+                metadata: None,
+                created_at: 0,
+            })
+            .await
+            .unwrap();
+
+        let client = MockLlmClient::new(r#"{"behavior_changes": ["check now rejects non-admin scopes"]}"#.to_string());
+        let summarizer = ChangeSummarizer::new(&client).with_knowledge_graph(&graph).with_cache(dir.path());
+        let summary = summarizer.summarize(&changeset).await.unwrap();
+
+        let names = |kind| summary.apis(kind).map(|api| api.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names(ApiChangeKind::Added), vec!["logout"]);
+        assert_eq!(names(ApiChangeKind::Removed), vec!["legacy_login"]);
+        assert_eq!(names(ApiChangeKind::Changed), vec!["check"]);
+        assert_eq!(summary.behavior_changes, vec!["check now rejects non-admin scopes"]);
+        assert_eq!(summary.hotspots.len(), 1);
+        assert_eq!(summary.hotspots[0].symbol, "check");
+        assert_eq!(summary.hotspots[0].dependents, 1);
+        assert!(summary.to_markdown().contains("- Changed `fn check` in `src/auth.rs`"));
+
+        // The mock has no replies left: a second summary must come from the cache
+        let again = summarizer.summarize(&changeset).await.unwrap();
+        assert_eq!(again.fingerprint, summary.fingerprint);
+        assert_eq!(summary.to_artifact().unwrap().artifact_type, ArtifactType::Documentation);
+    }
+}
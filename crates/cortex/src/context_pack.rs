@@ -0,0 +1,301 @@
+//! Context packs
+//!
+//! Before a task is dispatched the Cortex retrieves what the agent will
+//! need once, instead of every agent repeating the same lookups:
+//!
+//! 1. Knowledge-graph hits for the task description (up to 40% of the budget)
+//! 2. Session decisions matching the description (up to 20%)
+//! 3. Contents of the files behind those hits (whatever budget is left)
+//!
+//! Retrieval is best effort: a failing tier is logged and skipped, and a
+//! pack never exceeds its token budget.
+
+use std::collections::HashSet;
+use std::path::Path;
+use zed42_core::types::{ContextPack, ContextSnippet, ContextSource};
+use zed42_memory::knowledge_graph::{KnowledgeGraphMemory, SearchQuery, SearchResult};
+use zed42_memory::session::{EntryType, SessionMemory};
+use zed42_memory::MemorySubstrate;
+use zed42_mom::capabilities::estimate_tokens;
+
+/// Token budget of a pack unless the Cortex sets one
+pub const DEFAULT_CONTEXT_BUDGET: usize = 6_000;
+
+/// Knowledge-graph hits considered per task
+const GRAPH_HITS: usize = 8;
+
+/// Session decisions considered per task
+const DECISIONS: usize = 5;
+
+/// Characters kept from a node's content or a decision
+const SNIPPET_CHARS: usize = 800;
+
+/// Smallest useful slice of a file
+const MIN_FILE_TOKENS: usize = 100;
+
+/// Assembles a [`ContextPack`] from the memory tiers that are available
+pub struct ContextPackBuilder<'a> {
+    knowledge_graph: Option<&'a KnowledgeGraphMemory>,
+    session: Option<&'a SessionMemory>,
+    repo_root: Option<&'a Path>,
+    budget: usize,
+}
+
+impl<'a> ContextPackBuilder<'a> {
+    pub fn new(budget: usize) -> Self {
+        Self {
+            knowledge_graph: None,
+            session: None,
+            repo_root: None,
+            budget,
+        }
+    }
+
+    /// Builder over the tiers of `memory`
+    pub fn from_memory(memory: &'a MemorySubstrate, budget: usize) -> Self {
+        Self {
+            knowledge_graph: memory.knowledge_graph(),
+            session: memory.session(),
+            repo_root: None,
+            budget,
+        }
+    }
+
+    pub fn with_knowledge_graph(mut self, graph: &'a KnowledgeGraphMemory) -> Self {
+        self.knowledge_graph = Some(graph);
+        self
+    }
+
+    pub fn with_session(mut self, session: &'a SessionMemory) -> Self {
+        self.session = Some(session);
+        self
+    }
+
+    /// Read impacted files from this checkout; without one files are skipped
+    pub fn with_repo_root(mut self, repo_root: &'a Path) -> Self {
+        self.repo_root = Some(repo_root);
+        self
+    }
+
+    /// Pack for a task described by `query`
+    pub async fn build(&self, query: &str) -> ContextPack {
+        let mut pack = ContextPack {
+            token_budget: self.budget,
+            ..Default::default()
+        };
+
+        let hits = self.graph_hits(query).await;
+        let graph_limit = self.budget * 2 / 5;
+        for hit in &hits {
+            let snippet = ContextSnippet {
+                source: ContextSource::KnowledgeGraph,
+                title: format!("{} ({})", hit.node.name, hit.node.node_type),
+                content: truncate(&hit.node.content, SNIPPET_CHARS),
+            };
+            push(&mut pack, snippet, graph_limit);
+        }
+
+        let decision_limit = (pack.estimated_tokens + self.budget / 5).min(self.budget);
+        for snippet in self.decisions(query) {
+            push(&mut pack, snippet, decision_limit);
+        }
+
+        if let Some(root) = self.repo_root {
+            let mut seen = HashSet::new();
+            for path in hits.iter().filter_map(|hit| zed42_toolboxes::graph::node_path(&hit.node)) {
+                if !seen.insert(path.clone()) {
+                    continue;
+                }
+                let remaining = self.budget.saturating_sub(pack.estimated_tokens);
+                if remaining < MIN_FILE_TOKENS {
+                    break;
+                }
+                let Some(content) = read_within(root, &path) else { continue };
+                // Leave room for the path and the truncation marker
+                let room = remaining.saturating_sub(estimate_tokens(&path) + 2) * 4;
+                let snippet = ContextSnippet {
+                    source: ContextSource::File,
+                    title: path,
+                    content: truncate(&content, room),
+                };
+                push(&mut pack, snippet, self.budget);
+            }
+        }
+
+        pack
+    }
+
+    async fn graph_hits(&self, query: &str) -> Vec<SearchResult> {
+        let Some(graph) = self.knowledge_graph else {
+            return Vec::new();
+        };
+        let search = SearchQuery::Semantic {
+            query_text: query.to_string(),
+            top_k: GRAPH_HITS,
+            node_types: None,
+        };
+        graph.search(search).await.unwrap_or_else(|e| {
+            tracing::warn!("Context pack: knowledge graph search failed: {:#}", e);
+            Vec::new()
+        })
+    }
+
+    fn decisions(&self, query: &str) -> Vec<ContextSnippet> {
+        let Some(session) = self.session else {
+            return Vec::new();
+        };
+        let entries = match fts_query(query) {
+            Some(fts) => session.search(&fts, DECISIONS * 4),
+            None => session.get_recent(Some(EntryType::Decision), DECISIONS),
+        };
+        let entries = entries.unwrap_or_else(|e| {
+            tracing::warn!("Context pack: session search failed: {:#}", e);
+            Vec::new()
+        });
+        entries
+            .into_iter()
+            .filter(|entry| entry.entry_type == EntryType::Decision)
+            .take(DECISIONS)
+            .map(|entry| {
+                let title = ["summary", "title", "description"]
+                    .iter()
+                    .find_map(|key| entry.content[*key].as_str())
+                    .map(|t| truncate(t, 120))
+                    .unwrap_or_else(|| "Decision".to_string());
+                let content = match entry.content.as_str() {
+                    Some(text) => text.to_string(),
+                    None => entry.content.to_string(),
+                };
+                ContextSnippet {
+                    source: ContextSource::Decision,
+                    title,
+                    content: truncate(&content, SNIPPET_CHARS),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Add `snippet` unless it would take the pack past `limit` tokens
+fn push(pack: &mut ContextPack, snippet: ContextSnippet, limit: usize) -> bool {
+    let tokens = estimate_tokens(&snippet.title) + estimate_tokens(&snippet.content);
+    if pack.estimated_tokens + tokens > limit {
+        return false;
+    }
+    pack.estimated_tokens += tokens;
+    pack.snippets.push(snippet);
+    true
+}
+
+/// FTS5 query matching any significant word of `text`
+fn fts_query(text: &str) -> Option<String> {
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.len() >= 3)
+        .map(|w| format!("\"{}\"", w.to_lowercase()))
+        .collect();
+    (!words.is_empty()).then(|| words.join(" OR "))
+}
+
+/// Contents of `path` under `root`, if it is a readable text file inside it
+fn read_within(root: &Path, path: &str) -> Option<String> {
+    let relative = Path::new(path);
+    if relative.is_absolute() || relative.components().any(|c| matches!(c, std::path::Component::ParentDir)) {
+        return None;
+    }
+    let full = root.join(relative).canonicalize().ok()?;
+    if !full.starts_with(root.canonicalize().ok()?) {
+        return None;
+    }
+    std::fs::read_to_string(full).ok()
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((cut, _)) => format!("{}\n...", &text[..cut]),
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::Arc;
+    use zed42_memory::knowledge_graph::{AnnConfig, KnowledgeNode};
+
+    async fn graph_with_auth_nodes(dir: &Path) -> KnowledgeGraphMemory {
+        let client: Arc<dyn zed42_llm::LlmClient> = Arc::new(zed42_llm::MockLlmClient::new(String::new()));
+        let graph = KnowledgeGraphMemory::new(dir, "packs", Some(client)).await.unwrap();
+        for (id, path) in [("validate_token", "src/auth.rs"), ("refresh_token", "src/auth.rs")] {
+            graph
+                .insert_node(KnowledgeNode {
+                    id: id.to_string(),
+                    node_type: "function".to_string(),
+                    name: id.to_string(),
+                    content: format!("fn {}(token: &str) -> bool", id),
+                    embedding: Some(vec![0.1; 1536]),
+                    metadata: json!({ "path": path }).to_string(),
+                    created_at: 0,
+                    updated_at: 0,
+                })
+                .await
+                .unwrap();
+        }
+        graph.enable_ann_index(AnnConfig::default()).await.unwrap();
+        graph
+    }
+
+    #[tokio::test]
+    async fn test_pack_combines_tiers_under_budget() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path().join("repo");
+        std::fs::create_dir_all(repo.join("src")).unwrap();
+        std::fs::write(repo.join("src/auth.rs"), "pub fn validate_token(token: &str) -> bool {\n    !token.is_empty()\n}\n").unwrap();
+
+        let graph = graph_with_auth_nodes(&dir.path().join("kg")).await;
+        let session = SessionMemory::new(uuid::Uuid::new_v4(), &dir.path().join("session")).unwrap();
+        session
+            .insert(EntryType::Decision, json!({ "summary": "Tokens are validated in middleware" }), None)
+            .unwrap();
+        session.insert(EntryType::UserMessage, json!("validate tokens please"), None).unwrap();
+
+        let pack = ContextPackBuilder::new(DEFAULT_CONTEXT_BUDGET)
+            .with_knowledge_graph(&graph)
+            .with_session(&session)
+            .with_repo_root(&repo)
+            .build("Validate auth tokens on every request")
+            .await;
+
+        let sources: Vec<ContextSource> = pack.snippets.iter().map(|s| s.source).collect();
+        assert_eq!(sources.iter().filter(|s| **s == ContextSource::KnowledgeGraph).count(), 2);
+        assert_eq!(sources.iter().filter(|s| **s == ContextSource::Decision).count(), 1);
+        // Both hits live in one file, which is included once
+        let files: Vec<&ContextSnippet> = pack.snippets.iter().filter(|s| s.source == ContextSource::File).collect();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].title, "src/auth.rs");
+        assert!(pack.estimated_tokens <= pack.token_budget);
+        assert!(pack.render().contains("Earlier decisions:\n- Tokens are validated in middleware"));
+
+        // A tight budget keeps fewer snippets and still fits
+        let small = ContextPackBuilder::new(20)
+            .with_knowledge_graph(&graph)
+            .with_session(&session)
+            .with_repo_root(&repo)
+            .build("Validate auth tokens on every request")
+            .await;
+        assert!(small.snippets.len() < pack.snippets.len());
+        assert!(small.estimated_tokens <= 20);
+    }
+
+    #[test]
+    fn test_files_outside_repo_are_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("secret.txt"), "x").unwrap();
+        let repo = dir.path().join("repo");
+        std::fs::create_dir_all(&repo).unwrap();
+        assert!(read_within(&repo, "../secret.txt").is_none());
+        assert!(read_within(&repo, dir.path().join("secret.txt").to_str().unwrap()).is_none());
+        assert_eq!(fts_query("Fix the DB: retry!"), Some("\"fix\" OR \"the\" OR \"retry\"".to_string()));
+    }
+}
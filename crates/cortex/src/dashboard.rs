@@ -0,0 +1,701 @@
+//! Live dashboard event stream
+//!
+//! The Cortex publishes what a live view of a session needs as
+//! [`DashboardEvent`]s on its [`EventBus`]: agents with their status and
+//! current task, task progress, VOX traffic, alerts, circuit breaker states
+//! and budgets. The bus folds every event into a [`DashboardState`], so a
+//! viewer that connects late starts from a snapshot instead of an empty
+//! screen.
+//!
+//! Events go out wrapped in an [`EventEnvelope`] carrying the schema
+//! version ([`EVENT_SCHEMA_VERSION`]) and a sequence number. The bus keeps
+//! the last [`BACKFILL_CAPACITY`] envelopes: [`EventBus::backfill`] returns
+//! the last N, and a viewer that reconnects with the last sequence number
+//! it saw gets exactly what it missed ([`EventBus::since`]), or a snapshot
+//! when that has already been dropped.
+//!
+//! The desktop UI, `zed42 top` and browser viewers read the same bus: in
+//! process through [`EventBus::subscribe`], as JSON lines over TCP
+//! ([`EventBus::serve`] and [`DashboardClient`]) or over WebSocket
+//! ([`EventBus::serve_websocket`]).
+
+pub mod tui;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use futures_util::{SinkExt, StreamExt};
+use parking_lot::Mutex;
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast;
+use tokio_tungstenite::tungstenite::handshake::server::{Request, Response};
+use tokio_tungstenite::tungstenite::Message as WsMessage;
+use zed42_blackboard::TaskProgress;
+use zed42_core::types::{AgentId, AgentStatus, TaskId};
+use zed42_core::vox::{VoxMessage, VoxPayload};
+use zed42_core::CancellationToken;
+use zed42_ledger::types::Budget;
+use zed42_toolboxes::approval::{ApprovalDecision, ApprovalRequest};
+
+/// Address `serve_dashboard` and `zed42 top` use when none is given
+pub const DEFAULT_DASHBOARD_ADDR: &str = "127.0.0.1:4242";
+
+/// Messages older than this no longer count towards throughput
+pub const THROUGHPUT_WINDOW_SECS: i64 = 60;
+
+/// Version of the event schema; bumped on any incompatible change
+pub const EVENT_SCHEMA_VERSION: u32 = 1;
+
+/// Envelopes kept for backfill
+pub const BACKFILL_CAPACITY: usize = 1000;
+
+/// Alerts kept in the dashboard state, newest last
+pub const RECENT_ALERTS: usize = 50;
+
+/// Events buffered per subscriber before it lags and gets a fresh snapshot
+const CHANNEL_CAPACITY: usize = 1024;
+
+/// What the dashboard shows about one agent
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AgentView {
+    pub agent_type: String,
+    pub status: AgentStatus,
+    /// Description of the task the agent is working on
+    #[serde(default)]
+    pub task: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// State of one model's circuit breaker
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CircuitView {
+    pub model: String,
+    /// `Closed`, `Open` or `HalfOpen`
+    pub state: String,
+    pub failures: u32,
+}
+
+impl From<zed42_mom::circuit_breaker::CircuitStatus> for CircuitView {
+    fn from(status: zed42_mom::circuit_breaker::CircuitStatus) -> Self {
+        Self {
+            model: status.model,
+            state: status.state,
+            failures: status.failures,
+        }
+    }
+}
+
+/// A budget and where its spending stood when the dashboard first saw it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetView {
+    pub budget: Budget,
+    pub baseline_spent: rust_decimal::Decimal,
+    pub baseline_at: DateTime<Utc>,
+}
+
+impl BudgetView {
+    /// Share of the hard limit spent, from 0 to 1
+    pub fn used(&self) -> f64 {
+        if self.budget.hard_limit <= rust_decimal::Decimal::ZERO {
+            return 0.0;
+        }
+        (self.budget.spent / self.budget.hard_limit).to_f64().unwrap_or(0.0).clamp(0.0, 1.0)
+    }
+
+    /// Spending per hour since the baseline
+    pub fn burn_per_hour(&self) -> f64 {
+        let hours = (self.budget.updated_at - self.baseline_at).num_seconds() as f64 / 3600.0;
+        if hours <= 0.0 {
+            return 0.0;
+        }
+        (self.budget.spent - self.baseline_spent).to_f64().unwrap_or(0.0) / hours
+    }
+
+    /// Time until the hard limit at the current burn rate
+    pub fn exhausted_in(&self) -> Option<chrono::Duration> {
+        let burn = self.burn_per_hour();
+        if burn <= 0.0 {
+            return None;
+        }
+        let remaining = (self.budget.hard_limit - self.budget.spent).to_f64()?.max(0.0);
+        chrono::Duration::try_seconds((remaining / burn * 3600.0) as i64)
+    }
+}
+
+/// A system alert as the dashboard shows it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlertView {
+    /// e.g. `approval_required`, `task_stalled`, `worker_crashed`
+    pub action: String,
+    #[serde(default)]
+    pub agent_id: Option<AgentId>,
+    pub reason: String,
+    pub at: DateTime<Utc>,
+}
+
+/// One change to what the dashboard shows
+///
+/// Part of the versioned schema: variants and fields may be added, but
+/// renaming or removing one needs a new [`EVENT_SCHEMA_VERSION`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DashboardEvent {
+    /// Everything at once; replaces the viewer's state
+    Snapshot { state: DashboardState },
+    AgentUpdated { agent_id: AgentId, agent: AgentView },
+    AgentRemoved { agent_id: AgentId },
+    /// Latest progress report of a task
+    TaskProgress { progress: TaskProgress },
+    /// A VOX message went over the bus
+    Message { team: String, at: DateTime<Utc> },
+    Alert { alert: AlertView },
+    Circuits { circuits: Vec<CircuitView> },
+    Budget { budget: Budget },
+    /// Burn-down warning for a budget; `None` clears it
+    BudgetForecast { entity_id: String, warning: Option<String> },
+    /// A dangerous tool call waits for approval
+    ApprovalRequested { request: ApprovalRequest },
+    ApprovalResolved { request_id: String, decision: ApprovalDecision, actor: String },
+    /// Every task of a run finished
+    RunComplete { run_id: String, summary: String, success: bool },
+}
+
+impl DashboardEvent {
+    /// Events a VOX message implies: always its traffic, alerts and ghost agents
+    pub fn from_vox(message: &VoxMessage) -> Vec<Self> {
+        let mut events = vec![DashboardEvent::Message {
+            team: message.target_team.clone(),
+            at: message.created_at,
+        }];
+        if let VoxPayload::SystemAlert { action, agent_id, reason } = &message.payload {
+            events.push(DashboardEvent::Alert {
+                alert: AlertView {
+                    action: action.clone(),
+                    agent_id: *agent_id,
+                    reason: reason.clone(),
+                    at: message.created_at,
+                },
+            });
+            if let (Some(agent_id), "dissolve_ghost") = (agent_id, action.as_str()) {
+                events.push(DashboardEvent::AgentRemoved { agent_id: *agent_id });
+            }
+        }
+        events
+    }
+}
+
+/// A published event with its place in the stream
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventEnvelope {
+    /// Schema version, [`EVENT_SCHEMA_VERSION`] when published
+    pub v: u32,
+    /// Position in the bus's stream, starting at 1; snapshots sent to a
+    /// viewer carry the sequence number of the last event they include
+    pub seq: u64,
+    pub at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub event: DashboardEvent,
+}
+
+/// Everything a dashboard shows, built up from events
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DashboardState {
+    pub agents: BTreeMap<AgentId, AgentView>,
+    /// Latest progress of each running task
+    #[serde(default)]
+    pub tasks: BTreeMap<TaskId, TaskProgress>,
+    /// Send times of messages inside the throughput window, oldest first
+    pub recent_messages: VecDeque<DateTime<Utc>>,
+    pub total_messages: u64,
+    /// Most recent alerts, oldest first
+    #[serde(default)]
+    pub alerts: VecDeque<AlertView>,
+    pub circuits: Vec<CircuitView>,
+    pub budgets: BTreeMap<String, BudgetView>,
+    /// Burn-down warnings, by entity
+    #[serde(default)]
+    pub budget_warnings: BTreeMap<String, String>,
+    /// Tool calls waiting for approval, by request id
+    #[serde(default)]
+    pub approvals: BTreeMap<String, ApprovalRequest>,
+}
+
+impl DashboardState {
+    pub fn apply(&mut self, event: DashboardEvent) {
+        match event {
+            DashboardEvent::Snapshot { state } => *self = state,
+            DashboardEvent::AgentUpdated { agent_id, agent } => {
+                self.agents.insert(agent_id, agent);
+            }
+            DashboardEvent::AgentRemoved { agent_id } => {
+                self.agents.remove(&agent_id);
+                self.tasks.retain(|_, progress| progress.agent_id != agent_id);
+            }
+            DashboardEvent::TaskProgress { progress } => {
+                self.tasks.insert(progress.task_id.clone(), progress);
+            }
+            DashboardEvent::Message { at, .. } => {
+                self.total_messages += 1;
+                let position = self.recent_messages.partition_point(|t| *t <= at);
+                self.recent_messages.insert(position, at);
+                self.prune(at);
+            }
+            DashboardEvent::Alert { alert } => {
+                self.alerts.push_back(alert);
+                while self.alerts.len() > RECENT_ALERTS {
+                    self.alerts.pop_front();
+                }
+            }
+            DashboardEvent::Circuits { circuits } => self.circuits = circuits,
+            DashboardEvent::Budget { budget } => match self.budgets.get_mut(&budget.entity_id) {
+                Some(view) => view.budget = budget,
+                None => {
+                    let view = BudgetView {
+                        baseline_spent: budget.spent,
+                        baseline_at: budget.updated_at,
+                        budget,
+                    };
+                    self.budgets.insert(view.budget.entity_id.clone(), view);
+                }
+            },
+            DashboardEvent::BudgetForecast { entity_id, warning } => match warning {
+                Some(warning) => {
+                    self.budget_warnings.insert(entity_id, warning);
+                }
+                None => {
+                    self.budget_warnings.remove(&entity_id);
+                }
+            },
+            DashboardEvent::ApprovalRequested { request } => {
+                self.approvals.insert(request.id.clone(), request);
+            }
+            DashboardEvent::ApprovalResolved { request_id, .. } => {
+                self.approvals.remove(&request_id);
+            }
+            // Only notifications react to finished runs
+            DashboardEvent::RunComplete { .. } => {}
+        }
+    }
+
+    /// Messages per second over the throughput window ending at `now`
+    pub fn throughput(&self, now: DateTime<Utc>) -> f64 {
+        let since = now - chrono::Duration::seconds(THROUGHPUT_WINDOW_SECS);
+        let count = self.recent_messages.iter().filter(|t| **t > since && **t <= now).count();
+        count as f64 / THROUGHPUT_WINDOW_SECS as f64
+    }
+
+    fn prune(&mut self, now: DateTime<Utc>) {
+        let since = now - chrono::Duration::seconds(THROUGHPUT_WINDOW_SECS);
+        while self.recent_messages.front().is_some_and(|t| *t <= since) {
+            self.recent_messages.pop_front();
+        }
+    }
+}
+
+/// Publishes dashboard events, keeps the state they add up to and the latest envelopes
+pub struct EventBus {
+    sender: broadcast::Sender<EventEnvelope>,
+    inner: Mutex<BusState>,
+}
+
+#[derive(Default)]
+struct BusState {
+    state: DashboardState,
+    seq: u64,
+    history: VecDeque<EventEnvelope>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self {
+            sender,
+            inner: Mutex::new(BusState::default()),
+        }
+    }
+
+    pub fn publish(&self, event: DashboardEvent) {
+        let mut inner = self.inner.lock();
+        inner.state.apply(event.clone());
+        inner.seq += 1;
+        let envelope = EventEnvelope { v: EVENT_SCHEMA_VERSION, seq: inner.seq, at: Utc::now(), event };
+        if inner.history.len() == BACKFILL_CAPACITY {
+            inner.history.pop_front();
+        }
+        inner.history.push_back(envelope.clone());
+        // Nobody watching is fine
+        let _ = self.sender.send(envelope);
+    }
+
+    pub fn snapshot(&self) -> DashboardState {
+        self.inner.lock().state.clone()
+    }
+
+    /// Current state wrapped as a `Snapshot` envelope at the latest sequence number
+    pub fn snapshot_envelope(&self) -> EventEnvelope {
+        let inner = self.inner.lock();
+        snapshot_envelope(&inner)
+    }
+
+    /// Sequence number of the latest event, 0 before the first
+    pub fn last_seq(&self) -> u64 {
+        self.inner.lock().seq
+    }
+
+    /// The last `n` envelopes, oldest first
+    pub fn backfill(&self, n: usize) -> Vec<EventEnvelope> {
+        let inner = self.inner.lock();
+        let skip = inner.history.len().saturating_sub(n);
+        inner.history.iter().skip(skip).cloned().collect()
+    }
+
+    /// Envelopes after `seq`, or `None` if some of them were already dropped
+    pub fn since(&self, seq: u64) -> Option<Vec<EventEnvelope>> {
+        let inner = self.inner.lock();
+        since(&inner, seq)
+    }
+
+    /// Current state and every event after it
+    pub fn subscribe(&self) -> (DashboardState, broadcast::Receiver<EventEnvelope>) {
+        let inner = self.inner.lock();
+        (inner.state.clone(), self.sender.subscribe())
+    }
+
+    /// What a viewer that last saw `seq` needs to catch up, and the live stream
+    ///
+    /// The missed envelopes when they are all still kept, otherwise (or
+    /// without `seq`) a snapshot.
+    pub fn resume(&self, seq: Option<u64>) -> (Vec<EventEnvelope>, broadcast::Receiver<EventEnvelope>) {
+        let inner = self.inner.lock();
+        let catch_up = seq
+            .and_then(|seq| since(&inner, seq))
+            .unwrap_or_else(|| vec![snapshot_envelope(&inner)]);
+        (catch_up, self.sender.subscribe())
+    }
+
+    /// Stream events as JSON lines to every client of `listener` until `shutdown`
+    ///
+    /// Each client gets a snapshot first, and a new snapshot whenever it
+    /// falls behind.
+    pub async fn serve(self: Arc<Self>, listener: TcpListener, shutdown: CancellationToken) {
+        self.accept_loop(listener, shutdown, "Dashboard", |bus, stream, token| async move {
+            bus.stream_to(stream, token).await
+        })
+        .await
+    }
+
+    /// Stream events as WebSocket text frames to every client of `listener` until `shutdown`
+    ///
+    /// A client reconnecting with `?since=<seq>` in the request path gets
+    /// the events it missed; other clients start from a snapshot.
+    pub async fn serve_websocket(self: Arc<Self>, listener: TcpListener, shutdown: CancellationToken) {
+        self.accept_loop(listener, shutdown, "WebSocket", |bus, stream, token| async move {
+            bus.websocket_to(stream, token).await
+        })
+        .await
+    }
+
+    async fn accept_loop<F, Fut>(self: Arc<Self>, listener: TcpListener, shutdown: CancellationToken, kind: &'static str, handle: F)
+    where
+        F: Fn(Arc<Self>, TcpStream, CancellationToken) -> Fut,
+        Fut: std::future::Future<Output = Result<()>> + Send + 'static,
+    {
+        loop {
+            let (stream, peer) = tokio::select! {
+                _ = shutdown.cancelled() => break,
+                accepted = listener.accept() => match accepted {
+                    Ok(accepted) => accepted,
+                    Err(e) => {
+                        tracing::warn!(error = %e, "Failed to accept {} client", kind);
+                        continue;
+                    }
+                },
+            };
+            tracing::debug!(%peer, "{} client connected", kind);
+            let session = handle(self.clone(), stream, shutdown.clone());
+            tokio::spawn(async move {
+                if let Err(e) = session.await {
+                    tracing::debug!(%peer, error = %e, "{} client disconnected", kind);
+                }
+            });
+        }
+    }
+
+    async fn stream_to(&self, mut stream: TcpStream, shutdown: CancellationToken) -> Result<()> {
+        let (catch_up, mut events) = self.resume(None);
+        for envelope in &catch_up {
+            write_event(&mut stream, envelope).await?;
+        }
+        loop {
+            let Some(envelope) = self.next_live(&mut events, &shutdown).await else {
+                return Ok(());
+            };
+            write_event(&mut stream, &envelope).await?;
+        }
+    }
+
+    async fn websocket_to(&self, stream: TcpStream, shutdown: CancellationToken) -> Result<()> {
+        let mut since_seq = None;
+        let mut socket = tokio_tungstenite::accept_hdr_async(stream, |request: &Request, response: Response| {
+            since_seq = request.uri().query().and_then(since_param);
+            Ok(response)
+        })
+        .await
+        .context("WebSocket handshake failed")?;
+
+        let (catch_up, mut events) = self.resume(since_seq);
+        for envelope in &catch_up {
+            socket.send(WsMessage::Text(serde_json::to_string(envelope)?)).await?;
+        }
+        loop {
+            let envelope = tokio::select! {
+                envelope = self.next_live(&mut events, &shutdown) => envelope,
+                // Drain pings and notice closes; viewers send nothing else
+                incoming = socket.next() => match incoming {
+                    Some(Ok(WsMessage::Close(_))) | None => return Ok(()),
+                    Some(Ok(_)) => continue,
+                    Some(Err(e)) => return Err(e.into()),
+                },
+            };
+            let Some(envelope) = envelope else {
+                let _ = socket.close(None).await;
+                return Ok(());
+            };
+            socket.send(WsMessage::Text(serde_json::to_string(&envelope)?)).await?;
+        }
+    }
+
+    /// Next envelope for a viewer; a snapshot after lagging, `None` when done
+    async fn next_live(&self, events: &mut broadcast::Receiver<EventEnvelope>, shutdown: &CancellationToken) -> Option<EventEnvelope> {
+        let event = tokio::select! {
+            _ = shutdown.cancelled() => return None,
+            event = events.recv() => event,
+        };
+        match event {
+            Ok(envelope) => Some(envelope),
+            Err(broadcast::error::RecvError::Lagged(_)) => Some(self.snapshot_envelope()),
+            Err(broadcast::error::RecvError::Closed) => None,
+        }
+    }
+}
+
+fn snapshot_envelope(inner: &BusState) -> EventEnvelope {
+    EventEnvelope {
+        v: EVENT_SCHEMA_VERSION,
+        seq: inner.seq,
+        at: Utc::now(),
+        event: DashboardEvent::Snapshot { state: inner.state.clone() },
+    }
+}
+
+fn since(inner: &BusState, seq: u64) -> Option<Vec<EventEnvelope>> {
+    if seq >= inner.seq {
+        return Some(Vec::new());
+    }
+    // Every envelope after `seq` must still be kept
+    let oldest = inner.history.front()?.seq;
+    if seq + 1 < oldest {
+        return None;
+    }
+    Some(inner.history.iter().filter(|envelope| envelope.seq > seq).cloned().collect())
+}
+
+/// `since` from a query string such as `since=42`
+fn since_param(query: &str) -> Option<u64> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == "since")
+        .and_then(|(_, value)| value.parse().ok())
+}
+
+async fn write_event(stream: &mut TcpStream, envelope: &EventEnvelope) -> Result<()> {
+    let mut line = serde_json::to_vec(envelope)?;
+    line.push(b'\n');
+    stream.write_all(&line).await?;
+    Ok(())
+}
+
+/// Reads a dashboard stream served by another process
+pub struct DashboardClient {
+    lines: Lines<BufReader<TcpStream>>,
+}
+
+impl DashboardClient {
+    pub async fn connect(addr: &str) -> Result<Self> {
+        let stream = TcpStream::connect(addr)
+            .await
+            .with_context(|| format!("Failed to connect to the dashboard at {}", addr))?;
+        Ok(Self {
+            lines: BufReader::new(stream).lines(),
+        })
+    }
+
+    /// The next envelope, or `None` once the Cortex closes the stream
+    pub async fn next_envelope(&mut self) -> Result<Option<EventEnvelope>> {
+        let Some(line) = self.lines.next_line().await? else {
+            return Ok(None);
+        };
+        let envelope: EventEnvelope = serde_json::from_str(&line).context("Malformed dashboard event")?;
+        if envelope.v > EVENT_SCHEMA_VERSION {
+            anyhow::bail!(
+                "Dashboard speaks event schema v{}, this viewer only v{}",
+                envelope.v,
+                EVENT_SCHEMA_VERSION
+            );
+        }
+        Ok(Some(envelope))
+    }
+
+    /// The next event, or `None` once the Cortex closes the stream
+    pub async fn next_event(&mut self) -> Result<Option<DashboardEvent>> {
+        Ok(self.next_envelope().await?.map(|envelope| envelope.event))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+
+    fn budget(spent: i64, updated_at: DateTime<Utc>) -> Budget {
+        Budget {
+            entity_id: "session".to_string(),
+            hard_limit: Decimal::from(10),
+            soft_limit: Decimal::from(8),
+            spent: Decimal::from(spent),
+            currency: "USD".to_string(),
+            status: Default::default(),
+            updated_at,
+            tags: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_state_folds_events() {
+        let now = Utc::now();
+        let mut state = DashboardState::default();
+        let agent_id = AgentId::new_v4();
+        state.apply(DashboardEvent::AgentUpdated {
+            agent_id,
+            agent: AgentView {
+                agent_type: "FeatureImplementer".to_string(),
+                status: AgentStatus::Working,
+                task: Some("Add login".to_string()),
+                updated_at: now,
+            },
+        });
+        for secs in [90, 30, 10] {
+            state.apply(DashboardEvent::Message { team: "blue".to_string(), at: now - chrono::Duration::seconds(secs) });
+        }
+        assert_eq!(state.total_messages, 3);
+        assert_eq!(state.recent_messages.len(), 2);
+        assert!((state.throughput(now) - 2.0 / 60.0).abs() < 1e-9);
+
+        state.apply(DashboardEvent::Budget { budget: budget(2, now - chrono::Duration::hours(2)) });
+        state.apply(DashboardEvent::Budget { budget: budget(6, now) });
+        let view = &state.budgets["session"];
+        assert!((view.used() - 0.6).abs() < 1e-9);
+        assert!((view.burn_per_hour() - 2.0).abs() < 1e-9);
+        assert_eq!(view.exhausted_in(), Some(chrono::Duration::hours(2)));
+
+        let ghost = VoxMessage {
+            sender: surrealdb::sql::Thing::from(("system", "aura")),
+            target_team: "all".to_string(),
+            priority: 255,
+            correlation_id: uuid::Uuid::new_v4(),
+            payload: VoxPayload::SystemAlert {
+                action: "dissolve_ghost".to_string(),
+                agent_id: Some(agent_id),
+                reason: "no pulse".to_string(),
+            },
+            created_at: now,
+            ttl_secs: None,
+            idempotency_key: None,
+        };
+        for event in DashboardEvent::from_vox(&ghost) {
+            state.apply(event);
+        }
+        assert!(state.agents.is_empty());
+        assert_eq!(state.total_messages, 4);
+    }
+
+    #[tokio::test]
+    async fn test_client_receives_snapshot_then_events() {
+        let feed = Arc::new(EventBus::new());
+        feed.publish(DashboardEvent::Circuits {
+            circuits: vec![CircuitView { model: "gpt-4o".to_string(), state: "Open".to_string(), failures: 3 }],
+        });
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let shutdown = CancellationToken::new();
+        tokio::spawn(feed.clone().serve(listener, shutdown.clone()));
+
+        let mut client = DashboardClient::connect(&addr).await.unwrap();
+        let mut state = DashboardState::default();
+        state.apply(client.next_event().await.unwrap().unwrap());
+        assert_eq!(state.circuits[0].state, "Open");
+
+        feed.publish(DashboardEvent::Message { team: "red".to_string(), at: Utc::now() });
+        state.apply(client.next_event().await.unwrap().unwrap());
+        assert_eq!(state.total_messages, 1);
+        shutdown.cancel();
+    }
+
+    async fn next_envelope<S>(socket: &mut tokio_tungstenite::WebSocketStream<S>) -> EventEnvelope
+    where
+        S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+    {
+        match socket.next().await.unwrap().unwrap() {
+            WsMessage::Text(text) => serde_json::from_str(&text).unwrap(),
+            other => panic!("unexpected frame {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_backfill_and_websocket_resume() {
+        let bus = Arc::new(EventBus::new());
+        let agent_id = AgentId::new_v4();
+        bus.publish(DashboardEvent::TaskProgress { progress: TaskProgress::new(agent_id, "t1", "reading code") });
+        bus.publish(DashboardEvent::Alert {
+            alert: AlertView { action: "task_stalled".to_string(), agent_id: Some(agent_id), reason: "t1".to_string(), at: Utc::now() },
+        });
+        bus.publish(DashboardEvent::Message { team: "blue".to_string(), at: Utc::now() });
+
+        let last_two: Vec<u64> = bus.backfill(2).iter().map(|envelope| envelope.seq).collect();
+        assert_eq!(last_two, vec![2, 3]);
+        assert_eq!(bus.since(1).unwrap().len(), 2);
+        assert!(bus.since(3).unwrap().is_empty());
+        let json = serde_json::to_value(&bus.backfill(1)[0]).unwrap();
+        assert_eq!((json["v"].as_u64(), json["kind"].as_str()), (Some(EVENT_SCHEMA_VERSION as u64), Some("message")));
+
+        let state = bus.snapshot();
+        assert_eq!(state.tasks["t1"].step, "reading code");
+        assert_eq!(state.alerts.len(), 1);
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let shutdown = CancellationToken::new();
+        tokio::spawn(bus.clone().serve_websocket(listener, shutdown.clone()));
+
+        // A reconnecting viewer gets only what it missed, then live events
+        let (mut socket, _) = tokio_tungstenite::connect_async(format!("ws://{}/events?since=2", addr)).await.unwrap();
+        assert_eq!(next_envelope(&mut socket).await.seq, 3);
+        bus.publish(DashboardEvent::AgentRemoved { agent_id });
+        let live = next_envelope(&mut socket).await;
+        assert_eq!(live.seq, 4);
+        assert!(matches!(live.event, DashboardEvent::AgentRemoved { .. }));
+        assert!(bus.snapshot().tasks.is_empty());
+        shutdown.cancel();
+    }
+}
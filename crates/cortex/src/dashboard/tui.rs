@@ -0,0 +1,238 @@
+//! Terminal view of the dashboard stream (`zed42 top`)
+
+use super::{DashboardClient, DashboardState};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::execute;
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
+use ratatui::backend::{Backend, CrosstermBackend};
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Cell, Paragraph, Row, Table};
+use ratatui::{Frame, Terminal};
+use std::time::Duration;
+use zed42_core::types::AgentStatus;
+
+/// How often the screen redraws without new events
+const REFRESH: Duration = Duration::from_millis(250);
+
+/// Width of the budget bars, in cells
+const BAR_WIDTH: usize = 20;
+
+/// Take over the terminal and show `client`'s stream until `q`, Esc or Ctrl-C
+pub async fn run(mut client: DashboardClient, addr: &str) -> Result<()> {
+    enable_raw_mode()?;
+    let mut stdout = std::io::stdout();
+    execute!(stdout, EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout))?;
+
+    let result = event_loop(&mut terminal, &mut client, addr).await;
+
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+    result
+}
+
+async fn event_loop<B: Backend>(terminal: &mut Terminal<B>, client: &mut DashboardClient, addr: &str) -> Result<()> {
+    let mut state = DashboardState::default();
+    let mut connected = true;
+    let mut refresh = tokio::time::interval(REFRESH);
+    loop {
+        tokio::select! {
+            event = client.next_event(), if connected => match event? {
+                // Drain bursts before redrawing
+                Some(event) => {
+                    state.apply(event);
+                    continue;
+                }
+                None => connected = false,
+            },
+            _ = refresh.tick() => {}
+        }
+        terminal.draw(|frame| draw(frame, &state, Utc::now(), addr, connected))?;
+        while crossterm::event::poll(Duration::ZERO)? {
+            if let Event::Key(key) = crossterm::event::read()? {
+                let ctrl_c = key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+                if key.kind == KeyEventKind::Press && (ctrl_c || matches!(key.code, KeyCode::Char('q') | KeyCode::Esc)) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+/// Draw one frame: totals, budget warnings, agents, circuits and budgets
+pub fn draw(frame: &mut Frame, state: &DashboardState, now: DateTime<Utc>, addr: &str, connected: bool) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1),
+            Constraint::Length(state.budget_warnings.len() as u16),
+            Constraint::Min(5),
+            Constraint::Length(10),
+        ])
+        .split(frame.size());
+    let bottom = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+        .split(rows[3]);
+
+    let link = if connected {
+        Span::styled(format!("● {}", addr), Style::default().fg(Color::Green))
+    } else {
+        Span::styled(format!("○ {} (disconnected)", addr), Style::default().fg(Color::Red))
+    };
+    let header = Line::from(vec![
+        Span::styled("ZED42 top  ", Style::default().add_modifier(Modifier::BOLD)),
+        link,
+        Span::raw(format!(
+            "  agents {}  msgs/s {:.2}  msgs {}  [q] quit",
+            state.agents.len(),
+            state.throughput(now),
+            state.total_messages
+        )),
+    ]);
+    frame.render_widget(Paragraph::new(header), rows[0]);
+
+    let warnings: Vec<Line> = state
+        .budget_warnings
+        .values()
+        .map(|warning| Line::styled(format!("⚠ {}", warning), Style::default().fg(Color::Red)))
+        .collect();
+    frame.render_widget(Paragraph::new(warnings), rows[1]);
+
+    let agents = state.agents.iter().map(|(agent_id, agent)| {
+        Row::new(vec![
+            Cell::from(agent_id.to_string()[..8].to_string()),
+            Cell::from(agent.agent_type.clone()),
+            Cell::from(format!("{:?}", agent.status)).style(Style::default().fg(status_color(&agent.status))),
+            Cell::from(agent.task.clone().unwrap_or_else(|| "-".to_string())),
+            Cell::from(age(now, agent.updated_at)),
+        ])
+    });
+    let agents = Table::new(
+        agents,
+        [Constraint::Length(8), Constraint::Length(20), Constraint::Length(10), Constraint::Min(20), Constraint::Length(6)],
+    )
+    .header(bold_row(["Agent", "Type", "Status", "Task", "Age"]))
+    .block(Block::default().borders(Borders::ALL).title("Agents"));
+    frame.render_widget(agents, rows[2]);
+
+    let circuits = state.circuits.iter().map(|circuit| {
+        let color = match circuit.state.as_str() {
+            "Closed" => Color::Green,
+            "HalfOpen" => Color::Yellow,
+            _ => Color::Red,
+        };
+        Row::new(vec![
+            Cell::from(circuit.model.clone()),
+            Cell::from(circuit.state.clone()).style(Style::default().fg(color)),
+            Cell::from(circuit.failures.to_string()),
+        ])
+    });
+    let circuits = Table::new(circuits, [Constraint::Min(12), Constraint::Length(9), Constraint::Length(5)])
+        .header(bold_row(["Model", "State", "Fails"]))
+        .block(Block::default().borders(Borders::ALL).title("Circuit breakers"));
+    frame.render_widget(circuits, bottom[0]);
+
+    let budgets = state.budgets.values().map(|view| {
+        let used = view.used();
+        let color = if view.budget.spent >= view.budget.hard_limit {
+            Color::Red
+        } else if view.budget.spent >= view.budget.soft_limit {
+            Color::Yellow
+        } else {
+            Color::Green
+        };
+        let filled = (used * BAR_WIDTH as f64).round() as usize;
+        Row::new(vec![
+            Cell::from(view.budget.entity_id.clone()),
+            Cell::from(format!("{} / {} {}", view.budget.spent.round_dp(2), view.budget.hard_limit, view.budget.currency)),
+            Cell::from(format!("{}{} {:>3.0}%", "█".repeat(filled), "░".repeat(BAR_WIDTH - filled), used * 100.0))
+                .style(Style::default().fg(color)),
+            Cell::from(format!("{:.2}/h", view.burn_per_hour())),
+            Cell::from(view.exhausted_in().map_or_else(|| "-".to_string(), format_duration)),
+        ])
+    });
+    let budgets = Table::new(
+        budgets,
+        [
+            Constraint::Min(10),
+            Constraint::Length(18),
+            Constraint::Length(BAR_WIDTH as u16 + 5),
+            Constraint::Length(9),
+            Constraint::Length(8),
+        ],
+    )
+    .header(bold_row(["Entity", "Spent", "Burn-down", "Rate", "Left"]))
+    .block(Block::default().borders(Borders::ALL).title("Budgets"));
+    frame.render_widget(budgets, bottom[1]);
+}
+
+fn bold_row<const N: usize>(titles: [&'static str; N]) -> Row<'static> {
+    Row::new(titles).style(Style::default().add_modifier(Modifier::BOLD))
+}
+
+fn status_color(status: &AgentStatus) -> Color {
+    match status {
+        AgentStatus::Working => Color::Green,
+        AgentStatus::Idle => Color::Gray,
+        AgentStatus::Paused | AgentStatus::Laggard => Color::Yellow,
+        AgentStatus::Failed | AgentStatus::Ghost => Color::Red,
+        AgentStatus::Terminated => Color::DarkGray,
+    }
+}
+
+fn age(now: DateTime<Utc>, then: DateTime<Utc>) -> String {
+    format_duration(now - then)
+}
+
+/// `42s`, `7m`, `3h`
+fn format_duration(duration: chrono::Duration) -> String {
+    let secs = duration.num_seconds().max(0);
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m", secs / 60),
+        _ => format!("{}h", secs / 3600),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dashboard::{AgentView, CircuitView, DashboardEvent};
+    use ratatui::backend::TestBackend;
+
+    #[test]
+    fn test_draw_shows_agents_and_circuits() {
+        let now = Utc::now();
+        let mut state = DashboardState::default();
+        state.apply(DashboardEvent::AgentUpdated {
+            agent_id: zed42_core::types::AgentId::new_v4(),
+            agent: AgentView {
+                agent_type: "TestEngineer".to_string(),
+                status: AgentStatus::Working,
+                task: Some("Cover the parser".to_string()),
+                updated_at: now,
+            },
+        });
+        state.apply(DashboardEvent::Circuits {
+            circuits: vec![CircuitView { model: "gpt-4o-mini".to_string(), state: "Open".to_string(), failures: 3 }],
+        });
+        state.apply(DashboardEvent::BudgetForecast {
+            entity_id: "session".to_string(),
+            warning: Some("session budget exhausts in ~12 minutes".to_string()),
+        });
+
+        let mut terminal = Terminal::new(TestBackend::new(120, 30)).unwrap();
+        terminal.draw(|frame| draw(frame, &state, now, "127.0.0.1:4242", true)).unwrap();
+        let screen: String = terminal.backend().buffer().content().iter().map(|cell| cell.symbol()).collect();
+        for expected in ["agents 1", "exhausts in ~12 minutes", "TestEngineer", "Cover the parser", "gpt-4o-mini", "Open"] {
+            assert!(screen.contains(expected), "missing {:?}", expected);
+        }
+        assert_eq!(format_duration(chrono::Duration::seconds(7260)), "2h");
+    }
+}
@@ -0,0 +1,354 @@
+//! Cost and duration estimates for a plan
+//!
+//! Before the plan-review gate the Cortex projects LLM calls, prompt tokens,
+//! cost and wall-clock time for every planned task. Projections come from
+//! routing logs of earlier tasks handled by the same agent (falling back to
+//! all history, then to fixed defaults), reported as an interquartile range.
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::Duration;
+use zed42_mom::capabilities::estimate_tokens;
+use zed42_mom::types::RoutingLog;
+
+/// Assumed shape of a task when there is no history at all
+const DEFAULT_CALLS: f64 = 4.0;
+const DEFAULT_PROMPT_TOKENS_PER_CALL: f64 = 2_000.0;
+const DEFAULT_COST_PER_CALL: Decimal = Decimal::from_parts(2, 0, 0, false, 2); // $0.02
+const DEFAULT_SECS_PER_CALL: f64 = 15.0;
+
+/// A task from the plan, tagged with the agent that will handle it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlannedTask {
+    pub task_id: String,
+    pub description: String,
+    /// Agent id used in routing logs
+    pub agent_id: String,
+}
+
+/// Low/high bounds of a projection
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct Range<T> {
+    pub low: T,
+    pub high: T,
+}
+
+/// Where a task's projection came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EstimateBasis {
+    /// Earlier tasks handled by the same agent
+    SameAgent,
+    /// Every historical task
+    AllHistory,
+    /// No usable history
+    Defaults,
+}
+
+/// Projection for one task
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskEstimate {
+    pub task_id: String,
+    pub description: String,
+    pub llm_calls: Range<f64>,
+    pub prompt_tokens: Range<u64>,
+    pub cost: Range<Decimal>,
+    pub wall_clock: Range<Duration>,
+    pub basis: EstimateBasis,
+    /// Historical tasks the projection is based on
+    pub samples: usize,
+}
+
+/// Projection for a whole plan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunEstimate {
+    pub tasks: Vec<TaskEstimate>,
+    pub llm_calls: Range<f64>,
+    pub prompt_tokens: Range<u64>,
+    pub cost: Range<Decimal>,
+    /// Tasks are assumed to run one after another
+    pub wall_clock: Range<Duration>,
+}
+
+impl RunEstimate {
+    /// Whether even the optimistic cost exceeds `budget`
+    pub fn exceeds(&self, budget: Decimal) -> bool {
+        self.cost.low > budget
+    }
+
+    /// Whether the pessimistic cost exceeds `budget`
+    pub fn may_exceed(&self, budget: Decimal) -> bool {
+        self.cost.high > budget
+    }
+}
+
+impl fmt::Display for RunEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Estimate: {:.0}-{:.0} LLM calls, {}-{} prompt tokens, ${}-${}, {}-{} wall clock",
+            self.llm_calls.low,
+            self.llm_calls.high,
+            self.prompt_tokens.low,
+            self.prompt_tokens.high,
+            self.cost.low.round_dp(2),
+            self.cost.high.round_dp(2),
+            format_duration(self.wall_clock.low),
+            format_duration(self.wall_clock.high),
+        )?;
+        for task in &self.tasks {
+            writeln!(
+                f,
+                "  - {}: {:.0}-{:.0} calls, ${}-${} ({:?}, {} samples)",
+                task.description,
+                task.llm_calls.low,
+                task.llm_calls.high,
+                task.cost.low.round_dp(2),
+                task.cost.high.round_dp(2),
+                task.basis,
+                task.samples,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// What the user sees at the plan-review gate
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanReview {
+    pub estimate: RunEstimate,
+    /// Budget of the run's preset, if any
+    pub budget: Option<Decimal>,
+    /// The preset asks for plan review, or the estimate may overrun the budget
+    pub requires_approval: bool,
+}
+
+impl fmt::Display for PlanReview {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.estimate)?;
+        if let Some(budget) = self.budget {
+            if self.estimate.exceeds(budget) {
+                writeln!(f, "WARNING: expected cost exceeds the ${} budget", budget)?;
+            } else if self.estimate.may_exceed(budget) {
+                writeln!(f, "Note: pessimistic cost exceeds the ${} budget", budget)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn format_duration(d: Duration) -> String {
+    let secs = d.as_secs();
+    if secs >= 3600 {
+        format!("{}h{:02}m", secs / 3600, (secs % 3600) / 60)
+    } else {
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    }
+}
+
+/// Observed totals for one historical task
+#[derive(Debug, Clone)]
+struct TaskSample {
+    calls: f64,
+    prompt_tokens: f64,
+    cost: Decimal,
+    secs: f64,
+}
+
+/// Builds estimates from routing log history
+#[derive(Debug, Clone, Default)]
+pub struct RunEstimator {
+    /// Samples keyed by agent id
+    by_agent: BTreeMap<String, Vec<TaskSample>>,
+}
+
+impl RunEstimator {
+    /// Group logs into per-task samples; logs without a task id are ignored
+    pub fn from_logs(logs: &[RoutingLog]) -> Self {
+        let mut tasks: BTreeMap<(&str, &str), Vec<&RoutingLog>> = BTreeMap::new();
+        for log in logs {
+            if log.is_synthetic() {
+                continue;
+            }
+            if let Some(task_id) = log.task_id.as_deref() {
+                tasks.entry((log.agent_id.as_str(), task_id)).or_default().push(log);
+            }
+        }
+
+        let mut by_agent: BTreeMap<String, Vec<TaskSample>> = BTreeMap::new();
+        for ((agent, _), logs) in tasks {
+            let first = logs.iter().map(|l| l.timestamp).min().unwrap_or_default();
+            let last = logs.iter().map(|l| l.timestamp).max().unwrap_or_default();
+            let calls = logs.len() as f64;
+            // A single call has no measurable span, so charge the default latency
+            let secs = ((last - first).num_milliseconds() as f64 / 1000.0).max(DEFAULT_SECS_PER_CALL);
+            by_agent.entry(agent.to_string()).or_default().push(TaskSample {
+                calls,
+                prompt_tokens: logs.iter().map(|l| (l.original_prompt_len as f64 / 4.0).ceil()).sum(),
+                cost: logs.iter().filter_map(|l| l.cost).sum(),
+                secs,
+            });
+        }
+
+        Self { by_agent }
+    }
+
+    /// Estimate every task in a plan
+    pub fn estimate(&self, tasks: &[PlannedTask]) -> RunEstimate {
+        let tasks: Vec<TaskEstimate> = tasks.iter().map(|t| self.estimate_task(t)).collect();
+
+        let mut total = RunEstimate {
+            llm_calls: Range::default(),
+            prompt_tokens: Range::default(),
+            cost: Range::default(),
+            wall_clock: Range::default(),
+            tasks: Vec::new(),
+        };
+        for task in &tasks {
+            total.llm_calls.low += task.llm_calls.low;
+            total.llm_calls.high += task.llm_calls.high;
+            total.prompt_tokens.low += task.prompt_tokens.low;
+            total.prompt_tokens.high += task.prompt_tokens.high;
+            total.cost.low += task.cost.low;
+            total.cost.high += task.cost.high;
+            total.wall_clock.low += task.wall_clock.low;
+            total.wall_clock.high += task.wall_clock.high;
+        }
+        total.tasks = tasks;
+        total
+    }
+
+    fn estimate_task(&self, task: &PlannedTask) -> TaskEstimate {
+        let (samples, basis): (Vec<&TaskSample>, EstimateBasis) = match self.by_agent.get(&task.agent_id) {
+            Some(samples) if !samples.is_empty() => (samples.iter().collect(), EstimateBasis::SameAgent),
+            _ => {
+                let all: Vec<&TaskSample> = self.by_agent.values().flatten().collect();
+                if all.is_empty() {
+                    (Vec::new(), EstimateBasis::Defaults)
+                } else {
+                    (all, EstimateBasis::AllHistory)
+                }
+            }
+        };
+
+        if samples.is_empty() {
+            // The task description itself is part of every prompt
+            let prompt = DEFAULT_PROMPT_TOKENS_PER_CALL + estimate_tokens(&task.description) as f64;
+            let calls = Range { low: DEFAULT_CALLS / 2.0, high: DEFAULT_CALLS * 2.0 };
+            return TaskEstimate {
+                task_id: task.task_id.clone(),
+                description: task.description.clone(),
+                prompt_tokens: Range { low: (calls.low * prompt) as u64, high: (calls.high * prompt) as u64 },
+                cost: Range {
+                    low: DEFAULT_COST_PER_CALL * Decimal::from_f64_retain(calls.low).unwrap_or(Decimal::ONE),
+                    high: DEFAULT_COST_PER_CALL * Decimal::from_f64_retain(calls.high).unwrap_or(Decimal::ONE),
+                },
+                wall_clock: Range {
+                    low: Duration::from_secs_f64(calls.low * DEFAULT_SECS_PER_CALL),
+                    high: Duration::from_secs_f64(calls.high * DEFAULT_SECS_PER_CALL),
+                },
+                llm_calls: calls,
+                basis,
+                samples: 0,
+            };
+        }
+
+        let calls = quartiles(samples.iter().map(|s| s.calls).collect());
+        let tokens = quartiles(samples.iter().map(|s| s.prompt_tokens).collect());
+        let secs = quartiles(samples.iter().map(|s| s.secs).collect());
+        let mut costs: Vec<Decimal> = samples.iter().map(|s| s.cost).collect();
+        costs.sort();
+
+        TaskEstimate {
+            task_id: task.task_id.clone(),
+            description: task.description.clone(),
+            llm_calls: calls,
+            prompt_tokens: Range { low: tokens.low as u64, high: tokens.high as u64 },
+            cost: Range { low: costs[percentile_index(costs.len(), 25)], high: costs[percentile_index(costs.len(), 75)] },
+            wall_clock: Range { low: Duration::from_secs_f64(secs.low), high: Duration::from_secs_f64(secs.high) },
+            basis,
+            samples: samples.len(),
+        }
+    }
+}
+
+fn percentile_index(len: usize, pct: usize) -> usize {
+    ((len - 1) * pct + 50) / 100
+}
+
+/// 25th and 75th percentile of a non-empty sample
+fn quartiles(mut values: Vec<f64>) -> Range<f64> {
+    values.sort_by(|a, b| a.total_cmp(b));
+    Range {
+        low: values[percentile_index(values.len(), 25)],
+        high: values[percentile_index(values.len(), 75)],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration as ChronoDuration, Utc};
+
+    fn log(agent: &str, task: &str, offset_secs: i64, cost: Decimal) -> RoutingLog {
+        RoutingLog {
+            id: None,
+            timestamp: Utc::now() + ChronoDuration::seconds(offset_secs),
+            agent_id: agent.to_string(),
+            task_id: Some(task.to_string()),
+            original_prompt_len: 4_000,
+            selected_tier: 1,
+            selected_model: "cheap".to_string(),
+            retry_count: 0,
+            failover_reason: None,
+            cost: Some(cost),
+            is_critical: false,
+            speculation: None,
+            variant: None,
+            throttle: None,
+        }
+    }
+
+    fn planned(agent: &str) -> PlannedTask {
+        PlannedTask {
+            task_id: "new".to_string(),
+            description: "add pagination".to_string(),
+            agent_id: agent.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_estimate_from_same_agent_history() {
+        let cent = Decimal::new(1, 2);
+        let logs = vec![
+            log("implementer", "t1", 0, cent),
+            log("implementer", "t1", 60, cent),
+            log("implementer", "t2", 0, cent),
+            log("implementer", "t2", 30, cent),
+            log("implementer", "t2", 120, cent),
+            log("reviewer", "t3", 0, Decimal::new(5, 2)),
+        ];
+        let estimator = RunEstimator::from_logs(&logs);
+        let estimate = estimator.estimate(&[planned("implementer")]);
+
+        let task = &estimate.tasks[0];
+        assert_eq!(task.basis, EstimateBasis::SameAgent);
+        assert_eq!(task.samples, 2);
+        assert_eq!(task.llm_calls, Range { low: 2.0, high: 3.0 });
+        assert_eq!(task.cost, Range { low: Decimal::new(2, 2), high: Decimal::new(3, 2) });
+        assert_eq!(task.prompt_tokens.low, 2_000);
+        assert_eq!(task.wall_clock.high, Duration::from_secs(120));
+        assert!(estimate.may_exceed(Decimal::new(25, 3)));
+        assert!(!estimate.exceeds(Decimal::new(25, 3)));
+    }
+
+    #[test]
+    fn test_falls_back_to_defaults() {
+        let estimate = RunEstimator::default().estimate(&[planned("anyone"), planned("anyone")]);
+        assert_eq!(estimate.tasks[0].basis, EstimateBasis::Defaults);
+        assert_eq!(estimate.llm_calls.high, DEFAULT_CALLS * 4.0);
+        assert!(estimate.to_string().contains("add pagination"));
+    }
+}
@@ -0,0 +1,362 @@
+//! "Why did the system do X?"
+//!
+//! Given an artifact id or a changed file, follow the ids recorded along the
+//! way back to their origin: the artifact's trace record names its task, the
+//! session plan names the task's intent and agent, trace records hold the
+//! prompts and critiques, blackboard messages and decisions hold the review
+//! verdicts, and routing logs tagged with the task id show which models
+//! served it. The result is a timeline, oldest first.
+
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use zed42_blackboard::DecisionNode;
+use zed42_core::{Message, MessageType, TraceKind, TraceRecord};
+use zed42_mom::types::RoutingLog;
+
+use crate::planner::SessionPlan;
+
+/// What to explain
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum ExplainTarget {
+    Artifact(String),
+    /// Path of a changed file, as recorded on the artifact
+    File(String),
+}
+
+/// Step in the causal chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimelineKind {
+    Intent,
+    Task,
+    Agent,
+    Prompt,
+    ToolCall,
+    Critique,
+    Artifact,
+    Decision,
+    Approval,
+    Rejection,
+    Routing,
+}
+
+/// One entry of an explanation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelineEvent {
+    /// `None` for facts without a time of their own (the planned task)
+    pub at: Option<DateTime<Utc>>,
+    pub kind: TimelineKind,
+    pub summary: String,
+    /// Id of the record the event came from
+    pub reference: String,
+}
+
+/// Causal chain behind an artifact
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Explanation {
+    pub target: ExplainTarget,
+    pub task_id: String,
+    pub artifact_ids: Vec<String>,
+    pub events: Vec<TimelineEvent>,
+}
+
+impl Explanation {
+    /// Render the timeline as Markdown
+    pub fn to_markdown(&self) -> String {
+        let mut md = String::new();
+        let subject = match &self.target {
+            ExplainTarget::Artifact(id) => format!("artifact `{}`", id),
+            ExplainTarget::File(path) => format!("`{}`", path),
+        };
+        let _ = writeln!(md, "# Why {} changed\n", subject);
+        let _ = writeln!(md, "Task `{}`\n", self.task_id);
+        for event in &self.events {
+            let at = event.at.map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string()).unwrap_or_else(|| "-".to_string());
+            let _ = writeln!(
+                md,
+                "- {} **{:?}** {} (`{}`)",
+                at,
+                event.kind,
+                event.summary.replace('\n', " "),
+                event.reference
+            );
+        }
+        md
+    }
+}
+
+/// Records an explanation is assembled from
+#[derive(Default)]
+pub struct ExplainSources<'a> {
+    pub plan: Option<&'a SessionPlan>,
+    pub traces: &'a [TraceRecord],
+    pub messages: &'a [Message],
+    pub decisions: &'a [DecisionNode],
+    pub routing_logs: &'a [RoutingLog],
+}
+
+impl ExplainSources<'_> {
+    /// Timeline behind `target`; fails when no trace record produced it
+    pub fn explain(&self, target: &ExplainTarget) -> Result<Explanation> {
+        // The newest artifact matching the target names the task
+        let produced = self
+            .traces
+            .iter()
+            .filter(|t| match (&t.kind, target) {
+                (TraceKind::Artifact { artifact_id, .. }, ExplainTarget::Artifact(id)) => artifact_id == id,
+                (TraceKind::Artifact { file_path, .. }, ExplainTarget::File(path)) => {
+                    file_path.as_deref().is_some_and(|p| same_path(p, path))
+                }
+                _ => false,
+            })
+            .max_by_key(|t| t.timestamp);
+        let Some(produced) = produced else {
+            bail!("No recorded artifact for {:?}", target);
+        };
+        let task_id = produced.task_id.clone();
+
+        let task_traces: Vec<&TraceRecord> = self.traces.iter().filter(|t| t.task_id == task_id).collect();
+        let artifact_ids: Vec<String> = task_traces.iter().filter_map(|t| t.artifact_id().map(str::to_string)).collect();
+        let agents: Vec<zed42_core::AgentId> = {
+            let mut agents: Vec<_> = task_traces.iter().map(|t| t.agent_id).collect();
+            agents.sort_unstable();
+            agents.dedup();
+            agents
+        };
+        // Ids a verdict or decision may refer to
+        let mentions = |text: &str| text == task_id || artifact_ids.iter().any(|id| id == text);
+
+        let mut events = Vec::new();
+        if let Some(plan) = self.plan {
+            if let Some(node) = plan.graph.nodes.iter().find(|n| n.task_id == task_id) {
+                events.push(TimelineEvent {
+                    at: None,
+                    kind: TimelineKind::Intent,
+                    summary: plan.graph.intent.clone(),
+                    reference: plan.decision_id.clone().unwrap_or_else(|| "plan".to_string()),
+                });
+                events.push(TimelineEvent {
+                    at: None,
+                    kind: TimelineKind::Task,
+                    summary: node.description.clone(),
+                    reference: task_id.clone(),
+                });
+                if let Some(agent) = plan.task_agents.get(&task_id) {
+                    events.push(TimelineEvent {
+                        at: None,
+                        kind: TimelineKind::Agent,
+                        summary: format!("{:?} assigned", node.agent_type),
+                        reference: agent.to_string(),
+                    });
+                }
+            }
+        }
+
+        for trace in &task_traces {
+            let (kind, summary) = match &trace.kind {
+                TraceKind::Prompt { iteration } => (TimelineKind::Prompt, format!("Attempt {}: {}", iteration + 1, trace.summary)),
+                TraceKind::Response { iteration } => (TimelineKind::Prompt, format!("Attempt {} reply: {}", iteration + 1, trace.summary)),
+                TraceKind::ToolCall { tool, ok } => (
+                    TimelineKind::ToolCall,
+                    format!("Ran {}{}: {}", tool, if *ok { "" } else { " (failed)" }, trace.summary),
+                ),
+                TraceKind::Critique { iteration, passed, issues } => (
+                    TimelineKind::Critique,
+                    if *passed {
+                        format!("Attempt {} passed critique", iteration + 1)
+                    } else {
+                        format!("Attempt {} failed critique: {}", iteration + 1, issues.join("; "))
+                    },
+                ),
+                TraceKind::Artifact { file_path, .. } => (
+                    TimelineKind::Artifact,
+                    match file_path {
+                        Some(path) => format!("Produced {}: {}", path, trace.summary),
+                        None => format!("Produced artifact: {}", trace.summary),
+                    },
+                ),
+            };
+            events.push(TimelineEvent {
+                at: Some(trace.timestamp),
+                kind,
+                summary,
+                reference: trace.artifact_id().map(str::to_string).unwrap_or_else(|| trace.agent_id.to_string()),
+            });
+        }
+
+        for message in self.messages {
+            let (kind, summary) = match &message.message_type {
+                MessageType::ApproveChange { change_id, rationale } if mentions(change_id) => {
+                    (TimelineKind::Approval, format!("Approved: {}", rationale))
+                }
+                MessageType::RejectProposal { proposal_id, reason } if mentions(proposal_id) => {
+                    (TimelineKind::Rejection, format!("Rejected: {}", reason))
+                }
+                MessageType::RequestRevision { target_id, requested_changes } if mentions(target_id) => {
+                    (TimelineKind::Rejection, format!("Revision requested: {}", requested_changes))
+                }
+                _ => continue,
+            };
+            events.push(TimelineEvent {
+                at: Some(message.timestamp),
+                kind,
+                summary,
+                reference: message.id.to_string(),
+            });
+        }
+
+        for decision in self.decisions {
+            let rationale = decision.rationale.to_string();
+            let related = agents.contains(&decision.made_by)
+                || rationale.contains(&task_id)
+                || artifact_ids.iter().any(|id| rationale.contains(id.as_str()));
+            if !related {
+                continue;
+            }
+            events.push(TimelineEvent {
+                at: DateTime::from_timestamp(decision.timestamp, 0),
+                kind: TimelineKind::Decision,
+                summary: format!("{}: {}", decision.decision_type, decision.description),
+                reference: decision.id.clone(),
+            });
+        }
+
+        for log in self.routing_logs.iter().filter(|l| l.task_id.as_deref() == Some(task_id.as_str())) {
+            let mut summary = format!("Tier {} {}", log.selected_tier, log.selected_model);
+            if log.retry_count > 0 {
+                summary.push_str(&format!(" after {} retr{}", log.retry_count, if log.retry_count == 1 { "y" } else { "ies" }));
+            }
+            if let Some(ref reason) = log.failover_reason {
+                summary.push_str(&format!(" (failover: {})", reason));
+            }
+            events.push(TimelineEvent {
+                at: Some(log.timestamp),
+                kind: TimelineKind::Routing,
+                summary,
+                reference: log.agent_id.clone(),
+            });
+        }
+
+        // Untimed plan facts first, then everything else in order
+        events.sort_by_key(|e| e.at);
+        Ok(Explanation {
+            target: target.clone(),
+            task_id,
+            artifact_ids,
+            events,
+        })
+    }
+}
+
+/// Paths match ignoring a leading `./`
+fn same_path(a: &str, b: &str) -> bool {
+    a.trim_start_matches("./") == b.trim_start_matches("./")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::planner::{TaskGraph, TaskNode};
+    use zed42_agents::AgentType;
+    use zed42_core::MessageTarget;
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_700_000_000 + secs, 0).unwrap()
+    }
+
+    fn trace(secs: i64, agent: zed42_core::AgentId, kind: TraceKind, summary: &str) -> TraceRecord {
+        let mut record = TraceRecord::new("t1", agent, kind, summary);
+        record.timestamp = at(secs);
+        record
+    }
+
+    #[test]
+    fn test_explains_file_change() {
+        let agent = uuid::Uuid::new_v4();
+        let reviewer = uuid::Uuid::new_v4();
+        let mut plan = SessionPlan::new(TaskGraph {
+            intent: "Add pagination".to_string(),
+            nodes: vec![TaskNode {
+                task_id: "t1".to_string(),
+                description: "Paginate the users endpoint".to_string(),
+                agent_type: AgentType::FeatureImplementer,
+                depends_on: vec![],
+            }],
+            waves: vec![vec!["t1".to_string()]],
+        });
+        plan.task_agents.insert("t1".to_string(), agent);
+
+        let traces = vec![
+            trace(1, agent, TraceKind::Prompt { iteration: 0 }, "Implement pagination"),
+            trace(3, agent, TraceKind::Critique { iteration: 0, passed: false, issues: vec!["no tests".to_string()] }, ""),
+            trace(5, agent, TraceKind::Prompt { iteration: 1 }, "Implement pagination, fix issues"),
+            trace(7, agent, TraceKind::Critique { iteration: 1, passed: true, issues: vec![] }, ""),
+            trace(
+                8,
+                agent,
+                TraceKind::Artifact { artifact_id: "a1".to_string(), file_path: Some("src/users.rs".to_string()) },
+                "Cursor pagination",
+            ),
+        ];
+        let mut approval = Message::new(
+            reviewer,
+            MessageTarget::All,
+            MessageType::ApproveChange { change_id: "a1".to_string(), rationale: "tests cover edges".to_string() },
+            1,
+        );
+        approval.timestamp = at(9);
+        let unrelated = Message::new(
+            reviewer,
+            MessageTarget::All,
+            MessageType::ApproveChange { change_id: "other".to_string(), rationale: "fine".to_string() },
+            1,
+        );
+        let mut log = RoutingLog {
+            id: None,
+            timestamp: at(2),
+            agent_id: agent.to_string(),
+            task_id: Some("t1".to_string()),
+            original_prompt_len: 100,
+            selected_tier: 1,
+            selected_model: "model-a".to_string(),
+            retry_count: 1,
+            failover_reason: None,
+            cost: None,
+            is_critical: false,
+            speculation: None,
+            variant: None,
+            throttle: None,
+        };
+        let untagged = RoutingLog { task_id: None, ..log.clone() };
+        log.failover_reason = Some("timeout".to_string());
+
+        let messages = [approval, unrelated];
+        let logs = [log, untagged];
+        let sources = ExplainSources {
+            plan: Some(&plan),
+            traces: &traces,
+            messages: &messages,
+            routing_logs: &logs,
+            ..Default::default()
+        };
+        let explanation = sources.explain(&ExplainTarget::File("./src/users.rs".to_string())).unwrap();
+
+        let kinds: Vec<TimelineKind> = explanation.events.iter().map(|e| e.kind).collect();
+        use TimelineKind::*;
+        assert_eq!(
+            kinds,
+            vec![Intent, Task, Agent, Prompt, Routing, Critique, Prompt, Critique, Artifact, Approval]
+        );
+        assert_eq!(explanation.artifact_ids, vec!["a1"]);
+        let md = explanation.to_markdown();
+        assert!(md.starts_with("# Why `./src/users.rs` changed"));
+        assert!(md.contains("Tier 1 model-a after 1 retry (failover: timeout)"));
+        assert!(md.contains("Attempt 1 failed critique: no tests"));
+        assert!(md.contains("Approved: tests cover edges"));
+
+        assert!(sources.explain(&ExplainTarget::Artifact("missing".to_string())).is_err());
+    }
+}
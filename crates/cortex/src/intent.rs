@@ -1 +1,305 @@
 //! Intent parsing
+//!
+//! Before planning, the [`IntentParser`] restates the intent, lists its scope
+//! and rates its own confidence. When confidence is low or the scope is
+//! ambiguous it also returns clarifying questions, each with a best guess.
+//!
+//! Questions wait on the [`ClarificationDesk`]: the Cortex posts them as a
+//! `clarification_needed` alert (delivered by the notifier), whoever answers
+//! calls [`ClarificationDesk::answer`], and planning resumes. Unanswered
+//! questions fall back to their best guesses after the policy timeout.
+
+use anyhow::{anyhow, Result};
+use parking_lot::Mutex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::time::Duration;
+use tokio::sync::oneshot;
+use zed42_llm::{ConstrainedGen, LlmClient};
+
+const PARSER_SYSTEM_PROMPT: &str = "You read software change requests before they are planned. \
+Restate the intent in one sentence, list what is in scope, and rate your confidence (0.0-1.0) that \
+the request is specific enough to plan. If anything is ambiguous, list it and ask at most a few \
+short clarifying questions, each with your best-guess answer.";
+
+/// A question the user should answer before planning
+#[derive(Debug, Clone, PartialEq, JsonSchema, Serialize, Deserialize)]
+pub struct ClarifyingQuestion {
+    /// Short id, e.g. "q1"
+    pub id: String,
+    pub question: String,
+    /// Suggested answers, if the question is multiple choice
+    #[serde(default)]
+    pub options: Vec<String>,
+    /// Answer assumed when the user doesn't reply in time
+    pub best_guess: String,
+}
+
+/// LLM response schema for intent parsing
+#[derive(Debug, Clone, JsonSchema, Serialize, Deserialize)]
+pub struct ParsedIntent {
+    pub summary: String,
+    #[serde(default)]
+    pub scope: Vec<String>,
+    /// 0.0-1.0: how confident the parser is that the intent can be planned as stated
+    pub confidence: f32,
+    #[serde(default)]
+    pub ambiguities: Vec<String>,
+    #[serde(default)]
+    pub questions: Vec<ClarifyingQuestion>,
+}
+
+/// When to ask and how long to wait
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClarificationPolicy {
+    /// Ask when confidence is below this
+    pub min_confidence: f32,
+    /// Questions asked at most
+    pub max_questions: usize,
+    /// Wait this long for answers before using best guesses
+    pub timeout: Duration,
+}
+
+impl Default for ClarificationPolicy {
+    fn default() -> Self {
+        Self {
+            min_confidence: 0.7,
+            max_questions: 3,
+            timeout: Duration::from_secs(10 * 60),
+        }
+    }
+}
+
+impl ParsedIntent {
+    /// Questions to ask under `policy`; empty when the intent is clear enough
+    pub fn questions_to_ask(&self, policy: &ClarificationPolicy) -> Vec<ClarifyingQuestion> {
+        let unclear = self.confidence < policy.min_confidence || !self.ambiguities.is_empty();
+        if !unclear {
+            return Vec::new();
+        }
+        self.questions.iter().take(policy.max_questions).cloned().collect()
+    }
+}
+
+/// Parses intents with one constrained call
+pub struct IntentParser<'a> {
+    client: &'a dyn LlmClient,
+}
+
+impl<'a> IntentParser<'a> {
+    pub fn new(client: &'a dyn LlmClient) -> Self {
+        Self { client }
+    }
+
+    pub async fn parse(&self, intent: &str) -> Result<ParsedIntent> {
+        let mut parsed: ParsedIntent = ConstrainedGen::new(self.client)
+            .system(PARSER_SYSTEM_PROMPT)
+            .prompt(format!("Intent:\n{}", intent))
+            .max_retries(1)
+            .generate()
+            .await?;
+        parsed.confidence = parsed.confidence.clamp(0.0, 1.0);
+        Ok(parsed)
+    }
+}
+
+/// One answered (or assumed) question
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClarificationAnswer {
+    pub question_id: String,
+    pub question: String,
+    pub answer: String,
+    /// The user didn't answer; the parser's best guess was used
+    pub best_guess: bool,
+}
+
+/// Questions and answers for one intent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Clarification {
+    pub request_id: String,
+    pub intent: String,
+    pub answers: Vec<ClarificationAnswer>,
+    pub timed_out: bool,
+}
+
+impl Clarification {
+    /// The intent with the answers appended, as handed to the planner
+    pub fn clarified_intent(&self) -> String {
+        let mut text = self.intent.clone();
+        if self.answers.is_empty() {
+            return text;
+        }
+        text.push_str("\n\nClarifications:");
+        for answer in &self.answers {
+            let _ = write!(text, "\n- {} {}", answer.question, answer.answer);
+            if answer.best_guess {
+                text.push_str(" (assumed)");
+            }
+        }
+        text
+    }
+}
+
+/// Render questions for a notification, tagged with the request id to answer
+pub fn format_questions(request_id: &str, questions: &[ClarifyingQuestion]) -> String {
+    let mut text = format!("[{}]", request_id);
+    for question in questions {
+        let _ = write!(text, " {}: {}", question.id, question.question);
+        if !question.options.is_empty() {
+            let _ = write!(text, " ({})", question.options.join(" / "));
+        }
+        let _ = write!(text, " [best guess: {}]", question.best_guess);
+    }
+    text
+}
+
+struct PendingClarification {
+    questions: Vec<ClarifyingQuestion>,
+    reply: oneshot::Sender<HashMap<String, String>>,
+}
+
+/// Open clarification requests, shared between the Cortex and whoever answers
+#[derive(Default)]
+pub struct ClarificationDesk {
+    pending: Mutex<HashMap<String, PendingClarification>>,
+}
+
+impl ClarificationDesk {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Open a request; the receiver resolves with answers keyed by question id
+    pub fn open(&self, questions: Vec<ClarifyingQuestion>) -> (String, oneshot::Receiver<HashMap<String, String>>) {
+        let request_id = uuid::Uuid::new_v4().to_string();
+        let (reply, rx) = oneshot::channel();
+        self.pending
+            .lock()
+            .insert(request_id.clone(), PendingClarification { questions, reply });
+        (request_id, rx)
+    }
+
+    /// Open requests and their questions
+    pub fn pending(&self) -> Vec<(String, Vec<ClarifyingQuestion>)> {
+        self.pending
+            .lock()
+            .iter()
+            .map(|(id, p)| (id.clone(), p.questions.clone()))
+            .collect()
+    }
+
+    /// Answer a request; questions left out fall back to their best guesses
+    pub fn answer(&self, request_id: &str, answers: HashMap<String, String>) -> Result<()> {
+        let pending = self
+            .pending
+            .lock()
+            .remove(request_id)
+            .ok_or_else(|| anyhow!("No open clarification request {}", request_id))?;
+        pending
+            .reply
+            .send(answers)
+            .map_err(|_| anyhow!("Clarification request {} is no longer waiting", request_id))
+    }
+
+    /// Wait for answers until `timeout`, then close the request
+    pub async fn wait(
+        &self,
+        request_id: &str,
+        intent: &str,
+        questions: &[ClarifyingQuestion],
+        rx: oneshot::Receiver<HashMap<String, String>>,
+        timeout: Duration,
+    ) -> Clarification {
+        let (given, timed_out) = match tokio::time::timeout(timeout, rx).await {
+            Ok(Ok(answers)) => (answers, false),
+            _ => {
+                self.pending.lock().remove(request_id);
+                (HashMap::new(), true)
+            }
+        };
+
+        let answers = questions
+            .iter()
+            .map(|q| match given.get(&q.id).filter(|a| !a.trim().is_empty()) {
+                Some(answer) => ClarificationAnswer {
+                    question_id: q.id.clone(),
+                    question: q.question.clone(),
+                    answer: answer.clone(),
+                    best_guess: false,
+                },
+                None => ClarificationAnswer {
+                    question_id: q.id.clone(),
+                    question: q.question.clone(),
+                    answer: q.best_guess.clone(),
+                    best_guess: true,
+                },
+            })
+            .collect();
+
+        Clarification {
+            request_id: request_id.to_string(),
+            intent: intent.to_string(),
+            answers,
+            timed_out,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn question(id: &str) -> ClarifyingQuestion {
+        ClarifyingQuestion {
+            id: id.to_string(),
+            question: format!("{}?", id),
+            options: Vec::new(),
+            best_guess: format!("guess-{}", id),
+        }
+    }
+
+    #[test]
+    fn test_only_unclear_intents_ask() {
+        let mut parsed = ParsedIntent {
+            summary: "add metrics".to_string(),
+            scope: Vec::new(),
+            confidence: 0.9,
+            ambiguities: Vec::new(),
+            questions: vec![question("q1"), question("q2")],
+        };
+        assert!(parsed.questions_to_ask(&ClarificationPolicy::default()).is_empty());
+
+        parsed.confidence = 0.4;
+        let policy = ClarificationPolicy { max_questions: 1, ..Default::default() };
+        assert_eq!(parsed.questions_to_ask(&policy), vec![question("q1")]);
+    }
+
+    #[tokio::test]
+    async fn test_answers_and_timeouts() {
+        let desk = Arc::new(ClarificationDesk::new());
+        let questions = vec![question("q1"), question("q2")];
+
+        let (id, rx) = desk.open(questions.clone());
+        let answering = desk.clone();
+        let answer_id = id.clone();
+        tokio::spawn(async move {
+            answering
+                .answer(&answer_id, HashMap::from([("q1".to_string(), "JSON".to_string())]))
+                .unwrap();
+        });
+        let answered = desk.wait(&id, "export", &questions, rx, Duration::from_secs(5)).await;
+        assert!(!answered.timed_out);
+        assert!(!answered.answers[0].best_guess);
+        assert!(answered.answers[1].best_guess);
+        assert!(answered.clarified_intent().contains("- q1? JSON\n- q2? guess-q2 (assumed)"));
+
+        let (id, rx) = desk.open(questions.clone());
+        let silent = desk.wait(&id, "export", &questions, rx, Duration::from_millis(10)).await;
+        assert!(silent.timed_out);
+        assert!(desk.pending().is_empty());
+        assert!(desk.answer(&id, HashMap::new()).is_err());
+    }
+}
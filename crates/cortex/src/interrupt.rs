@@ -0,0 +1,198 @@
+//! Checkpoint on interrupt
+//!
+//! A Ctrl-C in the middle of a run used to kill the process with agents
+//! holding queue claims, budget leases open and SQLite/DuckDB writes sitting
+//! in their WALs. CLI runs now race their work against [`signal`] and, when
+//! it fires, call [`Cortex::checkpoint_on_interrupt`](crate::Cortex::checkpoint_on_interrupt):
+//!
+//! 1. Shut down as usual: agents stop, lease hooks settle or release open
+//!    budget leases, and memory (session WAL, archive, ANN index) is checkpointed.
+//! 2. Put back every queue task the session's agents had claimed.
+//! 3. Write an [`InterruptSnapshot`] of the plan and the task queue to
+//!    `.zed42/interrupted/<session>.json`.
+//!
+//! The report carries the command that picks the session up again.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use zed42_blackboard::{QueueStatus, QueuedTask};
+use zed42_core::types::{SessionId, TaskId};
+use zed42_core::ShutdownReport;
+
+use crate::planner::SessionPlan;
+
+/// Directory of interrupt snapshots relative to the project root
+pub const SNAPSHOT_DIR: &str = ".zed42/interrupted";
+
+/// Wait for Ctrl-C, or SIGTERM on Unix
+pub async fn signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        let mut term = match signal(SignalKind::terminate()) {
+            Ok(term) => term,
+            Err(_) => return std::future::pending().await,
+        };
+        tokio::select! {
+            _ = term.recv() => {}
+            _ = tokio::signal::ctrl_c() => {}
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
+
+/// Session state saved when a run is interrupted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InterruptSnapshot {
+    pub session_id: SessionId,
+    pub interrupted_at: DateTime<Utc>,
+    #[serde(default)]
+    pub plan: Option<SessionPlan>,
+    /// The session's task queue after claims were released
+    #[serde(default)]
+    pub queue: Vec<QueuedTask>,
+    /// Tasks whose claim was handed back
+    #[serde(default)]
+    pub released: Vec<TaskId>,
+    /// Whether every task stopped in time and every shutdown hook succeeded
+    pub clean: bool,
+}
+
+impl InterruptSnapshot {
+    pub fn path(root: &Path, session_id: SessionId) -> PathBuf {
+        root.join(SNAPSHOT_DIR).join(format!("{}.json", session_id))
+    }
+
+    pub fn save(&self, root: &Path) -> Result<PathBuf> {
+        let path = Self::path(root, self.session_id);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        std::fs::write(&path, serde_json::to_vec_pretty(self)?)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(path)
+    }
+
+    pub fn load(root: &Path, session_id: SessionId) -> Result<Self> {
+        let path = Self::path(root, session_id);
+        let content = std::fs::read(&path).with_context(|| format!("No interrupted session at {}", path.display()))?;
+        serde_json::from_slice(&content).with_context(|| format!("Invalid snapshot {}", path.display()))
+    }
+
+    /// Most recently interrupted session under `root`, if any
+    pub fn latest(root: &Path) -> Result<Option<Self>> {
+        let dir = root.join(SNAPSHOT_DIR);
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", dir.display())),
+        };
+        let mut latest: Option<Self> = None;
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            if entry.path().extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let snapshot: Self = match std::fs::read(entry.path()).map(|bytes| serde_json::from_slice(&bytes)) {
+                Ok(Ok(snapshot)) => snapshot,
+                _ => {
+                    tracing::warn!(path = %entry.path().display(), "Skipping unreadable interrupt snapshot");
+                    continue;
+                }
+            };
+            if latest.as_ref().is_none_or(|best| snapshot.interrupted_at > best.interrupted_at) {
+                latest = Some(snapshot);
+            }
+        }
+        Ok(latest)
+    }
+
+    /// Tasks still to run: pending, or claimed when the snapshot was taken
+    pub fn unfinished(&self) -> impl Iterator<Item = &QueuedTask> {
+        self.queue
+            .iter()
+            .filter(|task| matches!(task.status, QueueStatus::Pending | QueueStatus::Assigned))
+    }
+
+    pub fn resume_command(&self) -> String {
+        resume_command(self.session_id)
+    }
+}
+
+/// Command that continues an interrupted session
+pub fn resume_command(session_id: SessionId) -> String {
+    format!("zed42 resume {}", session_id)
+}
+
+/// What [`Cortex::checkpoint_on_interrupt`](crate::Cortex::checkpoint_on_interrupt) did
+#[derive(Debug, Clone)]
+pub struct InterruptReport {
+    pub shutdown: ShutdownReport,
+    pub snapshot: InterruptSnapshot,
+    /// Where the snapshot was written
+    pub path: PathBuf,
+}
+
+impl InterruptReport {
+    pub fn resume_command(&self) -> String {
+        self.snapshot.resume_command()
+    }
+}
+
+impl std::fmt::Display for InterruptReport {
+    /// What the CLI prints after Ctrl-C
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Interrupted. {} unfinished task(s) saved to {}", self.snapshot.unfinished().count(), self.path.display())?;
+        for (name, error) in &self.shutdown.hook_failures {
+            writeln!(f, "  warning: {} failed: {}", name, error)?;
+        }
+        for task in &self.shutdown.tasks_aborted {
+            writeln!(f, "  warning: {} was aborted", task)?;
+        }
+        write!(f, "Resume with: {}", self.resume_command())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_snapshot_roundtrip_and_latest() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(InterruptSnapshot::latest(dir.path()).unwrap().is_none());
+
+        let session = Uuid::new_v4();
+        let mut done = QueuedTask::new(session, "t1", "Parse config");
+        done.status = QueueStatus::Done;
+        let snapshot = InterruptSnapshot {
+            session_id: session,
+            interrupted_at: Utc::now(),
+            plan: None,
+            queue: vec![done, QueuedTask::new(session, "t2", "Validate config")],
+            released: vec!["t2".to_string()],
+            clean: true,
+        };
+        let path = snapshot.save(dir.path()).unwrap();
+        assert!(path.ends_with(format!("{}.json", session)));
+
+        let older = InterruptSnapshot {
+            session_id: Uuid::new_v4(),
+            interrupted_at: snapshot.interrupted_at - chrono::Duration::minutes(5),
+            ..snapshot.clone()
+        };
+        older.save(dir.path()).unwrap();
+
+        let loaded = InterruptSnapshot::latest(dir.path()).unwrap().unwrap();
+        assert_eq!(loaded.session_id, session);
+        let unfinished: Vec<&str> = loaded.unfinished().map(|task| task.task_id.as_str()).collect();
+        assert_eq!(unfinished, vec!["t2"]);
+        assert_eq!(loaded.resume_command(), format!("zed42 resume {}", session));
+        assert_eq!(InterruptSnapshot::load(dir.path(), older.session_id).unwrap().session_id, older.session_id);
+    }
+}
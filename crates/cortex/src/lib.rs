@@ -11,35 +11,585 @@ use zed42_core::traits::AgentBehavior;
 use zed42_blackboard::BlackboardDb;
 use zed42_agents::{Agent, AgentType};
 use zed42_memory::MemorySubstrate;
+use zed42_ledger::IntelligenceLedger;
+use zed42_ledger::types::{Budget, BudgetStatus};
 use std::collections::HashMap;
+use std::sync::Arc;
 use uuid::Uuid;
 
+/// Archive entry type of routing logs moved out of the router's table
+pub const ROUTING_LOG_ENTRY_TYPE: &str = "routing_log";
 
+pub mod adr;
+pub mod affinity;
+pub mod approvals;
+pub mod bootstrap;
+pub mod change_summary;
+pub mod context_pack;
+pub mod dashboard;
+pub mod estimate;
+pub mod explain;
 pub mod intent;
+pub mod interrupt;
+pub mod notifications;
 pub mod planner;
+pub mod presets;
+pub mod pull_request;
+pub mod queue;
+pub mod remote;
+pub mod retrospective;
+pub mod review;
+pub mod scheduler;
+pub mod settings;
 pub mod team_manager;
+pub mod workers;
 
 /// The Cortex - main orchestration component
 pub struct Cortex {
     session_id: SessionId,
-    blackboard: Option<BlackboardDb>,
+    started_at: chrono::DateTime<chrono::Utc>,
+    blackboard: Option<Arc<BlackboardDb>>,
     memory: MemorySubstrate,
     active_agents: HashMap<AgentId, Box<dyn AgentBehavior>>,
+    scheduler: scheduler::IntentScheduler,
+    queue: queue::IntentQueue,
+    presets: presets::PresetRegistry,
+    /// Preset selected for each queued run
+    run_presets: HashMap<String, presets::RunPreset>,
+    /// Stops background tasks and runs cleanup hooks on exit
+    shutdown: zed42_core::ShutdownController,
+    /// Set in worker mode: agents run as supervised OS processes
+    workers: Option<Arc<workers::WorkerPool>>,
+    /// Remote worker membership, tried before local placement
+    remote: Option<Arc<remote::RemoteRegistry<surrealdb::engine::any::Any>>>,
+    /// Agents placed on remote workers
+    remote_agents: std::collections::HashSet<AgentId>,
+    /// Hardware checks before local spawns; None places unconditionally
+    titan: Option<Arc<zed42_core::titan::TitanSubstrate>>,
+    /// Local agents run models on the GPU
+    local_models: bool,
+    /// Spawns waiting for resources, oldest first
+    deferred: std::collections::VecDeque<DeferredSpawn>,
+    /// Warms local model backends and unloads them while no agents run
+    keep_alive: Option<Arc<zed42_llm::KeepAlive>>,
+    /// Model used for intent decomposition and follow-ups
+    planner: Option<Arc<dyn zed42_llm::LlmClient>>,
+    /// Plan of the current session, revised by follow-up intents
+    plan: Option<planner::SessionPlan>,
+    /// Type of every agent this Cortex placed, for reuse on follow-ups
+    agent_types: HashMap<AgentId, AgentType>,
+    /// Files each agent touched, for affinity-based assignment
+    history: affinity::AgentHistory,
+    /// Clarifying questions waiting on the user
+    clarifications: Arc<intent::ClarificationDesk>,
+    clarification_policy: intent::ClarificationPolicy,
+    /// Checkout whose files go into context packs
+    repo_root: Option<std::path::PathBuf>,
+    /// Token budget of each task's context pack
+    context_budget: usize,
+    /// Live view of agents, traffic, circuits and budgets
+    dashboard: Arc<dashboard::EventBus>,
+    /// Entities whose budget burn-down warning is raised
+    burn_warnings: std::collections::HashSet<String>,
+    /// Dangerous tool calls waiting on the user
+    approvals: Arc<approvals::ApprovalDesk>,
+}
+
+/// Gate an estimate against a run preset's budget and plan-review setting
+fn plan_review(estimate: estimate::RunEstimate, preset: Option<&presets::RunPreset>) -> estimate::PlanReview {
+    let budget = preset.map(|p| p.budget);
+    let requires_approval = preset.is_some_and(|p| p.requires(presets::ApprovalGate::PlanReview))
+        || budget.is_some_and(|b| estimate.may_exceed(b));
+    estimate::PlanReview {
+        estimate,
+        budget,
+        requires_approval,
+    }
+}
+
+/// Share of a directory's history that makes someone its owner for review
+pub const OWNER_REVIEW_SHARE: f64 = 0.5;
+
+/// A changed path and the people who should review it
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct OwnerReview {
+    pub path: String,
+    pub owners: Vec<zed42_toolboxes::vcs::OwnerShare>,
+}
+
+/// Where a pull-request description ended up
+#[derive(Debug, Clone)]
+pub struct PullRequestOutcome {
+    /// Description written to the workspace
+    pub path: std::path::PathBuf,
+    /// Pull request opened on the forge, if one was posted
+    pub url: Option<String>,
+}
+
+/// A spawn held back by placement until resources free up
+#[derive(Debug, Clone)]
+pub struct DeferredSpawn {
+    /// Agent id the spawn will use once placed
+    pub ticket: AgentId,
+    pub agent_type: AgentType,
+    /// Latest reason placement gave
+    pub reason: String,
+    pub queued_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Result of [`Cortex::request_agent`]
+#[derive(Debug, Clone)]
+pub enum SpawnOutcome {
+    Spawned(AgentId),
+    Deferred(DeferredSpawn),
 }
 
 
 impl Cortex {
     pub fn new(session_id: SessionId) -> Self {
+        let dashboard = Arc::new(dashboard::EventBus::new());
+        let approvals = Arc::new(approvals::ApprovalDesk::new(session_id).with_events(dashboard.clone()));
         Self {
             session_id,
+            started_at: chrono::Utc::now(),
             blackboard: None,
             memory: MemorySubstrate::default(),
             active_agents: HashMap::new(),
+            scheduler: scheduler::IntentScheduler::new(),
+            queue: queue::IntentQueue::default(),
+            presets: presets::PresetRegistry::default(),
+            run_presets: HashMap::new(),
+            shutdown: zed42_core::ShutdownController::new(),
+            workers: None,
+            remote: None,
+            remote_agents: std::collections::HashSet::new(),
+            titan: None,
+            local_models: false,
+            deferred: std::collections::VecDeque::new(),
+            keep_alive: None,
+            planner: None,
+            plan: None,
+            agent_types: HashMap::new(),
+            history: affinity::AgentHistory::default(),
+            clarifications: Arc::new(intent::ClarificationDesk::new()),
+            clarification_policy: intent::ClarificationPolicy::default(),
+            repo_root: None,
+            context_budget: context_pack::DEFAULT_CONTEXT_BUDGET,
+            dashboard,
+            burn_warnings: std::collections::HashSet::new(),
+            approvals,
+        }
+    }
+
+    /// Decompose intents with this model (enables planning and follow-ups)
+    pub fn with_planner(mut self, client: Arc<dyn zed42_llm::LlmClient>) -> Self {
+        self.planner = Some(client);
+        self
+    }
+
+    pub fn session_id(&self) -> SessionId {
+        self.session_id
+    }
+
+    pub fn plan(&self) -> Option<&planner::SessionPlan> {
+        self.plan.as_ref()
+    }
+
+    /// Use these memory tiers for context packs and clarifications
+    pub fn with_memory(mut self, memory: MemorySubstrate) -> Self {
+        self.memory = memory;
+        self
+    }
+
+    /// Include file contents from this checkout in context packs
+    pub fn with_repo_root(mut self, repo_root: impl Into<std::path::PathBuf>) -> Self {
+        self.repo_root = Some(repo_root.into());
+        self
+    }
+
+    /// Token budget of each task's context pack (0 disables packs)
+    pub fn with_context_budget(mut self, budget: usize) -> Self {
+        self.context_budget = budget;
+        self
+    }
+
+    /// Record a user's edit or rejection of an agent's output
+    ///
+    /// Stored in session memory and the knowledge graph; later prompts for
+    /// the same agent type get it through [`Cortex::feedback_digest`].
+    pub async fn record_feedback(&self, feedback: zed42_memory::feedback::Feedback) -> anyhow::Result<()> {
+        tracing::info!(agent_type = %feedback.agent_type, kind = ?feedback.kind, "Recording user feedback");
+        self.memory.record_feedback(&feedback).await
+    }
+
+    /// The latest corrections for `agent_type` in this project, for its prompts
+    pub async fn feedback_digest(
+        &self,
+        agent_type: &AgentType,
+        limit: usize,
+    ) -> anyhow::Result<zed42_memory::feedback::FeedbackDigest> {
+        self.memory.feedback_digest(&planner::agent_type_name(agent_type), limit).await
+    }
+
+    /// Archive the evaluation of a completed task; a no-op without archive memory
+    pub fn record_evaluation(&self, evaluation: &zed42_memory::evaluation::TaskEvaluation) -> anyhow::Result<()> {
+        let Some(archive) = self.memory.archive() else {
+            return Ok(());
+        };
+        tracing::debug!(task = %evaluation.task_id, agent_type = %evaluation.agent_type, score = ?evaluation.score(), "Recording task evaluation");
+        archive.record_evaluation(evaluation)
+    }
+
+    /// Move routing logs older than `retention` from `router` to archive memory
+    ///
+    /// Returns how many were archived; a no-op without archive memory.
+    pub async fn archive_routing_logs(&self, router: &zed42_mom::Router, retention: chrono::Duration) -> anyhow::Result<usize> {
+        let Some(archive) = self.memory.archive() else {
+            return Ok(0);
+        };
+        let cutoff = chrono::Utc::now() - retention;
+        router
+            .archive_routing_logs_before(cutoff, |logs| {
+                let archived_at = chrono::Utc::now().timestamp();
+                let entries = logs
+                    .iter()
+                    .map(|log| {
+                        // Keyed on the log so a retry after a failed delete replaces, not duplicates
+                        let log_id = log.id.clone().unwrap_or_else(|| Uuid::new_v4().to_string());
+                        Ok(zed42_memory::archive::ArchiveEntry {
+                            id: format!("{}:{}", ROUTING_LOG_ENTRY_TYPE, log_id),
+                            source_tier: "mom".to_string(),
+                            entry_type: ROUTING_LOG_ENTRY_TYPE.to_string(),
+                            content: serde_json::to_value(log)?,
+                            timestamp: log.timestamp.timestamp(),
+                            archived_at,
+                            metadata: Some(serde_json::json!({ "agent_id": log.agent_id, "model": log.selected_model })),
+                        })
+                    })
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                archive.upsert_batch(entries).map(|_| ())
+            })
+            .await
+    }
+
+    /// Mark a planned task finished; its agent shows as idle again
+    pub fn complete_task(&mut self, task_id: &str) {
+        let Some(plan) = self.plan.as_mut() else { return };
+        if plan.graph.node(task_id).is_none() {
+            return;
+        }
+        plan.completed.insert(task_id.to_string());
+        if let Some(agent_id) = plan.task_agents.get(task_id).copied() {
+            self.publish_agent(agent_id, AgentStatus::Idle, None);
+            if let Some(blackboard) = self.blackboard.clone() {
+                let (session_id, task_id) = (self.session_id, task_id.to_string());
+                tokio::spawn(async move {
+                    if let Err(e) = blackboard.complete_queued_task(session_id, &task_id, agent_id).await {
+                        tracing::warn!(task = %task_id, error = %e, "Failed to mark queued task done");
+                    }
+                });
+            }
         }
     }
 
+    /// Project budget burn-down and warn about budgets about to run out
+    ///
+    /// Each of `entities` is projected from its recent ledger settlements.
+    /// With a session plan, the projection also counts the tasks left and,
+    /// from `router`'s logs, what a finished task cost, to tell how many
+    /// tasks the rest of the budget will not cover. A budget projected to run
+    /// out within the policy horizon raises one `budget_forecast` alert
+    /// (which notifications pick up) until its projection recovers; the
+    /// dashboard shows the warning meanwhile.
+    pub async fn check_burn_down(
+        &mut self,
+        ledger: &IntelligenceLedger,
+        router: Option<&zed42_mom::Router>,
+        entities: &[String],
+        policy: &zed42_ledger::projection::ProjectionPolicy,
+    ) -> anyhow::Result<Vec<zed42_ledger::projection::BurnProjection>> {
+        let outlook = self.task_outlook(router).await?;
+        let mut projections = Vec::new();
+        for entity_id in entities {
+            let Some(mut projection) = ledger.project_burn(entity_id, policy).await? else { continue };
+            if let Some(ref outlook) = outlook {
+                projection = projection.with_tasks(outlook.clone());
+            }
+
+            if projection.should_warn(policy) {
+                let message = projection.message();
+                if self.burn_warnings.insert(entity_id.clone()) {
+                    tracing::warn!(entity = %entity_id, "{}", message);
+                    if let Some(blackboard) = &self.blackboard {
+                        blackboard.broadcast_alert("budget_forecast", &message).await;
+                    }
+                }
+                self.dashboard.publish(dashboard::DashboardEvent::BudgetForecast {
+                    entity_id: entity_id.clone(),
+                    warning: Some(message),
+                });
+            } else if self.burn_warnings.remove(entity_id) {
+                self.dashboard.publish(dashboard::DashboardEvent::BudgetForecast {
+                    entity_id: entity_id.clone(),
+                    warning: None,
+                });
+            }
+            projections.push(projection);
+        }
+        Ok(projections)
+    }
+
+    /// Tasks left in the plan and the mean routed cost of a finished one
+    async fn task_outlook(
+        &self,
+        router: Option<&zed42_mom::Router>,
+    ) -> anyhow::Result<Option<zed42_ledger::projection::TaskOutlook>> {
+        let Some(ref plan) = self.plan else {
+            return Ok(None);
+        };
+        let mut task_costs: HashMap<String, rust_decimal::Decimal> = HashMap::new();
+        if let Some(router) = router {
+            let query = zed42_mom::analytics::RoutingLogQuery::new().since(self.started_at);
+            for log in router.query_routing_logs(&query).await? {
+                if let (Some(task_id), Some(cost)) = (log.task_id, log.cost) {
+                    if plan.completed.contains(&task_id) {
+                        *task_costs.entry(task_id).or_default() += cost;
+                    }
+                }
+            }
+        }
+        let cost_per_task = (!task_costs.is_empty())
+            .then(|| task_costs.values().copied().sum::<rust_decimal::Decimal>() / rust_decimal::Decimal::from(task_costs.len()));
+        Ok(Some(zed42_ledger::projection::TaskOutlook {
+            remaining_tasks: plan.remaining_tasks(),
+            cost_per_task,
+        }))
+    }
+
+    /// Compare an experiment's variants since `start_timestamp`
+    ///
+    /// Scores come from archived evaluations tagged with the experiment id
+    /// (value: the variant name), costs from the router's logs.
+    pub async fn experiment_report(
+        &self,
+        router: &zed42_mom::Router,
+        experiment: &zed42_mom::experiment::Experiment,
+        start_timestamp: i64,
+    ) -> anyhow::Result<zed42_mom::experiment::ExperimentReport> {
+        let scores = match self.memory.archive() {
+            Some(archive) => archive.tagged_scores(&experiment.id, start_timestamp, chrono::Utc::now().timestamp())?,
+            None => Vec::new(),
+        };
+        router.experiment_report(experiment, &scores).await
+    }
+
+    /// Build a planned task for dispatch, with its context pack attached
+    ///
+    /// The pack holds knowledge-graph hits, session decisions and impacted
+    /// files for the task's description, within the context budget.
+    pub async fn prepare_task(&self, task_id: &str) -> anyhow::Result<zed42_core::Task> {
+        let node = self
+            .plan
+            .as_ref()
+            .and_then(|plan| plan.graph.node(task_id))
+            .ok_or_else(|| anyhow::anyhow!("Task {} is not in the session plan", task_id))?;
+        let mut task = zed42_core::Task::new(node.description.clone());
+        task.id = node.task_id.clone();
+        if self.context_budget == 0 {
+            return Ok(task);
+        }
+
+        let mut builder = context_pack::ContextPackBuilder::from_memory(&self.memory, self.context_budget);
+        if let Some(ref root) = self.repo_root {
+            builder = builder.with_repo_root(root);
+        }
+        let pack = builder.build(&node.description).await;
+        tracing::debug!(task = %task_id, snippets = pack.snippets.len(), tokens = pack.estimated_tokens, "Assembled context pack");
+        Ok(task.with_context_pack(pack))
+    }
+
+    /// When to ask clarifying questions and how long to wait for answers
+    pub fn with_clarification_policy(mut self, policy: intent::ClarificationPolicy) -> Self {
+        self.clarification_policy = policy;
+        self
+    }
+
+    /// Where the UI or CLI answers clarifying questions
+    pub fn clarifications(&self) -> Arc<intent::ClarificationDesk> {
+        self.clarifications.clone()
+    }
+
+    /// Use this approval desk (e.g. one recording decisions in the ledger)
+    ///
+    /// Its requests and decisions are published on the Cortex's event bus.
+    pub fn with_approvals(mut self, desk: approvals::ApprovalDesk) -> Self {
+        self.approvals = Arc::new(desk.with_events(self.dashboard.clone()));
+        self
+    }
+
+    /// Where the UI answers approval requests for dangerous tool calls
+    pub fn approvals(&self) -> Arc<approvals::ApprovalDesk> {
+        self.approvals.clone()
+    }
+
+    /// Approver for the tool executors of a run, if its preset gates dangerous tools
+    pub fn approver_for(&self, preset: Option<&presets::RunPreset>) -> Option<Arc<dyn zed42_toolboxes::Approver>> {
+        preset
+            .is_some_and(|p| p.requires(presets::ApprovalGate::DangerousTools))
+            .then(|| self.approvals.clone() as Arc<dyn zed42_toolboxes::Approver>)
+    }
+
+    /// Tool executor for the agents of run `run_id`
+    ///
+    /// Calls are cancelled on shutdown, and held for approval when the run's
+    /// preset gates dangerous tools. Callers register the agent's tools.
+    pub fn tool_executor(&self, run_id: &str) -> zed42_toolboxes::ToolExecutor {
+        let executor = zed42_toolboxes::ToolExecutor::new().with_cancellation(self.shutdown.token());
+        match self.approver_for(self.run_preset(run_id)) {
+            Some(approver) => executor.with_approver(approver),
+            None => executor,
+        }
+    }
+
+    /// Check local spawns against SpaceSentry vitals and placement reservations
+    pub fn with_titan(mut self, titan: Arc<zed42_core::titan::TitanSubstrate>) -> Self {
+        self.titan = Some(titan);
+        self
+    }
+
+    /// Local agents run models on the GPU, so each needs a GPU slot
+    pub fn with_local_models(mut self) -> Self {
+        self.local_models = true;
+        self
+    }
+
+    /// Warm local models when the session starts and keep them loaded while agents run
+    pub fn with_keep_alive(mut self, keep_alive: Arc<zed42_llm::KeepAlive>) -> Self {
+        self.keep_alive = Some(keep_alive);
+        self
+    }
+
+    pub fn keep_alive(&self) -> Option<&zed42_llm::KeepAlive> {
+        self.keep_alive.as_deref()
+    }
+
+    /// Spawns waiting for resources
+    pub fn deferred_spawns(&self) -> impl Iterator<Item = &DeferredSpawn> {
+        self.deferred.iter()
+    }
+
+    /// Run agents as separate worker processes (configure before `initialize`)
+    pub fn with_workers(mut self, config: workers::WorkerConfig) -> Self {
+        self.workers = Some(Arc::new(workers::WorkerPool::new(config, self.session_id)));
+        self
+    }
+
+    pub fn workers(&self) -> Option<&workers::WorkerPool> {
+        self.workers.as_deref()
+    }
+
+    /// Place agents on authenticated remote workers when one can run them
+    pub fn with_remote_workers(
+        mut self,
+        registry: remote::RemoteRegistry<surrealdb::engine::any::Any>,
+    ) -> Self {
+        self.remote = Some(Arc::new(registry));
+        self
+    }
+
+    pub fn remote_workers(&self) -> Option<&remote::RemoteRegistry<surrealdb::engine::any::Any>> {
+        self.remote.as_deref()
+    }
+
+    /// Use a (typically persistent) scheduler for recurring intents
+    pub fn with_scheduler(mut self, scheduler: scheduler::IntentScheduler) -> Self {
+        self.scheduler = scheduler;
+        self
+    }
+
+    pub fn scheduler(&self) -> &scheduler::IntentScheduler {
+        &self.scheduler
+    }
+
+    pub fn scheduler_mut(&mut self) -> &mut scheduler::IntentScheduler {
+        &mut self.scheduler
+    }
+
+    /// Use a queue with a different concurrency limit or preemption rule
+    pub fn with_queue(mut self, queue: queue::IntentQueue) -> Self {
+        self.queue = queue;
+        self
+    }
+
+    pub fn queue(&self) -> &queue::IntentQueue {
+        &self.queue
+    }
+
+    /// Use presets loaded from config instead of the built-ins
+    pub fn with_presets(mut self, presets: presets::PresetRegistry) -> Self {
+        self.presets = presets;
+        self
+    }
+
+    pub fn presets(&self) -> &presets::PresetRegistry {
+        &self.presets
+    }
+
+    /// Preset a queued run was submitted with
+    pub fn run_preset(&self, id: &str) -> Option<&presets::RunPreset> {
+        self.run_presets.get(id)
+    }
+
+    /// Replace the approval gates of a queued run, or else of the preset named `target`
+    ///
+    /// Returns the gates it had before. Duplicates are dropped.
+    pub fn set_approval_gates(
+        &mut self,
+        target: &str,
+        gates: Vec<presets::ApprovalGate>,
+    ) -> anyhow::Result<Vec<presets::ApprovalGate>> {
+        let mut unique = Vec::new();
+        for gate in gates {
+            if !unique.contains(&gate) {
+                unique.push(gate);
+            }
+        }
+        if let Some(preset) = self.run_presets.get_mut(target) {
+            return Ok(std::mem::replace(&mut preset.approval_gates, unique));
+        }
+        let mut preset = self
+            .presets
+            .get(target)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("No queued run or preset named {}", target))?;
+        let previous = std::mem::replace(&mut preset.approval_gates, unique);
+        self.presets.insert(target, preset);
+        Ok(previous)
+    }
+
+    /// Register background tasks and cleanup hooks here so `shutdown` stops them
+    pub fn shutdown_controller(&self) -> &zed42_core::ShutdownController {
+        &self.shutdown
+    }
+
     /// Initialize the Cortex and connect to subsystems
     pub async fn initialize(&mut self, blackboard: BlackboardDb) -> anyhow::Result<()> {
+        self.shutdown.link(blackboard.watcher_token());
+        let blackboard = Arc::new(blackboard);
+        if let Some(pool) = &self.workers {
+            self.forward_worker_crashes(pool, blackboard.clone());
+        }
+        if let Some(registry) = self.remote.clone() {
+            self.shutdown.spawn("remote-membership", move |token| async move {
+                registry.run(std::time::Duration::from_secs(5), token).await;
+            });
+        }
+        if let Some(keep_alive) = self.keep_alive.clone() {
+            self.sync_keep_alive();
+            self.shutdown.spawn("local-model-keep-alive", move |token| async move {
+                keep_alive.run(token.cancelled()).await;
+            });
+        }
         self.blackboard = Some(blackboard);
         // Initialize memory substrate
         // Set up message subscriptions
@@ -47,17 +597,1176 @@ impl Cortex {
     }
 
     /// Process user intent and spawn appropriate agents
+    ///
+    /// With a planner attached the intent is parsed (asking clarifying
+    /// questions if it is unclear), becomes the session plan, and every task
+    /// gets an agent; without one this is a no-op.
     pub async fn process_intent(&mut self, intent: &str) -> anyhow::Result<()> {
-        // Parse intent
-        // Create task decomposition
-        // Spawn agents based on task requirements
+        let Some(client) = self.planner.clone() else {
+            return Ok(());
+        };
+        self.plan_intent_with(client, intent).await
+    }
+
+    /// Plan `intent` through `client` and spawn agents for its tasks
+    async fn plan_intent_with(&mut self, client: Arc<dyn zed42_llm::LlmClient>, intent: &str) -> anyhow::Result<()> {
+        let clarified = self.clarify_intent(client.as_ref(), intent).await?;
+        let graph = planner::plan_intent(client.as_ref(), &clarified).await?;
+        let tasks: Vec<String> = graph.nodes.iter().map(|n| n.task_id.clone()).collect();
+        let mut plan = planner::SessionPlan::new(graph);
+        self.assign_tasks(&mut plan, &tasks).await?;
+        plan.decision_id = self.record_plan_decision(&plan, intent, None).await?;
+        self.persist_tasks(&plan, &tasks).await?;
+        self.plan = Some(plan);
+        Ok(())
+    }
+
+    /// Parse an intent and, when it is unclear, block until the user answers
+    ///
+    /// Questions go out as a `clarification_needed` alert and wait on the
+    /// clarification desk; after the policy timeout the parser's best
+    /// guesses are used. The Q&A is stored in session memory (working
+    /// memory without one). Returns the intent with the answers appended.
+    async fn clarify_intent(&self, client: &dyn zed42_llm::LlmClient, intent: &str) -> anyhow::Result<String> {
+        let parsed = intent::IntentParser::new(client).parse(intent).await?;
+        let questions = parsed.questions_to_ask(&self.clarification_policy);
+        if questions.is_empty() {
+            return Ok(intent.to_string());
+        }
+
+        let (request_id, rx) = self.clarifications.open(questions.clone());
+        tracing::info!(
+            request = %request_id,
+            confidence = parsed.confidence,
+            questions = questions.len(),
+            "Waiting for intent clarification"
+        );
+        if let Some(blackboard) = &self.blackboard {
+            let text = intent::format_questions(&request_id, &questions);
+            blackboard.broadcast_alert("clarification_needed", &text).await;
+        }
+        let clarification = self
+            .clarifications
+            .wait(&request_id, intent, &questions, rx, self.clarification_policy.timeout)
+            .await;
+        if clarification.timed_out {
+            tracing::warn!(request = %request_id, "No clarification received; planning with best guesses");
+        }
+
+        let record = serde_json::to_value(&clarification)?;
+        match self.memory.session() {
+            Some(session) => {
+                session.insert(
+                    zed42_memory::session::EntryType::UserMessage,
+                    record,
+                    Some(serde_json::json!({ "kind": "clarification" })),
+                )?;
+            }
+            None => self.memory.store_working(format!("clarification:{}", request_id), record),
+        }
+        Ok(clarification.clarified_intent())
+    }
+
+    /// Refine the running session's plan with a follow-up intent
+    ///
+    /// The planner proposes amendments to existing tasks and new tasks.
+    /// Amended tasks keep their agent unless their agent type changed; new
+    /// and reassigned tasks go to a running agent of the right type when one
+    /// exists, otherwise a new agent is requested. The revision is recorded
+    /// as a `plan_revision` decision chained to the previous one.
+    pub async fn follow_up(&mut self, intent: &str) -> anyhow::Result<planner::PlanRevision> {
+        let client = self
+            .planner
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("Cortex has no planner attached"))?;
+        let mut plan = self
+            .plan
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("No plan in this session to follow up on"))?;
+
+        let delta = planner::plan_follow_up(client.as_ref(), &plan.graph, intent).await?;
+        if delta.is_empty() {
+            anyhow::bail!("Follow-up produced no changes to the plan");
+        }
+        let applied = plan.graph.apply_delta(delta)?;
+        plan.revision += 1;
+
+        let mut pending: Vec<String> = applied.reassigned.clone();
+        pending.extend(applied.added.iter().cloned());
+        for task_id in &applied.reassigned {
+            plan.task_agents.remove(task_id);
+        }
+        let (reused, spawned) = self.assign_tasks(&mut plan, &pending).await?;
+
+        let parent = plan.decision_id.clone();
+        plan.decision_id = self.record_plan_decision(&plan, intent, parent).await?;
+        let mut changed = applied.amended.clone();
+        changed.extend(applied.added.iter().cloned());
+        self.persist_tasks(&plan, &changed).await?;
+        tracing::info!(
+            revision = plan.revision,
+            amended = applied.amended.len(),
+            added = applied.added.len(),
+            reused = reused.len(),
+            "Applied follow-up intent"
+        );
+        let revision = planner::PlanRevision {
+            revision: plan.revision,
+            intent: intent.to_string(),
+            applied,
+            reused,
+            spawned,
+            decision_id: plan.decision_id.clone(),
+        };
+        self.plan = Some(plan);
+        Ok(revision)
+    }
+
+    /// Remember the file an agent's artifact touched, for later assignments
+    pub fn record_artifact(&mut self, agent_id: AgentId, artifact: &zed42_core::Artifact) {
+        self.history.record_artifact(agent_id, artifact);
+    }
+
+    /// Give each task an agent: a running one of its type, else a new one
+    ///
+    /// Among several running agents of the type, the one with the most
+    /// affinity for the task is reused (see [`affinity`]).
+    /// Returns the reused and newly requested assignments.
+    async fn assign_tasks(
+        &mut self,
+        plan: &mut planner::SessionPlan,
+        task_ids: &[String],
+    ) -> anyhow::Result<(std::collections::BTreeMap<String, AgentId>, std::collections::BTreeMap<String, AgentId>)> {
+        let mut reused = std::collections::BTreeMap::new();
+        let mut spawned = std::collections::BTreeMap::new();
+        for task_id in task_ids {
+            let Some(agent_type) = plan.graph.node(task_id).map(|n| n.agent_type.clone()) else { continue };
+            let candidates: Vec<AgentId> = self
+                .agent_types
+                .iter()
+                .filter(|(_, t)| **t == agent_type)
+                .map(|(id, _)| *id)
+                .collect();
+            let running = match candidates.len() {
+                0 | 1 => candidates.first().copied(),
+                _ => self.pick_by_affinity(plan, task_id, &candidates).await,
+            };
+            let agent_id = match running {
+                Some(agent_id) => {
+                    reused.insert(task_id.clone(), agent_id);
+                    agent_id
+                }
+                None => {
+                    let agent_id = match self.request_agent(agent_type).await? {
+                        SpawnOutcome::Spawned(agent_id) => agent_id,
+                        SpawnOutcome::Deferred(deferred) => deferred.ticket,
+                    };
+                    spawned.insert(task_id.clone(), agent_id);
+                    agent_id
+                }
+            };
+            plan.task_agents.insert(task_id.clone(), agent_id);
+            let task = plan.graph.node(task_id).map(|n| n.description.clone());
+            self.publish_agent(agent_id, AgentStatus::Working, task);
+        }
+        Ok((reused, spawned))
+    }
+
+    /// The candidate that produced the task's parents or touched its files
+    async fn pick_by_affinity(&self, plan: &planner::SessionPlan, task_id: &str, candidates: &[AgentId]) -> Option<AgentId> {
+        let node = plan.graph.node(task_id)?;
+        let parents = node
+            .depends_on
+            .iter()
+            .filter_map(|parent| plan.task_agents.get(parent).copied())
+            .collect();
+        let mut footprint = affinity::TaskFootprint::new(node.description.clone()).with_parent_agents(parents);
+        if let Some(graph) = self.memory.knowledge_graph() {
+            footprint = footprint.with_graph_files(graph).await;
+        }
+        let chosen = affinity::choose(candidates, &footprint, &self.history)?;
+        tracing::debug!(task = %task_id, agent = %chosen.agent_id, score = chosen.score, reasons = ?chosen.reasons, "Assigned by affinity");
+        Some(chosen.agent_id)
+    }
+
+    /// Put planned tasks in the blackboard's task queue, if there is one
+    ///
+    /// Tasks already queued keep their status and attempts, so a restarted
+    /// Cortex re-planning the same session doesn't rerun finished work.
+    async fn persist_tasks(&self, plan: &planner::SessionPlan, task_ids: &[String]) -> anyhow::Result<()> {
+        let Some(blackboard) = &self.blackboard else {
+            return Ok(());
+        };
+        for task_id in task_ids {
+            let Some(node) = plan.graph.node(task_id) else { continue };
+            let mut task = zed42_blackboard::QueuedTask::new(self.session_id, task_id.clone(), node.description.clone())
+                .with_dependencies(node.depends_on.clone());
+            if let Some(agent_id) = plan.task_agents.get(task_id) {
+                task = task.with_planned_agent(*agent_id);
+            }
+            blackboard.enqueue_task(task).await?;
+        }
+        Ok(())
+    }
+
+    /// Record a plan revision on the blackboard; `None` without a blackboard
+    async fn record_plan_decision(
+        &self,
+        plan: &planner::SessionPlan,
+        intent: &str,
+        parent: Option<String>,
+    ) -> anyhow::Result<Option<String>> {
+        let Some(blackboard) = &self.blackboard else {
+            return Ok(None);
+        };
+        let id = format!("plan-{}-rev{}", self.session_id, plan.revision);
+        blackboard
+            .record_decision(zed42_blackboard::DecisionNode {
+                id: id.clone(),
+                decision_type: "plan_revision".to_string(),
+                description: intent.to_string(),
+                made_by: AgentId::nil(),
+                rationale: serde_json::to_value(plan)?,
+                alternatives_considered: Vec::new(),
+                timestamp: chrono::Utc::now().timestamp(),
+                parent_decision: parent,
+                confidence: None,
+            })
+            .await?;
+        Ok(Some(id))
+    }
+
+    /// Queue an intent and dispatch whatever can run now
+    pub async fn submit_intent(
+        &mut self,
+        intent: &str,
+        priority: queue::IntentPriority,
+    ) -> anyhow::Result<(String, Vec<queue::QueueEvent>)> {
+        let id = self.queue.submit(intent, priority);
+        let events = self.dispatch_queue().await?;
+        Ok((id, events))
+    }
+
+    /// Queue an intent using the settings of a named preset
+    pub async fn submit_intent_with_preset(
+        &mut self,
+        intent: &str,
+        priority: queue::IntentPriority,
+        preset: &str,
+    ) -> anyhow::Result<(String, Vec<queue::QueueEvent>)> {
+        let preset = self.presets.resolve(preset)?.clone();
+        let id = self.queue.submit(intent, priority);
+        self.run_presets.insert(id.clone(), preset);
+        let events = self.dispatch_queue().await?;
+        Ok((id, events))
+    }
+
+    /// Estimate a plan before the plan-review gate
+    ///
+    /// `history` is the routing log of earlier runs (see `Router::query_routing_logs`).
+    pub fn review_plan(
+        &self,
+        run_id: &str,
+        tasks: &[estimate::PlannedTask],
+        history: &[zed42_mom::types::RoutingLog],
+    ) -> estimate::PlanReview {
+        let estimate = estimate::RunEstimator::from_logs(history).estimate(tasks);
+        plan_review(estimate, self.run_presets.get(run_id))
+    }
+
+    /// Plan an intent without running it
+    ///
+    /// Makes the planning call through `planner_client`, builds the task
+    /// DAG, estimates it against `history` and lists the agents that would
+    /// be spawned. Nothing is queued or spawned; with `preset` the plan is
+    /// checked against that preset's budget and plan-review gate.
+    pub async fn dry_run_intent(
+        &self,
+        intent: &str,
+        planner_client: &dyn zed42_llm::LlmClient,
+        history: &[zed42_mom::types::RoutingLog],
+        preset: Option<&str>,
+    ) -> anyhow::Result<planner::DryRunPlan> {
+        let graph = planner::plan_intent(planner_client, intent).await?;
+        let estimate = estimate::RunEstimator::from_logs(history).estimate(&graph.planned_tasks());
+        let preset = preset.map(|name| self.presets.resolve(name)).transpose()?;
+        tracing::info!(tasks = graph.nodes.len(), waves = graph.waves.len(), "Dry run planned intent");
+        Ok(planner::DryRunPlan::new(graph, plan_review(estimate, preset)))
+    }
+
+    /// Store model recommendations as Decision nodes for traceability
+    ///
+    /// Each becomes a `model_recommendation` decision made by the Cortex
+    /// (nil agent id) carrying the full recommendation as rationale, so a
+    /// later profile change can name it as its parent decision. Returns the
+    /// decision ids.
+    pub async fn record_recommendations(
+        &self,
+        recommendations: &[zed42_mom::recommend::ModelRecommendation],
+    ) -> anyhow::Result<Vec<String>> {
+        let blackboard = self
+            .blackboard
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Cortex has no blackboard attached"))?;
+        let now = chrono::Utc::now().timestamp();
+        let mut ids = Vec::with_capacity(recommendations.len());
+        for rec in recommendations {
+            let id = format!("model-recommendation-{}-tier{}-{}", rec.agent_id, rec.tier, now);
+            blackboard
+                .record_decision(zed42_blackboard::DecisionNode {
+                    id: id.clone(),
+                    decision_type: "model_recommendation".to_string(),
+                    description: rec.message.clone(),
+                    made_by: AgentId::nil(),
+                    rationale: serde_json::to_value(rec)?,
+                    alternatives_considered: rec.suggested_model.iter().cloned().collect(),
+                    timestamp: now,
+                    parent_decision: None,
+                    confidence: None,
+                })
+                .await?;
+            ids.push(id);
+        }
+        Ok(ids)
+    }
+
+    /// Recommend profile changes from the routing `history` since `since` and record them
+    ///
+    /// Each recommendation is stored as a decision (see
+    /// [`Cortex::record_recommendations`]); returns them with their decision
+    /// ids. A router's history is [`zed42_mom::Router::history`].
+    pub async fn recommend_models(
+        &self,
+        history: &zed42_mom::analytics::RoutingHistory,
+        since: Option<chrono::DateTime<chrono::Utc>>,
+        policy: &zed42_mom::recommend::RecommendationPolicy,
+    ) -> anyhow::Result<Vec<(String, zed42_mom::recommend::ModelRecommendation)>> {
+        let recommendations = history.model_recommendations(since, policy).await?;
+        let ids = self.record_recommendations(&recommendations).await?;
+        Ok(ids.into_iter().zip(recommendations).collect())
+    }
+
+    /// Mark a queued run as finished and start the next one
+    pub async fn complete_intent(&mut self, id: &str) -> anyhow::Result<Vec<queue::QueueEvent>> {
+        self.queue.complete(id);
+        self.run_presets.remove(id);
+        self.dispatch_queue().await
+    }
+
+    async fn dispatch_queue(&mut self) -> anyhow::Result<Vec<queue::QueueEvent>> {
+        let events = self.queue.dispatch();
+        for event in &events {
+            match event {
+                queue::QueueEvent::Started { id } => {
+                    let Some(run) = self.queue.run(id) else { continue };
+                    let intent = run.intent.clone();
+                    tracing::info!(run = %id, priority = ?run.priority, "Starting queued intent");
+                    self.process_intent(&intent).await?;
+                }
+                queue::QueueEvent::Paused { id, by } => {
+                    tracing::info!(run = %id, preempted_by = %by, "Pausing intent for higher-priority work");
+                }
+                queue::QueueEvent::Resumed { id } => {
+                    tracing::info!(run = %id, "Resuming paused intent");
+                }
+            }
+        }
+        Ok(events)
+    }
+
+    /// Run every scheduled intent that is due
+    ///
+    /// Each run is funded with its own budget envelope in the ledger, and its
+    /// planning calls are billed to that envelope (see
+    /// [`scheduler::BilledClient`]). A run that fails is reported in its
+    /// outcome; the other due runs still start.
+    pub async fn run_due_intents(
+        &mut self,
+        ledger: Option<&IntelligenceLedger>,
+    ) -> anyhow::Result<Vec<scheduler::ScheduledRunOutcome>> {
+        let runs = self.scheduler.take_due(chrono::Utc::now())?;
+        let mut outcomes = Vec::with_capacity(runs.len());
+        for run in runs {
+            tracing::info!(schedule = %run.schedule_id, run = %run.run_id, "Running scheduled intent");
+            let error = self.start_scheduled_run(&run, ledger).await.err();
+            if let Some(ref e) = error {
+                tracing::warn!(schedule = %run.schedule_id, run = %run.run_id, "Scheduled intent failed: {:#}", e);
+            }
+            outcomes.push(scheduler::ScheduledRunOutcome { run, error });
+        }
+        Ok(outcomes)
+    }
+
+    async fn start_scheduled_run(
+        &mut self,
+        run: &scheduler::ScheduledRun,
+        ledger: Option<&IntelligenceLedger>,
+    ) -> anyhow::Result<()> {
+        if let Some(ledger) = ledger {
+            ledger.set_budget(Budget {
+                entity_id: run.budget_entity.clone(),
+                hard_limit: run.budget,
+                soft_limit: run.budget,
+                spent: rust_decimal::Decimal::ZERO,
+                currency: "USD".to_string(),
+                status: BudgetStatus::Active,
+                updated_at: chrono::Utc::now(),
+                tags: Default::default(),
+            }).await?;
+        }
+        let Some(planner) = self.planner.clone() else {
+            return Ok(());
+        };
+        let client = Arc::new(scheduler::BilledClient::new(planner, run.budget_entity.clone()));
+        self.plan_intent_with(client, &run.intent).await
+    }
+
+    /// Run due scheduled intents until shutdown
+    ///
+    /// Checks the scheduler every `poll`, so schedules added meanwhile are
+    /// picked up; the task runs on the Cortex's shutdown controller.
+    pub async fn start_scheduler(
+        cortex: Arc<tokio::sync::Mutex<Cortex>>,
+        ledger: Option<Arc<IntelligenceLedger>>,
+        poll: std::time::Duration,
+    ) {
+        let driver = cortex.clone();
+        cortex.lock().await.shutdown.spawn("intent-scheduler", move |token| async move {
+            let mut ticks = tokio::time::interval(poll);
+            loop {
+                tokio::select! {
+                    _ = token.cancelled() => break,
+                    _ = ticks.tick() => {}
+                }
+                // Shutdown may hold the Cortex; don't wait for it
+                let mut cortex = tokio::select! {
+                    _ = token.cancelled() => break,
+                    cortex = driver.lock() => cortex,
+                };
+                let due = cortex.scheduler.next_due().is_some_and(|at| at <= chrono::Utc::now());
+                if !due {
+                    continue;
+                }
+                if let Err(e) = cortex.run_due_intents(ledger.as_deref()).await {
+                    tracing::warn!("Failed to run scheduled intents: {:#}", e);
+                }
+            }
+        });
+    }
+
+    /// Archive routing logs older than `retention` every `poll` until shutdown
+    ///
+    /// Keeps the router's live log table small; the archived logs stay
+    /// queryable in Tier 4 under the `routing_log` entry type.
+    pub async fn start_routing_log_archiver(
+        cortex: Arc<tokio::sync::Mutex<Cortex>>,
+        router: Arc<zed42_mom::Router>,
+        retention: chrono::Duration,
+        poll: std::time::Duration,
+    ) {
+        let driver = cortex.clone();
+        cortex.lock().await.shutdown.spawn("routing-log-archiver", move |token| async move {
+            let mut ticks = tokio::time::interval(poll);
+            loop {
+                tokio::select! {
+                    _ = token.cancelled() => break,
+                    _ = ticks.tick() => {}
+                }
+                let cortex = tokio::select! {
+                    _ = token.cancelled() => break,
+                    cortex = driver.lock() => cortex,
+                };
+                match cortex.archive_routing_logs(&router, retention).await {
+                    Ok(0) => {}
+                    Ok(archived) => tracing::info!(archived, "Archived routing logs to Tier 4"),
+                    Err(e) => tracing::warn!("Failed to archive routing logs: {:#}", e),
+                }
+            }
+        });
+    }
+
+    /// Write a retrospective for the session to `workspace`
+    ///
+    /// Spend per team is read from the ledger budgets of `agents`; lessons
+    /// are the reflections stored in the knowledge graph since the session
+    /// started.
+    pub async fn generate_retrospective(
+        &self,
+        workspace: &std::path::Path,
+        agents: &[Agent],
+        ledger: Option<&IntelligenceLedger>,
+    ) -> anyhow::Result<std::path::PathBuf> {
+        let retro = self.collect_retrospective(agents, ledger).await?;
+        let path = retro.write_to(workspace)?;
+        tracing::info!(session = %self.session_id, path = %path.display(), "Wrote session retrospective");
+        Ok(path)
+    }
+
+    /// Write a transcript of every blackboard thread of this session
+    ///
+    /// Each thread becomes `<thread>.md`, `.html` and `.json` under
+    /// [`Cortex::transcripts_dir`]; `zed42 transcript` lists and prints them.
+    pub async fn export_transcripts(&self, workspace: &std::path::Path) -> anyhow::Result<Vec<std::path::PathBuf>> {
+        let Some(ref blackboard) = self.blackboard else {
+            return Ok(Vec::new());
+        };
+        let dir = Self::transcripts_dir(workspace, self.session_id);
+        let mut paths = Vec::new();
+        for thread_id in blackboard.thread_ids().await? {
+            paths.push(blackboard.transcript(thread_id).await?.write_to(&dir)?);
+        }
+        tracing::info!(session = %self.session_id, threads = paths.len(), dir = %dir.display(), "Exported thread transcripts");
+        Ok(paths)
+    }
+
+    /// Where the transcripts of `session_id` are exported: `<workspace>/.zed42/transcripts/<session>/`
+    pub fn transcripts_dir(workspace: &std::path::Path, session_id: SessionId) -> std::path::PathBuf {
+        workspace.join(".zed42").join("transcripts").join(session_id.to_string())
+    }
+
+    /// Transcripts exported for `session_id`, by thread id
+    pub fn exported_transcripts(
+        workspace: &std::path::Path,
+        session_id: SessionId,
+    ) -> anyhow::Result<Vec<zed42_blackboard::Transcript>> {
+        let dir = Self::transcripts_dir(workspace, session_id);
+        let mut files: Vec<std::path::PathBuf> = std::fs::read_dir(&dir)
+            .map_err(|e| anyhow::anyhow!("No transcripts exported for session {} ({}): {}", session_id, dir.display(), e))?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        files.sort();
+        files.iter().map(|path| zed42_blackboard::Transcript::load(path)).collect()
+    }
+
+    /// The exported transcript of the thread whose id starts with `thread`
+    pub fn exported_transcript(
+        workspace: &std::path::Path,
+        session_id: SessionId,
+        thread: &str,
+    ) -> anyhow::Result<zed42_blackboard::Transcript> {
+        let mut matches: Vec<zed42_blackboard::Transcript> = Self::exported_transcripts(workspace, session_id)?
+            .into_iter()
+            .filter(|transcript| transcript.thread_id.to_string().starts_with(thread))
+            .collect();
+        match matches.len() {
+            1 => Ok(matches.remove(0)),
+            0 => anyhow::bail!("No transcript for thread {} in session {}", thread, session_id),
+            n => anyhow::bail!("Thread {} is ambiguous ({} transcripts match)", thread, n),
+        }
+    }
+
+    /// Write a pull-request description for the session and optionally open it
+    ///
+    /// The description (intent, plan, decisions, test results, cost) is
+    /// always written under `<workspace>/.zed42/pull_requests/`. With a
+    /// `forge` it is also posted from the `head` branch; a failed post is
+    /// logged and leaves the file for manual use.
+    pub async fn generate_pull_request(
+        &self,
+        workspace: &std::path::Path,
+        agents: &[Agent],
+        ledger: Option<&IntelligenceLedger>,
+        forge: Option<(&zed42_toolboxes::external::CreatePullRequest, &str)>,
+    ) -> anyhow::Result<PullRequestOutcome> {
+        let retro = self.collect_retrospective(agents, ledger).await?;
+        let intent = self
+            .plan
+            .as_ref()
+            .map(|plan| plan.graph.intent.clone())
+            .unwrap_or_else(|| format!("Session {}", self.session_id));
+        let description = pull_request::PullRequestDescription::from_run(&intent, self.plan.as_ref(), &retro);
+        let path = description.write_to(workspace)?;
+        tracing::info!(session = %self.session_id, path = %path.display(), "Wrote pull request description");
+
+        let mut url = None;
+        if let Some((forge, head)) = forge {
+            let params = zed42_toolboxes::external::CreatePullRequestParams {
+                title: description.title.clone(),
+                body: description.to_markdown(),
+                head: head.to_string(),
+                base: None,
+                draft: false,
+            };
+            match forge.open(&params).await {
+                Ok(opened) => url = Some(opened),
+                Err(e) => tracing::warn!(error = %e, path = %path.display(), "Failed to open pull request; description left for manual use"),
+            }
+        }
+        Ok(PullRequestOutcome { path, url })
+    }
+
+    /// Task outcomes, decisions, spend and lessons of the session so far
+    async fn collect_retrospective(
+        &self,
+        agents: &[Agent],
+        ledger: Option<&IntelligenceLedger>,
+    ) -> anyhow::Result<retrospective::Retrospective> {
+        let mut retro = retrospective::Retrospective::new(self.session_id);
+
+        if let Some(ref blackboard) = self.blackboard {
+            let messages = blackboard
+                .get_messages(zed42_blackboard::MessageFilter {
+                    since_timestamp: Some(self.started_at.timestamp()),
+                    ..Default::default()
+                })
+                .await?;
+            retro.record_messages(&messages);
+            let decisions = blackboard.get_decisions(None).await?;
+            retro.record_decisions(
+                decisions
+                    .into_iter()
+                    .filter(|d| d.timestamp >= self.started_at.timestamp())
+                    .collect(),
+            );
+        }
+
+        if let Some(ledger) = ledger {
+            for agent in agents {
+                if let Some(budget) = ledger.get_budget(&agent.id.to_string()).await? {
+                    retro.add_cost(agent.team(), budget.spent);
+                }
+            }
+        }
+
+        if let Some(kg) = self.memory.knowledge_graph() {
+            let nodes = kg
+                .get_nodes_by_type(zed42_memory::knowledge_graph::NodeType::Documentation)
+                .await?;
+            for node in nodes {
+                if !node.name.starts_with("Lesson:") || node.created_at < self.started_at.timestamp() {
+                    continue;
+                }
+                let lesson = serde_json::from_str::<serde_json::Value>(&node.content)
+                    .ok()
+                    .and_then(|v| v.get("lesson").and_then(|l| l.as_str()).map(str::to_string))
+                    .unwrap_or(node.content);
+                retro.lessons.push(lesson);
+            }
+        }
+
+        Ok(retro)
+    }
+
+    /// Sync architecture decisions with the ADRs under `<repo_root>/docs/adr`
+    ///
+    /// Significant decisions on the blackboard are exported as new ADRs,
+    /// then every ADR (hand-edited ones included) is re-indexed into the
+    /// knowledge graph. Either side is skipped when it is not configured.
+    pub async fn sync_adrs(&self, repo_root: &std::path::Path) -> anyhow::Result<adr::AdrSyncReport> {
+        let sync = adr::AdrSync::new(repo_root);
+        let mut report = adr::AdrSyncReport::default();
+        if let Some(ref blackboard) = self.blackboard {
+            report.exported = sync.export(&blackboard.get_decisions(None).await?)?;
+        }
+        if let Some(kg) = self.memory.knowledge_graph() {
+            report.imported = sync.import(kg).await?;
+        }
+        tracing::info!(
+            exported = report.exported.len(),
+            imported = report.imported,
+            "Synced architecture decision records"
+        );
+        Ok(report)
+    }
+
+    /// Explain how an artifact or file change came about
+    ///
+    /// Trace records, review verdicts and decisions come from the blackboard
+    /// and the intent from the session plan; `routing_logs` are the model
+    /// calls of the run (see `Router::query_routing_logs`).
+    pub async fn explain(
+        &self,
+        target: &explain::ExplainTarget,
+        routing_logs: &[zed42_mom::types::RoutingLog],
+    ) -> anyhow::Result<explain::Explanation> {
+        let Some(ref blackboard) = self.blackboard else {
+            anyhow::bail!("Explanations need the blackboard's trace records");
+        };
+        let traces = blackboard.get_traces(None).await?;
+        let messages = blackboard.get_messages(zed42_blackboard::MessageFilter::default()).await?;
+        let decisions = blackboard.get_decisions(None).await?;
+        explain::ExplainSources {
+            plan: self.plan.as_ref(),
+            traces: &traces,
+            messages: &messages,
+            decisions: &decisions,
+            routing_logs,
+        }
+        .explain(target)
+    }
+
+    /// Changed paths that need a human owner's review
+    ///
+    /// Owners come from the ownership map stored in the knowledge graph (see
+    /// `zed42_toolboxes::vcs::OwnershipMap`); anyone holding at least
+    /// [`OWNER_REVIEW_SHARE`] of a path's history is asked to review it.
+    /// Without a knowledge graph no change is routed to an owner.
+    pub async fn owner_reviews(&self, changed_paths: &[String]) -> anyhow::Result<Vec<OwnerReview>> {
+        let Some(kg) = self.memory.knowledge_graph() else {
+            return Ok(Vec::new());
+        };
+        let ownership = zed42_toolboxes::vcs::OwnershipMap::load(kg).await?;
+        Ok(changed_paths
+            .iter()
+            .filter_map(|path| {
+                let owners: Vec<_> = ownership
+                    .reviewers_for(path, OWNER_REVIEW_SHARE)
+                    .into_iter()
+                    .cloned()
+                    .collect();
+                (!owners.is_empty()).then(|| OwnerReview { path: path.clone(), owners })
+            })
+            .collect())
+    }
+
+    /// Watch process memory and publish it as AURA vitals
+    ///
+    /// Under pressure the monitor shrinks working memory and caps embedding
+    /// batches; every sample is recorded on the blackboard under the
+    /// `memory` source, and level changes are broadcast as system alerts.
+    /// The monitor stops on `shutdown`.
+    pub fn start_memory_monitor(
+        &self,
+        thresholds: zed42_memory::pressure::PressureThresholds,
+        interval: std::time::Duration,
+    ) {
+        let (monitor, mut vitals) = self
+            .memory
+            .pressure_monitor(thresholds)
+            .spawn(interval, self.shutdown.token());
+        self.shutdown.track("memory-monitor", monitor);
+        let blackboard = self.blackboard.clone();
+        // Ends when the monitor stops and drops its sender
+        self.shutdown.track("memory-vitals", tokio::spawn(async move {
+            let mut level = vitals.borrow().level;
+            while vitals.changed().await.is_ok() {
+                let sample = vitals.borrow_and_update().clone();
+                let Some(ref blackboard) = blackboard else { continue };
+                match serde_json::to_value(&sample) {
+                    Ok(value) => {
+                        if let Err(e) = blackboard.record_vitals("memory", value).await {
+                            tracing::warn!(error = %e, "Failed to record memory vitals");
+                        }
+                    }
+                    Err(e) => tracing::warn!(error = %e, "Failed to serialize memory vitals"),
+                }
+                if sample.level != level {
+                    let reason = format!(
+                        "{:?} -> {:?} (rss {} bytes)",
+                        level,
+                        sample.level,
+                        sample.rss_bytes.unwrap_or_default()
+                    );
+                    blackboard.broadcast_alert("memory_pressure", &reason).await;
+                    level = sample.level;
+                }
+            }
+        }));
+    }
+
+    /// Event bus behind the dashboard, TUI and desktop UI
+    pub fn dashboard(&self) -> Arc<dashboard::EventBus> {
+        self.dashboard.clone()
+    }
+
+    /// Deliver notifications for this session's dashboard events until shutdown
+    pub fn start_notifications(&self, notifier: Arc<zed42_notify::Notifier>) {
+        let bus = self.dashboard.clone();
+        self.shutdown.spawn("notifications", move |token| notifications::forward(bus, notifier, token));
+    }
+
+    /// Feed the dashboard until shutdown
+    ///
+    /// VOX traffic and alerts come from the blackboard as they arrive; task
+    /// progress from the blackboard, circuit states from `router` and the
+    /// budgets of `budget_entities` from `ledger` are polled every `interval`.
+    pub fn start_dashboard(
+        &self,
+        router: Option<Arc<zed42_mom::Router>>,
+        ledger: Option<Arc<IntelligenceLedger>>,
+        budget_entities: Vec<String>,
+        interval: std::time::Duration,
+    ) {
+        if let Some(blackboard) = &self.blackboard {
+            let teams = [zed42_core::Team::Red, zed42_core::Team::Blue, zed42_core::Team::Green];
+            for (i, team) in teams.into_iter().enumerate() {
+                let mut messages = blackboard.subscribe(team);
+                let feed = self.dashboard.clone();
+                self.shutdown.spawn(format!("dashboard-vox-{:?}", team).to_lowercase(), move |token| async move {
+                    loop {
+                        let message = tokio::select! {
+                            _ = token.cancelled() => break,
+                            message = messages.recv() => message,
+                        };
+                        match message {
+                            // Broadcasts reach every team; count them once
+                            Ok(message) if i > 0 && message.target_team == "all" => {}
+                            Ok(message) => dashboard::DashboardEvent::from_vox(&message)
+                                .into_iter()
+                                .for_each(|event| feed.publish(event)),
+                            Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                            Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                        }
+                    }
+                });
+            }
+        }
+
+        let feed = self.dashboard.clone();
+        let blackboard = self.blackboard.clone();
+        self.shutdown.spawn("dashboard-poll", move |token| async move {
+            let mut ticks = tokio::time::interval(interval);
+            let mut reported: HashMap<String, chrono::DateTime<chrono::Utc>> = HashMap::new();
+            loop {
+                tokio::select! {
+                    _ = token.cancelled() => break,
+                    _ = ticks.tick() => {}
+                }
+                if let Some(ref blackboard) = blackboard {
+                    match blackboard.list_progress().await {
+                        Ok(progress) => {
+                            for progress in progress {
+                                // Only reports the viewers haven't seen
+                                if reported.get(&progress.task_id) == Some(&progress.reported_at) {
+                                    continue;
+                                }
+                                reported.insert(progress.task_id.clone(), progress.reported_at);
+                                feed.publish(dashboard::DashboardEvent::TaskProgress { progress });
+                            }
+                        }
+                        Err(e) => tracing::warn!(error = %e, "Failed to read task progress for the dashboard"),
+                    }
+                }
+                if let Some(ref router) = router {
+                    let circuits = router.get_circuit_status().into_iter().map(Into::into).collect();
+                    feed.publish(dashboard::DashboardEvent::Circuits { circuits });
+                }
+                let Some(ref ledger) = ledger else { continue };
+                for entity_id in &budget_entities {
+                    match ledger.get_budget(entity_id).await {
+                        Ok(Some(budget)) => feed.publish(dashboard::DashboardEvent::Budget { budget }),
+                        Ok(None) => {}
+                        Err(e) => tracing::warn!(entity = %entity_id, error = %e, "Failed to read budget for the dashboard"),
+                    }
+                }
+            }
+        });
+    }
+
+    /// Stream the dashboard to `zed42 top` and other viewers until shutdown
+    ///
+    /// Returns the bound address (useful with port 0).
+    pub async fn serve_dashboard(&self, addr: &str) -> anyhow::Result<std::net::SocketAddr> {
+        let listener = tokio::net::TcpListener::bind(addr)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to bind dashboard stream to {}: {}", addr, e))?;
+        let local = listener.local_addr()?;
+        let feed = self.dashboard.clone();
+        self.shutdown.spawn("dashboard-stream", move |token| feed.serve(listener, token));
+        tracing::info!(addr = %local, "Serving dashboard stream");
+        Ok(local)
+    }
+
+    /// Serve the event bus to WebSocket viewers until shutdown
+    ///
+    /// Returns the bound address (useful with port 0).
+    pub async fn serve_dashboard_websocket(&self, addr: &str) -> anyhow::Result<std::net::SocketAddr> {
+        let listener = tokio::net::TcpListener::bind(addr)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to bind dashboard WebSocket to {}: {}", addr, e))?;
+        let local = listener.local_addr()?;
+        let bus = self.dashboard.clone();
+        self.shutdown.spawn("dashboard-websocket", move |token| bus.serve_websocket(listener, token));
+        tracing::info!(addr = %local, "Serving dashboard WebSocket");
+        Ok(local)
+    }
+
+    /// Broadcast worker crashes as system alerts until shutdown
+    fn forward_worker_crashes(&self, pool: &workers::WorkerPool, blackboard: Arc<BlackboardDb>) {
+        let mut events = pool.subscribe();
+        self.shutdown.spawn("worker-events", move |token| async move {
+            loop {
+                let event = tokio::select! {
+                    _ = token.cancelled() => break,
+                    event = events.recv() => event,
+                };
+                let reason = match event {
+                    Ok(workers::WorkerEvent::Exited { agent_id, success: false, code, signal }) => {
+                        format!("worker {} exited (code {:?}, signal {:?})", agent_id, code, signal)
+                    }
+                    Ok(workers::WorkerEvent::GaveUp { agent_id, restarts }) => {
+                        format!("worker {} abandoned after {} restarts", agent_id, restarts)
+                    }
+                    Ok(_) | Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                };
+                blackboard.broadcast_alert("worker_crashed", &reason).await;
+            }
+        });
+    }
+
+    /// Stop background tasks, shut agents down and checkpoint memory
+    ///
+    /// Tasks registered with the shutdown controller get `grace` to exit
+    /// (agent loops drain their mailboxes) before being aborted; lease and
+    /// checkpoint hooks run afterwards.
+    pub async fn shutdown(&mut self, grace: std::time::Duration) -> zed42_core::ShutdownReport {
+        let mut report = self.shutdown.shutdown(grace).await;
+
+        if let Some(pool) = &self.workers {
+            let stopped = pool.stop_all().await;
+            report.hooks_run.push(format!("stop-workers ({})", stopped));
+        }
+
+        for (agent_id, mut agent) in self.active_agents.drain() {
+            if let Err(e) = agent.shutdown().await {
+                tracing::warn!(agent = %agent_id, error = %e, "Agent failed to shut down cleanly");
+                report.hook_failures.push((format!("agent {}", agent_id), e.to_string()));
+            }
+        }
+        self.agent_types.clear();
+
+        match self.memory.checkpoint() {
+            Ok(()) => report.hooks_run.push("memory-checkpoint".to_string()),
+            Err(e) => report.hook_failures.push(("memory-checkpoint".to_string(), e.to_string())),
+        }
+        report
+    }
+
+    /// Shut down after Ctrl-C and save what is needed to resume the session
+    ///
+    /// Runs [`Cortex::shutdown`] (agents stop, leases are released, memory is
+    /// checkpointed), hands back the queue claims of this Cortex's agents and
+    /// writes an [`interrupt::InterruptSnapshot`] under the repo root (the
+    /// current directory without one).
+    pub async fn checkpoint_on_interrupt(&mut self, grace: std::time::Duration) -> anyhow::Result<interrupt::InterruptReport> {
+        let agents: Vec<AgentId> = self.agent_types.keys().copied().collect();
+        let shutdown = self.shutdown(grace).await;
+
+        let mut released = Vec::new();
+        let mut queue = Vec::new();
+        if let Some(blackboard) = &self.blackboard {
+            for agent_id in agents {
+                match blackboard.release_agent_tasks(agent_id).await {
+                    Ok(tasks) => released.extend(tasks.into_iter().map(|task| task.task_id)),
+                    Err(e) => tracing::warn!(agent = %agent_id, error = %e, "Failed to release queued tasks"),
+                }
+            }
+            queue = blackboard.list_queue(Some(self.session_id)).await?;
+        }
+
+        let snapshot = interrupt::InterruptSnapshot {
+            session_id: self.session_id,
+            interrupted_at: chrono::Utc::now(),
+            plan: self.plan.clone(),
+            queue,
+            released,
+            clean: shutdown.is_clean(),
+        };
+        let root = match &self.repo_root {
+            Some(root) => root.clone(),
+            None => std::env::current_dir()?,
+        };
+        let path = snapshot.save(&root)?;
+        tracing::info!(session = %self.session_id, path = %path.display(), "Saved interrupted session");
+        Ok(interrupt::InterruptReport { shutdown, snapshot, path })
+    }
+
+    /// Continue an interrupted session: restore its plan and re-queue its tasks
+    ///
+    /// Call on a fresh Cortex after [`Cortex::initialize`]. Tasks the
+    /// blackboard still knows keep their status and attempts. Agents are
+    /// not spawned here; unfinished tasks are open to whichever agents the
+    /// caller starts.
+    pub async fn resume(&mut self, snapshot: interrupt::InterruptSnapshot) -> anyhow::Result<()> {
+        self.session_id = snapshot.session_id;
+        if let Some(blackboard) = &self.blackboard {
+            for mut task in snapshot.queue {
+                // The agents the plan named are gone; any agent may pick the task up
+                if task.status != zed42_blackboard::QueueStatus::Done {
+                    task.planned_agent = None;
+                }
+                if task.status == zed42_blackboard::QueueStatus::Assigned {
+                    task.status = zed42_blackboard::QueueStatus::Pending;
+                }
+                blackboard.enqueue_task(task).await?;
+            }
+        }
+        self.plan = snapshot.plan;
         Ok(())
     }
 
+    /// Start agents for the unfinished tasks of a resumed session's plan
+    ///
+    /// Tasks go to a running agent of the planned type when there is one,
+    /// otherwise an agent is requested, as in [`Cortex::process_intent`].
+    /// Returns how many tasks were handed out.
+    pub async fn restart_agents(&mut self) -> anyhow::Result<usize> {
+        let Some(mut plan) = self.plan.take() else {
+            return Ok(0);
+        };
+        let unfinished: Vec<String> = match &self.blackboard {
+            Some(blackboard) => blackboard
+                .list_queue(Some(self.session_id))
+                .await?
+                .into_iter()
+                .filter(|task| !matches!(task.status, zed42_blackboard::QueueStatus::Done | zed42_blackboard::QueueStatus::Failed))
+                .map(|task| task.task_id)
+                .collect(),
+            None => plan
+                .graph
+                .nodes
+                .iter()
+                .filter(|node| !plan.completed.contains(&node.task_id))
+                .map(|node| node.task_id.clone())
+                .collect(),
+        };
+        let assigned = self.assign_tasks(&mut plan, &unfinished).await;
+        self.plan = Some(plan);
+        assigned?;
+        Ok(unfinished.len())
+    }
+
+    /// Wait until no task of the session is pending or claimed
+    ///
+    /// Polls the blackboard's task queue every `poll` and returns it once
+    /// every task is done or failed, announcing a `RunComplete` event;
+    /// returns at once without a blackboard.
+    /// CLI runs race this against [`interrupt::signal`].
+    pub async fn wait_for_session(&self, poll: std::time::Duration) -> anyhow::Result<Vec<zed42_blackboard::QueuedTask>> {
+        let Some(blackboard) = &self.blackboard else {
+            return Ok(Vec::new());
+        };
+        loop {
+            let queue = blackboard.list_queue(Some(self.session_id)).await?;
+            if !queue
+                .iter()
+                .any(|task| matches!(task.status, zed42_blackboard::QueueStatus::Pending | zed42_blackboard::QueueStatus::Assigned))
+            {
+                let failed = queue.iter().filter(|task| task.status == zed42_blackboard::QueueStatus::Failed).count();
+                self.dashboard.publish(dashboard::DashboardEvent::RunComplete {
+                    run_id: self.session_id.to_string(),
+                    summary: format!("{} task(s) done, {} failed", queue.len() - failed, failed),
+                    success: failed == 0,
+                });
+                return Ok(queue);
+            }
+            tokio::time::sleep(poll).await;
+        }
+    }
+
     /// Spawn a new agent
+    ///
+    /// Remote workers granted the agent type take it first; otherwise in
+    /// worker mode the agent runs in its own supervised process. With a
+    /// Titan substrate attached, local spawns must pass placement: this
+    /// fails with a SpaceSentry error when they don't fit right now (use
+    /// [`Cortex::request_agent`] to queue instead).
     pub async fn spawn_agent(&mut self, agent_type: AgentType) -> anyhow::Result<AgentId> {
         let agent_id = Uuid::new_v4();
-        
+        match self.place_and_spawn(agent_id, agent_type.clone()).await? {
+            None => Ok(agent_id),
+            Some(reason) => anyhow::bail!("SpaceSentry: spawn of {:?} deferred: {}", agent_type, reason),
+        }
+    }
+
+    /// Spawn an agent, or queue it if resources are short right now
+    ///
+    /// Deferred spawns are announced as `placement_deferred` alerts and
+    /// started in order by [`Cortex::retry_deferred`]. Agents this machine
+    /// can never run still fail.
+    pub async fn request_agent(&mut self, agent_type: AgentType) -> anyhow::Result<SpawnOutcome> {
+        let agent_id = Uuid::new_v4();
+        let Some(reason) = self.place_and_spawn(agent_id, agent_type.clone()).await? else {
+            return Ok(SpawnOutcome::Spawned(agent_id));
+        };
+        let deferred = DeferredSpawn {
+            ticket: agent_id,
+            agent_type,
+            reason,
+            queued_at: chrono::Utc::now(),
+        };
+        let message = format!(
+            "{:?} queued as {} ({} waiting): {}",
+            deferred.agent_type,
+            agent_id,
+            self.deferred.len() + 1,
+            deferred.reason
+        );
+        self.alert("placement_deferred", &message).await;
+        self.deferred.push_back(deferred.clone());
+        Ok(SpawnOutcome::Deferred(deferred))
+    }
+
+    /// Start queued spawns in order until one still doesn't fit
+    pub async fn retry_deferred(&mut self) -> anyhow::Result<Vec<AgentId>> {
+        let mut spawned = Vec::new();
+        while let Some(next) = self.deferred.front().cloned() {
+            match self.place_and_spawn(next.ticket, next.agent_type.clone()).await {
+                Ok(None) => {
+                    self.deferred.pop_front();
+                    spawned.push(next.ticket);
+                }
+                Ok(Some(reason)) => {
+                    if let Some(front) = self.deferred.front_mut() {
+                        front.reason = reason;
+                    }
+                    break;
+                }
+                Err(e) => {
+                    self.deferred.pop_front();
+                    return Err(e);
+                }
+            }
+        }
+        Ok(spawned)
+    }
+
+    /// Place and start an agent, returning the reason if placement defers
+    async fn place_and_spawn(&mut self, agent_id: AgentId, agent_type: AgentType) -> anyhow::Result<Option<String>> {
+        if let Some(registry) = &self.remote {
+            if registry.assign(agent_id, agent_type.clone()).await?.is_some() {
+                self.remote_agents.insert(agent_id);
+                self.agent_types.insert(agent_id, agent_type);
+                self.publish_agent(agent_id, AgentStatus::Idle, None);
+                return Ok(None);
+            }
+        }
+
+        if let Some(titan) = self.titan.clone() {
+            let mut requirements = agent_type.resource_requirements();
+            if self.local_models {
+                requirements = requirements.with_gpu();
+            }
+            match titan.place(agent_id, requirements) {
+                zed42_core::titan::placement::PlacementDecision::Place => {}
+                zed42_core::titan::placement::PlacementDecision::Defer { reason } => return Ok(Some(reason)),
+                zed42_core::titan::placement::PlacementDecision::Reject { reason } => {
+                    let message = format!("{:?} cannot run on this machine: {}", agent_type, reason);
+                    self.alert("placement_rejected", &message).await;
+                    anyhow::bail!("SpaceSentry: {}", message);
+                }
+            }
+        }
+
+        if let Err(e) = self.spawn_local(agent_id, agent_type.clone()) {
+            if let Some(titan) = &self.titan {
+                titan.placement.release(agent_id);
+            }
+            return Err(e);
+        }
+        self.agent_types.insert(agent_id, agent_type);
+        self.publish_agent(agent_id, AgentStatus::Idle, None);
+        self.sync_keep_alive();
+        Ok(None)
+    }
+
+    fn spawn_local(&mut self, agent_id: AgentId, agent_type: AgentType) -> anyhow::Result<()> {
+        if let Some(pool) = &self.workers {
+            return pool.spawn(agent_id, agent_type);
+        }
+
         // Mock implementation for test verification
         struct MockAgent { id: AgentId }
         #[async_trait::async_trait]
@@ -69,19 +1778,72 @@ impl Cortex {
         }
 
         self.active_agents.insert(agent_id, Box::new(MockAgent { id: agent_id }));
-        Ok(agent_id)
+        Ok(())
     }
 
+    /// SpaceSentry alert to every team (logged when no blackboard is attached)
+    async fn alert(&self, action: &str, reason: &str) {
+        tracing::warn!(action, reason, "SpaceSentry Alert");
+        if let Some(blackboard) = &self.blackboard {
+            blackboard.broadcast_alert(action, reason).await;
+        }
+    }
 
     /// Dissolve an agent
     pub async fn dissolve_agent(&mut self, agent_id: AgentId) -> anyhow::Result<()> {
-        self.active_agents.remove(&agent_id);
+        self.agent_types.remove(&agent_id);
+        self.history.forget(agent_id);
+        if let Some(blackboard) = &self.blackboard {
+            blackboard.release_agent_tasks(agent_id).await?;
+        }
+        self.dashboard.publish(dashboard::DashboardEvent::AgentRemoved { agent_id });
+        if let Some(titan) = &self.titan {
+            titan.placement.release(agent_id);
+        }
+        if self.remote_agents.remove(&agent_id) {
+            if let Some(registry) = &self.remote {
+                registry.unassign(agent_id).await?;
+            }
+            return Ok(());
+        }
+        let stopped_worker = match &self.workers {
+            Some(pool) => pool.stop(agent_id).await,
+            None => false,
+        };
+        if !stopped_worker {
+            self.active_agents.remove(&agent_id);
+        }
+        self.sync_keep_alive();
         Ok(())
     }
 
+    /// Show an agent on the dashboard; ignored for agents that are not placed
+    fn publish_agent(&self, agent_id: AgentId, status: AgentStatus, task: Option<String>) {
+        let Some(agent_type) = self.agent_types.get(&agent_id) else { return };
+        self.dashboard.publish(dashboard::DashboardEvent::AgentUpdated {
+            agent_id,
+            agent: dashboard::AgentView {
+                agent_type: planner::agent_type_name(agent_type),
+                status,
+                task,
+                updated_at: chrono::Utc::now(),
+            },
+        });
+    }
+
+    /// Tell the keep-alive how many agents run on this machine
+    fn sync_keep_alive(&self) {
+        if let Some(keep_alive) = &self.keep_alive {
+            let local = self.active_agents.len() + self.workers.as_ref().map_or(0, |pool| pool.len());
+            keep_alive.set_active_agents(local);
+        }
+    }
+
     /// Get active agent count
     pub fn active_agent_count(&self) -> usize {
         self.active_agents.len()
+            + self.workers.as_ref().map_or(0, |pool| pool.len())
+            + self.remote_agents.len()
     }
 }
 
@@ -104,4 +1866,302 @@ mod tests {
         cortex.dissolve_agent(agent_id).await.unwrap();
         assert_eq!(cortex.active_agent_count(), 0);
     }
+
+    #[tokio::test]
+    async fn test_follow_up_amends_plan_and_reuses_agents() {
+        let plan = serde_json::json!({"tasks": [
+            {"id": "t1", "description": "add /orders endpoint", "agent_type": "FeatureImplementer", "depends_on": []},
+            {"id": "t2", "description": "test /orders", "agent_type": "TestEngineer", "depends_on": ["t1"]}
+        ]});
+        let delta = serde_json::json!({
+            "amend": [{"id": "t1", "description": "add /orders endpoint with metrics"}],
+            "add": [
+                {"id": "t3", "description": "export request metrics", "agent_type": "FeatureImplementer", "depends_on": ["t1"]},
+                {"id": "t4", "description": "review metric labels", "agent_type": "SecurityReviewer", "depends_on": ["t3"]}
+            ]
+        });
+        let parsed = serde_json::json!({"summary": "orders endpoint", "confidence": 0.9});
+        let planner = zed42_llm::MockLlmClient::with_responses(vec![
+            parsed.to_string(),
+            plan.to_string(),
+            delta.to_string(),
+        ]);
+        let mut cortex = Cortex::new(SessionId::new_v4()).with_planner(Arc::new(planner));
+
+        assert!(cortex.follow_up("also add metrics").await.is_err());
+        cortex.process_intent("add an orders endpoint").await.unwrap();
+        assert_eq!(cortex.active_agent_count(), 2);
+        let implementer = cortex.plan().unwrap().task_agents["t1"];
+
+        let revision = cortex.follow_up("also add metrics to that endpoint").await.unwrap();
+        assert_eq!(revision.revision, 1);
+        assert_eq!(revision.applied.amended, vec!["t1".to_string()]);
+        assert_eq!(revision.reused.get("t3"), Some(&implementer));
+        assert!(revision.spawned.contains_key("t4"));
+        assert_eq!(cortex.active_agent_count(), 3);
+
+        let plan = cortex.plan().unwrap();
+        assert_eq!(plan.graph.node("t1").unwrap().description, "add /orders endpoint with metrics");
+        assert_eq!(plan.graph.waves.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_unclear_intent_waits_for_answers() {
+        let parsed = serde_json::json!({
+            "summary": "export data",
+            "confidence": 0.3,
+            "ambiguities": ["format"],
+            "questions": [{"id": "q1", "question": "Which format?", "options": ["CSV", "JSON"], "best_guess": "CSV"}]
+        });
+        let plan = serde_json::json!({"tasks": [
+            {"id": "t1", "description": "export as JSON", "agent_type": "FeatureImplementer", "depends_on": []}
+        ]});
+        let planner = zed42_llm::MockLlmClient::with_responses(vec![parsed.to_string(), plan.to_string()]);
+        let mut cortex = Cortex::new(SessionId::new_v4()).with_planner(Arc::new(planner));
+
+        let desk = cortex.clarifications();
+        let answering = tokio::spawn(async move {
+            loop {
+                if let Some((id, _)) = desk.pending().pop() {
+                    desk.answer(&id, HashMap::from([("q1".to_string(), "JSON".to_string())])).unwrap();
+                    break;
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+            }
+        });
+        cortex.process_intent("export the data").await.unwrap();
+        answering.await.unwrap();
+
+        let intent = &cortex.plan().unwrap().graph.intent;
+        assert!(intent.ends_with("Clarifications:\n- Which format? JSON"), "{}", intent);
+    }
+
+    #[tokio::test]
+    async fn test_dangerous_tools_gate_waits_for_approval() {
+        let cortex = Cortex::new(SessionId::new_v4());
+        assert!(cortex.approver_for(cortex.presets().get("fast-and-cheap")).is_none());
+        let approver = cortex.approver_for(cortex.presets().get("cautious")).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("stale.rs"), "fn old() {}\n").unwrap();
+        let mut executor = zed42_toolboxes::ToolExecutor::new().with_approver(approver);
+        executor.register(Arc::new(zed42_toolboxes::file_manipulation::DeleteFile::new(dir.path())));
+
+        let desk = cortex.approvals();
+        let mut requests = desk.subscribe();
+        let answering = tokio::spawn(async move {
+            let request = requests.recv().await.unwrap();
+            assert!(request.preview.unwrap().contains("-fn old() {}"));
+            desk.resolve(&request.id, approvals::ApprovalDecision::Allow, "alice").await.unwrap();
+        });
+        let call = zed42_toolboxes::ToolCall::new("delete_file", serde_json::json!({ "path": "stale.rs" }));
+        executor.execute(call).await.unwrap();
+        answering.await.unwrap();
+
+        assert!(!dir.path().join("stale.rs").exists());
+        let history = cortex.approvals().history();
+        assert_eq!(history[0].actor, "alice");
+        assert!(cortex.dashboard().snapshot().alerts.iter().any(|a| a.action == "approval_required"));
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_stops_agents() {
+        let session_id = SessionId::new_v4();
+        let mut cortex = Cortex::new(session_id);
+        cortex.spawn_agent(AgentType::FeatureImplementer).await.unwrap();
+        cortex
+            .shutdown_controller()
+            .spawn("watcher", |token| async move { token.cancelled().await });
+
+        let report = cortex.shutdown(std::time::Duration::from_millis(100)).await;
+        assert!(report.is_clean(), "{:?}", report);
+        assert_eq!(report.tasks_stopped, vec!["watcher".to_string()]);
+        assert!(report.hooks_run.contains(&"memory-checkpoint".to_string()));
+        assert_eq!(cortex.active_agent_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_interrupt_checkpoints_and_snapshots_session() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let session_id = SessionId::new_v4();
+        let mut cortex = Cortex::new(session_id).with_repo_root(temp.path());
+        cortex.spawn_agent(AgentType::FeatureImplementer).await.unwrap();
+        cortex.shutdown_controller().on_shutdown(zed42_core::ShutdownStage::ReleaseLeases, "release-leases", || async { Ok(()) });
+
+        let report = cortex.checkpoint_on_interrupt(std::time::Duration::from_millis(100)).await.unwrap();
+        assert!(report.snapshot.clean);
+        assert!(report.shutdown.hooks_run.contains(&"release-leases".to_string()));
+        assert!(report.shutdown.hooks_run.contains(&"memory-checkpoint".to_string()));
+        assert_eq!(cortex.active_agent_count(), 0);
+        assert_eq!(report.path, interrupt::InterruptSnapshot::path(temp.path(), session_id));
+        assert_eq!(report.resume_command(), format!("zed42 resume {}", session_id));
+
+        let snapshot = interrupt::InterruptSnapshot::load(temp.path(), session_id).unwrap();
+        let mut resumed = Cortex::new(SessionId::new_v4());
+        resumed.resume(snapshot).await.unwrap();
+        assert_eq!(resumed.session_id, session_id);
+    }
+
+    /// Records the agent id of every request before answering from a script
+    struct Recording {
+        inner: zed42_llm::MockLlmClient,
+        agents: parking_lot::Mutex<Vec<Option<String>>>,
+    }
+
+    #[async_trait::async_trait]
+    impl zed42_llm::LlmClient for Recording {
+        async fn complete(&self, request: zed42_llm::LlmRequest) -> zed42_llm::Result<zed42_llm::LlmResponse> {
+            self.agents.lock().push(request.agent_id.clone());
+            self.inner.complete(request).await
+        }
+
+        async fn stream(&self, request: zed42_llm::LlmRequest) -> zed42_llm::Result<Vec<zed42_llm::StreamChunk>> {
+            self.inner.stream(request).await
+        }
+
+        async fn embed(&self, request: zed42_llm::EmbeddingRequest) -> zed42_llm::Result<zed42_llm::EmbeddingResponse> {
+            self.inner.embed(request).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_due_intents_bill_their_envelope_and_fail_independently() {
+        let plan = serde_json::json!({"tasks": [
+            {"id": "t1", "description": "audit dependencies", "agent_type": "FeatureImplementer", "depends_on": []}
+        ]});
+        let parsed = serde_json::json!({"summary": "dependency audit", "confidence": 0.9});
+        // Enough answers for one run; the second run's planning fails
+        let planner = Arc::new(Recording {
+            inner: zed42_llm::MockLlmClient::with_responses(vec![parsed.to_string(), plan.to_string()]),
+            agents: parking_lot::Mutex::new(Vec::new()),
+        });
+        let mut cortex = Cortex::new(SessionId::new_v4()).with_planner(planner.clone());
+        let now = chrono::Utc::now();
+        for spec in ["hourly: audit dependencies", "daily: triage issues"] {
+            cortex.scheduler_mut().add(spec, rust_decimal::Decimal::new(2, 0)).unwrap();
+        }
+        for schedule in cortex.scheduler_mut().schedules_mut() {
+            schedule.next_run = Some(now - chrono::Duration::minutes(1));
+        }
+
+        let db = surrealdb::engine::any::connect("mem://").await.unwrap();
+        db.use_ns("zed42").use_db("ledger").await.unwrap();
+        let ledger = IntelligenceLedger::new(db);
+        let outcomes = cortex.run_due_intents(Some(&ledger)).await.unwrap();
+
+        assert_eq!(outcomes.len(), 2);
+        assert!(outcomes[0].error.is_none());
+        assert!(outcomes[1].error.is_some());
+        for outcome in &outcomes {
+            assert!(ledger.get_budget(&outcome.run.budget_entity).await.unwrap().is_some());
+        }
+        let first = Some(outcomes[0].run.budget_entity.clone());
+        assert_eq!(planner.agents.lock()[..2], [first.clone(), first]);
+        assert_eq!(planner.agents.lock()[2], Some(outcomes[1].run.budget_entity.clone()));
+        assert!(cortex.run_due_intents(Some(&ledger)).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_resumed_session_restarts_agents_for_unfinished_tasks() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let plan = serde_json::json!({"tasks": [
+            {"id": "t1", "description": "add /orders endpoint", "agent_type": "FeatureImplementer", "depends_on": []},
+            {"id": "t2", "description": "test /orders", "agent_type": "TestEngineer", "depends_on": ["t1"]}
+        ]});
+        let parsed = serde_json::json!({"summary": "orders endpoint", "confidence": 0.9});
+        let planner = zed42_llm::MockLlmClient::with_responses(vec![parsed.to_string(), plan.to_string()]);
+        let session_id = SessionId::new_v4();
+        let mut cortex = Cortex::new(session_id).with_repo_root(temp.path()).with_planner(Arc::new(planner));
+        cortex.process_intent("add an orders endpoint").await.unwrap();
+        cortex.complete_task("t1");
+        cortex.checkpoint_on_interrupt(std::time::Duration::from_millis(100)).await.unwrap();
+
+        let snapshot = interrupt::InterruptSnapshot::load(temp.path(), session_id).unwrap();
+        let mut resumed = Cortex::new(SessionId::new_v4());
+        resumed.resume(snapshot).await.unwrap();
+        assert_eq!(resumed.session_id(), session_id);
+        assert_eq!(resumed.restart_agents().await.unwrap(), 1);
+        assert_eq!(resumed.active_agent_count(), 1);
+        assert!(resumed.wait_for_session(std::time::Duration::from_millis(10)).await.unwrap().is_empty());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_worker_mode_runs_agents_out_of_process() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let config = workers::WorkerConfig::new("/bin/sh")
+            .with_args(["-c", "sleep 30"])
+            .with_data_dir(temp.path())
+            .with_stop_grace(std::time::Duration::from_millis(200));
+        let mut cortex = Cortex::new(SessionId::new_v4()).with_workers(config);
+
+        let agent_id = cortex.spawn_agent(AgentType::TestEngineer).await.unwrap();
+        assert_eq!(cortex.active_agent_count(), 1);
+        assert!(cortex.workers().unwrap().status(agent_id).unwrap().pid.is_some());
+        cortex.spawn_agent(AgentType::FeatureImplementer).await.unwrap();
+
+        cortex.dissolve_agent(agent_id).await.unwrap();
+        assert_eq!(cortex.active_agent_count(), 1);
+
+        let report = cortex.shutdown(std::time::Duration::from_millis(100)).await;
+        assert!(report.hooks_run.contains(&"stop-workers (1)".to_string()), "{:?}", report);
+        assert_eq!(cortex.active_agent_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_placement_defers_until_resources_free() {
+        let titan = zed42_core::titan::TitanSubstrate::new().with_placement_policy(
+            zed42_core::titan::placement::PlacementPolicy {
+                gpu_slots: 1,
+                memory_headroom_bytes: 0,
+                max_cpu_usage: 100.0,
+                ..Default::default()
+            },
+        );
+        let mut cortex = Cortex::new(SessionId::new_v4())
+            .with_titan(Arc::new(titan))
+            .with_local_models();
+
+        let first = cortex.spawn_agent(AgentType::DocumentationWriter).await.unwrap();
+        let err = cortex.spawn_agent(AgentType::DocumentationWriter).await.unwrap_err();
+        assert!(err.to_string().contains("GPU slots in use"), "{}", err);
+
+        let SpawnOutcome::Deferred(deferred) = cortex.request_agent(AgentType::DocumentationWriter).await.unwrap() else {
+            panic!("Expected the spawn to be deferred");
+        };
+        assert_eq!(cortex.deferred_spawns().count(), 1);
+        assert!(cortex.retry_deferred().await.unwrap().is_empty());
+
+        cortex.dissolve_agent(first).await.unwrap();
+        assert_eq!(cortex.retry_deferred().await.unwrap(), vec![deferred.ticket]);
+        assert_eq!(cortex.active_agent_count(), 1);
+        assert_eq!(cortex.deferred_spawns().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_keep_alive_tracks_local_agents() {
+        let keep_alive = Arc::new(zed42_llm::KeepAlive::new(zed42_llm::KeepAlivePolicy::default()));
+        let mut cortex = Cortex::new(SessionId::new_v4()).with_keep_alive(keep_alive.clone());
+
+        let first = cortex.spawn_agent(AgentType::FeatureImplementer).await.unwrap();
+        cortex.spawn_agent(AgentType::TestEngineer).await.unwrap();
+        assert_eq!(keep_alive.active_agents(), 2);
+
+        cortex.dissolve_agent(first).await.unwrap();
+        assert_eq!(keep_alive.active_agents(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_placement_rejects_impossible_agents() {
+        let titan = zed42_core::titan::TitanSubstrate::new().with_placement_policy(
+            zed42_core::titan::placement::PlacementPolicy {
+                memory_headroom_bytes: u64::MAX,
+                ..Default::default()
+            },
+        );
+        let mut cortex = Cortex::new(SessionId::new_v4()).with_titan(Arc::new(titan));
+        let err = cortex.request_agent(AgentType::PenetrationTester).await.unwrap_err();
+        assert!(err.to_string().starts_with("SpaceSentry:"), "{}", err);
+        assert_eq!(cortex.active_agent_count(), 0);
+    }
 }
@@ -0,0 +1,167 @@
+//! Human notifications for dashboard events
+//!
+//! [`Cortex::start_notifications`](crate::Cortex::start_notifications)
+//! feeds the session's event bus into a [`Notifier`]: finished runs,
+//! budgets crossing their soft or hard limit, burn-down forecasts, approval
+//! requests, ghost agents and clarification requests. Budgets are polled
+//! onto the bus, so a threshold is notified once when it is crossed, not on
+//! every poll.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use zed42_core::CancellationToken;
+use zed42_notify::{Notifier, NotifyEvent};
+
+use crate::dashboard::{DashboardEvent, EventBus};
+
+/// Budget limit last crossed, lowest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum BudgetLevel {
+    Below,
+    Soft,
+    Hard,
+}
+
+/// Turns dashboard events into notification events
+#[derive(Debug, Default)]
+pub struct NotificationFeed {
+    budget_levels: HashMap<String, BudgetLevel>,
+}
+
+impl NotificationFeed {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The notification `event` calls for, if any
+    pub fn translate(&mut self, event: &DashboardEvent) -> Option<NotifyEvent> {
+        match event {
+            DashboardEvent::RunComplete { run_id, summary, success } => Some(NotifyEvent::RunComplete {
+                run_id: run_id.clone(),
+                summary: summary.clone(),
+                success: *success,
+            }),
+            DashboardEvent::Budget { budget } => {
+                let (level, limit) = if budget.spent >= budget.hard_limit {
+                    (BudgetLevel::Hard, budget.hard_limit)
+                } else if budget.spent >= budget.soft_limit {
+                    (BudgetLevel::Soft, budget.soft_limit)
+                } else {
+                    (BudgetLevel::Below, budget.soft_limit)
+                };
+                let previous = self.budget_levels.insert(budget.entity_id.clone(), level);
+                (level > previous.unwrap_or(BudgetLevel::Below)).then(|| NotifyEvent::BudgetThreshold {
+                    entity_id: budget.entity_id.clone(),
+                    spent: budget.spent.to_string(),
+                    limit: limit.to_string(),
+                })
+            }
+            DashboardEvent::BudgetForecast { warning: Some(warning), .. } => {
+                Some(NotifyEvent::BudgetForecast { message: warning.clone() })
+            }
+            DashboardEvent::ApprovalRequested { request } => Some(NotifyEvent::ApprovalNeeded {
+                agent_id: String::new(),
+                action: request.tool.clone(),
+                reason: request.reason.clone(),
+            }),
+            // Approval alerts duplicate `ApprovalRequested`
+            DashboardEvent::Alert { alert } => match alert.action.as_str() {
+                "dissolve_ghost" => Some(NotifyEvent::AgentGhost {
+                    agent_id: alert.agent_id.map(|id| id.to_string()).unwrap_or_default(),
+                    last_seen: None,
+                }),
+                "clarification_needed" => Some(NotifyEvent::ClarificationNeeded { questions: alert.reason.clone() }),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Deliver notifications for events published on `bus` until `shutdown`
+///
+/// Events already published when shutdown starts, like the `RunComplete`
+/// of the run that ended, are still delivered.
+pub async fn forward(bus: Arc<EventBus>, notifier: Arc<Notifier>, shutdown: CancellationToken) {
+    let (_, mut events) = bus.subscribe();
+    let mut feed = NotificationFeed::new();
+    loop {
+        let envelope = tokio::select! {
+            _ = shutdown.cancelled() => {
+                while let Ok(envelope) = events.try_recv() {
+                    if let Some(event) = feed.translate(&envelope.event) {
+                        notifier.notify(&event).await;
+                    }
+                }
+                break;
+            }
+            event = events.recv() => match event {
+                Ok(envelope) => envelope,
+                Err(broadcast::error::RecvError::Lagged(missed)) => {
+                    tracing::warn!(missed, "Notifications fell behind the event bus");
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+        };
+        if let Some(event) = feed.translate(&envelope.event) {
+            notifier.notify(&event).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dashboard::AlertView;
+    use rust_decimal::Decimal;
+    use zed42_ledger::types::{Budget, BudgetStatus};
+
+    fn budget(spent: i64) -> DashboardEvent {
+        DashboardEvent::Budget {
+            budget: Budget {
+                entity_id: "session".to_string(),
+                hard_limit: Decimal::new(10, 0),
+                soft_limit: Decimal::new(8, 0),
+                spent: Decimal::new(spent, 0),
+                currency: "USD".to_string(),
+                status: BudgetStatus::Active,
+                updated_at: chrono::Utc::now(),
+                tags: Default::default(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_thresholds_notify_once_per_crossing() {
+        let mut feed = NotificationFeed::new();
+        assert_eq!(feed.translate(&budget(2)), None);
+        assert!(matches!(
+            feed.translate(&budget(8)),
+            Some(NotifyEvent::BudgetThreshold { ref limit, .. }) if limit == "8"
+        ));
+        assert_eq!(feed.translate(&budget(9)), None);
+        assert!(matches!(
+            feed.translate(&budget(10)),
+            Some(NotifyEvent::BudgetThreshold { ref limit, .. }) if limit == "10"
+        ));
+        assert_eq!(feed.translate(&budget(10)), None);
+
+        // Raised limits rearm the thresholds
+        assert_eq!(feed.translate(&budget(1)), None);
+        assert!(feed.translate(&budget(8)).is_some());
+
+        let run = DashboardEvent::RunComplete { run_id: "r1".to_string(), summary: "3 done".to_string(), success: true };
+        assert!(matches!(feed.translate(&run), Some(NotifyEvent::RunComplete { success: true, .. })));
+        let approval = DashboardEvent::Alert {
+            alert: AlertView {
+                action: "approval_required".to_string(),
+                agent_id: None,
+                reason: "Runs `git push`".to_string(),
+                at: chrono::Utc::now(),
+            },
+        };
+        assert_eq!(feed.translate(&approval), None);
+    }
+}
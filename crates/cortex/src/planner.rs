@@ -1 +1,519 @@
 //! Task planning
+//!
+//! One planning call turns an intent into a task DAG: each task names the
+//! agent type that should handle it and the tasks it waits for. The reply is
+//! validated (known agent types, no dangling or cyclic dependencies) into a
+//! [`TaskGraph`], which groups tasks into waves that can run in parallel.
+//!
+//! A dry run stops there: [`DryRunPlan`] adds cost estimates and the agents
+//! that would be spawned, without spawning anything or routing further calls.
+//!
+//! Follow-up intents ("also add metrics to that endpoint") are planned as a
+//! [`PlanDelta`] against the current graph: amended descriptions or agent
+//! types for existing tasks plus new tasks, which may depend on old ones.
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use zed42_agents::AgentType;
+use zed42_core::titan::placement::ResourceRequirements;
+use zed42_core::types::{Artifact, ArtifactType, TaskId};
+use zed42_core::Team;
+use zed42_llm::{ConstrainedGen, LlmClient};
+
+use crate::estimate::{PlanReview, PlannedTask};
+
+const PLANNER_SYSTEM_PROMPT: &str = "You are the planner of a multi-agent software team. \
+Split the intent into small tasks, assign each to exactly one agent type, and list the ids \
+of the tasks it depends on. Only use these agent types: PenetrationTester, ChaosEngineer, \
+PerformanceAnalyst, EdgeCaseMiner, TechnicalDebtor, FeatureImplementer, Refactorer, \
+TestEngineer, DocumentationWriter, MigrationSpecialist, Architect, StandardsEnforcer, \
+SecurityReviewer.";
+
+/// One task as proposed by the planning model
+#[derive(Debug, Clone, JsonSchema, Serialize, Deserialize)]
+pub struct PlanStep {
+    /// Short unique id, e.g. "t1"
+    pub id: String,
+    pub description: String,
+    /// Agent type name, e.g. "FeatureImplementer"
+    pub agent_type: String,
+    /// Ids of tasks that must finish first
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+}
+
+/// LLM response schema for intent decomposition
+#[derive(Debug, Clone, JsonSchema, Serialize, Deserialize)]
+pub struct PlanResponse {
+    pub tasks: Vec<PlanStep>,
+}
+
+/// A validated task in the DAG
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskNode {
+    pub task_id: TaskId,
+    pub description: String,
+    pub agent_type: AgentType,
+    pub depends_on: Vec<TaskId>,
+}
+
+/// Tasks of one intent and their dependencies
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskGraph {
+    pub intent: String,
+    pub nodes: Vec<TaskNode>,
+    /// Task ids grouped by dependency depth; each wave only depends on earlier ones
+    pub waves: Vec<Vec<TaskId>>,
+}
+
+impl TaskGraph {
+    /// Validate a planner response into a graph
+    pub fn from_response(intent: &str, response: PlanResponse) -> Result<Self> {
+        if response.tasks.is_empty() {
+            bail!("Planner returned no tasks for intent");
+        }
+
+        let mut seen = HashSet::new();
+        for step in &response.tasks {
+            if !seen.insert(step.id.as_str()) {
+                bail!("Duplicate task id {}", step.id);
+            }
+        }
+
+        let mut nodes = Vec::with_capacity(response.tasks.len());
+        for step in response.tasks {
+            let agent_type = parse_agent_type(&step.id, &step.agent_type)?;
+            if let Some(missing) = step.depends_on.iter().find(|d| !seen.contains(d.as_str())) {
+                bail!("Task {} depends on unknown task {}", step.id, missing);
+            }
+            nodes.push(TaskNode {
+                task_id: step.id,
+                description: step.description,
+                agent_type,
+                depends_on: step.depends_on,
+            });
+        }
+
+        let waves = layer(&nodes)?;
+        Ok(Self {
+            intent: intent.to_string(),
+            nodes,
+            waves,
+        })
+    }
+
+    pub fn node(&self, task_id: &str) -> Option<&TaskNode> {
+        self.nodes.iter().find(|n| n.task_id == task_id)
+    }
+
+    /// Tasks in the form the run estimator takes, keyed by agent type
+    pub fn planned_tasks(&self) -> Vec<PlannedTask> {
+        self.nodes
+            .iter()
+            .map(|node| PlannedTask {
+                task_id: node.task_id.clone(),
+                description: node.description.clone(),
+                agent_id: agent_type_name(&node.agent_type),
+            })
+            .collect()
+    }
+}
+
+/// Kahn layering; fails on cycles
+fn layer(nodes: &[TaskNode]) -> Result<Vec<Vec<TaskId>>> {
+    let mut remaining: HashMap<&str, HashSet<&str>> = nodes
+        .iter()
+        .map(|n| (n.task_id.as_str(), n.depends_on.iter().map(String::as_str).collect()))
+        .collect();
+    let mut waves = Vec::new();
+
+    while !remaining.is_empty() {
+        // Keep plan order within a wave
+        let ready: Vec<&str> = nodes
+            .iter()
+            .map(|n| n.task_id.as_str())
+            .filter(|id| remaining.get(id).is_some_and(HashSet::is_empty))
+            .collect();
+        if ready.is_empty() {
+            let mut stuck: Vec<&str> = remaining.keys().copied().collect();
+            stuck.sort_unstable();
+            bail!("Task dependencies form a cycle among {}", stuck.join(", "));
+        }
+        for id in &ready {
+            remaining.remove(id);
+        }
+        for deps in remaining.values_mut() {
+            deps.retain(|d| !ready.contains(d));
+        }
+        waves.push(ready.into_iter().map(str::to_string).collect());
+    }
+    Ok(waves)
+}
+
+/// Change to an existing task requested by a follow-up
+#[derive(Debug, Clone, JsonSchema, Serialize, Deserialize)]
+pub struct TaskAmendment {
+    /// Id of the task being changed
+    pub id: String,
+    pub description: String,
+    /// New agent type name, when the change needs a different specialist
+    #[serde(default)]
+    pub agent_type: Option<String>,
+}
+
+/// LLM response schema for a follow-up intent
+#[derive(Debug, Clone, Default, JsonSchema, Serialize, Deserialize)]
+pub struct PlanDelta {
+    #[serde(default)]
+    pub amend: Vec<TaskAmendment>,
+    /// New tasks; ids must not clash with existing ones
+    #[serde(default)]
+    pub add: Vec<PlanStep>,
+}
+
+impl PlanDelta {
+    pub fn is_empty(&self) -> bool {
+        self.amend.is_empty() && self.add.is_empty()
+    }
+}
+
+/// Task ids touched by one applied delta
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AppliedDelta {
+    pub amended: Vec<TaskId>,
+    /// Amended tasks whose agent type changed
+    pub reassigned: Vec<TaskId>,
+    pub added: Vec<TaskId>,
+}
+
+impl TaskGraph {
+    /// Apply a follow-up delta; the graph is unchanged if it doesn't validate
+    pub fn apply_delta(&mut self, delta: PlanDelta) -> Result<AppliedDelta> {
+        let mut next = self.clone();
+        let mut applied = AppliedDelta::default();
+
+        for amendment in delta.amend {
+            let node = next
+                .nodes
+                .iter_mut()
+                .find(|n| n.task_id == amendment.id)
+                .with_context(|| format!("Follow-up amends unknown task {}", amendment.id))?;
+            node.description = amendment.description;
+            if let Some(name) = amendment.agent_type {
+                let agent_type = parse_agent_type(&amendment.id, &name)?;
+                if agent_type != node.agent_type {
+                    node.agent_type = agent_type;
+                    applied.reassigned.push(amendment.id.clone());
+                }
+            }
+            applied.amended.push(amendment.id);
+        }
+
+        let mut known: HashSet<String> = next.nodes.iter().map(|n| n.task_id.clone()).collect();
+        for step in &delta.add {
+            if !known.insert(step.id.clone()) {
+                bail!("Follow-up adds task {} which already exists", step.id);
+            }
+        }
+        for step in delta.add {
+            if let Some(missing) = step.depends_on.iter().find(|d| !known.contains(*d)) {
+                bail!("Task {} depends on unknown task {}", step.id, missing);
+            }
+            applied.added.push(step.id.clone());
+            next.nodes.push(TaskNode {
+                agent_type: parse_agent_type(&step.id, &step.agent_type)?,
+                task_id: step.id,
+                description: step.description,
+                depends_on: step.depends_on,
+            });
+        }
+
+        next.waves = layer(&next.nodes)?;
+        *self = next;
+        Ok(applied)
+    }
+}
+
+fn parse_agent_type(task_id: &str, name: &str) -> Result<AgentType> {
+    serde_json::from_value(serde_json::Value::String(name.to_string()))
+        .with_context(|| format!("Task {} names unknown agent type {}", task_id, name))
+}
+
+/// Name used for the agent type in routing logs and profiles
+pub fn agent_type_name(agent_type: &AgentType) -> String {
+    format!("{:?}", agent_type)
+}
+
+/// Decompose an intent with a single constrained planning call
+pub async fn plan_intent(client: &dyn LlmClient, intent: &str) -> Result<TaskGraph> {
+    let response: PlanResponse = ConstrainedGen::new(client)
+        .system(PLANNER_SYSTEM_PROMPT)
+        .prompt(format!("Intent:\n{}", intent))
+        .max_retries(1)
+        .generate()
+        .await?;
+    TaskGraph::from_response(intent, response)
+}
+
+/// Plan a follow-up intent as a delta against `graph`
+pub async fn plan_follow_up(client: &dyn LlmClient, graph: &TaskGraph, intent: &str) -> Result<PlanDelta> {
+    let current = serde_json::to_string_pretty(&graph.nodes)?;
+    let delta: PlanDelta = ConstrainedGen::new(client)
+        .system(format!(
+            "{} You are refining a plan that is already running: change existing tasks only \
+             when the request alters them, and add new tasks for new work.",
+            PLANNER_SYSTEM_PROMPT
+        ))
+        .prompt(format!(
+            "Original intent:\n{}\n\nCurrent tasks:\n{}\n\nFollow-up:\n{}",
+            graph.intent, current, intent
+        ))
+        .max_retries(1)
+        .generate()
+        .await?;
+    Ok(delta)
+}
+
+/// The plan a session is working through
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionPlan {
+    pub graph: TaskGraph,
+    /// 0 for the original plan, bumped by every follow-up
+    pub revision: u32,
+    /// Agent (or deferred spawn ticket) handling each task
+    pub task_agents: BTreeMap<TaskId, zed42_core::AgentId>,
+    /// Decision node of the latest revision
+    pub decision_id: Option<String>,
+    /// Tasks reported finished
+    #[serde(default)]
+    pub completed: std::collections::BTreeSet<TaskId>,
+}
+
+impl SessionPlan {
+    pub fn new(graph: TaskGraph) -> Self {
+        Self {
+            graph,
+            revision: 0,
+            task_agents: BTreeMap::new(),
+            decision_id: None,
+            completed: Default::default(),
+        }
+    }
+
+    /// Tasks of the plan not reported finished yet
+    pub fn remaining_tasks(&self) -> usize {
+        self.graph.nodes.iter().filter(|n| !self.completed.contains(&n.task_id)).count()
+    }
+}
+
+/// Result of [`Cortex::follow_up`](crate::Cortex::follow_up)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanRevision {
+    pub revision: u32,
+    pub intent: String,
+    pub applied: AppliedDelta,
+    /// Tasks handed to an agent that was already running
+    pub reused: BTreeMap<TaskId, zed42_core::AgentId>,
+    /// Tasks that got a newly requested agent
+    pub spawned: BTreeMap<TaskId, zed42_core::AgentId>,
+    pub decision_id: Option<String>,
+}
+
+/// The agent a task would be handed to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentAssignment {
+    pub task_id: TaskId,
+    pub agent_type: AgentType,
+    /// Agents of one type are reused across waves; this is which one (from 1)
+    pub agent_slot: usize,
+    pub team: Team,
+    pub toolboxes: Vec<String>,
+    pub requirements: ResourceRequirements,
+}
+
+/// Everything a dry run produces
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DryRunPlan {
+    pub generated_at: DateTime<Utc>,
+    pub graph: TaskGraph,
+    pub assignments: Vec<AgentAssignment>,
+    /// Agents that would be spawned, per agent type
+    pub agents_needed: BTreeMap<String, usize>,
+    pub review: PlanReview,
+}
+
+impl DryRunPlan {
+    pub fn new(graph: TaskGraph, review: PlanReview) -> Self {
+        let mut assignments = Vec::with_capacity(graph.nodes.len());
+        let mut agents_needed: BTreeMap<String, usize> = BTreeMap::new();
+
+        for wave in &graph.waves {
+            let mut in_wave: HashMap<String, usize> = HashMap::new();
+            for task_id in wave {
+                let Some(node) = graph.node(task_id) else { continue };
+                let name = agent_type_name(&node.agent_type);
+                let slot = in_wave.entry(name.clone()).or_default();
+                *slot += 1;
+                let needed = agents_needed.entry(name).or_default();
+                *needed = (*needed).max(*slot);
+
+                assignments.push(AgentAssignment {
+                    task_id: task_id.clone(),
+                    agent_type: node.agent_type.clone(),
+                    agent_slot: *slot,
+                    team: node.agent_type.team(),
+                    toolboxes: node.agent_type.default_toolbox(),
+                    requirements: node.agent_type.resource_requirements(),
+                });
+            }
+        }
+
+        Self {
+            generated_at: Utc::now(),
+            graph,
+            assignments,
+            agents_needed,
+            review,
+        }
+    }
+
+    /// The plan as a JSON artifact for inspection
+    pub fn to_artifact(&self) -> Result<Artifact> {
+        Ok(Artifact {
+            id: uuid::Uuid::new_v4().to_string(),
+            task_id: "dry-run".to_string(),
+            artifact_type: ArtifactType::Other,
+            content: serde_json::to_string_pretty(self)?,
+            file_path: None,
+            created_at: self.generated_at,
+            confidence: None,
+            strategy: None,
+        })
+    }
+}
+
+impl fmt::Display for DryRunPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Dry run: {}", self.graph.intent)?;
+        for (index, wave) in self.graph.waves.iter().enumerate() {
+            writeln!(f, "Wave {}:", index + 1)?;
+            for assignment in self.assignments.iter().filter(|a| wave.contains(&a.task_id)) {
+                let description = self.graph.node(&assignment.task_id).map_or("", |n| n.description.as_str());
+                writeln!(
+                    f,
+                    "  - [{}] {} -> {:?} #{}",
+                    assignment.task_id, description, assignment.agent_type, assignment.agent_slot
+                )?;
+            }
+        }
+        let agents: Vec<String> = self.agents_needed.iter().map(|(name, n)| format!("{} x{}", name, n)).collect();
+        writeln!(f, "Agents: {}", agents.join(", "))?;
+        write!(f, "{}", self.review)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zed42_llm::MockLlmClient;
+
+    fn step(id: &str, agent_type: &str, depends_on: &[&str]) -> PlanStep {
+        PlanStep {
+            id: id.to_string(),
+            description: format!("task {}", id),
+            agent_type: agent_type.to_string(),
+            depends_on: depends_on.iter().map(|d| d.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_graph_layers_and_rejects_cycles() {
+        let graph = TaskGraph::from_response(
+            "add pagination",
+            PlanResponse {
+                tasks: vec![
+                    step("t1", "Architect", &[]),
+                    step("t2", "FeatureImplementer", &["t1"]),
+                    step("t3", "FeatureImplementer", &["t1"]),
+                    step("t4", "TestEngineer", &["t2", "t3"]),
+                ],
+            },
+        )
+        .unwrap();
+        assert_eq!(graph.waves, vec![vec!["t1"], vec!["t2", "t3"], vec!["t4"]]);
+
+        let cyclic = PlanResponse {
+            tasks: vec![step("a", "Refactorer", &["b"]), step("b", "Refactorer", &["a"])],
+        };
+        assert!(TaskGraph::from_response("x", cyclic).unwrap_err().to_string().contains("cycle"));
+
+        let unknown = PlanResponse { tasks: vec![step("a", "Wizard", &[])] };
+        assert!(TaskGraph::from_response("x", unknown).is_err());
+    }
+
+    #[test]
+    fn test_delta_amends_and_extends_graph() {
+        let mut graph = TaskGraph::from_response(
+            "add an endpoint",
+            PlanResponse {
+                tasks: vec![step("t1", "FeatureImplementer", &[]), step("t2", "TestEngineer", &["t1"])],
+            },
+        )
+        .unwrap();
+
+        let applied = graph
+            .apply_delta(PlanDelta {
+                amend: vec![TaskAmendment {
+                    id: "t1".to_string(),
+                    description: "endpoint with metrics".to_string(),
+                    agent_type: Some("FeatureImplementer".to_string()),
+                }],
+                add: vec![step("t3", "PerformanceAnalyst", &["t1"])],
+            })
+            .unwrap();
+        assert_eq!(applied.amended, vec!["t1"]);
+        assert!(applied.reassigned.is_empty());
+        assert_eq!(applied.added, vec!["t3"]);
+        assert_eq!(graph.waves, vec![vec!["t1"], vec!["t2", "t3"]]);
+
+        // A bad delta leaves the graph as it was
+        let clash = PlanDelta { add: vec![step("t2", "Refactorer", &[])], ..Default::default() };
+        assert!(graph.apply_delta(clash).is_err());
+        assert_eq!(graph.nodes.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_assigns_agents_without_spawning() {
+        let reply = serde_json::json!({
+            "tasks": [
+                {"id": "t1", "description": "design", "agent_type": "Architect", "depends_on": []},
+                {"id": "t2", "description": "api", "agent_type": "FeatureImplementer", "depends_on": ["t1"]},
+                {"id": "t3", "description": "ui", "agent_type": "FeatureImplementer", "depends_on": ["t1"]},
+                {"id": "t4", "description": "glue", "agent_type": "FeatureImplementer", "depends_on": ["t2"]}
+            ]
+        });
+        let client = MockLlmClient::new(reply.to_string());
+        let graph = plan_intent(&client, "add pagination").await.unwrap();
+        let review = PlanReview {
+            estimate: crate::estimate::RunEstimator::default().estimate(&graph.planned_tasks()),
+            budget: None,
+            requires_approval: false,
+        };
+        let plan = DryRunPlan::new(graph, review);
+
+        assert_eq!(plan.agents_needed["FeatureImplementer"], 2);
+        assert_eq!(plan.agents_needed["Architect"], 1);
+        let glue = plan.assignments.iter().find(|a| a.task_id == "t4").unwrap();
+        assert_eq!(glue.agent_slot, 1);
+        assert_eq!(plan.review.estimate.tasks.len(), 4);
+
+        let artifact = plan.to_artifact().unwrap();
+        assert_eq!(artifact.artifact_type, ArtifactType::Other);
+        assert!(artifact.content.contains("\"agents_needed\""));
+        assert!(plan.to_string().contains("Wave 2:"));
+    }
+}
@@ -0,0 +1,223 @@
+//! Named run presets
+//!
+//! A preset bundles the knobs a user would otherwise repeat on every intent:
+//! team composition, budget, model ladder, toolbox policy and approval gates.
+//! Presets live in `config/presets.toml` and are selected by name when an
+//! intent is submitted (`--preset cautious`).
+
+use anyhow::{anyhow, Result};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use zed42_agents::AgentType;
+
+/// Points in a run where a human must sign off
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalGate {
+    /// Review the task plan before any agent starts
+    PlanReview,
+    /// Approve each artifact before it is written to the workspace
+    ArtifactApproval,
+    /// Approve shell commands and other dangerous tool calls
+    DangerousTools,
+    /// Approve before committing or pushing
+    Commit,
+}
+
+/// Toolboxes agents may use on top of (or instead of) their defaults
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ToolboxPolicy {
+    /// Only these toolboxes are available (empty = agent defaults)
+    #[serde(default)]
+    pub allow: Vec<String>,
+    /// Toolboxes removed from every agent
+    #[serde(default)]
+    pub deny: Vec<String>,
+}
+
+impl ToolboxPolicy {
+    /// Apply the policy to an agent's default toolbox list
+    pub fn apply(&self, defaults: &[String]) -> Vec<String> {
+        defaults
+            .iter()
+            .filter(|t| self.allow.is_empty() || self.allow.contains(t))
+            .filter(|t| !self.deny.contains(t))
+            .cloned()
+            .collect()
+    }
+}
+
+/// A named bundle of run settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunPreset {
+    #[serde(default)]
+    pub description: String,
+    /// Agents spawned for the run
+    pub team: Vec<AgentType>,
+    /// Spend cap for the run
+    pub budget: Decimal,
+    /// Model ids for tiers 1-3, cheapest first
+    #[serde(default)]
+    pub models: Vec<String>,
+    #[serde(default)]
+    pub toolbox_policy: ToolboxPolicy,
+    #[serde(default)]
+    pub approval_gates: Vec<ApprovalGate>,
+}
+
+impl RunPreset {
+    pub fn requires(&self, gate: ApprovalGate) -> bool {
+        self.approval_gates.contains(&gate)
+    }
+
+    /// Toolboxes an agent of `agent_type` gets under this preset
+    pub fn toolbox_for(&self, agent_type: &AgentType) -> Vec<String> {
+        self.toolbox_policy.apply(&agent_type.default_toolbox())
+    }
+}
+
+#[derive(Deserialize)]
+struct PresetFile {
+    #[serde(default)]
+    presets: BTreeMap<String, RunPreset>,
+}
+
+/// Presets available for selection by name
+#[derive(Debug, Clone)]
+pub struct PresetRegistry {
+    presets: BTreeMap<String, RunPreset>,
+}
+
+impl Default for PresetRegistry {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl PresetRegistry {
+    /// Registry with the built-in `cautious` and `fast-and-cheap` presets
+    pub fn builtin() -> Self {
+        let mut presets = BTreeMap::new();
+        presets.insert(
+            "cautious".to_string(),
+            RunPreset {
+                description: "Full review team, strong models, human sign-off at every gate".to_string(),
+                team: vec![
+                    AgentType::FeatureImplementer,
+                    AgentType::TestEngineer,
+                    AgentType::EdgeCaseMiner,
+                    AgentType::SecurityReviewer,
+                    AgentType::StandardsEnforcer,
+                ],
+                budget: Decimal::new(10, 0),
+                models: Vec::new(),
+                toolbox_policy: ToolboxPolicy::default(),
+                approval_gates: vec![
+                    ApprovalGate::PlanReview,
+                    ApprovalGate::ArtifactApproval,
+                    ApprovalGate::DangerousTools,
+                    ApprovalGate::Commit,
+                ],
+            },
+        );
+        presets.insert(
+            "fast-and-cheap".to_string(),
+            RunPreset {
+                description: "Single implementer on cheap models, no shell access".to_string(),
+                team: vec![AgentType::FeatureImplementer],
+                budget: Decimal::new(1, 0),
+                models: Vec::new(),
+                toolbox_policy: ToolboxPolicy {
+                    allow: Vec::new(),
+                    deny: vec!["BuildSystem".to_string(), "Sandboxing".to_string()],
+                },
+                approval_gates: vec![ApprovalGate::Commit],
+            },
+        );
+        Self { presets }
+    }
+
+    /// Parse `[presets.<name>]` tables, overriding built-ins of the same name
+    pub fn from_toml_str(content: &str) -> Result<Self> {
+        let file: PresetFile = toml::from_str(content).map_err(|e| anyhow!("Invalid presets file: {}", e))?;
+        let mut registry = Self::builtin();
+        registry.presets.extend(file.presets);
+        Ok(registry)
+    }
+
+    /// Load presets from a TOML file (e.g. `config/presets.toml`)
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let content = std::fs::read_to_string(path.as_ref())
+            .map_err(|e| anyhow!("Failed to read presets file: {}", e))?;
+        Self::from_toml_str(&content)
+    }
+
+    pub fn insert(&mut self, name: &str, preset: RunPreset) {
+        self.presets.insert(name.to_string(), preset);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&RunPreset> {
+        self.presets.get(name)
+    }
+
+    /// Look up a preset, listing the known names if it is missing
+    pub fn resolve(&self, name: &str) -> Result<&RunPreset> {
+        self.get(name).ok_or_else(|| {
+            anyhow!(
+                "Unknown preset '{}' (available: {})",
+                name,
+                self.names().collect::<Vec<_>>().join(", ")
+            )
+        })
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.presets.keys().map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_presets() {
+        let registry = PresetRegistry::builtin();
+        let cautious = registry.resolve("cautious").unwrap();
+        assert!(cautious.requires(ApprovalGate::PlanReview));
+
+        let cheap = registry.resolve("fast-and-cheap").unwrap();
+        assert!(!cheap.requires(ApprovalGate::PlanReview));
+        assert!(!cheap.toolbox_for(&AgentType::FeatureImplementer).contains(&"BuildSystem".to_string()));
+
+        let err = registry.resolve("yolo").unwrap_err().to_string();
+        assert!(err.contains("cautious"));
+    }
+
+    #[test]
+    fn test_presets_from_toml() {
+        let registry = PresetRegistry::from_toml_str(
+            r#"
+[presets.docs-only]
+description = "Documentation pass"
+team = [{ type = "documentation_writer" }]
+budget = "0.50"
+models = ["deepseek/deepseek-coder-6.7b-instruct"]
+approval_gates = ["commit"]
+
+[presets.docs-only.toolbox_policy]
+allow = ["FileManipulation"]
+"#,
+        )
+        .unwrap();
+
+        let docs = registry.resolve("docs-only").unwrap();
+        assert_eq!(docs.team, vec![AgentType::DocumentationWriter]);
+        assert_eq!(docs.budget, Decimal::new(50, 2));
+        assert_eq!(docs.toolbox_for(&AgentType::DocumentationWriter), vec!["FileManipulation".to_string()]);
+        // Built-ins remain available
+        assert!(registry.get("cautious").is_some());
+    }
+}
@@ -0,0 +1,297 @@
+//! Pull-request descriptions for completed runs
+//!
+//! Built from the same material as the retrospective: the intent and plan,
+//! the session's decisions (each linked to its full rationale), results of
+//! the test tasks, and spend per team. The description is written to
+//! `.zed42/pull_requests/` and can be posted through the forge toolbox.
+
+use anyhow::{Context, Result};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use zed42_agents::AgentType;
+use zed42_blackboard::DecisionNode;
+use zed42_core::types::SessionId;
+use zed42_core::Team;
+
+use crate::planner::SessionPlan;
+use crate::retrospective::Retrospective;
+
+/// Longest title before it is cut
+const MAX_TITLE_LEN: usize = 72;
+
+/// Result of one test task
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestResult {
+    pub task_id: String,
+    pub passed: bool,
+    pub detail: String,
+}
+
+/// Everything that goes into a pull-request description
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PullRequestDescription {
+    pub session_id: SessionId,
+    pub title: String,
+    pub intent: String,
+    /// Planned task descriptions in wave order
+    pub plan: Vec<String>,
+    pub decisions: Vec<DecisionNode>,
+    pub tests: Vec<TestResult>,
+    pub cost_by_team: BTreeMap<Team, Decimal>,
+}
+
+impl PullRequestDescription {
+    /// Assemble a description from the session plan and its retrospective
+    ///
+    /// Outcomes of tasks planned for a `TestEngineer` become test results;
+    /// without a plan, outcomes whose task id mentions "test" are used.
+    pub fn from_run(intent: &str, plan: Option<&SessionPlan>, retro: &Retrospective) -> Self {
+        let mut steps = Vec::new();
+        let mut test_tasks = HashSet::new();
+        if let Some(plan) = plan {
+            for wave in &plan.graph.waves {
+                for task_id in wave {
+                    let Some(node) = plan.graph.nodes.iter().find(|n| &n.task_id == task_id) else {
+                        continue;
+                    };
+                    steps.push(node.description.clone());
+                    if node.agent_type == AgentType::TestEngineer {
+                        test_tasks.insert(node.task_id.clone());
+                    }
+                }
+            }
+        }
+        let is_test = |task_id: &str| {
+            if plan.is_some() {
+                test_tasks.contains(task_id)
+            } else {
+                task_id.to_ascii_lowercase().contains("test")
+            }
+        };
+
+        let mut tests = Vec::new();
+        for (passed, outcomes) in [(true, &retro.completed), (false, &retro.failed)] {
+            for outcome in outcomes.iter().filter(|o| is_test(&o.task_id)) {
+                tests.push(TestResult {
+                    task_id: outcome.task_id.clone(),
+                    passed,
+                    detail: outcome.detail.clone(),
+                });
+            }
+        }
+
+        Self {
+            session_id: retro.session_id,
+            title: title_for(intent),
+            intent: intent.trim().to_string(),
+            plan: steps,
+            decisions: retro.decisions.clone(),
+            tests,
+            cost_by_team: retro.cost_by_team.clone(),
+        }
+    }
+
+    pub fn total_cost(&self) -> Decimal {
+        self.cost_by_team.values().copied().sum()
+    }
+
+    /// Render the description as Markdown
+    pub fn to_markdown(&self) -> String {
+        let mut md = String::new();
+        let _ = writeln!(md, "## Intent\n");
+        let _ = writeln!(md, "{}\n", self.intent);
+
+        let _ = writeln!(md, "## Plan\n");
+        if self.plan.is_empty() {
+            let _ = writeln!(md, "No plan was recorded.\n");
+        } else {
+            for (i, step) in self.plan.iter().enumerate() {
+                let _ = writeln!(md, "{}. {}", i + 1, step.trim());
+            }
+            md.push('\n');
+        }
+
+        let _ = writeln!(md, "## Key Decisions\n");
+        if self.decisions.is_empty() {
+            let _ = writeln!(md, "None recorded.\n");
+        } else {
+            for decision in &self.decisions {
+                let _ = writeln!(
+                    md,
+                    "- **{}**: {} ([rationale](#{}))",
+                    decision.decision_type,
+                    decision.description,
+                    anchor(decision)
+                );
+            }
+            md.push('\n');
+        }
+
+        let _ = writeln!(md, "## Test Results\n");
+        if self.tests.is_empty() {
+            let _ = writeln!(md, "No test tasks ran.\n");
+        } else {
+            let passed = self.tests.iter().filter(|t| t.passed).count();
+            let _ = writeln!(md, "{}/{} passed\n", passed, self.tests.len());
+            for test in &self.tests {
+                let mark = if test.passed { "x" } else { " " };
+                let _ = writeln!(md, "- [{}] `{}`: {}", mark, test.task_id, test.detail.replace('\n', " "));
+            }
+            md.push('\n');
+        }
+
+        let _ = writeln!(md, "## Cost\n");
+        if self.cost_by_team.is_empty() {
+            let _ = writeln!(md, "No spend recorded.\n");
+        } else {
+            let teams: Vec<String> = self
+                .cost_by_team
+                .iter()
+                .map(|(team, cost)| format!("{:?} ${}", team, cost.round_dp(4)))
+                .collect();
+            let _ = writeln!(md, "${} total ({})\n", self.total_cost().round_dp(4), teams.join(", "));
+        }
+
+        if !self.decisions.is_empty() {
+            let _ = writeln!(md, "<details>\n<summary>Decision rationale</summary>\n");
+            for decision in &self.decisions {
+                let _ = writeln!(md, "<a id=\"{}\"></a>\n", anchor(decision));
+                let _ = writeln!(md, "**{}**: {}\n", decision.decision_type, decision.description);
+                let _ = writeln!(md, "{}\n", rationale_text(&decision.rationale));
+                if !decision.alternatives_considered.is_empty() {
+                    let _ = writeln!(md, "Alternatives: {}\n", decision.alternatives_considered.join(", "));
+                }
+            }
+            let _ = writeln!(md, "</details>\n");
+        }
+
+        let _ = write!(md, "_Session `{}`_", self.session_id);
+        md.push('\n');
+        md
+    }
+
+    /// Write the description to `<workspace>/.zed42/pull_requests/<session>.md`
+    pub fn write_to(&self, workspace: &Path) -> Result<PathBuf> {
+        let dir = workspace.join(".zed42").join("pull_requests");
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+        let path = dir.join(format!("{}.md", self.session_id));
+        let content = format!("# {}\n\n{}", self.title, self.to_markdown());
+        std::fs::write(&path, content)
+            .with_context(|| format!("Failed to write pull request description to {}", path.display()))?;
+        Ok(path)
+    }
+}
+
+/// First line of the intent, cut to a title
+fn title_for(intent: &str) -> String {
+    let line = intent.trim().lines().next().unwrap_or_default().trim_end_matches('.');
+    if line.chars().count() <= MAX_TITLE_LEN {
+        return line.to_string();
+    }
+    let cut: String = line.chars().take(MAX_TITLE_LEN - 3).collect();
+    format!("{}...", cut.trim_end())
+}
+
+fn anchor(decision: &DecisionNode) -> String {
+    let id: String = decision
+        .id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+        .collect();
+    format!("decision-{}", id)
+}
+
+/// Rationale as prose: a string as-is, objects as `key: value` lines
+fn rationale_text(rationale: &serde_json::Value) -> String {
+    match rationale {
+        serde_json::Value::Null => "No rationale recorded.".to_string(),
+        serde_json::Value::String(text) => text.clone(),
+        serde_json::Value::Object(fields) => fields
+            .iter()
+            .map(|(key, value)| match value {
+                serde_json::Value::String(text) => format!("- {}: {}", key, text),
+                other => format!("- {}: {}", key, other),
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::planner::{TaskGraph, TaskNode};
+    use crate::retrospective::TaskOutcome;
+
+    #[test]
+    fn test_description_sections() {
+        let agent = uuid::Uuid::new_v4();
+        let build = "t1".to_string();
+        let test = "t2".to_string();
+        let graph = TaskGraph {
+            intent: "Add pagination to the users endpoint".to_string(),
+            nodes: vec![
+                TaskNode {
+                    task_id: build.clone(),
+                    description: "Implement cursor pagination".to_string(),
+                    agent_type: AgentType::FeatureImplementer,
+                    depends_on: vec![],
+                },
+                TaskNode {
+                    task_id: test.clone(),
+                    description: "Test page boundaries".to_string(),
+                    agent_type: AgentType::TestEngineer,
+                    depends_on: vec![build.clone()],
+                },
+            ],
+            waves: vec![vec![build.clone()], vec![test.clone()]],
+        };
+        let plan = SessionPlan::new(graph);
+
+        let mut retro = Retrospective::new(uuid::Uuid::new_v4());
+        retro.completed.push(TaskOutcome { task_id: build, agent_id: agent, detail: "done".to_string() });
+        retro.failed.push(TaskOutcome { task_id: test, agent_id: agent, detail: "off by one".to_string() });
+        retro.add_cost(Team::Blue, Decimal::new(25, 2));
+        retro.record_decisions(vec![DecisionNode {
+            id: "decisions:abc".to_string(),
+            decision_type: "design".to_string(),
+            description: "Use cursors over offsets".to_string(),
+            made_by: agent,
+            rationale: serde_json::json!({ "reason": "offsets drift under inserts" }),
+            alternatives_considered: vec!["offset/limit".to_string()],
+            timestamp: 1,
+            parent_decision: None,
+            confidence: None,
+        }]);
+
+        let pr = PullRequestDescription::from_run(&plan.graph.intent, Some(&plan), &retro);
+        assert_eq!(pr.title, "Add pagination to the users endpoint");
+        assert_eq!(pr.tests.len(), 1);
+        assert!(!pr.tests[0].passed);
+
+        let md = pr.to_markdown();
+        assert!(md.contains("1. Implement cursor pagination\n2. Test page boundaries"));
+        assert!(md.contains("Use cursors over offsets ([rationale](#decision-decisions-abc))"));
+        assert!(md.contains("<a id=\"decision-decisions-abc\"></a>"));
+        assert!(md.contains("- reason: offsets drift under inserts"));
+        assert!(md.contains("0/1 passed"));
+        assert!(md.contains("$0.25 total (Blue $0.25)"));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = pr.write_to(dir.path()).unwrap();
+        assert!(std::fs::read_to_string(path).unwrap().starts_with("# Add pagination"));
+    }
+
+    #[test]
+    fn test_long_intent_title_is_cut() {
+        let intent = format!("{}\nmore detail", "word ".repeat(30));
+        let title = title_for(&intent);
+        assert!(title.ends_with("..."));
+        assert!(title.chars().count() <= MAX_TITLE_LEN);
+    }
+}
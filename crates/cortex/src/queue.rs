@@ -0,0 +1,307 @@
+//! Queue of pending intents
+//!
+//! Intents wait in priority order (FIFO within a priority) until one of the
+//! `max_active` run slots frees up. When every slot is taken, an intent at or
+//! above the preemption priority pauses the lowest-priority running intent;
+//! paused runs resume ahead of pending intents of the same priority.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use uuid::Uuid;
+
+/// Priority of a queued intent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum IntentPriority {
+    Low,
+    #[default]
+    Normal,
+    High,
+    /// Hotfixes and other work that may pause running intents
+    Urgent,
+}
+
+/// An intent waiting for a run slot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedIntent {
+    pub id: String,
+    pub intent: String,
+    pub priority: IntentPriority,
+    pub enqueued_at: DateTime<Utc>,
+    /// Submission order, used to keep FIFO within a priority
+    seq: u64,
+}
+
+impl PartialEq for QueuedIntent {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority && self.seq == other.seq
+    }
+}
+
+impl Eq for QueuedIntent {}
+
+impl PartialOrd for QueuedIntent {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for QueuedIntent {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Higher priority first, then earlier submission first
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+/// State of an intent that has been given a run slot
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunState {
+    Running,
+    Paused,
+}
+
+/// An intent that has started
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntentRun {
+    pub id: String,
+    pub intent: String,
+    pub priority: IntentPriority,
+    pub state: RunState,
+    pub started_at: DateTime<Utc>,
+}
+
+/// What the queue did while dispatching
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum QueueEvent {
+    Started { id: String },
+    Paused { id: String, by: String },
+    Resumed { id: String },
+}
+
+/// Prioritized queue with a concurrency limit
+#[derive(Debug)]
+pub struct IntentQueue {
+    max_active: usize,
+    /// Intents at or above this priority may pause lower-priority runs
+    preempt_at: IntentPriority,
+    pending: BinaryHeap<QueuedIntent>,
+    runs: Vec<IntentRun>,
+    next_seq: u64,
+}
+
+impl Default for IntentQueue {
+    fn default() -> Self {
+        Self::new(1)
+    }
+}
+
+impl IntentQueue {
+    /// Queue allowing at most `max_active` concurrent runs
+    pub fn new(max_active: usize) -> Self {
+        Self {
+            max_active: max_active.max(1),
+            preempt_at: IntentPriority::Urgent,
+            pending: BinaryHeap::new(),
+            runs: Vec::new(),
+            next_seq: 0,
+        }
+    }
+
+    /// Change the lowest priority allowed to preempt running intents
+    pub fn with_preemption_at(mut self, priority: IntentPriority) -> Self {
+        self.preempt_at = priority;
+        self
+    }
+
+    /// Add an intent to the queue, returning its id
+    pub fn submit(&mut self, intent: &str, priority: IntentPriority) -> String {
+        let id = Uuid::new_v4().to_string();
+        self.pending.push(QueuedIntent {
+            id: id.clone(),
+            intent: intent.to_string(),
+            priority,
+            enqueued_at: Utc::now(),
+            seq: self.next_seq,
+        });
+        self.next_seq += 1;
+        id
+    }
+
+    /// Pending intents in dispatch order
+    pub fn pending(&self) -> Vec<&QueuedIntent> {
+        let mut pending: Vec<&QueuedIntent> = self.pending.iter().collect();
+        pending.sort_by(|a, b| b.cmp(a));
+        pending
+    }
+
+    pub fn runs(&self) -> &[IntentRun] {
+        &self.runs
+    }
+
+    pub fn run(&self, id: &str) -> Option<&IntentRun> {
+        self.runs.iter().find(|r| r.id == id)
+    }
+
+    pub fn active_count(&self) -> usize {
+        self.runs.iter().filter(|r| r.state == RunState::Running).count()
+    }
+
+    /// Cancel a pending intent
+    pub fn cancel(&mut self, id: &str) -> bool {
+        let before = self.pending.len();
+        self.pending.retain(|q| q.id != id);
+        self.pending.len() != before
+    }
+
+    /// Mark a run as finished, freeing its slot
+    ///
+    /// Call `dispatch` afterwards to fill the slot.
+    pub fn complete(&mut self, id: &str) -> Option<IntentRun> {
+        let index = self.runs.iter().position(|r| r.id == id)?;
+        Some(self.runs.remove(index))
+    }
+
+    /// Fill free slots and apply preemption
+    ///
+    /// Returns the transitions in the order they happened. The caller starts,
+    /// pauses or resumes the corresponding work.
+    pub fn dispatch(&mut self) -> Vec<QueueEvent> {
+        let mut events = Vec::new();
+
+        loop {
+            let best_paused = self
+                .runs
+                .iter()
+                .enumerate()
+                .filter(|(_, r)| r.state == RunState::Paused)
+                .max_by(|(_, a), (_, b)| a.priority.cmp(&b.priority).then(b.started_at.cmp(&a.started_at)))
+                .map(|(i, r)| (i, r.priority));
+            let best_pending = self.pending.peek().map(|q| q.priority);
+
+            if self.active_count() < self.max_active {
+                // Paused runs win ties against pending intents of the same priority
+                let resume = best_paused
+                    .filter(|(_, paused)| best_pending.map_or(true, |p| *paused >= p))
+                    .map(|(i, _)| i);
+                if let Some(i) = resume {
+                    self.runs[i].state = RunState::Running;
+                    events.push(QueueEvent::Resumed { id: self.runs[i].id.clone() });
+                } else if let Some(queued) = self.pending.pop() {
+                    events.push(QueueEvent::Started { id: queued.id.clone() });
+                    self.start(queued);
+                } else {
+                    break;
+                }
+                continue;
+            }
+
+            // All slots busy: only a preempting intent can make room
+            let Some(incoming) = best_pending.filter(|p| *p >= self.preempt_at) else {
+                break;
+            };
+            let victim = self
+                .runs
+                .iter()
+                .enumerate()
+                .filter(|(_, r)| r.state == RunState::Running && r.priority < incoming)
+                .min_by(|(_, a), (_, b)| a.priority.cmp(&b.priority).then(b.started_at.cmp(&a.started_at)))
+                .map(|(i, _)| i);
+            let Some(victim) = victim else { break };
+
+            let queued = self.pending.pop().expect("peeked");
+            self.runs[victim].state = RunState::Paused;
+            events.push(QueueEvent::Paused {
+                id: self.runs[victim].id.clone(),
+                by: queued.id.clone(),
+            });
+            events.push(QueueEvent::Started { id: queued.id.clone() });
+            self.start(queued);
+        }
+
+        events
+    }
+
+    fn start(&mut self, queued: QueuedIntent) {
+        self.runs.push(IntentRun {
+            id: queued.id,
+            intent: queued.intent,
+            priority: queued.priority,
+            state: RunState::Running,
+            started_at: Utc::now(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn started(events: &[QueueEvent]) -> Vec<String> {
+        events
+            .iter()
+            .filter_map(|e| match e {
+                QueueEvent::Started { id } => Some(id.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_priority_then_fifo() {
+        let mut queue = IntentQueue::new(1);
+        let low = queue.submit("tidy docs", IntentPriority::Low);
+        let first = queue.submit("add endpoint", IntentPriority::High);
+        let second = queue.submit("fix flaky test", IntentPriority::High);
+
+        assert_eq!(started(&queue.dispatch()), vec![first.clone()]);
+        queue.complete(&first);
+        assert_eq!(started(&queue.dispatch()), vec![second.clone()]);
+        queue.complete(&second);
+        assert_eq!(started(&queue.dispatch()), vec![low]);
+    }
+
+    #[test]
+    fn test_concurrency_limit() {
+        let mut queue = IntentQueue::new(2);
+        for i in 0..3 {
+            queue.submit(&format!("intent {}", i), IntentPriority::Normal);
+        }
+        assert_eq!(started(&queue.dispatch()).len(), 2);
+        assert_eq!(queue.active_count(), 2);
+        assert_eq!(queue.pending().len(), 1);
+    }
+
+    #[test]
+    fn test_urgent_preempts_and_paused_resumes() {
+        let mut queue = IntentQueue::new(1);
+        let refactor = queue.submit("refactor storage layer", IntentPriority::Normal);
+        queue.dispatch();
+
+        // High does not preempt by default
+        let feature = queue.submit("add flag", IntentPriority::High);
+        assert!(queue.dispatch().is_empty());
+
+        let hotfix = queue.submit("hotfix prod crash", IntentPriority::Urgent);
+        let events = queue.dispatch();
+        assert_eq!(
+            events,
+            vec![
+                QueueEvent::Paused { id: refactor.clone(), by: hotfix.clone() },
+                QueueEvent::Started { id: hotfix.clone() },
+            ]
+        );
+        assert_eq!(queue.run(&refactor).unwrap().state, RunState::Paused);
+
+        // The pending High intent outranks the paused Normal run
+        queue.complete(&hotfix);
+        assert_eq!(queue.dispatch(), vec![QueueEvent::Started { id: feature.clone() }]);
+        queue.complete(&feature);
+        assert_eq!(queue.dispatch(), vec![QueueEvent::Resumed { id: refactor }]);
+    }
+}
@@ -0,0 +1,614 @@
+//! Remote agent workers
+//!
+//! Extends worker mode across machines: a remote host runs `zed42-worker`
+//! in remote mode, connects to the shared SurrealDB/MOM substrate over WS
+//! and offers a set of agent types it is willing to run. The Cortex keeps
+//! the membership in a [`RemoteRegistry`]:
+//!
+//! - **Authentication**: every heartbeat is HMAC-SHA256 signed with a
+//!   secret shared between the Cortex and that worker only. Unknown
+//!   workers, bad signatures and stale or future timestamps are rejected.
+//! - **Capability scoping**: a worker is granted the intersection of the
+//!   agent types it offers and the types its credential allows. Assignments
+//!   are signed by the Cortex, and workers refuse any they can't verify or
+//!   weren't offered.
+//! - **Membership**: heartbeats land in `worker_heartbeats`; a worker that
+//!   misses them for `heartbeat_timeout` is marked lost and gets no new
+//!   agents until it beats again.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, SecondsFormat, Utc};
+use hmac::{Hmac, Mac};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::time::Duration;
+use surrealdb::{Connection, Surreal};
+use zed42_agents::AgentType;
+use zed42_core::types::{AgentId, SessionId};
+use zed42_core::CancellationToken;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Heartbeats further than this many seconds in the future are rejected
+const MAX_CLOCK_SKEW_SECS: i64 = 30;
+
+/// What the Cortex knows about one remote worker's identity
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkerCredential {
+    pub worker_id: String,
+    /// Shared HMAC secret
+    #[serde(skip_serializing)]
+    pub secret: String,
+    /// Agent types this worker may run
+    pub allowed: Vec<AgentType>,
+}
+
+/// Signed liveness announcement written by a remote worker
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Heartbeat {
+    pub worker_id: String,
+    pub host: String,
+    /// Agent types the worker offers to run
+    pub capabilities: Vec<AgentType>,
+    /// Agents the worker is currently running
+    pub running: Vec<AgentId>,
+    pub sent_at: DateTime<Utc>,
+    pub signature: String,
+}
+
+impl Heartbeat {
+    fn payload(&self) -> String {
+        format!(
+            "heartbeat|{}|{}|{}|{}|{}",
+            self.worker_id,
+            self.host,
+            serde_json::to_string(&self.capabilities).unwrap_or_default(),
+            serde_json::to_string(&self.running).unwrap_or_default(),
+            self.sent_at.to_rfc3339_opts(SecondsFormat::Micros, true)
+        )
+    }
+}
+
+/// Agent placed on a remote worker, signed by the Cortex
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Assignment {
+    pub agent_id: AgentId,
+    pub agent_type: AgentType,
+    pub worker_id: String,
+    pub session_id: SessionId,
+    pub assigned_at: DateTime<Utc>,
+    pub signature: String,
+}
+
+impl Assignment {
+    fn payload(&self) -> String {
+        format!(
+            "assignment|{}|{}|{}|{}|{}",
+            self.agent_id,
+            serde_json::to_string(&self.agent_type).unwrap_or_default(),
+            self.worker_id,
+            self.session_id,
+            self.assigned_at.to_rfc3339_opts(SecondsFormat::Micros, true)
+        )
+    }
+}
+
+fn sign(secret: &str, payload: &str) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(payload.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+fn verify(secret: &str, payload: &str, signature: &str) -> bool {
+    let Ok(signature) = hex::decode(signature) else {
+        return false;
+    };
+    let Ok(mut mac) = HmacSha256::new_from_slice(secret.as_bytes()) else {
+        return false;
+    };
+    mac.update(payload.as_bytes());
+    mac.verify_slice(&signature).is_ok()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MemberState {
+    Active,
+    /// Missed heartbeats; gets no new agents
+    Lost,
+}
+
+/// A remote worker that has authenticated at least once
+#[derive(Debug, Clone, Serialize)]
+pub struct Member {
+    pub worker_id: String,
+    pub host: String,
+    /// Offered capabilities narrowed to what the credential allows
+    pub granted: Vec<AgentType>,
+    pub running: Vec<AgentId>,
+    pub last_heartbeat: DateTime<Utc>,
+    pub state: MemberState,
+}
+
+/// Result of one membership sweep
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MembershipChange {
+    Joined(String),
+    Rejoined(String),
+    Lost(String),
+    Rejected { worker_id: String, reason: String },
+}
+
+/// Cortex-side membership and placement for remote workers
+pub struct RemoteRegistry<C: Connection> {
+    db: Surreal<C>,
+    session_id: SessionId,
+    credentials: HashMap<String, WorkerCredential>,
+    members: Mutex<HashMap<String, Member>>,
+    /// Agents assigned since the worker's last heartbeat
+    pending: Mutex<HashMap<String, HashSet<AgentId>>>,
+    heartbeat_timeout: Duration,
+}
+
+impl<C: Connection> RemoteRegistry<C> {
+    /// `db` must be the shared substrate the remote workers connect to
+    pub fn new(db: Surreal<C>, session_id: SessionId, credentials: Vec<WorkerCredential>) -> Self {
+        Self {
+            db,
+            session_id,
+            credentials: credentials.into_iter().map(|c| (c.worker_id.clone(), c)).collect(),
+            members: Mutex::new(HashMap::new()),
+            pending: Mutex::new(HashMap::new()),
+            heartbeat_timeout: Duration::from_secs(30),
+        }
+    }
+
+    pub fn with_heartbeat_timeout(mut self, timeout: Duration) -> Self {
+        self.heartbeat_timeout = timeout;
+        self
+    }
+
+    pub fn members(&self) -> Vec<Member> {
+        self.members.lock().values().cloned().collect()
+    }
+
+    pub fn member(&self, worker_id: &str) -> Option<Member> {
+        self.members.lock().get(worker_id).cloned()
+    }
+
+    /// Read heartbeats and update membership
+    pub async fn sweep(&self) -> Result<Vec<MembershipChange>> {
+        let mut response = self.db.query("SELECT * OMIT id FROM worker_heartbeats").await?;
+        let heartbeats: Vec<Heartbeat> = response.take(0).context("Failed to read worker heartbeats")?;
+        let now = Utc::now();
+        let timeout = chrono::Duration::from_std(self.heartbeat_timeout).unwrap_or_else(|_| chrono::Duration::days(365));
+        let mut changes = Vec::new();
+        let mut members = self.members.lock();
+
+        for heartbeat in heartbeats {
+            let worker_id = heartbeat.worker_id.clone();
+            let Some(credential) = self.credentials.get(&worker_id) else {
+                changes.push(MembershipChange::Rejected { worker_id, reason: "unknown worker".to_string() });
+                continue;
+            };
+            if !verify(&credential.secret, &heartbeat.payload(), &heartbeat.signature) {
+                changes.push(MembershipChange::Rejected { worker_id, reason: "bad signature".to_string() });
+                continue;
+            }
+            if heartbeat.sent_at > now + chrono::Duration::seconds(MAX_CLOCK_SKEW_SECS) {
+                changes.push(MembershipChange::Rejected { worker_id, reason: "heartbeat from the future".to_string() });
+                continue;
+            }
+            if now - heartbeat.sent_at > timeout {
+                // Stale heartbeats only matter for members we already know
+                if let Some(member) = members.get_mut(&worker_id) {
+                    if member.state == MemberState::Active {
+                        member.state = MemberState::Lost;
+                        tracing::warn!(worker = %worker_id, last = %member.last_heartbeat, "Remote worker lost");
+                        changes.push(MembershipChange::Lost(worker_id));
+                    }
+                }
+                continue;
+            }
+
+            let granted: Vec<AgentType> = heartbeat
+                .capabilities
+                .iter()
+                .filter(|t| credential.allowed.contains(t))
+                .cloned()
+                .collect();
+            let change = match members.get(&worker_id).map(|m| m.state) {
+                None => Some(MembershipChange::Joined(worker_id.clone())),
+                Some(MemberState::Lost) => Some(MembershipChange::Rejoined(worker_id.clone())),
+                Some(MemberState::Active) => None,
+            };
+            if let Some(change) = change {
+                tracing::info!(worker = %worker_id, host = %heartbeat.host, ?granted, "Remote worker joined");
+                changes.push(change);
+            }
+            // The heartbeat now accounts for everything it reports running
+            if let Some(pending) = self.pending.lock().get_mut(&worker_id) {
+                pending.retain(|id| !heartbeat.running.contains(id));
+            }
+            members.insert(
+                worker_id.clone(),
+                Member {
+                    worker_id,
+                    host: heartbeat.host,
+                    granted,
+                    running: heartbeat.running,
+                    last_heartbeat: heartbeat.sent_at,
+                    state: MemberState::Active,
+                },
+            );
+        }
+
+        // Members whose heartbeat record vanished entirely
+        for member in members.values_mut() {
+            if member.state == MemberState::Active && now - member.last_heartbeat > timeout {
+                member.state = MemberState::Lost;
+                changes.push(MembershipChange::Lost(member.worker_id.clone()));
+            }
+        }
+        for change in &changes {
+            if let MembershipChange::Rejected { worker_id, reason } = change {
+                tracing::warn!(worker = %worker_id, %reason, "Rejected remote worker heartbeat");
+            }
+        }
+        Ok(changes)
+    }
+
+    /// Place an agent on the least loaded active worker granted its type
+    ///
+    /// Returns the worker id, or None if no member can run it.
+    pub async fn assign(&self, agent_id: AgentId, agent_type: AgentType) -> Result<Option<String>> {
+        let worker_id = {
+            let members = self.members.lock();
+            let pending = self.pending.lock();
+            members
+                .values()
+                .filter(|m| m.state == MemberState::Active && m.granted.contains(&agent_type))
+                .min_by_key(|m| m.running.len() + pending.get(&m.worker_id).map_or(0, |p| p.len()))
+                .map(|m| m.worker_id.clone())
+        };
+        let Some(worker_id) = worker_id else {
+            return Ok(None);
+        };
+        let credential = self
+            .credentials
+            .get(&worker_id)
+            .context("Remote member without credential")?;
+
+        let mut assignment = Assignment {
+            agent_id,
+            agent_type,
+            worker_id: worker_id.clone(),
+            session_id: self.session_id,
+            assigned_at: Utc::now(),
+            signature: String::new(),
+        };
+        assignment.signature = sign(&credential.secret, &assignment.payload());
+        self.db
+            .query("UPSERT type::thing('worker_assignments', $id) CONTENT $assignment")
+            .bind(("id", agent_id.to_string()))
+            .bind(("assignment", assignment))
+            .await?
+            .check()
+            .context("Failed to write worker assignment")?;
+        self.pending.lock().entry(worker_id.clone()).or_default().insert(agent_id);
+        tracing::info!(agent = %agent_id, worker = %worker_id, "Agent assigned to remote worker");
+        Ok(Some(worker_id))
+    }
+
+    /// Withdraw an assignment; the worker stops the agent on its next poll
+    pub async fn unassign(&self, agent_id: AgentId) -> Result<bool> {
+        let mut response = self
+            .db
+            .query("DELETE type::thing('worker_assignments', $id) RETURN BEFORE")
+            .bind(("id", agent_id.to_string()))
+            .await?;
+        let removed: Vec<Assignment> = response.take(0)?;
+        for pending in self.pending.lock().values_mut() {
+            pending.remove(&agent_id);
+        }
+        Ok(!removed.is_empty())
+    }
+
+    /// Whether an agent is placed on a remote worker
+    pub async fn is_assigned(&self, agent_id: AgentId) -> Result<bool> {
+        let mut response = self
+            .db
+            .query("SELECT * OMIT id FROM type::thing('worker_assignments', $id)")
+            .bind(("id", agent_id.to_string()))
+            .await?;
+        let found: Vec<Assignment> = response.take(0)?;
+        Ok(!found.is_empty())
+    }
+
+    /// Sweep every `interval` until `shutdown`
+    pub async fn run(&self, interval: Duration, shutdown: CancellationToken) {
+        let mut tick = tokio::time::interval(interval);
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => break,
+                _ = tick.tick() => {
+                    if let Err(e) = self.sweep().await {
+                        tracing::warn!(error = %e, "Remote membership sweep failed");
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Remote side configuration (`zed42-worker` with `ZED42_REMOTE_WORKER_CONFIG`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteWorkerConfig {
+    pub worker_id: String,
+    /// Shared substrate, e.g. `wss://cortex.example:8000`
+    pub mom_addr: String,
+    #[serde(default = "default_namespace")]
+    pub namespace: String,
+    pub database: String,
+    /// Shared secret; read from `ZED42_REMOTE_WORKER_SECRET` when not in the file
+    #[serde(default, skip_serializing)]
+    pub secret: String,
+    pub capabilities: Vec<AgentType>,
+    #[serde(default = "default_heartbeat_secs")]
+    pub heartbeat_secs: u64,
+    /// Local data directory for the agents' blackboard state
+    pub data_dir: PathBuf,
+}
+
+fn default_namespace() -> String {
+    "zed42".to_string()
+}
+
+fn default_heartbeat_secs() -> u64 {
+    10
+}
+
+impl RemoteWorkerConfig {
+    pub fn load(path: &std::path::Path) -> Result<Self> {
+        let raw = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read remote worker config {}", path.display()))?;
+        let mut config: Self = toml::from_str(&raw).context("Invalid remote worker config")?;
+        if config.secret.is_empty() {
+            config.secret = std::env::var("ZED42_REMOTE_WORKER_SECRET")
+                .context("Remote worker secret missing: set it in the config or ZED42_REMOTE_WORKER_SECRET")?;
+        }
+        Ok(config)
+    }
+}
+
+/// Remote side: heartbeats, picks up assignments and runs their agents
+pub struct RemoteWorker<C: Connection> {
+    config: RemoteWorkerConfig,
+    db: Surreal<C>,
+    host: String,
+}
+
+impl RemoteWorker<surrealdb::engine::any::Any> {
+    /// Connect to the shared substrate named in the config
+    pub async fn connect(config: RemoteWorkerConfig) -> Result<Self> {
+        let db = surrealdb::engine::any::connect(config.mom_addr.as_str())
+            .await
+            .with_context(|| format!("Failed to connect to {}", config.mom_addr))?;
+        db.use_ns(&config.namespace).use_db(&config.database).await?;
+        Ok(Self::with_db(config, db))
+    }
+}
+
+impl<C: Connection> RemoteWorker<C> {
+    pub fn with_db(config: RemoteWorkerConfig, db: Surreal<C>) -> Self {
+        let host = std::env::var("HOSTNAME").unwrap_or_else(|_| "unknown".to_string());
+        Self { config, db, host }
+    }
+
+    /// Announce liveness and the agents currently running
+    pub async fn heartbeat(&self, running: Vec<AgentId>) -> Result<()> {
+        let mut heartbeat = Heartbeat {
+            worker_id: self.config.worker_id.clone(),
+            host: self.host.clone(),
+            capabilities: self.config.capabilities.clone(),
+            running,
+            sent_at: Utc::now(),
+            signature: String::new(),
+        };
+        heartbeat.signature = sign(&self.config.secret, &heartbeat.payload());
+        self.db
+            .query("UPSERT type::thing('worker_heartbeats', $id) CONTENT $heartbeat")
+            .bind(("id", self.config.worker_id.clone()))
+            .bind(("heartbeat", heartbeat))
+            .await?
+            .check()
+            .context("Failed to write heartbeat")?;
+        Ok(())
+    }
+
+    /// Verified assignments for this worker
+    ///
+    /// Unsigned, forged or out-of-scope assignments are skipped.
+    pub async fn assignments(&self) -> Result<Vec<Assignment>> {
+        let mut response = self
+            .db
+            .query("SELECT * OMIT id FROM worker_assignments WHERE worker_id = $worker")
+            .bind(("worker", self.config.worker_id.clone()))
+            .await?;
+        let assignments: Vec<Assignment> = response.take(0).context("Failed to read assignments")?;
+        Ok(assignments
+            .into_iter()
+            .filter(|a| {
+                let trusted = verify(&self.config.secret, &a.payload(), &a.signature);
+                let in_scope = self.config.capabilities.contains(&a.agent_type);
+                if !trusted || !in_scope {
+                    tracing::warn!(agent = %a.agent_id, trusted, in_scope, "Refusing remote assignment");
+                }
+                trusted && in_scope
+            })
+            .collect())
+    }
+
+    /// Heartbeat and reconcile assignments until `shutdown`
+    ///
+    /// Each assigned agent runs as an in-process worker connected to the
+    /// shared substrate; withdrawn assignments are stopped.
+    pub async fn run(&self, shutdown: CancellationToken) -> Result<()> {
+        let mut running: HashMap<AgentId, (CancellationToken, tokio::task::JoinHandle<()>)> = HashMap::new();
+        let mut tick = tokio::time::interval(Duration::from_secs(self.config.heartbeat_secs.max(1)));
+        tracing::info!(worker = %self.config.worker_id, addr = %self.config.mom_addr, "Remote worker started");
+
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => break,
+                _ = tick.tick() => {}
+            }
+            running.retain(|_, (_, task)| !task.is_finished());
+
+            match self.assignments().await {
+                Ok(assignments) => {
+                    let assigned: HashSet<AgentId> = assignments.iter().map(|a| a.agent_id).collect();
+                    running.retain(|agent_id, (stop, _)| {
+                        let keep = assigned.contains(agent_id);
+                        if !keep {
+                            stop.cancel();
+                        }
+                        keep
+                    });
+                    for assignment in assignments {
+                        if running.contains_key(&assignment.agent_id) {
+                            continue;
+                        }
+                        let env = crate::workers::WorkerEnv {
+                            agent_id: assignment.agent_id,
+                            agent_type: assignment.agent_type,
+                            session_id: assignment.session_id,
+                            mom_addr: self.config.mom_addr.clone(),
+                            data_dir: self.config.data_dir.join(assignment.agent_id.to_string()),
+                        };
+                        let stop = shutdown.child_token();
+                        let token = stop.clone();
+                        let task = tokio::spawn(async move {
+                            let agent_id = env.agent_id;
+                            if let Err(e) = crate::workers::run_worker(env, token).await {
+                                tracing::error!(agent = %agent_id, error = %e, "Remote agent failed");
+                            }
+                        });
+                        running.insert(assignment.agent_id, (stop, task));
+                    }
+                }
+                Err(e) => tracing::warn!(error = %e, "Failed to fetch remote assignments"),
+            }
+
+            if let Err(e) = self.heartbeat(running.keys().copied().collect()).await {
+                tracing::warn!(error = %e, "Failed to send remote heartbeat");
+            }
+        }
+
+        for (_, (stop, task)) in running {
+            stop.cancel();
+            let _ = task.await;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use surrealdb::engine::any::{connect, Any};
+
+    async fn shared_db() -> Surreal<Any> {
+        let db = connect("mem://").await.unwrap();
+        db.use_ns("zed42").use_db("remote_test").await.unwrap();
+        db
+    }
+
+    fn worker_config(worker_id: &str, secret: &str, capabilities: Vec<AgentType>) -> RemoteWorkerConfig {
+        RemoteWorkerConfig {
+            worker_id: worker_id.to_string(),
+            mom_addr: "mem://".to_string(),
+            namespace: "zed42".to_string(),
+            database: "remote_test".to_string(),
+            secret: secret.to_string(),
+            capabilities,
+            heartbeat_secs: 1,
+            data_dir: std::env::temp_dir(),
+        }
+    }
+
+    fn credential(worker_id: &str, secret: &str, allowed: Vec<AgentType>) -> WorkerCredential {
+        WorkerCredential {
+            worker_id: worker_id.to_string(),
+            secret: secret.to_string(),
+            allowed,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_join_scopes_capabilities_and_rejects_forgeries() {
+        let db = shared_db().await;
+        let registry = RemoteRegistry::new(
+            db.clone(),
+            uuid::Uuid::new_v4(),
+            vec![credential("gpu-box", "s3cret", vec![AgentType::PerformanceAnalyst])],
+        );
+        let worker = RemoteWorker::with_db(
+            worker_config("gpu-box", "s3cret", vec![AgentType::PerformanceAnalyst, AgentType::SecurityReviewer]),
+            db.clone(),
+        );
+        let impostor = RemoteWorker::with_db(worker_config("laptop", "guess", vec![AgentType::Architect]), db.clone());
+        worker.heartbeat(Vec::new()).await.unwrap();
+        impostor.heartbeat(Vec::new()).await.unwrap();
+
+        let changes = registry.sweep().await.unwrap();
+        assert!(changes.contains(&MembershipChange::Joined("gpu-box".to_string())));
+        assert!(changes.contains(&MembershipChange::Rejected {
+            worker_id: "laptop".to_string(),
+            reason: "unknown worker".to_string()
+        }));
+        assert_eq!(registry.member("gpu-box").unwrap().granted, vec![AgentType::PerformanceAnalyst]);
+
+        // Offered but not allowed: never placed remotely
+        let agent_id = uuid::Uuid::new_v4();
+        assert_eq!(registry.assign(agent_id, AgentType::SecurityReviewer).await.unwrap(), None);
+        assert_eq!(
+            registry.assign(agent_id, AgentType::PerformanceAnalyst).await.unwrap(),
+            Some("gpu-box".to_string())
+        );
+        let assignments = worker.assignments().await.unwrap();
+        assert_eq!(assignments.len(), 1);
+        assert_eq!(assignments[0].agent_id, agent_id);
+
+        // A worker with the wrong secret can't read it as trusted
+        let wrong_key = RemoteWorker::with_db(worker_config("gpu-box", "other", vec![AgentType::PerformanceAnalyst]), db);
+        assert!(wrong_key.assignments().await.unwrap().is_empty());
+
+        assert!(registry.unassign(agent_id).await.unwrap());
+        assert!(worker.assignments().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_missed_heartbeats_mark_worker_lost() {
+        let db = shared_db().await;
+        let registry = RemoteRegistry::new(
+            db.clone(),
+            uuid::Uuid::new_v4(),
+            vec![credential("server", "key", vec![AgentType::EdgeCaseMiner])],
+        )
+        .with_heartbeat_timeout(Duration::from_millis(200));
+        let worker = RemoteWorker::with_db(worker_config("server", "key", vec![AgentType::EdgeCaseMiner]), db);
+
+        worker.heartbeat(Vec::new()).await.unwrap();
+        registry.sweep().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert_eq!(registry.sweep().await.unwrap(), vec![MembershipChange::Lost("server".to_string())]);
+        assert_eq!(registry.assign(uuid::Uuid::new_v4(), AgentType::EdgeCaseMiner).await.unwrap(), None);
+
+        worker.heartbeat(Vec::new()).await.unwrap();
+        assert_eq!(registry.sweep().await.unwrap(), vec![MembershipChange::Rejoined("server".to_string())]);
+    }
+}
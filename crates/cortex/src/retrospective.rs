@@ -0,0 +1,257 @@
+//! Post-run retrospective reports
+//!
+//! When a session completes the Cortex collects what happened (task outcomes,
+//! spend per team, decisions, review rejections and stored lessons) and writes
+//! it to the workspace as Markdown under `.zed42/retrospectives/`.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use zed42_blackboard::DecisionNode;
+use zed42_core::types::SessionId;
+use zed42_core::{AgentId, Message, MessageType, Team};
+
+/// Decisions listed in the report, most recent first
+const MAX_DECISIONS: usize = 10;
+
+/// Outcome of one task
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskOutcome {
+    pub task_id: String,
+    pub agent_id: AgentId,
+    /// Result summary or error message
+    pub detail: String,
+}
+
+/// A proposal rejected during review
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewRejection {
+    pub proposal_id: String,
+    pub rejected_by: AgentId,
+    pub reason: String,
+}
+
+/// Everything that goes into a retrospective
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Retrospective {
+    pub session_id: SessionId,
+    pub generated_at: DateTime<Utc>,
+    pub completed: Vec<TaskOutcome>,
+    pub failed: Vec<TaskOutcome>,
+    pub cost_by_team: BTreeMap<Team, Decimal>,
+    pub decisions: Vec<DecisionNode>,
+    pub rejections: Vec<ReviewRejection>,
+    /// Lessons stored in the knowledge graph during the session
+    pub lessons: Vec<String>,
+}
+
+impl Retrospective {
+    pub fn new(session_id: SessionId) -> Self {
+        Self {
+            session_id,
+            generated_at: Utc::now(),
+            completed: Vec::new(),
+            failed: Vec::new(),
+            cost_by_team: BTreeMap::new(),
+            decisions: Vec::new(),
+            rejections: Vec::new(),
+            lessons: Vec::new(),
+        }
+    }
+
+    /// Pick task outcomes and review rejections out of blackboard messages
+    pub fn record_messages(&mut self, messages: &[Message]) {
+        for message in messages {
+            match &message.message_type {
+                MessageType::TaskComplete { task_id, result } => self.completed.push(TaskOutcome {
+                    task_id: task_id.clone(),
+                    agent_id: message.from_agent,
+                    detail: result.clone(),
+                }),
+                MessageType::ErrorOccurred { error, context } => self.failed.push(TaskOutcome {
+                    task_id: context.clone(),
+                    agent_id: message.from_agent,
+                    detail: error.clone(),
+                }),
+                MessageType::RejectProposal { proposal_id, reason } => self.rejections.push(ReviewRejection {
+                    proposal_id: proposal_id.clone(),
+                    rejected_by: message.from_agent,
+                    reason: reason.clone(),
+                }),
+                _ => {}
+            }
+        }
+    }
+
+    /// Keep the most recent decisions
+    pub fn record_decisions(&mut self, mut decisions: Vec<DecisionNode>) {
+        decisions.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        decisions.truncate(MAX_DECISIONS);
+        self.decisions = decisions;
+    }
+
+    pub fn add_cost(&mut self, team: Team, amount: Decimal) {
+        *self.cost_by_team.entry(team).or_default() += amount;
+    }
+
+    pub fn total_cost(&self) -> Decimal {
+        self.cost_by_team.values().copied().sum()
+    }
+
+    /// Render the report as Markdown
+    pub fn to_markdown(&self) -> String {
+        let mut md = String::new();
+        let _ = writeln!(md, "# Session Retrospective\n");
+        let _ = writeln!(md, "- Session: `{}`", self.session_id);
+        let _ = writeln!(md, "- Generated: {}", self.generated_at.format("%Y-%m-%d %H:%M UTC"));
+        let _ = writeln!(
+            md,
+            "- Tasks: {} completed, {} failed",
+            self.completed.len(),
+            self.failed.len()
+        );
+        let _ = writeln!(md, "- Total cost: ${}\n", self.total_cost().round_dp(4));
+
+        let _ = writeln!(md, "## Tasks\n");
+        if self.completed.is_empty() && self.failed.is_empty() {
+            let _ = writeln!(md, "No task results were posted.\n");
+        } else {
+            let _ = writeln!(md, "| Status | Task | Agent | Detail |");
+            let _ = writeln!(md, "|---|---|---|---|");
+            for (status, outcomes) in [("completed", &self.completed), ("failed", &self.failed)] {
+                for outcome in outcomes {
+                    let _ = writeln!(
+                        md,
+                        "| {} | {} | `{}` | {} |",
+                        status,
+                        escape_cell(&outcome.task_id),
+                        outcome.agent_id,
+                        escape_cell(&outcome.detail)
+                    );
+                }
+            }
+            md.push('\n');
+        }
+
+        let _ = writeln!(md, "## Cost by Team\n");
+        if self.cost_by_team.is_empty() {
+            let _ = writeln!(md, "No spend recorded.\n");
+        } else {
+            let _ = writeln!(md, "| Team | Cost |");
+            let _ = writeln!(md, "|---|---|");
+            for (team, cost) in &self.cost_by_team {
+                let _ = writeln!(md, "| {:?} | ${} |", team, cost.round_dp(4));
+            }
+            md.push('\n');
+        }
+
+        let _ = writeln!(md, "## Notable Decisions\n");
+        if self.decisions.is_empty() {
+            let _ = writeln!(md, "None recorded.\n");
+        } else {
+            for decision in &self.decisions {
+                let _ = writeln!(md, "- **{}**: {}", decision.decision_type, decision.description);
+                if !decision.alternatives_considered.is_empty() {
+                    let _ = writeln!(md, "  - Alternatives: {}", decision.alternatives_considered.join(", "));
+                }
+            }
+            md.push('\n');
+        }
+
+        let _ = writeln!(md, "## Review Rejections\n");
+        if self.rejections.is_empty() {
+            let _ = writeln!(md, "None.\n");
+        } else {
+            for rejection in &self.rejections {
+                let _ = writeln!(
+                    md,
+                    "- `{}` rejected by `{}`: {}",
+                    rejection.proposal_id, rejection.rejected_by, rejection.reason
+                );
+            }
+            md.push('\n');
+        }
+
+        let _ = writeln!(md, "## Lessons Learned\n");
+        if self.lessons.is_empty() {
+            let _ = writeln!(md, "None stored.");
+        } else {
+            for lesson in &self.lessons {
+                let _ = writeln!(md, "- {}", lesson.trim());
+            }
+        }
+
+        md
+    }
+
+    /// Write the report to `<workspace>/.zed42/retrospectives/<session>.md`
+    pub fn write_to(&self, workspace: &Path) -> Result<PathBuf> {
+        let dir = workspace.join(".zed42").join("retrospectives");
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+        let path = dir.join(format!("{}.md", self.session_id));
+        std::fs::write(&path, self.to_markdown())
+            .with_context(|| format!("Failed to write retrospective to {}", path.display()))?;
+        Ok(path)
+    }
+}
+
+fn escape_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zed42_core::MessageTarget;
+
+    #[test]
+    fn test_report_sections() {
+        let implementer = uuid::Uuid::new_v4();
+        let reviewer = uuid::Uuid::new_v4();
+        let messages = vec![
+            Message::new(
+                implementer,
+                MessageTarget::All,
+                MessageType::TaskComplete { task_id: "t1".to_string(), result: "Added | pagination".to_string() },
+                1,
+            ),
+            Message::new(
+                implementer,
+                MessageTarget::All,
+                MessageType::ErrorOccurred { error: "tests failed".to_string(), context: "t2".to_string() },
+                1,
+            ),
+            Message::new(
+                reviewer,
+                MessageTarget::Team(Team::Blue),
+                MessageType::RejectProposal { proposal_id: "p1".to_string(), reason: "missing tests".to_string() },
+                1,
+            ),
+        ];
+
+        let mut retro = Retrospective::new(uuid::Uuid::new_v4());
+        retro.record_messages(&messages);
+        retro.add_cost(Team::Blue, Decimal::new(12, 2));
+        retro.add_cost(Team::Green, Decimal::new(3, 2));
+        retro.lessons.push("[Pattern]: cursor pagination avoids offset drift".to_string());
+
+        let md = retro.to_markdown();
+        assert!(md.contains("1 completed, 1 failed"));
+        assert!(md.contains("Added \\| pagination"));
+        assert!(md.contains("| Blue | $0.12 |"));
+        assert!(md.contains("rejected by"));
+        assert!(md.contains("missing tests"));
+        assert!(md.contains("cursor pagination"));
+        assert_eq!(retro.total_cost(), Decimal::new(15, 2));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = retro.write_to(dir.path()).unwrap();
+        assert!(path.starts_with(dir.path().join(".zed42")));
+        assert_eq!(std::fs::read_to_string(path).unwrap(), md);
+    }
+}
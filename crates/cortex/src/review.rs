@@ -0,0 +1,360 @@
+//! Consolidated diffs of a task's artifacts for review
+//!
+//! Reviewers that see one artifact at a time miss changes that only make
+//! sense together: a new endpoint and the auth check it forgot, a schema
+//! change and its migration. A [`ReviewChangeset`] diffs every file artifact
+//! of a task against the current workspace in one pass. It renders as
+//! Markdown for humans, and splits into [`ReviewChunk`]s that fit a model's
+//! context for the SecurityReviewer and Architect, cutting only between
+//! files or, for a file too big on its own, between hunks.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::{Component, Path};
+use zed42_core::types::{Artifact, TaskId};
+
+/// Characters of diff per chunk when the caller doesn't say
+pub const DEFAULT_CHUNK_CHARS: usize = 24_000;
+
+/// Lines of context around each change
+const CONTEXT_LINES: u32 = 3;
+
+/// How an artifact differs from the workspace
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileStatus {
+    /// The file doesn't exist in the workspace yet
+    Added,
+    Modified,
+    /// The artifact matches the workspace file
+    Unchanged,
+}
+
+impl FileStatus {
+    fn label(self) -> &'static str {
+        match self {
+            FileStatus::Added => "added",
+            FileStatus::Modified => "modified",
+            FileStatus::Unchanged => "unchanged",
+        }
+    }
+}
+
+/// Diff of one file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileDiff {
+    /// Path relative to the workspace root
+    pub path: String,
+    pub status: FileStatus,
+    /// `diff --git`, `---` and `+++` lines
+    pub header: String,
+    /// Unified-diff hunks, each starting with its `@@` line
+    pub hunks: Vec<String>,
+    pub additions: usize,
+    pub deletions: usize,
+}
+
+impl FileDiff {
+    /// Diff `new` against `old`; an empty `old` with `exists == false` is an added file
+    fn between(path: &str, old: &str, new: &str, exists: bool) -> Result<Self> {
+        let mut options = git2::DiffOptions::new();
+        options.context_lines(CONTEXT_LINES);
+        let old_path = exists.then(|| Path::new(path));
+        let mut patch = git2::Patch::from_buffers(
+            old.as_bytes(),
+            old_path,
+            new.as_bytes(),
+            Some(Path::new(path)),
+            Some(&mut options),
+        )
+        .with_context(|| format!("Failed to diff {}", path))?;
+        let (_, additions, deletions) = patch.line_stats()?;
+
+        let mut header = String::new();
+        let mut hunks: Vec<String> = Vec::new();
+        patch.print(&mut |_, _, line| {
+            let content = String::from_utf8_lossy(line.content());
+            match line.origin() {
+                'F' => header.push_str(&content),
+                'H' => hunks.push(content.into_owned()),
+                origin => {
+                    if let Some(hunk) = hunks.last_mut() {
+                        if matches!(origin, '+' | '-' | ' ') {
+                            hunk.push(origin);
+                        }
+                        hunk.push_str(&content);
+                    }
+                }
+            }
+            true
+        })?;
+
+        let status = if !exists {
+            FileStatus::Added
+        } else if hunks.is_empty() {
+            FileStatus::Unchanged
+        } else {
+            FileStatus::Modified
+        };
+        Ok(Self { path: path.to_string(), status, header, hunks, additions, deletions })
+    }
+
+    /// The whole unified diff of the file
+    pub fn patch(&self) -> String {
+        let mut patch = self.header.clone();
+        for hunk in &self.hunks {
+            patch.push_str(hunk);
+        }
+        patch
+    }
+}
+
+/// Every file a task changed, diffed against the workspace
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewChangeset {
+    pub task_id: TaskId,
+    /// One diff per path, in path order
+    pub files: Vec<FileDiff>,
+    /// Artifacts without a file path (notes, diagrams), left out of the diff
+    pub skipped: usize,
+}
+
+impl ReviewChangeset {
+    /// Diff the latest artifact for each path against `workspace`
+    ///
+    /// Artifacts of other tasks are ignored. Paths must be relative and stay
+    /// inside the workspace.
+    pub fn build(task_id: &TaskId, artifacts: &[Artifact], workspace: &Path) -> Result<Self> {
+        let mut latest: BTreeMap<&str, &Artifact> = BTreeMap::new();
+        let mut skipped = 0;
+        for artifact in artifacts.iter().filter(|a| &a.task_id == task_id) {
+            let Some(path) = artifact.file_path.as_deref() else {
+                skipped += 1;
+                continue;
+            };
+            check_relative(path)?;
+            match latest.get(path) {
+                Some(current) if current.created_at > artifact.created_at => {}
+                _ => {
+                    latest.insert(path, artifact);
+                }
+            }
+        }
+
+        let mut files = Vec::with_capacity(latest.len());
+        for (path, artifact) in latest {
+            let full = workspace.join(path);
+            let (old, exists) = match std::fs::read_to_string(&full) {
+                Ok(text) => (text, true),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => (String::new(), false),
+                Err(e) => return Err(e).with_context(|| format!("Failed to read {}", full.display())),
+            };
+            files.push(FileDiff::between(path, &old, &artifact.content, exists)?);
+        }
+        Ok(Self { task_id: task_id.clone(), files, skipped })
+    }
+
+    /// Files the task actually changes
+    pub fn changed(&self) -> impl Iterator<Item = &FileDiff> {
+        self.files.iter().filter(|f| f.status != FileStatus::Unchanged)
+    }
+
+    pub fn additions(&self) -> usize {
+        self.files.iter().map(|f| f.additions).sum()
+    }
+
+    pub fn deletions(&self) -> usize {
+        self.files.iter().map(|f| f.deletions).sum()
+    }
+
+    /// Split the changed files into chunks of at most `max_chars` of diff
+    ///
+    /// Whole files are packed together while they fit. A file bigger than a
+    /// chunk is split between hunks, repeating its header in each part; a
+    /// single hunk bigger than a chunk is kept whole.
+    pub fn chunks(&self, max_chars: usize) -> Vec<ReviewChunk> {
+        let mut parts: Vec<(String, String)> = Vec::new();
+        for file in self.changed() {
+            let patch = file.patch();
+            if patch.len() <= max_chars {
+                parts.push((file.path.clone(), patch));
+                continue;
+            }
+            let mut part = file.header.clone();
+            for hunk in &file.hunks {
+                if part.len() > file.header.len() && part.len() + hunk.len() > max_chars {
+                    parts.push((file.path.clone(), std::mem::replace(&mut part, file.header.clone())));
+                }
+                part.push_str(hunk);
+            }
+            parts.push((file.path.clone(), part));
+        }
+
+        let mut chunks: Vec<ReviewChunk> = Vec::new();
+        let mut current = ReviewChunk::default();
+        for (path, diff) in parts {
+            if !current.diff.is_empty() && current.diff.len() + diff.len() > max_chars {
+                chunks.push(std::mem::take(&mut current));
+            }
+            if current.files.last() != Some(&path) {
+                current.files.push(path);
+            }
+            current.diff.push_str(&diff);
+        }
+        if !current.diff.is_empty() {
+            chunks.push(current);
+        }
+
+        let total = chunks.len();
+        for (i, chunk) in chunks.iter_mut().enumerate() {
+            chunk.index = i + 1;
+            chunk.total = total;
+        }
+        chunks
+    }
+
+    /// Render the changeset as Markdown: a summary table, then each file's diff
+    pub fn to_markdown(&self) -> String {
+        let mut md = String::new();
+        let changed = self.changed().count();
+        let _ = writeln!(md, "## Changeset for task `{}`\n", self.task_id);
+        let _ = writeln!(
+            md,
+            "{} file{} changed, +{} -{}\n",
+            changed,
+            if changed == 1 { "" } else { "s" },
+            self.additions(),
+            self.deletions()
+        );
+        if self.files.is_empty() {
+            let _ = writeln!(md, "No file artifacts.\n");
+            return md;
+        }
+
+        let _ = writeln!(md, "| File | Status | + | - |");
+        let _ = writeln!(md, "|------|--------|---|---|");
+        for file in &self.files {
+            let _ = writeln!(
+                md,
+                "| `{}` | {} | {} | {} |",
+                file.path,
+                file.status.label(),
+                file.additions,
+                file.deletions
+            );
+        }
+        md.push('\n');
+
+        for file in self.changed() {
+            let _ = writeln!(md, "### `{}` ({})\n", file.path, file.status.label());
+            let _ = writeln!(md, "```diff\n{}```\n", file.patch());
+        }
+        md
+    }
+}
+
+/// Part of a changeset sized for one review request
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReviewChunk {
+    /// 1-based position among the changeset's chunks
+    pub index: usize,
+    pub total: usize,
+    /// Files with hunks in this chunk
+    pub files: Vec<String>,
+    pub diff: String,
+}
+
+impl ReviewChunk {
+    /// Line telling the reviewer which part of the changeset this is
+    pub fn heading(&self) -> String {
+        format!("Changeset part {} of {}: {}", self.index, self.total, self.files.join(", "))
+    }
+}
+
+fn check_relative(path: &str) -> Result<()> {
+    let parsed = Path::new(path);
+    if parsed.is_absolute() {
+        bail!("Artifact path {} must be relative to the workspace", path);
+    }
+    if parsed.components().any(|c| matches!(c, Component::ParentDir | Component::Prefix(_))) {
+        bail!("Artifact path {} leaves the workspace", path);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn artifact(task: &str, path: Option<&str>, content: &str) -> Artifact {
+        Artifact::code(task.to_string(), content.to_string(), path.map(str::to_string))
+    }
+
+    #[test]
+    fn test_changeset_diffs_against_workspace() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/auth.rs"), "fn check() -> bool {\n    true\n}\n").unwrap();
+        std::fs::write(dir.path().join("src/lib.rs"), "pub mod auth;\n").unwrap();
+
+        let task = "t1".to_string();
+        let mut stale = artifact("t1", Some("src/auth.rs"), "fn check() -> bool {\n    true\n}\n");
+        stale.created_at -= chrono::Duration::seconds(5);
+        let artifacts = vec![
+            stale,
+            artifact("t1", Some("src/auth.rs"), "fn check(token: &str) -> bool {\n    !token.is_empty()\n}\n"),
+            artifact("t1", Some("src/api.rs"), "pub fn users() {}\n"),
+            artifact("t1", Some("src/lib.rs"), "pub mod auth;\n"),
+            artifact("t1", None, "design notes"),
+            artifact("t2", Some("src/other.rs"), "fn other() {}\n"),
+        ];
+
+        let changeset = ReviewChangeset::build(&task, &artifacts, dir.path()).unwrap();
+        let statuses: Vec<_> = changeset.files.iter().map(|f| (f.path.as_str(), f.status)).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ("src/api.rs", FileStatus::Added),
+                ("src/auth.rs", FileStatus::Modified),
+                ("src/lib.rs", FileStatus::Unchanged),
+            ]
+        );
+        assert_eq!(changeset.skipped, 1);
+        assert_eq!((changeset.additions(), changeset.deletions()), (3, 2));
+
+        let md = changeset.to_markdown();
+        assert!(md.contains("2 files changed, +3 -2"));
+        assert!(md.contains("| `src/lib.rs` | unchanged | 0 | 0 |"));
+        assert!(md.contains("+    !token.is_empty()"));
+
+        let chunks = changeset.chunks(DEFAULT_CHUNK_CHARS);
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].files, vec!["src/api.rs", "src/auth.rs"]);
+
+        let escape = vec![artifact("t1", Some("../etc/passwd"), "x")];
+        assert!(ReviewChangeset::build(&task, &escape, dir.path()).is_err());
+    }
+
+    #[test]
+    fn test_large_file_splits_between_hunks() {
+        let dir = tempfile::tempdir().unwrap();
+        let old: String = (0..200).map(|i| format!("line {}\n", i)).collect();
+        std::fs::write(dir.path().join("big.txt"), &old).unwrap();
+        let new = old.replace("line 10\n", "line ten\n").replace("line 150\n", "line one-fifty\n");
+
+        let changeset =
+            ReviewChangeset::build(&"t1".to_string(), &[artifact("t1", Some("big.txt"), &new)], dir.path()).unwrap();
+        assert_eq!(changeset.files[0].hunks.len(), 2);
+
+        let whole = changeset.files[0].patch().len();
+        let chunks = changeset.chunks(whole - 1);
+        assert_eq!(chunks.len(), 2);
+        for chunk in &chunks {
+            assert!(chunk.diff.starts_with(&changeset.files[0].header));
+            assert_eq!(chunk.files, vec!["big.txt"]);
+        }
+        assert_eq!(chunks[1].heading(), "Changeset part 2 of 2: big.txt");
+    }
+}
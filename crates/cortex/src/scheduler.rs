@@ -0,0 +1,299 @@
+//! Scheduled and recurring intents
+//!
+//! A schedule pairs a cron expression with an intent and a budget envelope,
+//! e.g. `nightly: run dependency audit and open issues for new CVEs`.
+//! Schedules persist to a JSON file so they survive restarts; each due run
+//! gets its own ledger entity funded with the envelope, and its planning
+//! calls go through a [`BilledClient`] so the Router leases against it.
+//! [`Cortex::start_scheduler`](crate::Cortex::start_scheduler) runs due
+//! intents until shutdown.
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use cron::Schedule;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use uuid::Uuid;
+use zed42_llm::{EmbeddingRequest, EmbeddingResponse, LlmClient, LlmRequest, LlmResponse, StreamChunk};
+
+/// Named shorthands accepted before the `:` in an intent spec
+const ALIASES: &[(&str, &str)] = &[
+    ("hourly", "0 0 * * * *"),
+    ("nightly", "0 0 2 * * *"),
+    ("daily", "0 0 9 * * *"),
+    ("weekly", "0 0 9 * * Mon"),
+    ("monthly", "0 0 9 1 * *"),
+];
+
+/// A recurring intent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledIntent {
+    pub id: String,
+    /// Cron expression (seconds precision: `sec min hour day month weekday`)
+    pub cron: String,
+    pub intent: String,
+    /// Spend cap for each run
+    pub budget: Decimal,
+    pub enabled: bool,
+    pub next_run: Option<DateTime<Utc>>,
+    pub last_run: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ScheduledIntent {
+    /// Parse `"<alias or cron(...)>: <intent>"`
+    pub fn parse(spec: &str, budget: Decimal) -> Result<Self> {
+        let (when, intent) = spec
+            .split_once(':')
+            .ok_or_else(|| anyhow!("Expected '<schedule>: <intent>', got '{}'", spec))?;
+        let when = when.trim();
+        let intent = intent.trim();
+        if intent.is_empty() {
+            return Err(anyhow!("Scheduled intent is empty"));
+        }
+
+        let cron = match ALIASES.iter().find(|(alias, _)| alias.eq_ignore_ascii_case(when)) {
+            Some((_, expr)) => expr.to_string(),
+            None => when
+                .strip_prefix("cron(")
+                .and_then(|s| s.strip_suffix(')'))
+                .ok_or_else(|| anyhow!("Unknown schedule '{}' (use an alias or cron(...))", when))?
+                .to_string(),
+        };
+
+        Self::new(&cron, intent, budget)
+    }
+
+    pub fn new(cron: &str, intent: &str, budget: Decimal) -> Result<Self> {
+        let schedule = Schedule::from_str(cron).map_err(|e| anyhow!("Invalid cron '{}': {}", cron, e))?;
+        Ok(Self {
+            id: Uuid::new_v4().to_string(),
+            cron: cron.to_string(),
+            intent: intent.to_string(),
+            budget,
+            enabled: true,
+            next_run: schedule.upcoming(Utc).next(),
+            last_run: None,
+            created_at: Utc::now(),
+        })
+    }
+
+    fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        Schedule::from_str(&self.cron).ok()?.after(&after).next()
+    }
+}
+
+/// A run that is due now
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduledRun {
+    pub schedule_id: String,
+    pub run_id: String,
+    pub intent: String,
+    /// Ledger entity funded for this run
+    pub budget_entity: String,
+    pub budget: Decimal,
+    pub scheduled_for: DateTime<Utc>,
+}
+
+/// A due run and how starting it went
+#[derive(Debug)]
+pub struct ScheduledRunOutcome {
+    pub run: ScheduledRun,
+    /// Why funding or planning the run failed; `None` once it started
+    pub error: Option<anyhow::Error>,
+}
+
+/// LLM client billing every request to a scheduled run's budget entity
+///
+/// The Router leases against a request's agent id, so calls made through
+/// this client draw on the run's envelope instead of a shared budget.
+pub struct BilledClient {
+    inner: Arc<dyn LlmClient>,
+    entity: String,
+}
+
+impl BilledClient {
+    pub fn new(inner: Arc<dyn LlmClient>, entity: impl Into<String>) -> Self {
+        Self { inner, entity: entity.into() }
+    }
+}
+
+#[async_trait]
+impl LlmClient for BilledClient {
+    async fn complete(&self, mut request: LlmRequest) -> zed42_llm::Result<LlmResponse> {
+        request.agent_id = Some(self.entity.clone());
+        self.inner.complete(request).await
+    }
+
+    async fn stream(&self, mut request: LlmRequest) -> zed42_llm::Result<Vec<StreamChunk>> {
+        request.agent_id = Some(self.entity.clone());
+        self.inner.stream(request).await
+    }
+
+    async fn embed(&self, request: EmbeddingRequest) -> zed42_llm::Result<EmbeddingResponse> {
+        self.inner.embed(request).await
+    }
+}
+
+/// Persistent set of schedules
+#[derive(Debug, Default)]
+pub struct IntentScheduler {
+    path: Option<PathBuf>,
+    schedules: Vec<ScheduledIntent>,
+}
+
+impl IntentScheduler {
+    /// In-memory scheduler (nothing persisted)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load schedules from `path`, starting empty if the file does not exist
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let schedules = if path.exists() {
+            let content = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read schedules from {}", path.display()))?;
+            serde_json::from_str(&content).context("Failed to parse schedules")?
+        } else {
+            Vec::new()
+        };
+        Ok(Self {
+            path: Some(path),
+            schedules,
+        })
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn list(&self) -> &[ScheduledIntent] {
+        &self.schedules
+    }
+
+    #[cfg(test)]
+    pub(crate) fn schedules_mut(&mut self) -> &mut [ScheduledIntent] {
+        &mut self.schedules
+    }
+
+    /// Add a schedule from a spec like `nightly: run dependency audit`
+    pub fn add(&mut self, spec: &str, budget: Decimal) -> Result<ScheduledIntent> {
+        let schedule = ScheduledIntent::parse(spec, budget)?;
+        self.schedules.push(schedule.clone());
+        self.save()?;
+        Ok(schedule)
+    }
+
+    pub fn remove(&mut self, id: &str) -> Result<bool> {
+        let before = self.schedules.len();
+        self.schedules.retain(|s| s.id != id);
+        let removed = self.schedules.len() != before;
+        if removed {
+            self.save()?;
+        }
+        Ok(removed)
+    }
+
+    pub fn set_enabled(&mut self, id: &str, enabled: bool) -> Result<bool> {
+        let Some(schedule) = self.schedules.iter_mut().find(|s| s.id == id) else {
+            return Ok(false);
+        };
+        schedule.enabled = enabled;
+        self.save()?;
+        Ok(true)
+    }
+
+    /// Earliest upcoming run across enabled schedules
+    pub fn next_due(&self) -> Option<DateTime<Utc>> {
+        self.schedules
+            .iter()
+            .filter(|s| s.enabled)
+            .filter_map(|s| s.next_run)
+            .min()
+    }
+
+    /// Collect runs due at `now` and advance their schedules
+    ///
+    /// A schedule that missed several slots (e.g. the machine was off) runs
+    /// once, not once per missed slot.
+    pub fn take_due(&mut self, now: DateTime<Utc>) -> Result<Vec<ScheduledRun>> {
+        let mut runs = Vec::new();
+        for schedule in self.schedules.iter_mut().filter(|s| s.enabled) {
+            let Some(due) = schedule.next_run.filter(|t| *t <= now) else {
+                continue;
+            };
+            let run_id = Uuid::new_v4().to_string();
+            runs.push(ScheduledRun {
+                schedule_id: schedule.id.clone(),
+                budget_entity: format!("schedule-{}-{}", schedule.id, run_id),
+                run_id,
+                intent: schedule.intent.clone(),
+                budget: schedule.budget,
+                scheduled_for: due,
+            });
+            schedule.last_run = Some(now);
+            schedule.next_run = schedule.next_after(now);
+        }
+        if !runs.is_empty() {
+            self.save()?;
+        }
+        Ok(runs)
+    }
+
+    fn save(&self) -> Result<()> {
+        let Some(ref path) = self.path else { return Ok(()) };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let content = serde_json::to_string_pretty(&self.schedules)?;
+        std::fs::write(path, content)
+            .with_context(|| format!("Failed to write schedules to {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_parse_specs() {
+        let nightly = ScheduledIntent::parse("nightly: run dependency audit", Decimal::new(5, 0)).unwrap();
+        assert_eq!(nightly.cron, "0 0 2 * * *");
+        assert_eq!(nightly.intent, "run dependency audit");
+
+        let custom = ScheduledIntent::parse("cron(0 30 6 * * Mon-Fri): triage issues", Decimal::ONE).unwrap();
+        assert_eq!(custom.cron, "0 30 6 * * Mon-Fri");
+
+        assert!(ScheduledIntent::parse("sometimes: do things", Decimal::ONE).is_err());
+        assert!(ScheduledIntent::parse("no schedule here", Decimal::ONE).is_err());
+    }
+
+    #[test]
+    fn test_due_runs_persist_and_advance() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("schedules.json");
+
+        let mut scheduler = IntentScheduler::load(&path).unwrap();
+        let schedule = scheduler.add("hourly: check CI health", Decimal::new(2, 0)).unwrap();
+        let first = schedule.next_run.unwrap();
+
+        assert!(scheduler.take_due(first - Duration::seconds(1)).unwrap().is_empty());
+
+        // Three missed slots still produce one run
+        let runs = scheduler.take_due(first + Duration::hours(3)).unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].intent, "check CI health");
+        assert_eq!(runs[0].budget, Decimal::new(2, 0));
+
+        let reloaded = IntentScheduler::load(&path).unwrap();
+        assert_eq!(reloaded.list().len(), 1);
+        assert!(reloaded.list()[0].next_run.unwrap() > first + Duration::hours(3));
+        assert!(reloaded.list()[0].last_run.is_some());
+    }
+}
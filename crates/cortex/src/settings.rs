@@ -0,0 +1,191 @@
+//! Runtime settings editing
+//!
+//! Operators change budgets, model profiles and approval gates mid-session
+//! through [`SettingsService`] (the desktop UI's settings commands call it)
+//! instead of editing SurrealDB rows by hand. Every change is validated
+//! first and recorded as a system audit entry in the ledger's hash chain,
+//! naming who made it and what changed, and shows up on the event bus as a
+//! `settings_changed` alert.
+
+use anyhow::Result;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use zed42_core::ledger::LedgerEntry;
+use zed42_ledger::types::Budget;
+use zed42_ledger::IntelligenceLedger;
+use zed42_mom::types::ExecutionProfile;
+use zed42_mom::Router;
+
+use crate::dashboard::{AlertView, DashboardEvent, EventBus};
+use crate::presets::ApprovalGate;
+use crate::Cortex;
+
+/// New limits for a budget
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BudgetEdit {
+    pub entity_id: String,
+    pub hard_limit: Decimal,
+    pub soft_limit: Decimal,
+}
+
+/// Ledger entity approval-gate changes of `target` are audited under
+pub fn gate_audit_entity(target: &str) -> String {
+    format!("approval_gates:{}", target)
+}
+
+/// Validated, audited edits of runtime settings
+pub struct SettingsService {
+    ledger: Arc<IntelligenceLedger>,
+    router: Option<Arc<Router>>,
+    events: Option<Arc<EventBus>>,
+}
+
+impl SettingsService {
+    pub fn new(ledger: Arc<IntelligenceLedger>) -> Self {
+        Self { ledger, router: None, events: None }
+    }
+
+    /// Allow editing execution profiles through this router
+    pub fn with_router(mut self, router: Arc<Router>) -> Self {
+        self.router = Some(router);
+        self
+    }
+
+    /// Announce changes on the dashboard event bus
+    pub fn with_events(mut self, events: Arc<EventBus>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    pub async fn budget(&self, entity_id: &str) -> Result<Option<Budget>> {
+        Ok(self.ledger.get_budget(entity_id).await?)
+    }
+
+    /// Change a budget's limits
+    pub async fn update_budget(&self, edit: BudgetEdit, actor: &str) -> Result<Budget> {
+        let budget = self
+            .ledger
+            .update_budget_limits(&edit.entity_id, edit.hard_limit, edit.soft_limit, actor)
+            .await?;
+        if let Some(events) = &self.events {
+            events.publish(DashboardEvent::Budget { budget: budget.clone() });
+        }
+        self.announce(
+            actor,
+            format!("budget {}: hard {}, soft {}", edit.entity_id, edit.hard_limit, edit.soft_limit),
+        );
+        Ok(budget)
+    }
+
+    pub async fn execution_profile(&self, agent_id: &str) -> Result<Option<ExecutionProfile>> {
+        self.router()?.execution_profile(agent_id).await
+    }
+
+    /// Replace an agent's model ladder
+    pub async fn set_execution_profile(&self, profile: ExecutionProfile, actor: &str) -> Result<()> {
+        let agent_id = profile.agent_id.clone();
+        self.router()?.set_execution_profile(profile, actor).await?;
+        self.announce(actor, format!("execution profile of {}", agent_id));
+        Ok(())
+    }
+
+    /// Replace the approval gates of a queued run or preset on `cortex`
+    pub async fn set_approval_gates(
+        &self,
+        cortex: &mut Cortex,
+        target: &str,
+        gates: Vec<ApprovalGate>,
+        actor: &str,
+    ) -> Result<Vec<ApprovalGate>> {
+        let previous = cortex.set_approval_gates(target, gates)?;
+        let current = cortex
+            .run_preset(target)
+            .or_else(|| cortex.presets().get(target))
+            .map(|preset| preset.approval_gates.clone())
+            .unwrap_or_default();
+        let details = format!("Approval gates of {} changed by {}: {:?} -> {:?}", target, actor, previous, current);
+        self.ledger.record_audit(&gate_audit_entity(target), details).await?;
+        self.announce(actor, format!("approval gates of {}: {:?}", target, current));
+        Ok(current)
+    }
+
+    /// Settings changes and other audit entries, oldest first
+    pub async fn audit_log(&self, entity_id: Option<&str>) -> Result<Vec<LedgerEntry>> {
+        Ok(self.ledger.audit_log(entity_id).await?)
+    }
+
+    fn router(&self) -> Result<&Router> {
+        self.router
+            .as_deref()
+            .ok_or_else(|| anyhow::anyhow!("Execution profiles can't be edited without a router"))
+    }
+
+    fn announce(&self, actor: &str, change: String) {
+        tracing::info!(actor, %change, "Settings changed");
+        if let Some(events) = &self.events {
+            events.publish(DashboardEvent::Alert {
+                alert: AlertView {
+                    action: "settings_changed".to_string(),
+                    agent_id: None,
+                    reason: format!("{} changed {}", actor, change),
+                    at: chrono::Utc::now(),
+                },
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use zed42_ledger::types::BudgetStatus;
+
+    #[tokio::test]
+    async fn test_edits_are_validated_and_audited() {
+        let db = surrealdb::engine::any::connect("mem://").await.unwrap();
+        db.use_ns("zed42").use_db("ledger").await.unwrap();
+        let ledger = Arc::new(IntelligenceLedger::new(db));
+        ledger
+            .set_budget(Budget {
+                entity_id: "session".to_string(),
+                hard_limit: Decimal::new(5, 0),
+                soft_limit: Decimal::new(4, 0),
+                spent: Decimal::new(5, 0),
+                currency: "USD".to_string(),
+                status: BudgetStatus::Depleted,
+                updated_at: Utc::now(),
+                tags: Default::default(),
+            })
+            .await
+            .unwrap();
+        let events = Arc::new(EventBus::new());
+        let settings = SettingsService::new(ledger.clone()).with_events(events.clone());
+
+        let invalid = BudgetEdit { entity_id: "session".to_string(), hard_limit: Decimal::new(8, 0), soft_limit: Decimal::new(9, 0) };
+        assert!(settings.update_budget(invalid, "ops").await.is_err());
+
+        let edit = BudgetEdit { entity_id: "session".to_string(), hard_limit: Decimal::new(10, 0), soft_limit: Decimal::new(8, 0) };
+        let budget = settings.update_budget(edit, "ops").await.unwrap();
+        assert_eq!(budget.status, BudgetStatus::Active);
+        assert_eq!(events.snapshot().budgets["session"].budget.hard_limit, Decimal::new(10, 0));
+
+        let mut cortex = Cortex::new(uuid::Uuid::new_v4());
+        let gates = settings
+            .set_approval_gates(&mut cortex, "fast-and-cheap", vec![ApprovalGate::PlanReview, ApprovalGate::PlanReview], "ops")
+            .await
+            .unwrap();
+        assert_eq!(gates, vec![ApprovalGate::PlanReview]);
+        assert!(cortex.presets().get("fast-and-cheap").unwrap().requires(ApprovalGate::PlanReview));
+        assert!(settings.set_approval_gates(&mut cortex, "missing", Vec::new(), "ops").await.is_err());
+        assert!(settings.set_execution_profile(ExecutionProfile::new("a", Default::default()), "ops").await.is_err());
+
+        let audit = settings.audit_log(Some("session")).await.unwrap();
+        assert_eq!(audit.len(), 1);
+        assert!(audit[0].details.contains("changed by ops: hard 5 -> 10"));
+        assert_eq!(settings.audit_log(Some(&gate_audit_entity("fast-and-cheap"))).await.unwrap().len(), 1);
+        assert!(events.snapshot().alerts.iter().all(|alert| alert.action == "settings_changed"));
+        assert_eq!(events.snapshot().alerts.len(), 2);
+    }
+}
@@ -0,0 +1,553 @@
+//! Worker-process execution
+//!
+//! In worker mode the Cortex runs each agent as its own OS process (the
+//! `zed42-worker` binary) instead of a task in this one. Workers talk to
+//! the rest of the run only over the MOM substrate, so a worker that leaks
+//! memory, spins or panics takes down itself and nothing else.
+//!
+//! Each worker is started under the configured [`ResourceLimits`] (address
+//! space and CPU time, enforced by the kernel via `setrlimit`) and watched
+//! by a supervisor task that restarts it after a crash, up to
+//! `max_restarts` times, and reports every transition as a [`WorkerEvent`].
+
+use anyhow::{Context, Result};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::{ExitStatus, Stdio};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::process::{Child, Command};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use zed42_agents::AgentType;
+use zed42_core::types::{AgentId, SessionId};
+use zed42_core::CancellationToken;
+
+/// Environment passed to every worker process
+pub const ENV_AGENT_ID: &str = "ZED42_WORKER_AGENT_ID";
+pub const ENV_AGENT_TYPE: &str = "ZED42_WORKER_AGENT_TYPE";
+pub const ENV_SESSION_ID: &str = "ZED42_WORKER_SESSION_ID";
+pub const ENV_MOM_ADDR: &str = "ZED42_WORKER_MOM_ADDR";
+pub const ENV_DATA_DIR: &str = "ZED42_WORKER_DATA_DIR";
+
+/// Kernel-enforced limits applied to each worker process
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourceLimits {
+    /// Address space cap (RLIMIT_AS); allocations past it fail
+    pub memory_bytes: Option<u64>,
+    /// CPU time cap (RLIMIT_CPU); the process gets SIGXCPU past it
+    pub cpu_seconds: Option<u64>,
+}
+
+/// How the Cortex launches and supervises workers
+#[derive(Debug, Clone)]
+pub struct WorkerConfig {
+    /// Worker executable, normally `zed42-worker`
+    pub program: PathBuf,
+    pub args: Vec<String>,
+    /// MOM substrate the workers connect to
+    pub mom_addr: String,
+    /// Parent directory for each worker's local blackboard data
+    pub data_dir: PathBuf,
+    pub limits: ResourceLimits,
+    /// Restarts allowed per worker after abnormal exits
+    pub max_restarts: u32,
+    pub restart_backoff: Duration,
+    /// Time between SIGTERM and SIGKILL when stopping a worker
+    pub stop_grace: Duration,
+}
+
+impl WorkerConfig {
+    pub fn new(program: impl Into<PathBuf>) -> Self {
+        Self {
+            program: program.into(),
+            args: Vec::new(),
+            mom_addr: "ws://localhost:8000".to_string(),
+            data_dir: std::env::temp_dir().join("zed42-workers"),
+            limits: ResourceLimits::default(),
+            max_restarts: 3,
+            restart_backoff: Duration::from_secs(1),
+            stop_grace: Duration::from_secs(5),
+        }
+    }
+
+    pub fn with_args(mut self, args: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.args = args.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn with_mom_addr(mut self, addr: impl Into<String>) -> Self {
+        self.mom_addr = addr.into();
+        self
+    }
+
+    pub fn with_data_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.data_dir = dir.into();
+        self
+    }
+
+    pub fn with_limits(mut self, limits: ResourceLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn with_max_restarts(mut self, max_restarts: u32, backoff: Duration) -> Self {
+        self.max_restarts = max_restarts;
+        self.restart_backoff = backoff;
+        self
+    }
+
+    pub fn with_stop_grace(mut self, grace: Duration) -> Self {
+        self.stop_grace = grace;
+        self
+    }
+}
+
+/// Lifecycle transitions reported by the supervisors
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum WorkerEvent {
+    Started { agent_id: AgentId, pid: u32 },
+    /// The process exited; `success` is false for crashes and limit kills
+    Exited {
+        agent_id: AgentId,
+        success: bool,
+        code: Option<i32>,
+        signal: Option<i32>,
+    },
+    Restarting { agent_id: AgentId, attempt: u32 },
+    /// Crashed more often than `max_restarts` allows
+    GaveUp { agent_id: AgentId, restarts: u32 },
+    Stopped { agent_id: AgentId },
+}
+
+/// Snapshot of one worker
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkerStatus {
+    pub agent_id: AgentId,
+    pub agent_type: AgentType,
+    /// None between a crash and the restart, and after the worker ends
+    pub pid: Option<u32>,
+    pub restarts: u32,
+    pub running: bool,
+}
+
+struct Worker {
+    status: WorkerStatus,
+    stop: CancellationToken,
+    supervisor: Option<JoinHandle<()>>,
+}
+
+/// Spawns and supervises agent worker processes
+pub struct WorkerPool {
+    config: Arc<WorkerConfig>,
+    session_id: SessionId,
+    workers: Arc<Mutex<HashMap<AgentId, Worker>>>,
+    events: broadcast::Sender<WorkerEvent>,
+}
+
+impl WorkerPool {
+    pub fn new(config: WorkerConfig, session_id: SessionId) -> Self {
+        let (events, _) = broadcast::channel(256);
+        Self {
+            config: Arc::new(config),
+            session_id,
+            workers: Arc::new(Mutex::new(HashMap::new())),
+            events,
+        }
+    }
+
+    pub fn config(&self) -> &WorkerConfig {
+        &self.config
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<WorkerEvent> {
+        self.events.subscribe()
+    }
+
+    /// Start a supervised worker running `agent_type` as `agent_id`
+    pub fn spawn(&self, agent_id: AgentId, agent_type: AgentType) -> Result<()> {
+        anyhow::ensure!(!self.workers.lock().contains_key(&agent_id), "Worker {} already exists", agent_id);
+        let agent_type_json = serde_json::to_string(&agent_type)?;
+        let data_dir = self.config.data_dir.join(agent_id.to_string());
+        std::fs::create_dir_all(&data_dir).context("Failed to create worker data directory")?;
+
+        let mut command = Command::new(&self.config.program);
+        command
+            .args(&self.config.args)
+            .env(ENV_AGENT_ID, agent_id.to_string())
+            .env(ENV_AGENT_TYPE, agent_type_json)
+            .env(ENV_SESSION_ID, self.session_id.to_string())
+            .env(ENV_MOM_ADDR, &self.config.mom_addr)
+            .env(ENV_DATA_DIR, &data_dir)
+            .stdin(Stdio::null())
+            .kill_on_drop(true);
+        apply_limits(&mut command, &self.config.limits);
+
+        // Fail fast on a bad program path instead of inside the supervisor
+        let child = command
+            .spawn()
+            .with_context(|| format!("Failed to start worker {}", self.config.program.display()))?;
+
+        let stop = CancellationToken::new();
+        self.workers.lock().insert(
+            agent_id,
+            Worker {
+                status: WorkerStatus {
+                    agent_id,
+                    agent_type,
+                    pid: child.id(),
+                    restarts: 0,
+                    running: true,
+                },
+                stop: stop.clone(),
+                supervisor: None,
+            },
+        );
+        let supervisor = tokio::spawn(supervise(
+            agent_id,
+            command,
+            child,
+            self.config.clone(),
+            self.workers.clone(),
+            self.events.clone(),
+            stop,
+        ));
+        if let Some(worker) = self.workers.lock().get_mut(&agent_id) {
+            worker.supervisor = Some(supervisor);
+        }
+        Ok(())
+    }
+
+    /// Stop a worker (SIGTERM, then SIGKILL after the grace period) and forget it
+    pub async fn stop(&self, agent_id: AgentId) -> bool {
+        let Some(worker) = self.workers.lock().remove(&agent_id) else {
+            return false;
+        };
+        worker.stop.cancel();
+        if let Some(supervisor) = worker.supervisor {
+            let _ = supervisor.await;
+        }
+        true
+    }
+
+    /// Stop every worker concurrently, returning how many were stopped
+    pub async fn stop_all(&self) -> usize {
+        let workers: Vec<Worker> = self.workers.lock().drain().map(|(_, worker)| worker).collect();
+        for worker in &workers {
+            worker.stop.cancel();
+        }
+        let count = workers.len();
+        for worker in workers {
+            if let Some(supervisor) = worker.supervisor {
+                let _ = supervisor.await;
+            }
+        }
+        count
+    }
+
+    pub fn status(&self, agent_id: AgentId) -> Option<WorkerStatus> {
+        self.workers.lock().get(&agent_id).map(|w| w.status.clone())
+    }
+
+    pub fn statuses(&self) -> Vec<WorkerStatus> {
+        self.workers.lock().values().map(|w| w.status.clone()).collect()
+    }
+
+    /// Workers still supervised (running or waiting to restart)
+    pub fn len(&self) -> usize {
+        self.workers.lock().values().filter(|w| w.status.running).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn contains(&self, agent_id: AgentId) -> bool {
+        self.workers.lock().contains_key(&agent_id)
+    }
+}
+
+/// Run one worker until it exits cleanly, is stopped, or exhausts its restarts
+async fn supervise(
+    agent_id: AgentId,
+    mut command: Command,
+    mut child: Child,
+    config: Arc<WorkerConfig>,
+    workers: Arc<Mutex<HashMap<AgentId, Worker>>>,
+    events: broadcast::Sender<WorkerEvent>,
+    stop: CancellationToken,
+) {
+    let mut restarts = 0;
+    loop {
+        if let Some(pid) = child.id() {
+            tracing::info!(agent = %agent_id, pid, "Worker started");
+            let _ = events.send(WorkerEvent::Started { agent_id, pid });
+        }
+
+        let status = tokio::select! {
+            status = child.wait() => status,
+            _ = stop.cancelled() => {
+                terminate(&mut child, config.stop_grace).await;
+                tracing::info!(agent = %agent_id, "Worker stopped");
+                let _ = events.send(WorkerEvent::Stopped { agent_id });
+                return;
+            }
+        };
+
+        let exit = match status {
+            Ok(status) => exit_event(agent_id, status),
+            Err(e) => {
+                tracing::error!(agent = %agent_id, error = %e, "Failed to wait on worker");
+                WorkerEvent::Exited { agent_id, success: false, code: None, signal: None }
+            }
+        };
+        let crashed = matches!(exit, WorkerEvent::Exited { success: false, .. });
+        let _ = events.send(exit.clone());
+        update(&workers, agent_id, |s| s.pid = None);
+
+        if !crashed {
+            tracing::info!(agent = %agent_id, "Worker finished");
+            update(&workers, agent_id, |s| s.running = false);
+            return;
+        }
+        tracing::warn!(agent = %agent_id, ?exit, "Worker crashed");
+        if restarts >= config.max_restarts {
+            tracing::error!(agent = %agent_id, restarts, "Worker exceeded its restart limit");
+            let _ = events.send(WorkerEvent::GaveUp { agent_id, restarts });
+            update(&workers, agent_id, |s| s.running = false);
+            return;
+        }
+
+        restarts += 1;
+        let _ = events.send(WorkerEvent::Restarting { agent_id, attempt: restarts });
+        tokio::select! {
+            _ = tokio::time::sleep(config.restart_backoff * restarts) => {}
+            _ = stop.cancelled() => {
+                let _ = events.send(WorkerEvent::Stopped { agent_id });
+                return;
+            }
+        }
+        child = match command.spawn() {
+            Ok(child) => child,
+            Err(e) => {
+                tracing::error!(agent = %agent_id, error = %e, "Failed to restart worker");
+                let _ = events.send(WorkerEvent::GaveUp { agent_id, restarts });
+                update(&workers, agent_id, |s| s.running = false);
+                return;
+            }
+        };
+        update(&workers, agent_id, |s| {
+            s.pid = child.id();
+            s.restarts = restarts;
+        });
+    }
+}
+
+fn update(workers: &Mutex<HashMap<AgentId, Worker>>, agent_id: AgentId, f: impl FnOnce(&mut WorkerStatus)) {
+    if let Some(worker) = workers.lock().get_mut(&agent_id) {
+        f(&mut worker.status);
+    }
+}
+
+fn exit_event(agent_id: AgentId, status: ExitStatus) -> WorkerEvent {
+    #[cfg(unix)]
+    let signal = std::os::unix::process::ExitStatusExt::signal(&status);
+    #[cfg(not(unix))]
+    let signal = None;
+    WorkerEvent::Exited {
+        agent_id,
+        success: status.success(),
+        code: status.code(),
+        signal,
+    }
+}
+
+/// Ask the worker to exit, killing it if it doesn't within `grace`
+async fn terminate(child: &mut Child, grace: Duration) {
+    #[cfg(unix)]
+    if let Some(pid) = child.id() {
+        // SAFETY: plain syscall on a pid we own; a stale pid only yields ESRCH
+        unsafe {
+            libc::kill(pid as libc::pid_t, libc::SIGTERM);
+        }
+        if tokio::time::timeout(grace, child.wait()).await.is_ok() {
+            return;
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = grace;
+    let _ = child.kill().await;
+}
+
+#[cfg(unix)]
+fn apply_limits(command: &mut Command, limits: &ResourceLimits) {
+    let limits = limits.clone();
+    if limits == ResourceLimits::default() {
+        return;
+    }
+    // SAFETY: only async-signal-safe setrlimit calls run between fork and exec
+    unsafe {
+        command.pre_exec(move || {
+            let set = |resource, value: u64| {
+                let limit = libc::rlimit {
+                    rlim_cur: value as libc::rlim_t,
+                    rlim_max: value as libc::rlim_t,
+                };
+                if libc::setrlimit(resource, &limit) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                Ok(())
+            };
+            if let Some(bytes) = limits.memory_bytes {
+                set(libc::RLIMIT_AS, bytes)?;
+            }
+            if let Some(seconds) = limits.cpu_seconds {
+                set(libc::RLIMIT_CPU, seconds)?;
+            }
+            Ok(())
+        });
+    }
+}
+
+#[cfg(not(unix))]
+fn apply_limits(_command: &mut Command, limits: &ResourceLimits) {
+    if *limits != ResourceLimits::default() {
+        tracing::warn!("Worker resource limits are only enforced on unix");
+    }
+}
+
+/// Worker-side view of the environment set by [`WorkerPool::spawn`]
+#[derive(Debug, Clone)]
+pub struct WorkerEnv {
+    pub agent_id: AgentId,
+    pub agent_type: AgentType,
+    pub session_id: SessionId,
+    pub mom_addr: String,
+    pub data_dir: PathBuf,
+}
+
+impl WorkerEnv {
+    pub fn from_env() -> Result<Self> {
+        let var = |name: &str| std::env::var(name).with_context(|| format!("{} is not set; start workers through the Cortex", name));
+        Ok(Self {
+            agent_id: var(ENV_AGENT_ID)?.parse().context("Invalid worker agent id")?,
+            agent_type: serde_json::from_str(&var(ENV_AGENT_TYPE)?).context("Invalid worker agent type")?,
+            session_id: var(ENV_SESSION_ID)?.parse().context("Invalid worker session id")?,
+            mom_addr: var(ENV_MOM_ADDR)?,
+            data_dir: var(ENV_DATA_DIR)?.into(),
+        })
+    }
+}
+
+/// Body of a worker process: join the MOM substrate as the agent until `shutdown`
+///
+/// The worker registers itself for direct channels (peers in other
+/// processes fall back to blackboard messages), follows its team's VOX
+/// traffic and keeps its AURA pulse alive.
+pub async fn run_worker(env: WorkerEnv, shutdown: CancellationToken) -> Result<()> {
+    let blackboard = zed42_blackboard::BlackboardDb::new(&env.data_dir, &env.session_id.to_string(), &env.mom_addr).await?;
+    blackboard.register_direct_endpoint(env.agent_id).await?;
+    let mut vox = blackboard.subscribe(env.agent_type.team());
+    let mut pulse = tokio::time::interval(Duration::from_secs(30));
+    tracing::info!(agent = %env.agent_id, agent_type = ?env.agent_type, "Worker joined MOM substrate");
+
+    loop {
+        tokio::select! {
+            _ = shutdown.cancelled() => break,
+            _ = pulse.tick() => {
+                if let Err(e) = blackboard.send_pulse(env.agent_id, zed42_core::AgentStatus::Working).await {
+                    tracing::warn!(error = %e, "Failed to send worker pulse");
+                }
+            }
+            Ok(msg) = vox.recv() => {
+                tracing::debug!(agent = %env.agent_id, sender = %msg.sender, "Worker observed VOX message");
+            }
+        }
+    }
+
+    tracing::info!(agent = %env.agent_id, "Worker shutting down");
+    blackboard.watcher_token().cancel();
+    Ok(())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn config(script: &str, max_restarts: u32) -> WorkerConfig {
+        let dir = std::env::temp_dir().join(format!("zed42-worker-test-{}", uuid::Uuid::new_v4()));
+        WorkerConfig::new("/bin/sh")
+            .with_args(["-c", script])
+            .with_data_dir(dir)
+            .with_max_restarts(max_restarts, Duration::from_millis(10))
+            .with_stop_grace(Duration::from_millis(500))
+    }
+
+    fn pool(script: &str, max_restarts: u32) -> WorkerPool {
+        WorkerPool::new(config(script, max_restarts), uuid::Uuid::new_v4())
+    }
+
+    async fn next_event(events: &mut broadcast::Receiver<WorkerEvent>) -> WorkerEvent {
+        tokio::time::timeout(Duration::from_secs(5), events.recv()).await.unwrap().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_crashing_worker_is_restarted_then_abandoned() {
+        let pool = pool("exit 3", 1);
+        let mut events = pool.subscribe();
+        let agent_id = uuid::Uuid::new_v4();
+        pool.spawn(agent_id, AgentType::FeatureImplementer).unwrap();
+
+        let mut seen = Vec::new();
+        loop {
+            let event = next_event(&mut events).await;
+            let done = matches!(event, WorkerEvent::GaveUp { .. });
+            seen.push(event);
+            if done {
+                break;
+            }
+        }
+        assert!(seen.contains(&WorkerEvent::Exited { agent_id, success: false, code: Some(3), signal: None }));
+        assert!(seen.contains(&WorkerEvent::Restarting { agent_id, attempt: 1 }));
+        assert_eq!(seen.last(), Some(&WorkerEvent::GaveUp { agent_id, restarts: 1 }));
+        assert_eq!(pool.len(), 0);
+        assert_eq!(pool.status(agent_id).unwrap().restarts, 1);
+    }
+
+    #[tokio::test]
+    async fn test_stop_terminates_worker() {
+        let pool = pool("sleep 30", 0);
+        let mut events = pool.subscribe();
+        let agent_id = uuid::Uuid::new_v4();
+        pool.spawn(agent_id, AgentType::TestEngineer).unwrap();
+        assert!(matches!(next_event(&mut events).await, WorkerEvent::Started { .. }));
+        assert_eq!(pool.len(), 1);
+
+        assert!(pool.stop(agent_id).await);
+        assert_eq!(next_event(&mut events).await, WorkerEvent::Stopped { agent_id });
+        assert!(!pool.contains(agent_id));
+    }
+
+    #[tokio::test]
+    async fn test_memory_limit_is_applied() {
+        // ulimit reports the address space cap in KiB
+        let limits = ResourceLimits {
+            memory_bytes: Some(256 * 1024 * 1024),
+            cpu_seconds: Some(10),
+        };
+        let pool = WorkerPool::new(
+            config("test \"$(ulimit -v)\" = 262144 && test \"$(ulimit -t)\" = 10", 0).with_limits(limits),
+            uuid::Uuid::new_v4(),
+        );
+        let mut events = pool.subscribe();
+        let agent_id = uuid::Uuid::new_v4();
+        pool.spawn(agent_id, AgentType::Refactorer).unwrap();
+
+        next_event(&mut events).await;
+        let exit = next_event(&mut events).await;
+        assert_eq!(exit, WorkerEvent::Exited { agent_id, success: true, code: Some(0), signal: None });
+    }
+}
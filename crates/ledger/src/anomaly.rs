@@ -0,0 +1,75 @@
+//! Anomaly rules for catching runaway spend
+//!
+//! Soft and hard caps only trip once most of a budget is gone. Anomaly rules
+//! look at the shape of spending instead (rate, concurrency, single-call cost)
+//! and either warn or freeze the budget, leaving a SystemAudit entry behind.
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// What an anomaly rule measures
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AnomalyKind {
+    /// Settled spend over the trailing minute
+    SpendRate { max_per_minute: Decimal },
+    /// Concurrently active (unexpired) leases for one entity
+    LeaseCount { max_active: usize },
+    /// Cost of a single settled call
+    SingleCallCost { max_cost: Decimal },
+}
+
+/// Response when a rule trips
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnomalyAction {
+    /// Record an audit entry and surface a warning
+    Warn,
+    /// Record an audit entry and freeze the budget
+    Freeze,
+}
+
+/// A configured anomaly rule
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnomalyRule {
+    pub name: String,
+    #[serde(flatten)]
+    pub kind: AnomalyKind,
+    pub action: AnomalyAction,
+}
+
+impl AnomalyRule {
+    pub fn spend_rate(name: impl Into<String>, max_per_minute: Decimal, action: AnomalyAction) -> Self {
+        Self { name: name.into(), kind: AnomalyKind::SpendRate { max_per_minute }, action }
+    }
+
+    pub fn lease_count(name: impl Into<String>, max_active: usize, action: AnomalyAction) -> Self {
+        Self { name: name.into(), kind: AnomalyKind::LeaseCount { max_active }, action }
+    }
+
+    pub fn single_call_cost(name: impl Into<String>, max_cost: Decimal, action: AnomalyAction) -> Self {
+        Self { name: name.into(), kind: AnomalyKind::SingleCallCost { max_cost }, action }
+    }
+}
+
+/// A tripped anomaly rule
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnomalyEvent {
+    pub rule: String,
+    pub entity_id: String,
+    pub observed: Decimal,
+    pub threshold: Decimal,
+    pub action: AnomalyAction,
+    pub detected_at: DateTime<Utc>,
+}
+
+impl std::fmt::Display for AnomalyEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Anomaly '{}' on {}: observed {} exceeds {}",
+            self.rule, self.entity_id, self.observed, self.threshold
+        )
+    }
+}
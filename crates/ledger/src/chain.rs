@@ -0,0 +1,210 @@
+//! Tamper-evident hash chain over ledger entries
+//!
+//! Every entry stores a sequence number, the hash of the previous entry, and
+//! its own SHA-256 hash over its contents. Editing or deleting a row in the
+//! underlying database breaks the chain, which `verify_chain()` reports.
+//! Deleting the newest rows leaves a valid shorter chain, so each append
+//! also records the [`ChainHead`] in the same transaction, and verification
+//! checks that the chain still ends there. Ledgers written before heads were
+//! recorded have no head until their next append, and verify without it.
+//! Receipts can additionally be signed with an HMAC key held by the ledger.
+
+use crate::types::{CostTags, LedgerEntry, Receipt};
+use chrono::SecondsFormat;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Hash of an entry's contents chained to `prev_hash`
+pub fn entry_hash(entry: &LedgerEntry) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(entry.sequence.to_string());
+    hasher.update("|");
+    hasher.update(entry.timestamp.to_rfc3339_opts(SecondsFormat::Micros, true));
+    hasher.update("|");
+    hasher.update(&entry.entity_id);
+    hasher.update("|");
+    hasher.update(entry.lease_id.as_deref().unwrap_or(""));
+    hasher.update("|");
+    hasher.update(format!("{:?}", entry.transaction_type));
+    hasher.update("|");
+    hasher.update(entry.amount.normalize().to_string());
+    hasher.update("|");
+    hasher.update(entry.model.as_deref().unwrap_or(""));
+    hasher.update("|");
+    hasher.update(&entry.details);
+    hasher.update("|");
+    hasher.update(entry.prev_hash.as_deref().unwrap_or(""));
+    // Untagged entries hash as before, so existing chains still verify
+    if !entry.tags.is_empty() {
+        hasher.update("|");
+        hasher.update(tags_payload(&entry.tags));
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// Kind of chain integrity problem
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChainIssueKind {
+    /// Stored hash does not match the entry's contents (edited row)
+    HashMismatch,
+    /// `prev_hash` does not match the preceding entry (reordered or replaced row)
+    BrokenLink,
+    /// Sequence numbers skip (deleted rows)
+    MissingEntries,
+    /// The chain ends before the recorded head (newest rows deleted)
+    Truncated,
+}
+
+/// A single integrity problem
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainIssue {
+    pub sequence: u64,
+    pub kind: ChainIssueKind,
+    pub detail: String,
+}
+
+/// Result of verifying the ledger chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainVerification {
+    pub entries_checked: usize,
+    pub head_hash: Option<String>,
+    pub issues: Vec<ChainIssue>,
+}
+
+impl ChainVerification {
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Sequence and hash of the newest entry, recorded on every append
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainHead {
+    pub sequence: u64,
+    pub hash: String,
+}
+
+/// Verify entries sorted by sequence, and that they end at `head`
+///
+/// Without a head the chain predates head records and only the links are
+/// checked.
+pub fn verify_entries_with_head(entries: &[LedgerEntry], head: Option<&ChainHead>) -> ChainVerification {
+    let mut report = verify_entries(entries);
+    let last = entries.last();
+    let issue = match (head, last) {
+        (None, _) => None,
+        (Some(head), None) => Some(ChainIssue {
+            sequence: head.sequence,
+            kind: ChainIssueKind::Truncated,
+            detail: format!("chain is empty but head {} was recorded", head.sequence),
+        }),
+        (Some(head), Some(last)) if last.sequence < head.sequence => Some(ChainIssue {
+            sequence: head.sequence,
+            kind: ChainIssueKind::Truncated,
+            detail: format!("chain ends at {} but head {} was recorded", last.sequence, head.sequence),
+        }),
+        (Some(head), Some(last)) if last.sequence > head.sequence => Some(ChainIssue {
+            sequence: last.sequence,
+            kind: ChainIssueKind::BrokenLink,
+            detail: format!("entries past the recorded head {}", head.sequence),
+        }),
+        (Some(head), Some(last)) if last.hash.as_deref() != Some(head.hash.as_str()) => Some(ChainIssue {
+            sequence: last.sequence,
+            kind: ChainIssueKind::BrokenLink,
+            detail: "newest entry does not match the recorded head".to_string(),
+        }),
+        _ => None,
+    };
+    report.issues.extend(issue);
+    report
+}
+
+/// Verify entries sorted by sequence
+pub fn verify_entries(entries: &[LedgerEntry]) -> ChainVerification {
+    let mut issues = Vec::new();
+    let mut expected_sequence = 1u64;
+    let mut prev_hash: Option<String> = None;
+
+    for entry in entries {
+        if entry.sequence != expected_sequence {
+            issues.push(ChainIssue {
+                sequence: entry.sequence,
+                kind: ChainIssueKind::MissingEntries,
+                detail: format!("expected sequence {}, found {}", expected_sequence, entry.sequence),
+            });
+        }
+
+        if entry.prev_hash != prev_hash {
+            issues.push(ChainIssue {
+                sequence: entry.sequence,
+                kind: ChainIssueKind::BrokenLink,
+                detail: "prev_hash does not match the preceding entry".to_string(),
+            });
+        }
+
+        let computed = entry_hash(entry);
+        if entry.hash.as_deref() != Some(computed.as_str()) {
+            issues.push(ChainIssue {
+                sequence: entry.sequence,
+                kind: ChainIssueKind::HashMismatch,
+                detail: "stored hash does not match entry contents".to_string(),
+            });
+        }
+
+        expected_sequence = entry.sequence + 1;
+        prev_hash = entry.hash.clone();
+    }
+
+    ChainVerification {
+        entries_checked: entries.len(),
+        head_hash: prev_hash,
+        issues,
+    }
+}
+
+fn receipt_payload(receipt: &Receipt) -> String {
+    let mut payload = format!(
+        "{}|{}|{}|{}",
+        receipt.cost.normalize(),
+        receipt.remaining_budget.normalize(),
+        receipt.timestamp.to_rfc3339_opts(SecondsFormat::Micros, true),
+        receipt.entry_hash.as_deref().unwrap_or("")
+    );
+    // Only receipts with cache savings sign them, so older signatures stay valid
+    if !receipt.cache_savings.is_zero() {
+        payload.push_str(&format!("|{}", receipt.cache_savings.normalize()));
+    }
+    if !receipt.tags.is_empty() {
+        payload.push_str(&format!("|{}", tags_payload(&receipt.tags)));
+    }
+    payload
+}
+
+/// `key=value` pairs in key order, separated by `;`
+fn tags_payload(tags: &CostTags) -> String {
+    tags.iter().map(|(key, value)| format!("{}={}", key, value)).collect::<Vec<_>>().join(";")
+}
+
+/// HMAC-SHA256 signature over a receipt's financial fields
+pub fn sign_receipt(receipt: &Receipt, key: &[u8]) -> String {
+    // HMAC accepts keys of any length
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(receipt_payload(receipt).as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Check a receipt signature produced by [`sign_receipt`]
+pub fn verify_receipt(receipt: &Receipt, key: &[u8]) -> bool {
+    let Some(signature) = receipt.signature.as_deref().and_then(|s| hex::decode(s).ok()) else {
+        return false;
+    };
+    let Ok(mut mac) = HmacSha256::new_from_slice(key) else {
+        return false;
+    };
+    mac.update(receipt_payload(receipt).as_bytes());
+    mac.verify_slice(&signature).is_ok()
+}
@@ -14,9 +14,24 @@ pub enum LedgerError {
     #[error("Lease already committed: {0}")]
     LeaseAlreadyCommitted(String),
 
+    #[error("Anomaly detected: {0}")]
+    AnomalyDetected(String),
+
+    #[error("Tenant isolation violation: {0}")]
+    TenantIsolation(String),
+
+    #[error("Invalid usage export: {0}")]
+    InvalidExport(String),
+
     #[error("Rate not found for model: {0}")]
     RateNotFound(String),
 
+    #[error("Invalid budget: {0}")]
+    InvalidBudget(String),
+
+    #[error("Budget not found for entity {0}")]
+    BudgetNotFound(String),
+
     #[error("Database error: {0}")]
     Database(#[from] surrealdb::Error),
 
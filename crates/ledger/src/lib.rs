@@ -2,17 +2,41 @@
 //!
 //! A standalone, double-entry bookkeeping system for tracking token usage and costs.
 
+pub mod anomaly;
+pub mod chain;
 pub mod error;
+pub mod metering;
+pub mod projection;
+pub mod reconcile;
+pub mod report;
+pub mod reserve;
+pub mod throttle;
 pub mod types;
+pub mod variance;
 
+use crate::chain::{ChainHead, ChainVerification};
+use crate::anomaly::{AnomalyAction, AnomalyEvent, AnomalyKind, AnomalyRule};
 use crate::error::{LedgerError, Result};
+use crate::metering::{ResourceRate, ResourceUsage};
+use crate::projection::{BurnProjection, ProjectionPolicy};
+use crate::reconcile::{ReconciliationOptions, ReconciliationReport, SettlementRecord};
+use crate::report::{SpendReport, SpendReportQuery};
+use crate::reserve::ReservePool;
+use crate::throttle::{ThrottleDecision, ThrottlePolicy};
 use crate::types::*;
+use crate::variance::{VarianceRecord, VarianceStats};
 use chrono::Utc;
 use rust_decimal::prelude::*;
 use surrealdb::engine::any::Any;
+use std::sync::Arc;
 use surrealdb::Surreal;
 use uuid::Uuid;
 use zed42_core::ledger::BudgetStatus;
+use zed42_core::tenant::{scoped_id, tenant_of};
+use zed42_core::chaos::{fail_point, FaultPoint};
+
+/// Tries to append an entry while other ledger instances take its sequence
+const MAX_APPEND_ATTEMPTS: u32 = 8;
 
 /// The Intelligence Ledger - Single source of truth for financial data
 #[derive(Clone)]
@@ -24,8 +48,26 @@ pub struct IntelligenceLedger {
     table_rates: String,
     /// Table name for ledger entries
     table_ledger: String,
+    /// Table holding the chain head, updated with every append
+    table_chain_head: String,
     /// Table name for active leases
     table_leases: String,
+    /// Table name for soft-limit throttle policies
+    table_throttles: String,
+    /// Table name for lease estimate versus settlement records
+    table_variance: String,
+    /// Table name for reserve pools of critical operations
+    table_reserves: String,
+    /// Table name for unit rates of metered resources
+    table_resource_rates: String,
+    /// Rules checked on every lease and settlement
+    anomaly_rules: Vec<AnomalyRule>,
+    /// Serializes appends so each entry links to its predecessor
+    chain_lock: Arc<tokio::sync::Mutex<()>>,
+    /// Set once the unique index on `sequence` is defined
+    chain_index: Arc<tokio::sync::OnceCell<()>>,
+    /// Optional HMAC key for signing receipts
+    receipt_key: Option<Arc<Vec<u8>>>,
 }
 
 impl IntelligenceLedger {
@@ -36,30 +78,197 @@ impl IntelligenceLedger {
             table_budgets: "budgets".to_string(),
             table_rates: "rate_table".to_string(),
             table_ledger: "ledger_entries".to_string(),
+            table_chain_head: "ledger_head".to_string(),
             table_leases: "leases".to_string(),
+            table_throttles: "throttle_policies".to_string(),
+            table_variance: "lease_variance".to_string(),
+            table_reserves: "reserve_pools".to_string(),
+            table_resource_rates: "resource_rates".to_string(),
+            anomaly_rules: Vec::new(),
+            chain_lock: Arc::new(tokio::sync::Mutex::new(())),
+            chain_index: Arc::new(tokio::sync::OnceCell::new()),
+            receipt_key: None,
         }
     }
 
+    /// Sign every receipt with an HMAC-SHA256 key
+    pub fn with_receipt_signing_key(mut self, key: impl Into<Vec<u8>>) -> Self {
+        self.receipt_key = Some(Arc::new(key.into()));
+        self
+    }
+
+    /// Configure anomaly rules (spend rate, lease count, single-call cost)
+    pub fn with_anomaly_rules(mut self, rules: Vec<AnomalyRule>) -> Self {
+        self.anomaly_rules = rules;
+        self
+    }
+
     /// Initialize a budget for an entity
     pub async fn set_budget(&self, budget: Budget) -> Result<()> {
         let _: Option<Budget> = self
             .db
-            .update((&self.table_budgets, &budget.entity_id))
+            .upsert((&self.table_budgets, &budget.entity_id))
             .content(budget)
             .await?;
         Ok(())
     }
 
+    /// Enforce `policy` on an entity's budget once it passes the soft limit
+    pub async fn set_throttle_policy(&self, entity_id: &str, policy: ThrottlePolicy) -> Result<()> {
+        let _: Option<ThrottlePolicy> = self
+            .db
+            .upsert((&self.table_throttles, entity_id))
+            .content(policy)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_throttle_policy(&self, entity_id: &str) -> Result<Option<ThrottlePolicy>> {
+        Ok(self.db.select((&self.table_throttles, entity_id)).await?)
+    }
+
+    /// The throttle an entity is currently under
+    ///
+    /// `None` without a budget, without a policy, or below the soft limit.
+    pub async fn throttle_decision(&self, entity_id: &str) -> Result<Option<ThrottleDecision>> {
+        let Some(policy) = self.get_throttle_policy(entity_id).await? else {
+            return Ok(None);
+        };
+        Ok(self.get_budget(entity_id).await?.and_then(|budget| policy.decide(&budget)))
+    }
+
+    /// Set aside a reserve pool for an entity's (or tenant's) critical leases
+    pub async fn set_reserve_pool(&self, pool: ReservePool) -> Result<()> {
+        let _: Option<ReservePool> = self
+            .db
+            .upsert((&self.table_reserves, &pool.entity_id))
+            .content(pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_reserve_pool(&self, entity_id: &str) -> Result<Option<ReservePool>> {
+        Ok(self.db.select((&self.table_reserves, entity_id)).await?)
+    }
+
     /// Set the cost rate for a model
     pub async fn set_rate(&self, rate: RateTableEntry) -> Result<()> {
         let _: Option<RateTableEntry> = self
             .db
-            .update((&self.table_rates, &rate.model))
+            .upsert((&self.table_rates, &rate.model))
+            .content(rate)
+            .await?;
+        Ok(())
+    }
+
+    /// Set the unit rate for a metered resource
+    pub async fn set_resource_rate(&self, rate: ResourceRate) -> Result<()> {
+        let _: Option<ResourceRate> = self
+            .db
+            .upsert((&self.table_resource_rates, &rate.resource))
             .content(rate)
             .await?;
         Ok(())
     }
 
+    pub async fn get_resource_rate(&self, resource: &str) -> Result<Option<ResourceRate>> {
+        Ok(self.db.select((&self.table_resource_rates, resource)).await?)
+    }
+
+    /// Settle consumption of a non-LLM resource against an entity's budget
+    ///
+    /// The resource is already used, so there is no lease and no hard-cap
+    /// check; overspend shows up on the next lease request instead.
+    ///
+    /// # Errors
+    /// - `RateNotFound` - If the resource has no unit rate
+    pub async fn meter(&self, entity_id: &str, usage: ResourceUsage) -> Result<Receipt> {
+        fail_point(FaultPoint::Database)?;
+
+        let key = usage.resource.key();
+        let rate = self
+            .get_resource_rate(&key)
+            .await?
+            .ok_or_else(|| LedgerError::RateNotFound(key.clone()))?;
+        let cost = rate.cost(usage.quantity);
+
+        let mut budget = self
+            .get_budget(entity_id)
+            .await?
+            .ok_or_else(|| LedgerError::BudgetExceeded("Budget missing during metering".to_string()))?;
+        budget.spent += cost;
+        budget.updated_at = Utc::now();
+        let mut tags = budget.tags.clone();
+        let _: Option<Budget> = self
+            .db
+            .update((&self.table_budgets, entity_id))
+            .content(budget.clone())
+            .await?;
+
+        if let Some(tenant) = tenant_of(entity_id) {
+            if let Some(mut root) = self.get_budget(tenant).await? {
+                inherit_tags(&mut tags, &root);
+                root.spent += cost;
+                root.updated_at = Utc::now();
+                let _: Option<Budget> = self
+                    .db
+                    .update((&self.table_budgets, tenant))
+                    .content(root)
+                    .await?;
+            }
+        }
+
+        let entry = LedgerEntry {
+            id: None,
+            timestamp: Utc::now(),
+            entity_id: entity_id.to_string(),
+            lease_id: None,
+            transaction_type: TransactionType::Settlement,
+            amount: cost,
+            model: Some(key),
+            sequence: 0,
+            prev_hash: None,
+            hash: None,
+            details: format!("Metered {} {} at {} per {}", usage.quantity, rate.unit, rate.cost_per_unit, rate.unit),
+            tags: tags.clone(),
+        };
+        let settlement = self.append_entry(entry).await?;
+
+        let mut warnings = Vec::new();
+        if budget.spent > budget.soft_limit {
+            warnings.push(format!(
+                "Soft limit exceeded: {} > {}",
+                budget.spent, budget.soft_limit
+            ));
+        }
+        let events = self.evaluate_anomalies(entity_id, Some(cost), 0).await?;
+        if let Some(event) = self.handle_anomalies(entity_id, &events).await? {
+            warnings.push(format!("Budget frozen: {}", event));
+        }
+        warnings.extend(
+            events
+                .iter()
+                .filter(|e| e.action == AnomalyAction::Warn)
+                .map(|e| e.to_string()),
+        );
+
+        let mut receipt = Receipt {
+            cost,
+            remaining_budget: budget.hard_limit - budget.spent,
+            timestamp: Utc::now(),
+            warning: if warnings.is_empty() { None } else { Some(warnings.join("; ")) },
+            entry_hash: settlement.hash,
+            signature: None,
+            cache_savings: Decimal::ZERO,
+            tags,
+        };
+        if let Some(ref key) = self.receipt_key {
+            receipt.signature = Some(chain::sign_receipt(&receipt, key));
+        }
+
+        Ok(receipt)
+    }
+
     /// Request a lease for an estimated cost
     ///
     /// # Arguments
@@ -76,6 +285,22 @@ impl IntelligenceLedger {
         entity_id: &str,
         estimated_cost: Decimal,
     ) -> Result<LeaseId> {
+        self.request_priority_lease(entity_id, estimated_cost, LeasePriority::Normal).await
+    }
+
+    /// Request a lease, letting critical operations fall back on a reserve pool
+    ///
+    /// When the budget or its tenant root can't cover a critical lease, the
+    /// entity's reserve pool (or else the tenant's) grants it instead, and the
+    /// settlement is charged to that pool.
+    pub async fn request_priority_lease(
+        &self,
+        entity_id: &str,
+        estimated_cost: Decimal,
+        priority: LeasePriority,
+    ) -> Result<LeaseId> {
+        fail_point(FaultPoint::Database)?;
+
         // 1. Fetch Budget
         let budget: Option<Budget> = self.db.select((&self.table_budgets, entity_id)).await?;
         let budget = budget.ok_or_else(|| {
@@ -88,8 +313,48 @@ impl IntelligenceLedger {
         }
 
         // 2. Check Hard Cap
-        if budget.spent + estimated_cost > budget.hard_limit {
-            return Err(LedgerError::BudgetExceeded(entity_id.to_string()));
+        let mut exhausted = (budget.spent + estimated_cost > budget.hard_limit).then(|| entity_id.to_string());
+
+        // 2a. Tenant Budget Root (entities scoped as "<tenant>/<entity>")
+        let mut tags = budget.tags.clone();
+        if let Some(tenant) = tenant_of(entity_id) {
+            if let Some(root) = self.get_budget(tenant).await? {
+                if root.status != BudgetStatus::Active {
+                    return Err(LedgerError::BudgetFrozen(tenant.to_string()));
+                }
+                if root.spent + estimated_cost > root.hard_limit {
+                    exhausted.get_or_insert_with(|| tenant.to_string());
+                }
+                inherit_tags(&mut tags, &root);
+            }
+        }
+
+        // 2b. Reserve Pool (critical leases only, once the budget is exhausted)
+        let reserve_pool = match exhausted {
+            None => None,
+            Some(exhausted) if priority != LeasePriority::Critical => {
+                return Err(LedgerError::BudgetExceeded(exhausted));
+            }
+            Some(exhausted) => {
+                let mut candidates = vec![entity_id];
+                candidates.extend(tenant_of(entity_id));
+                let mut granted = None;
+                for candidate in candidates {
+                    if let Some(pool) = self.get_reserve_pool(candidate).await? {
+                        if pool.covers(estimated_cost) {
+                            granted = Some(pool.entity_id);
+                            break;
+                        }
+                    }
+                }
+                Some(granted.ok_or(LedgerError::BudgetExceeded(exhausted))?)
+            }
+        };
+
+        // 2c. Anomaly Rules (lease concurrency)
+        let events = self.evaluate_anomalies(entity_id, None, 1).await?;
+        if let Some(event) = self.handle_anomalies(entity_id, &events).await? {
+            return Err(LedgerError::AnomalyDetected(event.to_string()));
         }
 
         // 3. Create Lease
@@ -100,6 +365,7 @@ impl IntelligenceLedger {
             estimated_cost,
             created_at: Utc::now(),
             expires_at: Utc::now() + chrono::Duration::minutes(5), // 5 min TTL
+            reserve_pool: reserve_pool.clone(),
         };
 
         let _: Option<Lease> = self
@@ -116,10 +382,18 @@ impl IntelligenceLedger {
             lease_id: Some(lease_id.clone()),
             transaction_type: TransactionType::Grant,
             amount: estimated_cost,
-            details: format!("Lease requested for {}", estimated_cost),
+            model: None,
+            sequence: 0,
+            prev_hash: None,
+            hash: None,
+            details: match reserve_pool {
+                Some(ref pool) => format!("Critical lease requested for {} from the {} reserve pool", estimated_cost, pool),
+                None => format!("Lease requested for {}", estimated_cost),
+            },
+            tags,
         };
 
-        let _: Option<LedgerEntry> = self.db.create(&self.table_ledger).content(entry).await?;
+        self.append_entry(entry).await?;
 
         Ok(lease_id)
     }
@@ -133,6 +407,8 @@ impl IntelligenceLedger {
     /// # Returns
     /// - `Receipt` - Final cost and remaining budget
     pub async fn commit_usage(&self, lease_id: &str, usage: Usage) -> Result<Receipt> {
+        fail_point(FaultPoint::Database)?;
+
         // 1. Retrieve Lease
         let lease: Option<Lease> = self.db.select((&self.table_leases, lease_id)).await?;
         let lease = lease.ok_or_else(|| LedgerError::LeaseNotFound(lease_id.to_string()))?;
@@ -143,12 +419,9 @@ impl IntelligenceLedger {
         let rate = rate
             .ok_or_else(|| LedgerError::RateNotFound(usage.model.clone()))?;
 
-        // 3. Calculate Actual Cost
-        let input_cost = (Decimal::from(usage.input_tokens) / Decimal::from(1000))
-            * rate.input_cost_per_1k;
-        let output_cost = (Decimal::from(usage.output_tokens) / Decimal::from(1000))
-            * rate.output_cost_per_1k;
-        let actual_cost = input_cost + output_cost;
+        // 3. Calculate Actual Cost (cached input at the cache rates)
+        let actual_cost = rate.cost(&usage);
+        let cache_savings = rate.cache_savings(&usage);
 
         // 4. Update Budget (Atomic Increment logic simulated here, explicitly safe in single-writer or careful locking)
         // SurrealDB supports "UPDATE type::thing($tb, $id) SET spent += $amount" logic
@@ -162,15 +435,53 @@ impl IntelligenceLedger {
              .select((&self.table_budgets, &lease.entity_id))
              .await?
              .ok_or_else(|| LedgerError::BudgetExceeded("Budget missing during commit".to_string()))?;
-        
-        budget.spent += actual_cost;
-        budget.updated_at = Utc::now();
-        
-        let _: Option<Budget> = self
-            .db
-            .update((&self.table_budgets, &lease.entity_id))
-            .content(budget.clone())
-            .await?;
+        let mut tags = budget.tags.clone();
+        let tenant = tenant_of(&lease.entity_id);
+        let mut root = match tenant {
+            Some(tenant) => self.get_budget(tenant).await?,
+            None => None,
+        };
+        if let Some(ref root) = root {
+            inherit_tags(&mut tags, root);
+        }
+
+        // Critical leases granted from a reserve pool settle there; the budget stays exhausted
+        let remaining_budget = if let Some(ref pool_id) = lease.reserve_pool {
+            let mut pool = self
+                .get_reserve_pool(pool_id)
+                .await?
+                .ok_or_else(|| LedgerError::BudgetExceeded(format!("Reserve pool {} missing during commit", pool_id)))?;
+            pool.spent += actual_cost;
+            pool.updated_at = Utc::now();
+            let remaining = pool.remaining();
+            let _: Option<ReservePool> = self
+                .db
+                .update((&self.table_reserves, pool_id))
+                .content(pool)
+                .await?;
+            remaining
+        } else {
+            budget.spent += actual_cost;
+            budget.updated_at = Utc::now();
+
+            let _: Option<Budget> = self
+                .db
+                .update((&self.table_budgets, &lease.entity_id))
+                .content(budget.clone())
+                .await?;
+
+            // 4b. Roll spend up to the tenant budget root
+            if let (Some(tenant), Some(root)) = (tenant, root.as_mut()) {
+                root.spent += actual_cost;
+                root.updated_at = Utc::now();
+                let _: Option<Budget> = self
+                    .db
+                    .update((&self.table_budgets, tenant))
+                    .content(root.clone())
+                    .await?;
+            }
+            budget.hard_limit - budget.spent
+        };
 
         // 5. Record Settlement Entry
         let entry = LedgerEntry {
@@ -180,34 +491,121 @@ impl IntelligenceLedger {
             lease_id: Some(lease_id.to_string()),
             transaction_type: TransactionType::Settlement,
             amount: actual_cost,
-            details: format!(
-                "Usage committed: {} in / {} out on {}",
-                usage.input_tokens, usage.output_tokens, usage.model
-            ),
+            model: Some(usage.model.clone()),
+            sequence: 0,
+            prev_hash: None,
+            hash: None,
+            details: if usage.cache_read_tokens + usage.cache_write_tokens > 0 {
+                format!(
+                    "Usage committed: {} in ({} cache read, {} cache write) / {} out on {}",
+                    usage.input_tokens, usage.cache_read_tokens, usage.cache_write_tokens, usage.output_tokens, usage.model
+                )
+            } else {
+                format!(
+                    "Usage committed: {} in / {} out on {}",
+                    usage.input_tokens, usage.output_tokens, usage.model
+                )
+            },
+            tags: tags.clone(),
         };
-        let _: Option<LedgerEntry> = self.db.create(&self.table_ledger).content(entry).await?;
+        let settlement = self.append_entry(entry).await?;
 
         // 6. Close Lease (Delete)
         let _: Option<Lease> = self.db.delete((&self.table_leases, lease_id)).await?;
 
+        // 6b. Record estimate variance; releases without usage say nothing about the estimate
+        if usage.input_tokens + usage.output_tokens > 0 {
+            let record = VarianceRecord {
+                entity_id: lease.entity_id.clone(),
+                model: usage.model.clone(),
+                estimated: lease.estimated_cost,
+                actual: actual_cost,
+                settled_at: Utc::now(),
+            };
+            let _: Option<VarianceRecord> = self.db.create(&self.table_variance).content(record).await?;
+        }
+
         // 7. Check Soft Cap
-        let warning = if budget.spent > budget.soft_limit {
-            Some(format!(
+        let mut warnings = Vec::new();
+        if let Some(ref pool_id) = lease.reserve_pool {
+            warnings.push(format!("Drawn from the {} reserve pool: {} left", pool_id, remaining_budget));
+        } else if budget.spent > budget.soft_limit {
+            warnings.push(format!(
                 "Soft limit exceeded: {} > {}",
                 budget.spent, budget.soft_limit
-            ))
-        } else {
+            ));
+        }
+
+        // 8. Anomaly Rules (spend rate, single-call cost)
+        let events = self.evaluate_anomalies(&lease.entity_id, Some(actual_cost), 0).await?;
+        if let Some(event) = self.handle_anomalies(&lease.entity_id, &events).await? {
+            warnings.push(format!("Budget frozen: {}", event));
+        }
+        warnings.extend(
+            events
+                .iter()
+                .filter(|e| e.action == AnomalyAction::Warn)
+                .map(|e| e.to_string()),
+        );
+
+        let warning = if warnings.is_empty() {
             None
+        } else {
+            Some(warnings.join("; "))
         };
 
-        Ok(Receipt {
+        let mut receipt = Receipt {
             cost: actual_cost,
-            remaining_budget: budget.hard_limit - budget.spent,
+            remaining_budget,
             timestamp: Utc::now(),
             warning,
-        })
+            entry_hash: settlement.hash,
+            signature: None,
+            cache_savings,
+            tags,
+        };
+        if let Some(ref key) = self.receipt_key {
+            receipt.signature = Some(chain::sign_receipt(&receipt, key));
+        }
+
+        Ok(receipt)
     }
     
+    /// Request a lease for an entity of `tenant`
+    ///
+    /// The entity is scoped to the tenant, so the tenant's budget root caps it.
+    pub async fn request_tenant_lease(
+        &self,
+        tenant_id: &str,
+        entity_id: &str,
+        estimated_cost: Decimal,
+    ) -> Result<LeaseId> {
+        if tenant_of(entity_id).is_some() {
+            return Err(LedgerError::TenantIsolation(format!(
+                "entity {} is already tenant-scoped",
+                entity_id
+            )));
+        }
+        let scoped = scoped_id(tenant_id, entity_id).map_err(|e| match e {
+            zed42_core::Error::TenantIsolation(reason) => LedgerError::TenantIsolation(reason),
+            other => LedgerError::Other(other.into()),
+        })?;
+        self.request_lease(&scoped, estimated_cost).await
+    }
+
+    /// Commit usage on behalf of `tenant`, rejecting leases held by other tenants
+    pub async fn commit_tenant_usage(&self, tenant_id: &str, lease_id: &str, usage: Usage) -> Result<Receipt> {
+        let lease: Option<Lease> = self.db.select((&self.table_leases, lease_id)).await?;
+        let lease = lease.ok_or_else(|| LedgerError::LeaseNotFound(lease_id.to_string()))?;
+        if tenant_of(&lease.entity_id) != Some(tenant_id) {
+            return Err(LedgerError::TenantIsolation(format!(
+                "lease {} does not belong to tenant {}",
+                lease_id, tenant_id
+            )));
+        }
+        self.commit_usage(lease_id, usage).await
+    }
+
     /// Freeze a budget, preventing further leases
     pub async fn freeze_budget(&self, entity_id: &str, reason: &str) -> Result<()> {
         let mut budget: Budget = self
@@ -218,14 +616,66 @@ impl IntelligenceLedger {
         
         budget.status = BudgetStatus::Frozen;
         budget.updated_at = Utc::now();
-        
+
         let _: Option<Budget> = self
             .db
             .update((&self.table_budgets, entity_id))
             .content(budget)
             .await?;
-            
-        // Log System Audit
+
+        self.record_audit(entity_id, format!("Budget Frozen: {}", reason)).await?;
+        Ok(())
+    }
+
+    /// Change an entity's hard and soft limits, recording the change as a system audit entry
+    ///
+    /// Limits must satisfy `0 <= soft_limit <= hard_limit` and `hard_limit > 0`.
+    /// A depleted budget whose new hard limit leaves room becomes active
+    /// again; a frozen one stays frozen.
+    pub async fn update_budget_limits(
+        &self,
+        entity_id: &str,
+        hard_limit: Decimal,
+        soft_limit: Decimal,
+        actor: &str,
+    ) -> Result<Budget> {
+        if hard_limit <= Decimal::ZERO {
+            return Err(LedgerError::InvalidBudget(format!("hard limit must be positive, got {}", hard_limit)));
+        }
+        if soft_limit < Decimal::ZERO || soft_limit > hard_limit {
+            return Err(LedgerError::InvalidBudget(format!(
+                "soft limit {} must be between 0 and the hard limit {}",
+                soft_limit, hard_limit
+            )));
+        }
+        let mut budget = self
+            .get_budget(entity_id)
+            .await?
+            .ok_or_else(|| LedgerError::BudgetNotFound(entity_id.to_string()))?;
+
+        let details = format!(
+            "Budget limits changed by {}: hard {} -> {}, soft {} -> {}",
+            actor, budget.hard_limit, hard_limit, budget.soft_limit, soft_limit
+        );
+        budget.hard_limit = hard_limit;
+        budget.soft_limit = soft_limit;
+        if budget.status == BudgetStatus::Depleted && budget.spent < hard_limit {
+            budget.status = BudgetStatus::Active;
+        }
+        budget.updated_at = Utc::now();
+        let _: Option<Budget> = self
+            .db
+            .update((&self.table_budgets, entity_id))
+            .content(budget.clone())
+            .await?;
+
+        self.record_audit(entity_id, details).await?;
+        Ok(budget)
+    }
+
+    /// Append a system audit entry for an entity (settings changes, operator actions)
+    pub async fn record_audit(&self, entity_id: &str, details: impl Into<String>) -> Result<LedgerEntry> {
+        let tags = self.get_budget(entity_id).await?.map(|budget| budget.tags).unwrap_or_default();
         let entry = LedgerEntry {
             id: None,
             timestamp: Utc::now(),
@@ -233,15 +683,340 @@ impl IntelligenceLedger {
             lease_id: None,
             transaction_type: TransactionType::SystemAudit,
             amount: Decimal::default(),
-            details: format!("Budget Frozen: {}", reason),
+            model: None,
+            sequence: 0,
+            prev_hash: None,
+            hash: None,
+            details: details.into(),
+            tags,
         };
-        let _: Option<LedgerEntry> = self.db.create(&self.table_ledger).content(entry).await?;
-        
-        Ok(())
+        self.append_entry(entry).await
+    }
+
+    /// System audit entries in chain order, optionally for one entity
+    pub async fn audit_log(&self, entity_id: Option<&str>) -> Result<Vec<LedgerEntry>> {
+        let mut entries = match entity_id {
+            Some(entity_id) => self.entries_for(entity_id).await?,
+            None => self.chain_entries().await?,
+        };
+        entries.retain(|entry| entry.transaction_type == TransactionType::SystemAudit);
+        entries.sort_by_key(|entry| entry.sequence);
+        Ok(entries)
+    }
+
+    /// Get the cost rate for a model
+    pub async fn get_rate(&self, model: &str) -> Result<Option<RateTableEntry>> {
+        Ok(self.db.select((&self.table_rates, model)).await?)
     }
 
     /// Get current budget for an entity
     pub async fn get_budget(&self, entity_id: &str) -> Result<Option<Budget>> {
         Ok(self.db.select((&self.table_budgets, entity_id)).await?)
     }
+
+    /// Project when an entity's budget runs out at its recent spend rate
+    ///
+    /// `None` when the entity has no budget.
+    pub async fn project_burn(&self, entity_id: &str, policy: &ProjectionPolicy) -> Result<Option<BurnProjection>> {
+        let Some(budget) = self.get_budget(entity_id).await? else {
+            return Ok(None);
+        };
+        let entries = self.entries_for(entity_id).await?;
+        Ok(Some(BurnProjection::project(&budget, &entries, policy, Utc::now())))
+    }
+
+    /// Settled spend matching `query`, sliced by entity, model and cost center tag
+    pub async fn spend_report(&self, query: &SpendReportQuery) -> Result<SpendReport> {
+        let mut response = self
+            .db
+            .query("SELECT * FROM type::table($tb) WHERE transaction_type = 'Settlement'")
+            .bind(("tb", self.table_ledger.clone()))
+            .await?;
+        let entries: Vec<LedgerEntry> = response.take(0)?;
+        Ok(SpendReport::build(&entries, query))
+    }
+
+    /// Lease estimates next to settled costs, for one entity or all
+    pub async fn variance_records(&self, entity_id: Option<&str>) -> Result<Vec<VarianceRecord>> {
+        let mut response = match entity_id {
+            Some(entity_id) => {
+                self.db
+                    .query("SELECT * FROM type::table($tb) WHERE entity_id = $entity ORDER BY settled_at ASC")
+                    .bind(("tb", self.table_variance.clone()))
+                    .bind(("entity", entity_id.to_string()))
+                    .await?
+            }
+            None => {
+                self.db
+                    .query("SELECT * FROM type::table($tb) ORDER BY settled_at ASC")
+                    .bind(("tb", self.table_variance.clone()))
+                    .await?
+            }
+        };
+        Ok(response.take(0)?)
+    }
+
+    /// How far lease estimates are from settled costs, per entity and model
+    pub async fn lease_variance(&self, entity_id: Option<&str>) -> Result<Vec<VarianceStats>> {
+        Ok(VarianceStats::summarize(&self.variance_records(entity_id).await?))
+    }
+
+    /// Verify the ledger hash chain, reporting edited, reordered, or deleted entries
+    ///
+    /// Deleted newest entries show up against the recorded chain head.
+    pub async fn verify_chain(&self) -> Result<ChainVerification> {
+        let entries = self.chain_entries().await?;
+        let head = self.chain_head().await?;
+        Ok(chain::verify_entries_with_head(&entries, head.as_ref()))
+    }
+
+    /// Check a receipt's signature against this ledger's signing key
+    pub fn verify_receipt(&self, receipt: &Receipt) -> bool {
+        match self.receipt_key {
+            Some(ref key) => chain::verify_receipt(receipt, key),
+            None => false,
+        }
+    }
+
+    /// Append an entry to the hash chain and persist it
+    ///
+    /// The entry and the new chain head are written in one transaction. A
+    /// unique index on `sequence` rejects the append when another ledger
+    /// instance on the same database took the sequence first; the entry is
+    /// then linked to the new head and written again.
+    async fn append_entry(&self, mut entry: LedgerEntry) -> Result<LedgerEntry> {
+        let _guard = self.chain_lock.lock().await;
+        self.chain_index
+            .get_or_try_init(|| async {
+                self.db
+                    .query(format!(
+                        "DEFINE INDEX IF NOT EXISTS {tb}_sequence ON TABLE {tb} FIELDS sequence UNIQUE",
+                        tb = self.table_ledger
+                    ))
+                    .await?
+                    .check()
+                    .map(|_| ())
+            })
+            .await?;
+
+        let mut attempt = 1;
+        loop {
+            let last = self.last_entry().await?;
+            entry.sequence = last.as_ref().map(|e| e.sequence + 1).unwrap_or(1);
+            entry.prev_hash = last.and_then(|e| e.hash);
+            entry.hash = Some(chain::entry_hash(&entry));
+            let head = ChainHead { sequence: entry.sequence, hash: entry.hash.clone().unwrap_or_default() };
+
+            let written = self
+                .db
+                .query(
+                    "BEGIN TRANSACTION;
+                     CREATE type::table($tb) CONTENT $entry;
+                     UPSERT type::thing($head_tb, 'head') CONTENT $head;
+                     COMMIT TRANSACTION;",
+                )
+                .bind(("tb", self.table_ledger.clone()))
+                .bind(("entry", entry.clone()))
+                .bind(("head_tb", self.table_chain_head.clone()))
+                .bind(("head", head))
+                .await
+                .and_then(|response| response.check());
+            let Err(e) = written else {
+                return Ok(entry);
+            };
+            if attempt >= MAX_APPEND_ATTEMPTS || !self.sequence_taken(entry.sequence).await? {
+                return Err(e.into());
+            }
+            attempt += 1;
+        }
+    }
+
+    /// Newest entry of the chain
+    async fn last_entry(&self) -> Result<Option<LedgerEntry>> {
+        let mut response = self
+            .db
+            .query("SELECT * FROM type::table($tb) ORDER BY sequence DESC LIMIT 1")
+            .bind(("tb", self.table_ledger.clone()))
+            .await?;
+        let last: Vec<LedgerEntry> = response.take(0)?;
+        Ok(last.into_iter().next())
+    }
+
+    /// Whether another append already wrote `sequence`
+    async fn sequence_taken(&self, sequence: u64) -> Result<bool> {
+        Ok(self.last_entry().await?.is_some_and(|last| last.sequence >= sequence))
+    }
+
+    /// Chain head recorded by the latest append
+    async fn chain_head(&self) -> Result<Option<ChainHead>> {
+        Ok(self.db.select((&self.table_chain_head, "head")).await?)
+    }
+
+    /// All entries in chain order
+    async fn chain_entries(&self) -> Result<Vec<LedgerEntry>> {
+        let mut response = self
+            .db
+            .query("SELECT * FROM type::table($tb) ORDER BY sequence ASC")
+            .bind(("tb", self.table_ledger.clone()))
+            .await?;
+        Ok(response.take(0)?)
+    }
+
+    /// Evaluate anomaly rules for an entity without taking any action
+    pub async fn check_anomalies(&self, entity_id: &str) -> Result<Vec<AnomalyEvent>> {
+        self.evaluate_anomalies(entity_id, None, 0).await
+    }
+
+    /// Reconcile settlements against a provider usage export (CSV or JSON)
+    ///
+    /// Uses one-hour windows and a $0.01 per-bucket tolerance; see
+    /// [`IntelligenceLedger::reconcile_with`] to change them.
+    pub async fn reconcile(&self, usage_export: &str) -> Result<ReconciliationReport> {
+        self.reconcile_with(usage_export, ReconciliationOptions::default()).await
+    }
+
+    /// Reconcile settlements against a provider usage export with custom options
+    pub async fn reconcile_with(
+        &self,
+        usage_export: &str,
+        options: ReconciliationOptions,
+    ) -> Result<ReconciliationReport> {
+        let provider = reconcile::parse_usage_export(usage_export)?;
+
+        let mut response = self
+            .db
+            .query("SELECT * FROM type::table($tb) WHERE transaction_type = 'Settlement'")
+            .bind(("tb", self.table_ledger.clone()))
+            .await?;
+        let entries: Vec<LedgerEntry> = response.take(0)?;
+
+        // Provider exports only cover model calls
+        let settlements: Vec<SettlementRecord> = entries
+            .into_iter()
+            .filter(|e| !metering::is_metered(e))
+            .map(|e| SettlementRecord {
+                timestamp: e.timestamp,
+                model: e.model.unwrap_or_else(|| "unknown".to_string()),
+                cost: e.amount,
+            })
+            .collect();
+
+        reconcile::build_report(&settlements, &provider, &options)
+    }
+
+    /// All ledger entries for an entity
+    async fn entries_for(&self, entity_id: &str) -> Result<Vec<LedgerEntry>> {
+        let mut response = self
+            .db
+            .query("SELECT * FROM type::table($tb) WHERE entity_id = $entity")
+            .bind(("tb", self.table_ledger.clone()))
+            .bind(("entity", entity_id.to_string()))
+            .await?;
+        Ok(response.take(0)?)
+    }
+
+    /// Number of unexpired leases held by an entity
+    async fn active_lease_count(&self, entity_id: &str) -> Result<usize> {
+        let mut response = self
+            .db
+            .query("SELECT * FROM type::table($tb) WHERE entity_id = $entity")
+            .bind(("tb", self.table_leases.clone()))
+            .bind(("entity", entity_id.to_string()))
+            .await?;
+        let leases: Vec<Lease> = response.take(0)?;
+        let now = Utc::now();
+        Ok(leases.iter().filter(|l| l.expires_at > now).count())
+    }
+
+    /// Evaluate configured rules
+    ///
+    /// `last_call` is the cost of the settlement just recorded (if any);
+    /// `pending_leases` counts leases about to be created.
+    async fn evaluate_anomalies(
+        &self,
+        entity_id: &str,
+        last_call: Option<Decimal>,
+        pending_leases: usize,
+    ) -> Result<Vec<AnomalyEvent>> {
+        if self.anomaly_rules.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let window_start = Utc::now() - chrono::Duration::minutes(1);
+        let recent_spend: Decimal = self
+            .entries_for(entity_id)
+            .await?
+            .iter()
+            .filter(|e| e.transaction_type == TransactionType::Settlement && e.timestamp >= window_start)
+            .map(|e| e.amount)
+            .sum();
+        let active_leases = self.active_lease_count(entity_id).await? + pending_leases;
+
+        let mut events = Vec::new();
+        for rule in &self.anomaly_rules {
+            let (observed, threshold) = match &rule.kind {
+                AnomalyKind::SpendRate { max_per_minute } => (recent_spend, *max_per_minute),
+                AnomalyKind::LeaseCount { max_active } => {
+                    (Decimal::from(active_leases), Decimal::from(*max_active))
+                }
+                AnomalyKind::SingleCallCost { max_cost } => match last_call {
+                    Some(cost) => (cost, *max_cost),
+                    None => continue,
+                },
+            };
+
+            if observed > threshold {
+                events.push(AnomalyEvent {
+                    rule: rule.name.clone(),
+                    entity_id: entity_id.to_string(),
+                    observed,
+                    threshold,
+                    action: rule.action,
+                    detected_at: Utc::now(),
+                });
+            }
+        }
+
+        Ok(events)
+    }
+
+    /// Record audit entries for tripped rules and freeze if any rule demands it
+    ///
+    /// Returns the first freezing event, if the budget was frozen.
+    async fn handle_anomalies<'a>(
+        &self,
+        entity_id: &str,
+        events: &'a [AnomalyEvent],
+    ) -> Result<Option<&'a AnomalyEvent>> {
+        for event in events.iter().filter(|e| e.action == AnomalyAction::Warn) {
+            let entry = LedgerEntry {
+                id: None,
+                timestamp: Utc::now(),
+                entity_id: entity_id.to_string(),
+                lease_id: None,
+                transaction_type: TransactionType::SystemAudit,
+                amount: Decimal::default(),
+                model: None,
+                sequence: 0,
+                prev_hash: None,
+                hash: None,
+                details: format!("Anomaly warning: {}", event),
+                tags: Default::default(),
+            };
+            self.append_entry(entry).await?;
+        }
+
+        let freeze = events.iter().find(|e| e.action == AnomalyAction::Freeze);
+        if let Some(event) = freeze {
+            self.freeze_budget(entity_id, &event.to_string()).await?;
+        }
+        Ok(freeze)
+    }
+}
+
+/// Add a tenant root's tags that the entity's own budget doesn't set
+fn inherit_tags(tags: &mut CostTags, root: &Budget) {
+    for (key, value) in &root.tags {
+        tags.entry(key.clone()).or_insert_with(|| value.clone());
+    }
 }
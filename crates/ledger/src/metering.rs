@@ -0,0 +1,120 @@
+//! Usage metering for non-LLM resources
+//!
+//! Model tokens are only part of what a run costs. Sandbox CPU time, calls
+//! to external APIs (forge, web fetch) and storage growth are metered at
+//! configurable unit rates and settled against the same budgets, so spend
+//! reports and budget burn cover the whole system.
+//!
+//! Metered settlements are booked with the resource's key (e.g.
+//! `resource:sandbox_cpu`) in place of a model, and have no lease: the
+//! resource has already been consumed by the time it is metered.
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::types::LedgerEntry;
+
+/// Prefix of every metered resource key
+pub const RESOURCE_PREFIX: &str = "resource:";
+
+const BYTES_PER_GB: i64 = 1_000_000_000;
+
+/// A non-LLM resource billed by the ledger
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Resource {
+    /// Sandbox CPU time, in CPU-seconds
+    SandboxCpu,
+    /// Calls to an external service (`forge`, `web_fetch`, ...)
+    ApiCall(String),
+    /// Storage growth, in GB
+    Storage,
+}
+
+impl Resource {
+    /// Key the resource's rate and settlements are stored under
+    pub fn key(&self) -> String {
+        match self {
+            Resource::SandboxCpu => format!("{}sandbox_cpu", RESOURCE_PREFIX),
+            Resource::ApiCall(service) => format!("{}api:{}", RESOURCE_PREFIX, service),
+            Resource::Storage => format!("{}storage", RESOURCE_PREFIX),
+        }
+    }
+
+    pub fn unit(&self) -> &'static str {
+        match self {
+            Resource::SandboxCpu => "cpu-second",
+            Resource::ApiCall(_) => "call",
+            Resource::Storage => "GB",
+        }
+    }
+}
+
+/// Cost of one unit of a resource
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResourceRate {
+    /// [`Resource::key`] of the resource
+    pub resource: String,
+    pub unit: String,
+    pub cost_per_unit: Decimal,
+}
+
+impl ResourceRate {
+    pub fn new(resource: &Resource, cost_per_unit: Decimal) -> Self {
+        Self {
+            resource: resource.key(),
+            unit: resource.unit().to_string(),
+            cost_per_unit,
+        }
+    }
+
+    pub fn cost(&self, quantity: Decimal) -> Decimal {
+        quantity * self.cost_per_unit
+    }
+}
+
+/// Consumption of a resource, in its unit
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResourceUsage {
+    pub resource: Resource,
+    pub quantity: Decimal,
+}
+
+impl ResourceUsage {
+    pub fn cpu_seconds(seconds: Decimal) -> Self {
+        Self { resource: Resource::SandboxCpu, quantity: seconds }
+    }
+
+    pub fn api_calls(service: impl Into<String>, calls: u32) -> Self {
+        Self { resource: Resource::ApiCall(service.into()), quantity: Decimal::from(calls) }
+    }
+
+    /// Storage grown by `bytes`; shrinking storage is not refunded
+    pub fn storage_growth(bytes: i64) -> Self {
+        Self {
+            resource: Resource::Storage,
+            quantity: Decimal::from(bytes.max(0)) / Decimal::from(BYTES_PER_GB),
+        }
+    }
+}
+
+/// Whether a settlement is for a metered resource rather than a model call
+pub fn is_metered(entry: &LedgerEntry) -> bool {
+    entry.model.as_deref().is_some_and(|m| m.starts_with(RESOURCE_PREFIX))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_resource_keys_and_costs() {
+        assert_eq!(Resource::ApiCall("forge".into()).key(), "resource:api:forge");
+
+        let cpu = ResourceRate::new(&Resource::SandboxCpu, dec!(0.00005));
+        assert_eq!(cpu.cost(ResourceUsage::cpu_seconds(dec!(120)).quantity), dec!(0.006));
+
+        assert_eq!(ResourceUsage::storage_growth(250_000_000).quantity, dec!(0.25));
+        assert_eq!(ResourceUsage::storage_growth(-4_096).quantity, Decimal::ZERO);
+    }
+}
@@ -0,0 +1,219 @@
+//! Burn-down projections
+//!
+//! Caps and anomaly rules react to money already spent. A [`BurnProjection`]
+//! looks ahead instead: from the settled spend over a trailing window it
+//! estimates when a budget reaches its hard limit, and, given how many tasks
+//! are left and what a task costs, how many of them will not finish in time.
+
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::types::{Budget, LedgerEntry, TransactionType};
+
+/// How far back to measure spend and when to warn
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProjectionPolicy {
+    /// Trailing window the spend rate is measured over
+    pub window_secs: i64,
+    /// Warn when the hard limit is projected within this many seconds
+    pub warn_within_secs: i64,
+    /// Settlements needed inside the window before projecting
+    pub min_settlements: usize,
+}
+
+impl Default for ProjectionPolicy {
+    fn default() -> Self {
+        Self {
+            window_secs: 10 * 60,
+            warn_within_secs: 30 * 60,
+            min_settlements: 2,
+        }
+    }
+}
+
+/// Tasks still to run and what one costs, for task-aware warnings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskOutlook {
+    pub remaining_tasks: usize,
+    /// Mean cost of a finished task; unknown until one finishes
+    pub cost_per_task: Option<Decimal>,
+}
+
+/// Where a budget is heading at the current spend rate
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BurnProjection {
+    pub entity_id: String,
+    pub spent: Decimal,
+    pub hard_limit: Decimal,
+    pub currency: String,
+    /// Settled spend per minute over the window
+    pub per_minute: Decimal,
+    /// Settlements the rate is based on
+    pub settlements: usize,
+    /// Time until the hard limit; `None` when nothing is being spent
+    pub exhausts_in_secs: Option<i64>,
+    pub projected_at: DateTime<Utc>,
+    #[serde(default)]
+    pub tasks: Option<TaskOutlook>,
+}
+
+impl BurnProjection {
+    /// Project `budget` from its ledger entries as of `now`
+    ///
+    /// The rate divides the window's settled spend by the time since the
+    /// first settlement in the window (at least a minute), so a run that
+    /// started recently is not diluted by the idle part of the window.
+    pub fn project(budget: &Budget, entries: &[LedgerEntry], policy: &ProjectionPolicy, now: DateTime<Utc>) -> Self {
+        let window_start = now - Duration::seconds(policy.window_secs);
+        let settled: Vec<&LedgerEntry> = entries
+            .iter()
+            .filter(|e| {
+                e.entity_id == budget.entity_id
+                    && e.transaction_type == TransactionType::Settlement
+                    && e.timestamp > window_start
+                    && e.timestamp <= now
+            })
+            .collect();
+
+        let total: Decimal = settled.iter().map(|e| e.amount).sum();
+        let first = settled.iter().map(|e| e.timestamp).min().unwrap_or(now);
+        let minutes = (Decimal::from((now - first).num_seconds()) / Decimal::from(60)).max(Decimal::ONE);
+        let per_minute = total / minutes;
+
+        let remaining = (budget.hard_limit - budget.spent).max(Decimal::ZERO);
+        let exhausts_in_secs = (settled.len() >= policy.min_settlements && per_minute > Decimal::ZERO)
+            .then(|| (remaining / per_minute * Decimal::from(60)).to_i64())
+            .flatten();
+
+        Self {
+            entity_id: budget.entity_id.clone(),
+            spent: budget.spent,
+            hard_limit: budget.hard_limit,
+            currency: budget.currency.clone(),
+            per_minute,
+            settlements: settled.len(),
+            exhausts_in_secs,
+            projected_at: now,
+            tasks: None,
+        }
+    }
+
+    pub fn with_tasks(mut self, tasks: TaskOutlook) -> Self {
+        self.tasks = Some(tasks);
+        self
+    }
+
+    pub fn exhausts_at(&self) -> Option<DateTime<Utc>> {
+        self.exhausts_in_secs.map(|secs| self.projected_at + Duration::seconds(secs))
+    }
+
+    /// Remaining tasks the budget will not cover, when the cost per task is known
+    pub fn tasks_at_risk(&self) -> Option<usize> {
+        let tasks = self.tasks.as_ref()?;
+        let cost = tasks.cost_per_task.filter(|c| *c > Decimal::ZERO)?;
+        let remaining = (self.hard_limit - self.spent).max(Decimal::ZERO);
+        let affordable = (remaining / cost).floor().to_usize().unwrap_or(usize::MAX);
+        Some(tasks.remaining_tasks.saturating_sub(affordable))
+    }
+
+    /// Whether the hard limit is projected within the policy's warning horizon
+    pub fn should_warn(&self, policy: &ProjectionPolicy) -> bool {
+        self.exhausts_in_secs.is_some_and(|secs| secs <= policy.warn_within_secs)
+    }
+
+    /// One-line warning, e.g. "At this rate (0.5 USD/min) the session budget
+    /// exhausts in ~12 minutes, before 3 of 7 remaining tasks complete"
+    pub fn message(&self) -> String {
+        let Some(secs) = self.exhausts_in_secs else {
+            return format!("The {} budget is not being spent", self.entity_id);
+        };
+        let mut message = format!(
+            "At this rate ({} {}/min) the {} budget exhausts in ~{}",
+            self.per_minute.round_dp(4).normalize(),
+            self.currency,
+            self.entity_id,
+            format_eta(secs)
+        );
+        if let (Some(at_risk), Some(tasks)) = (self.tasks_at_risk(), self.tasks.as_ref()) {
+            if at_risk > 0 {
+                message.push_str(&format!(", before {} of {} remaining tasks complete", at_risk, tasks.remaining_tasks));
+            }
+        }
+        message
+    }
+}
+
+/// `45 seconds`, `12 minutes`, `3 hours`
+fn format_eta(secs: i64) -> String {
+    let (value, unit) = match secs {
+        ..=89 => (secs.max(0), "second"),
+        90..=5399 => ((secs + 30) / 60, "minute"),
+        _ => ((secs + 1800) / 3600, "hour"),
+    };
+    format!("{} {}{}", value, unit, if value == 1 { "" } else { "s" })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn settlement(entity_id: &str, amount: Decimal, at: DateTime<Utc>) -> LedgerEntry {
+        LedgerEntry {
+            id: None,
+            timestamp: at,
+            entity_id: entity_id.to_string(),
+            lease_id: None,
+            transaction_type: TransactionType::Settlement,
+            amount,
+            model: None,
+            details: String::new(),
+            sequence: 0,
+            prev_hash: None,
+            hash: None,
+            tags: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_projection_and_task_warning() {
+        let now = Utc::now();
+        let budget = Budget {
+            entity_id: "session".to_string(),
+            hard_limit: dec!(10),
+            soft_limit: dec!(8),
+            spent: dec!(4),
+            currency: "USD".to_string(),
+            status: Default::default(),
+            updated_at: now,
+            tags: Default::default(),
+        };
+        // $2 over the last 4 minutes: $0.50/min, $6 left lasts 12 minutes
+        let entries = vec![
+            settlement("session", dec!(1), now - Duration::minutes(4)),
+            settlement("session", dec!(1), now - Duration::minutes(1)),
+            settlement("session", dec!(2), now - Duration::minutes(30)),
+            settlement("other", dec!(5), now - Duration::minutes(1)),
+        ];
+        let policy = ProjectionPolicy::default();
+        let projection = BurnProjection::project(&budget, &entries, &policy, now).with_tasks(TaskOutlook {
+            remaining_tasks: 7,
+            cost_per_task: Some(dec!(1.5)),
+        });
+
+        assert_eq!(projection.settlements, 2);
+        assert_eq!(projection.per_minute, dec!(0.5));
+        assert_eq!(projection.exhausts_in_secs, Some(720));
+        assert!(projection.should_warn(&policy));
+        assert_eq!(projection.tasks_at_risk(), Some(3));
+        assert_eq!(
+            projection.message(),
+            "At this rate (0.5 USD/min) the session budget exhausts in ~12 minutes, before 3 of 7 remaining tasks complete"
+        );
+
+        let idle = BurnProjection::project(&budget, &entries[..1], &policy, now);
+        assert_eq!(idle.exhausts_in_secs, None);
+        assert!(!idle.should_warn(&policy));
+    }
+}
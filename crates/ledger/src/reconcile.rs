@@ -0,0 +1,301 @@
+//! Reconciliation of ledger settlements against provider billing exports
+//!
+//! Provider exports (e.g. the OpenRouter activity export) are parsed into
+//! [`ProviderUsageRecord`]s, bucketed by time window and model, and compared
+//! with the ledger's Settlement entries over the same windows.
+
+use crate::error::{LedgerError, Result};
+use chrono::{DateTime, Duration, DurationRound, NaiveDateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+
+const TIMESTAMP_FIELDS: &[&str] = &["created_at", "timestamp", "date", "time"];
+const MODEL_FIELDS: &[&str] = &["model", "model_permaslug", "model_id"];
+const COST_FIELDS: &[&str] = &["cost", "cost_usd", "total_cost", "usage"];
+const INPUT_FIELDS: &[&str] = &["tokens_prompt", "prompt_tokens", "native_tokens_prompt", "input_tokens"];
+const OUTPUT_FIELDS: &[&str] = &["tokens_completion", "completion_tokens", "native_tokens_completion", "output_tokens"];
+
+/// A single billed call from a provider export
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProviderUsageRecord {
+    pub timestamp: DateTime<Utc>,
+    pub model: String,
+    pub cost: Decimal,
+    pub input_tokens: Option<u64>,
+    pub output_tokens: Option<u64>,
+}
+
+/// Options controlling how records are matched
+#[derive(Debug, Clone)]
+pub struct ReconciliationOptions {
+    /// Bucket size used to match settlements with provider records
+    pub window: Duration,
+    /// Absolute difference per bucket tolerated before reporting a mismatch
+    pub tolerance: Decimal,
+}
+
+impl Default for ReconciliationOptions {
+    fn default() -> Self {
+        Self {
+            window: Duration::hours(1),
+            tolerance: Decimal::new(1, 2), // $0.01
+        }
+    }
+}
+
+/// Outcome for one (window, model) bucket
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReconciliationStatus {
+    Matched,
+    Mismatch,
+    MissingInLedger,
+    MissingInProvider,
+}
+
+/// Comparison for one (window, model) bucket
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReconciliationLine {
+    pub window_start: DateTime<Utc>,
+    pub model: String,
+    pub ledger_total: Decimal,
+    pub provider_total: Decimal,
+    pub ledger_count: usize,
+    pub provider_count: usize,
+    /// `provider_total - ledger_total`
+    pub difference: Decimal,
+    pub status: ReconciliationStatus,
+}
+
+/// Full reconciliation report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReconciliationReport {
+    pub lines: Vec<ReconciliationLine>,
+    pub ledger_total: Decimal,
+    pub provider_total: Decimal,
+    pub discrepancies: usize,
+    pub generated_at: DateTime<Utc>,
+}
+
+impl ReconciliationReport {
+    /// Whether every bucket matched within tolerance
+    pub fn is_balanced(&self) -> bool {
+        self.discrepancies == 0
+    }
+}
+
+/// A ledger settlement reduced to what reconciliation needs
+#[derive(Debug, Clone)]
+pub(crate) struct SettlementRecord {
+    pub timestamp: DateTime<Utc>,
+    pub model: String,
+    pub cost: Decimal,
+}
+
+/// Parse a provider usage export (JSON array, `{"data": [...]}`, or CSV with a header row)
+pub fn parse_usage_export(export: &str) -> Result<Vec<ProviderUsageRecord>> {
+    let trimmed = export.trim_start();
+    let rows: Vec<HashMap<String, String>> = if trimmed.starts_with('[') || trimmed.starts_with('{') {
+        json_rows(trimmed)?
+    } else {
+        csv_rows(export)?
+    };
+
+    rows.iter()
+        .enumerate()
+        .map(|(idx, row)| parse_row(row).map_err(|e| LedgerError::InvalidExport(format!("row {}: {}", idx + 1, e))))
+        .collect()
+}
+
+fn json_rows(export: &str) -> Result<Vec<HashMap<String, String>>> {
+    let value: Value = serde_json::from_str(export)?;
+    let items = match value {
+        Value::Array(items) => items,
+        Value::Object(mut map) => match map.remove("data") {
+            Some(Value::Array(items)) => items,
+            _ => return Err(LedgerError::InvalidExport("expected an array or a 'data' array".to_string())),
+        },
+        _ => return Err(LedgerError::InvalidExport("expected an array or a 'data' array".to_string())),
+    };
+
+    Ok(items
+        .into_iter()
+        .filter_map(|item| match item {
+            Value::Object(map) => Some(
+                map.into_iter()
+                    .map(|(k, v)| {
+                        let v = match v {
+                            Value::String(s) => s,
+                            Value::Null => String::new(),
+                            other => other.to_string(),
+                        };
+                        (k.to_lowercase(), v)
+                    })
+                    .collect(),
+            ),
+            _ => None,
+        })
+        .collect())
+}
+
+fn csv_rows(export: &str) -> Result<Vec<HashMap<String, String>>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(export.as_bytes());
+    let headers: Vec<String> = reader
+        .headers()
+        .map_err(|e| LedgerError::InvalidExport(e.to_string()))?
+        .iter()
+        .map(|h| h.to_lowercase())
+        .collect();
+
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|e| LedgerError::InvalidExport(e.to_string()))?;
+        rows.push(headers.iter().cloned().zip(record.iter().map(str::to_string)).collect());
+    }
+    Ok(rows)
+}
+
+fn field<'a>(row: &'a HashMap<String, String>, names: &[&str]) -> Option<&'a str> {
+    names
+        .iter()
+        .filter_map(|n| row.get(*n))
+        .map(|v| v.as_str())
+        .find(|v| !v.is_empty())
+}
+
+fn parse_row(row: &HashMap<String, String>) -> std::result::Result<ProviderUsageRecord, String> {
+    let timestamp = field(row, TIMESTAMP_FIELDS).ok_or("missing timestamp")?;
+    let model = field(row, MODEL_FIELDS).ok_or("missing model")?;
+    let cost = field(row, COST_FIELDS).ok_or("missing cost")?;
+
+    let cost = Decimal::from_str(cost)
+        .or_else(|_| Decimal::from_scientific(cost))
+        .map_err(|e| format!("invalid cost '{}': {}", cost, e))?;
+
+    Ok(ProviderUsageRecord {
+        timestamp: parse_timestamp(timestamp)?,
+        model: model.to_string(),
+        cost,
+        input_tokens: field(row, INPUT_FIELDS).and_then(|v| v.parse().ok()),
+        output_tokens: field(row, OUTPUT_FIELDS).and_then(|v| v.parse().ok()),
+    })
+}
+
+fn parse_timestamp(value: &str) -> std::result::Result<DateTime<Utc>, String> {
+    if let Ok(ts) = DateTime::parse_from_rfc3339(value) {
+        return Ok(ts.with_timezone(&Utc));
+    }
+    for format in ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S%.f"] {
+        if let Ok(ts) = NaiveDateTime::parse_from_str(value, format) {
+            return Ok(ts.and_utc());
+        }
+    }
+    Err(format!("invalid timestamp '{}'", value))
+}
+
+/// Compare settlements with provider records bucket by bucket
+pub(crate) fn build_report(
+    settlements: &[SettlementRecord],
+    provider: &[ProviderUsageRecord],
+    options: &ReconciliationOptions,
+) -> Result<ReconciliationReport> {
+    #[derive(Default)]
+    struct Bucket {
+        ledger_total: Decimal,
+        provider_total: Decimal,
+        ledger_count: usize,
+        provider_count: usize,
+    }
+
+    let bucket_start = |ts: DateTime<Utc>| -> Result<DateTime<Utc>> {
+        ts.duration_trunc(options.window)
+            .map_err(|e| LedgerError::InvalidExport(format!("invalid reconciliation window: {}", e)))
+    };
+
+    let mut buckets: BTreeMap<(DateTime<Utc>, String), Bucket> = BTreeMap::new();
+
+    for record in provider {
+        let bucket = buckets.entry((bucket_start(record.timestamp)?, record.model.clone())).or_default();
+        bucket.provider_total += record.cost;
+        bucket.provider_count += 1;
+    }
+
+    // Only settlements inside the export's time span are comparable
+    let span = provider
+        .iter()
+        .map(|r| r.timestamp)
+        .min()
+        .zip(provider.iter().map(|r| r.timestamp).max());
+
+    if let Some((first, last)) = span {
+        let from = bucket_start(first)?;
+        let until = bucket_start(last)? + options.window;
+        for record in settlements.iter().filter(|s| s.timestamp >= from && s.timestamp < until) {
+            let bucket = buckets.entry((bucket_start(record.timestamp)?, record.model.clone())).or_default();
+            bucket.ledger_total += record.cost;
+            bucket.ledger_count += 1;
+        }
+    }
+
+    let lines: Vec<ReconciliationLine> = buckets
+        .into_iter()
+        .map(|((window_start, model), b)| {
+            let difference = b.provider_total - b.ledger_total;
+            let status = if b.ledger_count == 0 {
+                ReconciliationStatus::MissingInLedger
+            } else if b.provider_count == 0 {
+                ReconciliationStatus::MissingInProvider
+            } else if difference.abs() > options.tolerance {
+                ReconciliationStatus::Mismatch
+            } else {
+                ReconciliationStatus::Matched
+            };
+            ReconciliationLine {
+                window_start,
+                model,
+                ledger_total: b.ledger_total,
+                provider_total: b.provider_total,
+                ledger_count: b.ledger_count,
+                provider_count: b.provider_count,
+                difference,
+                status,
+            }
+        })
+        .collect();
+
+    Ok(ReconciliationReport {
+        ledger_total: lines.iter().map(|l| l.ledger_total).sum(),
+        provider_total: lines.iter().map(|l| l.provider_total).sum(),
+        discrepancies: lines.iter().filter(|l| l.status != ReconciliationStatus::Matched).count(),
+        lines,
+        generated_at: Utc::now(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_parse_csv_and_json_exports() {
+        let csv = "created_at,model,tokens_prompt,tokens_completion,cost\n\
+                   2025-01-01 10:05:00,openai/gpt-4o,1000,200,0.0150\n\
+                   2025-01-01T10:20:00Z,openai/gpt-4o,500,100,0.0075\n";
+        let records = parse_usage_export(csv).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].cost, dec!(0.0150));
+        assert_eq!(records[0].input_tokens, Some(1000));
+
+        let json = r#"{"data": [{"created_at": "2025-01-01T10:05:00Z", "model": "openai/gpt-4o", "usage": 0.015}]}"#;
+        let records = parse_usage_export(json).unwrap();
+        assert_eq!(records[0].cost, dec!(0.015));
+
+        assert!(parse_usage_export("model,cost\ngpt-4,0.1\n").is_err());
+    }
+}
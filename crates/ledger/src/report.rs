@@ -0,0 +1,159 @@
+//! Spend reports sliced by cost center
+//!
+//! Settlements carry the cost center tags of the budget they were booked
+//! against. A [`SpendReportQuery`] filters them by entity, time and tags and
+//! the resulting [`SpendReport`] totals spend per entity, per model and,
+//! with `group_by`, per value of one tag. Metered resources count toward
+//! every total; `metered` says how much of it they account for.
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::metering;
+use crate::types::{CostTags, LedgerEntry, TransactionType};
+
+/// Group for settlements without the `group_by` tag
+pub const UNTAGGED: &str = "(untagged)";
+
+/// Filter and grouping for a spend report
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SpendReportQuery {
+    pub entity_id: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    /// Tags a settlement must carry, all with these values
+    #[serde(default)]
+    pub tags: CostTags,
+    /// Tag key to total spend by
+    pub group_by: Option<String>,
+}
+
+impl SpendReportQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn entity(mut self, entity_id: impl Into<String>) -> Self {
+        self.entity_id = Some(entity_id.into());
+        self
+    }
+
+    pub fn between(mut self, since: DateTime<Utc>, until: DateTime<Utc>) -> Self {
+        self.since = Some(since);
+        self.until = Some(until);
+        self
+    }
+
+    pub fn since(mut self, since: DateTime<Utc>) -> Self {
+        self.since = Some(since);
+        self
+    }
+
+    pub fn tag(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.tags.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn group_by(mut self, key: impl Into<String>) -> Self {
+        self.group_by = Some(key.into());
+        self
+    }
+
+    /// Whether a settlement passes the filters
+    pub fn matches(&self, entry: &LedgerEntry) -> bool {
+        if entry.transaction_type != TransactionType::Settlement {
+            return false;
+        }
+        if self.entity_id.as_deref().is_some_and(|e| e != entry.entity_id) {
+            return false;
+        }
+        if self.since.is_some_and(|s| entry.timestamp < s) || self.until.is_some_and(|u| entry.timestamp > u) {
+            return false;
+        }
+        self.tags.iter().all(|(key, value)| entry.tags.get(key) == Some(value))
+    }
+}
+
+/// Settled spend matching a [`SpendReportQuery`]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SpendReport {
+    pub total: Decimal,
+    pub settlements: usize,
+    pub by_entity: BTreeMap<String, Decimal>,
+    /// Spend per model, and per resource key for metered resources
+    pub by_model: BTreeMap<String, Decimal>,
+    /// Part of `total` spent on metered resources rather than model calls
+    pub metered: Decimal,
+    /// Tag key the `by_group` totals are for
+    pub group_by: Option<String>,
+    /// Spend per value of the `group_by` tag, [`UNTAGGED`] for the rest
+    pub by_group: BTreeMap<String, Decimal>,
+}
+
+impl SpendReport {
+    pub fn build(entries: &[LedgerEntry], query: &SpendReportQuery) -> Self {
+        let mut report = SpendReport { group_by: query.group_by.clone(), ..Default::default() };
+        for entry in entries.iter().filter(|e| query.matches(e)) {
+            report.total += entry.amount;
+            report.settlements += 1;
+            *report.by_entity.entry(entry.entity_id.clone()).or_default() += entry.amount;
+            let model = entry.model.clone().unwrap_or_else(|| "unknown".to_string());
+            *report.by_model.entry(model).or_default() += entry.amount;
+            if metering::is_metered(entry) {
+                report.metered += entry.amount;
+            }
+            if let Some(ref key) = query.group_by {
+                let group = entry.tags.get(key).cloned().unwrap_or_else(|| UNTAGGED.to_string());
+                *report.by_group.entry(group).or_default() += entry.amount;
+            }
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn settlement(entity_id: &str, amount: Decimal, tags: &[(&str, &str)]) -> LedgerEntry {
+        LedgerEntry {
+            id: None,
+            timestamp: Utc::now(),
+            entity_id: entity_id.to_string(),
+            lease_id: None,
+            transaction_type: TransactionType::Settlement,
+            amount,
+            model: Some("gpt-4o-mini".to_string()),
+            details: String::new(),
+            sequence: 0,
+            prev_hash: None,
+            hash: None,
+            tags: tags.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+        }
+    }
+
+    #[test]
+    fn test_filter_and_group_by_tag() {
+        let entries = vec![
+            settlement("search", dec!(1.5), &[("customer", "acme"), ("environment", "prod")]),
+            settlement("search", dec!(0.5), &[("customer", "globex"), ("environment", "prod")]),
+            settlement("indexer", dec!(2), &[("environment", "prod")]),
+            settlement("indexer", dec!(4), &[("environment", "staging")]),
+            LedgerEntry { transaction_type: TransactionType::Grant, ..settlement("search", dec!(9), &[("environment", "prod")]) },
+        ];
+
+        let report = SpendReport::build(&entries, &SpendReportQuery::new().tag("environment", "prod").group_by("customer"));
+        assert_eq!(report.total, dec!(4));
+        assert_eq!(report.settlements, 3);
+        assert_eq!(report.by_entity["search"], dec!(2));
+        assert_eq!(report.by_group["acme"], dec!(1.5));
+        assert_eq!(report.by_group[UNTAGGED], dec!(2));
+
+        let acme = SpendReport::build(&entries, &SpendReportQuery::new().tag("customer", "acme"));
+        assert_eq!(acme.total, dec!(1.5));
+        assert!(acme.by_group.is_empty());
+    }
+}
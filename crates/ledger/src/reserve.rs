@@ -0,0 +1,43 @@
+//! Reserve pools for critical operations
+//!
+//! Governance work (Green-team reviews, safety checks, dissolution cleanup)
+//! must keep running after Blue-team spend has exhausted a budget. A
+//! [`ReservePool`] set aside for an entity, or for its tenant root, covers
+//! critical leases the budget can no longer grant. Normal leases never
+//! touch it.
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::types::EntityId;
+
+/// Budget held back for critical leases
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReservePool {
+    /// Entity (or tenant root) whose critical leases the pool covers
+    pub entity_id: EntityId,
+    pub limit: Decimal,
+    pub spent: Decimal,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ReservePool {
+    pub fn new(entity_id: impl Into<EntityId>, limit: Decimal) -> Self {
+        Self {
+            entity_id: entity_id.into(),
+            limit,
+            spent: Decimal::ZERO,
+            updated_at: Utc::now(),
+        }
+    }
+
+    pub fn remaining(&self) -> Decimal {
+        (self.limit - self.spent).max(Decimal::ZERO)
+    }
+
+    /// Whether a lease of `estimated_cost` fits in what's left
+    pub fn covers(&self, estimated_cost: Decimal) -> bool {
+        self.spent + estimated_cost <= self.limit
+    }
+}
@@ -0,0 +1,135 @@
+//! Graduated soft-limit enforcement
+//!
+//! On its own, going past a budget's soft limit only adds a warning to
+//! receipts. A [`ThrottlePolicy`] stored with the budget makes the Router act
+//! on it: the closer spend gets to the hard limit, the lower the highest tier
+//! it may route to and the more requests it batches into one call.
+
+use rust_decimal::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::types::Budget;
+
+/// One level of enforcement, active from `from_pressure` upwards
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ThrottleStep {
+    /// Where between the soft (0.0) and hard (1.0) limit the step starts
+    pub from_pressure: f64,
+    /// Highest tier requests may route to
+    pub max_tier: u8,
+    /// Requests merged into one call; 1 disables batching
+    pub batch_size: usize,
+    /// How long the first request of a batch waits for others
+    pub batch_window_ms: u64,
+}
+
+/// Steps of graduated enforcement for one budget
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ThrottlePolicy {
+    pub steps: Vec<ThrottleStep>,
+}
+
+impl Default for ThrottlePolicy {
+    /// Drop Tier 3 past the soft limit, then batch on Tier 1 and below
+    fn default() -> Self {
+        Self {
+            steps: vec![
+                ThrottleStep { from_pressure: 0.0, max_tier: 2, batch_size: 1, batch_window_ms: 0 },
+                ThrottleStep { from_pressure: 0.5, max_tier: 1, batch_size: 4, batch_window_ms: 250 },
+            ],
+        }
+    }
+}
+
+impl ThrottlePolicy {
+    /// The enforcement `budget` is under, or `None` below its soft limit
+    pub fn decide(&self, budget: &Budget) -> Option<ThrottleDecision> {
+        let pressure = pressure(budget)?;
+        let (step, active) = self
+            .steps
+            .iter()
+            .enumerate()
+            .filter(|(_, step)| pressure >= step.from_pressure)
+            .max_by(|(_, a), (_, b)| a.from_pressure.total_cmp(&b.from_pressure))?;
+        Some(ThrottleDecision {
+            entity_id: budget.entity_id.clone(),
+            pressure,
+            step,
+            max_tier: active.max_tier,
+            batch_size: active.batch_size.max(1),
+            batch_window_ms: active.batch_window_ms,
+            batched: 1,
+        })
+    }
+}
+
+/// How far spend is past the soft limit, as a share of the soft-to-hard gap
+///
+/// `None` at or below the soft limit. A budget whose soft limit is not below
+/// its hard limit is at full pressure as soon as it passes the soft limit.
+pub fn pressure(budget: &Budget) -> Option<f64> {
+    if budget.spent <= budget.soft_limit {
+        return None;
+    }
+    let gap = budget.hard_limit - budget.soft_limit;
+    if gap <= Decimal::ZERO {
+        return Some(1.0);
+    }
+    let share = (budget.spent - budget.soft_limit) / gap;
+    Some(share.to_f64().unwrap_or(1.0).min(1.0))
+}
+
+/// The throttle applied to a request, recorded on its routing logs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ThrottleDecision {
+    pub entity_id: String,
+    pub pressure: f64,
+    /// Index of the active step in the policy
+    pub step: usize,
+    pub max_tier: u8,
+    pub batch_size: usize,
+    pub batch_window_ms: u64,
+    /// Requests that shared the call; 1 when the request went alone
+    #[serde(default = "one")]
+    pub batched: usize,
+}
+
+fn one() -> usize {
+    1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn budget(spent: Decimal) -> Budget {
+        Budget {
+            entity_id: "agent".to_string(),
+            hard_limit: dec!(10),
+            soft_limit: dec!(6),
+            spent,
+            currency: "USD".to_string(),
+            status: Default::default(),
+            updated_at: chrono::Utc::now(),
+            tags: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_steps_tighten_towards_the_hard_limit() {
+        let policy = ThrottlePolicy::default();
+
+        assert_eq!(policy.decide(&budget(dec!(6))), None);
+
+        let light = policy.decide(&budget(dec!(7))).unwrap();
+        assert_eq!((light.step, light.max_tier, light.batch_size), (0, 2, 1));
+        assert_eq!(light.pressure, 0.25);
+
+        let heavy = policy.decide(&budget(dec!(9))).unwrap();
+        assert_eq!((heavy.step, heavy.max_tier, heavy.batch_size), (1, 1, 4));
+
+        assert_eq!(policy.decide(&budget(dec!(12))).unwrap().pressure, 1.0);
+        assert_eq!(ThrottlePolicy { steps: Vec::new() }.decide(&budget(dec!(9))), None);
+    }
+}
@@ -5,6 +5,7 @@ use chrono::{DateTime, Utc};
 // Re-export all Ledger types from Core to enforce single source of truth
 pub use zed42_core::ledger::{
     EntityId,
+    CostTags,
     LeaseId,
     Usage,
     RateTableEntry,
@@ -13,5 +14,6 @@ pub use zed42_core::ledger::{
     LedgerEntry,
     Receipt,
     Lease,
+    LeasePriority,
     BudgetStatus
 };
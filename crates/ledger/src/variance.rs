@@ -0,0 +1,115 @@
+//! Lease estimate variance
+//!
+//! Every settlement records what its lease reserved next to what the call
+//! actually cost. Leases that reserve far more than they spend hold budget
+//! other agents could have leased; [`VarianceStats`] shows by how much, per
+//! entity and model, and the records seed the Router's lease estimator.
+
+use chrono::{DateTime, Utc};
+use rust_decimal::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A settled lease: reserved versus spent
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VarianceRecord {
+    pub entity_id: String,
+    pub model: String,
+    pub estimated: Decimal,
+    pub actual: Decimal,
+    pub settled_at: DateTime<Utc>,
+}
+
+impl VarianceRecord {
+    /// Actual minus estimated; negative when the lease over-reserved
+    pub fn delta(&self) -> Decimal {
+        self.actual - self.estimated
+    }
+}
+
+/// Estimate accuracy for one entity and model
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VarianceStats {
+    pub entity_id: String,
+    pub model: String,
+    pub samples: usize,
+    pub mean_estimated: Decimal,
+    pub mean_actual: Decimal,
+    pub mean_delta: Decimal,
+    pub max_actual: Decimal,
+    /// Settlements that cost more than their lease reserved
+    pub underestimates: usize,
+    /// Share of the reserved total left unspent (0.8: leases reserve 5x what calls cost)
+    pub over_reserved: f64,
+}
+
+impl VarianceStats {
+    /// Statistics per entity and model, in that order
+    pub fn summarize(records: &[VarianceRecord]) -> Vec<Self> {
+        let mut groups: BTreeMap<(&str, &str), Vec<&VarianceRecord>> = BTreeMap::new();
+        for record in records {
+            groups.entry((&record.entity_id, &record.model)).or_default().push(record);
+        }
+
+        groups
+            .into_iter()
+            .map(|((entity_id, model), records)| {
+                let count = Decimal::from(records.len());
+                let estimated: Decimal = records.iter().map(|r| r.estimated).sum();
+                let actual: Decimal = records.iter().map(|r| r.actual).sum();
+                let over_reserved = if estimated > Decimal::ZERO {
+                    ((estimated - actual) / estimated).to_f64().unwrap_or(0.0)
+                } else {
+                    0.0
+                };
+                Self {
+                    entity_id: entity_id.to_string(),
+                    model: model.to_string(),
+                    samples: records.len(),
+                    mean_estimated: estimated / count,
+                    mean_actual: actual / count,
+                    mean_delta: (actual - estimated) / count,
+                    max_actual: records.iter().map(|r| r.actual).max().unwrap_or_default(),
+                    underestimates: records.iter().filter(|r| r.actual > r.estimated).count(),
+                    over_reserved,
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn record(entity_id: &str, model: &str, estimated: Decimal, actual: Decimal) -> VarianceRecord {
+        VarianceRecord {
+            entity_id: entity_id.to_string(),
+            model: model.to_string(),
+            estimated,
+            actual,
+            settled_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_summarize_per_entity_and_model() {
+        let records = vec![
+            record("coder", "gpt-4o", dec!(0.05), dec!(0.01)),
+            record("coder", "gpt-4o", dec!(0.05), dec!(0.03)),
+            record("coder", "gpt-4o", dec!(0.05), dec!(0.06)),
+            record("coder", "gpt-4o-mini", dec!(0.01), dec!(0.002)),
+        ];
+        let stats = VarianceStats::summarize(&records);
+
+        assert_eq!(stats.len(), 2);
+        let gpt4o = &stats[0];
+        assert_eq!((gpt4o.model.as_str(), gpt4o.samples, gpt4o.underestimates), ("gpt-4o", 3, 1));
+        assert_eq!(gpt4o.mean_actual.round_dp(4), dec!(0.0333));
+        assert_eq!(gpt4o.mean_delta.round_dp(4), dec!(-0.0167));
+        assert_eq!(gpt4o.max_actual, dec!(0.06));
+        assert!((gpt4o.over_reserved - 1.0 / 3.0).abs() < 1e-9);
+        assert!((stats[1].over_reserved - 0.8).abs() < 1e-9);
+    }
+}
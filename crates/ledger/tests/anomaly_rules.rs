@@ -0,0 +1,97 @@
+use chrono::Utc;
+use rust_decimal_macros::dec;
+use surrealdb::engine::any::connect;
+use zed42_ledger::{
+    anomaly::{AnomalyAction, AnomalyRule},
+    error::LedgerError,
+    types::{Budget, BudgetStatus, RateTableEntry, Usage},
+    IntelligenceLedger,
+};
+
+async fn setup_ledger(rules: Vec<AnomalyRule>) -> IntelligenceLedger {
+    let db = connect("mem://").await.expect("Failed to connect to memory db");
+    db.use_ns("zed42").use_db("ledger").await.expect("Failed to select namespace");
+    let ledger = IntelligenceLedger::new(db).with_anomaly_rules(rules);
+
+    ledger.set_budget(Budget {
+        entity_id: "loop-agent".to_string(),
+        hard_limit: dec!(100.00),
+        soft_limit: dec!(90.00),
+        spent: dec!(0.00),
+        currency: "USD".to_string(),
+        status: BudgetStatus::Active,
+        updated_at: Utc::now(),
+        tags: Default::default(),
+    }).await.expect("Failed to set budget");
+
+    ledger.set_rate(RateTableEntry {
+        model: "gpt-4".to_string(),
+        input_cost_per_1k: dec!(1.00),
+        output_cost_per_1k: dec!(1.00),
+        ..Default::default()
+    }).await.expect("Failed to set rate");
+
+    ledger
+}
+
+fn usage(tokens: u32) -> Usage {
+    Usage {
+        input_tokens: tokens,
+        output_tokens: 0,
+        model: "gpt-4".to_string(),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_lease_count_freeze() {
+    let ledger = setup_ledger(vec![
+        AnomalyRule::lease_count("lease-storm", 2, AnomalyAction::Freeze),
+    ]).await;
+
+    ledger.request_lease("loop-agent", dec!(0.10)).await.expect("First lease");
+    ledger.request_lease("loop-agent", dec!(0.10)).await.expect("Second lease");
+
+    // Third concurrent lease trips the rule and freezes the budget
+    let result = ledger.request_lease("loop-agent", dec!(0.10)).await;
+    assert!(matches!(result, Err(LedgerError::AnomalyDetected(_))));
+
+    let budget = ledger.get_budget("loop-agent").await.unwrap().unwrap();
+    assert_eq!(budget.status, BudgetStatus::Frozen);
+
+    let result = ledger.request_lease("loop-agent", dec!(0.10)).await;
+    assert!(matches!(result, Err(LedgerError::BudgetFrozen(_))));
+}
+
+#[tokio::test]
+async fn test_single_call_cost_warning() {
+    let ledger = setup_ledger(vec![
+        AnomalyRule::single_call_cost("expensive-call", dec!(1.00), AnomalyAction::Warn),
+    ]).await;
+
+    let lease = ledger.request_lease("loop-agent", dec!(5.00)).await.unwrap();
+    let receipt = ledger.commit_usage(&lease, usage(2000)).await.unwrap();
+
+    assert!(receipt.warning.unwrap().contains("expensive-call"));
+    let budget = ledger.get_budget("loop-agent").await.unwrap().unwrap();
+    assert_eq!(budget.status, BudgetStatus::Active);
+}
+
+#[tokio::test]
+async fn test_spend_rate_freeze() {
+    let ledger = setup_ledger(vec![
+        AnomalyRule::spend_rate("runaway-loop", dec!(1.50), AnomalyAction::Freeze),
+    ]).await;
+
+    let lease = ledger.request_lease("loop-agent", dec!(1.00)).await.unwrap();
+    let receipt = ledger.commit_usage(&lease, usage(1000)).await.unwrap();
+    assert!(receipt.warning.is_none());
+
+    let lease = ledger.request_lease("loop-agent", dec!(1.00)).await.unwrap();
+    let receipt = ledger.commit_usage(&lease, usage(1000)).await.unwrap();
+    assert!(receipt.warning.unwrap().contains("Budget frozen"));
+
+    let events = ledger.check_anomalies("loop-agent").await.unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].observed, dec!(2.00));
+}
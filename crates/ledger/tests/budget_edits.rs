@@ -0,0 +1,76 @@
+use chrono::Utc;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use surrealdb::engine::any::connect;
+use zed42_ledger::{
+    error::LedgerError,
+    types::{Budget, BudgetStatus},
+    IntelligenceLedger,
+};
+
+fn budget(entity_id: &str, hard_limit: Decimal, spent: Decimal, status: BudgetStatus) -> Budget {
+    Budget {
+        entity_id: entity_id.to_string(),
+        hard_limit,
+        soft_limit: hard_limit,
+        spent,
+        currency: "USD".to_string(),
+        status,
+        updated_at: Utc::now(),
+        tags: Default::default(),
+    }
+}
+
+async fn setup() -> IntelligenceLedger {
+    let db = connect("mem://").await.expect("Failed to connect to memory db");
+    db.use_ns("zed42").use_db("ledger").await.expect("Failed to select namespace");
+    let ledger = IntelligenceLedger::new(db);
+    ledger
+        .set_budget(budget("agent-1", dec!(1.00), dec!(1.00), BudgetStatus::Depleted))
+        .await
+        .unwrap();
+    ledger
+        .set_budget(budget("agent-2", dec!(1.00), dec!(0.20), BudgetStatus::Frozen))
+        .await
+        .unwrap();
+    ledger
+}
+
+#[tokio::test]
+async fn test_invalid_limits_are_rejected_without_audit() {
+    let ledger = setup().await;
+
+    let zero = ledger.update_budget_limits("agent-1", dec!(0), dec!(0), "ops").await;
+    assert!(matches!(zero, Err(LedgerError::InvalidBudget(_))));
+    let soft_above_hard = ledger.update_budget_limits("agent-1", dec!(2.00), dec!(3.00), "ops").await;
+    assert!(matches!(soft_above_hard, Err(LedgerError::InvalidBudget(_))));
+    let negative_soft = ledger.update_budget_limits("agent-1", dec!(2.00), dec!(-1), "ops").await;
+    assert!(matches!(negative_soft, Err(LedgerError::InvalidBudget(_))));
+    let missing = ledger.update_budget_limits("nobody", dec!(2.00), dec!(1.00), "ops").await;
+    assert!(matches!(missing, Err(LedgerError::BudgetNotFound(_))));
+
+    assert_eq!(ledger.get_budget("agent-1").await.unwrap().unwrap().hard_limit, dec!(1.00));
+    assert!(ledger.audit_log(None).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_raising_limit_reactivates_depleted_budget_and_is_audited() {
+    let ledger = setup().await;
+
+    let raised = ledger.update_budget_limits("agent-1", dec!(5.00), dec!(4.00), "alice").await.unwrap();
+    assert_eq!(raised.status, BudgetStatus::Active);
+    assert_eq!(raised.soft_limit, dec!(4.00));
+    assert_eq!(ledger.get_budget("agent-1").await.unwrap().unwrap().hard_limit, dec!(5.00));
+
+    // Frozen budgets stay frozen whatever the new limits
+    let frozen = ledger.update_budget_limits("agent-2", dec!(5.00), dec!(5.00), "bob").await.unwrap();
+    assert_eq!(frozen.status, BudgetStatus::Frozen);
+
+    let audit = ledger.audit_log(Some("agent-1")).await.unwrap();
+    assert_eq!(audit.len(), 1);
+    assert_eq!(audit[0].details, "Budget limits changed by alice: hard 1.00 -> 5.00, soft 1.00 -> 4.00");
+    assert_eq!(ledger.audit_log(None).await.unwrap().len(), 2);
+
+    let report = ledger.verify_chain().await.unwrap();
+    assert!(report.is_valid(), "Unexpected issues: {:?}", report.issues);
+}
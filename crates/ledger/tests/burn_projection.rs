@@ -0,0 +1,63 @@
+use chrono::Utc;
+use rust_decimal_macros::dec;
+use surrealdb::engine::any::connect;
+use zed42_ledger::{
+    projection::{ProjectionPolicy, TaskOutlook},
+    types::{Budget, BudgetStatus, RateTableEntry, Usage},
+    IntelligenceLedger,
+};
+
+async fn setup_ledger() -> IntelligenceLedger {
+    let db = connect("mem://").await.expect("Failed to connect to memory db");
+    db.use_ns("zed42").use_db("ledger").await.expect("Failed to select namespace");
+    let ledger = IntelligenceLedger::new(db);
+
+    ledger.set_budget(Budget {
+        entity_id: "session".to_string(),
+        hard_limit: dec!(100.00),
+        soft_limit: dec!(90.00),
+        spent: dec!(0.00),
+        currency: "USD".to_string(),
+        status: BudgetStatus::Active,
+        updated_at: Utc::now(),
+        tags: Default::default(),
+    }).await.expect("Failed to set budget");
+
+    ledger.set_rate(RateTableEntry {
+        model: "gpt-4".to_string(),
+        input_cost_per_1k: dec!(1.00),
+        output_cost_per_1k: dec!(1.00),
+        ..Default::default()
+    }).await.expect("Failed to set rate");
+
+    ledger
+}
+
+#[tokio::test]
+async fn test_projection_from_settlements() {
+    let ledger = setup_ledger().await;
+    let policy = ProjectionPolicy::default();
+
+    // A single settlement is not a rate yet
+    let lease = ledger.request_lease("session", dec!(10.00)).await.unwrap();
+    ledger.commit_usage(&lease, Usage { input_tokens: 10_000, model: "gpt-4".to_string(), ..Default::default() }).await.unwrap();
+    let projection = ledger.project_burn("session", &policy).await.unwrap().unwrap();
+    assert_eq!(projection.exhausts_in_secs, None);
+
+    // $20 within the first minute: the remaining $80 lasts four minutes
+    let lease = ledger.request_lease("session", dec!(10.00)).await.unwrap();
+    ledger.commit_usage(&lease, Usage { input_tokens: 10_000, model: "gpt-4".to_string(), ..Default::default() }).await.unwrap();
+    let projection = ledger
+        .project_burn("session", &policy)
+        .await
+        .unwrap()
+        .unwrap()
+        .with_tasks(TaskOutlook { remaining_tasks: 5, cost_per_task: Some(dec!(20.00)) });
+    assert_eq!(projection.per_minute, dec!(20));
+    assert_eq!(projection.exhausts_in_secs, Some(240));
+    assert!(projection.should_warn(&policy));
+    assert_eq!(projection.tasks_at_risk(), Some(1));
+    assert!(projection.message().ends_with("exhausts in ~4 minutes, before 1 of 5 remaining tasks complete"));
+
+    assert!(ledger.project_burn("nobody", &policy).await.unwrap().is_none());
+}
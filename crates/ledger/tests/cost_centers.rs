@@ -0,0 +1,75 @@
+use chrono::Utc;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use surrealdb::engine::any::connect;
+use zed42_ledger::{
+    report::{SpendReportQuery, UNTAGGED},
+    types::{Budget, BudgetStatus, RateTableEntry, Usage},
+    IntelligenceLedger,
+};
+
+fn budget(entity_id: &str) -> Budget {
+    Budget {
+        entity_id: entity_id.to_string(),
+        hard_limit: dec!(100.00),
+        soft_limit: dec!(90.00),
+        spent: dec!(0.00),
+        currency: "USD".to_string(),
+        status: BudgetStatus::Active,
+        updated_at: Utc::now(),
+        tags: Default::default(),
+    }
+}
+
+async fn spend(ledger: &IntelligenceLedger, entity_id: &str, input_tokens: u32) -> Decimal {
+    let lease = ledger.request_lease(entity_id, dec!(1.00)).await.unwrap();
+    let receipt = ledger
+        .commit_usage(&lease, Usage { input_tokens, model: "gpt-4".to_string(), ..Default::default() })
+        .await
+        .unwrap();
+    receipt.cost
+}
+
+#[tokio::test]
+async fn test_tags_flow_into_entries_receipts_and_reports() {
+    let db = connect("mem://").await.expect("Failed to connect to memory db");
+    db.use_ns("zed42").use_db("ledger").await.expect("Failed to select namespace");
+    let ledger = IntelligenceLedger::new(db).with_receipt_signing_key("secret");
+
+    // The tenant root's customer tag reaches its agents; the agent's own tags win
+    ledger.set_budget(budget("acme").with_tag("customer", "acme").with_tag("environment", "prod")).await.unwrap();
+    ledger.set_budget(budget("acme/search").with_tag("feature", "search").with_tag("environment", "staging")).await.unwrap();
+    ledger.set_budget(budget("indexer").with_tag("feature", "indexing")).await.unwrap();
+    ledger.set_rate(RateTableEntry {
+        model: "gpt-4".to_string(),
+        input_cost_per_1k: dec!(1.00),
+        output_cost_per_1k: dec!(1.00),
+        ..Default::default()
+    }).await.unwrap();
+
+    let lease = ledger.request_lease("acme/search", dec!(1.00)).await.unwrap();
+    let receipt = ledger
+        .commit_usage(&lease, Usage { input_tokens: 500, model: "gpt-4".to_string(), ..Default::default() })
+        .await
+        .unwrap();
+    assert_eq!(receipt.tags.get("customer").map(String::as_str), Some("acme"));
+    assert_eq!(receipt.tags.get("environment").map(String::as_str), Some("staging"));
+    assert!(ledger.verify_receipt(&receipt));
+
+    spend(&ledger, "acme/search", 250).await;
+    spend(&ledger, "indexer", 2_000).await;
+    assert!(ledger.verify_chain().await.unwrap().is_valid());
+
+    let by_feature = ledger.spend_report(&SpendReportQuery::new().group_by("feature")).await.unwrap();
+    assert_eq!(by_feature.total, dec!(2.75));
+    assert_eq!(by_feature.settlements, 3);
+    assert_eq!(by_feature.by_group["search"], dec!(0.75));
+    assert_eq!(by_feature.by_group["indexing"], dec!(2.00));
+
+    let acme = ledger.spend_report(&SpendReportQuery::new().tag("customer", "acme").group_by("customer")).await.unwrap();
+    assert_eq!(acme.total, dec!(0.75));
+    assert_eq!(acme.by_entity.keys().collect::<Vec<_>>(), ["acme/search"]);
+
+    let by_customer = ledger.spend_report(&SpendReportQuery::new().group_by("customer")).await.unwrap();
+    assert_eq!(by_customer.by_group[UNTAGGED], dec!(2.00));
+}
@@ -0,0 +1,142 @@
+use chrono::Utc;
+use rust_decimal_macros::dec;
+use surrealdb::engine::any::{connect, Any};
+use surrealdb::Surreal;
+use zed42_ledger::{
+    chain::ChainIssueKind,
+    types::{Budget, BudgetStatus, RateTableEntry, Usage},
+    IntelligenceLedger,
+};
+
+async fn setup() -> (Surreal<Any>, IntelligenceLedger) {
+    let db = connect("mem://").await.expect("Failed to connect to memory db");
+    db.use_ns("zed42").use_db("ledger").await.expect("Failed to select namespace");
+    let ledger = IntelligenceLedger::new(db.clone()).with_receipt_signing_key(b"test-key".to_vec());
+
+    ledger.set_budget(Budget {
+        entity_id: "agent-1".to_string(),
+        hard_limit: dec!(10.00),
+        soft_limit: dec!(8.00),
+        spent: dec!(0.00),
+        currency: "USD".to_string(),
+        status: BudgetStatus::Active,
+        updated_at: Utc::now(),
+        tags: Default::default(),
+    }).await.expect("Failed to set budget");
+
+    ledger.set_rate(RateTableEntry {
+        model: "gpt-4".to_string(),
+        input_cost_per_1k: dec!(0.03),
+        output_cost_per_1k: dec!(0.06),
+        ..Default::default()
+    }).await.expect("Failed to set rate");
+
+    for _ in 0..2 {
+        let lease = ledger.request_lease("agent-1", dec!(1.00)).await.expect("Lease denied");
+        let receipt = ledger.commit_usage(&lease, Usage {
+            input_tokens: 1000,
+            output_tokens: 1000,
+            model: "gpt-4".to_string(),
+            ..Default::default()
+        }).await.expect("Commit failed");
+        assert!(ledger.verify_receipt(&receipt));
+    }
+
+    (db, ledger)
+}
+
+#[tokio::test]
+async fn test_chain_is_valid_after_normal_operation() {
+    let (_db, ledger) = setup().await;
+    let report = ledger.verify_chain().await.expect("Verification failed");
+
+    assert!(report.is_valid(), "Unexpected issues: {:?}", report.issues);
+    assert_eq!(report.entries_checked, 4); // 2 grants + 2 settlements
+    assert!(report.head_hash.is_some());
+}
+
+#[tokio::test]
+async fn test_chain_detects_tampering_and_deletion() {
+    let (db, ledger) = setup().await;
+
+    db.query("UPDATE ledger_entries SET details = 'rewritten' WHERE sequence = 2")
+        .await
+        .expect("Tamper failed");
+    db.query("DELETE ledger_entries WHERE sequence = 3")
+        .await
+        .expect("Delete failed");
+
+    let report = ledger.verify_chain().await.expect("Verification failed");
+    assert!(!report.is_valid());
+
+    let kinds: Vec<(u64, ChainIssueKind)> = report.issues.iter().map(|i| (i.sequence, i.kind)).collect();
+    assert!(kinds.contains(&(2, ChainIssueKind::HashMismatch)));
+    assert!(kinds.contains(&(4, ChainIssueKind::MissingEntries)));
+}
+
+#[tokio::test]
+async fn test_tampered_receipt_fails_verification() {
+    let (_db, ledger) = setup().await;
+    let lease = ledger.request_lease("agent-1", dec!(1.00)).await.unwrap();
+    let mut receipt = ledger.commit_usage(&lease, Usage {
+        input_tokens: 10,
+        output_tokens: 10,
+        model: "gpt-4".to_string(),
+        ..Default::default()
+    }).await.unwrap();
+
+    receipt.cost = dec!(0.00);
+    assert!(!ledger.verify_receipt(&receipt));
+}
+
+#[tokio::test]
+async fn test_chain_detects_deleted_newest_entries() {
+    let (db, ledger) = setup().await;
+
+    db.query("DELETE ledger_entries WHERE sequence >= 3")
+        .await
+        .expect("Delete failed");
+
+    let report = ledger.verify_chain().await.expect("Verification failed");
+    assert_eq!(report.entries_checked, 2);
+    let kinds: Vec<(u64, ChainIssueKind)> = report.issues.iter().map(|i| (i.sequence, i.kind)).collect();
+    assert_eq!(kinds, vec![(4, ChainIssueKind::Truncated)]);
+}
+
+#[tokio::test]
+async fn test_legacy_chain_without_head_verifies_and_gains_one_on_append() {
+    let (db, ledger) = setup().await;
+    db.query("DELETE ledger_head").await.expect("Delete failed");
+
+    let report = ledger.verify_chain().await.expect("Verification failed");
+    assert!(report.is_valid(), "Unexpected issues: {:?}", report.issues);
+
+    ledger.record_audit("agent-1", "after upgrade").await.expect("Append failed");
+    db.query("DELETE ledger_entries WHERE sequence = 5")
+        .await
+        .expect("Delete failed");
+
+    let report = ledger.verify_chain().await.expect("Verification failed");
+    let kinds: Vec<(u64, ChainIssueKind)> = report.issues.iter().map(|i| (i.sequence, i.kind)).collect();
+    assert_eq!(kinds, vec![(5, ChainIssueKind::Truncated)]);
+}
+
+#[tokio::test]
+async fn test_instances_sharing_a_database_do_not_fork_the_chain() {
+    let (db, ledger) = setup().await;
+    let other = IntelligenceLedger::new(db.clone());
+
+    let append = |ledger: &IntelligenceLedger, name: &'static str| {
+        let ledger = ledger.clone();
+        async move {
+            for i in 0..5 {
+                ledger.record_audit("agent-1", format!("{} instance {}", name, i)).await.expect("Append failed");
+            }
+        }
+    };
+    tokio::join!(append(&ledger, "first"), append(&other, "second"));
+
+    let report = ledger.verify_chain().await.expect("Verification failed");
+    assert!(report.is_valid(), "Unexpected issues: {:?}", report.issues);
+    assert_eq!(report.entries_checked, 14);
+}
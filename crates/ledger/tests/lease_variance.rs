@@ -0,0 +1,50 @@
+use chrono::Utc;
+use rust_decimal_macros::dec;
+use surrealdb::engine::any::connect;
+use zed42_ledger::{
+    types::{Budget, BudgetStatus, RateTableEntry, Usage},
+    IntelligenceLedger,
+};
+
+#[tokio::test]
+async fn test_settlements_record_estimate_variance() {
+    let db = connect("mem://").await.expect("Failed to connect to memory db");
+    db.use_ns("zed42").use_db("ledger").await.expect("Failed to select namespace");
+    let ledger = IntelligenceLedger::new(db);
+
+    ledger.set_budget(Budget {
+        entity_id: "coder".to_string(),
+        hard_limit: dec!(10.00),
+        soft_limit: dec!(8.00),
+        spent: dec!(0.00),
+        currency: "USD".to_string(),
+        status: BudgetStatus::Active,
+        updated_at: Utc::now(),
+        tags: Default::default(),
+    }).await.expect("Failed to set budget");
+    ledger.set_rate(RateTableEntry {
+        model: "gpt-4".to_string(),
+        input_cost_per_1k: dec!(0.01),
+        output_cost_per_1k: dec!(0.01),
+        ..Default::default()
+    }).await.expect("Failed to set rate");
+
+    for input_tokens in [1_000, 3_000] {
+        let lease = ledger.request_lease("coder", dec!(0.05)).await.unwrap();
+        ledger.commit_usage(&lease, Usage { input_tokens, model: "gpt-4".to_string(), ..Default::default() }).await.unwrap();
+    }
+    // A lease released without usage is not a settlement to learn from
+    let lease = ledger.request_lease("coder", dec!(0.05)).await.unwrap();
+    ledger.commit_usage(&lease, Usage { model: "gpt-4".to_string(), ..Default::default() }).await.unwrap();
+
+    let records = ledger.variance_records(Some("coder")).await.unwrap();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].delta(), dec!(-0.04));
+
+    let stats = ledger.lease_variance(None).await.unwrap();
+    assert_eq!(stats.len(), 1);
+    assert_eq!(stats[0].samples, 2);
+    assert_eq!(stats[0].mean_actual, dec!(0.02));
+    assert_eq!(stats[0].mean_delta, dec!(-0.03));
+    assert!((stats[0].over_reserved - 0.6).abs() < 1e-9);
+}
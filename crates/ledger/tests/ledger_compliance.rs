@@ -27,12 +27,14 @@ async fn test_ledger_happy_path() {
         spent: dec!(0.00),
         currency: "USD".to_string(),
         updated_at: Utc::now(),
+        tags: Default::default(),
     }).await.expect("Failed to set budget");
 
     ledger.set_rate(RateTableEntry {
         model: model.to_string(),
         input_cost_per_1k: dec!(0.03),
         output_cost_per_1k: dec!(0.06),
+        ..Default::default()
     }).await.expect("Failed to set rate");
 
     // 2. Request Lease (Estimate $1.00)
@@ -43,6 +45,7 @@ async fn test_ledger_happy_path() {
         input_tokens: 1000,
         output_tokens: 1000,
         model: model.to_string(),
+        ..Default::default()
     };
 
     let receipt = ledger.commit_usage(&lease_id, usage).await.expect("Commit failed");
@@ -69,6 +72,7 @@ async fn test_hard_cap_rejection() {
         spent: dec!(0.95),
         currency: "USD".to_string(),
         updated_at: Utc::now(),
+        tags: Default::default(),
     }).await.expect("Failed to set budget");
 
     // Request $0.06 (Total would be $1.01) -> Should Reject
@@ -94,12 +98,14 @@ async fn test_soft_cap_warning() {
         spent: dec!(4.95),
         currency: "USD".to_string(),
         updated_at: Utc::now(),
+        tags: Default::default(),
     }).await.expect("Failed to set budget");
 
     ledger.set_rate(RateTableEntry {
         model: model.to_string(),
         input_cost_per_1k: dec!(1.00), 
         output_cost_per_1k: dec!(1.00),
+        ..Default::default()
     }).await.expect("Failed to set rate");
 
     // Request $0.10 (Total $5.05) -> Lease Granted (Under Hard Cap)
@@ -110,6 +116,7 @@ async fn test_soft_cap_warning() {
         input_tokens: 50, // $0.05
         output_tokens: 50, // $0.05
         model: model.to_string(),
+        ..Default::default()
     };
 
     let receipt = ledger.commit_usage(&lease_id, usage).await.expect("Commit failed");
@@ -133,6 +140,7 @@ async fn test_precision_math() {
         spent: dec!(0.0000),
         currency: "USD".to_string(),
         updated_at: Utc::now(),
+        tags: Default::default(),
     }).await.expect("Failed to set budget");
 
     // Rate: $0.0001 per 1k input
@@ -140,6 +148,7 @@ async fn test_precision_math() {
         model: model.to_string(),
         input_cost_per_1k: dec!(0.0001), 
         output_cost_per_1k: dec!(0.0001),
+        ..Default::default()
     }).await.expect("Failed to set rate");
 
     let lease_id = ledger.request_lease(entity_id, dec!(0.10)).await.expect("Lease denied");
@@ -148,6 +157,7 @@ async fn test_precision_math() {
         input_tokens: 1500, // 1.5 * 0.0001 = 0.00015
         output_tokens: 0,
         model: model.to_string(),
+        ..Default::default()
     };
 
     let receipt = ledger.commit_usage(&lease_id, usage).await.expect("Commit failed");
@@ -158,3 +168,46 @@ async fn test_precision_math() {
     let budget = ledger.get_budget(entity_id).await.expect("DB error").unwrap();
     assert_eq!(budget.spent, dec!(0.00015));
 }
+
+#[tokio::test]
+async fn test_cache_reads_billed_at_cache_rate() {
+    let ledger = setup_ledger().await;
+    let entity_id = "agent-cache";
+    let model = "anthropic/claude-3.5-sonnet";
+
+    ledger.set_budget(Budget {
+        entity_id: entity_id.to_string(),
+        hard_limit: dec!(10.00),
+        soft_limit: dec!(8.00),
+        spent: dec!(0.00),
+        currency: "USD".to_string(),
+        updated_at: Utc::now(),
+        status: Default::default(),
+        tags: Default::default(),
+    }).await.expect("Failed to set budget");
+
+    // Reads at 10% of input, writes at 125%
+    ledger.set_rate(RateTableEntry {
+        model: model.to_string(),
+        input_cost_per_1k: dec!(0.003),
+        output_cost_per_1k: dec!(0.015),
+        cache_read_cost_per_1k: Some(dec!(0.0003)),
+        cache_write_cost_per_1k: Some(dec!(0.00375)),
+    }).await.expect("Failed to set rate");
+
+    let lease_id = ledger.request_lease(entity_id, dec!(0.10)).await.expect("Lease denied");
+    let usage = Usage {
+        input_tokens: 10_000,
+        output_tokens: 1000,
+        model: model.to_string(),
+        cache_read_tokens: 8000,
+        cache_write_tokens: 1000,
+    };
+    let receipt = ledger.commit_usage(&lease_id, usage).await.expect("Commit failed");
+
+    // 1k uncached 0.003 + 8k read 0.0024 + 1k write 0.00375 + 1k out 0.015
+    assert_eq!(receipt.cost, dec!(0.02415));
+    // Uncached: 10k in 0.03 + 1k out 0.015 = 0.045
+    assert_eq!(receipt.cache_savings, dec!(0.02085));
+    assert_eq!(ledger.get_budget(entity_id).await.unwrap().unwrap().spent, dec!(0.02415));
+}
@@ -0,0 +1,73 @@
+use chrono::Utc;
+use rust_decimal_macros::dec;
+use surrealdb::engine::any::connect;
+use zed42_ledger::{
+    reconcile::ReconciliationStatus,
+    types::{Budget, BudgetStatus, RateTableEntry, Usage},
+    IntelligenceLedger,
+};
+
+async fn setup_ledger() -> IntelligenceLedger {
+    let db = connect("mem://").await.expect("Failed to connect to memory db");
+    db.use_ns("zed42").use_db("ledger").await.expect("Failed to select namespace");
+    let ledger = IntelligenceLedger::new(db);
+
+    ledger.set_budget(Budget {
+        entity_id: "agent-1".to_string(),
+        hard_limit: dec!(10.00),
+        soft_limit: dec!(8.00),
+        spent: dec!(0.00),
+        currency: "USD".to_string(),
+        status: BudgetStatus::Active,
+        updated_at: Utc::now(),
+        tags: Default::default(),
+    }).await.expect("Failed to set budget");
+
+    for model in ["openai/gpt-4o", "anthropic/claude-3-haiku"] {
+        ledger.set_rate(RateTableEntry {
+            model: model.to_string(),
+            input_cost_per_1k: dec!(0.01),
+            output_cost_per_1k: dec!(0.03),
+            ..Default::default()
+        }).await.expect("Failed to set rate");
+    }
+
+    ledger
+}
+
+async fn settle(ledger: &IntelligenceLedger, model: &str) {
+    let lease = ledger.request_lease("agent-1", dec!(1.00)).await.expect("Lease denied");
+    ledger.commit_usage(&lease, Usage {
+        input_tokens: 1000,
+        output_tokens: 1000,
+        model: model.to_string(),
+        ..Default::default()
+    }).await.expect("Commit failed");
+}
+
+#[tokio::test]
+async fn test_reconcile_detects_discrepancies() {
+    let ledger = setup_ledger().await;
+    settle(&ledger, "openai/gpt-4o").await; // $0.04
+    settle(&ledger, "anthropic/claude-3-haiku").await; // $0.04
+
+    let now = Utc::now().to_rfc3339();
+    let export = format!(
+        "created_at,model,tokens_prompt,tokens_completion,cost\n\
+         {now},openai/gpt-4o,1000,1000,0.04\n\
+         {now},anthropic/claude-3-haiku,1000,1000,0.09\n\
+         {now},meta/llama-3-70b,500,500,0.02\n"
+    );
+
+    let report = ledger.reconcile(&export).await.expect("Reconcile failed");
+
+    let status_of = |model: &str| {
+        report.lines.iter().find(|l| l.model == model).map(|l| l.status)
+    };
+    assert_eq!(status_of("openai/gpt-4o"), Some(ReconciliationStatus::Matched));
+    assert_eq!(status_of("anthropic/claude-3-haiku"), Some(ReconciliationStatus::Mismatch));
+    assert_eq!(status_of("meta/llama-3-70b"), Some(ReconciliationStatus::MissingInLedger));
+    assert_eq!(report.discrepancies, 2);
+    assert_eq!(report.ledger_total, dec!(0.08));
+    assert!(!report.is_balanced());
+}
@@ -0,0 +1,79 @@
+use chrono::Utc;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use surrealdb::engine::any::connect;
+use zed42_ledger::{
+    error::LedgerError,
+    reserve::ReservePool,
+    types::{Budget, BudgetStatus, LeasePriority, RateTableEntry, Usage},
+    IntelligenceLedger,
+};
+
+fn budget(entity_id: &str, hard_limit: Decimal, spent: Decimal) -> Budget {
+    Budget {
+        entity_id: entity_id.to_string(),
+        hard_limit,
+        soft_limit: hard_limit,
+        spent,
+        currency: "USD".to_string(),
+        status: BudgetStatus::Active,
+        updated_at: Utc::now(),
+        tags: Default::default(),
+    }
+}
+
+async fn setup() -> IntelligenceLedger {
+    let db = connect("mem://").await.expect("Failed to connect to memory db");
+    db.use_ns("zed42").use_db("ledger").await.expect("Failed to select namespace");
+    let ledger = IntelligenceLedger::new(db);
+    ledger.set_rate(RateTableEntry {
+        model: "gpt-4".to_string(),
+        input_cost_per_1k: dec!(0.10),
+        output_cost_per_1k: dec!(0.10),
+        ..Default::default()
+    }).await.unwrap();
+    ledger
+}
+
+fn usage(input_tokens: u32) -> Usage {
+    Usage { input_tokens, model: "gpt-4".to_string(), ..Default::default() }
+}
+
+#[tokio::test]
+async fn test_only_critical_leases_draw_on_the_reserve() {
+    let ledger = setup().await;
+    // Blue-team spend has used up the session budget
+    ledger.set_budget(budget("session", dec!(1.00), dec!(1.00))).await.unwrap();
+    ledger.set_reserve_pool(ReservePool::new("session", dec!(0.25))).await.unwrap();
+
+    let normal = ledger.request_lease("session", dec!(0.10)).await;
+    assert!(matches!(normal, Err(LedgerError::BudgetExceeded(_))));
+
+    let lease = ledger.request_priority_lease("session", dec!(0.10), LeasePriority::Critical).await.unwrap();
+    let receipt = ledger.commit_usage(&lease, usage(1_000)).await.unwrap();
+    assert_eq!(receipt.remaining_budget, dec!(0.15));
+    assert!(receipt.warning.unwrap().contains("reserve pool"));
+
+    // The reserve absorbs the spend; the budget itself doesn't move
+    assert_eq!(ledger.get_budget("session").await.unwrap().unwrap().spent, dec!(1.00));
+    assert_eq!(ledger.get_reserve_pool("session").await.unwrap().unwrap().spent, dec!(0.10));
+
+    // Once the reserve is drained too, critical leases are refused
+    let drained = ledger.request_priority_lease("session", dec!(0.20), LeasePriority::Critical).await;
+    assert!(matches!(drained, Err(LedgerError::BudgetExceeded(_))));
+}
+
+#[tokio::test]
+async fn test_tenant_reserve_covers_its_agents() {
+    let ledger = setup().await;
+    ledger.set_budget(budget("acme", dec!(1.00), dec!(0.95))).await.unwrap();
+    ledger.set_budget(budget("acme/green-reviewer", dec!(5.00), dec!(0.00))).await.unwrap();
+    ledger.set_reserve_pool(ReservePool::new("acme", dec!(0.50))).await.unwrap();
+
+    // The agent has room but its tenant root doesn't
+    let lease = ledger.request_priority_lease("acme/green-reviewer", dec!(0.10), LeasePriority::Critical).await.unwrap();
+    ledger.commit_usage(&lease, usage(500)).await.unwrap();
+
+    assert_eq!(ledger.get_reserve_pool("acme").await.unwrap().unwrap().spent, dec!(0.05));
+    assert_eq!(ledger.get_budget("acme").await.unwrap().unwrap().spent, dec!(0.95));
+}
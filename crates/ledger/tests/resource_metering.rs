@@ -0,0 +1,61 @@
+use chrono::Utc;
+use rust_decimal_macros::dec;
+use surrealdb::engine::any::connect;
+use zed42_ledger::{
+    error::LedgerError,
+    metering::{Resource, ResourceRate, ResourceUsage},
+    report::SpendReportQuery,
+    types::{Budget, BudgetStatus, RateTableEntry, Usage},
+    IntelligenceLedger,
+};
+
+#[tokio::test]
+async fn test_run_cost_includes_metered_resources() {
+    let db = connect("mem://").await.expect("Failed to connect to memory db");
+    db.use_ns("zed42").use_db("ledger").await.expect("Failed to select namespace");
+    let ledger = IntelligenceLedger::new(db);
+
+    ledger.set_budget(Budget {
+        entity_id: "run-42".to_string(),
+        hard_limit: dec!(10.00),
+        soft_limit: dec!(1.00),
+        spent: dec!(0.00),
+        currency: "USD".to_string(),
+        status: BudgetStatus::Active,
+        updated_at: Utc::now(),
+        tags: Default::default(),
+    }).await.expect("Failed to set budget");
+    ledger.set_rate(RateTableEntry {
+        model: "gpt-4".to_string(),
+        input_cost_per_1k: dec!(0.50),
+        output_cost_per_1k: dec!(0.50),
+        ..Default::default()
+    }).await.expect("Failed to set rate");
+    ledger.set_resource_rate(ResourceRate::new(&Resource::SandboxCpu, dec!(0.001))).await.unwrap();
+    ledger.set_resource_rate(ResourceRate::new(&Resource::ApiCall("forge".into()), dec!(0.01))).await.unwrap();
+    ledger.set_resource_rate(ResourceRate::new(&Resource::Storage, dec!(0.10))).await.unwrap();
+
+    let lease = ledger.request_lease("run-42", dec!(1.00)).await.unwrap();
+    ledger.commit_usage(&lease, Usage { input_tokens: 2_000, model: "gpt-4".to_string(), ..Default::default() }).await.unwrap();
+
+    ledger.meter("run-42", ResourceUsage::cpu_seconds(dec!(300))).await.unwrap();
+    ledger.meter("run-42", ResourceUsage::api_calls("forge", 5)).await.unwrap();
+    let receipt = ledger.meter("run-42", ResourceUsage::storage_growth(2_000_000_000)).await.unwrap();
+    assert_eq!(receipt.cost, dec!(0.20));
+    assert_eq!(receipt.remaining_budget, dec!(8.45));
+    assert!(receipt.warning.unwrap().contains("Soft limit exceeded"));
+
+    let report = ledger.spend_report(&SpendReportQuery::new().entity("run-42")).await.unwrap();
+    assert_eq!(report.total, dec!(1.55));
+    assert_eq!(report.metered, dec!(0.55));
+    assert_eq!(report.by_model["resource:sandbox_cpu"], dec!(0.30));
+    assert_eq!(report.by_model["gpt-4"], dec!(1.00));
+
+    // Provider exports know nothing of metered resources
+    let reconciliation = ledger.reconcile("[]").await.unwrap();
+    assert_eq!(reconciliation.ledger_total, dec!(1.00));
+
+    // Unpriced resources are rejected rather than booked at zero
+    let unpriced = ledger.meter("run-42", ResourceUsage::api_calls("web_fetch", 1)).await;
+    assert!(matches!(unpriced, Err(LedgerError::RateNotFound(_))));
+}
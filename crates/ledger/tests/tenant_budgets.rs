@@ -0,0 +1,88 @@
+use chrono::Utc;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use surrealdb::engine::any::connect;
+use zed42_ledger::{
+    error::LedgerError,
+    types::{Budget, BudgetStatus, RateTableEntry, Usage},
+    IntelligenceLedger,
+};
+
+fn budget(entity_id: &str, hard_limit: Decimal) -> Budget {
+    Budget {
+        entity_id: entity_id.to_string(),
+        hard_limit,
+        soft_limit: hard_limit,
+        spent: dec!(0.00),
+        currency: "USD".to_string(),
+        status: BudgetStatus::Active,
+        updated_at: Utc::now(),
+        tags: Default::default(),
+    }
+}
+
+async fn setup() -> IntelligenceLedger {
+    let db = connect("mem://").await.expect("Failed to connect to memory db");
+    db.use_ns("zed42").use_db("ledger").await.expect("Failed to select namespace");
+    let ledger = IntelligenceLedger::new(db);
+
+    // Tenant root caps the sum of its agents, even if each agent has room
+    ledger.set_budget(budget("acme", dec!(1.50))).await.unwrap();
+    ledger.set_budget(budget("acme/agent-1", dec!(10.00))).await.unwrap();
+    ledger.set_budget(budget("acme/agent-2", dec!(10.00))).await.unwrap();
+    ledger.set_budget(budget("globex/agent-1", dec!(10.00))).await.unwrap();
+
+    ledger.set_rate(RateTableEntry {
+        model: "gpt-4".to_string(),
+        input_cost_per_1k: dec!(0.50),
+        output_cost_per_1k: dec!(0.50),
+        ..Default::default()
+    }).await.unwrap();
+
+    ledger
+}
+
+fn usage() -> Usage {
+    Usage {
+        input_tokens: 1000,
+        output_tokens: 1000,
+        model: "gpt-4".to_string(),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_tenant_root_caps_all_entities() {
+    let ledger = setup().await;
+
+    let lease = ledger.request_tenant_lease("acme", "agent-1", dec!(1.00)).await.unwrap();
+    ledger.commit_tenant_usage("acme", &lease, usage()).await.unwrap();
+
+    let root = ledger.get_budget("acme").await.unwrap().unwrap();
+    assert_eq!(root.spent, dec!(1.00));
+
+    // agent-2 has its own room, but the tenant root does not
+    let denied = ledger.request_tenant_lease("acme", "agent-2", dec!(1.00)).await;
+    assert!(matches!(denied, Err(LedgerError::BudgetExceeded(ref t)) if t == "acme"));
+
+    // Other tenants are unaffected
+    assert!(ledger.request_tenant_lease("globex", "agent-1", dec!(1.00)).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_cross_tenant_commit_rejected() {
+    let ledger = setup().await;
+
+    let lease = ledger.request_tenant_lease("globex", "agent-1", dec!(1.00)).await.unwrap();
+    let result = ledger.commit_tenant_usage("acme", &lease, usage()).await;
+    assert!(matches!(result, Err(LedgerError::TenantIsolation(_))));
+
+    let scoped = ledger.request_tenant_lease("acme", "globex/agent-1", dec!(1.00)).await;
+    assert!(matches!(scoped, Err(LedgerError::TenantIsolation(_))));
+
+    // "acme/x" would be billed to acme's root
+    let nested = ledger.request_tenant_lease("acme/x", "agent-1", dec!(1.00)).await;
+    assert!(matches!(nested, Err(LedgerError::TenantIsolation(_))));
+    let empty = ledger.request_tenant_lease("", "agent-1", dec!(1.00)).await;
+    assert!(matches!(empty, Err(LedgerError::TenantIsolation(_))));
+}
@@ -1,6 +1,6 @@
 //! LLM client implementation
 
-use crate::types::{LlmError, LlmRequest, LlmResponse, Result, StreamChunk, Usage};
+use crate::types::{CacheControl, LlmError, LlmRequest, LlmResponse, Result, StreamChunk, Usage};
 use async_trait::async_trait;
 use reqwest::Client;
 use serde_json::json;
@@ -49,19 +49,45 @@ impl OpenRouterClient {
     fn build_request_body(&self, request: &LlmRequest) -> serde_json::Value {
         let mut messages = Vec::new();
 
-        // Add system message if provided
+        // Add system message if provided (as a content block when cached)
         if let Some(system) = &request.system_prompt {
+            let content = match request.system_cache {
+                Some(cache) => json!([{ "type": "text", "text": system, "cache_control": cache }]),
+                None => json!(system),
+            };
             messages.push(json!({
                 "role": "system",
-                "content": system
+                "content": content
             }));
         }
 
-        // Add user message
-        messages.push(json!({
-            "role": "user",
-            "content": request.prompt
-        }));
+        // Add user message (multi-part when images are attached or context is cached)
+        let cached_context = request.cached_context();
+        if request.images.is_empty() && cached_context.is_empty() {
+            messages.push(json!({
+                "role": "user",
+                "content": request.rendered_prompt()
+            }));
+        } else {
+            // Cached context leads so it forms a stable prefix
+            let mut parts = Vec::new();
+            if !cached_context.is_empty() {
+                parts.push(json!({
+                    "type": "text",
+                    "text": cached_context.trim_start(),
+                    "cache_control": CacheControl::Ephemeral,
+                }));
+            }
+            parts.push(json!({ "type": "text", "text": request.uncached_prompt() }));
+            parts.extend(request.images.iter().map(|url| json!({
+                "type": "image_url",
+                "image_url": { "url": url }
+            })));
+            messages.push(json!({
+                "role": "user",
+                "content": parts
+            }));
+        }
 
         let mut body = json!({
             "model": request.config.model,
@@ -102,6 +128,14 @@ impl OpenRouterClient {
     }
 }
 
+/// First token count present under any of `pointers` (providers report cache usage differently)
+fn first_count(usage: &serde_json::Value, pointers: &[&str]) -> usize {
+    pointers
+        .iter()
+        .find_map(|pointer| usage.pointer(pointer).and_then(|v| v.as_u64()))
+        .unwrap_or(0) as usize
+}
+
 #[async_trait]
 impl LlmClient for OpenRouterClient {
     async fn complete(&self, request: LlmRequest) -> Result<LlmResponse> {
@@ -153,6 +187,14 @@ impl LlmClient for OpenRouterClient {
             total_tokens: response_json["usage"]["total_tokens"]
                 .as_u64()
                 .unwrap_or(0) as usize,
+            cache_read_tokens: first_count(&response_json["usage"], &[
+                "/prompt_tokens_details/cached_tokens",
+                "/cache_read_input_tokens",
+            ]),
+            cache_write_tokens: first_count(&response_json["usage"], &[
+                "/prompt_tokens_details/cache_write_tokens",
+                "/cache_creation_input_tokens",
+            ]),
         };
 
         Ok(LlmResponse {
@@ -215,6 +257,8 @@ impl LlmClient for OpenRouterClient {
             total_tokens: response_json["usage"]["total_tokens"]
                 .as_u64()
                 .unwrap_or(0) as usize,
+            cache_read_tokens: 0,
+            cache_write_tokens: 0,
         };
 
         Ok(crate::types::EmbeddingResponse {
@@ -262,6 +306,8 @@ impl LlmClient for MockLlmClient {
                 prompt_tokens: 10,
                 completion_tokens: 20,
                 total_tokens: 30,
+                cache_read_tokens: 0,
+                cache_write_tokens: 0,
             },
             finish_reason: "stop".to_string(),
         })
@@ -286,6 +332,8 @@ impl LlmClient for MockLlmClient {
                 prompt_tokens: 5,
                 completion_tokens: 0,
                 total_tokens: 5,
+                cache_read_tokens: 0,
+                cache_write_tokens: 0,
             },
         })
     }
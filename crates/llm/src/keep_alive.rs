@@ -0,0 +1,285 @@
+//! Warm-up and keep-alive for local model backends
+//!
+//! Loading a GGUF model takes seconds, so [`KeepAlive`] loads every local
+//! backend when a session starts instead of on the first agent request.
+//! While agents are active it pings models that have sat unused for a ping
+//! interval, keeping weights resident; once no agents are active and a
+//! model has been idle for `idle_unload`, it is unloaded to free VRAM and
+//! warmed up again when agents come back.
+
+use crate::types::Result;
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A model served from local hardware that can be loaded and unloaded
+#[async_trait]
+pub trait LocalBackend: Send + Sync {
+    fn model_name(&self) -> &str;
+
+    fn is_loaded(&self) -> bool;
+
+    /// Time since the last real request (keep-alive pings don't count)
+    fn idle_for(&self) -> Duration;
+
+    /// Load the weights if needed and run a minimal inference
+    async fn warm_up(&self) -> Result<()>;
+
+    /// Minimal inference against an already loaded model
+    async fn ping(&self) -> Result<()>;
+
+    /// Drop the weights; returns false if nothing was loaded
+    fn unload(&self) -> bool;
+}
+
+/// When to ping and when to unload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeepAlivePolicy {
+    /// Ping a loaded model after it has been unused this long
+    pub ping_interval: Duration,
+    /// Unload after this much idle time with no active agents; `None` keeps models loaded
+    pub idle_unload: Option<Duration>,
+}
+
+impl Default for KeepAlivePolicy {
+    fn default() -> Self {
+        Self {
+            ping_interval: Duration::from_secs(60),
+            idle_unload: Some(Duration::from_secs(15 * 60)),
+        }
+    }
+}
+
+/// What one keep-alive pass did
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeepAliveReport {
+    pub warmed: Vec<String>,
+    pub pinged: Vec<String>,
+    pub unloaded: Vec<String>,
+    /// Model name and error for backends that failed to warm up or answer a ping
+    pub failed: Vec<(String, String)>,
+}
+
+/// Keeps local backends warm while agents are active
+pub struct KeepAlive {
+    backends: Mutex<Vec<Arc<dyn LocalBackend>>>,
+    policy: KeepAlivePolicy,
+    active_agents: AtomicUsize,
+}
+
+impl KeepAlive {
+    pub fn new(policy: KeepAlivePolicy) -> Self {
+        Self {
+            backends: Mutex::new(Vec::new()),
+            policy,
+            active_agents: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn with_backend(self, backend: Arc<dyn LocalBackend>) -> Self {
+        self.backends.lock().push(backend);
+        self
+    }
+
+    pub fn add_backend(&self, backend: Arc<dyn LocalBackend>) {
+        self.backends.lock().push(backend);
+    }
+
+    pub fn policy(&self) -> &KeepAlivePolicy {
+        &self.policy
+    }
+
+    /// Agents currently relying on local models; 0 allows idle unloads
+    pub fn set_active_agents(&self, count: usize) {
+        self.active_agents.store(count, Ordering::Relaxed);
+    }
+
+    pub fn active_agents(&self) -> usize {
+        self.active_agents.load(Ordering::Relaxed)
+    }
+
+    fn snapshot(&self) -> Vec<Arc<dyn LocalBackend>> {
+        self.backends.lock().clone()
+    }
+
+    /// Load every backend now (session start)
+    pub async fn warm_up_all(&self) -> KeepAliveReport {
+        let mut report = KeepAliveReport::default();
+        for backend in self.snapshot() {
+            let name = backend.model_name().to_string();
+            match backend.warm_up().await {
+                Ok(()) => report.warmed.push(name),
+                Err(e) => {
+                    tracing::warn!(model = %name, error = %e, "Local model warm-up failed");
+                    report.failed.push((name, e.to_string()));
+                }
+            }
+        }
+        report
+    }
+
+    /// One pass: unload idle models, reload when agents return, ping the rest
+    pub async fn tick(&self) -> KeepAliveReport {
+        let mut report = KeepAliveReport::default();
+        let agents_active = self.active_agents() > 0;
+        for backend in self.snapshot() {
+            let name = backend.model_name().to_string();
+            let idle = backend.idle_for();
+
+            if !backend.is_loaded() {
+                if agents_active {
+                    match backend.warm_up().await {
+                        Ok(()) => report.warmed.push(name),
+                        Err(e) => report.failed.push((name, e.to_string())),
+                    }
+                }
+                continue;
+            }
+
+            let unload_due = self.policy.idle_unload.is_some_and(|limit| idle >= limit);
+            if !agents_active && unload_due {
+                if backend.unload() {
+                    tracing::info!(model = %name, idle_secs = idle.as_secs(), "Unloaded idle local model");
+                    report.unloaded.push(name);
+                }
+                continue;
+            }
+
+            if idle >= self.policy.ping_interval {
+                match backend.ping().await {
+                    Ok(()) => report.pinged.push(name),
+                    Err(e) => {
+                        tracing::warn!(model = %name, error = %e, "Local model keep-alive ping failed");
+                        report.failed.push((name, e.to_string()));
+                    }
+                }
+            }
+        }
+        report
+    }
+
+    /// Warm up, then run keep-alive passes until `shutdown` resolves
+    pub async fn run(&self, shutdown: impl Future<Output = ()>) {
+        self.warm_up_all().await;
+
+        let period = match self.policy.idle_unload {
+            Some(limit) => self.policy.ping_interval.min(limit),
+            None => self.policy.ping_interval,
+        };
+        let mut ticker = tokio::time::interval((period / 2).max(Duration::from_millis(10)));
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        ticker.tick().await;
+
+        tokio::pin!(shutdown);
+        loop {
+            tokio::select! {
+                _ = &mut shutdown => break,
+                _ = ticker.tick() => {
+                    self.tick().await;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    struct FakeBackend {
+        loaded: Mutex<bool>,
+        last_used: Mutex<Instant>,
+        pings: AtomicUsize,
+        loads: AtomicUsize,
+    }
+
+    impl FakeBackend {
+        fn new() -> Arc<Self> {
+            Arc::new(Self {
+                loaded: Mutex::new(false),
+                last_used: Mutex::new(Instant::now()),
+                pings: AtomicUsize::new(0),
+                loads: AtomicUsize::new(0),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl LocalBackend for FakeBackend {
+        fn model_name(&self) -> &str {
+            "local/fake"
+        }
+
+        fn is_loaded(&self) -> bool {
+            *self.loaded.lock()
+        }
+
+        fn idle_for(&self) -> Duration {
+            self.last_used.lock().elapsed()
+        }
+
+        async fn warm_up(&self) -> Result<()> {
+            *self.loaded.lock() = true;
+            *self.last_used.lock() = Instant::now();
+            self.loads.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        }
+
+        async fn ping(&self) -> Result<()> {
+            self.pings.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        }
+
+        fn unload(&self) -> bool {
+            std::mem::replace(&mut *self.loaded.lock(), false)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pings_while_active_and_unloads_when_idle() {
+        let backend = FakeBackend::new();
+        let keep_alive = KeepAlive::new(KeepAlivePolicy {
+            ping_interval: Duration::from_millis(20),
+            idle_unload: Some(Duration::from_millis(60)),
+        })
+        .with_backend(backend.clone());
+
+        assert_eq!(keep_alive.warm_up_all().await.warmed, vec!["local/fake".to_string()]);
+        keep_alive.set_active_agents(1);
+
+        // Idle past the unload limit, but agents are active: ping instead
+        tokio::time::sleep(Duration::from_millis(80)).await;
+        let report = keep_alive.tick().await;
+        assert_eq!(report.pinged.len(), 1);
+        assert!(backend.is_loaded());
+
+        keep_alive.set_active_agents(0);
+        assert_eq!(keep_alive.tick().await.unloaded.len(), 1);
+        assert!(!backend.is_loaded());
+
+        // Agents come back: the model is loaded again before they need it
+        keep_alive.set_active_agents(2);
+        assert_eq!(keep_alive.tick().await.warmed.len(), 1);
+        assert_eq!(backend.loads.load(Ordering::Relaxed), 2);
+    }
+
+    #[tokio::test]
+    async fn test_never_unloads_without_idle_limit() {
+        let backend = FakeBackend::new();
+        let keep_alive = KeepAlive::new(KeepAlivePolicy {
+            ping_interval: Duration::from_secs(60),
+            idle_unload: None,
+        })
+        .with_backend(backend.clone());
+
+        keep_alive.warm_up_all().await;
+        let report = keep_alive.tick().await;
+        assert_eq!(report, KeepAliveReport::default());
+        assert!(backend.is_loaded());
+    }
+}
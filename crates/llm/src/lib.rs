@@ -3,11 +3,13 @@
 //! Copyright (c) 2025 ZED42 Team. All rights reserved.
 //! This software is proprietary. See LICENSE file for terms.
 //!
-//! LLM client for development phase using OpenRouter.
-//! Will be replaced with local models in production.
+//! LLM client for development phase using OpenRouter. GGUF models on local
+//! hardware are served by `LocalLlmClient` (`local` feature).
 
 mod client;
 mod constrained;
+mod keep_alive;
+mod local;
 mod prompts;
 mod schema;
 mod types;
@@ -18,7 +20,11 @@ mod tests;
 // Re-export public API
 pub use client::{LlmClient, MockLlmClient, OpenRouterClient};
 pub use constrained::{ConstrainedGen, ConstrainedGenConfig};
+pub use keep_alive::{KeepAlive, KeepAlivePolicy, KeepAliveReport, LocalBackend};
+pub use local::{is_local_model, LocalModelConfig, LOCAL_MODEL_PREFIX};
+#[cfg(feature = "local")]
+pub use local::LocalLlmClient;
 pub use prompts::{PromptTemplate, PromptVariable};
 pub use schema::{JsonSchema, SchemaBuilder};
-pub use types::{LlmError, LlmRequest, LlmResponse, EmbeddingRequest, EmbeddingResponse, ModelConfig, StreamChunk, Result, RetryCause, Usage};
+pub use types::{CacheControl, ContextKind, ContextSegment, LlmError, LlmRequest, LlmResponse, EmbeddingRequest, EmbeddingResponse, ModelConfig, StreamChunk, Result, RetryCause, Usage};
 
@@ -0,0 +1,495 @@
+//! Local inference backend (llama.cpp)
+//!
+//! Serves completions and embeddings from GGUF models on local hardware.
+//! Local models are routed by name: anything starting with
+//! [`LOCAL_MODEL_PREFIX`] (e.g. `local/qwen2.5-7b-instruct`) runs on this
+//! machine, so the Router can treat it as Tier 0 and skip the ledger.
+//!
+//! The llama.cpp bindings are behind the `local` feature (`local-cuda` for
+//! GPU offload builds); [`LocalModelConfig`] and the naming helpers are
+//! always available so profiles can name local models in any build.
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Model names served by a [`LocalLlmClient`] start with this prefix
+pub const LOCAL_MODEL_PREFIX: &str = "local/";
+
+/// Whether `model` names a locally served model
+pub fn is_local_model(model: &str) -> bool {
+    model.starts_with(LOCAL_MODEL_PREFIX)
+}
+
+/// One GGUF model on disk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalModelConfig {
+    /// Routed name, including [`LOCAL_MODEL_PREFIX`]
+    pub name: String,
+    pub path: PathBuf,
+    /// Context window in tokens
+    pub context_size: u32,
+    /// Layers offloaded to the GPU; 0 runs on CPU only
+    pub gpu_layers: u32,
+    /// Inference threads; `None` lets llama.cpp pick
+    pub threads: Option<i32>,
+    /// Completion length when the request sets no `max_tokens`
+    pub default_max_tokens: usize,
+}
+
+impl LocalModelConfig {
+    /// Config for the GGUF file at `path`; a bare `name` gets the local prefix
+    pub fn new(name: &str, path: impl Into<PathBuf>) -> Self {
+        let name = if is_local_model(name) {
+            name.to_string()
+        } else {
+            format!("{}{}", LOCAL_MODEL_PREFIX, name)
+        };
+        Self {
+            name,
+            path: path.into(),
+            context_size: 4096,
+            gpu_layers: 0,
+            threads: None,
+            default_max_tokens: 512,
+        }
+    }
+
+    pub fn with_context_size(mut self, tokens: u32) -> Self {
+        self.context_size = tokens;
+        self
+    }
+
+    pub fn with_gpu_layers(mut self, layers: u32) -> Self {
+        self.gpu_layers = layers;
+        self
+    }
+
+    pub fn with_threads(mut self, threads: i32) -> Self {
+        self.threads = Some(threads);
+        self
+    }
+
+    pub fn with_default_max_tokens(mut self, tokens: usize) -> Self {
+        self.default_max_tokens = tokens;
+        self
+    }
+}
+
+/// Cut `text` at the first stop sequence, if any
+#[cfg_attr(not(feature = "local"), allow(dead_code))]
+pub(crate) fn truncate_at_stop(text: &mut String, stop_sequences: &[String]) -> bool {
+    let cut = stop_sequences
+        .iter()
+        .filter(|s| !s.is_empty())
+        .filter_map(|s| text.find(s.as_str()))
+        .min();
+    match cut {
+        Some(at) => {
+            text.truncate(at);
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(feature = "local")]
+pub use backend::LocalLlmClient;
+
+#[cfg(feature = "local")]
+mod backend {
+    use super::{truncate_at_stop, LocalModelConfig};
+    use crate::client::LlmClient;
+    use crate::keep_alive::LocalBackend;
+    use crate::types::{EmbeddingRequest, EmbeddingResponse, LlmError, LlmRequest, LlmResponse, ModelConfig, Result, StreamChunk, Usage};
+    use async_trait::async_trait;
+    use llama_cpp_2::context::params::LlamaContextParams;
+    use llama_cpp_2::llama_backend::LlamaBackend;
+    use llama_cpp_2::llama_batch::LlamaBatch;
+    use llama_cpp_2::model::params::LlamaModelParams;
+    use llama_cpp_2::model::{AddBos, LlamaChatMessage, LlamaModel, Special};
+    use llama_cpp_2::sampling::LlamaSampler;
+    use parking_lot::Mutex;
+    use std::num::NonZeroU32;
+    use std::sync::{Arc, OnceLock};
+    use std::time::{Duration, Instant};
+
+    /// llama.cpp may only be initialized once per process
+    fn backend() -> Result<&'static LlamaBackend> {
+        static BACKEND: OnceLock<std::result::Result<LlamaBackend, String>> = OnceLock::new();
+        BACKEND
+            .get_or_init(|| LlamaBackend::init().map_err(|e| e.to_string()))
+            .as_ref()
+            .map_err(|e| LlmError::ApiError(format!("llama.cpp backend unavailable: {}", e)))
+    }
+
+    fn local_error(context: &str, e: impl std::fmt::Display) -> LlmError {
+        LlmError::ApiError(format!("Local inference {}: {}", context, e))
+    }
+
+    struct Loaded {
+        config: LocalModelConfig,
+        model: Arc<LlamaModel>,
+    }
+
+    struct Shared {
+        config: LocalModelConfig,
+        /// `None` until first use or warm-up, and again after an idle unload
+        model: Mutex<Option<Arc<LlamaModel>>>,
+        /// Serializes loads so concurrent first requests load once
+        loading: tokio::sync::Mutex<()>,
+        last_used: Mutex<Instant>,
+    }
+
+    fn load_model(config: &LocalModelConfig) -> Result<LlamaModel> {
+        let started = Instant::now();
+        let params = LlamaModelParams::default().with_n_gpu_layers(config.gpu_layers);
+        let model = LlamaModel::load_from_file(backend()?, &config.path, &params)
+            .map_err(|e| local_error(&format!("failed to load {}", config.path.display()), e))?;
+        tracing::info!(
+            model = %config.name,
+            path = %config.path.display(),
+            gpu_layers = config.gpu_layers,
+            load_ms = started.elapsed().as_millis() as u64,
+            "Loaded local model"
+        );
+        Ok(model)
+    }
+
+    /// Completions and embeddings from one GGUF model
+    ///
+    /// Inference is blocking, so each request runs on the blocking pool with
+    /// its own llama.cpp context; the model weights are shared. Weights load
+    /// on first use unless warmed up first (see [`crate::KeepAlive`]), and can
+    /// be unloaded while idle.
+    #[derive(Clone)]
+    pub struct LocalLlmClient {
+        inner: Arc<Shared>,
+    }
+
+    impl LocalLlmClient {
+        /// Load the model weights (blocking; see [`LocalLlmClient::load`])
+        pub fn new(config: LocalModelConfig) -> Result<Self> {
+            let model = load_model(&config)?;
+            let client = Self::unloaded(config);
+            *client.inner.model.lock() = Some(Arc::new(model));
+            Ok(client)
+        }
+
+        /// Client whose weights load on first use
+        pub fn unloaded(config: LocalModelConfig) -> Self {
+            Self {
+                inner: Arc::new(Shared {
+                    config,
+                    model: Mutex::new(None),
+                    loading: tokio::sync::Mutex::new(()),
+                    last_used: Mutex::new(Instant::now()),
+                }),
+            }
+        }
+
+        /// Load the model weights on the blocking pool
+        pub async fn load(config: LocalModelConfig) -> Result<Self> {
+            let client = Self::unloaded(config);
+            client.loaded_model().await?;
+            Ok(client)
+        }
+
+        pub fn config(&self) -> &LocalModelConfig {
+            &self.inner.config
+        }
+
+        async fn loaded_model(&self) -> Result<Arc<LlamaModel>> {
+            if let Some(model) = self.inner.model.lock().clone() {
+                return Ok(model);
+            }
+            let _loading = self.inner.loading.lock().await;
+            if let Some(model) = self.inner.model.lock().clone() {
+                return Ok(model);
+            }
+            let config = self.inner.config.clone();
+            let model = tokio::task::spawn_blocking(move || load_model(&config))
+                .await
+                .map_err(|e| local_error("load task failed", e))??;
+            let model = Arc::new(model);
+            *self.inner.model.lock() = Some(Arc::clone(&model));
+            *self.inner.last_used.lock() = Instant::now();
+            Ok(model)
+        }
+
+        /// Run `job` on the blocking pool; `touch` marks the model as in use
+        async fn run<T, F>(&self, touch: bool, job: F) -> Result<T>
+        where
+            T: Send + 'static,
+            F: FnOnce(&Loaded) -> Result<T> + Send + 'static,
+        {
+            let loaded = Loaded {
+                config: self.inner.config.clone(),
+                model: self.loaded_model().await?,
+            };
+            if touch {
+                *self.inner.last_used.lock() = Instant::now();
+            }
+            let result = tokio::task::spawn_blocking(move || job(&loaded))
+                .await
+                .map_err(|e| local_error("task failed", e))?;
+            if touch {
+                *self.inner.last_used.lock() = Instant::now();
+            }
+            result
+        }
+
+        /// One-token generation that exercises the whole inference path
+        async fn probe(&self, touch: bool) -> Result<()> {
+            let mut request = LlmRequest::new("ping".to_string());
+            request.config = ModelConfig {
+                model: self.inner.config.name.clone(),
+                temperature: 0.0,
+                max_tokens: Some(1),
+                ..ModelConfig::default()
+            };
+            self.run(touch, move |loaded| generate(loaded, &request).map(|_| ())).await
+        }
+    }
+
+    fn context_params(config: &LocalModelConfig, embeddings: bool) -> LlamaContextParams {
+        let mut params = LlamaContextParams::default()
+            .with_n_ctx(NonZeroU32::new(config.context_size))
+            .with_embeddings(embeddings);
+        if let Some(threads) = config.threads {
+            params = params.with_n_threads(threads).with_n_threads_batch(threads);
+        }
+        params
+    }
+
+    /// Render the request with the model's chat template, or plain text if it has none
+    fn render_prompt(model: &LlamaModel, request: &LlmRequest) -> String {
+        let prompt = request.rendered_prompt();
+        let templated = model.chat_template(None).ok().and_then(|template| {
+            let mut messages = Vec::new();
+            if let Some(system) = &request.system_prompt {
+                messages.push(LlamaChatMessage::new("system".to_string(), system.clone()).ok()?);
+            }
+            messages.push(LlamaChatMessage::new("user".to_string(), prompt.clone()).ok()?);
+            model.apply_chat_template(&template, &messages, true).ok()
+        });
+        templated.unwrap_or_else(|| match &request.system_prompt {
+            Some(system) => format!("{}\n\n{}", system, prompt),
+            None => prompt,
+        })
+    }
+
+    fn generate(loaded: &Loaded, request: &LlmRequest) -> Result<LlmResponse> {
+        let Loaded { config, model } = loaded;
+        let max_tokens = request.config.max_tokens.unwrap_or(config.default_max_tokens);
+
+        let tokens = model
+            .str_to_token(&render_prompt(model, request), AddBos::Always)
+            .map_err(|e| local_error("tokenization failed", e))?;
+        if tokens.len() + max_tokens > config.context_size as usize {
+            return Err(LlmError::InvalidResponse(format!(
+                "Prompt of {} tokens plus {} output tokens exceeds {}'s {} token context",
+                tokens.len(),
+                max_tokens,
+                config.name,
+                config.context_size
+            )));
+        }
+
+        let mut ctx = model
+            .new_context(backend()?, context_params(config, false))
+            .map_err(|e| local_error("context creation failed", e))?;
+        let mut batch = LlamaBatch::new(tokens.len().max(1), 1);
+        let last = tokens.len() as i32 - 1;
+        for (pos, token) in tokens.iter().enumerate() {
+            batch
+                .add(*token, pos as i32, &[0], pos as i32 == last)
+                .map_err(|e| local_error("batch overflow", e))?;
+        }
+        ctx.decode(&mut batch).map_err(|e| local_error("prompt decode failed", e))?;
+
+        let mut sampler = if request.config.temperature <= 0.0 {
+            LlamaSampler::greedy()
+        } else {
+            LlamaSampler::chain_simple([
+                LlamaSampler::top_p(request.config.top_p.unwrap_or(1.0), 1),
+                LlamaSampler::temp(request.config.temperature),
+                LlamaSampler::dist(rand_seed()),
+            ])
+        };
+
+        let mut content = String::new();
+        let mut finish_reason = "length";
+        let mut position = tokens.len() as i32;
+        let mut completion_tokens = 0;
+        while completion_tokens < max_tokens {
+            let token = sampler.sample(&ctx, batch.n_tokens() - 1);
+            sampler.accept(token);
+            if model.is_eog_token(token) {
+                finish_reason = "stop";
+                break;
+            }
+            completion_tokens += 1;
+            let piece = model
+                .token_to_str(token, Special::Tokenize)
+                .map_err(|e| local_error("detokenization failed", e))?;
+            content.push_str(&piece);
+            if truncate_at_stop(&mut content, &request.stop_sequences) {
+                finish_reason = "stop";
+                break;
+            }
+
+            batch.clear();
+            batch
+                .add(token, position, &[0], true)
+                .map_err(|e| local_error("batch overflow", e))?;
+            position += 1;
+            ctx.decode(&mut batch).map_err(|e| local_error("decode failed", e))?;
+        }
+
+        Ok(LlmResponse {
+            content,
+            model: config.name.clone(),
+            usage: Usage {
+                prompt_tokens: tokens.len(),
+                completion_tokens,
+                total_tokens: tokens.len() + completion_tokens,
+                cache_read_tokens: 0,
+                cache_write_tokens: 0,
+            },
+            finish_reason: finish_reason.to_string(),
+        })
+    }
+
+    fn embed_text(loaded: &Loaded, input: &str) -> Result<(Vec<f32>, usize)> {
+        let Loaded { config, model } = loaded;
+        let tokens = model
+            .str_to_token(input, AddBos::Always)
+            .map_err(|e| local_error("tokenization failed", e))?;
+        if tokens.len() > config.context_size as usize {
+            return Err(LlmError::InvalidResponse(format!(
+                "Embedding input of {} tokens exceeds {}'s {} token context",
+                tokens.len(),
+                config.name,
+                config.context_size
+            )));
+        }
+
+        let mut ctx = model
+            .new_context(backend()?, context_params(config, true))
+            .map_err(|e| local_error("context creation failed", e))?;
+        let mut batch = LlamaBatch::new(tokens.len().max(1), 1);
+        batch
+            .add_sequence(&tokens, 0, false)
+            .map_err(|e| local_error("batch overflow", e))?;
+        ctx.decode(&mut batch).map_err(|e| local_error("embedding decode failed", e))?;
+
+        let embedding = ctx
+            .embeddings_seq_ith(0)
+            .map_err(|e| local_error("no embedding produced", e))?;
+        // Normalize so cosine similarity matches the hosted embedding models
+        let norm = embedding.iter().map(|v| v * v).sum::<f32>().sqrt().max(f32::EPSILON);
+        Ok((embedding.iter().map(|v| v / norm).collect(), tokens.len()))
+    }
+
+    fn rand_seed() -> u32 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.subsec_nanos())
+            .unwrap_or(1234)
+    }
+
+    #[async_trait]
+    impl LocalBackend for LocalLlmClient {
+        fn model_name(&self) -> &str {
+            &self.inner.config.name
+        }
+
+        fn is_loaded(&self) -> bool {
+            self.inner.model.lock().is_some()
+        }
+
+        fn idle_for(&self) -> Duration {
+            self.inner.last_used.lock().elapsed()
+        }
+
+        async fn warm_up(&self) -> Result<()> {
+            self.probe(true).await
+        }
+
+        async fn ping(&self) -> Result<()> {
+            if !self.is_loaded() {
+                return Ok(());
+            }
+            self.probe(false).await
+        }
+
+        /// In-flight requests keep their handle; the weights are freed when they finish
+        fn unload(&self) -> bool {
+            let unloaded = self.inner.model.lock().take().is_some();
+            if unloaded {
+                tracing::info!(model = %self.inner.config.name, "Unloaded local model");
+            }
+            unloaded
+        }
+    }
+
+    #[async_trait]
+    impl LlmClient for LocalLlmClient {
+        async fn complete(&self, request: LlmRequest) -> Result<LlmResponse> {
+            if !request.images.is_empty() {
+                return Err(LlmError::InvalidResponse(format!(
+                    "{} does not accept image input",
+                    self.model_name()
+                )));
+            }
+            self.run(true, move |loaded| generate(loaded, &request)).await
+        }
+
+        async fn stream(&self, request: LlmRequest) -> Result<Vec<StreamChunk>> {
+            let response = self.complete(request).await?;
+            Ok(vec![StreamChunk {
+                content: response.content,
+                is_final: true,
+            }])
+        }
+
+        async fn embed(&self, request: EmbeddingRequest) -> Result<EmbeddingResponse> {
+            let EmbeddingRequest { input, .. } = request;
+            let name = self.model_name().to_string();
+            let (embedding, tokens) = self.run(true, move |loaded| embed_text(loaded, &input)).await?;
+            Ok(EmbeddingResponse {
+                embedding,
+                model: name,
+                usage: Usage {
+                    prompt_tokens: tokens,
+                    completion_tokens: 0,
+                    total_tokens: tokens,
+                    cache_read_tokens: 0,
+                    cache_write_tokens: 0,
+                },
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_prefixes_local_names() {
+        let config = LocalModelConfig::new("qwen2.5-7b", "/models/qwen.gguf").with_gpu_layers(32);
+        assert_eq!(config.name, "local/qwen2.5-7b");
+        assert!(is_local_model(&config.name));
+        assert_eq!(LocalModelConfig::new("local/phi-3", "/models/phi.gguf").name, "local/phi-3");
+        assert!(!is_local_model("anthropic/claude-3-haiku"));
+    }
+
+    #[test]
+    fn test_truncate_at_earliest_stop() {
+        let mut text = "answer: 42\nEND\nmore</s>".to_string();
+        assert!(truncate_at_stop(&mut text, &["</s>".to_string(), "\nEND".to_string()]));
+        assert_eq!(text, "answer: 42");
+        assert!(!truncate_at_stop(&mut text, &[String::new()]));
+    }
+}
@@ -124,6 +124,21 @@ fn test_llm_request_builder() {
     assert_eq!(request.stop_sequences.len(), 1);
 }
 
+#[test]
+fn test_cacheable_context_leads_the_prompt() {
+    let request = LlmRequest::new("Fix the bug".to_string())
+        .system("You are a reviewer".to_string())
+        .cache_system()
+        .segment(ContextSegment::new(ContextKind::Memory, "recent", "yesterday's note"))
+        .segment(ContextSegment::new(ContextKind::Reference, "style guide", "use tabs").cacheable());
+
+    assert!(request.uses_prompt_cache());
+    assert_eq!(request.cached_context(), "\n\n[Reference: style guide]\nuse tabs");
+    assert_eq!(request.uncached_prompt(), "Fix the bug\n\n[Memory: recent]\nyesterday's note");
+    assert!(!LlmRequest::new("plain".to_string()).uses_prompt_cache());
+    assert_eq!(serde_json::to_value(CacheControl::Ephemeral).unwrap(), serde_json::json!({"type": "ephemeral"}));
+}
+
 #[test]
 fn test_common_prompts() {
     let code_review = CommonPrompts::code_review();
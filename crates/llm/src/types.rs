@@ -98,6 +98,27 @@ pub struct LlmRequest {
     pub retry_count: u8,
     pub retry_cause: Option<RetryCause>,
     pub agent_id: Option<String>,
+    /// Task the call belongs to, recorded in routing logs for estimation
+    #[serde(default)]
+    pub task_id: Option<String>,
+    /// Image URLs (or data URIs) attached to the user message
+    #[serde(default)]
+    pub images: Vec<String>,
+    /// Tenant the request is billed to (None = single-tenant)
+    #[serde(default)]
+    pub tenant_id: Option<String>,
+    /// Supplementary context appended after the prompt (compressible)
+    #[serde(default)]
+    pub segments: Vec<ContextSegment>,
+    /// Draft with a cheap model and have a stronger one review the draft
+    #[serde(default)]
+    pub speculative: bool,
+    /// Cache breakpoint after the system prompt (provider prompt caching)
+    #[serde(default)]
+    pub system_cache: Option<CacheControl>,
+    /// Governance work (reviews, safety checks, cleanup) that may draw on reserve budget
+    #[serde(default)]
+    pub critical: bool,
 }
 
 impl LlmRequest {
@@ -112,6 +133,13 @@ impl LlmRequest {
             retry_count: 0,
             retry_cause: None,
             agent_id: None,
+            task_id: None,
+            images: Vec::new(),
+            tenant_id: None,
+            segments: Vec::new(),
+            speculative: false,
+            system_cache: None,
+            critical: false,
         }
     }
 
@@ -121,6 +149,12 @@ impl LlmRequest {
         self
     }
 
+    /// Set task ID
+    pub fn task(mut self, task_id: String) -> Self {
+        self.task_id = Some(task_id);
+        self
+    }
+
     /// Set system prompt
     pub fn system(mut self, system: String) -> Self {
         self.system_prompt = Some(system);
@@ -156,6 +190,141 @@ impl LlmRequest {
         self.retry_cause = Some(cause);
         self
     }
+
+    /// Set tenant ID
+    pub fn tenant(mut self, tenant_id: String) -> Self {
+        self.tenant_id = Some(tenant_id);
+        self
+    }
+
+    /// Attach an image (requires a vision-capable model)
+    pub fn image(mut self, url: String) -> Self {
+        self.images.push(url);
+        self
+    }
+
+    /// Add a context segment (memory result, tool output, ...)
+    pub fn segment(mut self, segment: ContextSegment) -> Self {
+        self.segments.push(segment);
+        self
+    }
+
+    /// Mark the system prompt as a stable, cacheable prefix
+    pub fn cache_system(mut self) -> Self {
+        self.system_cache = Some(CacheControl::Ephemeral);
+        self
+    }
+
+    /// Whether any part of the request carries a cache breakpoint
+    pub fn uses_prompt_cache(&self) -> bool {
+        self.system_cache.is_some() || self.segments.iter().any(|s| s.cache.is_some())
+    }
+
+    /// Cacheable segments, rendered in order; providers cache this prefix of the user message
+    pub fn cached_context(&self) -> String {
+        render_segments(self.segments.iter().filter(|s| s.cache.is_some()))
+    }
+
+    /// Prompt followed by the segments not marked cacheable
+    pub fn uncached_prompt(&self) -> String {
+        let mut rendered = self.prompt.clone();
+        rendered.push_str(&render_segments(self.segments.iter().filter(|s| s.cache.is_none())));
+        rendered
+    }
+
+    /// Route as a speculative draft (see the Router's drafting mode)
+    pub fn speculative(mut self) -> Self {
+        self.speculative = true;
+        self
+    }
+
+    /// Mark as critical: never throttled, and leased from the reserve pool once the budget runs out
+    pub fn critical(mut self) -> Self {
+        self.critical = true;
+        self
+    }
+
+    /// Prompt with all context segments appended
+    pub fn rendered_prompt(&self) -> String {
+        if self.segments.is_empty() {
+            return self.prompt.clone();
+        }
+        let mut rendered = self.prompt.clone();
+        rendered.push_str(&render_segments(self.segments.iter()));
+        rendered
+    }
+}
+
+fn render_segments<'a>(segments: impl Iterator<Item = &'a ContextSegment>) -> String {
+    segments
+        .map(|segment| format!("\n\n[{}: {}]\n{}", segment.kind.label(), segment.label, segment.content))
+        .collect()
+}
+
+/// Prompt caching breakpoint (Anthropic-style `cache_control`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CacheControl {
+    /// Short-lived cache (about five minutes, refreshed on each hit)
+    Ephemeral,
+}
+
+/// Kind of supplementary context attached to a request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContextKind {
+    /// Retrieved memory result (droppable by relevance)
+    Memory,
+    /// Output of a tool call (summarizable)
+    ToolOutput,
+    /// Any other reference material
+    Reference,
+}
+
+impl ContextKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ContextKind::Memory => "Memory",
+            ContextKind::ToolOutput => "Tool output",
+            ContextKind::Reference => "Reference",
+        }
+    }
+}
+
+/// A piece of context with a relevance score used when compressing prompts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextSegment {
+    pub kind: ContextKind,
+    pub label: String,
+    pub content: String,
+    /// Higher is more relevant, in `[0.0, 1.0]`
+    pub relevance: f32,
+    /// Static context marked for prompt caching; compression only drops it as a last resort
+    #[serde(default)]
+    pub cache: Option<CacheControl>,
+}
+
+impl ContextSegment {
+    pub fn new(kind: ContextKind, label: impl Into<String>, content: impl Into<String>) -> Self {
+        Self {
+            kind,
+            label: label.into(),
+            content: content.into(),
+            relevance: 1.0,
+            cache: None,
+        }
+    }
+
+    pub fn relevance(mut self, relevance: f32) -> Self {
+        self.relevance = relevance;
+        self
+    }
+
+    /// Mark as stable context eligible for prompt caching
+    pub fn cacheable(mut self) -> Self {
+        self.cache = Some(CacheControl::Ephemeral);
+        self
+    }
 }
 
 /// Reason for retrying a request
@@ -177,11 +346,17 @@ pub struct LlmResponse {
 }
 
 /// Token usage statistics
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Usage {
     pub prompt_tokens: usize,
     pub completion_tokens: usize,
     pub total_tokens: usize,
+    /// Prompt tokens served from the provider's prompt cache (included in `prompt_tokens`)
+    #[serde(default)]
+    pub cache_read_tokens: usize,
+    /// Prompt tokens written to the prompt cache (included in `prompt_tokens`)
+    #[serde(default)]
+    pub cache_write_tokens: usize,
 }
 
 /// Streaming chunk
@@ -21,6 +21,9 @@ pub struct ToolContext {
     pub agent_id: uuid::Uuid,
     pub session_id: uuid::Uuid,
     pub workspace_path: std::path::PathBuf,
+    /// Tenant the tool call is attributed to (None = single-tenant)
+    #[serde(default)]
+    pub tenant_id: Option<String>,
 }
 
 /// MCP bridge for tool execution
@@ -0,0 +1,132 @@
+//! Recall/latency trade-off of embedding quantization
+//!
+//! Run with `cargo bench -p zed42-memory --bench quantization`. Uses clustered
+//! synthetic 1536-dim vectors and brute-force search so the numbers isolate
+//! the cost of the distance computation from the storage engine.
+
+use std::time::{Duration, Instant};
+use zed42_memory::knowledge_graph::{
+    cosine_similarity, ProductQuantizer, QuantizedEmbedding, QueryDistance,
+};
+
+const DIMENSION: usize = 1536;
+const DOCUMENTS: usize = 5_000;
+const CLUSTERS: usize = 50;
+const QUERIES: usize = 100;
+const TOP_K: usize = 10;
+
+/// xorshift so runs are reproducible without a rand dependency
+struct Rng(u64);
+
+impl Rng {
+    fn next_f32(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 >> 40) as f32 / (1u64 << 24) as f32 - 0.5
+    }
+
+    fn vector(&mut self) -> Vec<f32> {
+        (0..DIMENSION).map(|_| self.next_f32()).collect()
+    }
+
+    fn near(&mut self, center: &[f32], spread: f32) -> Vec<f32> {
+        center.iter().map(|c| c + self.next_f32() * spread).collect()
+    }
+}
+
+fn top_k(scores: impl Iterator<Item = (usize, f32)>, k: usize, ascending: bool) -> Vec<usize> {
+    let mut scored: Vec<(usize, f32)> = scores.collect();
+    if ascending {
+        scored.sort_by(|a, b| a.1.total_cmp(&b.1));
+    } else {
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+    }
+    scored.into_iter().take(k).map(|(i, _)| i).collect()
+}
+
+fn recall(found: &[usize], truth: &[usize]) -> f64 {
+    found.iter().filter(|i| truth.contains(i)).count() as f64 / truth.len() as f64
+}
+
+struct Row {
+    name: String,
+    bytes_per_vector: usize,
+    recall: f64,
+    latency: Duration,
+}
+
+fn run(
+    name: &str,
+    docs: &[Vec<f32>],
+    codes: &[QuantizedEmbedding],
+    pq: Option<&ProductQuantizer>,
+    queries: &[Vec<f32>],
+    truth: &[Vec<usize>],
+    rerank: usize,
+) -> Row {
+    let mut total_recall = 0.0;
+    let start = Instant::now();
+    for (query, truth) in queries.iter().zip(truth) {
+        let distance = QueryDistance::new(query, pq);
+        let candidates = top_k(
+            codes.iter().enumerate().map(|(i, c)| (i, distance.distance(c))),
+            rerank.max(TOP_K),
+            true,
+        );
+        let found = if rerank > 0 {
+            top_k(candidates.into_iter().map(|i| (i, cosine_similarity(query, &docs[i]))), TOP_K, false)
+        } else {
+            candidates
+        };
+        total_recall += recall(&found, truth);
+    }
+    let latency = start.elapsed() / queries.len() as u32;
+
+    let code_bytes = codes[0].byte_len();
+    Row {
+        name: name.to_string(),
+        bytes_per_vector: if rerank > 0 { code_bytes + DIMENSION * 4 } else { code_bytes },
+        recall: total_recall / queries.len() as f64,
+        latency,
+    }
+}
+
+fn main() {
+    let mut rng = Rng(0x5eed_1536);
+    let centers: Vec<Vec<f32>> = (0..CLUSTERS).map(|_| rng.vector()).collect();
+    let docs: Vec<Vec<f32>> = (0..DOCUMENTS).map(|i| rng.near(&centers[i % CLUSTERS], 0.6)).collect();
+    let queries: Vec<Vec<f32>> = (0..QUERIES).map(|i| rng.near(&centers[i % CLUSTERS], 0.6)).collect();
+
+    let start = Instant::now();
+    let truth: Vec<Vec<usize>> = queries
+        .iter()
+        .map(|q| top_k(docs.iter().enumerate().map(|(i, d)| (i, cosine_similarity(q, d))), TOP_K, false))
+        .collect();
+    let exact_latency = start.elapsed() / QUERIES as u32;
+
+    let scalar: Vec<QuantizedEmbedding> = docs.iter().map(|d| QuantizedEmbedding::scalar(d)).collect();
+
+    let train_start = Instant::now();
+    let pq = ProductQuantizer::train(&docs, 96, 256).expect("train product quantizer");
+    let train_time = train_start.elapsed();
+    let product: Vec<QuantizedEmbedding> = docs.iter().map(|d| pq.encode(d).unwrap()).collect();
+
+    let rows = vec![
+        Row { name: "f32 exact".to_string(), bytes_per_vector: DIMENSION * 4, recall: 1.0, latency: exact_latency },
+        run("scalar", &docs, &scalar, None, &queries, &truth, 0),
+        run("scalar + rerank 50", &docs, &scalar, None, &queries, &truth, 50),
+        run("product 96x256", &docs, &product, Some(&pq), &queries, &truth, 0),
+        run("product 96x256 + rerank 100", &docs, &product, Some(&pq), &queries, &truth, 100),
+    ];
+
+    println!("{} vectors x {} dims, {} queries, recall@{}", DOCUMENTS, DIMENSION, QUERIES, TOP_K);
+    println!("product codebook training: {:?}\n", train_time);
+    println!("{:<30} {:>12} {:>10} {:>14}", "method", "bytes/vec", "recall", "latency/query");
+    for row in rows {
+        println!(
+            "{:<30} {:>12} {:>10.3} {:>14?}",
+            row.name, row.bytes_per_vector, row.recall, row.latency
+        );
+    }
+}
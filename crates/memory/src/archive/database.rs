@@ -66,6 +66,13 @@ impl ArchiveMemory {
         Ok(())
     }
 
+    /// Flush the write-ahead log into the database file
+    pub fn checkpoint(&self) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute_batch("CHECKPOINT;").context("Failed to checkpoint archive database")?;
+        Ok(())
+    }
+
     /// Archive an entry
     ///
     /// # Arguments
@@ -119,6 +126,37 @@ impl ArchiveMemory {
         Ok(entries.len())
     }
 
+    /// Archive multiple entries, replacing any already archived under the same ID
+    ///
+    /// For callers that may archive the same entries twice, e.g. when
+    /// removing them from their source fails after the archive succeeded.
+    pub fn upsert_batch(&self, entries: Vec<ArchiveEntry>) -> Result<usize> {
+        let conn = self.conn.lock().unwrap();
+
+        let tx = conn.unchecked_transaction()?;
+
+        for entry in &entries {
+            tx.execute(
+                "INSERT OR REPLACE INTO archive_entries
+                 (id, source_tier, entry_type, content, timestamp, archived_at, metadata)
+                 VALUES (?, ?, ?, ?, ?, ?, ?)",
+                params![
+                    &entry.id,
+                    &entry.source_tier,
+                    &entry.entry_type,
+                    serde_json::to_string(&entry.content)?,
+                    entry.timestamp,
+                    entry.archived_at,
+                    entry.metadata.as_ref().and_then(|m| serde_json::to_string(m).ok()),
+                ],
+            )?;
+        }
+
+        tx.commit()?;
+
+        Ok(entries.len())
+    }
+
     /// Get an entry by ID
     pub fn get(&self, id: &str) -> Result<Option<ArchiveEntry>> {
         let conn = self.conn.lock().unwrap();
@@ -5,6 +5,7 @@ use super::types::{AggregateMetric, ArchiveEntry, ArchiveQuery, QueryResult};
 use anyhow::{Context, Result};
 use duckdb::params;
 use std::time::Instant;
+use zed42_core::page::{Page, PageRequest, Position};
 
 impl ArchiveMemory {
     /// Query the archive
@@ -52,6 +53,62 @@ impl ArchiveMemory {
         })
     }
 
+    /// One page of archived entries, newest first, optionally of one type
+    pub fn list_page(&self, entry_type: Option<&str>, page: PageRequest) -> Result<Page<ArchiveEntry>> {
+        let limit = page.limit();
+        let position: Option<Position<i64>> = page.position()?;
+        let conn = self.conn.lock().unwrap();
+
+        let mut conditions = Vec::new();
+        let mut params_vec: Vec<Box<dyn duckdb::ToSql>> = Vec::new();
+        if let Some(et) = entry_type {
+            conditions.push("entry_type = ?");
+            params_vec.push(Box::new(et.to_string()));
+        }
+        if let Some(ref position) = position {
+            conditions.push("timestamp <= ?");
+            params_vec.push(Box::new(position.key));
+        }
+        params_vec.push(Box::new((limit + 1) as i64));
+        params_vec.push(Box::new(position.as_ref().map_or(0, |p| p.ties) as i64));
+
+        let where_clause = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        };
+        let query = format!(
+            "SELECT id, source_tier, entry_type, content, timestamp, archived_at, metadata
+             FROM archive_entries
+             {}
+             ORDER BY timestamp DESC, id DESC
+             LIMIT ? OFFSET ?",
+            where_clause
+        );
+        let mut stmt = conn.prepare(&query)?;
+        let param_refs: Vec<&dyn duckdb::ToSql> =
+            params_vec.iter().map(|p| p.as_ref()).collect();
+
+        let rows: Vec<ArchiveEntry> = stmt
+            .query_map(param_refs.as_slice(), |row| {
+                Ok(ArchiveEntry {
+                    id: row.get(0)?,
+                    source_tier: row.get(1)?,
+                    entry_type: row.get(2)?,
+                    content: serde_json::from_str(&row.get::<_, String>(3)?).unwrap_or_default(),
+                    timestamp: row.get(4)?,
+                    archived_at: row.get(5)?,
+                    metadata: row
+                        .get::<_, Option<String>>(6)?
+                        .and_then(|s| serde_json::from_str(&s).ok()),
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(Page::from_rows(rows, limit, position.as_ref(), |e| e.timestamp))
+    }
+
     /// Query entries by time range
     fn query_time_range(
         &self,
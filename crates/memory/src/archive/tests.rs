@@ -52,6 +52,20 @@ fn test_batch_archive() {
     assert_eq!(stats.total_entries, 10);
 }
 
+#[test]
+fn test_upsert_batch_archives_each_id_once() {
+    let (archive, _temp) = create_test_archive();
+
+    let entries: Vec<ArchiveEntry> = (0..3)
+        .map(|i| create_test_entry("data", 1000 + i))
+        .collect();
+    archive.upsert_batch(entries.clone()).unwrap();
+    archive.upsert_batch(entries).unwrap();
+
+    let stats = archive.stats().unwrap();
+    assert_eq!(stats.total_entries, 3);
+}
+
 #[test]
 fn test_time_range_query() {
     let (archive, _temp) = create_test_archive();
@@ -159,3 +173,26 @@ fn test_parquet_export_import() {
     let stats = archive2.stats().unwrap();
     assert_eq!(stats.total_entries, 5);
 }
+
+#[test]
+fn test_list_page_walks_entries_newest_first() {
+    let (archive, _temp) = create_test_archive();
+    let entries: Vec<ArchiveEntry> = [1000, 1001, 1001, 1001, 1002]
+        .into_iter()
+        .map(|ts| create_test_entry("data", ts))
+        .chain([create_test_entry("other", 1003)])
+        .collect();
+    archive.archive_batch(entries).unwrap();
+
+    let mut request = zed42_core::PageRequest::first(2);
+    let mut timestamps = Vec::new();
+    loop {
+        let page = archive.list_page(Some("data"), request.clone()).unwrap();
+        timestamps.extend(page.items.iter().map(|e| e.timestamp));
+        match page.next_cursor {
+            Some(cursor) => request = zed42_core::PageRequest::first(2).after(cursor),
+            None => break,
+        }
+    }
+    assert_eq!(timestamps, vec![1002, 1001, 1001, 1001, 1000]);
+}
@@ -0,0 +1,365 @@
+//! Per-agent evaluation scores
+//!
+//! Every completed task is scored as a [`TaskEvaluation`]: how its critiques
+//! went, how many tests passed, whether a human accepted the output and what
+//! it cost. Evaluations are kept in [`ArchiveMemory`] so they outlive the
+//! session, and aggregate into [`AgentMetrics`] per agent type, overall or
+//! per day or week, to show whether a prompt or profile change helped.
+
+use anyhow::Result;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use crate::archive::{ArchiveEntry, ArchiveMemory, ArchiveQuery};
+use crate::feedback::{Feedback, FeedbackKind};
+
+/// Archive entry type of evaluations
+pub const EVALUATION_ENTRY_TYPE: &str = "evaluation";
+
+/// Weight of the critique pass rate in a score
+const CRITIQUE_WEIGHT: f64 = 0.3;
+
+/// Weight of the test pass rate in a score
+const TEST_WEIGHT: f64 = 0.3;
+
+/// Weight of human acceptance in a score
+const ACCEPTANCE_WEIGHT: f64 = 0.4;
+
+/// How a human received a task's output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Acceptance {
+    Accepted,
+    /// Accepted after the user changed it
+    Edited,
+    Rejected,
+}
+
+impl Acceptance {
+    fn value(self) -> f64 {
+        match self {
+            Acceptance::Accepted => 1.0,
+            Acceptance::Edited => 0.5,
+            Acceptance::Rejected => 0.0,
+        }
+    }
+}
+
+/// Outcome signals of one completed task
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskEvaluation {
+    pub id: String,
+    pub task_id: String,
+    /// Agent type that did the task, e.g. "FeatureImplementer"
+    pub agent_type: String,
+    #[serde(default)]
+    pub critiques_passed: u32,
+    #[serde(default)]
+    pub critiques_failed: u32,
+    #[serde(default)]
+    pub tests_passed: u32,
+    #[serde(default)]
+    pub tests_failed: u32,
+    /// Unset until a human has looked at the output
+    #[serde(default)]
+    pub acceptance: Option<Acceptance>,
+    #[serde(default)]
+    pub cost: Decimal,
+    /// Free-form labels, e.g. the prompt version in use
+    #[serde(default)]
+    pub tags: BTreeMap<String, String>,
+    pub created_at: i64,
+}
+
+impl TaskEvaluation {
+    pub fn new(task_id: impl Into<String>, agent_type: impl Into<String>) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            task_id: task_id.into(),
+            agent_type: agent_type.into(),
+            critiques_passed: 0,
+            critiques_failed: 0,
+            tests_passed: 0,
+            tests_failed: 0,
+            acceptance: None,
+            cost: Decimal::ZERO,
+            tags: BTreeMap::new(),
+            created_at: chrono::Utc::now().timestamp(),
+        }
+    }
+
+    pub fn with_critiques(mut self, passed: u32, failed: u32) -> Self {
+        self.critiques_passed = passed;
+        self.critiques_failed = failed;
+        self
+    }
+
+    pub fn with_tests(mut self, passed: u32, failed: u32) -> Self {
+        self.tests_passed = passed;
+        self.tests_failed = failed;
+        self
+    }
+
+    pub fn with_acceptance(mut self, acceptance: Acceptance) -> Self {
+        self.acceptance = Some(acceptance);
+        self
+    }
+
+    /// Acceptance from the user's correction of the output
+    pub fn with_feedback(self, feedback: &Feedback) -> Self {
+        self.with_acceptance(match feedback.kind {
+            FeedbackKind::Edited => Acceptance::Edited,
+            FeedbackKind::Rejected => Acceptance::Rejected,
+        })
+    }
+
+    pub fn with_cost(mut self, cost: Decimal) -> Self {
+        self.cost = cost;
+        self
+    }
+
+    pub fn with_tag(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.tags.insert(key.into(), value.into());
+        self
+    }
+
+    /// Quality in `0.0..=1.0` from the signals present
+    ///
+    /// Critique pass rate, test pass rate and acceptance are weighted
+    /// 0.3 / 0.3 / 0.4; missing signals are left out and the rest
+    /// re-weighted. A task with no signals at all scores `None`. Cost is
+    /// reported separately rather than folded in.
+    pub fn score(&self) -> Option<f64> {
+        let parts = [
+            (CRITIQUE_WEIGHT, rate(self.critiques_passed, self.critiques_failed)),
+            (TEST_WEIGHT, rate(self.tests_passed, self.tests_failed)),
+            (ACCEPTANCE_WEIGHT, self.acceptance.map(Acceptance::value)),
+        ];
+        let (weighted, weights) = parts
+            .iter()
+            .filter_map(|(weight, value)| value.map(|v| (weight * v, *weight)))
+            .fold((0.0, 0.0), |(sum, total), (v, w)| (sum + v, total + w));
+        (weights > 0.0).then(|| weighted / weights)
+    }
+
+    fn to_entry(&self) -> Result<ArchiveEntry> {
+        Ok(ArchiveEntry {
+            id: format!("evaluation:{}", self.id),
+            source_tier: EVALUATION_ENTRY_TYPE.to_string(),
+            entry_type: EVALUATION_ENTRY_TYPE.to_string(),
+            content: serde_json::to_value(self)?,
+            timestamp: self.created_at,
+            archived_at: chrono::Utc::now().timestamp(),
+            metadata: Some(serde_json::json!({ "agent_type": self.agent_type, "score": self.score() })),
+        })
+    }
+}
+
+fn rate(passed: u32, failed: u32) -> Option<f64> {
+    let total = passed + failed;
+    (total > 0).then(|| passed as f64 / total as f64)
+}
+
+/// Aggregated evaluations of one agent type
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AgentMetrics {
+    pub agent_type: String,
+    pub tasks: usize,
+    /// Mean score of the tasks that have one
+    pub mean_score: Option<f64>,
+    /// Passed over all critiques
+    pub critique_pass_rate: Option<f64>,
+    /// Passed over all tests
+    pub test_pass_rate: Option<f64>,
+    /// Accepted (edited or not) over tasks a human reviewed
+    pub acceptance_rate: Option<f64>,
+    pub total_cost: Decimal,
+    pub mean_cost: Decimal,
+}
+
+impl AgentMetrics {
+    pub fn from_evaluations<'a>(agent_type: &str, evaluations: impl IntoIterator<Item = &'a TaskEvaluation>) -> Self {
+        let mut metrics = Self {
+            agent_type: agent_type.to_string(),
+            ..Default::default()
+        };
+        let (mut score_sum, mut scored) = (0.0, 0usize);
+        let (mut critiques_passed, mut critiques_failed) = (0, 0);
+        let (mut tests_passed, mut tests_failed) = (0, 0);
+        let (mut accepted, mut reviewed) = (0u32, 0u32);
+        for evaluation in evaluations {
+            metrics.tasks += 1;
+            if let Some(score) = evaluation.score() {
+                score_sum += score;
+                scored += 1;
+            }
+            critiques_passed += evaluation.critiques_passed;
+            critiques_failed += evaluation.critiques_failed;
+            tests_passed += evaluation.tests_passed;
+            tests_failed += evaluation.tests_failed;
+            if let Some(acceptance) = evaluation.acceptance {
+                reviewed += 1;
+                if acceptance != Acceptance::Rejected {
+                    accepted += 1;
+                }
+            }
+            metrics.total_cost += evaluation.cost;
+        }
+        metrics.mean_score = (scored > 0).then(|| score_sum / scored as f64);
+        metrics.critique_pass_rate = rate(critiques_passed, critiques_failed);
+        metrics.test_pass_rate = rate(tests_passed, tests_failed);
+        metrics.acceptance_rate = rate(accepted, reviewed - accepted);
+        if metrics.tasks > 0 {
+            metrics.mean_cost = metrics.total_cost / Decimal::from(metrics.tasks);
+        }
+        metrics
+    }
+}
+
+/// Width of a trend bucket
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrendBucket {
+    Day,
+    Week,
+}
+
+impl TrendBucket {
+    fn seconds(self) -> i64 {
+        match self {
+            TrendBucket::Day => 86_400,
+            TrendBucket::Week => 7 * 86_400,
+        }
+    }
+}
+
+/// Metrics of the tasks completed in one bucket
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrendPoint {
+    /// Unix timestamp the bucket starts at
+    pub bucket_start: i64,
+    pub metrics: AgentMetrics,
+}
+
+impl ArchiveMemory {
+    /// Store the evaluation of a completed task
+    pub fn record_evaluation(&self, evaluation: &TaskEvaluation) -> Result<()> {
+        self.archive(evaluation.to_entry()?)
+    }
+
+    /// Evaluations of `agent_type` between two Unix timestamps, oldest first
+    pub fn evaluations(&self, agent_type: &str, start_timestamp: i64, end_timestamp: i64) -> Result<Vec<TaskEvaluation>> {
+        let mut evaluations = self.all_evaluations(start_timestamp, end_timestamp)?;
+        evaluations.retain(|evaluation| evaluation.agent_type == agent_type);
+        Ok(evaluations)
+    }
+
+    /// Evaluations carrying tag `key`, as `(tag value, score)` pairs of scored tasks
+    ///
+    /// With the experiment id as key this yields the per-variant scores an
+    /// experiment report compares.
+    pub fn tagged_scores(&self, key: &str, start_timestamp: i64, end_timestamp: i64) -> Result<Vec<(String, f64)>> {
+        Ok(self
+            .all_evaluations(start_timestamp, end_timestamp)?
+            .into_iter()
+            .filter_map(|evaluation| Some((evaluation.tags.get(key)?.clone(), evaluation.score()?)))
+            .collect())
+    }
+
+    fn all_evaluations(&self, start_timestamp: i64, end_timestamp: i64) -> Result<Vec<TaskEvaluation>> {
+        let result = self.query(ArchiveQuery::TimeRange {
+            start_timestamp,
+            end_timestamp,
+            entry_type: Some(EVALUATION_ENTRY_TYPE.to_string()),
+        })?;
+        let mut evaluations: Vec<TaskEvaluation> = result
+            .entries
+            .into_iter()
+            .filter_map(|entry| serde_json::from_value(entry.content).ok())
+            .collect();
+        evaluations.sort_by_key(|evaluation| evaluation.created_at);
+        Ok(evaluations)
+    }
+
+    /// Metrics of `agent_type` over a time range
+    pub fn agent_metrics(&self, agent_type: &str, start_timestamp: i64, end_timestamp: i64) -> Result<AgentMetrics> {
+        let evaluations = self.evaluations(agent_type, start_timestamp, end_timestamp)?;
+        Ok(AgentMetrics::from_evaluations(agent_type, &evaluations))
+    }
+
+    /// Metrics of `agent_type` per day or week, oldest first; empty buckets are left out
+    pub fn agent_trend(
+        &self,
+        agent_type: &str,
+        bucket: TrendBucket,
+        start_timestamp: i64,
+        end_timestamp: i64,
+    ) -> Result<Vec<TrendPoint>> {
+        let mut buckets: BTreeMap<i64, Vec<TaskEvaluation>> = BTreeMap::new();
+        for evaluation in self.evaluations(agent_type, start_timestamp, end_timestamp)? {
+            let start = evaluation.created_at.div_euclid(bucket.seconds()) * bucket.seconds();
+            buckets.entry(start).or_default().push(evaluation);
+        }
+        Ok(buckets
+            .into_iter()
+            .map(|(bucket_start, evaluations)| TrendPoint {
+                bucket_start,
+                metrics: AgentMetrics::from_evaluations(agent_type, &evaluations),
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_score_reweights_missing_signals() {
+        let evaluation = TaskEvaluation::new("t1", "FeatureImplementer");
+        assert_eq!(evaluation.score(), None);
+
+        let evaluation = evaluation.with_tests(3, 1);
+        assert_eq!(evaluation.score(), Some(0.75));
+
+        let evaluation = evaluation.with_critiques(1, 1).with_acceptance(Acceptance::Accepted);
+        let expected = 0.3 * 0.5 + 0.3 * 0.75 + 0.4 * 1.0;
+        assert!((evaluation.score().unwrap() - expected).abs() < 1e-9);
+
+        let rejected = Feedback::rejected("FeatureImplementer", "todo!()");
+        assert_eq!(evaluation.with_feedback(&rejected).acceptance, Some(Acceptance::Rejected));
+    }
+
+    #[test]
+    fn test_metrics_and_trend_from_archive() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = ArchiveMemory::new(dir.path(), "evaluations").unwrap();
+        let day = 86_400;
+        let evaluations = [
+            TaskEvaluation::new("t1", "TestEngineer").with_tag("prompt-v2", "v2").with_tests(4, 0).with_acceptance(Acceptance::Accepted).with_cost(Decimal::new(20, 2)),
+            TaskEvaluation::new("t2", "TestEngineer").with_tests(1, 1).with_acceptance(Acceptance::Rejected).with_cost(Decimal::new(40, 2)),
+            TaskEvaluation::new("t3", "TestEngineer").with_tests(2, 0).with_acceptance(Acceptance::Edited),
+            TaskEvaluation::new("t4", "Refactorer").with_tests(0, 5),
+        ];
+        for (i, mut evaluation) in evaluations.into_iter().enumerate() {
+            // t1 and t2 on day 10, t3 on day 11
+            evaluation.created_at = 10 * day + (i as i64 / 2) * day + 60;
+            archive.record_evaluation(&evaluation).unwrap();
+        }
+
+        let metrics = archive.agent_metrics("TestEngineer", 0, 20 * day).unwrap();
+        assert_eq!(metrics.tasks, 3);
+        assert_eq!(metrics.test_pass_rate, Some(7.0 / 8.0));
+        assert_eq!(metrics.acceptance_rate, Some(2.0 / 3.0));
+        assert_eq!(metrics.total_cost, Decimal::new(60, 2));
+        assert_eq!(metrics.mean_cost, Decimal::new(20, 2));
+
+        assert_eq!(archive.tagged_scores("prompt-v2", 0, 20 * day).unwrap(), vec![("v2".to_string(), 1.0)]);
+
+        let trend = archive.agent_trend("TestEngineer", TrendBucket::Day, 0, 20 * day).unwrap();
+        let starts: Vec<i64> = trend.iter().map(|point| point.bucket_start).collect();
+        assert_eq!(starts, vec![10 * day, 11 * day]);
+        assert_eq!(trend[0].metrics.tasks, 2);
+        // Full test pass rate, accepted after edits
+        assert!((trend[1].metrics.mean_score.unwrap() - 0.5 / 0.7).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,268 @@
+//! Human feedback on agent output
+//!
+//! When a user edits or rejects what an agent produced, the correction is
+//! recorded as a [`Feedback`]: session memory keeps it for this session, and
+//! a `feedback` node in the knowledge graph keeps it for the project. A
+//! [`FeedbackDigest`] aggregates the latest corrections for one agent type
+//! into a prompt section, so the next agent of that type sees them.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use crate::knowledge_graph::{KnowledgeNode, NodeType};
+use crate::session::EntryType;
+use crate::MemorySubstrate;
+
+/// Changed lines quoted in a feedback summary
+const SUMMARY_LINES: usize = 3;
+
+/// Characters kept from a quoted line
+const QUOTE_CHARS: usize = 120;
+
+/// Session entries scanned when there is no knowledge graph
+const SESSION_SCAN: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeedbackKind {
+    /// The user changed the output before accepting it
+    Edited,
+    /// The user threw the output away
+    Rejected,
+}
+
+/// One human correction of an agent's output
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Feedback {
+    pub id: String,
+    /// Agent type that produced the output, e.g. "FeatureImplementer"
+    pub agent_type: String,
+    #[serde(default)]
+    pub task_id: Option<String>,
+    pub kind: FeedbackKind,
+    /// What the agent produced
+    pub original: String,
+    /// What the user turned it into (edits only)
+    #[serde(default)]
+    pub corrected: Option<String>,
+    /// Why, if the user said
+    #[serde(default)]
+    pub reason: Option<String>,
+    pub created_at: i64,
+}
+
+impl Feedback {
+    pub fn edited(agent_type: impl Into<String>, original: impl Into<String>, corrected: impl Into<String>) -> Self {
+        Self::new(agent_type, FeedbackKind::Edited, original, Some(corrected.into()))
+    }
+
+    pub fn rejected(agent_type: impl Into<String>, original: impl Into<String>) -> Self {
+        Self::new(agent_type, FeedbackKind::Rejected, original, None)
+    }
+
+    fn new(agent_type: impl Into<String>, kind: FeedbackKind, original: impl Into<String>, corrected: Option<String>) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            agent_type: agent_type.into(),
+            task_id: None,
+            kind,
+            original: original.into(),
+            corrected,
+            reason: None,
+            created_at: chrono::Utc::now().timestamp(),
+        }
+    }
+
+    pub fn with_reason(mut self, reason: impl Into<String>) -> Self {
+        self.reason = Some(reason.into()).filter(|r: &String| !r.trim().is_empty());
+        self
+    }
+
+    pub fn with_task(mut self, task_id: impl Into<String>) -> Self {
+        self.task_id = Some(task_id.into());
+        self
+    }
+
+    /// What changed, in one line: removed and added lines with a few examples
+    pub fn summary(&self) -> String {
+        let Some(ref corrected) = self.corrected else {
+            return "Output rejected".to_string();
+        };
+        let before: HashSet<&str> = self.original.lines().map(str::trim).filter(|l| !l.is_empty()).collect();
+        let after: HashSet<&str> = corrected.lines().map(str::trim).filter(|l| !l.is_empty()).collect();
+        let removed: Vec<&str> = self.original.lines().map(str::trim).filter(|l| !l.is_empty() && !after.contains(l)).collect();
+        let added: Vec<&str> = corrected.lines().map(str::trim).filter(|l| !l.is_empty() && !before.contains(l)).collect();
+
+        let mut summary = format!("Edited: -{} / +{} lines", removed.len(), added.len());
+        let quote = |line: &str| line.chars().take(QUOTE_CHARS).collect::<String>();
+        for line in removed.iter().take(SUMMARY_LINES) {
+            summary.push_str(&format!("; removed `{}`", quote(line)));
+        }
+        for line in added.iter().take(SUMMARY_LINES) {
+            summary.push_str(&format!("; added `{}`", quote(line)));
+        }
+        summary
+    }
+
+    /// Knowledge-graph node holding this feedback
+    fn to_node(&self) -> Result<KnowledgeNode> {
+        Ok(KnowledgeNode {
+            id: format!("feedback:{}", self.id),
+            node_type: "feedback".to_string(),
+            name: format!("{} feedback: {}", self.agent_type, self.summary()),
+            content: serde_json::to_string(self)?,
+            embedding: None,
+            metadata: serde_json::json!({ "agent_type": self.agent_type, "kind": self.kind }).to_string(),
+            created_at: self.created_at,
+            updated_at: self.created_at,
+        })
+    }
+}
+
+/// Recent corrections for one agent type, newest first
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FeedbackDigest {
+    pub agent_type: String,
+    pub edited: usize,
+    pub rejected: usize,
+    /// One line per correction: the summary and the user's reason
+    pub lessons: Vec<String>,
+}
+
+impl FeedbackDigest {
+    fn from_feedback(agent_type: &str, mut items: Vec<Feedback>, limit: usize) -> Self {
+        items.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        let mut digest = Self {
+            agent_type: agent_type.to_string(),
+            ..Default::default()
+        };
+        for item in &items {
+            match item.kind {
+                FeedbackKind::Edited => digest.edited += 1,
+                FeedbackKind::Rejected => digest.rejected += 1,
+            }
+        }
+        digest.lessons = items
+            .iter()
+            .take(limit)
+            .map(|item| match item.reason {
+                Some(ref reason) => format!("{} (reason: {})", item.summary(), reason),
+                None => item.summary(),
+            })
+            .collect();
+        digest
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lessons.is_empty()
+    }
+
+    /// Prompt section with the corrections; empty when there are none
+    pub fn render(&self) -> String {
+        if self.is_empty() {
+            return String::new();
+        }
+        let mut out = format!(
+            "Reviewers corrected earlier output of this agent ({} edited, {} rejected). Avoid repeating these:\n",
+            self.edited, self.rejected
+        );
+        for lesson in &self.lessons {
+            out.push_str(&format!("- {}\n", lesson));
+        }
+        out
+    }
+}
+
+impl MemorySubstrate {
+    /// Store feedback in session memory and the knowledge graph, where present
+    pub async fn record_feedback(&self, feedback: &Feedback) -> Result<()> {
+        let content = serde_json::to_value(feedback)?;
+        if let Some(session) = self.session() {
+            let metadata = serde_json::json!({ "agent_type": feedback.agent_type, "task_id": feedback.task_id });
+            session
+                .insert(EntryType::Feedback, content, Some(metadata))
+                .context("Failed to store feedback in session memory")?;
+        }
+        if let Some(graph) = self.knowledge_graph() {
+            graph
+                .insert_node(feedback.to_node()?)
+                .await
+                .context("Failed to store feedback in the knowledge graph")?;
+        }
+        Ok(())
+    }
+
+    /// The latest `limit` corrections for `agent_type`, with totals
+    ///
+    /// Reads the project's knowledge graph when there is one (feedback from
+    /// every session), otherwise this session's memory.
+    pub async fn feedback_digest(&self, agent_type: &str, limit: usize) -> Result<FeedbackDigest> {
+        let items: Vec<Feedback> = if let Some(graph) = self.knowledge_graph() {
+            graph
+                .get_nodes_by_type(NodeType::Feedback)
+                .await?
+                .into_iter()
+                .filter_map(|node| serde_json::from_str(&node.content).ok())
+                .collect()
+        } else if let Some(session) = self.session() {
+            session
+                .get_recent(Some(EntryType::Feedback), SESSION_SCAN)?
+                .into_iter()
+                .filter_map(|entry| serde_json::from_value(entry.content).ok())
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let items = items.into_iter().filter(|f: &Feedback| f.agent_type == agent_type).collect();
+        Ok(FeedbackDigest::from_feedback(agent_type, items, limit))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary_and_digest() {
+        let edit = Feedback::edited(
+            "FeatureImplementer",
+            "fn add(a: i32, b: i32) -> i32 {\n    a + b\n}",
+            "/// Adds two numbers\nfn add(a: i32, b: i32) -> i32 {\n    a.saturating_add(b)\n}",
+        )
+        .with_reason("Overflow must not panic");
+        assert_eq!(
+            edit.summary(),
+            "Edited: -1 / +2 lines; removed `a + b`; added `/// Adds two numbers`; added `a.saturating_add(b)`"
+        );
+
+        let mut reject = Feedback::rejected("FeatureImplementer", "todo!()").with_reason("  ");
+        reject.created_at = edit.created_at + 1;
+        assert!(reject.reason.is_none());
+
+        let digest = FeedbackDigest::from_feedback("FeatureImplementer", vec![edit, reject], 5);
+        assert_eq!((digest.edited, digest.rejected), (1, 1));
+        assert_eq!(digest.lessons[0], "Output rejected");
+        assert!(digest.render().contains("(reason: Overflow must not panic)"));
+        assert_eq!(FeedbackDigest::default().render(), "");
+    }
+
+    #[tokio::test]
+    async fn test_feedback_round_trip_through_session_memory() {
+        let dir = tempfile::tempdir().unwrap();
+        let memory = MemorySubstrate {
+            session: Some(std::sync::Arc::new(crate::session::SessionMemory::new(uuid::Uuid::new_v4(), dir.path()).unwrap())),
+            ..MemorySubstrate::working_only()
+        };
+
+        memory
+            .record_feedback(&Feedback::rejected("TestEngineer", "assert!(true);").with_reason("Asserts nothing").with_task("t1"))
+            .await
+            .unwrap();
+        memory.record_feedback(&Feedback::rejected("Refactorer", "mod x;")).await.unwrap();
+
+        let digest = memory.feedback_digest("TestEngineer", 5).await.unwrap();
+        assert_eq!(digest.rejected, 1);
+        assert_eq!(digest.lessons, vec!["Output rejected (reason: Asserts nothing)".to_string()]);
+        assert!(memory.feedback_digest("Architect", 5).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,573 @@
+//! Approximate nearest neighbor index (HNSW)
+//!
+//! SurrealDB's vector scan degrades as the graph grows, so a graph can keep an
+//! in-process HNSW index over its embeddings. SurrealDB stays the source of
+//! truth: the index is updated on every node write/delete, snapshotted next to
+//! the database, and rebuilt from SurrealDB whenever the snapshot is missing or
+//! out of date.
+
+use super::database::KnowledgeGraphMemory;
+use super::types::{NodeType, SearchResult};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Nodes read per page while rebuilding
+const REBUILD_BATCH: usize = 1000;
+
+/// Index updates between automatic snapshots
+const SNAPSHOT_EVERY: usize = 1000;
+
+/// Rebuild once this share of entries are tombstones
+const MAX_TOMBSTONE_RATIO: f64 = 0.25;
+
+/// HNSW build and search parameters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnnConfig {
+    /// Links per node on upper layers (twice this on layer 0)
+    pub m: usize,
+    /// Candidate list size while inserting
+    pub ef_construction: usize,
+    /// Candidate list size while searching (raised to `top_k` if smaller)
+    pub ef_search: usize,
+}
+
+impl Default for AnnConfig {
+    fn default() -> Self {
+        Self {
+            m: 16,
+            ef_construction: 200,
+            ef_search: 64,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct HnswNode {
+    id: String,
+    /// Unit-length copy of the embedding
+    vector: Vec<f32>,
+    /// Neighbor indices per layer, layer 0 first
+    links: Vec<Vec<usize>>,
+    /// Removed nodes stay in the graph for navigation until the next rebuild
+    deleted: bool,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+struct Candidate {
+    distance: f32,
+    index: usize,
+}
+
+impl Eq for Candidate {}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.distance.total_cmp(&other.distance).then(self.index.cmp(&other.index))
+    }
+}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Hierarchical navigable small world graph over cosine distance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HnswIndex {
+    config: AnnConfig,
+    nodes: Vec<HnswNode>,
+    /// Node id -> index of its live entry
+    positions: HashMap<String, usize>,
+    entry_point: Option<usize>,
+    rng_state: u64,
+    #[serde(skip)]
+    pending_updates: usize,
+}
+
+impl HnswIndex {
+    pub fn new(config: AnnConfig) -> Self {
+        Self {
+            config,
+            nodes: Vec::new(),
+            positions: HashMap::new(),
+            entry_point: None,
+            rng_state: 0x2545_f491_4f6c_dd1d,
+            pending_updates: 0,
+        }
+    }
+
+    pub fn config(&self) -> AnnConfig {
+        self.config
+    }
+
+    /// Live (non-deleted) entries
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.positions.contains_key(id)
+    }
+
+    /// Approximate heap footprint in bytes
+    pub fn estimated_bytes(&self) -> usize {
+        self.nodes
+            .iter()
+            .map(|n| {
+                n.id.len()
+                    + n.vector.len() * std::mem::size_of::<f32>()
+                    + n.links.iter().map(|l| l.len() * std::mem::size_of::<usize>()).sum::<usize>()
+            })
+            .sum()
+    }
+
+    fn tombstone_ratio(&self) -> f64 {
+        if self.nodes.is_empty() {
+            0.0
+        } else {
+            (self.nodes.len() - self.positions.len()) as f64 / self.nodes.len() as f64
+        }
+    }
+
+    fn max_links(&self, layer: usize) -> usize {
+        if layer == 0 { self.config.m * 2 } else { self.config.m }
+    }
+
+    fn random_layer(&mut self) -> usize {
+        self.rng_state ^= self.rng_state << 13;
+        self.rng_state ^= self.rng_state >> 7;
+        self.rng_state ^= self.rng_state << 17;
+        let uniform = ((self.rng_state >> 11) as f64 / (1u64 << 53) as f64).max(f64::MIN_POSITIVE);
+        let ml = 1.0 / (self.config.m.max(2) as f64).ln();
+        (-uniform.ln() * ml) as usize
+    }
+
+    fn distance(&self, query: &[f32], index: usize) -> f32 {
+        1.0 - dot(query, &self.nodes[index].vector)
+    }
+
+    /// Add or replace a vector
+    pub fn insert(&mut self, id: &str, vector: &[f32]) {
+        self.remove(id);
+        let vector = normalized(vector);
+        let level = self.random_layer();
+        let index = self.nodes.len();
+        self.nodes.push(HnswNode {
+            id: id.to_string(),
+            vector,
+            links: vec![Vec::new(); level + 1],
+            deleted: false,
+        });
+        self.positions.insert(id.to_string(), index);
+        self.pending_updates += 1;
+
+        let Some(entry) = self.entry_point else {
+            self.entry_point = Some(index);
+            return;
+        };
+        let query = self.nodes[index].vector.clone();
+        let top_layer = self.nodes[entry].links.len() - 1;
+
+        let mut entry_points = vec![entry];
+        for layer in (level + 1..=top_layer).rev() {
+            entry_points = vec![self.search_layer(&query, &entry_points, 1, layer)[0].index];
+        }
+
+        for layer in (0..=level.min(top_layer)).rev() {
+            let candidates = self.search_layer(&query, &entry_points, self.config.ef_construction, layer);
+            let max_links = self.max_links(layer);
+            let neighbors: Vec<usize> = candidates.iter().take(max_links).map(|c| c.index).collect();
+            self.nodes[index].links[layer] = neighbors.clone();
+
+            for neighbor in neighbors {
+                self.nodes[neighbor].links[layer].push(index);
+                if self.nodes[neighbor].links[layer].len() > max_links {
+                    self.prune(neighbor, layer, max_links);
+                }
+            }
+            entry_points = candidates.into_iter().map(|c| c.index).collect();
+        }
+
+        if level > top_layer {
+            self.entry_point = Some(index);
+        }
+    }
+
+    /// Keep the closest `max_links` neighbors of a node on a layer
+    fn prune(&mut self, index: usize, layer: usize, max_links: usize) {
+        let base = self.nodes[index].vector.clone();
+        let mut links: Vec<Candidate> = self.nodes[index].links[layer]
+            .iter()
+            .map(|&n| Candidate { distance: self.distance(&base, n), index: n })
+            .collect();
+        links.sort();
+        links.truncate(max_links);
+        self.nodes[index].links[layer] = links.into_iter().map(|c| c.index).collect();
+    }
+
+    /// Remove a vector; returns whether it was present
+    pub fn remove(&mut self, id: &str) -> bool {
+        match self.positions.remove(id) {
+            Some(index) => {
+                self.nodes[index].deleted = true;
+                self.pending_updates += 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Best-first search of one layer, closest first
+    fn search_layer(&self, query: &[f32], entry_points: &[usize], ef: usize, layer: usize) -> Vec<Candidate> {
+        let mut visited: HashSet<usize> = entry_points.iter().copied().collect();
+        let mut frontier: BinaryHeap<Reverse<Candidate>> = BinaryHeap::new();
+        let mut best: BinaryHeap<Candidate> = BinaryHeap::new();
+
+        for &index in entry_points {
+            let candidate = Candidate { distance: self.distance(query, index), index };
+            frontier.push(Reverse(candidate));
+            best.push(candidate);
+        }
+        while best.len() > ef {
+            best.pop();
+        }
+
+        while let Some(Reverse(current)) = frontier.pop() {
+            if best.len() >= ef && best.peek().is_some_and(|worst| current.distance > worst.distance) {
+                break;
+            }
+            let Some(links) = self.nodes[current.index].links.get(layer) else { continue };
+            for &neighbor in links {
+                if !visited.insert(neighbor) {
+                    continue;
+                }
+                let candidate = Candidate { distance: self.distance(query, neighbor), index: neighbor };
+                if best.len() < ef || best.peek().is_some_and(|worst| candidate.distance < worst.distance) {
+                    frontier.push(Reverse(candidate));
+                    best.push(candidate);
+                    if best.len() > ef {
+                        best.pop();
+                    }
+                }
+            }
+        }
+
+        best.into_sorted_vec()
+    }
+
+    /// Nearest live entries as (node id, cosine similarity), best first
+    pub fn search(&self, query: &[f32], top_k: usize) -> Vec<(String, f32)> {
+        let Some(entry) = self.entry_point else {
+            return Vec::new();
+        };
+        let query = normalized(query);
+        if query.len() != self.nodes[entry].vector.len() {
+            return Vec::new();
+        }
+
+        let mut entry_points = vec![entry];
+        for layer in (1..self.nodes[entry].links.len()).rev() {
+            entry_points = vec![self.search_layer(&query, &entry_points, 1, layer)[0].index];
+        }
+
+        // Tombstones occupy candidate slots, so widen the search to compensate
+        let ef = self.config.ef_search.max(top_k) + (self.nodes.len() - self.positions.len()).min(top_k * 4);
+        self.search_layer(&query, &entry_points, ef, 0)
+            .into_iter()
+            .filter(|c| !self.nodes[c.index].deleted)
+            .take(top_k)
+            .map(|c| (self.nodes[c.index].id.clone(), 1.0 - c.distance))
+            .collect()
+    }
+
+    pub fn save(&mut self, path: &Path) -> Result<()> {
+        let content = serde_json::to_vec(self)?;
+        std::fs::write(path, content)
+            .with_context(|| format!("Failed to write ANN index to {}", path.display()))?;
+        self.pending_updates = 0;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read(path)
+            .with_context(|| format!("Failed to read ANN index from {}", path.display()))?;
+        serde_json::from_slice(&content).context("Failed to parse ANN index snapshot")
+    }
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+fn normalized(v: &[f32]) -> Vec<f32> {
+    let norm = dot(v, v).sqrt();
+    if norm == 0.0 {
+        v.to_vec()
+    } else {
+        v.iter().map(|x| x / norm).collect()
+    }
+}
+
+#[derive(Deserialize)]
+struct EmbeddingRow {
+    id: String,
+    embedding: Vec<f32>,
+}
+
+impl KnowledgeGraphMemory {
+    /// Snapshot file of the ANN index, next to the database
+    pub(crate) fn ann_path(&self) -> PathBuf {
+        self.db_path.with_extension("hnsw")
+    }
+
+    /// Whether semantic search goes through the ANN index
+    pub fn ann_enabled(&self) -> bool {
+        self.ann.read().is_some()
+    }
+
+    /// Approximate memory held by the ANN index (0 when disabled)
+    pub fn ann_index_bytes(&self) -> usize {
+        self.ann.read().as_ref().map_or(0, |index| index.estimated_bytes())
+    }
+
+    /// Build an HNSW index from the stored embeddings and use it for search
+    ///
+    /// Returns the number of indexed nodes.
+    pub async fn enable_ann_index(&self, config: AnnConfig) -> Result<usize> {
+        let mut index = self.build_ann_index(config).await?;
+        index.save(&self.ann_path())?;
+        let count = index.len();
+        *self.ann.write() = Some(index);
+        tracing::info!(nodes = count, "Built ANN index");
+        Ok(count)
+    }
+
+    /// Stop using the ANN index and delete its snapshot
+    pub fn disable_ann_index(&self) -> Result<()> {
+        *self.ann.write() = None;
+        let path = self.ann_path();
+        if path.exists() {
+            std::fs::remove_file(&path)
+                .with_context(|| format!("Failed to remove {}", path.display()))?;
+        }
+        Ok(())
+    }
+
+    /// Rebuild the index from SurrealDB, dropping tombstones
+    pub async fn rebuild_ann_index(&self) -> Result<usize> {
+        let Some(config) = self.ann.read().as_ref().map(|index| index.config()) else {
+            return Ok(0);
+        };
+        self.enable_ann_index(config).await
+    }
+
+    /// Write pending index updates to the snapshot
+    pub fn flush_ann_index(&self) -> Result<()> {
+        let path = self.ann_path();
+        if let Some(index) = self.ann.write().as_mut() {
+            index.save(&path)?;
+        }
+        Ok(())
+    }
+
+    async fn build_ann_index(&self, config: AnnConfig) -> Result<HnswIndex> {
+        let mut index = HnswIndex::new(config);
+        let mut start = 0;
+        loop {
+            let mut response = self
+                .db
+                .query(
+                    "SELECT meta::id(id) AS id, embedding FROM nodes
+                     WHERE embedding != NONE AND embedding != NULL
+                     ORDER BY id LIMIT $limit START $start",
+                )
+                .bind(("limit", REBUILD_BATCH))
+                .bind(("start", start))
+                .await?;
+            let rows: Vec<EmbeddingRow> = response.take(0)?;
+            if rows.is_empty() {
+                break;
+            }
+            start += rows.len();
+            for row in rows {
+                index.insert(&row.id, &row.embedding);
+            }
+        }
+        Ok(index)
+    }
+
+    /// Load the snapshot on open, rebuilding it if nodes changed since it was written
+    pub(crate) async fn load_ann_index(&self) -> Result<()> {
+        let path = self.ann_path();
+        if !path.exists() {
+            return Ok(());
+        }
+        let index = match HnswIndex::load(&path) {
+            Ok(index) => index,
+            Err(e) => {
+                tracing::warn!(error = %e, "ANN index snapshot unreadable, rebuilding");
+                return self.enable_ann_index(AnnConfig::default()).await.map(|_| ());
+            }
+        };
+
+        let mut response = self
+            .db
+            .query("SELECT count() FROM nodes WHERE embedding != NONE AND embedding != NULL GROUP ALL")
+            .await?;
+        let count: Option<serde_json::Value> = response.take(0)?;
+        let stored = count.and_then(|v| v.get("count").and_then(|c| c.as_u64())).unwrap_or(0) as usize;
+
+        if stored == index.len() {
+            *self.ann.write() = Some(index);
+            Ok(())
+        } else {
+            tracing::info!(snapshot = index.len(), stored, "ANN index snapshot is stale, rebuilding");
+            self.enable_ann_index(index.config()).await.map(|_| ())
+        }
+    }
+
+    /// Mirror a node write into the index
+    pub(crate) fn ann_upsert(&self, id: &str, embedding: Option<&[f32]>) {
+        let mut guard = self.ann.write();
+        let Some(index) = guard.as_mut() else { return };
+        match embedding {
+            Some(vector) => index.insert(id, vector),
+            None => {
+                index.remove(id);
+            }
+        }
+        self.after_ann_update(index);
+    }
+
+    /// Mirror a node delete into the index
+    pub(crate) fn ann_remove(&self, id: &str) {
+        let mut guard = self.ann.write();
+        let Some(index) = guard.as_mut() else { return };
+        if index.remove(id) {
+            self.after_ann_update(index);
+        }
+    }
+
+    fn after_ann_update(&self, index: &mut HnswIndex) {
+        if index.tombstone_ratio() > MAX_TOMBSTONE_RATIO {
+            // Compact in memory; the graph is only navigational state
+            let mut compacted = HnswIndex::new(index.config());
+            for node in index.nodes.iter().filter(|n| !n.deleted) {
+                compacted.insert(&node.id, &node.vector);
+            }
+            *index = compacted;
+        }
+        if index.pending_updates >= SNAPSHOT_EVERY {
+            if let Err(e) = index.save(&self.ann_path()) {
+                tracing::warn!(error = %e, "Failed to snapshot ANN index");
+            }
+        }
+    }
+
+    /// Semantic search through the ANN index
+    pub(crate) async fn ann_search(
+        &self,
+        query_vec: &[f32],
+        top_k: usize,
+        node_types: Option<&[NodeType]>,
+    ) -> Result<Vec<SearchResult>> {
+        // Over-fetch when filtering by type, since the index is type-agnostic
+        let fetch = if node_types.is_some() { top_k * 4 } else { top_k };
+        let hits = match self.ann.read().as_ref() {
+            Some(index) => index.search(query_vec, fetch),
+            None => return Ok(Vec::new()),
+        };
+        let wanted: Option<Vec<String>> = node_types.map(|types| {
+            types
+                .iter()
+                .filter_map(|t| serde_json::to_value(t).ok()?.as_str().map(str::to_string))
+                .collect()
+        });
+
+        let mut results = Vec::with_capacity(top_k);
+        for (id, relevance_score) in hits {
+            let Some(node) = self.get_node(&id).await? else { continue };
+            if wanted.as_ref().is_some_and(|w| !w.contains(&node.node_type)) {
+                continue;
+            }
+            results.push(SearchResult { node, relevance_score, path: None });
+            if results.len() == top_k {
+                break;
+            }
+        }
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn random_vectors(count: usize, dim: usize, seed: u64) -> Vec<Vec<f32>> {
+        let mut state = seed;
+        (0..count)
+            .map(|_| {
+                (0..dim)
+                    .map(|_| {
+                        state ^= state << 13;
+                        state ^= state >> 7;
+                        state ^= state << 17;
+                        (state >> 40) as f32 / (1u64 << 24) as f32 - 0.5
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_hnsw_recall() {
+        let docs = random_vectors(500, 32, 7);
+        let mut index = HnswIndex::new(AnnConfig::default());
+        for (i, doc) in docs.iter().enumerate() {
+            index.insert(&i.to_string(), doc);
+        }
+        assert_eq!(index.len(), 500);
+
+        let queries = random_vectors(20, 32, 99);
+        let mut hits = 0;
+        for query in &queries {
+            let q = normalized(query);
+            let mut exact: Vec<(usize, f32)> = docs.iter().enumerate().map(|(i, d)| (i, dot(&q, &normalized(d)))).collect();
+            exact.sort_by(|a, b| b.1.total_cmp(&a.1));
+            let truth: Vec<String> = exact.iter().take(10).map(|(i, _)| i.to_string()).collect();
+
+            let found = index.search(query, 10);
+            hits += found.iter().filter(|(id, _)| truth.contains(id)).count();
+        }
+        let recall = hits as f64 / (queries.len() * 10) as f64;
+        assert!(recall > 0.9, "recall@10 was {}", recall);
+    }
+
+    #[test]
+    fn test_hnsw_remove_and_snapshot() {
+        let docs = random_vectors(50, 8, 3);
+        let mut index = HnswIndex::new(AnnConfig::default());
+        for (i, doc) in docs.iter().enumerate() {
+            index.insert(&i.to_string(), doc);
+        }
+
+        assert_eq!(index.search(&docs[5], 1)[0].0, "5");
+        assert!(index.remove("5"));
+        assert!(index.search(&docs[5], 10).iter().all(|(id, _)| id != "5"));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("graph.hnsw");
+        index.save(&path).unwrap();
+        let loaded = HnswIndex::load(&path).unwrap();
+        assert_eq!(loaded.len(), 49);
+        assert_eq!(loaded.search(&docs[7], 1)[0].0, "7");
+    }
+}
@@ -1,11 +1,15 @@
 //! Core knowledge graph database operations
 
+use super::ann::HnswIndex;
+use super::migration::MigrationState;
+use super::quantization::QuantizationState;
 use super::types::{EdgeType, GraphStats, KnowledgeEdge, KnowledgeNode, NodeType};
 use anyhow::{Context, Result};
 use std::path::Path;
 use surrealdb::engine::local::{Db, RocksDb};
 use surrealdb::Surreal;
 use std::sync::Arc;
+use zed42_core::page::{Page, PageRequest, Position};
 use zed42_llm::LlmClient;
 
 /// Knowledge Graph Memory - Tier 3
@@ -13,6 +17,14 @@ pub struct KnowledgeGraphMemory {
     pub(crate) db: Surreal<Db>,
     pub(crate) db_path: std::path::PathBuf,
     pub(crate) llm_client: Option<Arc<dyn LlmClient>>,
+    /// Cached embedding migration state (decides which model queries use)
+    pub(crate) migration: parking_lot::RwLock<Option<MigrationState>>,
+    /// Quantization settings and codebooks, if enabled for this graph
+    pub(crate) quantization: parking_lot::RwLock<Option<QuantizationState>>,
+    /// In-process ANN index over embeddings, if enabled for this graph
+    pub(crate) ann: parking_lot::RwLock<Option<HnswIndex>>,
+    /// Upper bound on texts embedded per batch (0 = unbounded)
+    pub(crate) embedding_batch_limit: std::sync::atomic::AtomicUsize,
 }
 
 impl KnowledgeGraphMemory {
@@ -37,25 +49,75 @@ impl KnowledgeGraphMemory {
             .await
             .context("Failed to select namespace/database")?;
 
-        let memory = Self { db, db_path, llm_client };
+        let memory = Self {
+            db,
+            db_path,
+            llm_client,
+            migration: parking_lot::RwLock::new(None),
+            quantization: parking_lot::RwLock::new(None),
+            ann: parking_lot::RwLock::new(None),
+            embedding_batch_limit: std::sync::atomic::AtomicUsize::new(0),
+        };
         memory.initialize_schema().await?;
+        let migration = memory.migration_state().await?;
+        *memory.migration.write() = migration;
+        let quantization = memory.load_quantization_state().await?;
+        *memory.quantization.write() = quantization;
+        memory.load_ann_index().await?;
         Ok(memory)
     }
 
+    /// Cap embedding batch sizes (e.g. under memory pressure); `None` lifts the cap
+    pub fn set_embedding_batch_limit(&self, limit: Option<usize>) {
+        self.embedding_batch_limit
+            .store(limit.unwrap_or(0), std::sync::atomic::Ordering::Relaxed);
+    }
+
+    pub fn embedding_batch_limit(&self) -> Option<usize> {
+        match self.embedding_batch_limit.load(std::sync::atomic::Ordering::Relaxed) {
+            0 => None,
+            limit => Some(limit),
+        }
+    }
+
+    /// Batch size to use for a requested size, honoring the current limit
+    pub(crate) fn embedding_batch_size(&self, requested: usize) -> usize {
+        self.embedding_batch_limit()
+            .map_or(requested, |limit| requested.min(limit))
+            .max(1)
+    }
+
     pub(crate) async fn initialize_schema(&self) -> Result<()> {
         self.db.query("
             DEFINE TABLE IF NOT EXISTS nodes SCHEMALESS;
             DEFINE TABLE IF NOT EXISTS edges SCHEMALESS;
+            DEFINE TABLE IF NOT EXISTS embedding_migration SCHEMALESS;
+            DEFINE TABLE IF NOT EXISTS graph_config SCHEMALESS;
         ").await?;
         Ok(())
     }
 
     /// Insert a node into the graph
-    pub async fn insert_node(&self, node: KnowledgeNode) -> Result<()> {
-        self.db.query("CREATE nodes CONTENT $node")
-            .bind(("node", node))
-            .await
-            .context("Failed to insert node")?;
+    pub async fn insert_node(&self, mut node: KnowledgeNode) -> Result<()> {
+        let id = node.id.clone();
+        let embedding = node.embedding.clone();
+        match self.quantize_for_insert(&mut node)? {
+            Some(code) => {
+                self.db.query("CREATE nodes CONTENT $node; UPDATE nodes SET embedding_q = $code WHERE id = type::thing('nodes', $id) OR id = $id;")
+                    .bind(("id", node.id.clone()))
+                    .bind(("node", node))
+                    .bind(("code", code))
+                    .await
+                    .context("Failed to insert node")?;
+            }
+            None => {
+                self.db.query("CREATE nodes CONTENT $node")
+                    .bind(("node", node))
+                    .await
+                    .context("Failed to insert node")?;
+            }
+        }
+        self.ann_upsert(&id, embedding.as_deref());
         Ok(())
     }
 
@@ -87,6 +149,39 @@ impl KnowledgeGraphMemory {
         Ok(nodes)
     }
 
+    /// One page of nodes, newest first, optionally of one type
+    pub async fn list_nodes_page(&self, node_type: Option<NodeType>, page: PageRequest) -> Result<Page<KnowledgeNode>> {
+        let limit = page.limit();
+        let position: Option<Position<i64>> = page.position()?;
+
+        let mut conditions = Vec::new();
+        if node_type.is_some() {
+            conditions.push("node_type = $type");
+        }
+        if position.is_some() {
+            conditions.push("created_at <= $key");
+        }
+        let where_clause = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        };
+        let query = format!(
+            "SELECT *, meta::id(id) AS id FROM nodes {} ORDER BY created_at DESC, id DESC LIMIT $limit START $ties",
+            where_clause
+        );
+        let mut response = self.db.query(query)
+            .bind(("type", node_type))
+            .bind(("key", position.as_ref().map(|p| p.key)))
+            .bind(("ties", position.as_ref().map_or(0, |p| p.ties)))
+            .bind(("limit", limit + 1))
+            .await
+            .context("Failed to query node page")?;
+        let rows: Vec<KnowledgeNode> = response.take(0)?;
+
+        Ok(Page::from_rows(rows, limit, position.as_ref(), |n| n.created_at))
+    }
+
     /// Retrieve edges by type
     pub async fn get_edges_by_type(&self, edge_type: EdgeType) -> Result<Vec<KnowledgeEdge>> {
         let mut response = self.db.query("SELECT *, meta::id(id) AS id FROM edges WHERE edge_type = $type")
@@ -111,6 +206,7 @@ impl KnowledgeGraphMemory {
             .bind(("id", id_owned))
             .await
             .context("Failed to cascade delete node")?;
+        self.ann_remove(id);
         Ok(())
     }
 
@@ -0,0 +1,207 @@
+//! Change impact analysis
+//!
+//! Computes the blast radius of changing a node by walking `calls` and
+//! `depends_on` edges backwards: everything that calls or depends on the
+//! target, transitively, is affected. Affected nodes are then mapped to the
+//! files that contain them, the tests that cover them and the public APIs
+//! among them. Public APIs are nodes whose metadata sets `"public": true` or
+//! `"visibility": "pub"`/`"public"`.
+
+use super::database::KnowledgeGraphMemory;
+use super::types::{EdgeType, KnowledgeNode};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+
+/// Default traversal depth for impact analysis
+pub const DEFAULT_IMPACT_DEPTH: usize = 5;
+
+/// A node reached while walking dependents
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImpactedNode {
+    pub id: String,
+    pub name: String,
+    pub node_type: String,
+    /// Hops from the changed node
+    pub distance: usize,
+}
+
+/// How risky a change looks from its blast radius
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImpactRisk {
+    Low,
+    Medium,
+    High,
+}
+
+/// Blast radius of a change
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImpactReport {
+    pub target: ImpactedNode,
+    /// Nodes that call or depend on the target, nearest first
+    pub dependents: Vec<ImpactedNode>,
+    pub affected_files: Vec<String>,
+    pub affected_tests: Vec<String>,
+    pub public_apis: Vec<String>,
+    pub risk: ImpactRisk,
+    /// Dependents were cut off at the depth limit
+    pub truncated: bool,
+}
+
+impl ImpactReport {
+    /// One-line summary for plans and reviews
+    pub fn summary(&self) -> String {
+        format!(
+            "Changing {} affects {} dependent(s) across {} file(s), {} public API(s), covered by {} test(s); risk {:?}{}",
+            self.target.name,
+            self.dependents.len(),
+            self.affected_files.len(),
+            self.public_apis.len(),
+            self.affected_tests.len(),
+            self.risk,
+            if self.truncated { " (depth limit reached)" } else { "" }
+        )
+    }
+}
+
+fn is_public(node: &KnowledgeNode) -> bool {
+    let Ok(metadata) = serde_json::from_str::<serde_json::Value>(&node.metadata) else {
+        return false;
+    };
+    metadata.get("public").and_then(|v| v.as_bool()).unwrap_or(false)
+        || matches!(metadata.get("visibility").and_then(|v| v.as_str()), Some("pub" | "public"))
+}
+
+fn risk(dependents: usize, public_apis: usize, tests: usize) -> ImpactRisk {
+    let untested = tests == 0;
+    if (public_apis > 0 && untested) || dependents >= 20 {
+        ImpactRisk::High
+    } else if public_apis > 0 || untested || dependents >= 5 {
+        ImpactRisk::Medium
+    } else {
+        ImpactRisk::Low
+    }
+}
+
+impl KnowledgeGraphMemory {
+    /// Blast radius of changing `node_id`, following dependents up to `max_depth` hops
+    pub async fn analyze_impact(&self, node_id: &str, max_depth: usize) -> Result<ImpactReport> {
+        let target = self
+            .get_node(node_id)
+            .await?
+            .ok_or_else(|| anyhow!("No node '{}' in the knowledge graph", node_id))?;
+        let target_id = target.id.clone();
+
+        let mut dependents_of: HashMap<String, Vec<String>> = HashMap::new();
+        for edge_type in [EdgeType::Calls, EdgeType::DependsOn] {
+            for edge in self.get_edges_by_type(edge_type).await? {
+                dependents_of.entry(edge.to_id).or_default().push(edge.from_id);
+            }
+        }
+        let mut tests_of: HashMap<String, Vec<String>> = HashMap::new();
+        for edge in self.get_edges_by_type(EdgeType::Tests).await? {
+            tests_of.entry(edge.to_id).or_default().push(edge.from_id);
+        }
+        let mut container_of: HashMap<String, String> = HashMap::new();
+        for edge in self.get_edges_by_type(EdgeType::Contains).await? {
+            container_of.insert(edge.to_id, edge.from_id);
+        }
+
+        // Breadth-first over reverse edges
+        let mut distance: HashMap<String, usize> = HashMap::from([(target_id.clone(), 0)]);
+        let mut order = Vec::new();
+        let mut queue = VecDeque::from([target_id.clone()]);
+        let mut truncated = false;
+        while let Some(current) = queue.pop_front() {
+            let depth = distance[&current];
+            for dependent in dependents_of.get(&current).into_iter().flatten() {
+                if distance.contains_key(dependent) {
+                    continue;
+                }
+                if depth >= max_depth {
+                    truncated = true;
+                    continue;
+                }
+                distance.insert(dependent.clone(), depth + 1);
+                order.push(dependent.clone());
+                queue.push_back(dependent.clone());
+            }
+        }
+
+        let mut nodes: HashMap<String, KnowledgeNode> = HashMap::from([(target_id.clone(), target)]);
+        for id in &order {
+            if let Some(node) = self.get_node(id).await? {
+                nodes.insert(id.clone(), node);
+            }
+        }
+
+        let mut files = BTreeSet::new();
+        let mut tests = BTreeSet::new();
+        let mut public_apis = Vec::new();
+        let mut dependents = Vec::new();
+        let mut seen_containers = HashSet::new();
+        for id in std::iter::once(&target_id).chain(order.iter()) {
+            let Some(node) = nodes.get(id) else {
+                continue;
+            };
+            match node.node_type.as_str() {
+                "file" => {
+                    files.insert(node.name.clone());
+                }
+                "test" => {
+                    tests.insert(node.name.clone());
+                }
+                _ => {}
+            }
+            if is_public(node) {
+                public_apis.push(node.name.clone());
+            }
+            for tester in tests_of.get(id).into_iter().flatten() {
+                let name = match nodes.get(tester) {
+                    Some(node) => node.name.clone(),
+                    None => self.get_node(tester).await?.map(|n| n.name).unwrap_or_else(|| tester.clone()),
+                };
+                tests.insert(name);
+            }
+            // The file that (transitively) contains the node
+            let mut current = id.clone();
+            while let Some(parent) = container_of.get(&current) {
+                if !seen_containers.insert(parent.clone()) {
+                    break;
+                }
+                if let Some(container) = self.get_node(parent).await? {
+                    if container.node_type == "file" {
+                        files.insert(container.name);
+                    }
+                }
+                current = parent.clone();
+            }
+            if id != &target_id {
+                dependents.push(ImpactedNode {
+                    id: id.clone(),
+                    name: node.name.clone(),
+                    node_type: node.node_type.clone(),
+                    distance: distance[id],
+                });
+            }
+        }
+
+        let target = &nodes[&target_id];
+        let risk = risk(dependents.len(), public_apis.len(), tests.len());
+        Ok(ImpactReport {
+            target: ImpactedNode {
+                id: target_id.clone(),
+                name: target.name.clone(),
+                node_type: target.node_type.clone(),
+                distance: 0,
+            },
+            dependents,
+            affected_files: files.into_iter().collect(),
+            affected_tests: tests.into_iter().collect(),
+            public_apis,
+            risk,
+            truncated,
+        })
+    }
+}
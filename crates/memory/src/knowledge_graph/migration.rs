@@ -0,0 +1,339 @@
+//! Embedding model migration
+//!
+//! Vectors from different embedding models (or dimensions) cannot be compared,
+//! so switching models means re-embedding every node. The migration runs in
+//! three phases:
+//!
+//! 1. Backfill: nodes are re-embedded in batches into a staging field
+//!    (`embedding_next`), with progress and spend persisted so the run can be
+//!    paused by its budget and resumed later.
+//! 2. Dual read: semantic search queries both the old and the new vectors and
+//!    merges the results, so search keeps working while callers switch over.
+//! 3. Cutover: staged vectors replace the old ones and the new model becomes
+//!    the query model.
+
+use super::database::KnowledgeGraphMemory;
+use super::types::KnowledgeNode;
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use zed42_llm::{EmbeddingRequest, LlmClient};
+
+/// Record id of the migration state
+const STATE_ID: &str = "current";
+
+/// What to migrate to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MigrationPlan {
+    /// Embedding model queried before the migration
+    pub from_model: String,
+    pub to_model: String,
+    /// Expected vector length of the new model (checked on every response)
+    pub to_dimension: Option<usize>,
+    pub batch_size: usize,
+    /// Price of the new model per 1000 tokens
+    pub cost_per_1k_tokens: Decimal,
+    /// Stop backfilling once this much has been spent
+    pub budget: Option<Decimal>,
+}
+
+impl MigrationPlan {
+    pub fn new(from_model: impl Into<String>, to_model: impl Into<String>) -> Self {
+        Self {
+            from_model: from_model.into(),
+            to_model: to_model.into(),
+            to_dimension: None,
+            batch_size: 64,
+            cost_per_1k_tokens: Decimal::ZERO,
+            budget: None,
+        }
+    }
+
+    pub fn dimension(mut self, dimension: usize) -> Self {
+        self.to_dimension = Some(dimension);
+        self
+    }
+
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    pub fn pricing(mut self, cost_per_1k_tokens: Decimal) -> Self {
+        self.cost_per_1k_tokens = cost_per_1k_tokens;
+        self
+    }
+
+    pub fn budget(mut self, budget: Decimal) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+}
+
+/// Migration phase
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MigrationPhase {
+    Backfilling,
+    /// Every node has a staged vector; search reads both
+    DualRead,
+    Completed,
+    Aborted,
+}
+
+/// Persisted migration state and progress
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationState {
+    pub plan: MigrationPlan,
+    pub phase: MigrationPhase,
+    pub total_nodes: usize,
+    pub migrated_nodes: usize,
+    pub failed_nodes: usize,
+    pub tokens_used: u64,
+    pub cost: Decimal,
+    pub started_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// Why the last backfill stopped early
+    #[serde(default)]
+    pub paused_reason: Option<String>,
+}
+
+impl MigrationState {
+    pub fn percent_complete(&self) -> f64 {
+        if self.total_nodes == 0 {
+            100.0
+        } else {
+            self.migrated_nodes as f64 * 100.0 / self.total_nodes as f64
+        }
+    }
+
+    fn budget_exhausted(&self) -> bool {
+        self.plan.budget.is_some_and(|b| self.cost >= b)
+    }
+}
+
+impl KnowledgeGraphMemory {
+    /// Current migration state, if a migration was ever started
+    pub async fn migration_state(&self) -> Result<Option<MigrationState>> {
+        let state: Option<MigrationState> = self.db.select(("embedding_migration", STATE_ID)).await?;
+        Ok(state)
+    }
+
+    async fn save_migration_state(&self, state: &MigrationState) -> Result<()> {
+        let _: Option<MigrationState> = self
+            .db
+            .upsert(("embedding_migration", STATE_ID))
+            .content(state.clone())
+            .await
+            .context("Failed to persist embedding migration state")?;
+        *self.migration.write() = Some(state.clone());
+        Ok(())
+    }
+
+    /// Begin migrating to a new embedding model
+    ///
+    /// Fails if a migration is already in progress.
+    pub async fn start_embedding_migration(&self, plan: MigrationPlan) -> Result<MigrationState> {
+        if let Some(existing) = self.migration_state().await? {
+            if matches!(existing.phase, MigrationPhase::Backfilling | MigrationPhase::DualRead) {
+                bail!("Embedding migration to {} is already in progress", existing.plan.to_model);
+            }
+        }
+
+        let mut response = self
+            .db
+            .query("SELECT count() FROM nodes WHERE (embedding != NONE AND embedding != NULL) OR embedding_q != NONE GROUP ALL")
+            .await?;
+        let count: Option<serde_json::Value> = response.take(0)?;
+        let total = count.and_then(|v| v.get("count").and_then(|c| c.as_u64())).unwrap_or(0) as usize;
+
+        // Clear vectors staged by an earlier, aborted attempt
+        self.db.query("UPDATE nodes SET embedding_next = NONE").await?;
+
+        let state = MigrationState {
+            plan,
+            phase: MigrationPhase::Backfilling,
+            total_nodes: total,
+            migrated_nodes: 0,
+            failed_nodes: 0,
+            tokens_used: 0,
+            cost: Decimal::ZERO,
+            started_at: Utc::now(),
+            updated_at: Utc::now(),
+            paused_reason: None,
+        };
+        self.save_migration_state(&state).await?;
+        Ok(state)
+    }
+
+    /// Re-embed pending nodes until done or the budget runs out
+    ///
+    /// `on_progress` is called after every batch. Calling this again after a
+    /// budget pause (with a raised budget) resumes where it stopped.
+    pub async fn run_embedding_migration(
+        &self,
+        client: Arc<dyn LlmClient>,
+        mut on_progress: impl FnMut(&MigrationState),
+    ) -> Result<MigrationState> {
+        let mut state = self
+            .migration_state()
+            .await?
+            .context("No embedding migration has been started")?;
+        if state.phase != MigrationPhase::Backfilling {
+            return Ok(state);
+        }
+        state.paused_reason = None;
+        // Failed nodes from earlier passes get another attempt
+        state.failed_nodes = 0;
+        let mut skip: std::collections::HashSet<String> = std::collections::HashSet::new();
+
+        loop {
+            if state.budget_exhausted() {
+                state.paused_reason = Some(format!("Budget of {} exhausted", state.plan.budget.unwrap_or_default()));
+                break;
+            }
+
+            let batch_size = self.embedding_batch_size(state.plan.batch_size);
+            let mut response = self
+                .db
+                .query(
+                    "SELECT *, meta::id(id) AS id FROM nodes
+                     WHERE ((embedding != NONE AND embedding != NULL) OR embedding_q != NONE) AND embedding_next = NONE
+                     LIMIT $limit",
+                )
+                .bind(("limit", batch_size + skip.len()))
+                .await?;
+            let nodes: Vec<KnowledgeNode> = response.take(0)?;
+            let batch: Vec<KnowledgeNode> = nodes.into_iter().filter(|n| !skip.contains(&n.id)).collect();
+            if batch.is_empty() {
+                if skip.is_empty() {
+                    state.phase = MigrationPhase::DualRead;
+                } else {
+                    state.paused_reason = Some(format!("{} nodes failed to re-embed; run again to retry", skip.len()));
+                }
+                break;
+            }
+
+            for node in batch.into_iter().take(batch_size) {
+                let request = EmbeddingRequest {
+                    input: node.content.clone(),
+                    model: state.plan.to_model.clone(),
+                };
+                let embedded = match client.embed(request).await {
+                    Ok(embedded) => embedded,
+                    Err(e) => {
+                        tracing::warn!(node = %node.id, error = %e, "Re-embedding failed");
+                        state.failed_nodes += 1;
+                        skip.insert(node.id.clone());
+                        continue;
+                    }
+                };
+                if let Some(dim) = state.plan.to_dimension {
+                    if embedded.embedding.len() != dim {
+                        bail!(
+                            "Model {} returned {} dimensions, expected {}",
+                            state.plan.to_model,
+                            embedded.embedding.len(),
+                            dim
+                        );
+                    }
+                }
+
+                let tokens = embedded.usage.total_tokens as u64;
+                state.tokens_used += tokens;
+                state.cost += state.plan.cost_per_1k_tokens * Decimal::from(tokens) / Decimal::from(1000);
+
+                self.db
+                    .query("UPDATE nodes SET embedding_next = $vec WHERE id = type::thing('nodes', $id) OR id = $id")
+                    .bind(("vec", embedded.embedding))
+                    .bind(("id", node.id))
+                    .await
+                    .context("Failed to stage re-embedded vector")?;
+                state.migrated_nodes += 1;
+            }
+
+            state.updated_at = Utc::now();
+            self.save_migration_state(&state).await?;
+            on_progress(&state);
+        }
+
+        state.updated_at = Utc::now();
+        self.save_migration_state(&state).await?;
+        on_progress(&state);
+        Ok(state)
+    }
+
+    /// Change the spend cap of the current migration (e.g. to resume after a pause)
+    pub async fn set_migration_budget(&self, budget: Option<Decimal>) -> Result<MigrationState> {
+        let mut state = self
+            .migration_state()
+            .await?
+            .context("No embedding migration has been started")?;
+        state.plan.budget = budget;
+        state.updated_at = Utc::now();
+        self.save_migration_state(&state).await?;
+        Ok(state)
+    }
+
+    /// Replace old vectors with the staged ones and switch the query model
+    pub async fn complete_embedding_migration(&self) -> Result<MigrationState> {
+        let mut state = self
+            .migration_state()
+            .await?
+            .context("No embedding migration has been started")?;
+        if state.phase != MigrationPhase::DualRead {
+            bail!("Cannot cut over from phase {:?}; backfill must finish first", state.phase);
+        }
+
+        self.db
+            .query(
+                "BEGIN TRANSACTION;
+                 UPDATE nodes SET embedding = embedding_next, embedding_next = NONE WHERE embedding_next != NONE;
+                 COMMIT TRANSACTION;",
+            )
+            .await
+            .context("Failed to promote staged embeddings")?;
+
+        state.phase = MigrationPhase::Completed;
+        state.updated_at = Utc::now();
+        self.save_migration_state(&state).await?;
+
+        // Codes (and product codebooks) were built from the old vectors
+        if let Some(config) = self.quantization() {
+            self.enable_quantization(config).await?;
+        }
+        self.rebuild_ann_index().await?;
+        Ok(state)
+    }
+
+    /// Abandon a migration, discarding staged vectors
+    pub async fn abort_embedding_migration(&self) -> Result<()> {
+        let Some(mut state) = self.migration_state().await? else {
+            return Ok(());
+        };
+        self.db.query("UPDATE nodes SET embedding_next = NONE").await?;
+        state.phase = MigrationPhase::Aborted;
+        state.updated_at = Utc::now();
+        self.save_migration_state(&state).await
+    }
+
+    /// Model used to embed search queries against `embedding`
+    pub(crate) fn query_embedding_model(&self) -> Option<String> {
+        match self.migration.read().as_ref() {
+            Some(state) if state.phase == MigrationPhase::Completed => Some(state.plan.to_model.clone()),
+            Some(state) => Some(state.plan.from_model.clone()),
+            None => None,
+        }
+    }
+
+    /// New model to additionally query while in the dual-read window
+    pub(crate) fn dual_read_model(&self) -> Option<String> {
+        self.migration
+            .read()
+            .as_ref()
+            .filter(|s| s.phase == MigrationPhase::DualRead)
+            .map(|s| s.plan.to_model.clone())
+    }
+}
@@ -11,6 +11,12 @@
 //! - Temporal queries (historical state)
 //! - AST storage for codebase
 //! - Architectural decision tracking
+//! - Lesson/decision transfer between projects
+//! - Embedding model migration with a dual-read cutover window
+//! - Optional scalar/product quantization of stored embeddings
+//! - Optional in-process HNSW index for approximate nearest neighbor search
+//! - Workspace packages with inter-package dependency edges
+//! - Change impact analysis over reverse call/dependency edges
 //!
 //! Query Modes:
 //! - Semantic: Vector similarity search
@@ -18,15 +24,29 @@
 //! - Hybrid: Combined vector + graph ranking
 //! - Temporal: Historical snapshots
 
+mod ann;
 mod database;
+mod impact;
+mod migration;
+mod quantization;
 mod search;
+mod transfer;
 mod types;
+mod workspace;
 
 #[cfg(test)]
 mod tests;
 
 // Re-export public API
+pub use ann::{AnnConfig, HnswIndex};
 pub use database::KnowledgeGraphMemory;
+pub use impact::{ImpactReport, ImpactRisk, ImpactedNode, DEFAULT_IMPACT_DEPTH};
+pub use migration::{MigrationPhase, MigrationPlan, MigrationState};
+pub use quantization::{
+    cosine_similarity, ProductQuantizer, QuantizationConfig, QuantizationMethod, QuantizedEmbedding, QueryDistance,
+};
+pub use transfer::{imported_id, ImportReport, KnowledgeBundle, Provenance, BUNDLE_VERSION, LESSON_PREFIX};
+pub use workspace::package_node_id;
 pub use types::{
     EdgeType, GraphStats, KnowledgeEdge, KnowledgeNode, NodeType, SearchQuery, SearchResult,
 };
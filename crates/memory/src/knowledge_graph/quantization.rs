@@ -0,0 +1,578 @@
+//! Embedding quantization
+//!
+//! Full-precision 1536-dim vectors cost 6KB per node. A graph can opt into
+//! storing a compact code next to (or instead of) the f32 vector:
+//!
+//! - Scalar: one byte per dimension (~4x smaller)
+//! - Product: one byte per sub-vector, using codebooks trained on the graph's
+//!   own vectors (~100x smaller at 16 sub-vectors)
+//!
+//! Semantic search scans the codes for candidates and, when the full vectors
+//! are kept, re-ranks the best `rerank_candidates` with exact cosine
+//! similarity. Dropping the full vectors saves the most space at a recall
+//! cost; `benches/quantization.rs` reports the trade-off.
+
+use super::database::KnowledgeGraphMemory;
+use super::types::{KnowledgeNode, SearchResult};
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// Record id of the quantization state in `graph_config`
+const STATE_ID: &str = "quantization";
+
+/// Vectors sampled to train product quantization codebooks
+const TRAINING_SAMPLE: usize = 10_000;
+
+/// Nodes encoded per batch when quantization is enabled
+const ENCODE_BATCH: usize = 500;
+
+/// K-means iterations per codebook
+const KMEANS_ITERATIONS: usize = 10;
+
+/// How stored embeddings are compressed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum QuantizationMethod {
+    /// 8-bit code per dimension with a per-vector range
+    Scalar,
+    /// 8-bit centroid id per sub-vector
+    Product { subvectors: usize, centroids: usize },
+}
+
+/// Per-graph quantization settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuantizationConfig {
+    pub method: QuantizationMethod,
+    /// Candidates re-ranked at full precision (0 disables re-ranking)
+    pub rerank_candidates: usize,
+    /// Keep the f32 vector next to the code (required for re-ranking)
+    pub keep_full_precision: bool,
+}
+
+impl QuantizationConfig {
+    pub fn scalar() -> Self {
+        Self {
+            method: QuantizationMethod::Scalar,
+            rerank_candidates: 50,
+            keep_full_precision: true,
+        }
+    }
+
+    pub fn product(subvectors: usize) -> Self {
+        Self {
+            method: QuantizationMethod::Product { subvectors, centroids: 256 },
+            rerank_candidates: 100,
+            keep_full_precision: true,
+        }
+    }
+
+    pub fn rerank(mut self, candidates: usize) -> Self {
+        self.rerank_candidates = candidates;
+        self
+    }
+
+    /// Store only the code; search ranks by approximate distance
+    pub fn drop_full_precision(mut self) -> Self {
+        self.keep_full_precision = false;
+        self.rerank_candidates = 0;
+        self
+    }
+
+    fn validate(&self) -> Result<()> {
+        if let QuantizationMethod::Product { subvectors, centroids } = self.method {
+            if subvectors == 0 {
+                bail!("Product quantization needs at least one sub-vector");
+            }
+            if !(1..=256).contains(&centroids) {
+                bail!("Product quantization supports 1-256 centroids, got {}", centroids);
+            }
+        }
+        if self.rerank_candidates > 0 && !self.keep_full_precision {
+            bail!("Re-ranking needs full-precision vectors to be kept");
+        }
+        Ok(())
+    }
+}
+
+/// Compact code stored in a node's `embedding_q` field
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum QuantizedEmbedding {
+    Scalar {
+        min: f32,
+        scale: f32,
+        #[serde(with = "bytes")]
+        codes: Vec<u8>,
+    },
+    Product {
+        #[serde(with = "bytes")]
+        codes: Vec<u8>,
+    },
+}
+
+impl QuantizedEmbedding {
+    /// Scalar-quantize a vector (normalized first, so distances track cosine)
+    pub fn scalar(vector: &[f32]) -> Self {
+        let v = normalized(vector);
+        let (min, max) = v
+            .iter()
+            .fold((f32::MAX, f32::MIN), |(lo, hi), &x| (lo.min(x), hi.max(x)));
+        let scale = if max > min { (max - min) / 255.0 } else { 1.0 };
+        let codes = v.iter().map(|&x| ((x - min) / scale).round() as u8).collect();
+        QuantizedEmbedding::Scalar { min, scale, codes }
+    }
+
+    /// Size of the code in bytes
+    pub fn byte_len(&self) -> usize {
+        match self {
+            QuantizedEmbedding::Scalar { codes, .. } => codes.len() + 8,
+            QuantizedEmbedding::Product { codes } => codes.len(),
+        }
+    }
+}
+
+/// Product quantization codebooks
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProductQuantizer {
+    pub dimension: usize,
+    pub subvectors: usize,
+    /// Per sub-vector: `centroids * (dimension / subvectors)` floats
+    codebooks: Vec<Vec<f32>>,
+}
+
+impl ProductQuantizer {
+    /// Train codebooks with k-means over each sub-vector space
+    pub fn train(samples: &[Vec<f32>], subvectors: usize, centroids: usize) -> Result<Self> {
+        let Some(first) = samples.first() else {
+            bail!("Product quantization needs sample vectors to train on");
+        };
+        let dimension = first.len();
+        if subvectors == 0 || dimension % subvectors != 0 {
+            bail!("Dimension {} is not divisible into {} sub-vectors", dimension, subvectors);
+        }
+        if samples.iter().any(|s| s.len() != dimension) {
+            bail!("Training vectors have mixed dimensions");
+        }
+
+        let samples: Vec<Vec<f32>> = samples.iter().map(|s| normalized(s)).collect();
+        let sub_dim = dimension / subvectors;
+        let k = centroids.clamp(1, 256).min(samples.len());
+
+        let codebooks = (0..subvectors)
+            .map(|j| {
+                let points: Vec<&[f32]> = samples.iter().map(|s| &s[j * sub_dim..(j + 1) * sub_dim]).collect();
+                kmeans(&points, k, sub_dim)
+            })
+            .collect();
+
+        Ok(Self { dimension, subvectors, codebooks })
+    }
+
+    fn sub_dim(&self) -> usize {
+        self.dimension / self.subvectors
+    }
+
+    pub fn encode(&self, vector: &[f32]) -> Result<QuantizedEmbedding> {
+        if vector.len() != self.dimension {
+            bail!("Expected {} dimensions, got {}", self.dimension, vector.len());
+        }
+        let v = normalized(vector);
+        let sub_dim = self.sub_dim();
+        let codes = self
+            .codebooks
+            .iter()
+            .enumerate()
+            .map(|(j, book)| nearest(book, &v[j * sub_dim..(j + 1) * sub_dim], sub_dim) as u8)
+            .collect();
+        Ok(QuantizedEmbedding::Product { codes })
+    }
+}
+
+/// Precomputed query for fast distance to many codes
+pub struct QueryDistance {
+    query: Vec<f32>,
+    /// Squared distance from each query sub-vector to each centroid
+    table: Option<Vec<Vec<f32>>>,
+}
+
+impl QueryDistance {
+    pub fn new(query: &[f32], pq: Option<&ProductQuantizer>) -> Self {
+        let query = normalized(query);
+        let table = pq.map(|pq| {
+            let sub_dim = pq.sub_dim();
+            pq.codebooks
+                .iter()
+                .enumerate()
+                .map(|(j, book)| {
+                    let q = &query[j * sub_dim..(j + 1) * sub_dim];
+                    book.chunks(sub_dim).map(|c| squared_l2(q, c)).collect()
+                })
+                .collect()
+        });
+        Self { query, table }
+    }
+
+    /// Approximate squared L2 distance between unit vectors (0 = identical, 4 = opposite)
+    pub fn distance(&self, code: &QuantizedEmbedding) -> f32 {
+        match code {
+            QuantizedEmbedding::Scalar { min, scale, codes } => self
+                .query
+                .iter()
+                .zip(codes)
+                .map(|(q, &c)| {
+                    let d = q - (min + c as f32 * scale);
+                    d * d
+                })
+                .sum(),
+            QuantizedEmbedding::Product { codes } => match &self.table {
+                Some(table) => codes.iter().zip(table).map(|(&c, row)| row.get(c as usize).copied().unwrap_or(4.0)).sum(),
+                None => f32::MAX,
+            },
+        }
+    }
+}
+
+/// Exact cosine similarity
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = (a.iter().map(|x| x * x).sum::<f32>() * b.iter().map(|x| x * x).sum::<f32>()).sqrt();
+    if norm == 0.0 { 0.0 } else { dot / norm }
+}
+
+fn normalized(v: &[f32]) -> Vec<f32> {
+    let norm = v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm == 0.0 {
+        v.to_vec()
+    } else {
+        v.iter().map(|x| x / norm).collect()
+    }
+}
+
+fn squared_l2(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum()
+}
+
+fn nearest(book: &[f32], point: &[f32], sub_dim: usize) -> usize {
+    book.chunks(sub_dim)
+        .enumerate()
+        .map(|(i, c)| (i, squared_l2(point, c)))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(i, _)| i)
+        .unwrap_or(0)
+}
+
+/// Lloyd's k-means with evenly spaced initial centroids (deterministic)
+fn kmeans(points: &[&[f32]], k: usize, sub_dim: usize) -> Vec<f32> {
+    let mut book: Vec<f32> = (0..k)
+        .flat_map(|i| points[i * points.len() / k].iter().copied())
+        .collect();
+
+    for _ in 0..KMEANS_ITERATIONS {
+        let mut sums = vec![0.0f32; k * sub_dim];
+        let mut counts = vec![0usize; k];
+        for point in points {
+            let c = nearest(&book, point, sub_dim);
+            counts[c] += 1;
+            for (s, x) in sums[c * sub_dim..(c + 1) * sub_dim].iter_mut().zip(point.iter()) {
+                *s += x;
+            }
+        }
+        for c in 0..k {
+            // Empty clusters keep their previous centroid
+            if counts[c] > 0 {
+                for d in 0..sub_dim {
+                    book[c * sub_dim + d] = sums[c * sub_dim + d] / counts[c] as f32;
+                }
+            }
+        }
+    }
+    book
+}
+
+/// Serialize codes as a byte string rather than an array of numbers
+mod bytes {
+    use serde::de::{SeqAccess, Visitor};
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(codes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(codes)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        struct BytesVisitor;
+
+        impl<'de> Visitor<'de> for BytesVisitor {
+            type Value = Vec<u8>;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a byte string")
+            }
+
+            fn visit_bytes<E>(self, v: &[u8]) -> Result<Vec<u8>, E> {
+                Ok(v.to_vec())
+            }
+
+            fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Vec<u8>, E> {
+                Ok(v)
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<u8>, A::Error> {
+                let mut out = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+                while let Some(b) = seq.next_element()? {
+                    out.push(b);
+                }
+                Ok(out)
+            }
+        }
+
+        deserializer.deserialize_byte_buf(BytesVisitor)
+    }
+}
+
+/// Persisted quantization settings and trained codebooks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuantizationState {
+    pub config: QuantizationConfig,
+    #[serde(default)]
+    pub product: Option<ProductQuantizer>,
+}
+
+impl QuantizationState {
+    fn encode(&self, vector: &[f32]) -> Result<QuantizedEmbedding> {
+        match &self.product {
+            Some(pq) => pq.encode(vector),
+            None => Ok(QuantizedEmbedding::scalar(vector)),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct EmbeddingRow {
+    id: String,
+    embedding: Vec<f32>,
+}
+
+#[derive(Deserialize)]
+struct CodeRow {
+    id: String,
+    embedding_q: QuantizedEmbedding,
+}
+
+impl KnowledgeGraphMemory {
+    /// Current quantization settings, if enabled for this graph
+    pub fn quantization(&self) -> Option<QuantizationConfig> {
+        self.quantization.read().as_ref().map(|s| s.config)
+    }
+
+    pub(crate) async fn load_quantization_state(&self) -> Result<Option<QuantizationState>> {
+        let state: Option<QuantizationState> = self.db.select(("graph_config", STATE_ID)).await?;
+        Ok(state)
+    }
+
+    /// Enable quantization and encode every stored embedding
+    ///
+    /// Product quantization trains its codebooks on a sample of the existing
+    /// vectors, so enable it once the graph holds representative data.
+    /// Returns the number of nodes encoded.
+    pub async fn enable_quantization(&self, config: QuantizationConfig) -> Result<usize> {
+        config.validate()?;
+
+        // Codes are all that is left of nodes whose full vectors were dropped
+        let mut response = self
+            .db
+            .query("SELECT count() FROM nodes WHERE embedding_q != NONE AND (embedding = NONE OR embedding = NULL) GROUP ALL")
+            .await?;
+        let code_only: Option<serde_json::Value> = response.take(0)?;
+        if code_only.and_then(|v| v.get("count").and_then(|c| c.as_u64())).unwrap_or(0) > 0 {
+            bail!("Some nodes only have quantized embeddings; re-embed the graph before changing quantization");
+        }
+
+        let product = match config.method {
+            QuantizationMethod::Scalar => None,
+            QuantizationMethod::Product { subvectors, centroids } => {
+                let mut response = self
+                    .db
+                    .query("SELECT meta::id(id) AS id, embedding FROM nodes WHERE embedding != NONE AND embedding != NULL LIMIT $limit")
+                    .bind(("limit", TRAINING_SAMPLE))
+                    .await?;
+                let rows: Vec<EmbeddingRow> = response.take(0)?;
+                let samples: Vec<Vec<f32>> = rows.into_iter().map(|r| r.embedding).collect();
+                Some(ProductQuantizer::train(&samples, subvectors, centroids)?)
+            }
+        };
+
+        let state = QuantizationState { config, product };
+        let _: Option<QuantizationState> = self
+            .db
+            .upsert(("graph_config", STATE_ID))
+            .content(state.clone())
+            .await
+            .context("Failed to persist quantization settings")?;
+        *self.quantization.write() = Some(state);
+
+        self.db.query("UPDATE nodes SET embedding_q = NONE").await?;
+        self.encode_pending_embeddings().await
+    }
+
+    /// Turn quantization off, removing the stored codes
+    pub async fn disable_quantization(&self) -> Result<()> {
+        if let Some(config) = self.quantization() {
+            if !config.keep_full_precision {
+                bail!("Full-precision vectors were dropped; re-embed the graph before disabling quantization");
+            }
+        }
+        self.db.query("UPDATE nodes SET embedding_q = NONE").await?;
+        let _: Option<QuantizationState> = self.db.delete(("graph_config", STATE_ID)).await?;
+        *self.quantization.write() = None;
+        Ok(())
+    }
+
+    /// Encode nodes that have a full vector but no code yet
+    pub(crate) async fn encode_pending_embeddings(&self) -> Result<usize> {
+        let Some(state) = self.quantization.read().clone() else {
+            return Ok(0);
+        };
+        let update = if state.config.keep_full_precision {
+            "UPDATE nodes SET embedding_q = $code WHERE id = type::thing('nodes', $id) OR id = $id"
+        } else {
+            "UPDATE nodes SET embedding_q = $code, embedding = NONE WHERE id = type::thing('nodes', $id) OR id = $id"
+        };
+
+        let mut encoded = 0;
+        loop {
+            let mut response = self
+                .db
+                .query(
+                    "SELECT meta::id(id) AS id, embedding FROM nodes
+                     WHERE embedding != NONE AND embedding != NULL AND embedding_q = NONE
+                     LIMIT $limit",
+                )
+                .bind(("limit", ENCODE_BATCH))
+                .await?;
+            let rows: Vec<EmbeddingRow> = response.take(0)?;
+            if rows.is_empty() {
+                break;
+            }
+            for row in rows {
+                let code = state
+                    .encode(&row.embedding)
+                    .with_context(|| format!("Failed to quantize embedding of node {}", row.id))?;
+                self.db
+                    .query(update)
+                    .bind(("code", code))
+                    .bind(("id", row.id))
+                    .await
+                    .context("Failed to store quantized embedding")?;
+                encoded += 1;
+            }
+        }
+        tracing::debug!(encoded, "Quantized stored embeddings");
+        Ok(encoded)
+    }
+
+    /// Code to store with a newly inserted node, if quantization is enabled
+    pub(crate) fn quantize_for_insert(&self, node: &mut KnowledgeNode) -> Result<Option<QuantizedEmbedding>> {
+        let guard = self.quantization.read();
+        let (Some(state), Some(embedding)) = (guard.as_ref(), node.embedding.as_ref()) else {
+            return Ok(None);
+        };
+        let code = state.encode(embedding)?;
+        if !state.config.keep_full_precision {
+            node.embedding = None;
+        }
+        Ok(Some(code))
+    }
+
+    /// Scan codes for candidates, then re-rank at full precision if configured
+    pub(crate) async fn quantized_search(
+        &self,
+        query_vec: &[f32],
+        top_k: usize,
+        type_filter: &str,
+    ) -> Result<Vec<SearchResult>> {
+        let Some(state) = self.quantization.read().clone() else {
+            bail!("Quantization is not enabled for this graph");
+        };
+
+        let query_str = format!(
+            "SELECT meta::id(id) AS id, embedding_q FROM nodes WHERE embedding_q != NONE {}",
+            type_filter
+        );
+        let mut response = self.db.query(query_str).await?;
+        let rows: Vec<CodeRow> = response.take(0)?;
+
+        if let Some(pq) = &state.product {
+            if query_vec.len() != pq.dimension {
+                bail!("Query has {} dimensions, codebooks expect {}", query_vec.len(), pq.dimension);
+            }
+        }
+        let distance = QueryDistance::new(query_vec, state.product.as_ref());
+        let mut scored: Vec<(String, f32)> = rows
+            .into_iter()
+            .map(|row| {
+                let d = distance.distance(&row.embedding_q);
+                (row.id, d)
+            })
+            .collect();
+        scored.sort_by(|a, b| a.1.total_cmp(&b.1));
+        scored.truncate(state.config.rerank_candidates.max(top_k));
+
+        let mut results = Vec::with_capacity(scored.len());
+        for (id, approx) in scored {
+            let Some(node) = self.get_node(&id).await? else { continue };
+            let relevance_score = match (&node.embedding, state.config.rerank_candidates > 0) {
+                (Some(full), true) => cosine_similarity(query_vec, full),
+                // Unit vectors: squared L2 = 2 - 2cos
+                _ => 1.0 - approx / 2.0,
+            };
+            results.push(SearchResult { node, relevance_score, path: None });
+        }
+        results.sort_by(|a, b| b.relevance_score.total_cmp(&a.relevance_score));
+        results.truncate(top_k);
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vector(seed: u32, dim: usize) -> Vec<f32> {
+        (0..dim).map(|i| (((seed * 31 + i as u32 * 17) % 97) as f32 / 97.0) - 0.5).collect()
+    }
+
+    #[test]
+    fn test_scalar_ranks_like_cosine() {
+        let docs: Vec<Vec<f32>> = (0..20).map(|s| vector(s, 64)).collect();
+        let query = vector(7, 64);
+        let distance = QueryDistance::new(&query, None);
+
+        let best = docs
+            .iter()
+            .enumerate()
+            .min_by(|a, b| {
+                distance
+                    .distance(&QuantizedEmbedding::scalar(a.1))
+                    .total_cmp(&distance.distance(&QuantizedEmbedding::scalar(b.1)))
+            })
+            .unwrap()
+            .0;
+        assert_eq!(best, 7);
+        assert_eq!(QuantizedEmbedding::scalar(&query).byte_len(), 64 + 8);
+    }
+
+    #[test]
+    fn test_product_quantizer() {
+        let docs: Vec<Vec<f32>> = (0..50).map(|s| vector(s, 32)).collect();
+        let pq = ProductQuantizer::train(&docs, 8, 64).unwrap();
+        let code = pq.encode(&docs[3]).unwrap();
+        assert_eq!(code.byte_len(), 8);
+
+        let distance = QueryDistance::new(&docs[3], Some(&pq));
+        assert!(distance.distance(&code) < 0.1);
+
+        assert!(ProductQuantizer::train(&docs, 5, 16).is_err());
+        assert!(pq.encode(&[0.0; 16]).is_err());
+        assert!(QuantizationConfig::scalar().rerank(10).drop_full_precision().validate().is_ok());
+    }
+}
@@ -56,18 +56,36 @@ impl KnowledgeGraphMemory {
         let client = self.llm_client.as_ref()
             .context("LLM client not configured for semantic search")?;
 
-        // 1. Generate embedding for the query
-        let embedding_resp = client.embed(zed42_llm::EmbeddingRequest::new(query_text.to_string())).await?;
+        // 1. Generate embedding for the query with the model the stored vectors came from
+        let mut embed_req = zed42_llm::EmbeddingRequest::new(query_text.to_string());
+        if let Some(model) = self.query_embedding_model() {
+            embed_req.model = model;
+        }
+        let embedding_resp = client.embed(embed_req).await?;
         let query_embedding = embedding_resp.embedding;
 
         // 2. Build SurrealQL vector search query
         let type_filter = if let Some(types) = node_types {
-            let type_strs: Vec<String> = types.iter().map(|t| format!("'{:?}'", t)).collect();
+            // Stored as the serde (snake_case) names, e.g. 'function'
+            let type_strs: Vec<String> = types
+                .iter()
+                .filter_map(|t| serde_json::to_value(t).ok()?.as_str().map(|s| format!("'{}'", s)))
+                .collect();
             format!("AND node_type IN [{}]", type_strs.join(", "))
         } else {
             String::new()
         };
 
+        // The index only covers full-precision vectors and the current model
+        let use_ann = self.ann.read().as_ref().is_some_and(|index| !index.is_empty());
+        if use_ann && self.dual_read_model().is_none() {
+            return self.ann_search(&query_embedding, top_k, node_types).await;
+        }
+
+        if self.quantization.read().is_some() {
+            return self.quantized_search(&query_embedding, top_k, &type_filter).await;
+        }
+
         // Use native vector search syntax with cosine distance
         // The <40> is a threshold/limit for the MTREE index search
         let query_str = format!(
@@ -83,7 +101,33 @@ impl KnowledgeGraphMemory {
             .bind(("query_vec", query_embedding))
             .await?;
         
-        let nodes: Vec<KnowledgeNode> = response.take(0)?;
+        let mut nodes: Vec<KnowledgeNode> = response.take(0)?;
+
+        // Dual-read window of an embedding migration: also search the staged vectors
+        if let Some(model) = self.dual_read_model() {
+            let staged_embedding = client
+                .embed(zed42_llm::EmbeddingRequest { input: query_text.to_string(), model })
+                .await?
+                .embedding;
+            let staged_query = format!(
+                "SELECT *, meta::id(id) AS id, vector::distance::cosine(embedding_next, $query_vec) AS dist
+                 FROM nodes
+                 WHERE embedding_next != NONE {}
+                 ORDER BY dist ASC
+                 LIMIT {}",
+                type_filter, top_k
+            );
+            let mut response: surrealdb::Response = self.db.query(staged_query)
+                .bind(("query_vec", staged_embedding))
+                .await?;
+            let staged: Vec<KnowledgeNode> = response.take(0)?;
+            for node in staged {
+                if !nodes.iter().any(|n| n.id == node.id) {
+                    nodes.push(node);
+                }
+            }
+            nodes.truncate(top_k);
+        }
 
         let results = nodes
             .into_iter()
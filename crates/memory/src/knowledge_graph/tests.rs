@@ -234,3 +234,309 @@ async fn test_stats() {
     // Should start with 0 nodes
     assert_eq!(stats.node_count, 0);
 }
+
+#[tokio::test]
+async fn test_knowledge_transfer_with_provenance() {
+    let (source, _src_temp) = create_test_graph().await;
+    let (target, _dst_temp) = create_test_graph().await;
+
+    let node = |id: &str, node_type: &str, name: &str| KnowledgeNode {
+        id: id.to_string(),
+        node_type: node_type.to_string(),
+        name: name.to_string(),
+        content: json!({"lesson": "prefer cursor pagination"}).to_string(),
+        embedding: None,
+        metadata: json!({"agent_id": "a1"}).to_string(),
+        created_at: 0,
+        updated_at: 0,
+    };
+    source.insert_node(node("lesson1", "documentation", "Lesson: t1")).await.unwrap();
+    source.insert_node(node("adr1", "decision", "Use SurrealDB")).await.unwrap();
+    source.insert_node(node("readme", "documentation", "README")).await.unwrap();
+    source.insert_node(node("fn1", "function", "main")).await.unwrap();
+
+    let bundle = source.export_knowledge("alpha").await.unwrap();
+    assert_eq!(bundle.nodes.len(), 2);
+
+    let report = target.import_knowledge(&bundle).await.unwrap();
+    assert_eq!(report.imported_nodes, 2);
+
+    let imported = target.get_node(&imported_id("alpha", "lesson1")).await.unwrap().unwrap();
+    let metadata: serde_json::Value = serde_json::from_str(&imported.metadata).unwrap();
+    assert_eq!(metadata["agent_id"], "a1");
+    assert_eq!(metadata["provenance"]["source_project"], "alpha");
+    assert_eq!(metadata["provenance"]["source_node_id"], "lesson1");
+
+    // Re-importing the same bundle is a no-op
+    let again = target.import_knowledge(&bundle).await.unwrap();
+    assert_eq!(again.imported_nodes, 0);
+    assert_eq!(again.skipped_nodes, 2);
+}
+
+#[tokio::test]
+async fn test_embedding_migration_phases() {
+    let temp_dir = TempDir::new().unwrap();
+    let client: std::sync::Arc<dyn zed42_llm::LlmClient> =
+        std::sync::Arc::new(zed42_llm::MockLlmClient::new(String::new()));
+    let graph = KnowledgeGraphMemory::new(temp_dir.path(), "migrate_kg", Some(client.clone()))
+        .await
+        .unwrap();
+
+    for i in 0..5 {
+        graph.insert_node(KnowledgeNode {
+            id: format!("n{}", i),
+            node_type: "function".to_string(),
+            name: format!("f{}", i),
+            content: format!("fn f{}() {{}}", i),
+            embedding: Some(vec![0.5; 8]),
+            metadata: "{}".to_string(),
+            created_at: 0,
+            updated_at: 0,
+        }).await.unwrap();
+    }
+
+    let plan = MigrationPlan::new("text-embedding-3-small", "bge-m3")
+        .dimension(1536)
+        .batch_size(2)
+        .pricing(rust_decimal::Decimal::ONE)
+        .budget(rust_decimal::Decimal::new(12, 3)); // 0.012 = ~2 nodes at 5 tokens each
+    let state = graph.start_embedding_migration(plan).await.unwrap();
+    assert_eq!(state.total_nodes, 5);
+    assert!(graph.start_embedding_migration(MigrationPlan::new("a", "b")).await.is_err());
+
+    // Budget pauses the backfill part-way
+    let mut batches = 0;
+    let paused = graph.run_embedding_migration(client.clone(), |_| batches += 1).await.unwrap();
+    assert_eq!(paused.phase, MigrationPhase::Backfilling);
+    assert!(paused.paused_reason.unwrap().contains("Budget"));
+    assert!(paused.migrated_nodes < 5);
+    assert!(batches > 0);
+    assert!(graph.complete_embedding_migration().await.is_err());
+
+    // Raising the budget resumes where it stopped
+    graph.set_migration_budget(None).await.unwrap();
+    let done = graph.run_embedding_migration(client.clone(), |_| {}).await.unwrap();
+    assert_eq!(done.phase, MigrationPhase::DualRead);
+    assert_eq!(done.migrated_nodes, 5);
+
+    let completed = graph.complete_embedding_migration().await.unwrap();
+    assert_eq!(completed.phase, MigrationPhase::Completed);
+    let node = graph.get_node("n0").await.unwrap().unwrap();
+    assert_eq!(node.embedding.unwrap().len(), 1536);
+}
+
+#[tokio::test]
+async fn test_quantized_semantic_search() {
+    let temp_dir = TempDir::new().unwrap();
+    let client: std::sync::Arc<dyn zed42_llm::LlmClient> =
+        std::sync::Arc::new(zed42_llm::MockLlmClient::new(String::new()));
+    let graph = KnowledgeGraphMemory::new(temp_dir.path(), "quantized_kg", Some(client))
+        .await
+        .unwrap();
+
+    let node = |id: &str, embedding: Vec<f32>| KnowledgeNode {
+        id: id.to_string(),
+        node_type: "function".to_string(),
+        name: id.to_string(),
+        content: String::new(),
+        embedding: Some(embedding),
+        metadata: "{}".to_string(),
+        created_at: 0,
+        updated_at: 0,
+    };
+    // The mock embeds every query as [0.1; 1536]
+    graph.insert_node(node("match", vec![0.1; 1536])).await.unwrap();
+    graph
+        .insert_node(node("other", (0..1536).map(|i| if i % 2 == 0 { 1.0 } else { -1.0 }).collect()))
+        .await
+        .unwrap();
+
+    let encoded = graph.enable_quantization(QuantizationConfig::scalar()).await.unwrap();
+    assert_eq!(encoded, 2);
+
+    // Inserts after enabling are encoded on write
+    graph.insert_node(node("late", (0..1536).map(|i| i as f32).collect())).await.unwrap();
+
+    let query = || SearchQuery::Semantic { query_text: "q".to_string(), top_k: 2, node_types: None };
+    let results = graph.search(query()).await.unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].node.id, "match");
+    assert!(results[0].relevance_score > 0.99);
+
+    // Without full-precision vectors ranking falls back to the codes
+    graph
+        .enable_quantization(QuantizationConfig::scalar().drop_full_precision())
+        .await
+        .unwrap();
+    assert!(graph.get_node("match").await.unwrap().unwrap().embedding.is_none());
+    let results = graph.search(query()).await.unwrap();
+    assert_eq!(results[0].node.id, "match");
+    assert!(graph.disable_quantization().await.is_err());
+    assert!(graph.enable_quantization(QuantizationConfig::scalar()).await.is_err());
+}
+
+#[tokio::test]
+async fn test_ann_index_tracks_writes() {
+    let temp_dir = TempDir::new().unwrap();
+    let client: std::sync::Arc<dyn zed42_llm::LlmClient> =
+        std::sync::Arc::new(zed42_llm::MockLlmClient::new(String::new()));
+    let graph = KnowledgeGraphMemory::new(temp_dir.path(), "ann_kg", Some(client))
+        .await
+        .unwrap();
+
+    let node = |id: &str, node_type: &str, embedding: Vec<f32>| KnowledgeNode {
+        id: id.to_string(),
+        node_type: node_type.to_string(),
+        name: id.to_string(),
+        content: String::new(),
+        embedding: Some(embedding),
+        metadata: "{}".to_string(),
+        created_at: 0,
+        updated_at: 0,
+    };
+    graph
+        .insert_node(node("far", "function", (0..1536).map(|i| if i % 2 == 0 { 1.0 } else { -1.0 }).collect()))
+        .await
+        .unwrap();
+
+    assert_eq!(graph.enable_ann_index(AnnConfig::default()).await.unwrap(), 1);
+    assert!(temp_dir.path().join("ann_kg.hnsw").exists());
+
+    // Writes after the build reach the index; the mock embeds queries as [0.1; 1536]
+    graph.insert_node(node("near", "test", vec![0.1; 1536])).await.unwrap();
+    let query = |types: Option<Vec<NodeType>>| SearchQuery::Semantic { query_text: "q".to_string(), top_k: 1, node_types: types };
+    let results = graph.search(query(None)).await.unwrap();
+    assert_eq!(results[0].node.id, "near");
+    assert!(results[0].relevance_score > 0.99);
+
+    let results = graph.search(query(Some(vec![NodeType::Function]))).await.unwrap();
+    assert_eq!(results[0].node.id, "far");
+
+    graph.delete_node("near").await.unwrap();
+    let results = graph.search(query(None)).await.unwrap();
+    assert_eq!(results[0].node.id, "far");
+
+    graph.disable_ann_index().unwrap();
+    assert!(!temp_dir.path().join("ann_kg.hnsw").exists());
+}
+
+#[tokio::test]
+async fn test_index_workspace_packages() {
+    use zed42_core::workspace::{Package, PackageKind, ProjectWorkspace};
+
+    let (graph, _temp) = create_test_graph().await;
+    let package = |name: &str, deps: &[&str]| Package {
+        name: name.to_string(),
+        path: format!("crates/{}", name).into(),
+        kind: PackageKind::Cargo,
+        dependencies: deps.iter().map(|d| d.to_string()).collect(),
+        build_command: vec!["cargo".to_string(), "build".to_string(), "-p".to_string(), name.to_string()],
+        test_command: vec!["cargo".to_string(), "test".to_string(), "-p".to_string(), name.to_string()],
+    };
+    let mut workspace = ProjectWorkspace {
+        root: "/repo".into(),
+        packages: vec![package("core", &[]), package("api", &["core"])],
+    };
+
+    assert_eq!(graph.index_workspace(&workspace).await.unwrap(), (2, 1));
+    let api = graph.get_node(&package_node_id("api")).await.unwrap().unwrap();
+    assert!(api.metadata.contains("cargo"));
+    let edges = graph.get_edges_by_type(EdgeType::DependsOn).await.unwrap();
+    assert_eq!(edges.len(), 1);
+
+    // Re-indexing replaces the previous packages and edges
+    workspace.packages.remove(1);
+    assert_eq!(graph.index_workspace(&workspace).await.unwrap(), (1, 0));
+    assert_eq!(graph.get_nodes_by_type(NodeType::Package).await.unwrap().len(), 1);
+    assert!(graph.get_edges_by_type(EdgeType::DependsOn).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_analyze_impact_blast_radius() {
+    let (graph, _temp) = create_test_graph().await;
+    let node = |id: &str, node_type: &str, metadata: serde_json::Value| KnowledgeNode {
+        id: id.to_string(),
+        node_type: node_type.to_string(),
+        name: id.to_string(),
+        content: String::new(),
+        embedding: None,
+        metadata: metadata.to_string(),
+        created_at: 0,
+        updated_at: 0,
+    };
+    let edge = |edge_type: &str, from: &str, to: &str| KnowledgeEdge {
+        id: format!("{}-{}-{}", edge_type, from, to),
+        edge_type: edge_type.to_string(),
+        from_id: from.to_string(),
+        to_id: to.to_string(),
+        metadata: None,
+        created_at: 0,
+    };
+
+    for n in [
+        node("parse", "function", json!({})),
+        node("load", "function", json!({})),
+        node("api_get", "function", json!({"visibility": "pub"})),
+        node("unrelated", "function", json!({})),
+        node("parser.rs", "file", json!({})),
+        node("handlers.rs", "file", json!({})),
+        node("test_load", "test", json!({})),
+    ] {
+        graph.insert_node(n).await.unwrap();
+    }
+    for e in [
+        edge("calls", "load", "parse"),
+        edge("calls", "api_get", "load"),
+        edge("calls", "unrelated", "api_get_other"),
+        edge("contains", "parser.rs", "parse"),
+        edge("contains", "parser.rs", "load"),
+        edge("contains", "handlers.rs", "api_get"),
+        edge("tests", "test_load", "load"),
+    ] {
+        graph.insert_edge(e).await.unwrap();
+    }
+
+    let report = graph.analyze_impact("parse", DEFAULT_IMPACT_DEPTH).await.unwrap();
+    let dependents: Vec<(&str, usize)> = report.dependents.iter().map(|d| (d.name.as_str(), d.distance)).collect();
+    assert_eq!(dependents, vec![("load", 1), ("api_get", 2)]);
+    assert_eq!(report.affected_files, vec!["handlers.rs", "parser.rs"]);
+    assert_eq!(report.affected_tests, vec!["test_load"]);
+    assert_eq!(report.public_apis, vec!["api_get"]);
+    assert_eq!(report.risk, ImpactRisk::Medium);
+    assert!(!report.truncated);
+
+    let shallow = graph.analyze_impact("parse", 1).await.unwrap();
+    assert_eq!(shallow.dependents.len(), 1);
+    assert!(shallow.truncated);
+    assert!(graph.analyze_impact("missing", 3).await.is_err());
+}
+
+#[tokio::test]
+async fn test_list_nodes_page() {
+    let (graph, _temp) = create_test_graph().await;
+    for (i, created_at) in [10, 20, 20, 30].into_iter().enumerate() {
+        graph
+            .insert_node(KnowledgeNode {
+                id: format!("node-{}", i),
+                node_type: "function".to_string(),
+                name: format!("f{}", i),
+                content: String::new(),
+                embedding: None,
+                metadata: json!({}).to_string(),
+                created_at,
+                updated_at: created_at,
+            })
+            .await
+            .unwrap();
+    }
+
+    let first = graph.list_nodes_page(None, zed42_core::PageRequest::first(3)).await.unwrap();
+    assert_eq!(first.items.iter().map(|n| n.created_at).collect::<Vec<_>>(), vec![30, 20, 20]);
+    let rest = graph
+        .list_nodes_page(None, zed42_core::PageRequest::first(3).after(first.next_cursor.unwrap()))
+        .await
+        .unwrap();
+    assert_eq!(rest.items.len(), 1);
+    assert_eq!(rest.items[0].name, "f0");
+    assert!(!rest.has_more());
+}
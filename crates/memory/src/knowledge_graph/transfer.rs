@@ -0,0 +1,171 @@
+//! Knowledge transfer between project graphs
+//!
+//! Lessons learned and architectural decisions are exported as a portable
+//! bundle and imported into another project's graph, so a new project can
+//! start from what earlier runs already learned. Imported nodes carry a
+//! `provenance` entry in their metadata and get deterministic ids, which makes
+//! re-importing the same bundle a no-op.
+
+use super::database::KnowledgeGraphMemory;
+use super::types::{EdgeType, KnowledgeEdge, KnowledgeNode, NodeType};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Bundle format version, bumped on incompatible changes
+pub const BUNDLE_VERSION: u32 = 1;
+
+/// Name prefix of reflection nodes written by agents
+pub const LESSON_PREFIX: &str = "Lesson:";
+
+/// Where an imported node came from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Provenance {
+    pub source_project: String,
+    pub source_node_id: String,
+    pub exported_at: DateTime<Utc>,
+    #[serde(default)]
+    pub imported_at: Option<DateTime<Utc>>,
+}
+
+/// Portable set of lessons and decisions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KnowledgeBundle {
+    pub version: u32,
+    pub source_project: String,
+    pub exported_at: DateTime<Utc>,
+    pub nodes: Vec<KnowledgeNode>,
+    /// Edges whose endpoints are both in `nodes`
+    pub edges: Vec<KnowledgeEdge>,
+}
+
+impl KnowledgeBundle {
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let content = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, content)
+            .with_context(|| format!("Failed to write knowledge bundle to {}", path.display()))
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read knowledge bundle from {}", path.display()))?;
+        let bundle: Self = serde_json::from_str(&content).context("Failed to parse knowledge bundle")?;
+        if bundle.version > BUNDLE_VERSION {
+            anyhow::bail!(
+                "Knowledge bundle version {} is newer than supported version {}",
+                bundle.version,
+                BUNDLE_VERSION
+            );
+        }
+        Ok(bundle)
+    }
+}
+
+/// Outcome of an import
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub imported_nodes: usize,
+    /// Nodes already present from an earlier import of the same source
+    pub skipped_nodes: usize,
+    pub imported_edges: usize,
+}
+
+/// Id an imported node is stored under
+pub fn imported_id(source_project: &str, source_node_id: &str) -> String {
+    format!("import:{}:{}", source_project, source_node_id)
+}
+
+fn is_transferable(node: &KnowledgeNode) -> bool {
+    match node.node_type.as_str() {
+        "decision" => true,
+        "documentation" => node.name.starts_with(LESSON_PREFIX),
+        _ => false,
+    }
+}
+
+impl KnowledgeGraphMemory {
+    /// Export every lesson and decision node in this graph
+    pub async fn export_knowledge(&self, source_project: &str) -> Result<KnowledgeBundle> {
+        let mut nodes = self.get_nodes_by_type(NodeType::Decision).await?;
+        nodes.extend(self.get_nodes_by_type(NodeType::Documentation).await?);
+        nodes.retain(is_transferable);
+
+        let ids: HashSet<&str> = nodes.iter().map(|n| n.id.as_str()).collect();
+        let edges = self
+            .get_edges_by_type(EdgeType::Supersedes)
+            .await?
+            .into_iter()
+            .filter(|e| ids.contains(e.from_id.as_str()) && ids.contains(e.to_id.as_str()))
+            .collect();
+
+        Ok(KnowledgeBundle {
+            version: BUNDLE_VERSION,
+            source_project: source_project.to_string(),
+            exported_at: Utc::now(),
+            nodes,
+            edges,
+        })
+    }
+
+    /// Import a bundle, recording provenance on every node
+    pub async fn import_knowledge(&self, bundle: &KnowledgeBundle) -> Result<ImportReport> {
+        let mut report = ImportReport::default();
+        let now = Utc::now();
+
+        for node in &bundle.nodes {
+            let id = imported_id(&bundle.source_project, &node.id);
+            if self.get_node(&id).await?.is_some() {
+                report.skipped_nodes += 1;
+                continue;
+            }
+
+            let provenance = Provenance {
+                source_project: bundle.source_project.clone(),
+                source_node_id: node.id.clone(),
+                exported_at: bundle.exported_at,
+                imported_at: Some(now),
+            };
+            let mut metadata: serde_json::Value =
+                serde_json::from_str(&node.metadata).unwrap_or_else(|_| serde_json::json!({}));
+            if !metadata.is_object() {
+                metadata = serde_json::json!({ "original": metadata });
+            }
+            metadata["provenance"] = serde_json::to_value(&provenance)?;
+
+            self.insert_node(KnowledgeNode {
+                id,
+                metadata: metadata.to_string(),
+                created_at: now.timestamp(),
+                updated_at: now.timestamp(),
+                ..node.clone()
+            })
+            .await?;
+            report.imported_nodes += 1;
+        }
+
+        for edge in &bundle.edges {
+            let id = imported_id(&bundle.source_project, &edge.id);
+            if !self.get_edges_by_type(EdgeType::from(edge.edge_type.clone())).await?.iter().any(|e| e.id == id) {
+                self.insert_edge(KnowledgeEdge {
+                    id,
+                    from_id: imported_id(&bundle.source_project, &edge.from_id),
+                    to_id: imported_id(&bundle.source_project, &edge.to_id),
+                    created_at: now.timestamp(),
+                    ..edge.clone()
+                })
+                .await?;
+                report.imported_edges += 1;
+            }
+        }
+
+        tracing::info!(
+            source = %bundle.source_project,
+            imported = report.imported_nodes,
+            skipped = report.skipped_nodes,
+            "Imported knowledge bundle"
+        );
+        Ok(report)
+    }
+}
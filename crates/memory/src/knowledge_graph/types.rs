@@ -20,6 +20,12 @@ pub enum NodeType {
     Test,
     /// Documentation
     Documentation,
+    /// Workspace member package (crate, npm package)
+    Package,
+    /// Contributor, from git history
+    Person,
+    /// Human correction of an agent's output
+    Feedback,
 }
 
 impl From<String> for NodeType {
@@ -33,6 +39,9 @@ impl From<String> for NodeType {
             "dependency" => NodeType::Dependency,
             "test" => NodeType::Test,
             "documentation" => NodeType::Documentation,
+            "package" => NodeType::Package,
+            "person" => NodeType::Person,
+            "feedback" => NodeType::Feedback,
             _ => NodeType::Documentation,
         }
     }
@@ -58,6 +67,8 @@ pub enum EdgeType {
     ImplementsDecision,
     /// A supersedes B (newer version)
     Supersedes,
+    /// Person A regularly changes module B
+    Owns,
 }
 
 impl From<String> for EdgeType {
@@ -71,6 +82,7 @@ impl From<String> for EdgeType {
             "documents" => EdgeType::Documents,
             "implements_decision" => EdgeType::ImplementsDecision,
             "supersedes" => EdgeType::Supersedes,
+            "owns" => EdgeType::Owns,
             _ => EdgeType::DependsOn,
         }
     }
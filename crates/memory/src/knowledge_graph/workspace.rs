@@ -0,0 +1,63 @@
+//! Workspace packages in the graph
+//!
+//! Indexes the members of a [`ProjectWorkspace`] as `package` nodes with
+//! `depends_on` edges between them, so structural queries can follow
+//! inter-crate dependencies and agents can scope work to one member.
+//! Re-indexing replaces the previous package nodes and their edges.
+
+use super::database::KnowledgeGraphMemory;
+use super::types::{KnowledgeEdge, KnowledgeNode, NodeType};
+use anyhow::Result;
+use serde_json::json;
+use zed42_core::workspace::ProjectWorkspace;
+
+/// Id a workspace package is stored under
+pub fn package_node_id(name: &str) -> String {
+    format!("package:{}", name)
+}
+
+impl KnowledgeGraphMemory {
+    /// Store the workspace's packages and inter-package edges; returns (nodes, edges) written
+    pub async fn index_workspace(&self, workspace: &ProjectWorkspace) -> Result<(usize, usize)> {
+        for stale in self.get_nodes_by_type(NodeType::Package).await? {
+            self.delete_node(&stale.id).await?;
+        }
+
+        let now = chrono::Utc::now().timestamp();
+        let mut edges = 0;
+        for package in &workspace.packages {
+            self.insert_node(KnowledgeNode {
+                id: package_node_id(&package.name),
+                node_type: "package".to_string(),
+                name: package.name.clone(),
+                content: serde_json::to_string(package)?,
+                embedding: None,
+                metadata: json!({
+                    "path": package.path,
+                    "kind": package.kind,
+                    "build_command": package.build_command,
+                    "test_command": package.test_command,
+                })
+                .to_string(),
+                created_at: now,
+                updated_at: now,
+            })
+            .await?;
+        }
+        for package in &workspace.packages {
+            for dependency in &package.dependencies {
+                self.insert_edge(KnowledgeEdge {
+                    id: format!("{}->{}", package_node_id(&package.name), package_node_id(dependency)),
+                    edge_type: "depends_on".to_string(),
+                    from_id: package_node_id(&package.name),
+                    to_id: package_node_id(dependency),
+                    metadata: None,
+                    created_at: now,
+                })
+                .await?;
+                edges += 1;
+            }
+        }
+        Ok((workspace.packages.len(), edges))
+    }
+}
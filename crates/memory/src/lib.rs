@@ -16,12 +16,16 @@ use std::sync::Arc;
 use zed42_llm::LlmClient;
 
 pub mod archive;
+pub mod evaluation;
+pub mod feedback;
 pub mod knowledge_graph;
+pub mod pressure;
 pub mod session;
 pub mod working;
 
 use archive::{ArchiveMemory, ArchiveQuery};
 use knowledge_graph::{KnowledgeGraphMemory, SearchQuery};
+use pressure::{MemoryPressureMonitor, PressureThresholds};
 use session::SessionMemory;
 pub use working::{WorkingMemory, CacheStats};
 use zed42_core::types::SessionId;
@@ -228,6 +232,25 @@ impl MemorySubstrate {
         self.knowledge_graph.as_deref()
     }
 
+    /// Monitor that adjusts working memory and embedding batches to RSS
+    pub fn pressure_monitor(&self, thresholds: PressureThresholds) -> MemoryPressureMonitor {
+        MemoryPressureMonitor::new((*self.working).clone(), self.knowledge_graph.clone(), thresholds)
+    }
+
+    /// Flush persistent tiers: checkpoint the session and archive WALs and snapshot the ANN index
+    pub fn checkpoint(&self) -> Result<()> {
+        if let Some(session) = &self.session {
+            session.checkpoint().context("Failed to checkpoint session memory")?;
+        }
+        if let Some(archive) = &self.archive {
+            archive.checkpoint()?;
+        }
+        if let Some(kg) = &self.knowledge_graph {
+            kg.flush_ann_index()?;
+        }
+        Ok(())
+    }
+
     /// Get reference to archive memory
     pub fn archive(&self) -> Option<&ArchiveMemory> {
         self.archive.as_deref()
@@ -0,0 +1,252 @@
+//! Memory pressure monitoring
+//!
+//! Long sessions on laptops can run out of memory: the working cache fills
+//! up, the ANN index grows with the graph and embedding batches spike RSS.
+//! The monitor samples process RSS and per-tier cache sizes, and when RSS
+//! crosses a threshold it shrinks the WorkingMemory capacity and caps embedding
+//! batch sizes. Limits are restored once RSS falls back below the
+//! threshold (with some hysteresis so they don't flap).
+
+use crate::knowledge_graph::KnowledgeGraphMemory;
+use crate::working::WorkingMemory;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+/// Share of a threshold RSS must drop below before the level is lowered
+const HYSTERESIS: f64 = 0.9;
+
+/// How hard the process is pushing against its memory budget
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PressureLevel {
+    Normal,
+    Elevated,
+    Critical,
+}
+
+/// RSS thresholds and the limits applied at each level
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PressureThresholds {
+    pub elevated_rss_bytes: u64,
+    pub critical_rss_bytes: u64,
+    /// Fraction of the baseline WorkingMemory capacity kept when elevated
+    pub elevated_working_fraction: f64,
+    pub critical_working_fraction: f64,
+    pub elevated_embedding_batch: usize,
+    pub critical_embedding_batch: usize,
+}
+
+impl Default for PressureThresholds {
+    fn default() -> Self {
+        Self {
+            elevated_rss_bytes: 4 * 1024 * 1024 * 1024,
+            critical_rss_bytes: 6 * 1024 * 1024 * 1024,
+            elevated_working_fraction: 0.5,
+            critical_working_fraction: 0.25,
+            elevated_embedding_batch: 16,
+            critical_embedding_batch: 4,
+        }
+    }
+}
+
+/// One sample of memory usage, published as an AURA vital
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryVitals {
+    pub sampled_at: DateTime<Utc>,
+    /// Resident set size (None where the platform doesn't expose it)
+    pub rss_bytes: Option<u64>,
+    pub level: PressureLevel,
+    pub working_bytes: usize,
+    pub working_capacity_bytes: usize,
+    pub working_entries: usize,
+    pub ann_index_bytes: usize,
+    pub embedding_batch_limit: Option<usize>,
+}
+
+/// Samples memory usage and applies pressure limits
+pub struct MemoryPressureMonitor {
+    working: WorkingMemory,
+    knowledge_graph: Option<Arc<KnowledgeGraphMemory>>,
+    thresholds: PressureThresholds,
+    /// Working capacity before any pressure limits were applied
+    baseline_working_bytes: usize,
+    level: parking_lot::Mutex<PressureLevel>,
+}
+
+impl MemoryPressureMonitor {
+    pub fn new(
+        working: WorkingMemory,
+        knowledge_graph: Option<Arc<KnowledgeGraphMemory>>,
+        thresholds: PressureThresholds,
+    ) -> Self {
+        let baseline_working_bytes = working.capacity_bytes();
+        Self {
+            working,
+            knowledge_graph,
+            thresholds,
+            baseline_working_bytes,
+            level: parking_lot::Mutex::new(PressureLevel::Normal),
+        }
+    }
+
+    pub fn level(&self) -> PressureLevel {
+        *self.level.lock()
+    }
+
+    /// Level for an RSS reading, given the level currently applied
+    pub fn classify(&self, rss: u64, current: PressureLevel) -> PressureLevel {
+        let t = &self.thresholds;
+        let below = |threshold: u64| (rss as f64) < threshold as f64 * HYSTERESIS;
+        if rss >= t.critical_rss_bytes {
+            PressureLevel::Critical
+        } else if rss >= t.elevated_rss_bytes {
+            if current == PressureLevel::Critical && !below(t.critical_rss_bytes) {
+                PressureLevel::Critical
+            } else {
+                PressureLevel::Elevated
+            }
+        } else if current > PressureLevel::Normal && !below(t.elevated_rss_bytes) {
+            PressureLevel::Elevated
+        } else {
+            PressureLevel::Normal
+        }
+    }
+
+    /// Apply the limits of a level to the memory tiers
+    fn apply(&self, level: PressureLevel) {
+        let t = &self.thresholds;
+        let (fraction, batch) = match level {
+            PressureLevel::Normal => (1.0, None),
+            PressureLevel::Elevated => (t.elevated_working_fraction, Some(t.elevated_embedding_batch)),
+            PressureLevel::Critical => (t.critical_working_fraction, Some(t.critical_embedding_batch)),
+        };
+
+        let capacity = (self.baseline_working_bytes as f64 * fraction) as usize;
+        if let Err(e) = self.working.set_capacity_bytes(capacity) {
+            tracing::warn!(error = %e, "Failed to resize working memory");
+        }
+        if let Some(kg) = &self.knowledge_graph {
+            kg.set_embedding_batch_limit(batch);
+        }
+    }
+
+    /// Take a sample, adjusting limits if the pressure level changed
+    pub fn sample(&self) -> MemoryVitals {
+        self.sample_with_rss(current_rss_bytes())
+    }
+
+    fn sample_with_rss(&self, rss_bytes: Option<u64>) -> MemoryVitals {
+        if let Some(rss) = rss_bytes {
+            let mut level = self.level.lock();
+            let next = self.classify(rss, *level);
+            if next != *level {
+                tracing::warn!(from = ?*level, to = ?next, rss_bytes = rss, "Memory pressure level changed");
+                self.apply(next);
+                *level = next;
+            }
+        }
+
+        let stats = self.working.stats();
+        MemoryVitals {
+            sampled_at: Utc::now(),
+            rss_bytes,
+            level: self.level(),
+            working_bytes: stats.total_size_bytes,
+            working_capacity_bytes: stats.capacity_bytes,
+            working_entries: stats.entry_count,
+            ann_index_bytes: self.knowledge_graph.as_ref().map_or(0, |kg| kg.ann_index_bytes()),
+            embedding_batch_limit: self.knowledge_graph.as_ref().and_then(|kg| kg.embedding_batch_limit()),
+        }
+    }
+
+    /// Sample on an interval until `shutdown` is cancelled, publishing every
+    /// sample on the returned channel
+    pub fn spawn(
+        self,
+        interval: Duration,
+        shutdown: zed42_core::CancellationToken,
+    ) -> (JoinHandle<()>, watch::Receiver<MemoryVitals>) {
+        let (tx, rx) = watch::channel(self.sample());
+        let handle = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            loop {
+                tokio::select! {
+                    _ = shutdown.cancelled() => break,
+                    _ = ticker.tick() => {}
+                }
+                if tx.send(self.sample()).is_err() {
+                    // Every receiver is gone
+                    break;
+                }
+            }
+        });
+        (handle, rx)
+    }
+}
+
+/// Resident set size of this process
+pub fn current_rss_bytes() -> Option<u64> {
+    let pid = sysinfo::get_current_pid().ok()?;
+    let mut system = sysinfo::System::new();
+    system.refresh_processes_specifics(
+        sysinfo::ProcessesToUpdate::Some(&[pid]),
+        true,
+        sysinfo::ProcessRefreshKind::nothing().with_memory(),
+    );
+    system.process(pid).map(|p| p.memory())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const MB: u64 = 1024 * 1024;
+
+    fn monitor(working: WorkingMemory) -> MemoryPressureMonitor {
+        MemoryPressureMonitor::new(
+            working,
+            None,
+            PressureThresholds {
+                elevated_rss_bytes: 100 * MB,
+                critical_rss_bytes: 200 * MB,
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn test_pressure_shrinks_and_restores_working_memory() {
+        let working = WorkingMemory::new();
+        working.insert("k".to_string(), json!({"v": 1}), 0.5, false).unwrap();
+        let baseline = working.capacity_bytes();
+        let monitor = monitor(working.clone());
+
+        let vitals = monitor.sample_with_rss(Some(150 * MB));
+        assert_eq!(vitals.level, PressureLevel::Elevated);
+        assert_eq!(working.capacity_bytes(), baseline / 2);
+
+        let vitals = monitor.sample_with_rss(Some(250 * MB));
+        assert_eq!(vitals.level, PressureLevel::Critical);
+        assert_eq!(vitals.working_capacity_bytes, baseline / 4);
+
+        // Hysteresis: just under the critical threshold stays critical
+        assert_eq!(monitor.sample_with_rss(Some(195 * MB)).level, PressureLevel::Critical);
+        assert_eq!(monitor.sample_with_rss(Some(95 * MB)).level, PressureLevel::Elevated);
+        assert_eq!(monitor.sample_with_rss(Some(50 * MB)).level, PressureLevel::Normal);
+        assert_eq!(working.capacity_bytes(), baseline);
+    }
+
+    #[test]
+    fn test_unknown_rss_keeps_level() {
+        let monitor = monitor(WorkingMemory::new());
+        let vitals = monitor.sample_with_rss(None);
+        assert_eq!(vitals.level, PressureLevel::Normal);
+        assert_eq!(vitals.working_entries, 0);
+    }
+}
@@ -33,6 +33,8 @@ pub enum EntryType {
     Action,
     /// Arbitrary data
     Data,
+    /// Human correction of an agent's output
+    Feedback,
 }
 
 impl std::fmt::Display for EntryType {
@@ -44,6 +46,7 @@ impl std::fmt::Display for EntryType {
             EntryType::AgentState => write!(f, "agent_state"),
             EntryType::Action => write!(f, "action"),
             EntryType::Data => write!(f, "data"),
+            EntryType::Feedback => write!(f, "feedback"),
         }
     }
 }
@@ -57,6 +60,7 @@ impl EntryType {
             "decision" => EntryType::Decision,
             "agent_state" => EntryType::AgentState,
             "action" => EntryType::Action,
+            "feedback" => EntryType::Feedback,
             _ => EntryType::Data,
         }
     }
@@ -11,11 +11,12 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::{Instant, SystemTime, UNIX_EPOCH};
 use parking_lot::RwLock;
 
-/// Maximum memory allocation in bytes (~500MB)
+/// Default memory allocation in bytes (~500MB)
 const MAX_MEMORY_BYTES: usize = 500 * 1024 * 1024;
 
 /// Estimated average entry size for capacity calculation
@@ -85,6 +86,8 @@ impl CacheEntry {
 pub struct WorkingMemory {
     cache: Arc<RwLock<HashMap<String, CacheEntry>>>,
     total_size: Arc<RwLock<usize>>,
+    /// Current capacity (lowered under memory pressure)
+    capacity: Arc<AtomicUsize>,
 }
 
 impl WorkingMemory {
@@ -93,6 +96,7 @@ impl WorkingMemory {
         Self {
             cache: Arc::new(RwLock::new(HashMap::new())),
             total_size: Arc::new(RwLock::new(0)),
+            capacity: Arc::new(AtomicUsize::new(MAX_MEMORY_BYTES)),
         }
     }
 
@@ -164,6 +168,17 @@ impl WorkingMemory {
         Ok(())
     }
 
+    /// Current capacity in bytes
+    pub fn capacity_bytes(&self) -> usize {
+        self.capacity.load(Ordering::Relaxed)
+    }
+
+    /// Change the capacity, evicting entries if the cache is now over it
+    pub fn set_capacity_bytes(&self, bytes: usize) -> anyhow::Result<()> {
+        self.capacity.store(bytes, Ordering::Relaxed);
+        self.evict_if_needed()
+    }
+
     /// Check if key exists in cache
     pub fn contains(&self, key: &str) -> bool {
         self.cache.read().contains_key(key)
@@ -173,6 +188,7 @@ impl WorkingMemory {
     pub fn stats(&self) -> CacheStats {
         let cache = self.cache.read();
         let total_size = *self.total_size.read();
+        let capacity = self.capacity_bytes().max(1);
 
         let pinned_count = cache.values().filter(|e| e.is_pinned).count();
 
@@ -180,8 +196,8 @@ impl WorkingMemory {
             entry_count: cache.len(),
             pinned_count,
             total_size_bytes: total_size,
-            capacity_bytes: MAX_MEMORY_BYTES,
-            utilization_pct: (total_size as f32 / MAX_MEMORY_BYTES as f32) * 100.0,
+            capacity_bytes: capacity,
+            utilization_pct: (total_size as f32 / capacity as f32) * 100.0,
         }
     }
 
@@ -198,15 +214,16 @@ impl WorkingMemory {
     /// Evict least valuable entries until under capacity
     fn evict_if_needed(&self) -> anyhow::Result<()> {
         let total_size = *self.total_size.read();
+        let capacity = self.capacity_bytes();
 
-        if total_size <= MAX_MEMORY_BYTES {
+        if total_size <= capacity {
             return Ok(());
         }
 
         let mut cache = self.cache.write();
         let mut size = total_size;
 
-        while size > MAX_MEMORY_BYTES {
+        while size > capacity {
              let victim = cache
                 .iter()
                 .filter(|(_, entry)| !entry.is_pinned)
@@ -297,6 +314,22 @@ mod tests {
         assert!(stats.total_size_bytes <= MAX_MEMORY_BYTES);
     }
 
+    #[test]
+    fn test_shrinking_capacity_evicts() {
+        let memory = WorkingMemory::new();
+
+        memory.insert("pinned".to_string(), json!({"pin": true}), 1.0, true).unwrap();
+        for i in 0..100 {
+            memory.insert(format!("key{}", i), json!({"data": vec!["x"; 100]}), 0.5, false).unwrap();
+        }
+
+        memory.set_capacity_bytes(4096).unwrap();
+        let stats = memory.stats();
+        assert_eq!(stats.capacity_bytes, 4096);
+        assert!(stats.total_size_bytes <= 4096);
+        assert!(memory.contains("pinned"));
+    }
+
     #[test]
     fn test_access_updates_recency() {
         let memory = WorkingMemory::new();
@@ -0,0 +1,294 @@
+//! Routing log queries and aggregations
+//!
+//! The Router writes a RoutingLog for every routed call and every failed tier.
+//! This module provides the read side: filtered queries plus aggregate
+//! statistics (failover rate per model, mean retries, cost per tier,
+//! speculative drafting savings). [`RoutingHistory`] runs the queries
+//! straight on the MOM database, so reading history needs no LLM client.
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use surrealdb::engine::any::Any;
+use surrealdb::Surreal;
+
+use crate::recommend::{self, CritiqueOutcome, ModelRecommendation, RecommendationPolicy};
+use crate::types::RoutingLog;
+
+/// Filter for routing log queries
+#[derive(Debug, Clone, Default)]
+pub struct RoutingLogQuery {
+    pub agent_id: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    /// Substring matched against `failover_reason`
+    pub failover_reason: Option<String>,
+    /// Only logs that recorded a failover
+    pub failovers_only: bool,
+    /// Only calls served by an arm of this experiment
+    pub experiment: Option<String>,
+    pub limit: Option<usize>,
+}
+
+impl RoutingLogQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn agent(mut self, agent_id: impl Into<String>) -> Self {
+        self.agent_id = Some(agent_id.into());
+        self
+    }
+
+    pub fn between(mut self, since: DateTime<Utc>, until: DateTime<Utc>) -> Self {
+        self.since = Some(since);
+        self.until = Some(until);
+        self
+    }
+
+    pub fn since(mut self, since: DateTime<Utc>) -> Self {
+        self.since = Some(since);
+        self
+    }
+
+    pub fn failover_reason(mut self, reason: impl Into<String>) -> Self {
+        self.failover_reason = Some(reason.into());
+        self
+    }
+
+    pub fn failovers_only(mut self) -> Self {
+        self.failovers_only = true;
+        self
+    }
+
+    pub fn experiment(mut self, experiment_id: impl Into<String>) -> Self {
+        self.experiment = Some(experiment_id.into());
+        self
+    }
+
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// SurrealQL conditions for the filters, `None` when there are none
+    ///
+    /// Values are referenced as parameters (`$agent`, `$since`, `$until`,
+    /// `$reason`, `$experiment`) that the caller binds.
+    pub(crate) fn where_clause(&self) -> Option<String> {
+        let mut conditions = Vec::new();
+        if self.agent_id.is_some() {
+            conditions.push("agent_id = $agent");
+        }
+        if self.since.is_some() {
+            conditions.push("timestamp >= $since");
+        }
+        if self.until.is_some() {
+            conditions.push("timestamp <= $until");
+        }
+        if self.failovers_only {
+            conditions.push("(failover_reason != NONE AND failover_reason != NULL)");
+        }
+        if self.failover_reason.is_some() {
+            conditions.push("string::contains(failover_reason ?? '', $reason)");
+        }
+        if self.experiment.is_some() {
+            conditions.push("variant.experiment = $experiment");
+        }
+        (!conditions.is_empty()).then(|| conditions.join(" AND "))
+    }
+}
+
+/// Routing logs and critique outcomes stored in the MOM database
+#[derive(Clone)]
+pub struct RoutingHistory {
+    db: Surreal<Any>,
+}
+
+impl RoutingHistory {
+    pub fn new(db: Surreal<Any>) -> Self {
+        Self { db }
+    }
+
+    /// Routing logs matching `query`, oldest first
+    pub async fn routing_logs(&self, query: &RoutingLogQuery) -> anyhow::Result<Vec<RoutingLog>> {
+        let mut sql = "SELECT *, meta::id(id) AS id FROM routing_logs".to_string();
+        if let Some(conditions) = query.where_clause() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&conditions);
+        }
+        sql.push_str(" ORDER BY timestamp ASC");
+        if query.limit.is_some() {
+            sql.push_str(" LIMIT $limit");
+        }
+
+        let mut response = self
+            .db
+            .query(sql)
+            .bind(("agent", query.agent_id.clone()))
+            .bind(("since", query.since))
+            .bind(("until", query.until))
+            .bind(("reason", query.failover_reason.clone()))
+            .bind(("experiment", query.experiment.clone()))
+            .bind(("limit", query.limit.unwrap_or_default()))
+            .await?;
+        Ok(response.take(0)?)
+    }
+
+    /// Critique outcomes since `since` (all when `None`), oldest first
+    pub async fn critique_outcomes(&self, since: Option<DateTime<Utc>>) -> anyhow::Result<Vec<CritiqueOutcome>> {
+        let sql = match since {
+            Some(_) => "SELECT * FROM critique_outcomes WHERE timestamp >= $since ORDER BY timestamp ASC",
+            None => "SELECT * FROM critique_outcomes ORDER BY timestamp ASC",
+        };
+        let mut response = self.db.query(sql).bind(("since", since)).await?;
+        Ok(response.take(0)?)
+    }
+
+    /// Profile changes suggested by the routing logs and critiques since `since`
+    pub async fn model_recommendations(
+        &self,
+        since: Option<DateTime<Utc>>,
+        policy: &RecommendationPolicy,
+    ) -> anyhow::Result<Vec<ModelRecommendation>> {
+        let query = RoutingLogQuery { since, ..Default::default() };
+        let logs = self.routing_logs(&query).await?;
+        let critiques = self.critique_outcomes(since).await?;
+        Ok(recommend::recommend(&logs, &critiques, policy))
+    }
+}
+
+/// Failover statistics for one model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelFailoverStats {
+    pub model: String,
+    pub calls: usize,
+    pub failovers: usize,
+    pub failover_rate: f64,
+}
+
+/// Aggregate view over a set of routing logs
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RoutingAnalytics {
+    pub total_logs: usize,
+    pub critical_failures: usize,
+    pub mean_retries: f64,
+    pub failover_by_model: Vec<ModelFailoverStats>,
+    pub cost_by_tier: BTreeMap<u8, Decimal>,
+    pub total_cost: Decimal,
+    /// Speculative runs and their summed savings versus direct Tier 2 generation
+    #[serde(default)]
+    pub speculative_runs: usize,
+    #[serde(default)]
+    pub speculative_savings: Decimal,
+}
+
+impl RoutingAnalytics {
+    /// Aggregate a set of logs
+    pub fn from_logs(logs: &[RoutingLog]) -> Self {
+        let mut per_model: BTreeMap<&str, (usize, usize)> = BTreeMap::new();
+        let mut cost_by_tier: BTreeMap<u8, Decimal> = BTreeMap::new();
+        let mut retries = 0u64;
+
+        for log in logs {
+            retries += u64::from(log.retry_count);
+
+            if !log.is_synthetic() {
+                let entry = per_model.entry(log.selected_model.as_str()).or_default();
+                entry.0 += 1;
+                if log.failover_reason.is_some() {
+                    entry.1 += 1;
+                }
+            }
+
+            if let Some(cost) = log.cost {
+                *cost_by_tier.entry(log.selected_tier).or_default() += cost;
+            }
+        }
+
+        let failover_by_model = per_model
+            .into_iter()
+            .map(|(model, (calls, failovers))| ModelFailoverStats {
+                model: model.to_string(),
+                calls,
+                failovers,
+                failover_rate: failovers as f64 / calls as f64,
+            })
+            .collect();
+
+        Self {
+            total_logs: logs.len(),
+            critical_failures: logs.iter().filter(|l| l.is_critical).count(),
+            mean_retries: if logs.is_empty() { 0.0 } else { retries as f64 / logs.len() as f64 },
+            failover_by_model,
+            total_cost: cost_by_tier.values().copied().sum(),
+            cost_by_tier,
+            speculative_runs: logs.iter().filter(|l| l.speculation.is_some()).count(),
+            speculative_savings: logs
+                .iter()
+                .filter_map(|l| l.speculation.as_ref().and_then(|s| s.savings))
+                .sum(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn log(agent: &str, tier: u8, model: &str, retries: u8, failover: Option<&str>, cost: Option<Decimal>) -> RoutingLog {
+        RoutingLog {
+            id: None,
+            timestamp: Utc::now(),
+            agent_id: agent.to_string(),
+            task_id: None,
+            original_prompt_len: 10,
+            selected_tier: tier,
+            selected_model: model.to_string(),
+            retry_count: retries,
+            failover_reason: failover.map(str::to_string),
+            cost,
+            is_critical: false,
+            speculation: None,
+            variant: None,
+            throttle: None,
+        }
+    }
+
+    #[test]
+    fn test_analytics_aggregation() {
+        let logs = vec![
+            log("a", 1, "cheap", 2, Some("Tier 1 failed: rate limit"), None),
+            log("a", 2, "mid", 0, None, Some(dec!(0.05))),
+            log("b", 1, "cheap", 0, None, Some(dec!(0.01))),
+            log("b", 1, "cheap", 1, None, Some(dec!(0.01))),
+        ];
+
+        let analytics = RoutingAnalytics::from_logs(&logs);
+        assert_eq!(analytics.total_logs, 4);
+        assert_eq!(analytics.mean_retries, 0.75);
+        assert_eq!(analytics.cost_by_tier[&1], dec!(0.02));
+        assert_eq!(analytics.cost_by_tier[&2], dec!(0.05));
+        assert_eq!(analytics.total_cost, dec!(0.07));
+
+        let cheap = analytics.failover_by_model.iter().find(|m| m.model == "cheap").unwrap();
+        assert_eq!(cheap.calls, 3);
+        assert_eq!(cheap.failovers, 1);
+    }
+
+    #[test]
+    fn test_query_filters() {
+        assert_eq!(RoutingLogQuery::new().limit(5).where_clause(), None);
+
+        let query = RoutingLogQuery::new().failover_reason("rate limit");
+        assert_eq!(query.where_clause().unwrap(), "string::contains(failover_reason ?? '', $reason)");
+
+        let query = RoutingLogQuery::new().agent("b").since(Utc::now() - chrono::Duration::minutes(5)).failovers_only();
+        assert_eq!(
+            query.where_clause().unwrap(),
+            "agent_id = $agent AND timestamp >= $since AND (failover_reason != NONE AND failover_reason != NULL)"
+        );
+    }
+}
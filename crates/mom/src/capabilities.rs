@@ -0,0 +1,204 @@
+//! Provider capability registry
+//!
+//! Tier selection consults the registry so a request is never sent to a model
+//! that cannot serve it (context window too small, no JSON-schema mode, no
+//! vision). Models missing from the registry are assumed capable.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use zed42_llm::LlmRequest;
+
+/// What a model can do
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelCapabilities {
+    pub model: String,
+    /// Total context window in tokens (prompt + completion)
+    pub context_window: usize,
+    #[serde(default)]
+    pub max_output_tokens: Option<usize>,
+    #[serde(default = "default_true")]
+    pub supports_json_schema: bool,
+    #[serde(default)]
+    pub supports_vision: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl ModelCapabilities {
+    pub fn new(model: &str, context_window: usize) -> Self {
+        Self {
+            model: model.to_string(),
+            context_window,
+            max_output_tokens: None,
+            supports_json_schema: true,
+            supports_vision: false,
+        }
+    }
+
+    pub fn with_max_output_tokens(mut self, tokens: usize) -> Self {
+        self.max_output_tokens = Some(tokens);
+        self
+    }
+
+    pub fn with_json_schema(mut self, supported: bool) -> Self {
+        self.supports_json_schema = supported;
+        self
+    }
+
+    pub fn with_vision(mut self, supported: bool) -> Self {
+        self.supports_vision = supported;
+        self
+    }
+
+    /// Reason this model cannot serve `req`, if any
+    pub fn unsatisfied(&self, req: &RequestRequirements) -> Option<String> {
+        let output_budget = match (req.max_output_tokens, self.max_output_tokens) {
+            (Some(requested), Some(limit)) if requested > limit => {
+                return Some(format!(
+                    "requested {} output tokens but {} allows {}",
+                    requested, self.model, limit
+                ));
+            }
+            (Some(requested), _) => requested,
+            (None, _) => 0,
+        };
+
+        let needed = req.estimated_prompt_tokens + output_budget;
+        if needed > self.context_window {
+            return Some(format!(
+                "needs ~{} tokens but {} has a {}-token context window",
+                needed, self.model, self.context_window
+            ));
+        }
+        if req.needs_json_schema && !self.supports_json_schema {
+            return Some(format!("{} does not support JSON schema output", self.model));
+        }
+        if req.needs_vision && !self.supports_vision {
+            return Some(format!("{} does not accept image input", self.model));
+        }
+        None
+    }
+}
+
+/// What a request needs from a model
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RequestRequirements {
+    pub estimated_prompt_tokens: usize,
+    pub max_output_tokens: Option<usize>,
+    pub needs_json_schema: bool,
+    pub needs_vision: bool,
+}
+
+impl RequestRequirements {
+    /// Derive requirements from a request (output budget taken from `max_tokens`)
+    pub fn from_request(request: &LlmRequest) -> Self {
+        let system = request.system_prompt.as_deref().unwrap_or("");
+        Self {
+            estimated_prompt_tokens: estimate_tokens(&request.rendered_prompt())
+                + estimate_tokens(system),
+            max_output_tokens: request.config.max_tokens,
+            needs_json_schema: request.json_schema.is_some(),
+            needs_vision: !request.images.is_empty(),
+        }
+    }
+}
+
+/// Rough token estimate (~4 characters per token)
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+/// Registry of known model capabilities
+#[derive(Debug, Clone, Default)]
+pub struct CapabilityRegistry {
+    models: HashMap<String, ModelCapabilities>,
+}
+
+#[derive(Deserialize)]
+struct CapabilityFile {
+    #[serde(default)]
+    capabilities: Vec<ModelCapabilities>,
+}
+
+impl CapabilityRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load `[[capabilities]]` entries from a TOML document (e.g. `config/providers.toml`)
+    pub fn from_toml_str(content: &str) -> anyhow::Result<Self> {
+        let file: CapabilityFile = toml::from_str(content)?;
+        let mut registry = Self::new();
+        for caps in file.capabilities {
+            registry.register(caps);
+        }
+        Ok(registry)
+    }
+
+    pub fn register(&mut self, capabilities: ModelCapabilities) {
+        self.models.insert(capabilities.model.clone(), capabilities);
+    }
+
+    pub fn get(&self, model: &str) -> Option<&ModelCapabilities> {
+        self.models.get(model)
+    }
+
+    /// Check whether `model` can serve a request; unknown models pass
+    pub fn check(&self, model: &str, req: &RequestRequirements) -> Result<(), String> {
+        match self.get(model).and_then(|caps| caps.unsatisfied(req)) {
+            Some(reason) => Err(reason),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zed42_llm::ModelConfig;
+
+    #[test]
+    fn test_context_window_and_features() {
+        let mut registry = CapabilityRegistry::new();
+        registry.register(ModelCapabilities::new("small", 8_000).with_json_schema(false));
+        registry.register(ModelCapabilities::new("large", 200_000).with_vision(true));
+
+        let long_prompt = "x".repeat(100_000); // ~25k tokens
+        let request = LlmRequest::new(long_prompt).config(ModelConfig::default().max_tokens(1024));
+        let req = RequestRequirements::from_request(&request);
+
+        assert!(registry.check("small", &req).unwrap_err().contains("context window"));
+        assert!(registry.check("large", &req).is_ok());
+        assert!(registry.check("unknown-model", &req).is_ok());
+
+        let request = LlmRequest::new("hi".to_string())
+            .schema(serde_json::json!({"type": "object"}))
+            .image("https://example.com/a.png".to_string());
+        let req = RequestRequirements::from_request(&request);
+        assert!(registry.check("small", &req).unwrap_err().contains("JSON schema"));
+        assert!(registry.check("large", &req).is_ok());
+    }
+
+    #[test]
+    fn test_from_toml() {
+        let registry = CapabilityRegistry::from_toml_str(
+            r#"
+[development]
+provider = "openrouter"
+
+[[capabilities]]
+model = "deepseek/deepseek-coder-6.7b-instruct"
+context_window = 16384
+supports_json_schema = false
+"#,
+        )
+        .unwrap();
+
+        let caps = registry.get("deepseek/deepseek-coder-6.7b-instruct").unwrap();
+        assert_eq!(caps.context_window, 16384);
+        assert!(!caps.supports_json_schema);
+        assert!(!caps.supports_vision);
+    }
+}
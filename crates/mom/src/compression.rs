@@ -0,0 +1,194 @@
+//! Prompt compression for oversized requests
+//!
+//! When a request would overflow the selected model's context window, the
+//! Router compresses it instead of letting the provider reject it. Stages run
+//! in order and stop as soon as the request fits:
+//!
+//! 1. Drop memory segments, lowest relevance first
+//! 2. Summarize long tool output (keep head and tail, mark the omission)
+//! 3. Truncate the remaining prompt with a marker
+//!
+//! Segments marked cacheable are left alone by the first two stages so
+//! routine compression doesn't invalidate a provider's prompt cache.
+
+use serde::{Deserialize, Serialize};
+use zed42_llm::{ContextKind, LlmRequest};
+
+use crate::capabilities::estimate_tokens;
+
+/// Outcome of a compression pass
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompressionReport {
+    pub original_tokens: usize,
+    pub compressed_tokens: usize,
+    pub dropped_segments: usize,
+    pub summarized_segments: usize,
+    pub truncated: bool,
+}
+
+impl CompressionReport {
+    pub fn was_compressed(&self) -> bool {
+        self.dropped_segments > 0 || self.summarized_segments > 0 || self.truncated
+    }
+}
+
+/// Shrinks requests to fit a token budget
+#[derive(Debug, Clone)]
+pub struct PromptCompressor {
+    /// Tool output longer than this (in tokens) is summarized
+    tool_output_limit: usize,
+}
+
+impl Default for PromptCompressor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PromptCompressor {
+    pub fn new() -> Self {
+        Self { tool_output_limit: 500 }
+    }
+
+    pub fn with_tool_output_limit(mut self, tokens: usize) -> Self {
+        self.tool_output_limit = tokens;
+        self
+    }
+
+    /// Compress `request` in place until its prompt fits in `budget` tokens
+    pub fn compress(&self, request: &mut LlmRequest, budget: usize) -> CompressionReport {
+        let mut report = CompressionReport {
+            original_tokens: prompt_tokens(request),
+            ..Default::default()
+        };
+
+        // Stage 1: drop memory results, least relevant first
+        while prompt_tokens(request) > budget {
+            let weakest = request
+                .segments
+                .iter()
+                .enumerate()
+                .filter(|(_, s)| s.kind == ContextKind::Memory && s.cache.is_none())
+                .min_by(|(_, a), (_, b)| a.relevance.total_cmp(&b.relevance))
+                .map(|(i, _)| i);
+            let Some(index) = weakest else { break };
+            request.segments.remove(index);
+            report.dropped_segments += 1;
+        }
+
+        // Stage 2: summarize tool output, largest first
+        let mut tool_outputs: Vec<usize> = request
+            .segments
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| s.kind == ContextKind::ToolOutput && s.cache.is_none())
+            .map(|(i, _)| i)
+            .collect();
+        tool_outputs.sort_by_key(|&i| std::cmp::Reverse(request.segments[i].content.len()));
+        for index in tool_outputs {
+            if prompt_tokens(request) <= budget {
+                break;
+            }
+            let segment = &mut request.segments[index];
+            if estimate_tokens(&segment.content) > self.tool_output_limit {
+                segment.content = elide(&segment.content, self.tool_output_limit * 4, "tool output");
+                report.summarized_segments += 1;
+            }
+        }
+
+        // Stage 3: flatten and truncate whatever is left
+        if prompt_tokens(request) > budget {
+            let system_tokens = request.system_prompt.as_deref().map(estimate_tokens).unwrap_or(0);
+            let keep_chars = budget.saturating_sub(system_tokens) * 4;
+            request.prompt = elide(&request.rendered_prompt(), keep_chars, "prompt");
+            request.segments.clear();
+            report.truncated = true;
+        }
+
+        report.compressed_tokens = prompt_tokens(request);
+        report
+    }
+}
+
+/// Estimated prompt tokens for a request (system prompt included)
+pub fn prompt_tokens(request: &LlmRequest) -> usize {
+    let system = request.system_prompt.as_deref().map(estimate_tokens).unwrap_or(0);
+    estimate_tokens(&request.rendered_prompt()) + system
+}
+
+/// Keep the head and tail of `text` within `max_chars`, marking the gap
+fn elide(text: &str, max_chars: usize, what: &str) -> String {
+    let total = text.chars().count();
+    if total <= max_chars {
+        return text.to_string();
+    }
+
+    // Reserve room for the marker itself (its digit count is at most total's)
+    let marker_len = format!("\n[... {} characters of {} omitted ...]\n", total, what).chars().count();
+    let keep = max_chars.saturating_sub(marker_len);
+    let head = keep * 2 / 3;
+    let tail = keep - head;
+    let omitted = total - head - tail;
+
+    let head_text: String = text.chars().take(head).collect();
+    let tail_text: String = text.chars().skip(total - tail).collect();
+    format!("{}\n[... {} characters of {} omitted ...]\n{}", head_text, omitted, what, tail_text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zed42_llm::ContextSegment;
+
+    fn request() -> LlmRequest {
+        LlmRequest::new("Fix the failing test.".to_string())
+            .segment(ContextSegment::new(ContextKind::Memory, "old note", "a".repeat(4_000)).relevance(0.2))
+            .segment(ContextSegment::new(ContextKind::Memory, "key fact", "b".repeat(400)).relevance(0.9))
+            .segment(ContextSegment::new(ContextKind::ToolOutput, "cargo test", "c".repeat(8_000)))
+    }
+
+    #[test]
+    fn test_drops_low_relevance_memory_first() {
+        let mut req = request();
+        let budget = prompt_tokens(&req) - 500;
+        let report = PromptCompressor::new().compress(&mut req, budget);
+
+        assert_eq!(report.dropped_segments, 1);
+        assert_eq!(report.summarized_segments, 0);
+        assert!(!report.truncated);
+        assert!(req.segments.iter().any(|s| s.label == "key fact"));
+        assert!(!req.segments.iter().any(|s| s.label == "old note"));
+    }
+
+    #[test]
+    fn test_cacheable_segments_survive_dropping() {
+        let mut req = LlmRequest::new("Fix the failing test.".to_string())
+            .segment(ContextSegment::new(ContextKind::Memory, "style guide", "a".repeat(4_000)).relevance(0.1).cacheable())
+            .segment(ContextSegment::new(ContextKind::Memory, "old note", "b".repeat(4_000)).relevance(0.5));
+        let budget = prompt_tokens(&req) - 500;
+        let report = PromptCompressor::new().compress(&mut req, budget);
+
+        assert_eq!(report.dropped_segments, 1);
+        assert_eq!(req.segments.len(), 1);
+        assert_eq!(req.segments[0].label, "style guide");
+    }
+
+    #[test]
+    fn test_summarizes_tool_output_then_truncates() {
+        let mut req = request();
+        let report = PromptCompressor::new().compress(&mut req, 800);
+        assert_eq!(report.dropped_segments, 2);
+        assert_eq!(report.summarized_segments, 1);
+        assert!(!report.truncated);
+        assert!(req.segments[0].content.contains("characters of tool output omitted"));
+        assert!(report.compressed_tokens <= 800);
+
+        let mut req = request();
+        let report = PromptCompressor::new().compress(&mut req, 100);
+        assert!(report.truncated);
+        assert!(req.segments.is_empty());
+        assert!(req.prompt.starts_with("Fix the failing test."));
+        assert!(req.prompt.contains("characters of prompt omitted"));
+        assert!(report.compressed_tokens <= 100);
+    }
+}
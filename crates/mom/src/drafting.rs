@@ -0,0 +1,175 @@
+//! Speculative drafting
+//!
+//! A cheap model (Tier 0 or 1) drafts the artifact; a Tier 2+ model then
+//! reviews the draft instead of generating from scratch. The verifier
+//! answers `APPROVE`, a set of search/replace edits, or (when the draft is
+//! beyond repair) `REWRITE` followed by the full artifact, so in the common
+//! case its output is a few tokens instead of the whole artifact.
+//!
+//! Requests opt in with `LlmRequest::speculative`. The Router records each
+//! run as a `SPECULATIVE` routing log whose [`SpeculationRecord`] compares
+//! what the draft and review cost against the verifier generating the
+//! draft's output itself.
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use zed42_llm::LlmRequest;
+
+const SEARCH: &str = "<<<<<<< SEARCH";
+const DIVIDER: &str = "=======";
+const REPLACE: &str = ">>>>>>> REPLACE";
+
+/// How the verifier is asked to review
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DraftingPolicy {
+    /// Instructions prepended to the verifier's system prompt
+    pub instructions: String,
+}
+
+impl Default for DraftingPolicy {
+    fn default() -> Self {
+        Self {
+            instructions: format!(
+                "You are reviewing a draft written by a faster model for the task below. \
+                 If the draft fully and correctly completes the task, reply with exactly APPROVE. \
+                 If it needs changes, reply only with edit blocks, each of the form\n\
+                 {SEARCH}\n<exact text from the draft>\n{DIVIDER}\n<replacement>\n{REPLACE}\n\
+                 If the draft is unusable, reply with REWRITE on its own line followed by the complete artifact."
+            ),
+        }
+    }
+}
+
+/// What the verifier did with the draft
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "verdict", rename_all = "snake_case")]
+pub enum DraftVerdict {
+    Approved,
+    Edited { edits: usize },
+    /// The verifier wrote the artifact itself
+    Rewritten,
+    /// The verifier's edits didn't apply; the request was regenerated directly
+    Rejected { reason: String },
+}
+
+/// Cost comparison for one speculative run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpeculationRecord {
+    pub draft_model: String,
+    pub verifier_model: String,
+    pub verdict: DraftVerdict,
+    pub draft_cost: Decimal,
+    /// Review plus any direct regeneration after a rejected draft
+    pub verify_cost: Decimal,
+    /// Verifier rates applied to the draft's token usage; `None` without a rate entry
+    pub direct_estimate: Option<Decimal>,
+    /// `direct_estimate` minus what was spent; negative when drafting cost more
+    pub savings: Option<Decimal>,
+}
+
+impl SpeculationRecord {
+    pub fn spent(&self) -> Decimal {
+        self.draft_cost + self.verify_cost
+    }
+}
+
+/// Review request for `draft`, built from the original request
+pub fn verifier_request(request: &LlmRequest, draft: &str, policy: &DraftingPolicy) -> LlmRequest {
+    let mut review = request.clone();
+    review.system_prompt = Some(match &request.system_prompt {
+        Some(system) => format!("{}\n\n[Original instructions]\n{}", policy.instructions, system),
+        None => policy.instructions.clone(),
+    });
+    review.prompt = format!("[Task]\n{}\n\n[Draft]\n{}", request.rendered_prompt(), draft);
+    review.segments.clear();
+    // The review speaks its own format; the caller validates the final artifact
+    review.json_schema = None;
+    review.stop_sequences.clear();
+    review.retry_cause = None;
+    review.speculative = false;
+    review
+}
+
+/// Apply the verifier's reply to the draft
+///
+/// Returns the final artifact, or an error describing why the reply
+/// couldn't be applied (an edit whose search text isn't in the draft).
+pub fn apply_review(draft: &str, review: &str) -> Result<(String, DraftVerdict), String> {
+    let trimmed = review.trim();
+    if trimmed == "APPROVE" || trimmed.starts_with("APPROVE\n") {
+        return Ok((draft.to_string(), DraftVerdict::Approved));
+    }
+    if let Some(rest) = trimmed.strip_prefix("REWRITE") {
+        return Ok((rest.trim_start_matches([' ', '\r', '\n']).to_string(), DraftVerdict::Rewritten));
+    }
+    if !trimmed.contains(SEARCH) {
+        // No recognizable protocol: the verifier answered in full
+        return Ok((review.to_string(), DraftVerdict::Rewritten));
+    }
+
+    let mut artifact = draft.to_string();
+    let mut edits = 0;
+    let mut rest = trimmed;
+    while let Some(start) = rest.find(SEARCH) {
+        let block = &rest[start + SEARCH.len()..];
+        let divider = block.find(DIVIDER).ok_or("edit block without divider")?;
+        let end = block.find(REPLACE).ok_or("edit block without end marker")?;
+        if end < divider {
+            return Err("edit block markers out of order".to_string());
+        }
+        let search = strip_newlines(&block[..divider]);
+        let replacement = strip_newlines(&block[divider + DIVIDER.len()..end]);
+        if search.is_empty() || !artifact.contains(search) {
+            return Err(format!("edit {} search text not found in draft", edits + 1));
+        }
+        artifact = artifact.replacen(search, replacement, 1);
+        edits += 1;
+        rest = &block[end + REPLACE.len()..];
+    }
+    Ok((artifact, DraftVerdict::Edited { edits }))
+}
+
+/// Drop the single newline each marker line leaves on either side
+fn strip_newlines(text: &str) -> &str {
+    let text = text.strip_prefix("\r\n").or_else(|| text.strip_prefix('\n')).unwrap_or(text);
+    text.strip_suffix("\r\n").or_else(|| text.strip_suffix('\n')).unwrap_or(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DRAFT: &str = "fn add(a: i32, b: i32) -> i32 {\n    a - b\n}\n";
+
+    #[test]
+    fn test_approve_and_rewrite() {
+        assert_eq!(apply_review(DRAFT, " APPROVE\n").unwrap(), (DRAFT.to_string(), DraftVerdict::Approved));
+        let (artifact, verdict) = apply_review(DRAFT, "REWRITE\nfn add() {}").unwrap();
+        assert_eq!(artifact, "fn add() {}");
+        assert_eq!(verdict, DraftVerdict::Rewritten);
+    }
+
+    #[test]
+    fn test_edit_blocks_apply_in_order() {
+        let review = format!("{SEARCH}\n    a - b\n{DIVIDER}\n    a + b\n{REPLACE}\n{SEARCH}\nfn add\n{DIVIDER}\npub fn add\n{REPLACE}");
+        let (artifact, verdict) = apply_review(DRAFT, &review).unwrap();
+        assert_eq!(artifact, "pub fn add(a: i32, b: i32) -> i32 {\n    a + b\n}\n");
+        assert_eq!(verdict, DraftVerdict::Edited { edits: 2 });
+
+        let missing = format!("{SEARCH}\na * b\n{DIVIDER}\na + b\n{REPLACE}");
+        assert!(apply_review(DRAFT, &missing).unwrap_err().contains("not found"));
+    }
+
+    #[test]
+    fn test_verifier_request_keeps_routing_fields() {
+        let request = LlmRequest::new("Write add".to_string())
+            .agent("coder".to_string())
+            .schema(serde_json::json!({"type": "object"}))
+            .speculative();
+        let review = verifier_request(&request, DRAFT, &DraftingPolicy::default());
+        assert_eq!(review.agent_id.as_deref(), Some("coder"));
+        assert!(review.json_schema.is_none());
+        assert!(!review.speculative);
+        assert!(review.prompt.contains("[Draft]\nfn add"));
+    }
+}
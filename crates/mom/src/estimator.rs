@@ -0,0 +1,110 @@
+//! Learned lease estimates
+//!
+//! Without history the Router leases a fixed amount per tier ($0.01, $0.05,
+//! $0.20), which can be many times what a call costs. Reserved but unspent
+//! budget still counts against the hard limit, so over-reserving blocks
+//! concurrent agents for nothing. The [`LeaseEstimator`] learns what calls
+//! actually settle at per entity and model and leases a high percentile of
+//! that instead, with some headroom.
+
+use std::collections::VecDeque;
+
+use dashmap::DashMap;
+use rust_decimal::Decimal;
+use zed42_ledger::variance::VarianceRecord;
+
+/// How estimates are learned
+#[derive(Debug, Clone)]
+pub struct EstimatorPolicy {
+    /// Recent settlements kept per entity and model
+    pub window: usize,
+    /// Settlements needed before the learned estimate replaces the default
+    pub min_samples: usize,
+    /// Percentile of recent settled costs to lease (0.0 to 1.0)
+    pub percentile: f64,
+    /// Multiplier on the percentile, so typical calls don't outgrow their lease
+    pub headroom: Decimal,
+    /// Smallest lease ever requested
+    pub floor: Decimal,
+}
+
+impl Default for EstimatorPolicy {
+    fn default() -> Self {
+        Self {
+            window: 50,
+            min_samples: 5,
+            percentile: 0.95,
+            headroom: Decimal::new(125, 2),
+            floor: Decimal::new(1, 4),
+        }
+    }
+}
+
+/// Settled costs by billing entity and model
+#[derive(Default)]
+pub struct LeaseEstimator {
+    policy: EstimatorPolicy,
+    samples: DashMap<(String, String), VecDeque<Decimal>>,
+}
+
+impl LeaseEstimator {
+    pub fn new(policy: EstimatorPolicy) -> Self {
+        Self { policy, samples: DashMap::new() }
+    }
+
+    /// Learn from past settlements, e.g. `IntelligenceLedger::variance_records`
+    pub fn seed(&self, records: &[VarianceRecord]) {
+        for record in records {
+            self.observe(&record.entity_id, &record.model, record.actual);
+        }
+    }
+
+    pub fn observe(&self, entity_id: &str, model: &str, actual: Decimal) {
+        let mut window = self.samples.entry((entity_id.to_string(), model.to_string())).or_default();
+        window.push_back(actual);
+        while window.len() > self.policy.window {
+            window.pop_front();
+        }
+    }
+
+    /// What to lease for a call, or `default` until enough settlements are known
+    pub fn estimate(&self, entity_id: &str, model: &str, default: Decimal) -> Decimal {
+        let Some(window) = self.samples.get(&(entity_id.to_string(), model.to_string())) else {
+            return default;
+        };
+        if window.len() < self.policy.min_samples.max(1) {
+            return default;
+        }
+        let mut costs: Vec<Decimal> = window.iter().copied().collect();
+        costs.sort();
+        let rank = ((costs.len() as f64) * self.policy.percentile).ceil() as usize;
+        let percentile = costs[rank.clamp(1, costs.len()) - 1];
+        (percentile * self.policy.headroom).max(self.policy.floor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_estimate_tracks_settled_costs() {
+        let estimator = LeaseEstimator::new(EstimatorPolicy { window: 4, min_samples: 3, ..Default::default() });
+        assert_eq!(estimator.estimate("coder", "gpt-4o", dec!(0.05)), dec!(0.05));
+
+        for cost in [dec!(0.004), dec!(0.002), dec!(0.008)] {
+            estimator.observe("coder", "gpt-4o", cost);
+        }
+        assert_eq!(estimator.estimate("coder", "gpt-4o", dec!(0.05)), dec!(0.01));
+        assert_eq!(estimator.estimate("coder", "gpt-4o-mini", dec!(0.01)), dec!(0.01));
+
+        // The oldest settlement slides out of the window
+        estimator.observe("coder", "gpt-4o", dec!(0.001));
+        estimator.observe("coder", "gpt-4o", dec!(0.001));
+        assert_eq!(estimator.estimate("coder", "gpt-4o", dec!(0.05)), dec!(0.01));
+        estimator.observe("coder", "gpt-4o", dec!(0.001));
+        estimator.observe("coder", "gpt-4o", dec!(0.001));
+        assert_eq!(estimator.estimate("coder", "gpt-4o", dec!(0.05)), dec!(0.00125));
+    }
+}
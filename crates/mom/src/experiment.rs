@@ -0,0 +1,410 @@
+//! A/B experiments on prompts and execution profiles
+//!
+//! An [`Experiment`] splits one agent's calls between a control and a
+//! treatment [`Variant`], each of which may replace the system prompt, the
+//! execution profile, or both. Assignment hashes the task id, so every call
+//! of a task lands in the same variant and the task's evaluation can be
+//! tagged with it (key: the experiment id, value: the variant name). Routing
+//! logs carry a [`VariantTag`].
+//!
+//! [`ExperimentReport`] compares the variants on evaluation score and cost
+//! per task with Welch's t-test.
+
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::types::{ExecutionProfile, RoutingLog};
+
+/// p-value below which a difference is reported as significant
+pub const DEFAULT_ALPHA: f64 = 0.05;
+
+/// One arm of an experiment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Variant {
+    pub name: String,
+    /// Replaces the request's system prompt
+    #[serde(default)]
+    pub system_prompt: Option<String>,
+    /// Replaces the agent's stored execution profile
+    #[serde(default)]
+    pub profile: Option<ExecutionProfile>,
+}
+
+impl Variant {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            system_prompt: None,
+            profile: None,
+        }
+    }
+
+    pub fn with_system_prompt(mut self, system_prompt: impl Into<String>) -> Self {
+        self.system_prompt = Some(system_prompt.into());
+        self
+    }
+
+    pub fn with_profile(mut self, profile: ExecutionProfile) -> Self {
+        self.profile = Some(profile);
+        self
+    }
+}
+
+/// Which experiment arm served a call
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VariantTag {
+    pub experiment: String,
+    pub variant: String,
+}
+
+/// A traffic split between two variants for one agent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Experiment {
+    pub id: String,
+    /// Agent whose requests are split (`LlmRequest::agent_id`)
+    pub agent_id: String,
+    pub control: Variant,
+    pub treatment: Variant,
+    /// Fraction of tasks sent to the treatment
+    pub treatment_share: f64,
+}
+
+impl Experiment {
+    pub fn new(id: impl Into<String>, agent_id: impl Into<String>, control: Variant, treatment: Variant) -> Self {
+        Self {
+            id: id.into(),
+            agent_id: agent_id.into(),
+            control,
+            treatment,
+            treatment_share: 0.5,
+        }
+    }
+
+    pub fn with_treatment_share(mut self, share: f64) -> Self {
+        self.treatment_share = share.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Variant for `key` (the task id, or the prompt of untagged calls)
+    ///
+    /// Stable across calls and processes for the same experiment and key.
+    pub fn assign(&self, key: &str) -> &Variant {
+        let bucket = fnv1a(format!("{}:{}", self.id, key).as_bytes()) as f64 / u64::MAX as f64;
+        if bucket < self.treatment_share {
+            &self.treatment
+        } else {
+            &self.control
+        }
+    }
+
+    pub fn tag(&self, variant: &Variant) -> VariantTag {
+        VariantTag {
+            experiment: self.id.clone(),
+            variant: variant.name.clone(),
+        }
+    }
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| (hash ^ *byte as u64).wrapping_mul(0x0100_0000_01b3))
+}
+
+/// Count, mean and variance of one variant's samples
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SampleStats {
+    pub n: usize,
+    pub mean: f64,
+    /// Sample variance (n - 1 denominator)
+    pub variance: f64,
+}
+
+impl SampleStats {
+    pub fn from_samples(samples: &[f64]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        let n = samples.len();
+        let mean = samples.iter().sum::<f64>() / n as f64;
+        let variance = if n > 1 {
+            samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1) as f64
+        } else {
+            0.0
+        };
+        Some(Self { n, mean, variance })
+    }
+}
+
+/// Treatment against control on one metric
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Comparison {
+    pub control: SampleStats,
+    pub treatment: SampleStats,
+    /// Treatment mean minus control mean
+    pub difference: f64,
+    /// Two-sided Welch's t-test; `None` with fewer than two samples per side
+    pub p_value: Option<f64>,
+}
+
+impl Comparison {
+    pub fn new(control: &[f64], treatment: &[f64]) -> Option<Self> {
+        let c = SampleStats::from_samples(control)?;
+        let t = SampleStats::from_samples(treatment)?;
+        Some(Self {
+            control: c,
+            treatment: t,
+            difference: t.mean - c.mean,
+            p_value: welch_p_value(&c, &t),
+        })
+    }
+
+    pub fn is_significant(&self, alpha: f64) -> bool {
+        self.p_value.is_some_and(|p| p < alpha)
+    }
+}
+
+/// Two-sided p-value of Welch's t-test
+fn welch_p_value(a: &SampleStats, b: &SampleStats) -> Option<f64> {
+    if a.n < 2 || b.n < 2 {
+        return None;
+    }
+    let (va, vb) = (a.variance / a.n as f64, b.variance / b.n as f64);
+    let se2 = va + vb;
+    if se2 == 0.0 {
+        // No spread at all: identical means are no evidence, different ones are certain
+        return Some(if a.mean == b.mean { 1.0 } else { 0.0 });
+    }
+    let t = (b.mean - a.mean) / se2.sqrt();
+    let df = se2.powi(2) / (va.powi(2) / (a.n - 1) as f64 + vb.powi(2) / (b.n - 1) as f64);
+    Some(incomplete_beta(df / 2.0, 0.5, df / (df + t * t)).clamp(0.0, 1.0))
+}
+
+/// Regularized incomplete beta function I_x(a, b)
+fn incomplete_beta(a: f64, b: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    let front = (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp();
+    // The continued fraction converges fast only below the mean
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * beta_fraction(a, b, x) / a
+    } else {
+        1.0 - front * beta_fraction(b, a, 1.0 - x) / b
+    }
+}
+
+/// Continued fraction of the incomplete beta function (modified Lentz)
+fn beta_fraction(a: f64, b: f64, x: f64) -> f64 {
+    const TINY: f64 = 1e-300;
+    let mut c = 1.0;
+    let mut d = 1.0 - (a + b) * x / (a + 1.0);
+    d = 1.0 / if d.abs() < TINY { TINY } else { d };
+    let mut h = d;
+    for m in 1..200 {
+        let m = m as f64;
+        for numerator in [
+            m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m)),
+            -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0)),
+        ] {
+            d = 1.0 + numerator * d;
+            d = 1.0 / if d.abs() < TINY { TINY } else { d };
+            c = 1.0 + numerator / c;
+            c = if c.abs() < TINY { TINY } else { c };
+            h *= d * c;
+        }
+        if (d * c - 1.0).abs() < 1e-12 {
+            break;
+        }
+    }
+    h
+}
+
+/// Lanczos approximation of ln Γ(x)
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 6] = [
+        76.180_091_729_471_46,
+        -86.505_320_329_416_77,
+        24.014_098_240_830_91,
+        -1.231_739_572_450_155,
+        0.001_208_650_973_866_179,
+        -0.000_005_395_239_384_953,
+    ];
+    let tmp = x + 5.5 - (x + 0.5) * (x + 5.5).ln();
+    let series = COEFFICIENTS
+        .iter()
+        .enumerate()
+        .fold(1.000_000_000_190_015, |sum, (i, c)| sum + c / (x + 1.0 + i as f64));
+    -tmp + (2.506_628_274_631_000_5 * series / x).ln()
+}
+
+/// What one variant did during the experiment
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct VariantSummary {
+    pub variant: String,
+    /// Routed calls tagged with the variant
+    pub calls: usize,
+    /// Distinct tasks among those calls
+    pub tasks: usize,
+    pub mean_cost_per_task: Option<f64>,
+    pub scored_tasks: usize,
+    pub mean_score: Option<f64>,
+}
+
+/// Control against treatment on evaluation score and cost per task
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExperimentReport {
+    pub experiment_id: String,
+    pub control: VariantSummary,
+    pub treatment: VariantSummary,
+    pub score: Option<Comparison>,
+    pub cost: Option<Comparison>,
+}
+
+impl ExperimentReport {
+    /// Report from routing logs and `(variant, score)` pairs of evaluated tasks
+    ///
+    /// Logs of other experiments are ignored. Calls without a task id count
+    /// as tasks of their own.
+    pub fn build(experiment: &Experiment, logs: &[RoutingLog], scores: &[(String, f64)]) -> Self {
+        let mut costs: BTreeMap<&str, BTreeMap<String, f64>> = BTreeMap::new();
+        let mut calls: BTreeMap<&str, usize> = BTreeMap::new();
+        for (i, log) in logs.iter().enumerate() {
+            let Some(ref tag) = log.variant else { continue };
+            if tag.experiment != experiment.id || log.is_synthetic() {
+                continue;
+            }
+            *calls.entry(tag.variant.as_str()).or_default() += 1;
+            let task = log.task_id.clone().unwrap_or_else(|| format!("call-{}", i));
+            let cost = log.cost.and_then(|c| c.to_f64()).unwrap_or(0.0);
+            *costs.entry(tag.variant.as_str()).or_default().entry(task).or_default() += cost;
+        }
+
+        let samples = |variant: &str| -> (Vec<f64>, Vec<f64>) {
+            let cost = costs.get(variant).map(|tasks| tasks.values().copied().collect()).unwrap_or_default();
+            let score = scores.iter().filter(|(v, _)| v == variant).map(|(_, s)| *s).collect();
+            (cost, score)
+        };
+        let summary = |variant: &str, cost: &[f64], score: &[f64]| VariantSummary {
+            variant: variant.to_string(),
+            calls: calls.get(variant).copied().unwrap_or(0),
+            tasks: cost.len(),
+            mean_cost_per_task: SampleStats::from_samples(cost).map(|s| s.mean),
+            scored_tasks: score.len(),
+            mean_score: SampleStats::from_samples(score).map(|s| s.mean),
+        };
+
+        let (control_cost, control_score) = samples(&experiment.control.name);
+        let (treatment_cost, treatment_score) = samples(&experiment.treatment.name);
+        Self {
+            experiment_id: experiment.id.clone(),
+            control: summary(&experiment.control.name, &control_cost, &control_score),
+            treatment: summary(&experiment.treatment.name, &treatment_cost, &treatment_score),
+            score: Comparison::new(&control_score, &treatment_score),
+            cost: Comparison::new(&control_cost, &treatment_cost),
+        }
+    }
+
+    /// Plain-text table with a verdict per metric
+    pub fn render(&self, alpha: f64) -> String {
+        let fmt = |value: Option<f64>| value.map(|v| format!("{:.3}", v)).unwrap_or_else(|| "-".to_string());
+        let mut out = format!("Experiment {}\n", self.experiment_id);
+        for summary in [&self.control, &self.treatment] {
+            out.push_str(&format!(
+                "  {:<12} calls {:>5}  tasks {:>4}  cost/task {:>8}  scored {:>4}  score {:>6}\n",
+                summary.variant,
+                summary.calls,
+                summary.tasks,
+                fmt(summary.mean_cost_per_task),
+                summary.scored_tasks,
+                fmt(summary.mean_score)
+            ));
+        }
+        for (metric, comparison) in [("score", &self.score), ("cost/task", &self.cost)] {
+            let verdict = match comparison {
+                None => "not enough data".to_string(),
+                Some(c) => format!(
+                    "{:+.3} (p = {}){}",
+                    c.difference,
+                    fmt(c.p_value),
+                    if c.is_significant(alpha) { ", significant" } else { ", not significant" }
+                ),
+            };
+            out.push_str(&format!("  {} difference: {}\n", metric, verdict));
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use rust_decimal::Decimal;
+
+    fn experiment() -> Experiment {
+        Experiment::new("prompt-v2", "implementer", Variant::new("control"), Variant::new("v2").with_system_prompt("Be terse."))
+    }
+
+    #[test]
+    fn test_assignment_is_stable_and_split() {
+        let experiment = experiment();
+        let first = experiment.assign("task-1").name.clone();
+        assert!((0..10).all(|_| experiment.assign("task-1").name == first));
+
+        let treated = (0..1000).filter(|i| experiment.assign(&format!("task-{}", i)).name == "v2").count();
+        assert!((400..600).contains(&treated), "treated {}", treated);
+
+        let all_control = experiment.with_treatment_share(0.0);
+        assert!((0..100).all(|i| all_control.assign(&i.to_string()).name == "control"));
+    }
+
+    #[test]
+    fn test_welch_p_values() {
+        // t = 6.309, df = 7.764
+        let c = Comparison::new(&[0.6, 0.7, 0.65, 0.55, 0.62], &[0.8, 0.85, 0.78, 0.9, 0.82]).unwrap();
+        assert!((c.p_value.unwrap() - 0.000_261).abs() < 0.000_001, "{:?}", c.p_value);
+        assert!(c.is_significant(DEFAULT_ALPHA));
+
+        let c = Comparison::new(&[1.0, 2.0, 3.0], &[1.5, 2.5, 2.0]).unwrap();
+        assert!((c.p_value.unwrap() - 1.0).abs() < 1e-9);
+        assert!(Comparison::new(&[1.0], &[2.0]).unwrap().p_value.is_none());
+        assert!(Comparison::new(&[], &[2.0]).is_none());
+    }
+
+    #[test]
+    fn test_report_groups_logs_by_variant_and_task() {
+        let experiment = experiment();
+        let log = |variant: &str, task: &str, cents: i64| RoutingLog {
+            id: None,
+            timestamp: Utc::now(),
+            agent_id: "implementer".to_string(),
+            task_id: Some(task.to_string()),
+            original_prompt_len: 10,
+            selected_tier: 1,
+            selected_model: "gpt-4o-mini".to_string(),
+            retry_count: 0,
+            failover_reason: None,
+            cost: Some(Decimal::new(cents, 2)),
+            is_critical: false,
+            speculation: None,
+            variant: Some(VariantTag { experiment: experiment.id.clone(), variant: variant.to_string() }),
+            throttle: None,
+        };
+        let other = RoutingLog {
+            variant: Some(VariantTag { experiment: "other".to_string(), variant: "control".to_string() }),
+            ..log("control", "t9", 100)
+        };
+        let logs = vec![log("control", "t1", 10), log("control", "t1", 20), log("v2", "t2", 5), other];
+        let scores = vec![("control".to_string(), 0.5), ("v2".to_string(), 0.9)];
+
+        let report = ExperimentReport::build(&experiment, &logs, &scores);
+        assert_eq!((report.control.calls, report.control.tasks), (2, 1));
+        assert!((report.control.mean_cost_per_task.unwrap() - 0.3).abs() < 1e-9);
+        assert_eq!(report.treatment.mean_score, Some(0.9));
+        assert!(report.score.as_ref().unwrap().p_value.is_none());
+        assert!(report.render(DEFAULT_ALPHA).contains("score difference: +0.400 (p = -), not significant"));
+    }
+}
@@ -0,0 +1,126 @@
+//! Crash-safe lease journal
+//!
+//! `LeaseGuard` releases a leaked lease from a spawned task, which never runs
+//! if the process dies mid-request. The journal records every lease the
+//! Router holds in the `lease_journal` table before the provider is called
+//! and removes it once the lease is settled or released, so a restarted
+//! process can find and release whatever a crashed one left open.
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use surrealdb::engine::any::Any;
+use surrealdb::Surreal;
+use uuid::Uuid;
+
+const TABLE: &str = "lease_journal";
+
+/// One open lease as recorded in the journal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub lease_id: String,
+    /// Router instance that opened the lease
+    pub instance_id: String,
+    pub pid: u32,
+    pub opened_at: DateTime<Utc>,
+}
+
+/// Leases held by this Router, mirrored to the database
+pub struct LeaseJournal {
+    db: Surreal<Any>,
+    instance_id: String,
+    open: DashMap<String, ()>,
+}
+
+impl LeaseJournal {
+    pub fn new(db: Surreal<Any>) -> Self {
+        Self {
+            db,
+            instance_id: Uuid::new_v4().to_string(),
+            open: DashMap::new(),
+        }
+    }
+
+    pub fn instance_id(&self) -> &str {
+        &self.instance_id
+    }
+
+    /// Leases opened by this instance and not yet closed
+    pub fn len(&self) -> usize {
+        self.open.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.open.is_empty()
+    }
+
+    pub fn open_ids(&self) -> Vec<String> {
+        self.open.iter().map(|e| e.key().clone()).collect()
+    }
+
+    /// Record a lease before any provider call is made with it
+    ///
+    /// The lease is tracked in memory even if the write fails, so shutdown
+    /// still releases it; only crash recovery is lost.
+    pub async fn open(&self, lease_id: &str) -> anyhow::Result<()> {
+        self.open.insert(lease_id.to_string(), ());
+        let entry = JournalEntry {
+            lease_id: lease_id.to_string(),
+            instance_id: self.instance_id.clone(),
+            pid: std::process::id(),
+            opened_at: Utc::now(),
+        };
+        let _: Option<JournalEntry> = self.db.create((TABLE, lease_id)).content(entry).await?;
+        Ok(())
+    }
+
+    /// Forget a lease once it has been settled or released
+    pub async fn close(&self, lease_id: &str) -> anyhow::Result<()> {
+        self.open.remove(lease_id);
+        let _: Option<JournalEntry> = self.db.delete((TABLE, lease_id)).await?;
+        Ok(())
+    }
+
+    /// Entries written by other Router instances, oldest first
+    pub async fn orphaned(&self) -> anyhow::Result<Vec<JournalEntry>> {
+        let mut response = self
+            .db
+            .query("SELECT lease_id, instance_id, pid, opened_at FROM type::table($tb) WHERE instance_id != $instance")
+            .bind(("tb", TABLE))
+            .bind(("instance", self.instance_id.clone()))
+            .await?;
+        let mut entries: Vec<JournalEntry> = response.take(0)?;
+        entries.sort_by_key(|e| e.opened_at);
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use surrealdb::engine::any::connect;
+
+    async fn db() -> Surreal<Any> {
+        let db = connect("mem://").await.unwrap();
+        db.use_ns("zed42").use_db("mom").await.unwrap();
+        db
+    }
+
+    #[tokio::test]
+    async fn test_orphaned_excludes_own_entries() {
+        let db = db().await;
+        let crashed = LeaseJournal::new(db.clone());
+        crashed.open("lease-a").await.unwrap();
+        crashed.open("lease-b").await.unwrap();
+        crashed.close("lease-b").await.unwrap();
+
+        let current = LeaseJournal::new(db);
+        current.open("lease-c").await.unwrap();
+
+        let orphaned = current.orphaned().await.unwrap();
+        assert_eq!(orphaned.len(), 1);
+        assert_eq!(orphaned[0].lease_id, "lease-a");
+        assert_eq!(orphaned[0].instance_id, crashed.instance_id());
+        assert_eq!(current.open_ids(), vec!["lease-c".to_string()]);
+    }
+}
@@ -0,0 +1,191 @@
+//! Router middleware
+//!
+//! A `RouterLayer` intercepts requests before the tier waterfall and responses
+//! after it, in the style of tower layers. Layers run in registration order on
+//! the way in and in reverse order on the way out, so the first layer added is
+//! the outermost. A layer may short-circuit by returning a response from
+//! `on_request` (e.g. a cache hit); only the layers outside it then see the
+//! response.
+
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Instant;
+use tracing::{info, warn};
+use zed42_llm::{LlmError, LlmRequest, LlmResponse, Result};
+
+/// Request/response interceptor around the Router
+#[async_trait]
+pub trait RouterLayer: Send + Sync {
+    /// Layer name (for logs)
+    fn name(&self) -> &str;
+
+    /// Inspect or rewrite a request; return `Some` to answer without routing
+    async fn on_request(&self, _request: &mut LlmRequest) -> Result<Option<LlmResponse>> {
+        Ok(None)
+    }
+
+    /// Inspect or rewrite a successful response
+    async fn on_response(&self, _request: &LlmRequest, _response: &mut LlmResponse) -> Result<()> {
+        Ok(())
+    }
+
+    /// Observe a failed request
+    async fn on_error(&self, _request: &LlmRequest, _error: &LlmError) {}
+}
+
+/// Logs every request and its outcome
+#[derive(Debug, Default)]
+pub struct LoggingLayer {
+    started: Mutex<HashMap<String, Instant>>,
+}
+
+impl LoggingLayer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn key(request: &LlmRequest) -> String {
+        format!("{}:{}", request.agent_id.as_deref().unwrap_or("default"), request.prompt.len())
+    }
+}
+
+#[async_trait]
+impl RouterLayer for LoggingLayer {
+    fn name(&self) -> &str {
+        "logging"
+    }
+
+    async fn on_request(&self, request: &mut LlmRequest) -> Result<Option<LlmResponse>> {
+        info!(
+            agent = request.agent_id.as_deref().unwrap_or("default"),
+            prompt_len = request.prompt.len(),
+            "Router request"
+        );
+        self.started.lock().unwrap().insert(Self::key(request), Instant::now());
+        Ok(None)
+    }
+
+    async fn on_response(&self, request: &LlmRequest, response: &mut LlmResponse) -> Result<()> {
+        let elapsed = self.started.lock().unwrap().remove(&Self::key(request)).map(|t| t.elapsed());
+        info!(
+            agent = request.agent_id.as_deref().unwrap_or("default"),
+            model = %response.model,
+            tokens = response.usage.total_tokens,
+            elapsed_ms = elapsed.map(|d| d.as_millis() as u64).unwrap_or(0),
+            "Router response"
+        );
+        Ok(())
+    }
+
+    async fn on_error(&self, request: &LlmRequest, error: &LlmError) {
+        self.started.lock().unwrap().remove(&Self::key(request));
+        warn!(agent = request.agent_id.as_deref().unwrap_or("default"), error = %error, "Router request failed");
+    }
+}
+
+/// Serves repeated identical requests from memory
+///
+/// Only deterministic requests (temperature 0) are cached.
+#[derive(Debug)]
+pub struct CacheLayer {
+    entries: Mutex<HashMap<String, LlmResponse>>,
+    max_entries: usize,
+}
+
+impl Default for CacheLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CacheLayer {
+    pub fn new() -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            max_entries: 1024,
+        }
+    }
+
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn key(request: &LlmRequest) -> Option<String> {
+        if request.config.temperature != 0.0 {
+            return None;
+        }
+        serde_json::to_string(&(
+            &request.agent_id,
+            &request.config.model,
+            &request.system_prompt,
+            request.rendered_prompt(),
+            &request.json_schema,
+            &request.images,
+        ))
+        .ok()
+    }
+}
+
+#[async_trait]
+impl RouterLayer for CacheLayer {
+    fn name(&self) -> &str {
+        "cache"
+    }
+
+    async fn on_request(&self, request: &mut LlmRequest) -> Result<Option<LlmResponse>> {
+        let Some(key) = Self::key(request) else { return Ok(None) };
+        Ok(self.entries.lock().unwrap().get(&key).cloned())
+    }
+
+    async fn on_response(&self, request: &LlmRequest, response: &mut LlmResponse) -> Result<()> {
+        if let Some(key) = Self::key(request) {
+            let mut entries = self.entries.lock().unwrap();
+            if entries.len() < self.max_entries || entries.contains_key(&key) {
+                entries.insert(key, response.clone());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zed42_llm::{ModelConfig, Usage};
+
+    fn response(content: &str) -> LlmResponse {
+        LlmResponse {
+            content: content.to_string(),
+            model: "m".to_string(),
+            usage: Usage { prompt_tokens: 1, completion_tokens: 1, total_tokens: 2, ..Default::default() },
+            finish_reason: "stop".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cache_layer_only_caches_deterministic_requests() {
+        let cache = CacheLayer::new();
+        let deterministic = ModelConfig { temperature: 0.0, ..ModelConfig::default() };
+
+        let mut request = LlmRequest::new("hello".to_string()).config(deterministic);
+        assert!(cache.on_request(&mut request).await.unwrap().is_none());
+        cache.on_response(&request, &mut response("hi")).await.unwrap();
+        let hit = cache.on_request(&mut request).await.unwrap();
+        assert_eq!(hit.unwrap().content, "hi");
+
+        let mut sampled = LlmRequest::new("hello".to_string());
+        cache.on_response(&sampled, &mut response("hi")).await.unwrap();
+        assert!(cache.on_request(&mut sampled).await.unwrap().is_none());
+        assert_eq!(cache.len(), 1);
+    }
+}
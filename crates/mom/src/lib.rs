@@ -2,10 +2,21 @@
 //!
 //! Central routing intelligence for ZED42 agents.
 
+pub mod analytics;
+pub mod capabilities;
 pub mod circuit_breaker;
+pub mod compression;
+pub mod drafting;
+pub mod estimator;
+pub mod experiment;
+pub mod journal;
+pub mod layers;
+pub mod recommend;
+pub mod throttle;
 pub mod types;
 
 use std::collections::HashMap;
+use std::ops::RangeInclusive;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -17,24 +28,76 @@ use surrealdb::Surreal;
 use tokio::time::sleep;
 use tracing::{error, info, warn};
 
+use crate::analytics::{RoutingAnalytics, RoutingHistory, RoutingLogQuery};
+use crate::capabilities::{CapabilityRegistry, RequestRequirements};
+use crate::compression::PromptCompressor;
+use crate::drafting::{DraftVerdict, DraftingPolicy, SpeculationRecord};
+use crate::estimator::LeaseEstimator;
+use crate::experiment::{Experiment, VariantTag};
+use crate::recommend::{CritiqueOutcome, ModelRecommendation, RecommendationPolicy};
+use crate::layers::RouterLayer;
 use crate::circuit_breaker::CircuitBreaker;
+use crate::journal::LeaseJournal;
+use crate::throttle::{Batcher, Joined};
 use crate::types::{ExecutionProfile, RoutingLog};
-use zed42_ledger::{IntelligenceLedger, types::Usage};
+use zed42_ledger::{IntelligenceLedger, throttle::ThrottleDecision, types::{LeasePriority, Usage}};
 use zed42_llm::LlmClient;
 use zed42_llm::{LlmError, LlmRequest, LlmResponse, RetryCause, StreamChunk, EmbeddingRequest, EmbeddingResponse};
 
+/// A routed response and what its ledger settlement cost
+struct Routed {
+    response: LlmResponse,
+    cost: Option<Decimal>,
+}
+
+/// The experiment arm a request was assigned to
+#[derive(Clone)]
+struct Assignment {
+    tag: VariantTag,
+    profile: Option<ExecutionProfile>,
+}
+
+/// Decisions made once per request and applied on every tier it tries
+struct RouteContext {
+    assignment: Option<Assignment>,
+    /// Soft-limit enforcement for the billing entity's budget
+    throttle: Option<ThrottleDecision>,
+}
+
+impl RouteContext {
+    fn variant(&self) -> Option<VariantTag> {
+        self.assignment.as_ref().map(|a| a.tag.clone())
+    }
+}
+
+/// Entity a request is billed to: its agent, tenant-scoped when a tenant is set
+///
+/// Fails for tenant ids that can't scope an entity (see `zed42_core::tenant`).
+fn billing_entity(request: &LlmRequest) -> zed42_llm::Result<String> {
+    let agent_id = request.agent_id.as_deref().unwrap_or("default");
+    match request.tenant_id.as_deref() {
+        Some(tenant) => zed42_core::tenant::scoped_id(tenant, agent_id).map_err(|e| LlmError::ApiError(e.to_string())),
+        None => Ok(agent_id.to_string()),
+    }
+}
+
+/// Model name recorded when a lease is released without real usage
+const LEASE_CLEANUP_MODEL: &str = "lease-guard-cleanup";
+
 /// Guard that ensures a lease is settled or released back to the budget
 struct LeaseGuard {
     lease_id: Option<String>,
     ledger: Arc<IntelligenceLedger>,
+    journal: Arc<LeaseJournal>,
     settled: bool,
 }
 
 impl LeaseGuard {
-    fn new(lease_id: String, ledger: Arc<IntelligenceLedger>) -> Self {
+    fn new(lease_id: String, ledger: Arc<IntelligenceLedger>, journal: Arc<LeaseJournal>) -> Self {
         Self {
             lease_id: Some(lease_id),
             ledger,
+            journal,
             settled: false,
         }
     }
@@ -51,13 +114,20 @@ impl Drop for LeaseGuard {
         if !self.settled {
             if let Some(lease_id) = self.lease_id.take() {
                 let ledger = Arc::clone(&self.ledger);
+                let journal = Arc::clone(&self.journal);
                 warn!(lease_id = %lease_id, "Lease leaked! Releasing budget via LeaseGuard");
+                // If the process stops before this runs, the journal entry
+                // survives and `Router::recover_leases` releases it on restart
                 tokio::spawn(async move {
                     let _ = ledger.commit_usage(&lease_id, Usage {
                         input_tokens: 0,
                         output_tokens: 0,
-                        model: "lease-guard-cleanup".to_string(),
+                        model: LEASE_CLEANUP_MODEL.to_string(),
+                        ..Default::default()
                     }).await;
+                    if let Err(e) = journal.close(&lease_id).await {
+                        warn!(lease_id = %lease_id, error = %e, "Failed to clear lease journal entry");
+                    }
                 });
             }
         }
@@ -73,6 +143,22 @@ pub struct Router {
     clients: HashMap<String, Arc<dyn LlmClient>>,
     /// Fallback client if no specific provider matches
     default_client: Arc<dyn LlmClient>,
+    /// Known model limits consulted during tier selection
+    capabilities: CapabilityRegistry,
+    /// Shrinks prompts that would overflow a model's context window
+    compressor: PromptCompressor,
+    /// Middleware around the waterfall, outermost first
+    layers: Vec<Arc<dyn RouterLayer>>,
+    /// Leases held by in-flight requests, persisted for crash recovery
+    lease_journal: Arc<LeaseJournal>,
+    /// How speculative requests ask the verifier to review drafts
+    drafting: DraftingPolicy,
+    /// Running A/B experiments by agent id
+    experiments: HashMap<String, Experiment>,
+    /// Open request batches of throttled budgets
+    batcher: Batcher,
+    /// Lease amounts learned from settled costs
+    lease_estimator: LeaseEstimator,
 }
 
 impl Router {
@@ -81,20 +167,150 @@ impl Router {
         db: Surreal<Any>,
         default_client: Arc<dyn LlmClient>,
     ) -> Self {
+        let lease_journal = Arc::new(LeaseJournal::new(db.clone()));
         Self {
             ledger,
             db,
             circuit_breaker: CircuitBreaker::new(),
             clients: HashMap::new(),
             default_client,
+            capabilities: CapabilityRegistry::new(),
+            compressor: PromptCompressor::new(),
+            layers: Vec::new(),
+            lease_journal,
+            drafting: DraftingPolicy::default(),
+            experiments: HashMap::new(),
+            batcher: Batcher::default(),
+            lease_estimator: LeaseEstimator::default(),
         }
     }
 
+    pub fn with_capabilities(mut self, registry: CapabilityRegistry) -> Self {
+        self.capabilities = registry;
+        self
+    }
+
+    pub fn with_compressor(mut self, compressor: PromptCompressor) -> Self {
+        self.compressor = compressor;
+        self
+    }
+
+    /// Add a middleware layer; layers added first wrap those added later
+    pub fn with_layer(mut self, layer: Arc<dyn RouterLayer>) -> Self {
+        self.layers.push(layer);
+        self
+    }
+
     pub fn with_circuit_breaker(mut self, cb: CircuitBreaker) -> Self {
         self.circuit_breaker = cb;
         self
     }
 
+    pub fn with_drafting(mut self, policy: DraftingPolicy) -> Self {
+        self.drafting = policy;
+        self
+    }
+
+    pub fn with_lease_estimator(mut self, estimator: LeaseEstimator) -> Self {
+        self.lease_estimator = estimator;
+        self
+    }
+
+    pub fn lease_estimator(&self) -> &LeaseEstimator {
+        &self.lease_estimator
+    }
+
+    /// Seed the lease estimator from the ledger's settlement history
+    ///
+    /// Returns the number of settlements learned from.
+    pub async fn load_lease_estimates(&self) -> anyhow::Result<usize> {
+        let records = self.ledger.variance_records(None).await?;
+        self.lease_estimator.seed(&records);
+        Ok(records.len())
+    }
+
+    /// Split the experiment's agent between its variants (one experiment per agent)
+    pub fn with_experiment(mut self, experiment: Experiment) -> Self {
+        self.experiments.insert(experiment.agent_id.clone(), experiment);
+        self
+    }
+
+    pub fn experiment(&self, agent_id: &str) -> Option<&Experiment> {
+        self.experiments.get(agent_id)
+    }
+
+    /// Leases currently held by in-flight requests
+    pub fn open_lease_count(&self) -> usize {
+        self.lease_journal.len()
+    }
+
+    /// Release every lease still open, returning their budget (used on shutdown)
+    ///
+    /// Returns the number of leases released.
+    pub async fn release_open_leases(&self) -> usize {
+        let mut released = 0;
+        for lease_id in self.lease_journal.open_ids() {
+            if self.release_lease(&lease_id).await {
+                released += 1;
+            }
+        }
+        if released > 0 {
+            info!(released, "Released open leases");
+        }
+        released
+    }
+
+    /// Release leases journaled by a previous Router that never settled them
+    ///
+    /// Call once at startup, before other Routers sharing this database
+    /// start routing: every journal entry not written by this instance is
+    /// treated as abandoned. Returns the number of leases released.
+    pub async fn recover_leases(&self) -> anyhow::Result<usize> {
+        let orphaned = self.lease_journal.orphaned().await?;
+        let mut released = 0;
+        for entry in orphaned {
+            warn!(
+                lease_id = %entry.lease_id,
+                pid = entry.pid,
+                opened_at = %entry.opened_at,
+                "Releasing lease left open by a previous process"
+            );
+            if self.release_lease(&entry.lease_id).await {
+                released += 1;
+            }
+        }
+        if released > 0 {
+            info!(released, "Recovered leases from journal");
+        }
+        Ok(released)
+    }
+
+    /// Settle a lease with zero usage and drop its journal entry
+    ///
+    /// Returns false if the lease could not be released; its journal entry
+    /// is then kept so a later attempt can retry.
+    async fn release_lease(&self, lease_id: &str) -> bool {
+        let result = self.ledger.commit_usage(lease_id, Usage {
+            input_tokens: 0,
+            output_tokens: 0,
+            model: LEASE_CLEANUP_MODEL.to_string(),
+            ..Default::default()
+        }).await;
+        let released = match result {
+            Ok(_) => true,
+            // Settled or expired already; only the journal entry is stale
+            Err(zed42_ledger::error::LedgerError::LeaseNotFound(_)) => false,
+            Err(e) => {
+                error!(lease_id = %lease_id, error = %e, "Failed to release lease");
+                return false;
+            }
+        };
+        if let Err(e) = self.lease_journal.close(lease_id).await {
+            warn!(lease_id = %lease_id, error = %e, "Failed to clear lease journal entry");
+        }
+        released
+    }
+
     pub fn register_client(&mut self, prefix: &str, client: Arc<dyn LlmClient>) {
         self.clients.insert(prefix.to_string(), client);
     }
@@ -108,6 +324,66 @@ impl Router {
         &self.default_client
     }
 
+    /// The stored execution profile of an agent, if any
+    pub async fn execution_profile(&self, agent_id: &str) -> anyhow::Result<Option<ExecutionProfile>> {
+        Ok(self.db.select(("model_profiles", agent_id)).await?)
+    }
+
+    /// Replace an agent's execution profile at runtime, recording the change in the ledger
+    ///
+    /// Every tier needs a model name and a temperature in 0-2; paid tiers
+    /// (1-3) also need a rate in the ledger, so the change can't route calls
+    /// to a model that can't be billed.
+    pub async fn set_execution_profile(&self, profile: ExecutionProfile, actor: &str) -> anyhow::Result<()> {
+        if profile.agent_id.trim().is_empty() {
+            anyhow::bail!("Execution profile needs an agent id");
+        }
+        let tiers = [
+            (0u8, profile.tier_0.as_ref()),
+            (1, Some(&profile.tier_1)),
+            (2, profile.tier_2.as_ref()),
+            (3, profile.tier_3.as_ref()),
+        ];
+        for (tier, config) in tiers {
+            let Some(config) = config else { continue };
+            if config.model.trim().is_empty() {
+                anyhow::bail!("Tier {} has no model", tier);
+            }
+            if !(0.0..=2.0).contains(&config.temperature) {
+                anyhow::bail!("Tier {} temperature {} is outside 0-2", tier, config.temperature);
+            }
+            if tier > 0 && self.ledger.get_rate(&config.model).await?.is_none() {
+                anyhow::bail!("Tier {} model {} has no rate in the ledger", tier, config.model);
+            }
+        }
+
+        let previous = self.execution_profile(&profile.agent_id).await?;
+        let describe = |profile: Option<&ExecutionProfile>| -> String {
+            let Some(profile) = profile else { return "none".to_string() };
+            [profile.tier_0.as_ref(), Some(&profile.tier_1), profile.tier_2.as_ref(), profile.tier_3.as_ref()]
+                .into_iter()
+                .map(|config| config.map_or("-", |config| config.model.as_str()))
+                .collect::<Vec<_>>()
+                .join(" / ")
+        };
+        let details = format!(
+            "Execution profile of {} changed by {}: {} -> {}",
+            profile.agent_id,
+            actor,
+            describe(previous.as_ref()),
+            describe(Some(&profile))
+        );
+
+        let _: Option<ExecutionProfile> = self
+            .db
+            .upsert(("model_profiles", profile.agent_id.clone()))
+            .content(profile.clone())
+            .await?;
+        self.ledger.record_audit(&profile.agent_id, details).await?;
+        info!(agent = %profile.agent_id, actor, "Updated execution profile");
+        Ok(())
+    }
+
     async fn get_profile(&self, agent_id: &str) -> anyhow::Result<ExecutionProfile> {
         let profile: Option<ExecutionProfile> = self.db
             .select(("model_profiles", agent_id))
@@ -117,21 +393,331 @@ impl Router {
     }
 
     async fn log_routing(&self, log: RoutingLog) {
-        // Updated to use Option return type and pass owned log
-        let _: Option<RoutingLog> = self.db.create("routing_logs").content(log).await.ok().flatten();
+        // The stored record isn't needed back
+        let _: Option<serde::de::IgnoredAny> = self.db.create("routing_logs").content(log).await.ok().flatten();
+    }
+
+    /// The read side of this router's logs, usable without the router
+    pub fn history(&self) -> RoutingHistory {
+        RoutingHistory::new(self.db.clone())
+    }
+
+    /// Query routing logs, oldest first
+    pub async fn query_routing_logs(&self, query: &RoutingLogQuery) -> anyhow::Result<Vec<RoutingLog>> {
+        self.history().routing_logs(query).await
+    }
+
+    /// Aggregate statistics over the logs matching `query`
+    pub async fn routing_analytics(&self, query: &RoutingLogQuery) -> anyhow::Result<RoutingAnalytics> {
+        let logs = self.query_routing_logs(query).await?;
+        Ok(RoutingAnalytics::from_logs(&logs))
+    }
+
+    /// Compare an experiment's variants on cost per task and the given scores
+    ///
+    /// `scores` pairs a variant name with the evaluation score of one task.
+    pub async fn experiment_report(
+        &self,
+        experiment: &Experiment,
+        scores: &[(String, f64)],
+    ) -> anyhow::Result<experiment::ExperimentReport> {
+        let query = RoutingLogQuery::new().agent(experiment.agent_id.clone()).experiment(experiment.id.clone());
+        let logs = self.query_routing_logs(&query).await?;
+        Ok(experiment::ExperimentReport::build(experiment, &logs, scores))
+    }
+
+    /// Record the result of a critique or validation pass on a routed output
+    pub async fn record_critique(&self, outcome: CritiqueOutcome) -> anyhow::Result<()> {
+        let _: Option<CritiqueOutcome> = self.db.create("critique_outcomes").content(outcome).await?;
+        Ok(())
+    }
+
+    /// Critique outcomes since `since` (all when `None`), oldest first
+    pub async fn query_critique_outcomes(&self, since: Option<chrono::DateTime<Utc>>) -> anyhow::Result<Vec<CritiqueOutcome>> {
+        self.history().critique_outcomes(since).await
+    }
+
+    /// Profile changes suggested by the routing logs and critiques since `since`
+    pub async fn model_recommendations(
+        &self,
+        since: Option<chrono::DateTime<Utc>>,
+        policy: &RecommendationPolicy,
+    ) -> anyhow::Result<Vec<ModelRecommendation>> {
+        self.history().model_recommendations(since, policy).await
+    }
+
+    /// Move logs older than `cutoff` out of the live table
+    ///
+    /// `archive` gets the logs, oldest first and with their `id` set, and
+    /// exactly those logs are deleted once it succeeds; the Cortex passes
+    /// Tier 4 (`ArchiveMemory`) here. If the delete fails the same logs are
+    /// offered again next time, so `archive` should key them on their id.
+    /// Returns how many logs were archived.
+    pub async fn archive_routing_logs_before<F>(&self, cutoff: chrono::DateTime<Utc>, archive: F) -> anyhow::Result<usize>
+    where
+        F: FnOnce(&[RoutingLog]) -> anyhow::Result<()>,
+    {
+        let mut response = self
+            .db
+            .query("SELECT *, meta::id(id) AS id FROM routing_logs WHERE timestamp < $cutoff ORDER BY timestamp ASC")
+            .bind(("cutoff", cutoff))
+            .await?;
+        let logs: Vec<RoutingLog> = response.take(0)?;
+        if logs.is_empty() {
+            return Ok(0);
+        }
+
+        archive(&logs)?;
+        // By id, so logs written since the SELECT stay until they're archived too
+        let ids: Vec<String> = logs.iter().filter_map(|log| log.id.clone()).collect();
+        self.db
+            .query("FOR $rid IN $ids { DELETE type::thing('routing_logs', $rid); }")
+            .bind(("ids", ids))
+            .await?
+            .check()?;
+        Ok(logs.len())
     }
 
     /// Get transparency on model health
     pub fn get_circuit_status(&self) -> Vec<crate::circuit_breaker::CircuitStatus> {
         self.circuit_breaker.get_status()
     }
-}
 
-#[async_trait]
-impl LlmClient for Router {
-    async fn complete(&self, request: LlmRequest) -> zed42_llm::Result<LlmResponse> {
+    /// Pass a result back out through the first `depth` layers, innermost first
+    async fn unwind_layers(
+        &self,
+        depth: usize,
+        request: &LlmRequest,
+        mut result: zed42_llm::Result<LlmResponse>,
+    ) -> zed42_llm::Result<LlmResponse> {
+        for layer in self.layers[..depth].iter().rev() {
+            result = match result {
+                Ok(mut response) => layer.on_response(request, &mut response).await.map(|_| response),
+                Err(e) => {
+                    layer.on_error(request, &e).await;
+                    Err(e)
+                }
+            };
+        }
+        result
+    }
+
+    /// Assign the request to an arm of its agent's experiment, if any
+    ///
+    /// Applies the variant's system prompt; its profile is applied when routing.
+    fn assign_variant(&self, request: &mut LlmRequest) -> Option<Assignment> {
+        let experiment = self.experiments.get(request.agent_id.as_deref()?)?;
+        let variant = experiment.assign(request.task_id.as_deref().unwrap_or(&request.prompt));
+        if let Some(ref system_prompt) = variant.system_prompt {
+            request.system_prompt = Some(system_prompt.clone());
+        }
+        Some(Assignment {
+            tag: experiment.tag(variant),
+            profile: variant.profile.clone(),
+        })
+    }
+
+    /// Profile for the request's agent, or a single-tier one from its config
+    ///
+    /// An experiment variant's profile takes precedence over the stored one.
+    async fn resolve_profile(&self, request: &LlmRequest, ctx: &RouteContext) -> ExecutionProfile {
+        if let Some(profile) = ctx.assignment.as_ref().and_then(|a| a.profile.clone()) {
+            return profile;
+        }
+        let agent_id = request.agent_id.as_deref().unwrap_or("default");
+        self.get_profile(agent_id).await.unwrap_or_else(|_| {
+            ExecutionProfile::new(
+                "default",
+                request.config.clone(),
+            )
+        })
+    }
+
+    /// Soft-limit throttle for the request's billing entity, if its budget has one
+    ///
+    /// Critical requests are never throttled.
+    async fn throttle_decision(&self, request: &LlmRequest) -> Option<ThrottleDecision> {
+        if request.critical {
+            return None;
+        }
+        // Invalid tenants fail in `route`
+        let entity = billing_entity(request).ok()?;
+        match self.ledger.throttle_decision(&entity).await {
+            Ok(decision) => decision,
+            Err(e) => {
+                warn!(entity = %entity, error = %e, "Failed to load throttle policy, routing unthrottled");
+                None
+            }
+        }
+    }
+
+    async fn waterfall(&self, request: LlmRequest, ctx: &RouteContext) -> zed42_llm::Result<LlmResponse> {
+        self.route(request, 0..=3, ctx).await.map(|routed| routed.response)
+    }
+
+    /// Route a throttled request together with compatible ones (see `throttle`)
+    ///
+    /// Requests go alone when no others arrive within the window, when the
+    /// batched call fails, or when its answer can't be split.
+    async fn batched(&self, request: LlmRequest, ctx: &RouteContext, decision: &ThrottleDecision) -> zed42_llm::Result<LlmResponse> {
+        let key = format!(
+            "{}\u{1f}{}\u{1f}{:?}\u{1f}{}\u{1f}{}",
+            decision.entity_id,
+            request.agent_id.as_deref().unwrap_or("default"),
+            ctx.variant(),
+            request.config.model,
+            request.system_prompt.as_deref().unwrap_or("")
+        );
+        let window = Duration::from_millis(decision.batch_window_ms);
+        let members = match self.batcher.join(key, request.clone(), decision.batch_size, window).await {
+            Joined::Follower(reply) => {
+                return match reply.await {
+                    Ok(result) => result,
+                    // The leader gave up on the batch
+                    Err(_) => self.waterfall(request, ctx).await,
+                };
+            }
+            Joined::Leader(members) if members.len() == 1 => return self.waterfall(request, ctx).await,
+            Joined::Leader(members) => members,
+        };
+
+        let batch_ctx = RouteContext {
+            assignment: ctx.assignment.clone(),
+            throttle: Some(ThrottleDecision { batched: members.len(), ..decision.clone() }),
+        };
+        let merged = throttle::merge(&members.iter().map(|m| &m.request).collect::<Vec<_>>());
+        let answers = match self.waterfall(merged, &batch_ctx).await {
+            Ok(response) => throttle::split(&response, members.len()),
+            Err(e) => {
+                warn!(error = %e, size = members.len(), "Batched call failed, routing requests alone");
+                return self.waterfall(request, ctx).await;
+            }
+        };
+        let Some(mut answers) = answers else {
+            // Dropping the reply channels sends the followers off on their own
+            warn!(size = members.len(), "Batched answer could not be split, routing requests alone");
+            drop(members);
+            return self.waterfall(request, ctx).await;
+        };
+
+        let own = answers.remove(0);
+        for (member, answer) in members.into_iter().skip(1).zip(answers) {
+            if let Some(reply) = member.reply {
+                let _ = reply.send(Ok(answer));
+            }
+        }
+        Ok(own)
+    }
+
+    /// Speculative drafting: Tier 0/1 drafts, Tier 2+ reviews (see `drafting`)
+    ///
+    /// Without a Tier 2 or 3 model to review, the request routes normally.
+    async fn speculate(&self, request: LlmRequest, ctx: &RouteContext) -> zed42_llm::Result<LlmResponse> {
+        let profile = self.resolve_profile(&request, ctx).await;
+        if profile.tier_2.is_none() && profile.tier_3.is_none() {
+            return self.waterfall(request, ctx).await;
+        }
+        let variant = ctx.variant();
+
+        let draft = match self.route(request.clone(), 0..=1, ctx).await {
+            Ok(draft) => draft,
+            Err(e) => {
+                warn!(error = %e, "Draft failed, generating directly on Tier 2+");
+                return self.route(request, 2..=3, ctx).await.map(|routed| routed.response);
+            }
+        };
+        let review_request = drafting::verifier_request(&request, &draft.response.content, &self.drafting);
+        let review = self.route(review_request, 2..=3, ctx).await?;
+
+        let (content, verdict, direct) = match drafting::apply_review(&draft.response.content, &review.response.content) {
+            Ok((content, verdict)) => (content, verdict, None),
+            Err(reason) => {
+                warn!(reason = %reason, "Verifier edits did not apply, generating directly on Tier 2+");
+                let direct = self.route(request.clone(), 2..=3, ctx).await?;
+                (direct.response.content.clone(), DraftVerdict::Rejected { reason }, Some(direct))
+            }
+        };
+
+        // What the verifier would have charged to write the draft's output itself
+        let direct_estimate = match self.ledger.get_rate(&review.response.model).await {
+            Ok(Some(rate)) => Some(
+                Decimal::from(draft.response.usage.prompt_tokens) / Decimal::from(1000) * rate.input_cost_per_1k
+                    + Decimal::from(draft.response.usage.completion_tokens) / Decimal::from(1000) * rate.output_cost_per_1k,
+            ),
+            _ => None,
+        };
+        let draft_cost = draft.cost.unwrap_or_default();
+        let verify_cost = review.cost.unwrap_or_default() + direct.as_ref().and_then(|d| d.cost).unwrap_or_default();
+        let record = SpeculationRecord {
+            draft_model: draft.response.model.clone(),
+            verifier_model: review.response.model.clone(),
+            verdict,
+            draft_cost,
+            verify_cost,
+            direct_estimate,
+            savings: direct_estimate.map(|estimate| estimate - draft_cost - verify_cost),
+        };
+        info!(
+            draft = %record.draft_model,
+            verifier = %record.verifier_model,
+            verdict = ?record.verdict,
+            spent = %record.spent(),
+            savings = ?record.savings,
+            "Speculative draft reviewed"
+        );
+
+        let mut usage = draft.response.usage.clone();
+        for extra in std::iter::once(&review).chain(direct.as_ref()) {
+            usage.prompt_tokens += extra.response.usage.prompt_tokens;
+            usage.completion_tokens += extra.response.usage.completion_tokens;
+            usage.total_tokens += extra.response.usage.total_tokens;
+            usage.cache_read_tokens += extra.response.usage.cache_read_tokens;
+            usage.cache_write_tokens += extra.response.usage.cache_write_tokens;
+        }
+        let (model, finish_reason) = match &direct {
+            Some(direct) => (direct.response.model.clone(), direct.response.finish_reason.clone()),
+            None => (review.response.model.clone(), draft.response.finish_reason.clone()),
+        };
+
+        self.log_routing(RoutingLog {
+            id: None,
+            timestamp: Utc::now(),
+            agent_id: request.agent_id.clone().unwrap_or_else(|| "default".to_string()),
+            task_id: request.task_id.clone(),
+            original_prompt_len: request.prompt.len(),
+            selected_tier: 0,
+            selected_model: "SPECULATIVE".to_string(),
+            retry_count: 0,
+            failover_reason: None,
+            // The draft and review calls carry the costs; this entry only compares them
+            cost: None,
+            is_critical: false,
+            speculation: Some(record),
+            variant: variant.clone(),
+            throttle: ctx.throttle.clone(),
+        }).await;
+
+        Ok(LlmResponse {
+            content,
+            model,
+            usage,
+            finish_reason,
+        })
+    }
+
+    /// The core routing loop: the profile's tiers within `tiers`, in order, with retries and failover
+    async fn route(
+        &self,
+        request: LlmRequest,
+        tiers: RangeInclusive<u8>,
+        ctx: &RouteContext,
+    ) -> zed42_llm::Result<Routed> {
         // 1. Identify Agent
         let agent_id = request.agent_id.as_deref().unwrap_or("default");
+        let variant = ctx.variant();
+        let throttle = ctx.throttle.clone();
+        // Tenant requests bill the tenant-scoped entity (and its budget root)
+        let billing_entity = billing_entity(&request)?;
 
         // 2. Check Backpressure
         let total = self.circuit_breaker.total_models();
@@ -145,6 +731,7 @@ impl LlmClient for Router {
                     id: None,
                     timestamp: Utc::now(),
                     agent_id: agent_id.to_string(),
+                    task_id: request.task_id.clone(),
                     original_prompt_len: request.prompt.len(),
                     selected_tier: 0,
                     selected_model: "BACKPRESSURE".to_string(),
@@ -152,6 +739,9 @@ impl LlmClient for Router {
                     failover_reason: Some(format!("ProviderExhaustion: {}/{} circuits open", open, total)),
                     cost: None,
                     is_critical: true,
+                    speculation: None,
+                    variant: variant.clone(),
+                    throttle: throttle.clone(),
                 }).await;
 
                 return Err(LlmError::Backpressure(wait));
@@ -159,37 +749,94 @@ impl LlmClient for Router {
         }
 
         // 2. Resolve Profile
-        let profile = self.get_profile(agent_id).await.unwrap_or_else(|_| {
-            ExecutionProfile::new(
-                "default",
-                request.config.clone(),
-            )
-        });
+        let profile = self.resolve_profile(&request, ctx).await;
 
         // 3. Determine Starting Tier
-        let start_tier = if let Some(RetryCause::ValidationFailure) = request.retry_cause {
+        let mut start_tier = if let Some(RetryCause::ValidationFailure) = request.retry_cause {
             info!("Smart Escalation: Validation failed, skipping to Tier 2");
             2
         } else {
-            1
-        };
+            0
+        }
+        .max(*tiers.start());
+
+        // Soft-limit throttle: stay at or below the cap, unless the profile has nothing there
+        let mut tiers = tiers;
+        if let Some(ref decision) = throttle {
+            let cap = decision.max_tier.min(*tiers.end());
+            let configured = [profile.tier_0.is_some(), true, profile.tier_2.is_some(), profile.tier_3.is_some()];
+            if (*tiers.start()..=cap).any(|tier| configured[tier as usize]) {
+                info!(entity = %decision.entity_id, pressure = decision.pressure, max_tier = cap, "Soft limit throttle: capping tiers");
+                start_tier = start_tier.min(cap);
+                tiers = *tiers.start()..=cap;
+            } else {
+                warn!(entity = %decision.entity_id, max_tier = cap, "No tier configured under the throttle cap, routing uncapped");
+            }
+        }
 
         // 4. Waterfall Loop
-        let tiers = [
+        let ladder = [
+            (0u8, profile.tier_0),
             (1u8, Some(profile.tier_1)),
             (2u8, profile.tier_2),
             (3u8, profile.tier_3),
         ];
 
         let mut last_error = LlmError::InvalidResponse("No models configured".to_string());
+        let requirements = RequestRequirements::from_request(&request);
 
-        for (tier_num, config_opt) in tiers.iter() {
-            if *tier_num < start_tier { continue; }
+        for (tier_num, config_opt) in ladder.iter() {
+            if *tier_num < start_tier || !tiers.contains(tier_num) { continue; }
             let config = match config_opt {
                 Some(c) => c,
                 None => continue,
             };
 
+            // Compress the prompt if it would overflow this model's context window
+            let mut tier_request = request.clone();
+            let mut tier_requirements = requirements.clone();
+            tier_requirements.max_output_tokens = config.max_tokens;
+            if let Some(caps) = self.capabilities.get(&config.model) {
+                let budget = caps.context_window.saturating_sub(config.max_tokens.unwrap_or(0));
+                if tier_requirements.estimated_prompt_tokens > budget {
+                    let report = self.compressor.compress(&mut tier_request, budget);
+                    info!(
+                        model = %config.model,
+                        from = report.original_tokens,
+                        to = report.compressed_tokens,
+                        dropped = report.dropped_segments,
+                        summarized = report.summarized_segments,
+                        truncated = report.truncated,
+                        "Compressed prompt for tier {}", tier_num
+                    );
+                    tier_requirements = RequestRequirements::from_request(&tier_request);
+                    tier_requirements.max_output_tokens = config.max_tokens;
+                }
+            }
+
+            // Check Capabilities (context window, JSON schema, vision)
+            if let Err(reason) = self.capabilities.check(&config.model, &tier_requirements) {
+                warn!(model = %config.model, reason = %reason, "Model cannot serve request, skipping tier {}", tier_num);
+                self.log_routing(RoutingLog {
+                    id: None,
+                    timestamp: Utc::now(),
+                    agent_id: agent_id.to_string(),
+                    task_id: request.task_id.clone(),
+                    original_prompt_len: request.prompt.len(),
+                    selected_tier: *tier_num,
+                    selected_model: config.model.clone(),
+                    retry_count: 0,
+                    failover_reason: Some(format!("CapabilityMismatch: {}", reason)),
+                    cost: None,
+                    is_critical: false,
+                    speculation: None,
+                    variant: variant.clone(),
+                    throttle: throttle.clone(),
+                }).await;
+                last_error = LlmError::InvalidResponse(format!("No capable model: {}", reason));
+                continue;
+            }
+
             // Check Circuit Breaker
             if self.circuit_breaker.is_open(&config.model) {
                 warn!(model = %config.model, "Circuit open, skipping tier {}", tier_num);
@@ -200,29 +847,50 @@ impl LlmClient for Router {
             let client = self.get_client(&config.model).await;
 
             // Financial Handshake
-            // Using strict Decimal for estimation
-            let est_cost = match *tier_num {
-                1 => Decimal::new(1, 2), // $0.01
-                2 => Decimal::new(5, 2), // $0.05
-                _ => Decimal::new(20, 2), // $0.20
-            };
+            // Tier 0 runs on local hardware: nothing to lease or bill
+            let mut lease_guard = if *tier_num == 0 {
+                None
+            } else {
+                // Using strict Decimal for estimation
+                let tier_default = match *tier_num {
+                    1 => Decimal::new(1, 2), // $0.01
+                    2 => Decimal::new(5, 2), // $0.05
+                    _ => Decimal::new(20, 2), // $0.20
+                };
+                let est_cost = self.lease_estimator.estimate(&billing_entity, &config.model, tier_default);
 
-            let lease_id = match self.ledger.request_lease(agent_id, est_cost).await {
-                Ok(id) => id,
-                Err(e) => {
-                    error!(agent = %agent_id, error = %e, "Budget denied");
-                    return Err(LlmError::ApiError(format!("Budget exceeded: {}", e)));
+                // Critical requests fall back on the reserve pool once the budget is exhausted
+                let priority = if request.critical { LeasePriority::Critical } else { LeasePriority::Normal };
+                let lease_id = match self.ledger.request_priority_lease(&billing_entity, est_cost, priority).await {
+                    Ok(id) => id,
+                    Err(e) => {
+                        error!(agent = %agent_id, error = %e, "Budget denied");
+                        return Err(LlmError::ApiError(format!("Budget exceeded: {}", e)));
+                    }
+                };
+
+                // Journal before the provider sees the request so a crash can't orphan the lease
+                if let Err(e) = self.lease_journal.open(&lease_id).await {
+                    warn!(lease_id = %lease_id, error = %e, "Failed to journal lease; it won't be recovered after a crash");
                 }
-            };
+                let lease_guard = LeaseGuard::new(lease_id, Arc::clone(&self.ledger), Arc::clone(&self.lease_journal));
 
-            let mut lease_guard = LeaseGuard::new(lease_id, Arc::clone(&self.ledger));
+                // Chaos: abandon the lease as if the request died mid-flight; the guard must release it
+                if let Err(e) = zed42_core::chaos::fail_point(zed42_core::chaos::FaultPoint::PoisonedLease) {
+                    warn!(model = %config.model, error = %e, "Lease poisoned, abandoning tier {}", tier_num);
+                    drop(lease_guard);
+                    last_error = LlmError::ApiError(e.to_string());
+                    continue;
+                }
+                Some(lease_guard)
+            };
 
             // Execute with Retries (Backoff)
             let mut attempt = 0;
             let max_retries = 2;
             
             loop {
-                let mut req_clone = request.clone();
+                let mut req_clone = tier_request.clone();
                 req_clone.config = config.clone();
 
                 match client.complete(req_clone).await {
@@ -230,31 +898,50 @@ impl LlmClient for Router {
                         self.circuit_breaker.report_success(&config.model);
                         
                         // Settle Ledger
-                        let usage = Usage {
-                            input_tokens: response.usage.prompt_tokens as u32,
-                            output_tokens: response.usage.completion_tokens as u32,
-                            model: config.model.clone(),
+                        let cost = match lease_guard.as_mut() {
+                            Some(guard) => {
+                                let usage = Usage {
+                                    input_tokens: response.usage.prompt_tokens as u32,
+                                    output_tokens: response.usage.completion_tokens as u32,
+                                    model: config.model.clone(),
+                                    cache_read_tokens: response.usage.cache_read_tokens as u32,
+                                    cache_write_tokens: response.usage.cache_write_tokens as u32,
+                                };
+
+                                let actual_lease_id = guard.settle();
+                                let receipt = self.ledger.commit_usage(&actual_lease_id, usage).await;
+                                if let Err(e) = self.lease_journal.close(&actual_lease_id).await {
+                                    warn!(lease_id = %actual_lease_id, error = %e, "Failed to clear lease journal entry");
+                                }
+                                let cost = receipt.ok().map(|r| r.cost);
+                                if let Some(cost) = cost {
+                                    self.lease_estimator.observe(&billing_entity, &config.model, cost);
+                                }
+                                cost
+                            }
+                            None => Some(Decimal::ZERO),
                         };
                         
-                        let actual_lease_id = lease_guard.settle();
-                        let receipt = self.ledger.commit_usage(&actual_lease_id, usage).await;
-                        
                         // Log
                         self.log_routing(RoutingLog {
                             id: None,
                             timestamp: Utc::now(),
                             agent_id: agent_id.to_string(),
+                            task_id: request.task_id.clone(),
                             original_prompt_len: request.prompt.len(),
                             selected_tier: *tier_num,
                             selected_model: config.model.clone(),
                             retry_count: attempt,
                             failover_reason: None,
-                            cost: receipt.ok().map(|r| r.cost),
+                            cost,
                             is_critical: false,
+                            speculation: None,
+                            variant: variant.clone(),
+                            throttle: throttle.clone(),
                         }).await;
 
                         response.model = config.model.clone();
-                        return Ok(response);
+                        return Ok(Routed { response, cost });
                     },
                     Err(e) => {
                         attempt += 1;
@@ -270,6 +957,22 @@ impl LlmClient for Router {
                         }
                         
                         self.circuit_breaker.report_failure(&config.model);
+                        self.log_routing(RoutingLog {
+                            id: None,
+                            timestamp: Utc::now(),
+                            agent_id: agent_id.to_string(),
+                            task_id: request.task_id.clone(),
+                            original_prompt_len: request.prompt.len(),
+                            selected_tier: *tier_num,
+                            selected_model: config.model.clone(),
+                            retry_count: attempt,
+                            failover_reason: Some(format!("Tier {} failed: {}", tier_num, e)),
+                            cost: None,
+                            is_critical: false,
+                            speculation: None,
+                            variant: variant.clone(),
+                            throttle: throttle.clone(),
+                        }).await;
                         last_error = e;
                         break;
                     }
@@ -283,6 +986,7 @@ impl LlmClient for Router {
             id: None,
             timestamp: Utc::now(),
             agent_id: agent_id.to_string(),
+            task_id: request.task_id.clone(),
             original_prompt_len: request.prompt.len(),
             selected_tier: 0,
             selected_model: "NONE".to_string(),
@@ -290,18 +994,50 @@ impl LlmClient for Router {
             failover_reason: Some(format!("All tiers failed: {:?}", last_error)),
             cost: None,
             is_critical: true,
+            speculation: None,
+            variant: variant.clone(),
+            throttle: throttle.clone(),
         }).await;
 
         Err(last_error)
     }
+}
+
+#[async_trait]
+impl LlmClient for Router {
+    async fn complete(&self, mut request: LlmRequest) -> zed42_llm::Result<LlmResponse> {
+        // Before the layers, so caches key on the variant's prompt
+        let assignment = self.assign_variant(&mut request);
+        let throttle = self.throttle_decision(&request).await;
+        for (depth, layer) in self.layers.iter().enumerate() {
+            match layer.on_request(&mut request).await {
+                Ok(None) => {}
+                Ok(Some(response)) => {
+                    info!(layer = layer.name(), "Request answered by router layer");
+                    return self.unwind_layers(depth, &request, Ok(response)).await;
+                }
+                Err(e) => return self.unwind_layers(depth, &request, Err(e)).await,
+            }
+        }
+
+        let ctx = RouteContext { assignment, throttle };
+        let result = match ctx.throttle {
+            Some(ref decision) if decision.batch_size > 1 && throttle::is_batchable(&request) => {
+                self.batched(request.clone(), &ctx, decision).await
+            }
+            // Drafting pays for two tiers; a throttled budget routes directly
+            _ if request.speculative && ctx.throttle.is_none() => self.speculate(request.clone(), &ctx).await,
+            _ => self.waterfall(request.clone(), &ctx).await,
+        };
+        self.unwind_layers(self.layers.len(), &request, result).await
+    }
 
     async fn stream(&self, _request: LlmRequest) -> zed42_llm::Result<Vec<StreamChunk>> {
         Err(LlmError::ApiError("Streaming not yet implemented in Router".to_string()))
     }
 
     async fn embed(&self, request: EmbeddingRequest) -> zed42_llm::Result<EmbeddingResponse> {
-        // Forward to default client or specific embedding client
-        // For MVP, just route to default
-        self.default_client.embed(request).await
+        // Same prefix routing as completions, so local models embed locally
+        self.get_client(&request.model).await.embed(request).await
     }
 }
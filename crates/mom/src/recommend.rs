@@ -0,0 +1,301 @@
+//! Usage-based model recommendations
+//!
+//! Aggregates routing logs and critique outcomes per agent, tier and model,
+//! then flags combinations that fail too often ("refactorer tier 1 fails
+//! validation 62% of the time"). When another model has a better record at
+//! the same tier with enough samples, the recommendation names it as the
+//! swap candidate.
+//!
+//! Critique outcomes come from review loops (self-critique, schema
+//! validation) and are recorded with `Router::record_critique`.
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::types::RoutingLog;
+
+/// Result of reviewing one model output
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CritiqueOutcome {
+    pub timestamp: DateTime<Utc>,
+    pub agent_id: String,
+    pub tier: u8,
+    pub model: String,
+    pub passed: bool,
+}
+
+impl CritiqueOutcome {
+    pub fn new(agent_id: impl Into<String>, tier: u8, model: impl Into<String>, passed: bool) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            agent_id: agent_id.into(),
+            tier,
+            model: model.into(),
+            passed,
+        }
+    }
+}
+
+/// Thresholds for raising a recommendation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecommendationPolicy {
+    /// Calls (or reviews) needed before a rate is trusted
+    pub min_samples: usize,
+    /// Failover or validation failure rate above which a model is flagged
+    pub max_failure_rate: f64,
+}
+
+impl Default for RecommendationPolicy {
+    fn default() -> Self {
+        Self {
+            min_samples: 10,
+            max_failure_rate: 0.3,
+        }
+    }
+}
+
+/// What a model did for one agent at one tier
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ModelUsage {
+    pub agent_id: String,
+    pub tier: u8,
+    pub model: String,
+    pub calls: usize,
+    pub failovers: usize,
+    pub reviews: usize,
+    pub rejected: usize,
+    pub cost: Decimal,
+}
+
+impl ModelUsage {
+    pub fn failover_rate(&self) -> f64 {
+        rate(self.failovers, self.calls)
+    }
+
+    pub fn validation_failure_rate(&self) -> f64 {
+        rate(self.rejected, self.reviews)
+    }
+}
+
+fn rate(part: usize, whole: usize) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 / whole as f64
+    }
+}
+
+/// Which signal triggered a recommendation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecommendationReason {
+    /// Outputs rejected by critique or schema validation
+    ValidationFailures,
+    /// Calls that failed over to the next tier
+    Failovers,
+}
+
+/// A suggested profile change
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelRecommendation {
+    pub agent_id: String,
+    pub tier: u8,
+    pub model: String,
+    pub reason: RecommendationReason,
+    pub failure_rate: f64,
+    pub samples: usize,
+    /// Model with a better record at the same tier, if any
+    pub suggested_model: Option<String>,
+    pub suggested_failure_rate: Option<f64>,
+    /// Human-readable summary
+    pub message: String,
+}
+
+/// Per agent/tier/model usage, skipping synthetic log entries
+pub fn aggregate(logs: &[RoutingLog], critiques: &[CritiqueOutcome]) -> Vec<ModelUsage> {
+    let mut usage: BTreeMap<(String, u8, String), ModelUsage> = BTreeMap::new();
+
+    for log in logs.iter().filter(|l| !l.is_synthetic()) {
+        let stats = usage_entry(&mut usage, &log.agent_id, log.selected_tier, &log.selected_model);
+        stats.calls += 1;
+        if log.failover_reason.is_some() {
+            stats.failovers += 1;
+        }
+        stats.cost += log.cost.unwrap_or_default();
+    }
+    for critique in critiques {
+        let stats = usage_entry(&mut usage, &critique.agent_id, critique.tier, &critique.model);
+        stats.reviews += 1;
+        if !critique.passed {
+            stats.rejected += 1;
+        }
+    }
+    usage.into_values().collect()
+}
+
+fn usage_entry<'a>(
+    usage: &'a mut BTreeMap<(String, u8, String), ModelUsage>,
+    agent_id: &str,
+    tier: u8,
+    model: &str,
+) -> &'a mut ModelUsage {
+    usage
+        .entry((agent_id.to_string(), tier, model.to_string()))
+        .or_insert_with(|| ModelUsage {
+            agent_id: agent_id.to_string(),
+            tier,
+            model: model.to_string(),
+            ..Default::default()
+        })
+}
+
+/// Flag agent/tier/model combinations that fail more often than the policy allows
+pub fn recommend(logs: &[RoutingLog], critiques: &[CritiqueOutcome], policy: &RecommendationPolicy) -> Vec<ModelRecommendation> {
+    let usage = aggregate(logs, critiques);
+    let mut recommendations = Vec::new();
+
+    for stats in &usage {
+        let signals = [
+            (RecommendationReason::ValidationFailures, stats.validation_failure_rate(), stats.reviews, stats.rejected),
+            (RecommendationReason::Failovers, stats.failover_rate(), stats.calls, stats.failovers),
+        ];
+        let Some(&(reason, failure_rate, samples, failures)) = signals
+            .iter()
+            .find(|(_, rate, samples, _)| *samples >= policy.min_samples && *rate > policy.max_failure_rate)
+        else {
+            continue;
+        };
+
+        let alternative = best_alternative(&usage, stats, reason, policy);
+        let what = match reason {
+            RecommendationReason::ValidationFailures => "fails validation",
+            RecommendationReason::Failovers => "fails over",
+        };
+        let mut message = format!(
+            "{} tier {} {} {:.0}% of the time ({} of {} on {})",
+            stats.agent_id,
+            stats.tier,
+            what,
+            failure_rate * 100.0,
+            failures,
+            samples,
+            stats.model
+        );
+        match &alternative {
+            Some((model, rate)) => message.push_str(&format!(
+                "; consider swapping {} for {} ({:.0}% at tier {})",
+                stats.model,
+                model,
+                rate * 100.0,
+                stats.tier
+            )),
+            None => message.push_str(&format!("; consider a stronger model for tier {}", stats.tier)),
+        }
+
+        recommendations.push(ModelRecommendation {
+            agent_id: stats.agent_id.clone(),
+            tier: stats.tier,
+            model: stats.model.clone(),
+            reason,
+            failure_rate,
+            samples,
+            suggested_model: alternative.as_ref().map(|(model, _)| model.clone()),
+            suggested_failure_rate: alternative.map(|(_, rate)| rate),
+            message,
+        });
+    }
+
+    recommendations.sort_by(|a, b| b.failure_rate.total_cmp(&a.failure_rate));
+    recommendations
+}
+
+/// Lowest-failure model at the same tier across all agents, if it beats `current`
+fn best_alternative(
+    usage: &[ModelUsage],
+    current: &ModelUsage,
+    reason: RecommendationReason,
+    policy: &RecommendationPolicy,
+) -> Option<(String, f64)> {
+    let mut pooled: BTreeMap<&str, (usize, usize)> = BTreeMap::new();
+    for stats in usage.iter().filter(|s| s.tier == current.tier && s.model != current.model) {
+        let entry = pooled.entry(stats.model.as_str()).or_default();
+        match reason {
+            RecommendationReason::ValidationFailures => {
+                entry.0 += stats.rejected;
+                entry.1 += stats.reviews;
+            }
+            RecommendationReason::Failovers => {
+                entry.0 += stats.failovers;
+                entry.1 += stats.calls;
+            }
+        }
+    }
+    pooled
+        .into_iter()
+        .filter(|(_, (_, samples))| *samples >= policy.min_samples)
+        .map(|(model, (failures, samples))| (model.to_string(), rate(failures, samples)))
+        .filter(|(_, rate)| *rate <= policy.max_failure_rate)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log(agent: &str, tier: u8, model: &str, failover: bool) -> RoutingLog {
+        RoutingLog {
+            id: None,
+            timestamp: Utc::now(),
+            agent_id: agent.to_string(),
+            task_id: None,
+            original_prompt_len: 10,
+            selected_tier: tier,
+            selected_model: model.to_string(),
+            retry_count: 0,
+            failover_reason: failover.then(|| "Tier failed: timeout".to_string()),
+            cost: None,
+            is_critical: false,
+            speculation: None,
+            variant: None,
+            throttle: None,
+        }
+    }
+
+    fn critiques(agent: &str, model: &str, passed: usize, failed: usize) -> Vec<CritiqueOutcome> {
+        (0..passed + failed)
+            .map(|i| CritiqueOutcome::new(agent, 1, model, i < passed))
+            .collect()
+    }
+
+    #[test]
+    fn test_flags_validation_failures_and_suggests_swap() {
+        let mut reviews = critiques("refactorer", "model-x", 8, 13);
+        reviews.extend(critiques("implementer", "model-y", 18, 2));
+
+        let recommendations = recommend(&[], &reviews, &RecommendationPolicy::default());
+        assert_eq!(recommendations.len(), 1);
+        let rec = &recommendations[0];
+        assert_eq!(rec.agent_id, "refactorer");
+        assert_eq!(rec.reason, RecommendationReason::ValidationFailures);
+        assert_eq!(rec.suggested_model.as_deref(), Some("model-y"));
+        assert_eq!(
+            rec.message,
+            "refactorer tier 1 fails validation 62% of the time (13 of 21 on model-x); \
+             consider swapping model-x for model-y (10% at tier 1)"
+        );
+    }
+
+    #[test]
+    fn test_needs_enough_samples() {
+        let logs: Vec<RoutingLog> = (0..6).map(|i| log("tester", 2, "model-z", i % 2 == 0)).collect();
+        assert!(recommend(&logs, &[], &RecommendationPolicy::default()).is_empty());
+
+        let policy = RecommendationPolicy { min_samples: 5, ..Default::default() };
+        let recommendations = recommend(&logs, &[], &policy);
+        assert_eq!(recommendations[0].reason, RecommendationReason::Failovers);
+        assert!(recommendations[0].suggested_model.is_none());
+        assert!(recommendations[0].message.ends_with("consider a stronger model for tier 2"));
+    }
+}
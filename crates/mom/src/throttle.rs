@@ -0,0 +1,210 @@
+//! Request batching for throttled budgets
+//!
+//! Past a budget's soft limit its [`ThrottlePolicy`] may ask for batching:
+//! compatible requests arriving within a short window are merged into one
+//! prompt, routed once, and the answer is split back out. The first request
+//! of a batch leads it; the others wait for their share of the answer.
+//!
+//! [`ThrottlePolicy`]: zed42_ledger::throttle::ThrottlePolicy
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::sync::{oneshot, Notify};
+use zed42_llm::{LlmRequest, LlmResponse, Usage};
+
+/// Line that opens each answer in a merged response, followed by its number
+pub const ANSWER_MARKER: &str = "### Answer";
+
+/// Channel a follower's share of the answer is sent on
+pub(crate) type Reply = oneshot::Sender<zed42_llm::Result<LlmResponse>>;
+
+/// A request in a batch; the leader's own has no reply channel
+pub(crate) struct Member {
+    pub request: LlmRequest,
+    pub reply: Option<Reply>,
+}
+
+pub(crate) enum Joined {
+    /// Route the batch and answer the other members
+    Leader(Vec<Member>),
+    /// Wait for the leader; a closed channel means route alone
+    Follower(oneshot::Receiver<zed42_llm::Result<LlmResponse>>),
+}
+
+struct Pending {
+    members: Vec<(LlmRequest, Reply)>,
+    size: usize,
+    full: Arc<Notify>,
+}
+
+/// Open batches by key (billing entity, agent, variant and system prompt)
+#[derive(Default)]
+pub(crate) struct Batcher {
+    pending: Mutex<HashMap<String, Pending>>,
+}
+
+impl Batcher {
+    /// Join the open batch for `key`, or open one and lead it
+    ///
+    /// The leader waits until the batch holds `size` requests or `window`
+    /// passes. Requests arriving after that open the next batch.
+    pub(crate) async fn join(&self, key: String, request: LlmRequest, size: usize, window: Duration) -> Joined {
+        let full = {
+            let mut pending = self.pending.lock().unwrap();
+            if let Some(batch) = pending.get_mut(&key) {
+                let (tx, rx) = oneshot::channel();
+                batch.members.push((request, tx));
+                if batch.members.len() + 1 >= batch.size {
+                    batch.full.notify_one();
+                }
+                return Joined::Follower(rx);
+            }
+            let full = Arc::new(Notify::new());
+            pending.insert(key.clone(), Pending { members: Vec::new(), size, full: full.clone() });
+            full
+        };
+
+        // Dropping the leader mid-wait closes the batch so followers route alone
+        let guard = CloseOnDrop { batcher: self, key: &key };
+        tokio::select! {
+            _ = full.notified() => {}
+            _ = tokio::time::sleep(window) => {}
+        }
+        let followers = guard.close();
+
+        let mut members = vec![Member { request, reply: None }];
+        members.extend(followers.into_iter().map(|(request, reply)| Member { request, reply: Some(reply) }));
+        Joined::Leader(members)
+    }
+
+    fn close(&self, key: &str) -> Vec<(LlmRequest, Reply)> {
+        self.pending.lock().unwrap().remove(key).map(|batch| batch.members).unwrap_or_default()
+    }
+}
+
+struct CloseOnDrop<'a> {
+    batcher: &'a Batcher,
+    key: &'a str,
+}
+
+impl CloseOnDrop<'_> {
+    fn close(self) -> Vec<(LlmRequest, Reply)> {
+        let members = self.batcher.close(self.key);
+        std::mem::forget(self);
+        members
+    }
+}
+
+impl Drop for CloseOnDrop<'_> {
+    fn drop(&mut self) {
+        self.batcher.close(self.key);
+    }
+}
+
+/// Whether a request can share a call with others
+///
+/// Structured output, images and speculative drafting each need the whole call.
+pub(crate) fn is_batchable(request: &LlmRequest) -> bool {
+    request.json_schema.is_none() && request.images.is_empty() && !request.speculative
+}
+
+/// One prompt asking for every request's answer under numbered markers
+///
+/// The merged request takes the first request's settings; output limits add up.
+pub(crate) fn merge(requests: &[&LlmRequest]) -> LlmRequest {
+    let first = requests[0];
+    let mut prompt = format!(
+        "Answer each of the following {} independent requests in order. Begin each answer with a line \
+         `{} <n>` matching its request number and write nothing else around the answers.",
+        requests.len(),
+        ANSWER_MARKER
+    );
+    for (i, request) in requests.iter().enumerate() {
+        prompt.push_str(&format!("\n\n### Request {}\n{}", i + 1, request.rendered_prompt()));
+    }
+
+    let mut merged = first.clone();
+    merged.prompt = prompt;
+    merged.segments = Vec::new();
+    // A batch serves several tasks; cost isn't attributed to any one of them
+    merged.task_id = None;
+    merged.config.max_tokens = requests
+        .iter()
+        .map(|r| r.config.max_tokens)
+        .sum::<Option<usize>>();
+    merged
+}
+
+/// Split a merged response into `count` answers, or `None` if markers are missing
+///
+/// Usage is shared out evenly; the first answer takes any remainder.
+pub(crate) fn split(response: &LlmResponse, count: usize) -> Option<Vec<LlmResponse>> {
+    let mut answers: Vec<Vec<&str>> = Vec::new();
+    for line in response.content.lines() {
+        let marker = line
+            .trim()
+            .strip_prefix(ANSWER_MARKER)
+            .and_then(|rest| rest.trim().trim_end_matches(':').parse::<usize>().ok());
+        match marker {
+            Some(n) if n == answers.len() + 1 => answers.push(Vec::new()),
+            _ if answers.is_empty() && line.trim().is_empty() => {}
+            _ => answers.last_mut()?.push(line),
+        }
+    }
+    if answers.len() != count {
+        return None;
+    }
+
+    Some(
+        answers
+            .into_iter()
+            .enumerate()
+            .map(|(i, lines)| LlmResponse {
+                content: lines.join("\n").trim().to_string(),
+                model: response.model.clone(),
+                usage: share(&response.usage, count, i),
+                finish_reason: response.finish_reason.clone(),
+            })
+            .collect(),
+    )
+}
+
+fn share(usage: &Usage, count: usize, index: usize) -> Usage {
+    let part = |total: usize| total / count + if index == 0 { total % count } else { 0 };
+    Usage {
+        prompt_tokens: part(usage.prompt_tokens),
+        completion_tokens: part(usage.completion_tokens),
+        total_tokens: part(usage.total_tokens),
+        cache_read_tokens: part(usage.cache_read_tokens),
+        cache_write_tokens: part(usage.cache_write_tokens),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_and_split_round_trip() {
+        let requests = [LlmRequest::new("2 + 2?".to_string()), LlmRequest::new("Capital of France?".to_string())];
+        let merged = merge(&requests.iter().collect::<Vec<_>>());
+        assert!(merged.prompt.contains("### Request 1\n2 + 2?"));
+        assert!(merged.prompt.contains("### Request 2\nCapital of France?"));
+
+        let response = LlmResponse {
+            content: "### Answer 1\n4\n\n### Answer 2:\nParis".to_string(),
+            model: "gpt-4o-mini".to_string(),
+            usage: Usage { prompt_tokens: 41, completion_tokens: 10, total_tokens: 51, ..Default::default() },
+            finish_reason: "stop".to_string(),
+        };
+        let answers = split(&response, 2).unwrap();
+        assert_eq!(answers[0].content, "4");
+        assert_eq!(answers[1].content, "Paris");
+        assert_eq!(answers[0].usage.prompt_tokens + answers[1].usage.prompt_tokens, 41);
+
+        assert!(split(&response, 3).is_none());
+        assert!(split(&LlmResponse { content: "4 and Paris".to_string(), ..response }, 2).is_none());
+    }
+}
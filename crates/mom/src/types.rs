@@ -7,6 +7,9 @@ use chrono::{DateTime, Utc};
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExecutionProfile {
     pub agent_id: String,
+    /// Local model tried before any paid tier; runs without a ledger lease
+    #[serde(default)]
+    pub tier_0: Option<ModelConfig>,
     pub tier_1: ModelConfig,
     pub tier_2: Option<ModelConfig>,
     pub tier_3: Option<ModelConfig>,
@@ -16,12 +19,19 @@ impl ExecutionProfile {
     pub fn new(agent_id: &str, tier_1: ModelConfig) -> Self {
         Self {
             agent_id: agent_id.to_string(),
+            tier_0: None,
             tier_1,
             tier_2: None,
             tier_3: None,
         }
     }
 
+    /// Serve from a local model first (see `zed42_llm::LocalLlmClient`)
+    pub fn with_tier_0(mut self, config: ModelConfig) -> Self {
+        self.tier_0 = Some(config);
+        self
+    }
+
     pub fn with_tier_2(mut self, config: ModelConfig) -> Self {
         self.tier_2 = Some(config);
         self
@@ -36,10 +46,14 @@ impl ExecutionProfile {
 /// Log of a routing decision and execution
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RoutingLog {
-    #[serde(skip)]
+    /// Record key, set on logs read back from the database
+    #[serde(default, skip_serializing)]
     pub id: Option<String>,
     pub timestamp: DateTime<Utc>,
     pub agent_id: String,
+    /// Task the call was made for, when the caller tagged the request
+    #[serde(default)]
+    pub task_id: Option<String>,
     pub original_prompt_len: usize,
     pub selected_tier: u8,
     pub selected_model: String,
@@ -47,4 +61,23 @@ pub struct RoutingLog {
     pub failover_reason: Option<String>,
     pub cost: Option<Decimal>,
     pub is_critical: bool,
+    /// Draft/review cost comparison on `SPECULATIVE` summary entries
+    #[serde(default)]
+    pub speculation: Option<crate::drafting::SpeculationRecord>,
+    /// Experiment arm that served the call
+    #[serde(default)]
+    pub variant: Option<crate::experiment::VariantTag>,
+    /// Soft-limit throttle the call was routed under
+    #[serde(default)]
+    pub throttle: Option<zed42_ledger::throttle::ThrottleDecision>,
+}
+
+/// Model names recorded on entries that aren't real model calls
+pub const SYNTHETIC_MODELS: [&str; 3] = ["BACKPRESSURE", "NONE", "SPECULATIVE"];
+
+impl RoutingLog {
+    /// Backpressure, total-failure and speculation summaries, as opposed to Tier 0 local calls
+    pub fn is_synthetic(&self) -> bool {
+        self.selected_tier == 0 && SYNTHETIC_MODELS.contains(&self.selected_model.as_str())
+    }
 }
@@ -0,0 +1,91 @@
+//! Fault injection against the Router; run with `--features chaos`
+#![cfg(feature = "chaos")]
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use rust_decimal_macros::dec;
+use surrealdb::engine::any::{connect, Any};
+use surrealdb::Surreal;
+use zed42_core::chaos::{chaos, FaultPoint, FaultSpec};
+use zed42_ledger::types::{Budget, BudgetStatus, RateTableEntry};
+use zed42_ledger::IntelligenceLedger;
+use zed42_llm::{LlmClient, LlmRequest, MockLlmClient, ModelConfig};
+use zed42_mom::{types::ExecutionProfile, Router};
+
+async fn setup(responses: Vec<&str>) -> (Router, Surreal<Any>) {
+    let db = connect("mem://").await.unwrap();
+    db.use_ns("zed42").use_db("chaos").await.unwrap();
+    let ledger = Arc::new(IntelligenceLedger::new(db.clone()));
+    ledger.set_budget(Budget {
+        entity_id: "default".to_string(),
+        hard_limit: dec!(100.0),
+        soft_limit: dec!(50.0),
+        spent: dec!(0.0),
+        currency: "USD".to_string(),
+        updated_at: chrono::Utc::now(),
+        status: BudgetStatus::Active,
+        tags: Default::default(),
+    }).await.unwrap();
+    for model in ["lease-guard-cleanup", "tier1-model", "tier2-model"] {
+        ledger.set_rate(RateTableEntry {
+            model: model.to_string(),
+            input_cost_per_1k: dec!(0.0),
+            output_cost_per_1k: dec!(0.0),
+            ..Default::default()
+        }).await.unwrap();
+    }
+
+    let client = Arc::new(MockLlmClient::with_responses(responses.into_iter().map(String::from).collect()));
+    let router = Router::new(ledger, db.clone(), client);
+    let profile = ExecutionProfile::new("default", ModelConfig { model: "tier1-model".to_string(), ..ModelConfig::default() })
+        .with_tier_2(ModelConfig { model: "tier2-model".to_string(), ..ModelConfig::default() });
+    let _: Option<ExecutionProfile> = db.create(("model_profiles", "default")).content(profile).await.unwrap();
+    (router, db)
+}
+
+async fn open_leases(db: &Surreal<Any>) -> i64 {
+    let mut response = db.query("SELECT count() FROM leases GROUP ALL").await.unwrap();
+    let count: Option<i64> = response.take("count").unwrap();
+    count.unwrap_or(0)
+}
+
+#[tokio::test]
+async fn test_poisoned_lease_is_released_and_next_tier_serves() {
+    let _session = chaos().session(7).await;
+    let (router, db) = setup(vec!["ok"]).await;
+    chaos().arm(FaultPoint::PoisonedLease, FaultSpec::always().times(1));
+
+    let response = router
+        .complete(LlmRequest::new("Hello".to_string()).agent("default".to_string()))
+        .await
+        .unwrap();
+    assert_eq!(response.model, "tier2-model");
+    assert_eq!(chaos().triggered(FaultPoint::PoisonedLease), 1);
+
+    // The abandoned lease is released by the guard's cleanup task
+    for _ in 0..50 {
+        if open_leases(&db).await == 0 && router.open_lease_count() == 0 {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("Poisoned lease was never released");
+}
+
+#[tokio::test]
+async fn test_ledger_database_fault_surfaces_as_error() {
+    let _session = chaos().session(7).await;
+    let (router, db) = setup(vec!["ok"]).await;
+    chaos().arm(FaultPoint::Database, FaultSpec::always());
+
+    let err = router
+        .complete(LlmRequest::new("Hello".to_string()).agent("default".to_string()))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("injected fault"), "{}", err);
+    chaos().disarm_all();
+
+    assert_eq!(open_leases(&db).await, 0);
+    assert_eq!(router.open_lease_count(), 0);
+}
@@ -5,12 +5,13 @@ use surrealdb::engine::any::{connect, Any};
 use surrealdb::Surreal;
 use zed42_ledger::{IntelligenceLedger, types::{Budget, BudgetStatus}};
 use zed42_llm::{LlmClient, LlmRequest, LlmResponse, LlmError, ModelConfig, RetryCause, Usage, EmbeddingRequest, EmbeddingResponse};
-use zed42_mom::{Router, types::ExecutionProfile};
+use zed42_mom::{Router, experiment::{Experiment, Variant}, types::ExecutionProfile};
 
 #[derive(Clone)]
 struct TrackingClient {
     name: String,
     calls: Arc<Mutex<Vec<String>>>, 
+    system_prompts: Arc<Mutex<Vec<Option<String>>>>,
     responses: Arc<Mutex<Vec<Result<LlmResponse, LlmError>>>>,
 }
 
@@ -19,6 +20,7 @@ impl TrackingClient {
         Self {
             name: name.to_string(),
             calls: Arc::new(Mutex::new(Vec::new())),
+            system_prompts: Arc::new(Mutex::new(Vec::new())),
             responses: Arc::new(Mutex::new(Vec::new())),
         }
     }
@@ -41,6 +43,7 @@ impl TrackingClient {
 impl LlmClient for TrackingClient {
     async fn complete(&self, request: LlmRequest) -> zed42_llm::Result<LlmResponse> {
         self.calls.lock().unwrap().push(format!("{}: {}", self.name, request.prompt));
+        self.system_prompts.lock().unwrap().push(request.system_prompt.clone());
         self.pop_response()
     }
 
@@ -74,6 +77,7 @@ async fn setup_env() -> (Router, Arc<IntelligenceLedger>, Surreal<Any>) {
         currency: "USD".to_string(),
         updated_at: chrono::Utc::now(),
         status: BudgetStatus::Active,
+        tags: Default::default(),
     }).await.unwrap();
 
     // Register cleanup model rate to avoid settlement failures during LeaseGuard drop
@@ -81,6 +85,7 @@ async fn setup_env() -> (Router, Arc<IntelligenceLedger>, Surreal<Any>) {
         model: "lease-guard-cleanup".to_string(),
         input_cost_per_1k: dec!(0.0),
         output_cost_per_1k: dec!(0.0),
+        ..Default::default()
     }).await.unwrap();
 
     (router, ledger, db)
@@ -104,7 +109,7 @@ async fn test_waterfall_failover() {
     tier2_client.push_response(Ok(LlmResponse {
         content: "Success".to_string(),
         model: "tier2-model".to_string(),
-        usage: Usage { prompt_tokens: 10, completion_tokens: 10, total_tokens: 20 },
+        usage: Usage { prompt_tokens: 10, completion_tokens: 10, total_tokens: 20, ..Default::default() },
         finish_reason: "stop".to_string(),
     }));
 
@@ -147,7 +152,7 @@ async fn test_smart_escalation() {
     tier2_client.push_response(Ok(LlmResponse {
         content: "Smart Success".to_string(),
         model: "tier2-model".to_string(),
-        usage: Usage { prompt_tokens: 10, completion_tokens: 10, total_tokens: 20 },
+        usage: Usage { prompt_tokens: 10, completion_tokens: 10, total_tokens: 20, ..Default::default() },
         finish_reason: "stop".to_string(),
     }));
 
@@ -274,7 +279,7 @@ async fn test_canary_recovery() {
     tier1_client.push_response(Ok(LlmResponse {
         content: "Canary Success".to_string(),
         model: "tier1-model".to_string(),
-        usage: Usage { prompt_tokens: 1, completion_tokens: 1, total_tokens: 2 },
+        usage: Usage { prompt_tokens: 1, completion_tokens: 1, total_tokens: 2, ..Default::default() },
         finish_reason: "stop".to_string(),
     }));
 
@@ -338,3 +343,264 @@ async fn test_backpressure_signaling() {
         _ => panic!("Expected Backpressure error, got {:?}", err),
     }
 }
+
+#[tokio::test]
+async fn test_recover_leases_from_crashed_process() {
+    let (router, ledger, db) = setup_env().await;
+
+    // A previous process took a lease and died before settling it
+    let crashed = zed42_mom::journal::LeaseJournal::new(db.clone());
+    let lease_id = ledger.request_lease("default", dec!(0.05)).await.unwrap();
+    crashed.open(&lease_id).await.unwrap();
+
+    let released = router.recover_leases().await.unwrap();
+    assert_eq!(released, 1);
+
+    let mut response = db.query("SELECT count() FROM leases GROUP ALL").await.unwrap();
+    let leases: Option<i64> = response.take("count").unwrap();
+    assert_eq!(leases.unwrap_or(0), 0, "Lease should be settled");
+
+    assert_eq!(router.recover_leases().await.unwrap(), 0, "Recovery should be idempotent");
+    let mut response = db.query("SELECT count() FROM lease_journal GROUP ALL").await.unwrap();
+    let entries: Option<i64> = response.take("count").unwrap();
+    assert_eq!(entries.unwrap_or(0), 0, "Journal entry should be cleared");
+}
+
+#[tokio::test]
+async fn test_settled_request_clears_journal() {
+    let (mut router, _, db) = setup_env().await;
+
+    let client = Arc::new(TrackingClient::new("tier1"));
+    client.push_response(Ok(LlmResponse {
+        content: "ok".to_string(),
+        model: "tier1-model".to_string(),
+        usage: Usage { prompt_tokens: 10, completion_tokens: 10, total_tokens: 20, ..Default::default() },
+        finish_reason: "stop".to_string(),
+    }));
+    router.register_client("tier1", client);
+
+    let config = ModelConfig { model: "tier1-model".to_string(), ..ModelConfig::default() };
+    let _: Option<ExecutionProfile> = db.create(("model_profiles", "default"))
+        .content(ExecutionProfile::new("default", config)).await.unwrap();
+
+    let request = LlmRequest::new("Hello".to_string()).agent("default".to_string());
+    router.complete(request).await.unwrap();
+
+    assert_eq!(router.open_lease_count(), 0);
+    let mut response = db.query("SELECT count() FROM lease_journal GROUP ALL").await.unwrap();
+    let entries: Option<i64> = response.take("count").unwrap();
+    assert_eq!(entries.unwrap_or(0), 0);
+}
+
+#[tokio::test]
+async fn test_local_tier_0_served_without_ledger_cost() {
+    let (mut router, ledger, db) = setup_env().await;
+
+    // No rate is registered for the local model: any settlement would fail
+    let local = Arc::new(TrackingClient::new("local"));
+    local.push_response(Ok(LlmResponse {
+        content: "local answer".to_string(),
+        model: "local/qwen2.5-7b".to_string(),
+        usage: Usage { prompt_tokens: 10, completion_tokens: 10, total_tokens: 20, ..Default::default() },
+        finish_reason: "stop".to_string(),
+    }));
+    let tier1_client = Arc::new(TrackingClient::new("tier1"));
+    router.register_client(zed42_llm::LOCAL_MODEL_PREFIX, local.clone());
+    router.register_client("tier1", tier1_client.clone());
+
+    let profile = ExecutionProfile::new("default", ModelConfig { model: "tier1-model".to_string(), ..ModelConfig::default() })
+        .with_tier_0(ModelConfig { model: "local/qwen2.5-7b".to_string(), ..ModelConfig::default() });
+    let _: Option<ExecutionProfile> = db.create(("model_profiles", "default"))
+        .content(profile).await.unwrap();
+
+    let request = LlmRequest::new("Hello".to_string()).agent("default".to_string());
+    let response = router.complete(request).await.unwrap();
+
+    assert_eq!(response.model, "local/qwen2.5-7b");
+    assert_eq!(local.calls.lock().unwrap().len(), 1);
+    assert!(tier1_client.calls.lock().unwrap().is_empty());
+    assert_eq!(router.open_lease_count(), 0);
+    assert_eq!(ledger.get_budget("default").await.unwrap().unwrap().spent, dec!(0.0));
+
+    let logs = router.query_routing_logs(&Default::default()).await.unwrap();
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs[0].selected_tier, 0);
+    assert_eq!(logs[0].cost, Some(rust_decimal::Decimal::ZERO));
+    assert!(!logs[0].is_synthetic());
+}
+
+#[tokio::test]
+async fn test_speculative_draft_approved_by_verifier() {
+    let (mut router, ledger, db) = setup_env().await;
+    for (model, input, output) in [("tier1-model", dec!(0.001), dec!(0.001)), ("tier2-model", dec!(0.03), dec!(0.06))] {
+        ledger.set_rate(zed42_ledger::types::RateTableEntry {
+            model: model.to_string(),
+            input_cost_per_1k: input,
+            output_cost_per_1k: output,
+            ..Default::default()
+        }).await.unwrap();
+    }
+
+    let tier1_client = Arc::new(TrackingClient::new("tier1"));
+    tier1_client.push_response(Ok(LlmResponse {
+        content: "fn answer() -> u32 { 42 }".to_string(),
+        model: "tier1-model".to_string(),
+        usage: Usage { prompt_tokens: 1000, completion_tokens: 1000, total_tokens: 2000, ..Default::default() },
+        finish_reason: "stop".to_string(),
+    }));
+    let tier2_client = Arc::new(TrackingClient::new("tier2"));
+    tier2_client.push_response(Ok(LlmResponse {
+        content: "APPROVE".to_string(),
+        model: "tier2-model".to_string(),
+        usage: Usage { prompt_tokens: 1100, completion_tokens: 5, total_tokens: 1105, ..Default::default() },
+        finish_reason: "stop".to_string(),
+    }));
+    router.register_client("tier1", tier1_client.clone());
+    router.register_client("tier2", tier2_client.clone());
+
+    let profile = ExecutionProfile::new("default", ModelConfig { model: "tier1-model".to_string(), ..ModelConfig::default() })
+        .with_tier_2(ModelConfig { model: "tier2-model".to_string(), ..ModelConfig::default() });
+    let _: Option<ExecutionProfile> = db.create(("model_profiles", "default"))
+        .content(profile).await.unwrap();
+
+    let request = LlmRequest::new("Write answer()".to_string()).agent("default".to_string()).speculative();
+    let response = router.complete(request).await.unwrap();
+
+    assert_eq!(response.content, "fn answer() -> u32 { 42 }");
+    assert_eq!(response.model, "tier2-model");
+    assert_eq!(response.usage.completion_tokens, 1005);
+    assert!(tier2_client.calls.lock().unwrap()[0].contains("[Draft]"));
+
+    let analytics = router.routing_analytics(&Default::default()).await.unwrap();
+    assert_eq!(analytics.speculative_runs, 1);
+    // Direct: 1k in + 1k out at Tier 2 = 0.09; spent: 0.002 draft + 0.0333 review
+    assert_eq!(analytics.speculative_savings, dec!(0.0547));
+    assert_eq!(analytics.total_cost, dec!(0.0353));
+}
+
+#[tokio::test]
+async fn test_experiment_variant_sets_prompt_profile_and_log_tag() {
+    let (mut router, _ledger, db) = setup_env().await;
+
+    let control = Arc::new(TrackingClient::new("control"));
+    let treatment = Arc::new(TrackingClient::new("treatment"));
+    for _ in 0..2 {
+        treatment.push_response(Ok(LlmResponse {
+            content: "ok".to_string(),
+            model: "local/treatment".to_string(),
+            usage: Usage { prompt_tokens: 1, completion_tokens: 1, total_tokens: 2, ..Default::default() },
+            finish_reason: "stop".to_string(),
+        }));
+    }
+    router.register_client("local/control", control.clone());
+    router.register_client("local/treatment", treatment.clone());
+
+    let stored = ExecutionProfile::new("implementer", ModelConfig { model: "tier1-model".to_string(), ..ModelConfig::default() })
+        .with_tier_0(ModelConfig { model: "local/control".to_string(), ..ModelConfig::default() });
+    let _: Option<ExecutionProfile> = db.create(("model_profiles", "implementer"))
+        .content(stored).await.unwrap();
+
+    let treatment_profile = ExecutionProfile::new("implementer", ModelConfig { model: "tier1-model".to_string(), ..ModelConfig::default() })
+        .with_tier_0(ModelConfig { model: "local/treatment".to_string(), ..ModelConfig::default() });
+    let experiment = Experiment::new(
+        "terse-prompt",
+        "implementer",
+        Variant::new("control"),
+        Variant::new("terse").with_system_prompt("Answer tersely.").with_profile(treatment_profile),
+    )
+    .with_treatment_share(1.0);
+    let router = router.with_experiment(experiment.clone());
+
+    for task in ["t1", "t2"] {
+        let request = LlmRequest::new("Hello".to_string()).agent("implementer".to_string()).task(task.to_string());
+        router.complete(request).await.unwrap();
+    }
+
+    assert!(control.calls.lock().unwrap().is_empty());
+    assert_eq!(*treatment.system_prompts.lock().unwrap(), vec![Some("Answer tersely.".to_string()); 2]);
+
+    let logs = router.query_routing_logs(&zed42_mom::analytics::RoutingLogQuery::new().experiment("terse-prompt")).await.unwrap();
+    assert_eq!(logs.len(), 2);
+    assert!(logs.iter().all(|log| log.variant.as_ref().is_some_and(|tag| tag.variant == "terse")));
+
+    let report = router.experiment_report(&experiment, &[("terse".to_string(), 0.8)]).await.unwrap();
+    assert_eq!((report.treatment.calls, report.treatment.tasks), (2, 2));
+    assert_eq!(report.control.calls, 0);
+    assert!(report.cost.is_none());
+}
+
+fn routing_log(agent: &str, timestamp: chrono::DateTime<chrono::Utc>, failover: Option<&str>) -> zed42_mom::types::RoutingLog {
+    zed42_mom::types::RoutingLog {
+        id: None,
+        timestamp,
+        agent_id: agent.to_string(),
+        task_id: None,
+        original_prompt_len: 5,
+        selected_tier: 1,
+        selected_model: "tier1-model".to_string(),
+        retry_count: 0,
+        failover_reason: failover.map(str::to_string),
+        cost: None,
+        is_critical: false,
+        speculation: None,
+        variant: None,
+        throttle: None,
+    }
+}
+
+// Multi-threaded so the archive callback can write a log mid-archive
+#[tokio::test(flavor = "multi_thread")]
+async fn test_routing_log_filters_run_in_the_database_and_archive_before_delete() {
+    let (router, _ledger, db) = setup_env().await;
+    let now = chrono::Utc::now();
+    for (agent, hours_ago, failover) in [("a", 48, Some("Tier 1 failed: rate limit")), ("a", 2, None), ("b", 1, Some("Tier 1 failed: timeout"))] {
+        let log = routing_log(agent, now - chrono::Duration::hours(hours_ago), failover);
+        let _: Option<serde::de::IgnoredAny> = db.create("routing_logs").content(log).await.unwrap();
+    }
+
+    let query = zed42_mom::analytics::RoutingLogQuery::new().failover_reason("rate limit");
+    assert_eq!(router.query_routing_logs(&query).await.unwrap().len(), 1);
+    let query = zed42_mom::analytics::RoutingLogQuery::new().since(now - chrono::Duration::hours(3)).limit(1);
+    let logs = router.query_routing_logs(&query).await.unwrap();
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs[0].agent_id, "a");
+
+    // A failed archive keeps the logs
+    let cutoff = now - chrono::Duration::hours(24);
+    assert!(router.archive_routing_logs_before(cutoff, |_| Err(anyhow::anyhow!("archive offline"))).await.is_err());
+    assert_eq!(router.query_routing_logs(&Default::default()).await.unwrap().len(), 3);
+
+    // A log written while the archive runs isn't deleted unarchived
+    let mut archived = Vec::new();
+    let moved = router
+        .archive_routing_logs_before(cutoff, |logs| {
+            archived.extend_from_slice(logs);
+            let late = routing_log("c", now - chrono::Duration::hours(30), None);
+            tokio::task::block_in_place(|| {
+                tokio::runtime::Handle::current().block_on(async {
+                    let _: Option<serde::de::IgnoredAny> = db.create("routing_logs").content(late).await?;
+                    Ok::<_, anyhow::Error>(())
+                })
+            })
+        })
+        .await
+        .unwrap();
+    assert_eq!(moved, 1);
+    assert_eq!(archived[0].failover_reason.as_deref(), Some("Tier 1 failed: rate limit"));
+    assert!(archived[0].id.is_some());
+    let live = router.query_routing_logs(&Default::default()).await.unwrap();
+    assert_eq!(live.len(), 3);
+    assert!(live.iter().all(|log| log.id.is_some() && log.id != archived[0].id));
+
+    let mut late = Vec::new();
+    let moved = router
+        .archive_routing_logs_before(cutoff, |logs| {
+            late.extend_from_slice(logs);
+            Ok(())
+        })
+        .await
+        .unwrap();
+    assert_eq!(moved, 1);
+    assert_eq!(late[0].agent_id, "c");
+    assert_eq!(router.query_routing_logs(&Default::default()).await.unwrap().len(), 2);
+}
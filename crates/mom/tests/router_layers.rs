@@ -0,0 +1,115 @@
+use std::sync::{Arc, Mutex};
+use async_trait::async_trait;
+use rust_decimal_macros::dec;
+use surrealdb::engine::any::connect;
+use zed42_ledger::{IntelligenceLedger, types::{Budget, BudgetStatus, RateTableEntry}};
+use zed42_llm::{EmbeddingRequest, EmbeddingResponse, LlmClient, LlmRequest, LlmResponse, ModelConfig, Usage};
+use zed42_mom::{Router, layers::{CacheLayer, RouterLayer}};
+
+/// Echoes the prompt back and records every call
+#[derive(Default)]
+struct EchoClient {
+    prompts: Mutex<Vec<String>>,
+}
+
+#[async_trait]
+impl LlmClient for EchoClient {
+    async fn complete(&self, request: LlmRequest) -> zed42_llm::Result<LlmResponse> {
+        self.prompts.lock().unwrap().push(request.prompt.clone());
+        Ok(LlmResponse {
+            content: request.prompt,
+            model: request.config.model,
+            usage: Usage { prompt_tokens: 1, completion_tokens: 1, total_tokens: 2, ..Default::default() },
+            finish_reason: "stop".to_string(),
+        })
+    }
+
+    async fn stream(&self, _request: LlmRequest) -> zed42_llm::Result<Vec<zed42_llm::StreamChunk>> {
+        unimplemented!()
+    }
+
+    async fn embed(&self, _request: EmbeddingRequest) -> zed42_llm::Result<EmbeddingResponse> {
+        unimplemented!()
+    }
+}
+
+/// Rewrites prompts on the way in and tags responses on the way out
+struct TagLayer {
+    tag: &'static str,
+}
+
+#[async_trait]
+impl RouterLayer for TagLayer {
+    fn name(&self) -> &str {
+        self.tag
+    }
+
+    async fn on_request(&self, request: &mut LlmRequest) -> zed42_llm::Result<Option<LlmResponse>> {
+        request.prompt = format!("{}>{}", self.tag, request.prompt);
+        Ok(None)
+    }
+
+    async fn on_response(&self, _request: &LlmRequest, response: &mut LlmResponse) -> zed42_llm::Result<()> {
+        response.content = format!("{}<{}", response.content, self.tag);
+        Ok(())
+    }
+}
+
+async fn setup(layers: Vec<Arc<dyn RouterLayer>>) -> (Router, Arc<EchoClient>) {
+    let db = connect("mem://").await.unwrap();
+    db.use_ns("zed42").use_db("mom").await.unwrap();
+
+    let ledger = Arc::new(IntelligenceLedger::new(db.clone()));
+    ledger.set_budget(Budget {
+        entity_id: "default".to_string(),
+        hard_limit: dec!(1000.0),
+        soft_limit: dec!(500.0),
+        spent: dec!(0.0),
+        currency: "USD".to_string(),
+        updated_at: chrono::Utc::now(),
+        status: BudgetStatus::Active,
+        tags: Default::default(),
+    }).await.unwrap();
+    ledger.set_rate(RateTableEntry {
+        model: ModelConfig::default().model,
+        input_cost_per_1k: dec!(0.0),
+        output_cost_per_1k: dec!(0.0),
+        ..Default::default()
+    }).await.unwrap();
+
+    let client = Arc::new(EchoClient::default());
+    let mut router = Router::new(ledger, db, client.clone());
+    for layer in layers {
+        router = router.with_layer(layer);
+    }
+    (router, client)
+}
+
+#[tokio::test]
+async fn test_layers_wrap_the_waterfall_in_order() {
+    let (router, client) = setup(vec![
+        Arc::new(TagLayer { tag: "outer" }),
+        Arc::new(TagLayer { tag: "inner" }),
+    ]).await;
+
+    let response = router.complete(LlmRequest::new("hello".to_string())).await.unwrap();
+
+    assert_eq!(client.prompts.lock().unwrap().as_slice(), ["inner>outer>hello"]);
+    assert_eq!(response.content, "inner>outer>hello<inner<outer");
+}
+
+#[tokio::test]
+async fn test_cache_layer_short_circuits_routing() {
+    let cache = Arc::new(CacheLayer::new());
+    let (router, client) = setup(vec![cache.clone()]).await;
+    let deterministic = ModelConfig { temperature: 0.0, ..ModelConfig::default() };
+
+    for _ in 0..3 {
+        let request = LlmRequest::new("hello".to_string()).config(deterministic.clone());
+        let response = router.complete(request).await.unwrap();
+        assert_eq!(response.content, "hello");
+    }
+
+    assert_eq!(client.prompts.lock().unwrap().len(), 1);
+    assert_eq!(cache.len(), 1);
+}
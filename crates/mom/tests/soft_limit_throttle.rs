@@ -0,0 +1,129 @@
+use std::sync::{Arc, Mutex};
+use async_trait::async_trait;
+use rust_decimal_macros::dec;
+use surrealdb::engine::any::{connect, Any};
+use surrealdb::Surreal;
+use zed42_ledger::{
+    IntelligenceLedger,
+    throttle::{ThrottlePolicy, ThrottleStep},
+    types::{Budget, BudgetStatus, RateTableEntry},
+};
+use zed42_llm::{EmbeddingRequest, EmbeddingResponse, LlmClient, LlmRequest, LlmResponse, ModelConfig, RetryCause, Usage};
+use zed42_mom::{Router, analytics::RoutingLogQuery, types::ExecutionProfile};
+
+/// Echoes prompts back, answering merged batches under their answer markers
+#[derive(Default)]
+struct BatchAwareClient {
+    calls: Mutex<Vec<(String, String)>>,
+}
+
+#[async_trait]
+impl LlmClient for BatchAwareClient {
+    async fn complete(&self, request: LlmRequest) -> zed42_llm::Result<LlmResponse> {
+        self.calls.lock().unwrap().push((request.config.model.clone(), request.prompt.clone()));
+        let content = match request.prompt.split_once("### Request ") {
+            Some((_, requests)) => requests
+                .split("\n\n### Request ")
+                .map(|section| {
+                    let (n, prompt) = section.split_once('\n').unwrap();
+                    format!("### Answer {}\necho: {}", n, prompt)
+                })
+                .collect::<Vec<_>>()
+                .join("\n\n"),
+            None => format!("echo: {}", request.prompt),
+        };
+        Ok(LlmResponse {
+            content,
+            model: request.config.model,
+            usage: Usage { prompt_tokens: 30, completion_tokens: 30, total_tokens: 60, ..Default::default() },
+            finish_reason: "stop".to_string(),
+        })
+    }
+
+    async fn stream(&self, _request: LlmRequest) -> zed42_llm::Result<Vec<zed42_llm::StreamChunk>> {
+        unimplemented!()
+    }
+
+    async fn embed(&self, _request: EmbeddingRequest) -> zed42_llm::Result<EmbeddingResponse> {
+        unimplemented!()
+    }
+}
+
+/// A router whose "default" budget is 75% of the way from soft to hard limit
+async fn setup(policy: ThrottlePolicy) -> (Router, Arc<BatchAwareClient>) {
+    let db: Surreal<Any> = connect("mem://").await.unwrap();
+    db.use_ns("zed42").use_db("mom").await.unwrap();
+
+    let ledger = Arc::new(IntelligenceLedger::new(db.clone()));
+    ledger.set_budget(Budget {
+        entity_id: "default".to_string(),
+        hard_limit: dec!(10.0),
+        soft_limit: dec!(6.0),
+        spent: dec!(9.0),
+        currency: "USD".to_string(),
+        updated_at: chrono::Utc::now(),
+        status: BudgetStatus::Active,
+        tags: Default::default(),
+    }).await.unwrap();
+    ledger.set_throttle_policy("default", policy).await.unwrap();
+    for model in ["tier1-model", "tier2-model"] {
+        ledger.set_rate(RateTableEntry {
+            model: model.to_string(),
+            input_cost_per_1k: dec!(0.0),
+            output_cost_per_1k: dec!(0.0),
+            ..Default::default()
+        }).await.unwrap();
+    }
+
+    let profile = ExecutionProfile::new("default", ModelConfig { model: "tier1-model".to_string(), ..ModelConfig::default() })
+        .with_tier_2(ModelConfig { model: "tier2-model".to_string(), ..ModelConfig::default() });
+    let _: Option<ExecutionProfile> = db.create(("model_profiles", "default")).content(profile).await.unwrap();
+
+    let client = Arc::new(BatchAwareClient::default());
+    (Router::new(ledger, db, client.clone()), client)
+}
+
+#[tokio::test]
+async fn test_throttle_caps_tier_and_is_logged() {
+    let policy = ThrottlePolicy {
+        steps: vec![ThrottleStep { from_pressure: 0.0, max_tier: 1, batch_size: 1, batch_window_ms: 0 }],
+    };
+    let (router, client) = setup(policy).await;
+
+    // Validation failures normally skip straight to Tier 2
+    let request = LlmRequest::new("Hello".to_string())
+        .agent("default".to_string())
+        .retry_cause(RetryCause::ValidationFailure);
+    let response = router.complete(request).await.unwrap();
+
+    assert_eq!(response.model, "tier1-model");
+    assert_eq!(client.calls.lock().unwrap().len(), 1);
+
+    let logs = router.query_routing_logs(&RoutingLogQuery::new()).await.unwrap();
+    let throttle = logs[0].throttle.as_ref().expect("throttle recorded on the routing log");
+    assert_eq!((throttle.max_tier, throttle.pressure, throttle.batched), (1, 0.75, 1));
+}
+
+#[tokio::test]
+async fn test_throttle_batches_concurrent_requests() {
+    let (router, client) = setup(ThrottlePolicy::default()).await;
+    let request = |prompt: &str| LlmRequest::new(prompt.to_string()).agent("default".to_string());
+
+    let (a, b, c) = tokio::join!(
+        router.complete(request("first")),
+        router.complete(request("second")),
+        router.complete(request("third")),
+    );
+
+    assert_eq!(a.unwrap().content, "echo: first");
+    assert_eq!(b.unwrap().content, "echo: second");
+    assert_eq!(c.unwrap().content, "echo: third");
+
+    let calls = client.calls.lock().unwrap();
+    assert_eq!(calls.len(), 1, "three requests share one call");
+    assert_eq!(calls[0].0, "tier1-model");
+
+    let logs = router.query_routing_logs(&RoutingLogQuery::new()).await.unwrap();
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs[0].throttle.as_ref().unwrap().batched, 3);
+}
@@ -0,0 +1,183 @@
+//! Delivery channels
+
+use async_trait::async_trait;
+use serde_json::json;
+use tokio::sync::broadcast;
+
+use crate::template::RenderedNotification;
+use crate::{NotifyError, Result};
+
+/// A destination for notifications
+#[async_trait]
+pub trait NotificationChannel: Send + Sync {
+    fn name(&self) -> &str;
+    async fn deliver(&self, notification: &RenderedNotification) -> Result<()>;
+}
+
+/// POSTs notifications as JSON to a URL (Slack/Discord-compatible `text` field)
+pub struct WebhookChannel {
+    name: String,
+    url: String,
+    client: reqwest::Client,
+}
+
+impl WebhookChannel {
+    pub fn new(name: impl Into<String>, url: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            url: url.into(),
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for WebhookChannel {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn deliver(&self, notification: &RenderedNotification) -> Result<()> {
+        let payload = json!({
+            "text": format!("{}\n{}", notification.title, notification.body),
+            "title": notification.title,
+            "body": notification.body,
+            "event": notification.event,
+        });
+
+        let response = self
+            .client
+            .post(&self.url)
+            .json(&payload)
+            .send()
+            .await
+            .map_err(|e| NotifyError::Delivery(format!("{}: {}", self.name, e)))?;
+
+        if !response.status().is_success() {
+            return Err(NotifyError::Delivery(format!(
+                "{}: webhook returned {}",
+                self.name,
+                response.status()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Hands notifications to the desktop shell
+///
+/// The desktop app subscribes (`zed42_ui::notifications::forward`) and
+/// shows each notification in its window; with no subscriber, delivery is
+/// a no-op.
+pub struct DesktopChannel {
+    sender: broadcast::Sender<RenderedNotification>,
+}
+
+impl Default for DesktopChannel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DesktopChannel {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(64);
+        Self { sender }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<RenderedNotification> {
+        self.sender.subscribe()
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for DesktopChannel {
+    fn name(&self) -> &str {
+        "desktop"
+    }
+
+    async fn deliver(&self, notification: &RenderedNotification) -> Result<()> {
+        // Err only means no window is listening right now
+        let _ = self.sender.send(notification.clone());
+        Ok(())
+    }
+}
+
+/// SMTP settings for the email channel
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SmtpConfig {
+    pub host: String,
+    #[serde(default = "default_smtp_port")]
+    pub port: u16,
+    pub username: Option<String>,
+    /// Name of the environment variable holding the SMTP password
+    pub password_env: Option<String>,
+    pub from: String,
+    pub to: Vec<String>,
+}
+
+fn default_smtp_port() -> u16 {
+    587
+}
+
+/// Sends notifications by email (requires the `email` feature)
+pub struct EmailChannel {
+    name: String,
+    config: SmtpConfig,
+}
+
+impl EmailChannel {
+    pub fn new(name: impl Into<String>, config: SmtpConfig) -> Self {
+        Self {
+            name: name.into(),
+            config,
+        }
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for EmailChannel {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[cfg(feature = "email")]
+    async fn deliver(&self, notification: &RenderedNotification) -> Result<()> {
+        use lettre::transport::smtp::authentication::Credentials;
+        use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+
+        let delivery = |e: &dyn std::fmt::Display| NotifyError::Delivery(format!("{}: {}", self.name, e));
+
+        let mut builder = Message::builder()
+            .from(self.config.from.parse().map_err(|e| delivery(&e))?)
+            .subject(notification.title.clone());
+        for to in &self.config.to {
+            builder = builder.to(to.parse().map_err(|e| delivery(&e))?);
+        }
+        let message = builder.body(notification.body.clone()).map_err(|e| delivery(&e))?;
+
+        let mut transport = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&self.config.host)
+            .map_err(|e| delivery(&e))?
+            .port(self.config.port);
+        if let Some(ref username) = self.config.username {
+            let password = self
+                .config
+                .password_env
+                .as_deref()
+                .and_then(|var| std::env::var(var).ok())
+                .unwrap_or_default();
+            transport = transport.credentials(Credentials::new(username.clone(), password));
+        }
+
+        transport.build().send(message).await.map_err(|e| delivery(&e))?;
+        Ok(())
+    }
+
+    #[cfg(not(feature = "email"))]
+    async fn deliver(&self, _notification: &RenderedNotification) -> Result<()> {
+        Err(NotifyError::Unsupported(format!(
+            "{}: email delivery requires the `email` feature",
+            self.name
+        )))
+    }
+}
@@ -0,0 +1,144 @@
+//! Events the notifier reacts to
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use zed42_core::vox::{VoxMessage, VoxPayload};
+
+/// Kind of notification event (used for channel subscriptions and templates)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    RunComplete,
+    BudgetThreshold,
+    BudgetForecast,
+    ApprovalNeeded,
+    AgentGhost,
+    ClarificationNeeded,
+}
+
+impl EventKind {
+    pub const ALL: [EventKind; 6] = [
+        EventKind::RunComplete,
+        EventKind::BudgetThreshold,
+        EventKind::BudgetForecast,
+        EventKind::ApprovalNeeded,
+        EventKind::AgentGhost,
+        EventKind::ClarificationNeeded,
+    ];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.as_str() == name)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventKind::RunComplete => "run_complete",
+            EventKind::BudgetThreshold => "budget_threshold",
+            EventKind::BudgetForecast => "budget_forecast",
+            EventKind::ApprovalNeeded => "approval_needed",
+            EventKind::AgentGhost => "agent_ghost",
+            EventKind::ClarificationNeeded => "clarification_needed",
+        }
+    }
+}
+
+/// A notable event worth telling a human about
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum NotifyEvent {
+    RunComplete {
+        run_id: String,
+        summary: String,
+        success: bool,
+    },
+    BudgetThreshold {
+        entity_id: String,
+        spent: String,
+        limit: String,
+    },
+    /// A budget is projected to run out soon at its current spend rate
+    BudgetForecast {
+        message: String,
+    },
+    ApprovalNeeded {
+        agent_id: String,
+        action: String,
+        reason: String,
+    },
+    AgentGhost {
+        agent_id: String,
+        last_seen: Option<DateTime<Utc>>,
+    },
+    /// Intent parsing is waiting on answers from the user
+    ClarificationNeeded {
+        questions: String,
+    },
+}
+
+impl NotifyEvent {
+    pub fn kind(&self) -> EventKind {
+        match self {
+            NotifyEvent::RunComplete { .. } => EventKind::RunComplete,
+            NotifyEvent::BudgetThreshold { .. } => EventKind::BudgetThreshold,
+            NotifyEvent::BudgetForecast { .. } => EventKind::BudgetForecast,
+            NotifyEvent::ApprovalNeeded { .. } => EventKind::ApprovalNeeded,
+            NotifyEvent::AgentGhost { .. } => EventKind::AgentGhost,
+            NotifyEvent::ClarificationNeeded { .. } => EventKind::ClarificationNeeded,
+        }
+    }
+
+    /// Default one-line text when no template is configured
+    pub fn default_text(&self) -> String {
+        match self {
+            NotifyEvent::RunComplete { run_id, summary, success } => format!(
+                "Run {} {}: {}",
+                run_id,
+                if *success { "completed" } else { "failed" },
+                summary
+            ),
+            NotifyEvent::BudgetThreshold { entity_id, spent, limit } => {
+                format!("Budget threshold reached for {}: {} of {}", entity_id, spent, limit)
+            }
+            NotifyEvent::BudgetForecast { message } => message.clone(),
+            NotifyEvent::ApprovalNeeded { agent_id, action, reason } => {
+                format!("Approval needed: {} wants to {} ({})", agent_id, action, reason)
+            }
+            NotifyEvent::AgentGhost { agent_id, .. } => {
+                format!("Agent {} stopped responding and was dissolved", agent_id)
+            }
+            NotifyEvent::ClarificationNeeded { questions } => {
+                format!("Clarification needed before planning: {}", questions)
+            }
+        }
+    }
+
+    /// Map a VOX system alert onto a notification event
+    pub fn from_vox(message: &VoxMessage) -> Option<Self> {
+        match &message.payload {
+            VoxPayload::SystemAlert { action, agent_id, .. } if action == "dissolve_ghost" => {
+                Some(NotifyEvent::AgentGhost {
+                    agent_id: agent_id.map(|id| id.to_string()).unwrap_or_default(),
+                    last_seen: None,
+                })
+            }
+            VoxPayload::SystemAlert { action, agent_id, reason } if action == "approval_required" => {
+                Some(NotifyEvent::ApprovalNeeded {
+                    agent_id: agent_id.map(|id| id.to_string()).unwrap_or_default(),
+                    action: action.clone(),
+                    reason: reason.clone(),
+                })
+            }
+            VoxPayload::SystemAlert { action, reason, .. } if action == "budget_forecast" => {
+                Some(NotifyEvent::BudgetForecast {
+                    message: reason.clone(),
+                })
+            }
+            VoxPayload::SystemAlert { action, reason, .. } if action == "clarification_needed" => {
+                Some(NotifyEvent::ClarificationNeeded {
+                    questions: reason.clone(),
+                })
+            }
+            _ => None,
+        }
+    }
+}
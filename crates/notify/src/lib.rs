@@ -0,0 +1,285 @@
+//! ZED42 Notify - Human-facing notifications
+//!
+//! Subscribes to key events (run complete, budget threshold, approval needed,
+//! agent ghost, clarification needed) and delivers them through configurable channels: webhook
+//! POST, the desktop shell, and SMTP email. Bodies are templated per event
+//! kind and each channel is rate limited. The Cortex feeds a notifier its
+//! dashboard events (`zed42_cortex::notifications`).
+
+pub mod channels;
+pub mod event;
+pub mod template;
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+use zed42_core::vox::VoxReceiver;
+
+use crate::channels::{DesktopChannel, EmailChannel, NotificationChannel, SmtpConfig, WebhookChannel};
+pub use crate::event::{EventKind, NotifyEvent};
+use crate::template::TemplateSet;
+
+#[derive(Debug, thiserror::Error)]
+pub enum NotifyError {
+    #[error("Delivery failed: {0}")]
+    Delivery(String),
+    #[error("Unsupported channel: {0}")]
+    Unsupported(String),
+    #[error("Invalid notify config: {0}")]
+    Config(String),
+}
+
+pub type Result<T> = std::result::Result<T, NotifyError>;
+
+/// At most `max_per_window` notifications per channel and event kind
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimit {
+    pub max_per_window: u32,
+    pub window_secs: u64,
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        Self {
+            max_per_window: 10,
+            window_secs: 60,
+        }
+    }
+}
+
+/// Sliding-window limiter keyed by event kind
+#[derive(Debug)]
+struct RateLimiter {
+    limit: RateLimit,
+    sent: Mutex<HashMap<EventKind, VecDeque<Instant>>>,
+}
+
+impl RateLimiter {
+    fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            sent: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Record a send if allowed
+    fn try_acquire(&self, kind: EventKind) -> bool {
+        let window = Duration::from_secs(self.limit.window_secs);
+        let now = Instant::now();
+        let mut sent = self.sent.lock().unwrap();
+        let history = sent.entry(kind).or_default();
+        while history.front().is_some_and(|t| now.duration_since(*t) >= window) {
+            history.pop_front();
+        }
+        if history.len() >= self.limit.max_per_window as usize {
+            return false;
+        }
+        history.push_back(now);
+        true
+    }
+}
+
+struct Route {
+    channel: Arc<dyn NotificationChannel>,
+    /// Subscribed event kinds (empty = all)
+    events: Vec<EventKind>,
+    limiter: RateLimiter,
+}
+
+/// What happened to a notification on one channel
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum DeliveryOutcome {
+    Delivered { channel: String },
+    RateLimited { channel: String },
+    Failed { channel: String, error: String },
+}
+
+/// Fans events out to subscribed channels
+pub struct Notifier {
+    routes: Vec<Route>,
+    templates: TemplateSet,
+}
+
+impl Default for Notifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Notifier {
+    pub fn new() -> Self {
+        Self {
+            routes: Vec::new(),
+            templates: TemplateSet::new(),
+        }
+    }
+
+    pub fn with_templates(mut self, templates: TemplateSet) -> Self {
+        self.templates = templates;
+        self
+    }
+
+    /// Add a channel subscribed to `events` (empty = all events)
+    pub fn with_channel(
+        mut self,
+        channel: Arc<dyn NotificationChannel>,
+        events: Vec<EventKind>,
+        limit: RateLimit,
+    ) -> Self {
+        self.routes.push(Route {
+            channel,
+            events,
+            limiter: RateLimiter::new(limit),
+        });
+        self
+    }
+
+    /// Build from a parsed config; `desktop` backs any `desktop` channel
+    pub fn from_config(config: NotifyConfig, desktop: Option<Arc<DesktopChannel>>) -> Result<Self> {
+        let mut templates = TemplateSet::new();
+        for (name, template) in config.templates {
+            let kind = EventKind::parse(&name)
+                .ok_or_else(|| NotifyError::Config(format!("unknown event kind in templates: {}", name)))?;
+            templates = templates.with_template(kind, template);
+        }
+
+        let mut notifier = Self::new().with_templates(templates);
+        for entry in config.channels {
+            let channel: Arc<dyn NotificationChannel> = match entry.channel {
+                ChannelConfig::Webhook { name, url } => Arc::new(WebhookChannel::new(name, url)),
+                ChannelConfig::Desktop => match desktop {
+                    Some(ref desktop) => desktop.clone(),
+                    None => {
+                        return Err(NotifyError::Config(
+                            "desktop channel configured but no desktop shell attached".to_string(),
+                        ))
+                    }
+                },
+                ChannelConfig::Email { name, smtp } => Arc::new(EmailChannel::new(name, smtp)),
+            };
+            let limit = entry.rate_limit.unwrap_or(config.rate_limit);
+            notifier = notifier.with_channel(channel, entry.events, limit);
+        }
+        Ok(notifier)
+    }
+
+    /// Deliver an event to every subscribed channel
+    pub async fn notify(&self, event: &NotifyEvent) -> Vec<DeliveryOutcome> {
+        let notification = self.templates.render(event);
+        let mut outcomes = Vec::new();
+
+        for route in &self.routes {
+            if !route.events.is_empty() && !route.events.contains(&event.kind()) {
+                continue;
+            }
+            let channel = route.channel.name().to_string();
+            if !route.limiter.try_acquire(event.kind()) {
+                debug!(channel = %channel, kind = event.kind().as_str(), "Notification rate limited");
+                outcomes.push(DeliveryOutcome::RateLimited { channel });
+                continue;
+            }
+            match route.channel.deliver(&notification).await {
+                Ok(()) => outcomes.push(DeliveryOutcome::Delivered { channel }),
+                Err(e) => {
+                    warn!(channel = %channel, error = %e, "Notification delivery failed");
+                    outcomes.push(DeliveryOutcome::Failed {
+                        channel,
+                        error: e.to_string(),
+                    });
+                }
+            }
+        }
+        outcomes
+    }
+
+    /// Deliver every event received on `events` until the sender closes
+    pub fn spawn(self: Arc<Self>, mut events: broadcast::Receiver<NotifyEvent>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(event) => {
+                        self.notify(&event).await;
+                    }
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        warn!("Notifier lagged, dropped {} events", n);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+
+    /// Watch a VOX stream (e.g. `BlackboardDb::subscribe`) for notable system alerts
+    pub fn spawn_vox(self: Arc<Self>, mut messages: VoxReceiver) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                match messages.recv().await {
+                    Ok(message) => {
+                        if let Some(event) = NotifyEvent::from_vox(&message) {
+                            self.notify(&event).await;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        warn!("Notifier lagged, dropped {} VOX messages", n);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+}
+
+/// Channel definition in `config/notify.toml`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ChannelConfig {
+    Webhook {
+        name: String,
+        url: String,
+    },
+    Desktop,
+    Email {
+        name: String,
+        #[serde(flatten)]
+        smtp: SmtpConfig,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelEntry {
+    #[serde(flatten)]
+    pub channel: ChannelConfig,
+    #[serde(default)]
+    pub events: Vec<EventKind>,
+    pub rate_limit: Option<RateLimit>,
+}
+
+/// Notifier configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NotifyConfig {
+    #[serde(default)]
+    pub rate_limit: RateLimit,
+    /// Body template per event kind name (e.g. `run_complete`)
+    #[serde(default)]
+    pub templates: HashMap<String, String>,
+    #[serde(default)]
+    pub channels: Vec<ChannelEntry>,
+}
+
+impl NotifyConfig {
+    pub fn from_toml_str(content: &str) -> Result<Self> {
+        toml::from_str(content).map_err(|e| NotifyError::Config(e.to_string()))
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| NotifyError::Config(format!("{}: {}", path.display(), e)))?;
+        Self::from_toml_str(&content)
+    }
+}
@@ -0,0 +1,91 @@
+//! Notification templates
+//!
+//! Templates use `{{field}}` placeholders filled from the event's fields,
+//! plus `{{kind}}` and `{{text}}` (the event's default one-line text).
+//! Unknown placeholders are left as-is.
+
+use std::collections::BTreeMap;
+
+use crate::event::{EventKind, NotifyEvent};
+
+/// A rendered notification ready for delivery
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct RenderedNotification {
+    pub kind: EventKind,
+    pub title: String,
+    pub body: String,
+    pub event: NotifyEvent,
+}
+
+/// Per-event-kind body templates
+#[derive(Debug, Clone, Default)]
+pub struct TemplateSet {
+    templates: BTreeMap<EventKind, String>,
+}
+
+impl TemplateSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_template(mut self, kind: EventKind, template: impl Into<String>) -> Self {
+        self.templates.insert(kind, template.into());
+        self
+    }
+
+    pub fn render(&self, event: &NotifyEvent) -> RenderedNotification {
+        let body = match self.templates.get(&event.kind()) {
+            Some(template) => render_template(template, event),
+            None => event.default_text(),
+        };
+        RenderedNotification {
+            kind: event.kind(),
+            title: format!("ZED42: {}", event.kind().as_str().replace('_', " ")),
+            body,
+            event: event.clone(),
+        }
+    }
+}
+
+fn render_template(template: &str, event: &NotifyEvent) -> String {
+    let mut values: BTreeMap<String, String> = BTreeMap::new();
+    if let Ok(serde_json::Value::Object(fields)) = serde_json::to_value(event) {
+        for (key, value) in fields {
+            let text = match value {
+                serde_json::Value::String(s) => s,
+                serde_json::Value::Null => String::new(),
+                other => other.to_string(),
+            };
+            values.insert(key, text);
+        }
+    }
+    values.insert("text".to_string(), event.default_text());
+
+    let mut rendered = template.to_string();
+    for (key, value) in &values {
+        rendered = rendered.replace(&format!("{{{{{}}}}}", key), value);
+    }
+    rendered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_with_and_without_template() {
+        let event = NotifyEvent::BudgetThreshold {
+            entity_id: "agent-1".to_string(),
+            spent: "8.00".to_string(),
+            limit: "10.00".to_string(),
+        };
+
+        let templates = TemplateSet::new()
+            .with_template(EventKind::BudgetThreshold, "{{entity_id}} spent {{spent}}/{{limit}} {{missing}}");
+        assert_eq!(templates.render(&event).body, "agent-1 spent 8.00/10.00 {{missing}}");
+
+        let plain = TemplateSet::new().render(&event);
+        assert_eq!(plain.body, "Budget threshold reached for agent-1: 8.00 of 10.00");
+        assert_eq!(plain.title, "ZED42: budget threshold");
+    }
+}
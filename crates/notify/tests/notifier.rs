@@ -0,0 +1,88 @@
+use std::sync::{Arc, Mutex};
+use async_trait::async_trait;
+use zed42_notify::{
+    channels::{DesktopChannel, NotificationChannel},
+    template::RenderedNotification,
+    DeliveryOutcome, EventKind, Notifier, NotifyConfig, NotifyEvent, RateLimit,
+};
+
+#[derive(Default)]
+struct RecordingChannel {
+    bodies: Mutex<Vec<String>>,
+}
+
+#[async_trait]
+impl NotificationChannel for RecordingChannel {
+    fn name(&self) -> &str {
+        "recording"
+    }
+
+    async fn deliver(&self, notification: &RenderedNotification) -> zed42_notify::Result<()> {
+        self.bodies.lock().unwrap().push(notification.body.clone());
+        Ok(())
+    }
+}
+
+fn ghost(agent: &str) -> NotifyEvent {
+    NotifyEvent::AgentGhost {
+        agent_id: agent.to_string(),
+        last_seen: None,
+    }
+}
+
+#[tokio::test]
+async fn test_subscriptions_and_rate_limit() {
+    let channel = Arc::new(RecordingChannel::default());
+    let limit = RateLimit { max_per_window: 2, window_secs: 60 };
+    let notifier = Notifier::new().with_channel(channel.clone(), vec![EventKind::AgentGhost], limit);
+
+    let run = NotifyEvent::RunComplete {
+        run_id: "r1".to_string(),
+        summary: "done".to_string(),
+        success: true,
+    };
+    assert!(notifier.notify(&run).await.is_empty());
+
+    notifier.notify(&ghost("a")).await;
+    notifier.notify(&ghost("b")).await;
+    let outcomes = notifier.notify(&ghost("c")).await;
+
+    assert!(matches!(outcomes.as_slice(), [DeliveryOutcome::RateLimited { .. }]));
+    assert_eq!(channel.bodies.lock().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_config_with_templates_and_desktop() {
+    let config = NotifyConfig::from_toml_str(
+        r#"
+[templates]
+budget_threshold = "{{entity_id}} is at {{spent}} of {{limit}}"
+
+[[channels]]
+type = "desktop"
+events = ["budget_threshold"]
+
+[[channels]]
+type = "webhook"
+name = "ops"
+url = "http://127.0.0.1:9/hook"
+events = ["run_complete"]
+"#,
+    )
+    .unwrap();
+
+    let desktop = Arc::new(DesktopChannel::new());
+    let mut rx = desktop.subscribe();
+    let notifier = Notifier::from_config(config, Some(desktop)).unwrap();
+
+    let outcomes = notifier
+        .notify(&NotifyEvent::BudgetThreshold {
+            entity_id: "agent-1".to_string(),
+            spent: "8.00".to_string(),
+            limit: "10.00".to_string(),
+        })
+        .await;
+
+    assert_eq!(outcomes, vec![DeliveryOutcome::Delivered { channel: "desktop".to_string() }]);
+    assert_eq!(rx.recv().await.unwrap().body, "agent-1 is at 8.00 of 10.00");
+}
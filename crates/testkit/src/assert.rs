@@ -0,0 +1,121 @@
+//! Assertions over blackboard and ledger state
+//!
+//! Each helper panics with the state it actually found, so a failing
+//! integration test says what went wrong without extra debugging.
+
+use rust_decimal::Decimal;
+use zed42_blackboard::{AgentId, BlackboardDb, DecisionNode, Message, MessageFilter};
+use zed42_ledger::IntelligenceLedger;
+
+use crate::db::LedgerFixture;
+
+/// Assert a state key holds `expected`
+pub async fn state_eq(blackboard: &BlackboardDb, key: &str, expected: serde_json::Value) {
+    let entry = blackboard
+        .get_state(&key.to_string())
+        .await
+        .unwrap_or_else(|e| panic!("Failed to read state {:?}: {}", key, e));
+    match entry {
+        Some(entry) => assert_eq!(entry.value, expected, "State {:?} has an unexpected value", key),
+        None => panic!("State {:?} was never set (expected {})", key, expected),
+    }
+}
+
+/// Assert a state key was never set
+pub async fn state_absent(blackboard: &BlackboardDb, key: &str) {
+    let entry = blackboard
+        .get_state(&key.to_string())
+        .await
+        .unwrap_or_else(|e| panic!("Failed to read state {:?}: {}", key, e));
+    if let Some(entry) = entry {
+        panic!("State {:?} should be unset but holds {}", key, entry.value);
+    }
+}
+
+/// Assert at least one message matching `filter` satisfies `predicate`, returning it
+pub async fn message_posted(
+    blackboard: &BlackboardDb,
+    filter: MessageFilter,
+    predicate: impl Fn(&Message) -> bool,
+) -> Message {
+    let messages = blackboard
+        .get_messages(filter)
+        .await
+        .unwrap_or_else(|e| panic!("Failed to read messages: {}", e));
+    let count = messages.len();
+    messages.into_iter().find(|m| predicate(m)).unwrap_or_else(|| {
+        panic!("No matching message among {} posted for the filter", count)
+    })
+}
+
+/// Assert exactly `expected` messages match `filter`
+pub async fn message_count(blackboard: &BlackboardDb, filter: MessageFilter, expected: usize) {
+    let messages = blackboard
+        .get_messages(filter)
+        .await
+        .unwrap_or_else(|e| panic!("Failed to read messages: {}", e));
+    assert_eq!(messages.len(), expected, "Unexpected message count: {:?}", messages);
+}
+
+/// Assert `agent_id` recorded a decision of `decision_type`, returning the latest one
+pub async fn decision_recorded(blackboard: &BlackboardDb, agent_id: AgentId, decision_type: &str) -> DecisionNode {
+    let decisions = blackboard
+        .get_decisions(Some(agent_id))
+        .await
+        .unwrap_or_else(|e| panic!("Failed to read decisions: {}", e));
+    let types: Vec<String> = decisions.iter().map(|d| d.decision_type.clone()).collect();
+    decisions
+        .into_iter()
+        .filter(|d| d.decision_type == decision_type)
+        .max_by_key(|d| d.timestamp)
+        .unwrap_or_else(|| {
+            panic!(
+                "Agent {} recorded no {:?} decision (recorded: {:?})",
+                agent_id, decision_type, types
+            )
+        })
+}
+
+/// Assert `entity_id` has spent exactly `expected`
+pub async fn budget_spent(ledger: &IntelligenceLedger, entity_id: &str, expected: Decimal) {
+    let budget = ledger
+        .get_budget(entity_id)
+        .await
+        .unwrap_or_else(|e| panic!("Failed to read budget {:?}: {}", entity_id, e))
+        .unwrap_or_else(|| panic!("No budget for {:?}", entity_id));
+    assert_eq!(budget.spent, expected, "Budget {:?} spent an unexpected amount", entity_id);
+}
+
+/// Assert `entity_id` has spent no more than `limit`
+pub async fn budget_within(ledger: &IntelligenceLedger, entity_id: &str, limit: Decimal) {
+    let budget = ledger
+        .get_budget(entity_id)
+        .await
+        .unwrap_or_else(|e| panic!("Failed to read budget {:?}: {}", entity_id, e))
+        .unwrap_or_else(|| panic!("No budget for {:?}", entity_id));
+    assert!(
+        budget.spent <= limit,
+        "Budget {:?} spent {} which exceeds {}",
+        entity_id,
+        budget.spent,
+        limit
+    );
+}
+
+/// Assert every lease was settled or released, and none is left in the Router journal
+pub async fn no_open_leases(fixture: &LedgerFixture) {
+    let leases = fixture.count("leases").await.expect("Failed to count leases");
+    assert_eq!(leases, 0, "{} leases were never settled", leases);
+    let journaled = fixture.count("lease_journal").await.expect("Failed to count lease journal");
+    assert_eq!(journaled, 0, "{} leases are still in the Router journal", journaled);
+}
+
+/// Assert the ledger hash chain verifies, returning the number of entries
+pub async fn chain_valid(ledger: &IntelligenceLedger) -> usize {
+    let verification = ledger
+        .verify_chain()
+        .await
+        .unwrap_or_else(|e| panic!("Failed to verify ledger chain: {}", e));
+    assert!(verification.is_valid(), "Ledger chain is broken: {:?}", verification.issues);
+    verification.entries_checked
+}
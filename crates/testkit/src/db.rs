@@ -0,0 +1,134 @@
+//! Database-backed fixtures
+
+use std::ops::Deref;
+use std::sync::Arc;
+
+use anyhow::Result;
+use chrono::Utc;
+use rust_decimal::Decimal;
+use surrealdb::engine::any::{connect, Any};
+use surrealdb::Surreal;
+use tempfile::TempDir;
+use zed42_blackboard::BlackboardDb;
+use zed42_ledger::types::{Budget, BudgetStatus, RateTableEntry};
+use zed42_ledger::IntelligenceLedger;
+
+/// Model the Router settles released leases against (see `zed42-mom`)
+pub const LEASE_CLEANUP_MODEL: &str = "lease-guard-cleanup";
+
+/// Fresh in-memory SurrealDB with a unique database selected
+pub async fn memory_db() -> Result<Surreal<Any>> {
+    let db = connect("mem://").await?;
+    db.use_ns("zed42_test")
+        .use_db(format!("test_{}", uuid::Uuid::new_v4().simple()))
+        .await?;
+    Ok(db)
+}
+
+/// An Intelligence Ledger on an in-memory database
+///
+/// The lease cleanup rate is registered up front so released leases settle
+/// instead of failing with `RateNotFound`.
+pub struct LedgerFixture {
+    db: Surreal<Any>,
+    ledger: Arc<IntelligenceLedger>,
+}
+
+impl LedgerFixture {
+    pub async fn new() -> Result<Self> {
+        let db = memory_db().await?;
+        let ledger = Arc::new(IntelligenceLedger::new(db.clone()));
+        let fixture = Self { db, ledger };
+        fixture.set_rate(LEASE_CLEANUP_MODEL, Decimal::ZERO, Decimal::ZERO).await?;
+        Ok(fixture)
+    }
+
+    /// Give `entity_id` an active budget; the soft limit is half the hard limit
+    pub async fn with_budget(self, entity_id: &str, hard_limit: Decimal) -> Result<Self> {
+        self.ledger
+            .set_budget(Budget {
+                entity_id: entity_id.to_string(),
+                hard_limit,
+                soft_limit: hard_limit / Decimal::from(2),
+                spent: Decimal::ZERO,
+                currency: "USD".to_string(),
+                updated_at: Utc::now(),
+                status: BudgetStatus::Active,
+                tags: Default::default(),
+            })
+            .await?;
+        Ok(self)
+    }
+
+    /// Price a model per 1k input/output tokens
+    pub async fn set_rate(&self, model: &str, input_per_1k: Decimal, output_per_1k: Decimal) -> Result<()> {
+        self.ledger
+            .set_rate(RateTableEntry {
+                model: model.to_string(),
+                input_cost_per_1k: input_per_1k,
+                output_cost_per_1k: output_per_1k,
+                cache_read_cost_per_1k: None,
+                cache_write_cost_per_1k: None,
+            })
+            .await?;
+        Ok(())
+    }
+
+    pub fn ledger(&self) -> Arc<IntelligenceLedger> {
+        Arc::clone(&self.ledger)
+    }
+
+    /// The database backing the ledger, shared with anything built on top (e.g. a Router)
+    pub fn db(&self) -> Surreal<Any> {
+        self.db.clone()
+    }
+
+    /// Number of rows in `table`
+    pub async fn count(&self, table: &str) -> Result<usize> {
+        let mut response = self
+            .db
+            .query("SELECT count() FROM type::table($tb) GROUP ALL")
+            .bind(("tb", table.to_string()))
+            .await?;
+        let count: Option<i64> = response.take("count")?;
+        Ok(count.unwrap_or(0) as usize)
+    }
+}
+
+/// A blackboard in a temp directory that is removed on drop
+///
+/// Derefs to [`BlackboardDb`]. The MOM watcher points at an address nothing
+/// listens on, so only the local store is exercised.
+pub struct BlackboardFixture {
+    blackboard: Arc<BlackboardDb>,
+    _dir: TempDir,
+}
+
+impl BlackboardFixture {
+    pub async fn new() -> Result<Self> {
+        let dir = TempDir::new()?;
+        let blackboard = BlackboardDb::new(dir.path(), "test_project", "ws://localhost:8000").await?;
+        Ok(Self {
+            blackboard: Arc::new(blackboard),
+            _dir: dir,
+        })
+    }
+
+    pub fn shared(&self) -> Arc<BlackboardDb> {
+        Arc::clone(&self.blackboard)
+    }
+}
+
+impl Deref for BlackboardFixture {
+    type Target = BlackboardDb;
+
+    fn deref(&self) -> &BlackboardDb {
+        &self.blackboard
+    }
+}
+
+impl Drop for BlackboardFixture {
+    fn drop(&mut self) {
+        self.blackboard.watcher_token().cancel();
+    }
+}
@@ -0,0 +1,357 @@
+//! Scenario-based golden tests
+//!
+//! A scenario is a TOML file describing an intent, the plan the Cortex
+//! would derive from it, and for each plan step the agent that runs it, the
+//! prompt it sends and the canned LLM response it gets back. The runner
+//! plays the scenario against a fresh blackboard and workspace, then
+//! snapshots the decisions recorded on the blackboard and the artifacts
+//! written to the workspace.
+//!
+//! Snapshots are pretty-printed JSON checked in next to the scenarios.
+//! Set `ZED42_UPDATE_GOLDEN=1` to rewrite them after an intentional change.
+//!
+//! ```toml
+//! name = "add-subtraction"
+//! intent = "Add a subtraction function"
+//! sample = "rust_lib"
+//!
+//! [[steps]]
+//! agent = "FeatureImplementer"
+//! task = "Implement sub() in src/math.rs"
+//! prompt = "Implement the TODO in src/math.rs"
+//! response = "pub fn sub(a: i64, b: i64) -> i64 { a - b }"
+//! decision = "implementation"
+//! artifact = { path = "src/math.rs", mode = "append" }
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use zed42_blackboard::{AgentId, DecisionNode, StateEntry};
+use zed42_llm::{LlmClient, LlmRequest};
+
+use crate::db::BlackboardFixture;
+use crate::llm::ScriptedLlm;
+use crate::workspace::{SampleRepo, TempWorkspace};
+
+/// Environment variable that switches the runner to update mode
+pub const UPDATE_ENV: &str = "ZED42_UPDATE_GOLDEN";
+
+/// Sample repositories a scenario can start from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScenarioSample {
+    RustLib,
+    PythonPackage,
+    TypeScriptApp,
+}
+
+impl From<ScenarioSample> for SampleRepo {
+    fn from(sample: ScenarioSample) -> Self {
+        match sample {
+            ScenarioSample::RustLib => SampleRepo::RustLib,
+            ScenarioSample::PythonPackage => SampleRepo::PythonPackage,
+            ScenarioSample::TypeScriptApp => SampleRepo::TypeScriptApp,
+        }
+    }
+}
+
+/// How a step's response lands in the workspace
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactMode {
+    #[default]
+    Replace,
+    Append,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArtifactSpec {
+    pub path: String,
+    #[serde(default)]
+    pub mode: ArtifactMode,
+}
+
+/// One agent interaction in the plan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScenarioStep {
+    /// Agent role running the step (e.g. "FeatureImplementer")
+    pub agent: String,
+    pub task: String,
+    pub prompt: String,
+    /// Canned LLM response for the prompt
+    pub response: String,
+    /// Decision type recorded on the blackboard
+    pub decision: String,
+    #[serde(default)]
+    pub artifact: Option<ArtifactSpec>,
+}
+
+/// A scripted intent → plan → agent interactions run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Scenario {
+    pub name: String,
+    pub intent: String,
+    #[serde(default)]
+    pub sample: Option<ScenarioSample>,
+    pub steps: Vec<ScenarioStep>,
+}
+
+impl Scenario {
+    pub fn load(path: &Path) -> Result<Self> {
+        let raw = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read scenario {}", path.display()))?;
+        let scenario: Scenario = toml::from_str(&raw)
+            .with_context(|| format!("Failed to parse scenario {}", path.display()))?;
+        if scenario.steps.is_empty() {
+            bail!("Scenario {} has no steps", scenario.name);
+        }
+        Ok(scenario)
+    }
+}
+
+/// A decision as captured in a snapshot (ids and timestamps are left out)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DecisionSnapshot {
+    pub step: usize,
+    pub agent: String,
+    pub decision_type: String,
+    pub description: String,
+    pub rationale: serde_json::Value,
+    /// Step whose decision this one follows
+    pub follows: Option<usize>,
+}
+
+/// Everything a scenario run produced that the snapshot compares
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScenarioSnapshot {
+    pub scenario: String,
+    pub intent: String,
+    pub plan: Vec<String>,
+    pub decisions: Vec<DecisionSnapshot>,
+    /// Final contents of every file a step wrote
+    pub artifacts: BTreeMap<String, String>,
+}
+
+/// Play a scenario and capture its snapshot
+pub async fn run_scenario(scenario: &Scenario) -> Result<ScenarioSnapshot> {
+    let workspace = match scenario.sample {
+        Some(sample) => TempWorkspace::with_sample(sample.into())?,
+        None => TempWorkspace::new()?,
+    };
+    let blackboard = BlackboardFixture::new().await?;
+    let llm = scenario
+        .steps
+        .iter()
+        .fold(ScriptedLlm::new(), |llm, step| llm.reply(step.response.clone()));
+
+    let cortex_id = uuid::Uuid::new_v4();
+    let plan: Vec<String> = scenario.steps.iter().map(|s| s.task.clone()).collect();
+    for (key, value) in [("intent", json!(scenario.intent)), ("plan", json!(plan))] {
+        blackboard
+            .set_state(StateEntry {
+                key: key.to_string(),
+                value,
+                owner_agent: cortex_id,
+                timestamp: chrono::Utc::now().timestamp(),
+                version: 1,
+                tenant_id: None,
+            })
+            .await?;
+    }
+
+    let mut agents: HashMap<String, AgentId> = HashMap::new();
+    let mut previous: Option<String> = None;
+    let mut artifacts = Vec::new();
+    for (index, step) in scenario.steps.iter().enumerate() {
+        let agent_id = *agents.entry(step.agent.clone()).or_insert_with(uuid::Uuid::new_v4);
+        let response = llm
+            .complete(LlmRequest::new(step.prompt.clone()).agent(step.agent.clone()))
+            .await
+            .with_context(|| format!("Step {} ({}) failed", index, step.task))?;
+
+        if let Some(artifact) = &step.artifact {
+            let content = match artifact.mode {
+                ArtifactMode::Replace => response.content.clone(),
+                ArtifactMode::Append => {
+                    let existing = workspace.read(&artifact.path).unwrap_or_default();
+                    format!("{}{}\n", existing, response.content)
+                }
+            };
+            workspace.write(&artifact.path, &content)?;
+            artifacts.push(artifact.path.clone());
+        }
+
+        let decision_id = format!("{}-{}", scenario.name, index);
+        blackboard
+            .record_decision(DecisionNode {
+                id: decision_id.clone(),
+                decision_type: step.decision.clone(),
+                description: step.task.clone(),
+                made_by: agent_id,
+                rationale: json!({ "step": index, "response": response.content }),
+                alternatives_considered: Vec::new(),
+                timestamp: chrono::Utc::now().timestamp(),
+                parent_decision: previous.replace(decision_id),
+                confidence: None,
+            })
+            .await?;
+    }
+    llm.assert_exhausted();
+
+    let names: HashMap<AgentId, String> = agents.into_iter().map(|(name, id)| (id, name)).collect();
+    let mut decisions: Vec<DecisionSnapshot> = blackboard
+        .get_decisions(None)
+        .await?
+        .into_iter()
+        .filter(|d| d.made_by != cortex_id)
+        .map(|d| DecisionSnapshot {
+            step: d.rationale["step"].as_u64().unwrap_or_default() as usize,
+            agent: names.get(&d.made_by).cloned().unwrap_or_else(|| d.made_by.to_string()),
+            decision_type: d.decision_type,
+            description: d.description,
+            follows: d
+                .parent_decision
+                .and_then(|p| p.rsplit('-').next().and_then(|n| n.parse().ok())),
+            rationale: d.rationale,
+        })
+        .collect();
+    decisions.sort_by_key(|d| d.step);
+
+    let artifacts = artifacts
+        .into_iter()
+        .map(|path| {
+            let content = workspace.read(&path)?;
+            Ok((path, content))
+        })
+        .collect::<Result<BTreeMap<_, _>>>()?;
+
+    Ok(ScenarioSnapshot {
+        scenario: scenario.name.clone(),
+        intent: scenario.intent.clone(),
+        plan,
+        decisions,
+        artifacts,
+    })
+}
+
+/// Runs every scenario in a directory against its checked-in snapshot
+pub struct GoldenRunner {
+    scenario_dir: PathBuf,
+    snapshot_dir: PathBuf,
+    update: bool,
+}
+
+impl GoldenRunner {
+    /// Scenarios are `*.toml` in `scenario_dir`; snapshots `<name>.json` in `snapshot_dir`
+    pub fn new(scenario_dir: impl Into<PathBuf>, snapshot_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            scenario_dir: scenario_dir.into(),
+            snapshot_dir: snapshot_dir.into(),
+            update: std::env::var(UPDATE_ENV).is_ok_and(|v| v != "0" && !v.is_empty()),
+        }
+    }
+
+    /// Force update mode on or off, ignoring the environment
+    pub fn with_update(mut self, update: bool) -> Self {
+        self.update = update;
+        self
+    }
+
+    fn scenarios(&self) -> Result<Vec<PathBuf>> {
+        let mut paths: Vec<PathBuf> = std::fs::read_dir(&self.scenario_dir)
+            .with_context(|| format!("Failed to list {}", self.scenario_dir.display()))?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+            .collect();
+        paths.sort();
+        Ok(paths)
+    }
+
+    /// Run one scenario file, comparing (or in update mode, rewriting) its snapshot
+    pub async fn check(&self, scenario_path: &Path) -> Result<()> {
+        let scenario = Scenario::load(scenario_path)?;
+        let snapshot = run_scenario(&scenario).await?;
+        let actual = serde_json::to_string_pretty(&snapshot)? + "\n";
+        let snapshot_path = self.snapshot_dir.join(format!("{}.json", scenario.name));
+
+        if self.update {
+            std::fs::create_dir_all(&self.snapshot_dir)?;
+            std::fs::write(&snapshot_path, &actual)?;
+            tracing::info!(scenario = %scenario.name, "Updated golden snapshot");
+            return Ok(());
+        }
+
+        let expected = std::fs::read_to_string(&snapshot_path).with_context(|| {
+            format!(
+                "No snapshot for scenario {} at {} (run with {}=1 to create it)",
+                scenario.name,
+                snapshot_path.display(),
+                UPDATE_ENV
+            )
+        })?;
+        if expected != actual {
+            bail!(
+                "Scenario {} differs from {}:\n{}\nRun with {}=1 if the change is intended.",
+                scenario.name,
+                snapshot_path.display(),
+                first_difference(&expected, &actual),
+                UPDATE_ENV
+            );
+        }
+        Ok(())
+    }
+
+    /// Run every scenario, reporting all mismatches together
+    pub async fn check_all(&self) -> Result<usize> {
+        let scenarios = self.scenarios()?;
+        let mut failures = Vec::new();
+        for path in &scenarios {
+            if let Err(e) = self.check(path).await {
+                failures.push(format!("{:#}", e));
+            }
+        }
+        if !failures.is_empty() {
+            bail!("{} of {} golden scenarios failed:\n\n{}", failures.len(), scenarios.len(), failures.join("\n\n"));
+        }
+        Ok(scenarios.len())
+    }
+}
+
+/// The first line where two snapshots differ, with its line number
+fn first_difference(expected: &str, actual: &str) -> String {
+    let mut expected_lines = expected.lines();
+    let mut actual_lines = actual.lines();
+    let mut line = 1;
+    loop {
+        match (expected_lines.next(), actual_lines.next()) {
+            (Some(e), Some(a)) if e == a => line += 1,
+            (None, None) => return "(snapshots are equal)".to_string(),
+            (e, a) => {
+                return format!(
+                    "line {}:\n  expected: {}\n  actual:   {}",
+                    line,
+                    e.unwrap_or("<end of snapshot>"),
+                    a.unwrap_or("<end of output>")
+                )
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_difference() {
+        assert_eq!(
+            first_difference("a\nb\nc", "a\nx\nc"),
+            "line 2:\n  expected: b\n  actual:   x"
+        );
+        assert!(first_difference("a", "a\nb").contains("<end of snapshot>"));
+    }
+}
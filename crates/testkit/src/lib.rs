@@ -0,0 +1,36 @@
+//! Test fixtures for agent-level integration tests
+//!
+//! Everything an integration test usually has to build by hand:
+//!
+//! - [`db`]: in-memory SurrealDB, a funded [`LedgerFixture`] and a
+//!   [`BlackboardFixture`] that owns its temp directory
+//! - [`llm`]: [`ScriptedLlm`], an `LlmClient` driven by a small scripting DSL
+//!   that records every call
+//! - [`workspace`]: [`TempWorkspace`] with sample repositories, optionally
+//!   committed to git
+//! - [`assert`]: assertions over blackboard and ledger state with readable
+//!   failure messages
+//! - [`golden`]: scenario runner comparing decisions and artifacts against
+//!   checked-in snapshots
+//!
+//! ```ignore
+//! let ledger = LedgerFixture::new().await?.with_budget("default", dec!(10)).await?;
+//! let llm = ScriptedLlm::new()
+//!     .when_prompt_contains("plan").reply("1. write the test")
+//!     .reply("done");
+//! let router = Router::new(ledger.ledger(), ledger.db(), Arc::new(llm.clone()));
+//! // ... drive the agent ...
+//! assert::no_open_leases(&ledger).await;
+//! assert_eq!(llm.call_count(), 2);
+//! ```
+
+pub mod assert;
+pub mod db;
+pub mod golden;
+pub mod llm;
+pub mod workspace;
+
+pub use db::{memory_db, BlackboardFixture, LedgerFixture};
+pub use golden::{run_scenario, GoldenRunner, Scenario, ScenarioSnapshot};
+pub use llm::{LlmCall, ScriptedLlm};
+pub use workspace::{SampleRepo, TempWorkspace};
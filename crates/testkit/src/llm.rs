@@ -0,0 +1,346 @@
+//! Scripted LLM client
+//!
+//! `MockLlmClient` replays a fixed list of strings. `ScriptedLlm` adds what
+//! agent-level tests need on top: prompt-matched replies, scripted
+//! failures, JSON replies, usage/model overrides, deterministic embeddings
+//! and a record of every call.
+//!
+//! Resolution order for each completion: the first matching `when_*` rule,
+//! then the next queued reply, then the fallback. Rules stay active for the
+//! whole test; queued replies are consumed once.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::VecDeque;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use zed42_llm::{
+    EmbeddingRequest, EmbeddingResponse, LlmClient, LlmError, LlmRequest, LlmResponse, StreamChunk, Usage,
+};
+
+type Matcher = Box<dyn Fn(&LlmRequest) -> bool + Send + Sync>;
+type ErrorFactory = Box<dyn Fn() -> LlmError + Send + Sync>;
+
+/// What a scripted step produces
+enum Step {
+    Reply(String),
+    Fail(ErrorFactory),
+}
+
+impl Step {
+    fn fail(error: impl Fn() -> LlmError + Send + Sync + 'static) -> Self {
+        Step::Fail(Box::new(error))
+    }
+}
+
+/// One recorded completion call
+#[derive(Debug, Clone)]
+pub struct LlmCall {
+    pub prompt: String,
+    pub system_prompt: Option<String>,
+    pub model: String,
+    pub agent_id: Option<String>,
+}
+
+struct Script {
+    rules: Vec<(Matcher, Step)>,
+    queue: VecDeque<Step>,
+    fallback: Option<String>,
+    model: Option<String>,
+    usage: Usage,
+    embedding_dimension: usize,
+    calls: Vec<LlmCall>,
+    embed_calls: Vec<String>,
+}
+
+/// An `LlmClient` driven by a script; clones share the script and call log
+#[derive(Clone)]
+pub struct ScriptedLlm {
+    script: Arc<Mutex<Script>>,
+}
+
+impl Default for ScriptedLlm {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ScriptedLlm {
+    pub fn new() -> Self {
+        Self {
+            script: Arc::new(Mutex::new(Script {
+                rules: Vec::new(),
+                queue: VecDeque::new(),
+                fallback: None,
+                model: None,
+                usage: Usage {
+                    prompt_tokens: 10,
+                    completion_tokens: 20,
+                    total_tokens: 30,
+                    cache_read_tokens: 0,
+                    cache_write_tokens: 0,
+                },
+                embedding_dimension: 1536,
+                calls: Vec::new(),
+                embed_calls: Vec::new(),
+            })),
+        }
+    }
+
+    /// Queue a reply for the next call no rule matches
+    pub fn reply(self, content: impl Into<String>) -> Self {
+        self.script.lock().queue.push_back(Step::Reply(content.into()));
+        self
+    }
+
+    /// Queue a JSON reply (for constrained/structured calls)
+    pub fn reply_json(self, value: serde_json::Value) -> Self {
+        self.reply(value.to_string())
+    }
+
+    /// Queue a failure for the next call no rule matches
+    pub fn fail(self, error: impl Fn() -> LlmError + Send + Sync + 'static) -> Self {
+        self.script.lock().queue.push_back(Step::fail(error));
+        self
+    }
+
+    /// Queue `n` rate-limit errors (e.g. to exhaust Router retries)
+    pub fn rate_limited(self, n: usize) -> Self {
+        (0..n).fold(self, |llm, _| llm.fail(|| LlmError::RateLimitExceeded))
+    }
+
+    /// Reply used once the queue is empty, instead of an error
+    pub fn otherwise(self, content: impl Into<String>) -> Self {
+        self.script.lock().fallback = Some(content.into());
+        self
+    }
+
+    /// Start a rule for calls whose prompt contains `needle`
+    pub fn when_prompt_contains(self, needle: impl Into<String>) -> When {
+        let needle = needle.into();
+        self.when(move |req| req.prompt.contains(&needle))
+    }
+
+    /// Start a rule for calls made on behalf of `agent_id`
+    pub fn when_agent(self, agent_id: impl Into<String>) -> When {
+        let agent_id = agent_id.into();
+        self.when(move |req| req.agent_id.as_deref() == Some(agent_id.as_str()))
+    }
+
+    /// Start a rule for calls matching an arbitrary predicate
+    pub fn when(self, matcher: impl Fn(&LlmRequest) -> bool + Send + Sync + 'static) -> When {
+        When {
+            llm: self,
+            matcher: Box::new(matcher),
+        }
+    }
+
+    /// Report this model in responses instead of the requested one
+    pub fn with_model(self, model: impl Into<String>) -> Self {
+        self.script.lock().model = Some(model.into());
+        self
+    }
+
+    pub fn with_usage(self, prompt_tokens: usize, completion_tokens: usize) -> Self {
+        self.script.lock().usage = Usage {
+            prompt_tokens,
+            completion_tokens,
+            total_tokens: prompt_tokens + completion_tokens,
+            cache_read_tokens: 0,
+            cache_write_tokens: 0,
+        };
+        self
+    }
+
+    pub fn with_embedding_dimension(self, dimension: usize) -> Self {
+        self.script.lock().embedding_dimension = dimension;
+        self
+    }
+
+    /// Completion and stream calls so far, in order
+    pub fn calls(&self) -> Vec<LlmCall> {
+        self.script.lock().calls.clone()
+    }
+
+    pub fn call_count(&self) -> usize {
+        self.script.lock().calls.len()
+    }
+
+    /// Inputs passed to `embed`, in order
+    pub fn embedded_inputs(&self) -> Vec<String> {
+        self.script.lock().embed_calls.clone()
+    }
+
+    /// Queued replies not consumed yet
+    pub fn remaining(&self) -> usize {
+        self.script.lock().queue.len()
+    }
+
+    /// Panic unless every queued reply was used
+    pub fn assert_exhausted(&self) {
+        let remaining = self.remaining();
+        assert_eq!(remaining, 0, "ScriptedLlm: {} queued replies were never requested", remaining);
+    }
+
+    fn respond(&self, request: &LlmRequest) -> zed42_llm::Result<LlmResponse> {
+        let mut script = self.script.lock();
+        script.calls.push(LlmCall {
+            prompt: request.prompt.clone(),
+            system_prompt: request.system_prompt.clone(),
+            model: request.config.model.clone(),
+            agent_id: request.agent_id.clone(),
+        });
+
+        let content = match script.rules.iter().find(|(matcher, _)| matcher(request)) {
+            Some((_, Step::Reply(content))) => content.clone(),
+            Some((_, Step::Fail(error))) => return Err(error()),
+            None => match script.queue.pop_front() {
+                Some(Step::Reply(content)) => content,
+                Some(Step::Fail(error)) => return Err(error()),
+                None => script.fallback.clone().ok_or_else(|| {
+                    LlmError::InvalidResponse(format!(
+                        "ScriptedLlm: no scripted response for prompt {:?}",
+                        request.prompt
+                    ))
+                })?,
+            },
+        };
+
+        Ok(LlmResponse {
+            content,
+            model: script.model.clone().unwrap_or_else(|| request.config.model.clone()),
+            usage: script.usage.clone(),
+            finish_reason: "stop".to_string(),
+        })
+    }
+}
+
+/// A rule being built by `ScriptedLlm::when*`
+pub struct When {
+    llm: ScriptedLlm,
+    matcher: Matcher,
+}
+
+impl When {
+    /// Answer every matching call with `content`
+    pub fn reply(self, content: impl Into<String>) -> ScriptedLlm {
+        self.then(Step::Reply(content.into()))
+    }
+
+    pub fn reply_json(self, value: serde_json::Value) -> ScriptedLlm {
+        self.reply(value.to_string())
+    }
+
+    /// Fail every matching call
+    pub fn fail(self, error: impl Fn() -> LlmError + Send + Sync + 'static) -> ScriptedLlm {
+        self.then(Step::fail(error))
+    }
+
+    fn then(self, step: Step) -> ScriptedLlm {
+        self.llm.script.lock().rules.push((self.matcher, step));
+        self.llm
+    }
+}
+
+/// Unit vector derived from the text, so equal inputs embed identically
+fn embedding_for(text: &str, dimension: usize) -> Vec<f32> {
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    let mut state = hasher.finish() | 1;
+    let mut vector: Vec<f32> = (0..dimension)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state >> 40) as f32 / (1u64 << 24) as f32 - 0.5
+        })
+        .collect();
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|v| *v /= norm);
+    }
+    vector
+}
+
+#[async_trait]
+impl LlmClient for ScriptedLlm {
+    async fn complete(&self, request: LlmRequest) -> zed42_llm::Result<LlmResponse> {
+        self.respond(&request)
+    }
+
+    async fn stream(&self, request: LlmRequest) -> zed42_llm::Result<Vec<StreamChunk>> {
+        let response = self.respond(&request)?;
+        Ok(vec![StreamChunk {
+            content: response.content,
+            is_final: true,
+        }])
+    }
+
+    async fn embed(&self, request: EmbeddingRequest) -> zed42_llm::Result<EmbeddingResponse> {
+        let dimension = {
+            let mut script = self.script.lock();
+            script.embed_calls.push(request.input.clone());
+            script.embedding_dimension
+        };
+        Ok(EmbeddingResponse {
+            embedding: embedding_for(&request.input, dimension),
+            model: request.model,
+            usage: Usage {
+                prompt_tokens: request.input.split_whitespace().count(),
+                completion_tokens: 0,
+                total_tokens: request.input.split_whitespace().count(),
+                cache_read_tokens: 0,
+                cache_write_tokens: 0,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(prompt: &str) -> LlmRequest {
+        LlmRequest::new(prompt.to_string())
+    }
+
+    #[tokio::test]
+    async fn test_rules_take_precedence_over_queue() {
+        let llm = ScriptedLlm::new()
+            .when_prompt_contains("plan")
+            .reply("the plan")
+            .reply("first")
+            .rate_limited(1)
+            .otherwise("fallback");
+
+        assert_eq!(llm.complete(request("make a plan")).await.unwrap().content, "the plan");
+        assert_eq!(llm.complete(request("go")).await.unwrap().content, "first");
+        assert!(matches!(llm.complete(request("go")).await, Err(LlmError::RateLimitExceeded)));
+        assert_eq!(llm.complete(request("go")).await.unwrap().content, "fallback");
+        assert_eq!(llm.complete(request("another plan")).await.unwrap().content, "the plan");
+
+        assert_eq!(llm.call_count(), 5);
+        assert_eq!(llm.calls()[0].prompt, "make a plan");
+        llm.assert_exhausted();
+    }
+
+    #[tokio::test]
+    async fn test_unscripted_call_errors() {
+        let llm = ScriptedLlm::new();
+        assert!(matches!(llm.complete(request("hi")).await, Err(LlmError::InvalidResponse(_))));
+    }
+
+    #[tokio::test]
+    async fn test_embeddings_are_deterministic() {
+        let llm = ScriptedLlm::new().with_embedding_dimension(8);
+        let a = llm.embed(EmbeddingRequest::new("auth".to_string())).await.unwrap();
+        let b = llm.embed(EmbeddingRequest::new("auth".to_string())).await.unwrap();
+        let c = llm.embed(EmbeddingRequest::new("billing".to_string())).await.unwrap();
+        assert_eq!(a.embedding.len(), 8);
+        assert_eq!(a.embedding, b.embedding);
+        assert_ne!(a.embedding, c.embedding);
+        assert_eq!(llm.embedded_inputs().len(), 3);
+    }
+}
@@ -0,0 +1,169 @@
+//! Temporary workspaces with sample repositories
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use tempfile::TempDir;
+
+/// Small projects agents can be pointed at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SampleRepo {
+    /// Cargo library with one module, a unit test and a TODO to implement
+    RustLib,
+    /// Python package with a module and a pytest file
+    PythonPackage,
+    /// TypeScript project with a source file and a package.json
+    TypeScriptApp,
+}
+
+impl SampleRepo {
+    /// (relative path, contents) of every file in the sample
+    pub fn files(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            SampleRepo::RustLib => &[
+                (
+                    "Cargo.toml",
+                    "[package]\nname = \"sample\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+                ),
+                (
+                    "src/lib.rs",
+                    "pub mod math;\n\npub use math::add;\n",
+                ),
+                (
+                    "src/math.rs",
+                    "/// Add two numbers\npub fn add(a: i64, b: i64) -> i64 {\n    a + b\n}\n\n\
+                     // TODO: implement subtraction\n\n\
+                     #[cfg(test)]\nmod tests {\n    use super::*;\n\n    #[test]\n    fn test_add() {\n        assert_eq!(add(2, 2), 4);\n    }\n}\n",
+                ),
+                ("README.md", "# sample\n\nA tiny library used in ZED42 integration tests.\n"),
+            ],
+            SampleRepo::PythonPackage => &[
+                ("pyproject.toml", "[project]\nname = \"sample\"\nversion = \"0.1.0\"\n"),
+                ("sample/__init__.py", "from .math import add\n"),
+                ("sample/math.py", "def add(a, b):\n    \"\"\"Add two numbers.\"\"\"\n    return a + b\n"),
+                (
+                    "tests/test_math.py",
+                    "from sample import add\n\n\ndef test_add():\n    assert add(2, 2) == 4\n",
+                ),
+            ],
+            SampleRepo::TypeScriptApp => &[
+                (
+                    "package.json",
+                    "{\n  \"name\": \"sample\",\n  \"version\": \"0.1.0\",\n  \"scripts\": { \"test\": \"tsc --noEmit\" }\n}\n",
+                ),
+                ("tsconfig.json", "{\n  \"compilerOptions\": { \"strict\": true }\n}\n"),
+                (
+                    "src/index.ts",
+                    "export function add(a: number, b: number): number {\n  return a + b;\n}\n",
+                ),
+            ],
+        }
+    }
+}
+
+/// A directory that is removed when the workspace is dropped
+pub struct TempWorkspace {
+    dir: TempDir,
+}
+
+impl TempWorkspace {
+    /// Empty workspace
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            dir: TempDir::new().context("Failed to create temp workspace")?,
+        })
+    }
+
+    /// Workspace populated with a sample repository (not under git)
+    pub fn with_sample(sample: SampleRepo) -> Result<Self> {
+        let workspace = Self::new()?;
+        for (path, contents) in sample.files() {
+            workspace.write(path, contents)?;
+        }
+        Ok(workspace)
+    }
+
+    /// Sample repository committed to a fresh git repo
+    pub fn with_git_sample(sample: SampleRepo) -> Result<Self> {
+        let workspace = Self::with_sample(sample)?;
+        git2::Repository::init(workspace.path()).context("Failed to init git repo")?;
+        workspace.commit_all("Initial commit")?;
+        Ok(workspace)
+    }
+
+    pub fn path(&self) -> &Path {
+        self.dir.path()
+    }
+
+    /// Absolute path of a workspace-relative path
+    pub fn join(&self, relative: impl AsRef<Path>) -> PathBuf {
+        self.dir.path().join(relative)
+    }
+
+    /// Write a file, creating parent directories
+    pub fn write(&self, relative: impl AsRef<Path>, contents: &str) -> Result<PathBuf> {
+        let path = self.join(relative);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, contents).with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(path)
+    }
+
+    pub fn read(&self, relative: impl AsRef<Path>) -> Result<String> {
+        let path = self.join(relative);
+        std::fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))
+    }
+
+    pub fn exists(&self, relative: impl AsRef<Path>) -> bool {
+        self.join(relative).exists()
+    }
+
+    /// Stage every file and commit it, returning the commit id
+    pub fn commit_all(&self, message: &str) -> Result<String> {
+        let repo = git2::Repository::open(self.path()).context("Workspace is not a git repo")?;
+        let mut index = repo.index()?;
+        index.add_all(["*"], git2::IndexAddOption::DEFAULT, None)?;
+        index.write()?;
+        let tree = repo.find_tree(index.write_tree()?)?;
+        let signature = git2::Signature::now("ZED42 Test", "test@zed42.local")?;
+        let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        let oid = repo.commit(Some("HEAD"), &signature, &signature, message, &tree, &parents)?;
+        Ok(oid.to_string())
+    }
+
+    /// Paths changed since the last commit (modified, added or deleted)
+    pub fn git_changes(&self) -> Result<Vec<String>> {
+        let repo = git2::Repository::open(self.path()).context("Workspace is not a git repo")?;
+        let mut options = git2::StatusOptions::new();
+        options.include_untracked(true).recurse_untracked_dirs(true);
+        let statuses = repo.statuses(Some(&mut options))?;
+        let mut changes: Vec<String> = statuses
+            .iter()
+            .filter(|entry| entry.status() != git2::Status::CURRENT)
+            .filter_map(|entry| entry.path().map(str::to_string))
+            .collect();
+        changes.sort();
+        Ok(changes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_git_sample_tracks_changes() {
+        let workspace = TempWorkspace::with_git_sample(SampleRepo::RustLib).unwrap();
+        assert!(workspace.exists("src/math.rs"));
+        assert!(workspace.git_changes().unwrap().is_empty());
+
+        workspace.write("src/math.rs", "pub fn add() {}\n").unwrap();
+        workspace.write("src/new.rs", "").unwrap();
+        assert_eq!(workspace.git_changes().unwrap(), vec!["src/math.rs", "src/new.rs"]);
+
+        workspace.commit_all("Edit").unwrap();
+        assert!(workspace.git_changes().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,13 @@
+//! Golden scenarios; set ZED42_UPDATE_GOLDEN=1 to rewrite the snapshots
+
+use std::path::Path;
+
+use zed42_testkit::GoldenRunner;
+
+#[tokio::test]
+async fn test_golden_scenarios() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests");
+    let runner = GoldenRunner::new(root.join("scenarios"), root.join("golden"));
+    let checked = runner.check_all().await.unwrap_or_else(|e| panic!("{:#}", e));
+    assert!(checked > 0, "No scenarios found");
+}
@@ -0,0 +1,75 @@
+//! The fixtures wired together the way an agent-level test uses them
+
+use std::sync::Arc;
+
+use rust_decimal::Decimal;
+use serde_json::json;
+use zed42_blackboard::StateEntry;
+use zed42_llm::{LlmClient, LlmRequest, ModelConfig};
+use zed42_mom::{types::ExecutionProfile, Router};
+use zed42_testkit::{assert, BlackboardFixture, LedgerFixture, SampleRepo, ScriptedLlm, TempWorkspace};
+
+#[tokio::test]
+async fn test_agent_round_trip() {
+    let ledger = LedgerFixture::new()
+        .await
+        .unwrap()
+        .with_budget("coder", Decimal::from(10))
+        .await
+        .unwrap();
+    ledger.set_rate("tier1-model", Decimal::ONE, Decimal::from(2)).await.unwrap();
+
+    let llm = ScriptedLlm::new()
+        .rate_limited(1)
+        .reply("fn sub(a: i64, b: i64) -> i64 { a - b }");
+    let router = Router::new(ledger.ledger(), ledger.db(), Arc::new(llm.clone()));
+    let profile = ExecutionProfile::new(
+        "coder",
+        ModelConfig { model: "tier1-model".to_string(), ..ModelConfig::default() },
+    );
+    let _: Option<ExecutionProfile> = ledger.db().create(("model_profiles", "coder")).content(profile).await.unwrap();
+
+    let workspace = TempWorkspace::with_git_sample(SampleRepo::RustLib).unwrap();
+    let blackboard = BlackboardFixture::new().await.unwrap();
+    let agent_id = uuid::Uuid::new_v4();
+
+    // The agent asks for a change, applies it and reports back
+    let prompt = format!("Implement the TODO in src/math.rs:\n{}", workspace.read("src/math.rs").unwrap());
+    let response = router
+        .complete(LlmRequest::new(prompt).agent("coder".to_string()))
+        .await
+        .unwrap();
+    workspace
+        .write("src/math.rs", &format!("{}\n{}", workspace.read("src/math.rs").unwrap(), response.content))
+        .unwrap();
+    blackboard
+        .set_state(StateEntry {
+            key: "task:sub".to_string(),
+            value: json!({ "status": "done", "patch": response.content }),
+            owner_agent: agent_id,
+            timestamp: chrono::Utc::now().timestamp(),
+            version: 1,
+            tenant_id: None,
+        })
+        .await
+        .unwrap();
+
+    // The first attempt was rate limited and retried
+    assert_eq!(llm.call_count(), 2);
+    assert!(llm.calls().iter().all(|call| call.prompt.contains("TODO: implement subtraction")));
+    llm.assert_exhausted();
+    assert_eq!(workspace.git_changes().unwrap(), vec!["src/math.rs"]);
+
+    assert::state_eq(
+        &blackboard,
+        "task:sub",
+        json!({ "status": "done", "patch": "fn sub(a: i64, b: i64) -> i64 { a - b }" }),
+    )
+    .await;
+    assert::state_absent(&blackboard, "task:other").await;
+
+    // 10 input + 20 output tokens at $1/$2 per 1k
+    assert::budget_spent(&ledger.ledger(), "coder", Decimal::new(5, 2)).await;
+    assert::no_open_leases(&ledger).await;
+    assert!(assert::chain_valid(&ledger.ledger()).await >= 2);
+}
@@ -1 +1,230 @@
 //! Code analysis tools
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use zed42_core::workspace::ProjectWorkspace;
+use crate::file_manipulation::PathSanitizer;
+use crate::languages::{detect_languages, index_source, index_tree};
+use crate::{Tool, ToolError, ToolResult};
+
+/// Parameters for ParseAst tool
+#[derive(Debug, Default, Deserialize)]
+pub struct ParseAstParams {
+    /// File to index (relative to sandbox root); omit to index the whole project
+    #[serde(default)]
+    pub path: Option<String>,
+}
+
+/// ParseAst tool - indexes functions, types and modules with tree-sitter
+///
+/// Supports Rust, TypeScript, JavaScript, Python and Go. A single file
+/// returns its language and symbols; the whole project returns every indexed
+/// file plus the languages found, most common first.
+pub struct ParseAst {
+    sandbox_root: PathBuf,
+    sanitizer: PathSanitizer,
+}
+
+impl ParseAst {
+    pub fn new(sandbox_root: impl Into<PathBuf>) -> Self {
+        let sandbox_root = sandbox_root.into();
+        Self {
+            sanitizer: PathSanitizer::new(sandbox_root.clone()),
+            sandbox_root,
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for ParseAst {
+    fn name(&self) -> &str {
+        "parse_ast"
+    }
+
+    fn description(&self) -> &str {
+        "Index the functions, types and modules of a source file (Rust, TypeScript, JavaScript, Python, Go), or of the whole project"
+    }
+
+    fn parameter_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "File to index (relative to project root); omit for the whole project"
+                }
+            }
+        })
+    }
+
+    async fn execute(&self, params: Value) -> ToolResult {
+        let params: ParseAstParams = if params.is_null() {
+            ParseAstParams::default()
+        } else {
+            serde_json::from_value(params).map_err(ToolError::invalid_params)?
+        };
+
+        let Some(path) = params.path else {
+            let root = self.sandbox_root.clone();
+            let files = tokio::task::spawn_blocking(move || index_tree(&root)).await??;
+            let languages: Vec<Value> = detect_languages(&files)
+                .into_iter()
+                .map(|(language, files)| json!({ "language": language, "files": files }))
+                .collect();
+            return Ok(json!({ "success": true, "languages": languages, "files": files }));
+        };
+
+        let safe_path = self.sanitizer.sanitize(&path)
+            .map_err(ToolError::sandbox)?;
+        let source = tokio::fs::read_to_string(&safe_path).await
+            .map_err(|e| ToolError::io(format!("Failed to read file '{}'", path), e))?;
+        let index = index_source(&path, &source)?
+            .ok_or_else(|| ToolError::invalid_params(format!("Unsupported language for '{}'", path)))?;
+        Ok(json!({
+            "success": true,
+            "path": path,
+            "language": index.language,
+            "prompt_hint": index.language.prompt_hint(),
+            "symbols": index.symbols
+        }))
+    }
+}
+
+/// Parameters for WorkspacePackages tool
+#[derive(Debug, Default, Deserialize)]
+pub struct WorkspacePackagesParams {
+    /// Files to map to their owning package (relative to sandbox root)
+    #[serde(default)]
+    pub paths: Vec<String>,
+}
+
+/// WorkspacePackages tool - lists member packages and scopes files to them
+///
+/// For each path it reports the owning package plus every member that depends
+/// on it, with the build and test commands to run for just those packages.
+pub struct WorkspacePackages {
+    sandbox_root: PathBuf,
+}
+
+impl WorkspacePackages {
+    pub fn new(sandbox_root: impl Into<PathBuf>) -> Self {
+        Self {
+            sandbox_root: sandbox_root.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for WorkspacePackages {
+    fn name(&self) -> &str {
+        "workspace_packages"
+    }
+
+    fn description(&self) -> &str {
+        "List the project's packages (Cargo/npm workspace members) with their inter-package dependencies and build/test commands; map files to the package that owns them"
+    }
+
+    fn parameter_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "paths": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Files to map to their owning package (optional)"
+                }
+            }
+        })
+    }
+
+    async fn execute(&self, params: Value) -> ToolResult {
+        let params: WorkspacePackagesParams = if params.is_null() {
+            WorkspacePackagesParams::default()
+        } else {
+            serde_json::from_value(params).map_err(ToolError::invalid_params)?
+        };
+
+        // Manifests change as agents work, so the workspace is read per call
+        let root = self.sandbox_root.clone();
+        let workspace = tokio::task::spawn_blocking(move || ProjectWorkspace::discover(root)).await??;
+
+        let scopes: Vec<Value> = params
+            .paths
+            .iter()
+            .map(|path| match workspace.package_for_path(path) {
+                Some(owner) => {
+                    let affected: Vec<&str> = std::iter::once(owner)
+                        .chain(workspace.dependents(&owner.name))
+                        .map(|p| p.name.as_str())
+                        .collect();
+                    json!({
+                        "path": path,
+                        "package": owner.name,
+                        "affected_packages": affected,
+                        "build_command": owner.build_command,
+                        "test_command": owner.test_command,
+                    })
+                }
+                None => json!({ "path": path, "package": null }),
+            })
+            .collect();
+
+        Ok(json!({
+            "success": true,
+            "multi_package": workspace.is_multi_package(),
+            "packages": workspace.packages,
+            "scopes": scopes
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_parse_ast_python_file() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        std::fs::write(root.join("app.py"), "def handler(event):\n    return event\n").unwrap();
+
+        let tool = ParseAst::new(&root);
+        let result = tool.execute(json!({ "path": "app.py" })).await.unwrap();
+        assert_eq!(result["language"], "python");
+        assert_eq!(result["symbols"][0]["name"], "handler");
+        assert_eq!(result["symbols"][0]["kind"], "function");
+
+        let project = tool.execute(json!({})).await.unwrap();
+        assert_eq!(project["languages"][0]["language"], "python");
+    }
+
+    #[tokio::test]
+    async fn test_scopes_files_to_member_crates() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        let write = |path: &str, content: &str| {
+            let path = root.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        };
+        write("Cargo.toml", "[workspace]\nmembers = [\"crates/*\"]\n");
+        write("crates/store/Cargo.toml", "[package]\nname = \"store\"\n");
+        write("crates/server/Cargo.toml", "[package]\nname = \"server\"\n[dependencies]\nstore = { path = \"../store\" }\n");
+
+        let tool = WorkspacePackages::new(root);
+        let result = tool
+            .execute(json!({ "paths": ["crates/store/src/db.rs", "notes.txt"] }))
+            .await
+            .unwrap();
+
+        assert_eq!(result["multi_package"], true);
+        assert_eq!(result["packages"].as_array().unwrap().len(), 2);
+        assert_eq!(result["scopes"][0]["package"], "store");
+        assert_eq!(result["scopes"][0]["affected_packages"], json!(["store", "server"]));
+        assert_eq!(result["scopes"][0]["test_command"], json!(["cargo", "test", "-p", "store"]));
+        assert!(result["scopes"][1]["package"].is_null());
+    }
+}
@@ -0,0 +1,225 @@
+//! Human approval of dangerous tool calls
+//!
+//! Tools flag calls a person should see first through
+//! [`Tool::approval_needed`]: shell commands and code snippets, deletes,
+//! moves and overwrites, writes to databases, pull requests. A
+//! [`ToolExecutor`](crate::ToolExecutor) given an [`Approver`] (runs whose
+//! preset has the `dangerous_tools` gate) holds each flagged call until the
+//! approver answers. The request carries the full parameters and, where the
+//! tool can tell, a diff of what the call would change
+//! ([`Tool::approval_preview`]). A denied call fails with
+//! [`ToolError::PolicyDenied`] under the [`APPROVAL_RULE`] rule.
+//!
+//! "Always allow for this session" answers cover later calls with the same
+//! [`ApprovalNeed::scope`], e.g. every `git push` or every `delete_file`.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+use zed42_core::CancellationToken;
+
+use crate::{Tool, ToolError};
+
+/// Policy rule named by denials
+pub const APPROVAL_RULE: &str = "approval";
+
+/// Longest preview sent with a request, in bytes
+pub const PREVIEW_LIMIT: usize = 20_000;
+
+/// Why a call needs approval and what a session-wide answer covers
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApprovalNeed {
+    /// Shown to the user, e.g. "Runs `git push origin main`"
+    pub reason: String,
+    /// Calls with the same tool and scope share "always allow" answers
+    pub scope: String,
+}
+
+impl ApprovalNeed {
+    pub fn new(reason: impl Into<String>, scope: impl Into<String>) -> Self {
+        Self { reason: reason.into(), scope: scope.into() }
+    }
+}
+
+/// A tool call waiting for a human
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApprovalRequest {
+    pub id: String,
+    pub tool: String,
+    pub params: Value,
+    pub reason: String,
+    pub scope: String,
+    /// What the call would change, usually a unified diff
+    #[serde(default)]
+    pub preview: Option<String>,
+    pub requested_at: DateTime<Utc>,
+}
+
+impl ApprovalRequest {
+    pub fn new(tool: impl Into<String>, params: Value, need: ApprovalNeed, preview: Option<String>) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            tool: tool.into(),
+            params,
+            reason: need.reason,
+            scope: need.scope,
+            preview: preview.map(truncate_preview),
+            requested_at: Utc::now(),
+        }
+    }
+}
+
+/// A human's answer to an [`ApprovalRequest`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalDecision {
+    Allow,
+    /// Allow this call and later ones with the same tool and scope
+    AllowForSession,
+    Deny,
+}
+
+impl ApprovalDecision {
+    pub fn allows(self) -> bool {
+        !matches!(self, ApprovalDecision::Deny)
+    }
+}
+
+/// Answers approval requests, typically by asking a person
+#[async_trait]
+pub trait Approver: Send + Sync {
+    /// Wait for a decision on `request`
+    async fn decide(&self, request: ApprovalRequest) -> ApprovalDecision;
+}
+
+/// Ask `approver` about the call if `tool` flags it
+///
+/// Returns once the call may run. Fails with [`ToolError::PolicyDenied`]
+/// when it is denied and [`ToolError::Cancelled`] when `cancel` fires
+/// while waiting.
+pub async fn request_approval(
+    tool: &dyn Tool,
+    params: &Value,
+    approver: &dyn Approver,
+    cancel: &CancellationToken,
+) -> Result<(), ToolError> {
+    let Some(need) = tool.approval_needed(params) else {
+        return Ok(());
+    };
+    let preview = tool.approval_preview(params).await;
+    let request = ApprovalRequest::new(tool.name(), params.clone(), need, preview);
+    let reason = request.reason.clone();
+
+    let decision = tokio::select! {
+        biased;
+        _ = cancel.cancelled() => return Err(ToolError::Cancelled),
+        decision = approver.decide(request) => decision,
+    };
+    if decision.allows() {
+        return Ok(());
+    }
+    tracing::info!(tool = tool.name(), %reason, "Tool call denied by the user");
+    Err(ToolError::PolicyDenied {
+        rule: APPROVAL_RULE.to_string(),
+        reason: format!("The user denied this call ({}). Don't retry it; try another approach or ask", reason),
+    })
+}
+
+/// Unified diff turning `old` into `new` for `path`
+pub fn diff_preview(path: &str, old: &[u8], new: &[u8]) -> Option<String> {
+    let path = Path::new(path);
+    let mut patch = git2::Patch::from_buffers(old, Some(path), new, Some(path), None).ok()?;
+    let buf = patch.to_buf().ok()?;
+    Some(String::from_utf8_lossy(&buf).into_owned())
+}
+
+/// Cut `text` to [`PREVIEW_LIMIT`] bytes at a line boundary
+pub fn truncate_preview(text: String) -> String {
+    if text.len() <= PREVIEW_LIMIT {
+        return text;
+    }
+    let mut end = PREVIEW_LIMIT;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    let end = text[..end].rfind('\n').map_or(end, |i| i + 1);
+    let omitted = text[end..].lines().count();
+    format!("{}... {} more line(s)\n", &text[..end], omitted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_manipulation::DeleteFile;
+    use crate::shell::ExecuteCommand;
+    use crate::{ToolCall, ToolExecutor};
+    use serde_json::json;
+    use std::sync::{Arc, Mutex};
+
+    /// Answers every request with `decision`, remembering what it was asked
+    struct Scripted {
+        decision: ApprovalDecision,
+        asked: Mutex<Vec<ApprovalRequest>>,
+    }
+
+    #[async_trait]
+    impl Approver for Scripted {
+        async fn decide(&self, request: ApprovalRequest) -> ApprovalDecision {
+            self.asked.lock().unwrap().push(request);
+            self.decision
+        }
+    }
+
+    fn executor(root: &Path, decision: ApprovalDecision) -> (ToolExecutor, Arc<Scripted>) {
+        let approver = Arc::new(Scripted { decision, asked: Mutex::new(Vec::new()) });
+        let mut executor = ToolExecutor::new().with_approver(approver.clone());
+        executor.register(Arc::new(DeleteFile::new(root)));
+        executor.register(Arc::new(ExecuteCommand::new(root)));
+        (executor, approver)
+    }
+
+    #[tokio::test]
+    async fn test_denied_delete_keeps_file_and_shows_diff() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "keep me\n").unwrap();
+        let (executor, approver) = executor(dir.path(), ApprovalDecision::Deny);
+
+        let result = executor.execute(ToolCall::new("delete_file", json!({ "path": "notes.txt" }))).await;
+        assert!(matches!(result, Err(ToolError::PolicyDenied { ref rule, .. }) if rule == APPROVAL_RULE));
+        assert!(dir.path().join("notes.txt").exists());
+
+        let asked = approver.asked.lock().unwrap();
+        assert_eq!(asked[0].tool, "delete_file");
+        assert_eq!(asked[0].params["path"], "notes.txt");
+        assert!(asked[0].preview.as_deref().unwrap().contains("-keep me"));
+    }
+
+    #[tokio::test]
+    async fn test_allowed_call_runs_and_scope_names_subcommand() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("old.txt"), "x").unwrap();
+        let (executor, approver) = executor(dir.path(), ApprovalDecision::AllowForSession);
+
+        let result = executor.execute(ToolCall::new("delete_file", json!({ "path": "old.txt" }))).await.unwrap();
+        assert_eq!(result["success"], true);
+        assert!(!dir.path().join("old.txt").exists());
+
+        let need = ExecuteCommand::new(dir.path())
+            .approval_needed(&json!({ "command": "git", "args": ["push", "origin", "main"] }))
+            .unwrap();
+        assert_eq!(need.scope, "git push");
+        assert_eq!(need.reason, "Runs `git push origin main`");
+        assert_eq!(approver.asked.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_truncate_preview_keeps_whole_lines() {
+        let text = "line\n".repeat(PREVIEW_LIMIT);
+        let cut = truncate_preview(text);
+        assert!(cut.len() <= PREVIEW_LIMIT + 40);
+        assert!(cut.ends_with("more line(s)\n"));
+        assert!(cut.lines().rev().nth(1).is_some_and(|line| line == "line"));
+    }
+}
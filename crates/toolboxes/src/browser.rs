@@ -0,0 +1,436 @@
+//! Browser automation for UI verification
+//!
+//! Drives a headless browser through a W3C WebDriver server (chromedriver,
+//! geckodriver) so a TestEngineer can load a locally served frontend, take
+//! screenshots and check what the page shows. One [`Browser`] session is
+//! shared by the tools and started on first use.
+//!
+//! Navigation is held to a [`HostAllowList`]: requested URLs are checked
+//! before loading, and the page's URL after loading and before every
+//! screenshot or query, so redirects and scripted navigation can't leave
+//! it. Screenshots go to the blackboard [`ArtifactStore`] and only their
+//! id is returned.
+//!
+//! Built with the `browser` feature.
+
+use anyhow::{bail, Context};
+use async_trait::async_trait;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use zed42_blackboard::ArtifactStore;
+use crate::http::HostAllowList;
+use crate::{Tool, ToolError, ToolResult};
+
+/// Key WebDriver uses for element references
+const ELEMENT_KEY: &str = "element-6066-11e4-a52e-4f735466cecb";
+
+/// Default limit for page loads and queries
+const BROWSER_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Texts returned per query
+const MAX_ELEMENTS: usize = 50;
+
+/// Where the WebDriver server runs and which browser it starts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrowserConfig {
+    /// WebDriver server URL
+    #[serde(default = "default_webdriver_url")]
+    pub webdriver_url: String,
+    /// `chrome` or `firefox`
+    #[serde(default = "default_browser_name")]
+    pub browser_name: String,
+    #[serde(default = "default_window")]
+    pub window: (u32, u32),
+}
+
+fn default_webdriver_url() -> String {
+    "http://localhost:9515".to_string()
+}
+
+fn default_browser_name() -> String {
+    "chrome".to_string()
+}
+
+fn default_window() -> (u32, u32) {
+    (1280, 800)
+}
+
+impl Default for BrowserConfig {
+    fn default() -> Self {
+        Self {
+            webdriver_url: default_webdriver_url(),
+            browser_name: default_browser_name(),
+            window: default_window(),
+        }
+    }
+}
+
+impl BrowserConfig {
+    pub fn with_webdriver_url(mut self, webdriver_url: impl Into<String>) -> Self {
+        self.webdriver_url = webdriver_url.into();
+        self
+    }
+
+    /// New-session capabilities for a headless browser
+    fn capabilities(&self) -> Value {
+        let (width, height) = self.window;
+        let window = format!("--window-size={},{}", width, height);
+        let options = match self.browser_name.as_str() {
+            "firefox" => json!({ "moz:firefoxOptions": { "args": ["-headless", format!("-width={}", width), format!("-height={}", height)] } }),
+            _ => json!({ "goog:chromeOptions": { "args": ["--headless=new", "--disable-gpu", "--no-sandbox", window] } }),
+        };
+        let mut always = json!({ "browserName": self.browser_name });
+        if let (Some(always), Some(options)) = (always.as_object_mut(), options.as_object()) {
+            always.extend(options.clone());
+        }
+        json!({ "capabilities": { "alwaysMatch": always } })
+    }
+}
+
+/// A WebDriver session shared by the browser tools
+pub struct Browser {
+    config: BrowserConfig,
+    allow_list: HostAllowList,
+    artifacts: ArtifactStore,
+    client: reqwest::Client,
+    session: Mutex<Option<String>>,
+}
+
+impl Browser {
+    pub fn new(config: BrowserConfig, allow_list: HostAllowList, artifacts: ArtifactStore) -> Self {
+        Self {
+            config,
+            allow_list,
+            artifacts,
+            client: reqwest::Client::new(),
+            session: Mutex::new(None),
+        }
+    }
+
+    /// Load `url`, which must be on the allow-list, and return where the page ended up
+    pub async fn navigate(&self, url: &str) -> Result<String, ToolError> {
+        let target = reqwest::Url::parse(url)
+            .map_err(|e| ToolError::invalid_params(format!("Invalid URL '{}': {}", url, e)))?;
+        if !self.allow_list.allows_url(&target) {
+            return Err(navigation_denied(target.as_str()));
+        }
+        self.command(reqwest::Method::POST, "url", json!({ "url": target.as_str() })).await?;
+        self.ensure_allowed().await
+    }
+
+    /// Screenshot of the current page, stored as an artifact
+    pub async fn screenshot(&self) -> Result<(String, String), ToolError> {
+        let url = self.ensure_allowed().await?;
+        let encoded = self.command(reqwest::Method::GET, "screenshot", Value::Null).await?;
+        let png = base64::engine::general_purpose::STANDARD
+            .decode(encoded.as_str().unwrap_or_default())
+            .map_err(|e| ToolError::External(format!("WebDriver returned an invalid screenshot: {}", e)))?;
+        let artifact_id = self.artifacts.put(&png)?;
+        Ok((artifact_id, url))
+    }
+
+    /// Visible text of the elements matching `selector`
+    pub async fn texts(&self, selector: &str) -> Result<Vec<String>, ToolError> {
+        self.ensure_allowed().await?;
+        let found = self
+            .command(reqwest::Method::POST, "elements", json!({ "using": "css selector", "value": selector }))
+            .await?;
+        let mut texts = Vec::new();
+        for id in element_ids(&found).into_iter().take(MAX_ELEMENTS) {
+            let text = self.command(reqwest::Method::GET, &format!("element/{}/text", id), Value::Null).await?;
+            texts.push(text.as_str().unwrap_or_default().to_string());
+        }
+        Ok(texts)
+    }
+
+    /// Page title
+    pub async fn title(&self) -> Result<String, ToolError> {
+        let title = self.command(reqwest::Method::GET, "title", Value::Null).await?;
+        Ok(title.as_str().unwrap_or_default().to_string())
+    }
+
+    /// End the browser session, if one was started
+    pub async fn close(&self) -> anyhow::Result<()> {
+        if let Some(id) = self.session.lock().await.take() {
+            let url = format!("{}/session/{}", self.base_url(), id);
+            self.client.delete(&url).send().await.context("Failed to close browser session")?;
+        }
+        Ok(())
+    }
+
+    /// Current URL, leaving the page (for `about:blank`) if it is off the allow-list
+    async fn ensure_allowed(&self) -> Result<String, ToolError> {
+        let current = self.command(reqwest::Method::GET, "url", Value::Null).await?;
+        let current = current.as_str().unwrap_or_default().to_string();
+        let allowed = reqwest::Url::parse(&current).is_ok_and(|url| self.allow_list.allows_url(&url));
+        if !allowed {
+            let _ = self.command(reqwest::Method::POST, "url", json!({ "url": "about:blank" })).await;
+            return Err(navigation_denied(&current));
+        }
+        Ok(current)
+    }
+
+    /// Run a command in the session, starting the session if needed
+    async fn command(&self, method: reqwest::Method, path: &str, body: Value) -> Result<Value, ToolError> {
+        let session = self.session_id().await.map_err(|e| ToolError::Environment(format!("{:#}", e)))?;
+        let url = format!("{}/session/{}/{}", self.base_url(), session, path);
+        let mut request = self.client.request(method, &url);
+        if !body.is_null() {
+            request = request.json(&body);
+        }
+        webdriver_value(request.send().await)
+            .await
+            .map_err(|e| ToolError::External(format!("{:#}", e)))
+    }
+
+    async fn session_id(&self) -> anyhow::Result<String> {
+        let mut session = self.session.lock().await;
+        if let Some(ref id) = *session {
+            return Ok(id.clone());
+        }
+        let url = format!("{}/session", self.base_url());
+        let created = webdriver_value(self.client.post(&url).json(&self.config.capabilities()).send().await)
+            .await
+            .with_context(|| format!("Failed to start a browser through {}", self.config.webdriver_url))?;
+        let id = created["sessionId"]
+            .as_str()
+            .context("WebDriver returned no session id")?
+            .to_string();
+        tracing::info!(session = %id, browser = %self.config.browser_name, "Started browser session");
+        *session = Some(id.clone());
+        Ok(id)
+    }
+
+    fn base_url(&self) -> &str {
+        self.config.webdriver_url.trim_end_matches('/')
+    }
+}
+
+/// The `value` of a WebDriver response, or its error
+async fn webdriver_value(response: reqwest::Result<reqwest::Response>) -> anyhow::Result<Value> {
+    let response = response.context("WebDriver server unreachable")?;
+    let status = response.status();
+    let body: Value = response.json().await.context("WebDriver returned invalid JSON")?;
+    let value = body.get("value").cloned().unwrap_or(Value::Null);
+    if !status.is_success() {
+        let error = value["error"].as_str().unwrap_or("unknown error");
+        let message = value["message"].as_str().unwrap_or_default();
+        bail!("WebDriver {} ({}): {}", error, status, message);
+    }
+    Ok(value)
+}
+
+/// Element references in a find-elements result
+fn element_ids(found: &Value) -> Vec<String> {
+    found
+        .as_array()
+        .map(|elements| {
+            elements
+                .iter()
+                .filter_map(|element| element[ELEMENT_KEY].as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn navigation_denied(url: &str) -> ToolError {
+    ToolError::PolicyDenied {
+        rule: "browser_allow_list".to_string(),
+        reason: format!("{} is not an allowed page", url),
+    }
+}
+
+/// BrowserNavigate tool - loads a page in the headless browser
+pub struct BrowserNavigate {
+    browser: Arc<Browser>,
+}
+
+impl BrowserNavigate {
+    pub fn new(browser: Arc<Browser>) -> Self {
+        Self { browser }
+    }
+}
+
+#[async_trait]
+impl Tool for BrowserNavigate {
+    fn name(&self) -> &str {
+        "browser_navigate"
+    }
+
+    fn description(&self) -> &str {
+        "Load a page of a locally served frontend in a headless browser"
+    }
+
+    fn default_timeout(&self) -> Duration {
+        BROWSER_TIMEOUT
+    }
+
+    fn parameter_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "url": { "type": "string", "description": "Page to load, e.g. 'http://localhost:3000/login'" }
+            },
+            "required": ["url"]
+        })
+    }
+
+    async fn execute(&self, params: Value) -> ToolResult {
+        let url = params["url"]
+            .as_str()
+            .ok_or_else(|| ToolError::invalid_params("missing field `url`"))?;
+        let current = self.browser.navigate(url).await?;
+        Ok(json!({
+            "url": current,
+            "title": self.browser.title().await?
+        }))
+    }
+}
+
+/// BrowserScreenshot tool - captures the current page as a PNG artifact
+pub struct BrowserScreenshot {
+    browser: Arc<Browser>,
+}
+
+impl BrowserScreenshot {
+    pub fn new(browser: Arc<Browser>) -> Self {
+        Self { browser }
+    }
+}
+
+#[async_trait]
+impl Tool for BrowserScreenshot {
+    fn name(&self) -> &str {
+        "browser_screenshot"
+    }
+
+    fn description(&self) -> &str {
+        "Take a screenshot of the current page; returns the artifact id of the PNG"
+    }
+
+    fn default_timeout(&self) -> Duration {
+        BROWSER_TIMEOUT
+    }
+
+    fn parameter_schema(&self) -> Value {
+        json!({ "type": "object", "properties": {} })
+    }
+
+    async fn execute(&self, _params: Value) -> ToolResult {
+        let (artifact_id, url) = self.browser.screenshot().await?;
+        Ok(json!({
+            "artifact_id": artifact_id,
+            "url": url,
+            "content_type": "image/png"
+        }))
+    }
+}
+
+/// Parameters for BrowserAssertDom tool
+#[derive(Debug, Deserialize)]
+pub struct BrowserAssertDomParams {
+    /// CSS selector
+    pub selector: String,
+    /// Some matching element's text must contain this
+    #[serde(default)]
+    pub contains: Option<String>,
+    /// Exactly this many elements must match
+    #[serde(default)]
+    pub count: Option<usize>,
+}
+
+/// BrowserAssertDom tool - checks the elements matching a CSS selector
+pub struct BrowserAssertDom {
+    browser: Arc<Browser>,
+}
+
+impl BrowserAssertDom {
+    pub fn new(browser: Arc<Browser>) -> Self {
+        Self { browser }
+    }
+}
+
+#[async_trait]
+impl Tool for BrowserAssertDom {
+    fn name(&self) -> &str {
+        "browser_assert_dom"
+    }
+
+    fn description(&self) -> &str {
+        "Find elements on the current page by CSS selector and check their count or text"
+    }
+
+    fn default_timeout(&self) -> Duration {
+        BROWSER_TIMEOUT
+    }
+
+    fn parameter_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "selector": { "type": "string", "description": "CSS selector, e.g. 'h1' or '#error'" },
+                "contains": { "type": "string", "description": "Text some matching element must contain (optional)" },
+                "count": { "type": "integer", "description": "Exact number of matching elements (optional)" }
+            },
+            "required": ["selector"]
+        })
+    }
+
+    async fn execute(&self, params: Value) -> ToolResult {
+        let params: BrowserAssertDomParams = serde_json::from_value(params)
+            .map_err(ToolError::invalid_params)?;
+        let texts = self.browser.texts(&params.selector).await?;
+
+        let mut failures = Vec::new();
+        if let Some(expected) = params.count {
+            if texts.len() != expected {
+                failures.push(format!("expected {} elements, found {}", expected, texts.len()));
+            }
+        }
+        if let Some(ref needle) = params.contains {
+            if !texts.iter().any(|text| text.contains(needle.as_str())) {
+                failures.push(format!("no element contains '{}'", needle));
+            }
+        }
+
+        Ok(json!({
+            "selector": params.selector,
+            "passed": failures.is_empty(),
+            "failures": failures,
+            "matched": texts.len(),
+            "texts": texts
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_capabilities_and_element_ids() {
+        let caps = BrowserConfig::default().capabilities();
+        assert_eq!(caps["capabilities"]["alwaysMatch"]["browserName"], "chrome");
+        assert_eq!(caps["capabilities"]["alwaysMatch"]["goog:chromeOptions"]["args"][0], "--headless=new");
+
+        let found = json!([{ ELEMENT_KEY: "a" }, { "other": "x" }, { ELEMENT_KEY: "b" }]);
+        assert_eq!(element_ids(&found), vec!["a", "b"]);
+    }
+
+    #[tokio::test]
+    async fn test_navigation_outside_allow_list_is_denied_before_loading() {
+        let temp = tempdir().unwrap();
+        // Nothing listens here; a denied URL must never reach the driver
+        let config = BrowserConfig::default().with_webdriver_url("http://127.0.0.1:9");
+        let browser = Arc::new(Browser::new(config, HostAllowList::local(), ArtifactStore::new(temp.path()).unwrap()));
+        let tool = BrowserNavigate::new(browser);
+
+        let err = tool.execute(json!({ "url": "https://example.com/" })).await.unwrap_err();
+        assert!(matches!(err, ToolError::PolicyDenied { ref rule, .. } if rule == "browser_allow_list"));
+    }
+}
@@ -6,7 +6,7 @@ use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::sync::Arc;
-use crate::{Tool, ToolResult};
+use crate::{Tool, ToolError, ToolResult};
 use zed42_llm::{ConstrainedGen, LlmClient};
 use schemars::JsonSchema;
 
@@ -77,7 +77,7 @@ impl Tool for GenerateFunction {
 
     async fn execute(&self, params: Value) -> ToolResult {
         let params: GenerateFunctionParams = serde_json::from_value(params)
-            .map_err(|e| anyhow::anyhow!("Invalid parameters: {}", e))?;
+            .map_err(ToolError::invalid_params)?;
 
         let language = params.language.unwrap_or_else(|| "rust".to_string());
         
@@ -93,7 +93,7 @@ impl Tool for GenerateFunction {
             .prompt(prompt)
             .generate()
             .await
-            .map_err(|e| anyhow::anyhow!("Generation failed: {}", e))?;
+            .map_err(|e| ToolError::External(format!("Generation failed: {}", e)))?;
 
         Ok(serde_json::to_value(result)?)
     }
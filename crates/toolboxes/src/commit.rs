@@ -0,0 +1,514 @@
+//! Commit message and changelog generation
+//!
+//! Every agent commit goes through the same pipeline: the task's diff is
+//! summarized into a conventional-commit message (`type(scope): subject`),
+//! the message is validated against the project's [`CommitFormat`], a
+//! changelog entry is added under `## [Unreleased]`, and the change is
+//! committed. The format can be set in `.zed42/commit.toml`:
+//!
+//! ```toml
+//! types = ["feat", "fix", "refactor", "docs", "test", "chore"]
+//! max_subject_len = 72
+//! require_scope = false
+//! changelog = "CHANGELOG.md"
+//! changelog_types = ["feat", "fix"]
+//! ```
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use zed42_llm::{ConstrainedGen, LlmClient};
+use crate::{Tool, ToolError, ToolResult};
+
+/// Location of the commit format relative to the project root
+pub const COMMIT_FORMAT_PATH: &str = ".zed42/commit.toml";
+
+/// Diff characters sent to the model; larger diffs are cut
+const MAX_DIFF_CHARS: usize = 12_000;
+
+const COMMIT_SYSTEM_PROMPT: &str = "You write conventional commit messages. Summarize what the diff \
+changes and why, in the imperative mood (\"add\", not \"added\"). The subject is one short line \
+without a trailing period; put details in the body.";
+
+/// Rules commit messages are checked against
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CommitFormat {
+    /// Allowed conventional-commit types
+    pub types: Vec<String>,
+    /// Longest allowed header (`type(scope): subject`)
+    pub max_subject_len: usize,
+    pub require_scope: bool,
+    /// Changelog file relative to the project root; `None` disables changelog entries
+    pub changelog: Option<String>,
+    /// Types that get a changelog entry
+    pub changelog_types: Vec<String>,
+}
+
+impl Default for CommitFormat {
+    fn default() -> Self {
+        let list = |items: &[&str]| items.iter().map(|s| s.to_string()).collect();
+        Self {
+            types: list(&["feat", "fix", "perf", "refactor", "docs", "test", "build", "ci", "chore", "style"]),
+            max_subject_len: 72,
+            require_scope: false,
+            changelog: Some("CHANGELOG.md".to_string()),
+            changelog_types: list(&["feat", "fix", "perf", "refactor"]),
+        }
+    }
+}
+
+impl CommitFormat {
+    pub fn from_toml_str(content: &str) -> Result<Self> {
+        toml::from_str(content).map_err(|e| anyhow::anyhow!("Invalid commit format: {}", e))
+    }
+
+    /// Format from `.zed42/commit.toml` under `project_root`, or the default
+    pub fn load(project_root: &Path) -> Result<Self> {
+        let path = project_root.join(COMMIT_FORMAT_PATH);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::from_toml_str(&content)
+    }
+}
+
+/// A conventional commit message
+#[derive(Debug, Clone, PartialEq, JsonSchema, Serialize, Deserialize)]
+pub struct ConventionalCommit {
+    /// Commit type, e.g. "feat" or "fix"
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// Area of the codebase, e.g. a crate or module name
+    #[serde(default)]
+    pub scope: Option<String>,
+    /// Imperative one-line summary without a trailing period
+    pub subject: String,
+    /// Optional longer explanation
+    #[serde(default)]
+    pub body: Option<String>,
+    /// The change breaks existing users
+    #[serde(default)]
+    pub breaking: bool,
+}
+
+impl ConventionalCommit {
+    /// `type(scope)!: subject`
+    pub fn header(&self) -> String {
+        let scope = self.scope.as_deref().filter(|s| !s.is_empty()).map(|s| format!("({})", s)).unwrap_or_default();
+        let bang = if self.breaking { "!" } else { "" };
+        format!("{}{}{}: {}", self.kind, scope, bang, self.subject)
+    }
+
+    /// Full message: header, blank line, body
+    pub fn message(&self) -> String {
+        match self.body.as_deref().map(str::trim).filter(|b| !b.is_empty()) {
+            Some(body) => format!("{}\n\n{}\n", self.header(), body),
+            None => format!("{}\n", self.header()),
+        }
+    }
+
+    /// Parse a message written by hand
+    pub fn parse(message: &str) -> Result<Self> {
+        let mut parts = message.trim().splitn(2, '\n');
+        let header = parts.next().unwrap_or_default().trim();
+        let body = parts.next().map(str::trim).filter(|b| !b.is_empty()).map(String::from);
+
+        let (prefix, subject) = header
+            .split_once(": ")
+            .with_context(|| format!("'{}' is not a conventional commit header", header))?;
+        let (prefix, breaking) = match prefix.strip_suffix('!') {
+            Some(prefix) => (prefix, true),
+            None => (prefix, false),
+        };
+        let (kind, scope) = match prefix.split_once('(') {
+            Some((kind, scope)) => (
+                kind,
+                Some(scope.strip_suffix(')').with_context(|| format!("Unclosed scope in '{}'", header))?.to_string()),
+            ),
+            None => (prefix, None),
+        };
+        Ok(Self {
+            kind: kind.to_string(),
+            scope,
+            subject: subject.trim().to_string(),
+            body,
+            breaking,
+        })
+    }
+
+    /// Every way the message breaks `format`; empty when it is valid
+    pub fn validate(&self, format: &CommitFormat) -> Vec<String> {
+        let mut problems = Vec::new();
+        if !format.types.iter().any(|t| *t == self.kind) {
+            problems.push(format!("Type '{}' is not one of: {}", self.kind, format.types.join(", ")));
+        }
+        if format.require_scope && self.scope.as_deref().map_or(true, str::is_empty) {
+            problems.push("A scope is required".to_string());
+        }
+        let subject = self.subject.trim();
+        if subject.is_empty() {
+            problems.push("Subject is empty".to_string());
+        }
+        if subject.ends_with('.') {
+            problems.push("Subject ends with a period".to_string());
+        }
+        if subject.chars().next().is_some_and(char::is_uppercase) {
+            problems.push("Subject starts with an uppercase letter".to_string());
+        }
+        if subject.contains('\n') {
+            problems.push("Subject spans several lines".to_string());
+        }
+        let header_len = self.header().chars().count();
+        if header_len > format.max_subject_len {
+            problems.push(format!("Header is {} characters, limit is {}", header_len, format.max_subject_len));
+        }
+        problems
+    }
+}
+
+/// Summarizes diffs into conventional commits
+pub struct CommitMessageGenerator<'a> {
+    client: &'a dyn LlmClient,
+    format: &'a CommitFormat,
+}
+
+impl<'a> CommitMessageGenerator<'a> {
+    pub fn new(client: &'a dyn LlmClient, format: &'a CommitFormat) -> Self {
+        Self { client, format }
+    }
+
+    /// Message for `diff` made for `task`; invalid messages are sent back once with the problems
+    pub async fn generate(&self, task: &str, diff: &str) -> Result<ConventionalCommit> {
+        let diff = truncate(diff, MAX_DIFF_CHARS);
+        let mut prompt = format!(
+            "Task: {}\n\nAllowed types: {}\nHeader limit: {} characters{}\n\nDiff:\n```diff\n{}\n```",
+            task,
+            self.format.types.join(", "),
+            self.format.max_subject_len,
+            if self.format.require_scope { "\nA scope is required." } else { "" },
+            diff
+        );
+
+        let mut problems = Vec::new();
+        for _ in 0..2 {
+            let commit: ConventionalCommit = ConstrainedGen::new(self.client)
+                .system(COMMIT_SYSTEM_PROMPT)
+                .prompt(prompt.clone())
+                .max_retries(1)
+                .generate()
+                .await?;
+            problems = commit.validate(self.format);
+            if problems.is_empty() {
+                return Ok(commit);
+            }
+            prompt.push_str(&format!(
+                "\n\nYour previous message `{}` was rejected:\n- {}",
+                commit.header(),
+                problems.join("\n- ")
+            ));
+        }
+        bail!("Generated commit message is invalid: {}", problems.join("; "))
+    }
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((idx, _)) => format!("{}\n... (diff truncated)", &text[..idx]),
+        None => text.to_string(),
+    }
+}
+
+/// Keep a Changelog section for a commit type
+fn changelog_section(kind: &str) -> &'static str {
+    match kind {
+        "feat" => "Added",
+        "fix" => "Fixed",
+        _ => "Changed",
+    }
+}
+
+/// Add `commit` under `## [Unreleased]` in `changelog`, creating sections as needed
+///
+/// Returns the updated text, or `None` when the commit's type gets no entry.
+pub fn changelog_entry(changelog: &str, commit: &ConventionalCommit, format: &CommitFormat) -> Option<String> {
+    if !format.changelog_types.iter().any(|t| *t == commit.kind) {
+        return None;
+    }
+    let scope = commit.scope.as_deref().map(|s| format!("**{}**: ", s)).unwrap_or_default();
+    let breaking = if commit.breaking { "**BREAKING** " } else { "" };
+    let entry = format!("- {}{}{}", breaking, scope, commit.subject.trim());
+    let section = format!("### {}", changelog_section(&commit.kind));
+
+    let mut lines: Vec<String> = if changelog.trim().is_empty() {
+        vec!["# Changelog".to_string(), String::new()]
+    } else {
+        changelog.lines().map(String::from).collect()
+    };
+
+    let unreleased = match lines.iter().position(|l| l.trim().eq_ignore_ascii_case("## [unreleased]")) {
+        Some(idx) => idx,
+        None => {
+            // Before the first release heading, or at the end
+            let mut idx = lines.iter().position(|l| l.starts_with("## ")).unwrap_or(lines.len());
+            if idx > 0 && !lines[idx - 1].trim().is_empty() {
+                lines.insert(idx, String::new());
+                idx += 1;
+            }
+            lines.splice(idx..idx, ["## [Unreleased]".to_string(), String::new()]);
+            idx
+        }
+    };
+    let end = lines[unreleased + 1..]
+        .iter()
+        .position(|l| l.starts_with("## "))
+        .map_or(lines.len(), |i| unreleased + 1 + i);
+
+    match lines[unreleased + 1..end].iter().position(|l| l.trim() == section) {
+        // Newest entries first
+        Some(i) => lines.insert(unreleased + 1 + i + 1, entry),
+        None => {
+            // After the last non-blank line of the Unreleased block
+            let at = (unreleased + 1..end)
+                .rev()
+                .find(|&i| !lines[i].trim().is_empty())
+                .map_or(unreleased + 1, |i| i + 1);
+            let block = [String::new(), section, entry];
+            let after = at + block.len();
+            lines.splice(at..at, block);
+            if lines.get(after).is_some_and(|l| !l.trim().is_empty()) {
+                lines.insert(after, String::new());
+            }
+        }
+    }
+
+    let mut text = lines.join("\n").trim_end().to_string();
+    text.push('\n');
+    Some(text)
+}
+
+/// Unstaged and staged changes against HEAD, as a unified diff
+pub fn working_diff(repo: &git2::Repository) -> Result<String> {
+    let head = repo.head().ok().and_then(|h| h.peel_to_tree().ok());
+    let mut options = git2::DiffOptions::new();
+    options.include_untracked(true).recurse_untracked_dirs(true).show_untracked_content(true);
+    let diff = repo.diff_tree_to_workdir_with_index(head.as_ref(), Some(&mut options))?;
+    let mut text = String::new();
+    diff.print(git2::DiffFormat::Patch, |_, _, line| {
+        if matches!(line.origin(), '+' | '-' | ' ') {
+            text.push(line.origin());
+        }
+        text.push_str(&String::from_utf8_lossy(line.content()));
+        true
+    })?;
+    Ok(text)
+}
+
+/// Stage everything and commit with `message`, returning the commit id
+pub fn commit_all(repo: &git2::Repository, message: &str) -> Result<String> {
+    let mut index = repo.index()?;
+    index.add_all(["*"], git2::IndexAddOption::DEFAULT, None)?;
+    index.update_all(["*"], None)?;
+    index.write()?;
+    let tree = repo.find_tree(index.write_tree()?)?;
+    let signature = repo
+        .signature()
+        .or_else(|_| git2::Signature::now("ZED42 Agent", "agent@zed42.local"))?;
+    let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+    let parents: Vec<&git2::Commit> = parent.iter().collect();
+    let oid = repo.commit(Some("HEAD"), &signature, &signature, message, &tree, &parents)?;
+    Ok(oid.to_string())
+}
+
+/// Parameters for GitCommit tool
+#[derive(Debug, Deserialize)]
+pub struct GitCommitParams {
+    /// Task the change was made for; guides the generated message
+    pub task: String,
+    /// Message to use instead of generating one (still validated)
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+/// GitCommit tool - commits the working tree with a generated, validated message
+///
+/// Also records a changelog entry for user-facing types, so every agent
+/// commit is described the same way.
+pub struct GitCommit {
+    repo_root: PathBuf,
+    llm_client: Arc<dyn LlmClient>,
+}
+
+impl GitCommit {
+    pub fn new(repo_root: impl Into<PathBuf>, llm_client: Arc<dyn LlmClient>) -> Self {
+        Self {
+            repo_root: repo_root.into(),
+            llm_client,
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for GitCommit {
+    fn name(&self) -> &str {
+        "git_commit"
+    }
+
+    fn description(&self) -> &str {
+        "Commit all changes with a conventional-commit message summarizing the diff, and add a changelog entry"
+    }
+
+    fn parameter_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "task": {
+                    "type": "string",
+                    "description": "Task the change was made for"
+                },
+                "message": {
+                    "type": "string",
+                    "description": "Conventional commit message to use instead of a generated one"
+                }
+            },
+            "required": ["task"]
+        })
+    }
+
+    async fn execute(&self, params: Value) -> ToolResult {
+        let params: GitCommitParams = serde_json::from_value(params)
+            .map_err(ToolError::invalid_params)?;
+
+        let format = CommitFormat::load(&self.repo_root)?;
+        let diff = {
+            let repo = git2::Repository::open(&self.repo_root).context("Not a git repository")?;
+            working_diff(&repo)?
+        };
+        if diff.trim().is_empty() {
+            return Ok(json!({ "success": false, "reason": "Nothing to commit" }));
+        }
+
+        let commit = match params.message {
+            Some(message) => {
+                let commit = ConventionalCommit::parse(&message).map_err(ToolError::invalid_params)?;
+                let problems = commit.validate(&format);
+                if !problems.is_empty() {
+                    return Ok(json!({ "success": false, "problems": problems }));
+                }
+                commit
+            }
+            None => {
+                CommitMessageGenerator::new(self.llm_client.as_ref(), &format)
+                    .generate(&params.task, &diff)
+                    .await
+                    .map_err(|e| ToolError::External(format!("{:#}", e)))?
+            }
+        };
+
+        let mut changelog_updated = false;
+        if let Some(ref file) = format.changelog {
+            let path = self.repo_root.join(file);
+            let current = std::fs::read_to_string(&path).unwrap_or_default();
+            if let Some(updated) = changelog_entry(&current, &commit, &format) {
+                std::fs::write(&path, updated).with_context(|| format!("Failed to write {}", path.display()))?;
+                changelog_updated = true;
+            }
+        }
+
+        let repo = git2::Repository::open(&self.repo_root).context("Not a git repository")?;
+        let id = commit_all(&repo, &commit.message())?;
+        tracing::info!(commit = %id, header = %commit.header(), "Committed agent changes");
+        Ok(json!({
+            "success": true,
+            "commit": id,
+            "message": commit.message(),
+            "changelog_updated": changelog_updated
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zed42_llm::MockLlmClient;
+
+    #[test]
+    fn test_parse_and_validate() {
+        let commit = ConventionalCommit::parse("feat(parser)!: support nested tables\n\nDetails here.").unwrap();
+        assert_eq!(commit.kind, "feat");
+        assert_eq!(commit.scope.as_deref(), Some("parser"));
+        assert!(commit.breaking);
+        assert_eq!(commit.message(), "feat(parser)!: support nested tables\n\nDetails here.\n");
+        assert!(commit.validate(&CommitFormat::default()).is_empty());
+
+        let bad = ConventionalCommit::parse("update: Fixed the thing.").unwrap();
+        let problems = bad.validate(&CommitFormat { require_scope: true, ..Default::default() });
+        assert_eq!(problems.len(), 4);
+        assert!(ConventionalCommit::parse("no colon here").is_err());
+    }
+
+    #[test]
+    fn test_changelog_entries() {
+        let format = CommitFormat::default();
+        let feat = ConventionalCommit::parse("feat(api): add pagination").unwrap();
+        let fix = ConventionalCommit::parse("fix: handle empty input").unwrap();
+
+        let text = changelog_entry("", &feat, &format).unwrap();
+        assert_eq!(text, "# Changelog\n\n## [Unreleased]\n\n### Added\n- **api**: add pagination\n");
+
+        let existing = "# Changelog\n\n## [1.0.0]\n\n### Added\n- first release\n";
+        let text = changelog_entry(existing, &fix, &format).unwrap();
+        assert_eq!(
+            text,
+            "# Changelog\n\n## [Unreleased]\n\n### Fixed\n- handle empty input\n\n## [1.0.0]\n\n### Added\n- first release\n"
+        );
+        let text = changelog_entry(&text, &feat, &format).unwrap();
+        assert!(text.starts_with("# Changelog\n\n## [Unreleased]\n\n### Fixed\n- handle empty input\n\n### Added\n- **api**: add pagination\n\n## [1.0.0]"));
+
+        let chore = ConventionalCommit::parse("chore: bump deps").unwrap();
+        assert!(changelog_entry(&text, &chore, &format).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_generator_retries_invalid_messages() {
+        let client = MockLlmClient::with_responses(vec![
+            r#"{"type": "feature", "subject": "Added caching."}"#.to_string(),
+            r#"{"type": "feat", "scope": "cache", "subject": "add response caching"}"#.to_string(),
+        ]);
+        let format = CommitFormat::default();
+        let commit = CommitMessageGenerator::new(&client, &format)
+            .generate("Cache responses", "+fn cache() {}")
+            .await
+            .unwrap();
+        assert_eq!(commit.header(), "feat(cache): add response caching");
+    }
+
+    #[tokio::test]
+    async fn test_git_commit_tool() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = git2::Repository::init(dir.path()).unwrap();
+        std::fs::write(dir.path().join("lib.rs"), "fn a() {}\n").unwrap();
+        commit_all(&repo, "chore: initial commit\n").unwrap();
+        std::fs::write(dir.path().join("lib.rs"), "fn a() {}\nfn b() {}\n").unwrap();
+
+        let client = Arc::new(MockLlmClient::new(
+            r#"{"type": "feat", "subject": "add b", "body": "Adds the b helper."}"#.to_string(),
+        ));
+        let tool = GitCommit::new(dir.path(), client);
+        let result = tool.execute(json!({ "task": "Add helper b" })).await.unwrap();
+
+        assert_eq!(result["success"], true);
+        assert_eq!(result["changelog_updated"], true);
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.message().unwrap(), "feat: add b\n\nAdds the b helper.\n");
+        assert!(head.tree().unwrap().get_name("CHANGELOG.md").is_some());
+
+        let nothing = tool.execute(json!({ "task": "Add helper b" })).await.unwrap();
+        assert_eq!(nothing["success"], false);
+    }
+}
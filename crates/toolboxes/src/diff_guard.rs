@@ -0,0 +1,173 @@
+//! Workspace diff guard
+//!
+//! A human may edit a file while an agent is working on it. Without a check
+//! the agent's next write silently overwrites that edit. The guard remembers
+//! the content hash of every file an agent read for a task (its *base*) and
+//! compares it with the file on disk before the agent writes. When they
+//! differ, the write is held and reported as `approval_required`; it goes
+//! through once the agent rebases (reads the file again and writes its change
+//! on top) or a human approves the overwrite.
+
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use zed42_core::types::TaskId;
+
+/// Result of checking a pending write against the recorded base
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WriteCheck {
+    /// The file is as the agent read it (or was just rebased / approved)
+    Clean,
+    /// The agent never read the file for this task, e.g. a new file
+    Untracked,
+    /// Someone changed the file since the agent read it; `current` is
+    /// `None` when the file was deleted
+    Diverged { base: String, current: Option<String> },
+}
+
+/// Base hashes per task, shared by the file tools of every agent
+#[derive(Debug, Clone, Default)]
+pub struct WorkspaceDiffGuard {
+    bases: Arc<Mutex<HashMap<TaskId, HashMap<PathBuf, String>>>>,
+}
+
+impl WorkspaceDiffGuard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remember `content` as what `task_id` saw at `path`
+    pub fn record_read(&self, task_id: &str, path: &Path, content: &[u8]) -> Result<()> {
+        let hash = content_hash(content)?;
+        self.set_base(task_id, path, hash);
+        Ok(())
+    }
+
+    /// The agent wrote `content`; its own write is the new base
+    pub fn record_write(&self, task_id: &str, path: &Path, content: &[u8]) -> Result<()> {
+        self.record_read(task_id, path, content)
+    }
+
+    /// Compare the file at `path` with what `task_id` last saw
+    pub fn check_write(&self, task_id: &str, path: &Path) -> Result<WriteCheck> {
+        let Some(base) = self.base(task_id, path) else {
+            return Ok(WriteCheck::Untracked);
+        };
+        let current = disk_hash(path)?;
+        if current.as_deref() == Some(base.as_str()) {
+            Ok(WriteCheck::Clean)
+        } else {
+            Ok(WriteCheck::Diverged { base, current })
+        }
+    }
+
+    /// A human confirmed the overwrite: accept the file on disk as the base
+    pub fn approve_overwrite(&self, task_id: &str, path: &Path) -> Result<()> {
+        match disk_hash(path)? {
+            Some(hash) => self.set_base(task_id, path, hash),
+            None => self.forget(task_id, path),
+        }
+        Ok(())
+    }
+
+    /// Drop every base recorded for a finished task
+    pub fn release(&self, task_id: &str) {
+        self.lock().remove(task_id);
+    }
+
+    /// Files `task_id` has read or written
+    pub fn tracked(&self, task_id: &str) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = self
+            .lock()
+            .get(task_id)
+            .map(|bases| bases.keys().cloned().collect())
+            .unwrap_or_default();
+        paths.sort();
+        paths
+    }
+
+    fn base(&self, task_id: &str, path: &Path) -> Option<String> {
+        self.lock().get(task_id).and_then(|bases| bases.get(path).cloned())
+    }
+
+    fn set_base(&self, task_id: &str, path: &Path, hash: String) {
+        self.lock()
+            .entry(task_id.to_string())
+            .or_default()
+            .insert(path.to_path_buf(), hash);
+    }
+
+    fn forget(&self, task_id: &str, path: &Path) {
+        if let Some(bases) = self.lock().get_mut(task_id) {
+            bases.remove(path);
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<TaskId, HashMap<PathBuf, String>>> {
+        // A panic while holding the lock can't leave the map half-updated
+        self.bases.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Git blob id of `content`, so hashes line up with `git hash-object`
+pub fn content_hash(content: &[u8]) -> Result<String> {
+    let oid = git2::Oid::hash_object(git2::ObjectType::Blob, content).context("Failed to hash file content")?;
+    Ok(oid.to_string())
+}
+
+fn disk_hash(path: &Path) -> Result<Option<String>> {
+    match std::fs::read(path) {
+        Ok(content) => content_hash(&content).map(Some),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_manipulation::{ReadFile, WriteFile};
+    use crate::Tool;
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_user_edit_holds_agent_write_until_rebase_or_approval() {
+        let temp = tempdir().unwrap();
+        let file = temp.path().join("lib.rs");
+        std::fs::write(&file, "fn a() {}").unwrap();
+
+        let guard = WorkspaceDiffGuard::new();
+        let read = ReadFile::new(temp.path()).with_diff_guard(guard.clone(), "t1");
+        let write = WriteFile::new(temp.path()).with_diff_guard(guard.clone(), "t1");
+
+        read.execute(json!({ "path": "lib.rs" })).await.unwrap();
+        std::fs::write(&file, "fn a() {} // human").unwrap();
+
+        let held = write.execute(json!({ "path": "lib.rs", "content": "fn b() {}" })).await.unwrap();
+        assert_eq!(held["approval_required"], true);
+        assert_eq!(held["rule"], "diff_guard");
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "fn a() {} // human");
+
+        // Rebase: reading again makes the human edit the base
+        read.execute(json!({ "path": "lib.rs" })).await.unwrap();
+        let written = write.execute(json!({ "path": "lib.rs", "content": "fn a() {} // human\nfn b() {}" })).await.unwrap();
+        assert_eq!(written["success"], true);
+        // The agent's own write is the new base
+        let again = write.execute(json!({ "path": "lib.rs", "content": "fn b() {}" })).await.unwrap();
+        assert_eq!(again["success"], true);
+
+        // A human approval clears a new edit
+        std::fs::write(&file, "fn c() {}").unwrap();
+        let canonical = crate::file_manipulation::PathSanitizer::new(temp.path()).sanitize("lib.rs").unwrap();
+        assert!(matches!(guard.check_write("t1", &canonical).unwrap(), WriteCheck::Diverged { .. }));
+        guard.approve_overwrite("t1", &canonical).unwrap();
+        let approved = write.execute(json!({ "path": "lib.rs", "content": "fn d() {}" })).await.unwrap();
+        assert_eq!(approved["success"], true);
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "fn d() {}");
+
+        guard.release("t1");
+        assert!(guard.tracked("t1").is_empty());
+    }
+}
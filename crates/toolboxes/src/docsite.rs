@@ -0,0 +1,547 @@
+//! Documentation site generation from the knowledge graph
+//!
+//! Renders an mdBook (or Docusaurus) tree: one API page per module node with
+//! the types and functions it contains, an architecture page with a Mermaid
+//! diagram of module dependencies, and one ADR per decision node. A manifest
+//! next to the pages records a fingerprint per module, so a refresh only
+//! re-renders modules whose graph nodes changed. ADRs are written once and
+//! never rewritten.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use zed42_memory::knowledge_graph::{EdgeType, KnowledgeGraphMemory, KnowledgeNode, NodeType};
+
+/// Manifest file, relative to the docs directory
+const MANIFEST_FILE: &str = ".docs-manifest.json";
+
+/// Site generator the pages are laid out for
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DocsLayout {
+    /// `docs/book.toml` with pages under `docs/src/` and a `SUMMARY.md`
+    #[default]
+    MdBook,
+    /// Pages with front matter under `website/docs/`; sidebars are autogenerated
+    Docusaurus,
+}
+
+impl DocsLayout {
+    /// Site root relative to the project root
+    pub fn root(&self) -> &'static str {
+        match self {
+            DocsLayout::MdBook => "docs",
+            DocsLayout::Docusaurus => "website/docs",
+        }
+    }
+
+    /// Page directory relative to the project root
+    pub fn pages(&self) -> &'static str {
+        match self {
+            DocsLayout::MdBook => "docs/src",
+            DocsLayout::Docusaurus => "website/docs",
+        }
+    }
+
+    fn page(&self, title: &str, body: &str) -> String {
+        match self {
+            DocsLayout::MdBook => format!("# {}\n\n{}", title, body),
+            DocsLayout::Docusaurus => format!("---\ntitle: \"{}\"\n---\n\n{}", title.replace('"', "'"), body),
+        }
+    }
+}
+
+/// A definition listed on a module page
+#[derive(Debug, Clone)]
+pub struct DocItem {
+    pub name: String,
+    pub node_type: String,
+    pub content: String,
+}
+
+/// A module and everything the graph says about it
+#[derive(Debug, Clone)]
+pub struct DocModule {
+    pub id: String,
+    pub name: String,
+    pub content: String,
+    pub items: Vec<DocItem>,
+    /// Names of modules this one depends on
+    pub depends_on: Vec<String>,
+    /// Changes whenever the module or one of its items changes
+    pub fingerprint: String,
+}
+
+/// An architectural decision rendered as an ADR
+#[derive(Debug, Clone)]
+pub struct DocDecision {
+    pub id: String,
+    pub title: String,
+    pub content: String,
+    pub created_at: i64,
+    /// Decision that replaced this one
+    pub superseded_by: Option<String>,
+}
+
+/// What the docs are rendered from
+#[derive(Debug, Clone, Default)]
+pub struct DocsModel {
+    pub modules: Vec<DocModule>,
+    pub decisions: Vec<DocDecision>,
+}
+
+impl DocsModel {
+    /// Modules with their contained nodes, module dependencies and decisions
+    pub async fn load(graph: &KnowledgeGraphMemory) -> Result<Self> {
+        let modules = graph.get_nodes_by_type(NodeType::Module).await?;
+        let module_names: HashMap<&str, &str> = modules.iter().map(|m| (m.id.as_str(), m.name.as_str())).collect();
+
+        let mut contained: HashMap<String, Vec<String>> = HashMap::new();
+        for edge in graph.get_edges_by_type(EdgeType::Contains).await? {
+            contained.entry(edge.from_id).or_default().push(edge.to_id);
+        }
+        let mut depends: HashMap<String, BTreeSet<String>> = HashMap::new();
+        for edge in graph.get_edges_by_type(EdgeType::DependsOn).await? {
+            if let Some(name) = module_names.get(edge.to_id.as_str()) {
+                depends.entry(edge.from_id).or_default().insert(name.to_string());
+            }
+        }
+
+        let mut model = DocsModel::default();
+        for module in &modules {
+            let mut items = Vec::new();
+            let mut latest = module.updated_at;
+            for id in contained.get(&module.id).into_iter().flatten() {
+                let Some(node) = graph.get_node(id).await? else {
+                    continue;
+                };
+                latest = latest.max(node.updated_at);
+                if matches!(node.node_type.as_str(), "function" | "type") {
+                    items.push(DocItem {
+                        name: node.name,
+                        node_type: node.node_type,
+                        content: node.content,
+                    });
+                }
+            }
+            items.sort_by(|a, b| (&a.node_type, &a.name).cmp(&(&b.node_type, &b.name)));
+            let depends_on: Vec<String> = depends.remove(&module.id).unwrap_or_default().into_iter().collect();
+            model.modules.push(DocModule {
+                fingerprint: format!("{}:{}:{}", latest, items.len(), depends_on.len()),
+                id: module.id.clone(),
+                name: module.name.clone(),
+                content: module.content.clone(),
+                items,
+                depends_on,
+            });
+        }
+        model.modules.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let superseded: HashMap<String, String> = graph
+            .get_edges_by_type(EdgeType::Supersedes)
+            .await?
+            .into_iter()
+            .map(|edge| (edge.to_id, edge.from_id))
+            .collect();
+        let mut decisions: Vec<KnowledgeNode> = graph.get_nodes_by_type(NodeType::Decision).await?;
+        decisions.sort_by(|a, b| (a.created_at, &a.id).cmp(&(b.created_at, &b.id)));
+        model.decisions = decisions
+            .into_iter()
+            .map(|node| DocDecision {
+                superseded_by: superseded.get(&node.id).cloned(),
+                id: node.id,
+                title: node.name,
+                content: node.content,
+                created_at: node.created_at,
+            })
+            .collect();
+        Ok(model)
+    }
+}
+
+/// Fingerprints and ADR files from the last generation
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DocsManifest {
+    /// Module id to fingerprint
+    pub modules: BTreeMap<String, String>,
+    /// Decision id to ADR file name
+    pub adrs: BTreeMap<String, String>,
+}
+
+/// A page to write, relative to the project root
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocsPage {
+    pub path: PathBuf,
+    pub content: String,
+}
+
+/// Pages a refresh would write
+#[derive(Debug, Clone, Default)]
+pub struct DocsUpdate {
+    pub pages: Vec<DocsPage>,
+    /// Modules skipped because their nodes did not change
+    pub unchanged: Vec<String>,
+    pub manifest: DocsManifest,
+}
+
+/// Renders the docs site for one project
+pub struct DocsGenerator {
+    project_root: PathBuf,
+    layout: DocsLayout,
+}
+
+impl DocsGenerator {
+    pub fn new(project_root: impl Into<PathBuf>, layout: DocsLayout) -> Self {
+        Self {
+            project_root: project_root.into(),
+            layout,
+        }
+    }
+
+    fn manifest_path(&self) -> PathBuf {
+        Path::new(self.layout.root()).join(MANIFEST_FILE)
+    }
+
+    /// Manifest of the last generation; empty before the first
+    pub fn load_manifest(&self) -> Result<DocsManifest> {
+        let path = self.project_root.join(self.manifest_path());
+        if !path.exists() {
+            return Ok(DocsManifest::default());
+        }
+        let content = std::fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&content).with_context(|| format!("Invalid docs manifest {}", path.display()))
+    }
+
+    /// Pages that changed since `previous`, plus the index pages and manifest
+    pub fn plan(&self, model: &DocsModel, previous: &DocsManifest) -> Result<DocsUpdate> {
+        let pages_dir = Path::new(self.layout.pages());
+        let mut update = DocsUpdate::default();
+        let mut structure_changed = false;
+
+        for module in &model.modules {
+            update.manifest.modules.insert(module.id.clone(), module.fingerprint.clone());
+            if previous.modules.get(&module.id) == Some(&module.fingerprint) {
+                update.unchanged.push(module.name.clone());
+                continue;
+            }
+            structure_changed = true;
+            update.pages.push(DocsPage {
+                path: pages_dir.join("api").join(format!("{}.md", slug(&module.name))),
+                content: self.layout.page(&module.name, &module_body(module)),
+            });
+        }
+        structure_changed |= previous.modules.keys().any(|id| !update.manifest.modules.contains_key(id));
+
+        for (number, decision) in model.decisions.iter().enumerate() {
+            if let Some(file) = previous.adrs.get(&decision.id) {
+                update.manifest.adrs.insert(decision.id.clone(), file.clone());
+                continue;
+            }
+            structure_changed = true;
+            let file = format!("{:04}-{}.md", number + 1, slug(&decision.title));
+            update.pages.push(DocsPage {
+                path: pages_dir.join("adr").join(&file),
+                content: self.layout.page(
+                    &format!("ADR {:04}: {}", number + 1, decision.title),
+                    &adr_body(decision, model),
+                ),
+            });
+            update.manifest.adrs.insert(decision.id.clone(), file);
+        }
+
+        if structure_changed || previous == &DocsManifest::default() {
+            update.pages.push(DocsPage {
+                path: pages_dir.join("architecture.md"),
+                content: self.layout.page("Architecture", &architecture_body(model)),
+            });
+            update.pages.push(DocsPage {
+                path: pages_dir.join("introduction.md"),
+                content: self.layout.page("Introduction", "Generated from the project knowledge graph.\n"),
+            });
+            match self.layout {
+                DocsLayout::MdBook => {
+                    update.pages.push(DocsPage {
+                        path: pages_dir.join("SUMMARY.md"),
+                        content: summary(model, &update.manifest),
+                    });
+                    if !self.project_root.join("docs/book.toml").exists() {
+                        update.pages.push(DocsPage {
+                            path: PathBuf::from("docs/book.toml"),
+                            content: "[book]\ntitle = \"Documentation\"\nsrc = \"src\"\n\n[output.html]\n".to_string(),
+                        });
+                    }
+                }
+                DocsLayout::Docusaurus => {
+                    for (dir, label) in [("api", "API"), ("adr", "Decisions")] {
+                        update.pages.push(DocsPage {
+                            path: pages_dir.join(dir).join("_category_.json"),
+                            content: format!("{{\n  \"label\": \"{}\"\n}}\n", label),
+                        });
+                    }
+                }
+            }
+        }
+
+        update.pages.push(DocsPage {
+            path: self.manifest_path(),
+            content: serde_json::to_string_pretty(&update.manifest)? + "\n",
+        });
+        Ok(update)
+    }
+
+    /// Load the graph, plan against the stored manifest and write the changed pages
+    pub async fn refresh(&self, graph: &KnowledgeGraphMemory) -> Result<DocsUpdate> {
+        let model = DocsModel::load(graph).await?;
+        let update = self.plan(&model, &self.load_manifest()?)?;
+        self.write(&update)?;
+        Ok(update)
+    }
+
+    /// Write every page of `update` under the project root
+    pub fn write(&self, update: &DocsUpdate) -> Result<()> {
+        for page in &update.pages {
+            let path = self.project_root.join(&page.path);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent).with_context(|| format!("Failed to create {}", parent.display()))?;
+            }
+            std::fs::write(&path, &page.content).with_context(|| format!("Failed to write {}", path.display()))?;
+        }
+        tracing::info!(
+            pages = update.pages.len(),
+            unchanged = update.unchanged.len(),
+            "Refreshed documentation site"
+        );
+        Ok(())
+    }
+}
+
+/// Lowercase file name part: letters and digits, everything else a dash
+fn slug(text: &str) -> String {
+    let mut slug = String::new();
+    for c in text.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_matches('-').to_string()
+}
+
+fn module_body(module: &DocModule) -> String {
+    let mut md = String::new();
+    if !module.content.trim().is_empty() {
+        let _ = writeln!(md, "{}\n", module.content.trim());
+    }
+    if !module.depends_on.is_empty() {
+        let _ = writeln!(md, "Depends on: {}\n", module.depends_on.join(", "));
+    }
+    for (node_type, heading) in [("type", "Types"), ("function", "Functions")] {
+        let items: Vec<&DocItem> = module.items.iter().filter(|i| i.node_type == node_type).collect();
+        if items.is_empty() {
+            continue;
+        }
+        let _ = writeln!(md, "## {}\n", heading);
+        for item in items {
+            let _ = writeln!(md, "### `{}`\n", item.name);
+            if !item.content.trim().is_empty() {
+                let _ = writeln!(md, "{}\n", item.content.trim());
+            }
+        }
+    }
+    if md.is_empty() {
+        md.push_str("Nothing documented yet.\n");
+    }
+    md
+}
+
+fn architecture_body(model: &DocsModel) -> String {
+    let mut md = String::from("Module dependencies, from the knowledge graph.\n\n```mermaid\ngraph TD\n");
+    for module in &model.modules {
+        let _ = writeln!(md, "    {}[\"{}\"]", slug(&module.name).replace('-', "_"), module.name);
+    }
+    for module in &model.modules {
+        for dependency in &module.depends_on {
+            let _ = writeln!(
+                md,
+                "    {} --> {}",
+                slug(&module.name).replace('-', "_"),
+                slug(dependency).replace('-', "_")
+            );
+        }
+    }
+    md.push_str("```\n");
+    md
+}
+
+fn adr_body(decision: &DocDecision, model: &DocsModel) -> String {
+    let status = match decision.superseded_by.as_ref().and_then(|id| model.decisions.iter().find(|d| &d.id == id)) {
+        Some(newer) => format!("Superseded by \"{}\"", newer.title),
+        None => "Accepted".to_string(),
+    };
+    let date = chrono::DateTime::from_timestamp(decision.created_at, 0)
+        .map(|d| d.format("%Y-%m-%d").to_string())
+        .unwrap_or_default();
+    format!("- Status: {}\n- Date: {}\n\n## Decision\n\n{}\n", status, date, decision.content.trim())
+}
+
+fn summary(model: &DocsModel, manifest: &DocsManifest) -> String {
+    let mut md = String::from("# Summary\n\n[Introduction](introduction.md)\n\n- [Architecture](architecture.md)\n");
+    if !model.modules.is_empty() {
+        md.push_str("- [API]()\n");
+        for module in &model.modules {
+            let _ = writeln!(md, "    - [{}](api/{}.md)", module.name, slug(&module.name));
+        }
+    }
+    if !model.decisions.is_empty() {
+        md.push_str("- [Decisions]()\n");
+        for decision in &model.decisions {
+            if let Some(file) = manifest.adrs.get(&decision.id) {
+                let _ = writeln!(md, "    - [{}](adr/{})", decision.title, file);
+            }
+        }
+    }
+    md
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn module(name: &str, fingerprint: &str) -> DocModule {
+        DocModule {
+            id: format!("module:{}", name),
+            name: name.to_string(),
+            content: String::new(),
+            items: vec![DocItem {
+                name: "parse".to_string(),
+                node_type: "function".to_string(),
+                content: "Parses input.".to_string(),
+            }],
+            depends_on: Vec::new(),
+            fingerprint: fingerprint.to_string(),
+        }
+    }
+
+    fn paths(update: &DocsUpdate) -> Vec<String> {
+        update.pages.iter().map(|p| p.path.display().to_string()).collect()
+    }
+
+    #[test]
+    fn test_incremental_mdbook_refresh() {
+        let dir = tempfile::tempdir().unwrap();
+        let generator = DocsGenerator::new(dir.path(), DocsLayout::MdBook);
+        let mut core = module("core", "1:1:0");
+        core.depends_on.push("util".to_string());
+        let mut model = DocsModel {
+            modules: vec![core, module("util", "1:1:0")],
+            decisions: vec![DocDecision {
+                id: "d1".to_string(),
+                title: "Use SurrealDB".to_string(),
+                content: "Embedded graph storage.".to_string(),
+                created_at: 0,
+                superseded_by: None,
+            }],
+        };
+
+        let first = generator.plan(&model, &DocsManifest::default()).unwrap();
+        assert_eq!(
+            paths(&first),
+            vec![
+                "docs/src/api/core.md",
+                "docs/src/api/util.md",
+                "docs/src/adr/0001-use-surrealdb.md",
+                "docs/src/architecture.md",
+                "docs/src/introduction.md",
+                "docs/src/SUMMARY.md",
+                "docs/book.toml",
+                "docs/.docs-manifest.json",
+            ]
+        );
+        let architecture = &first.pages[3].content;
+        assert!(architecture.contains("core --> util"));
+        assert!(first.pages[0].content.contains("### `parse`\n\nParses input."));
+        generator.write(&first).unwrap();
+
+        // Only the changed module is re-rendered; ADRs are kept
+        model.modules[1].fingerprint = "2:1:0".to_string();
+        let second = generator.plan(&model, &generator.load_manifest().unwrap()).unwrap();
+        assert_eq!(second.unchanged, vec!["core"]);
+        assert!(paths(&second).contains(&"docs/src/api/util.md".to_string()));
+        assert!(!paths(&second).iter().any(|p| p.contains("core.md") || p.contains("adr/")));
+        assert!(!paths(&second).contains(&"docs/book.toml".to_string()));
+
+        generator.write(&second).unwrap();
+        let third = generator.plan(&model, &generator.load_manifest().unwrap()).unwrap();
+        assert_eq!(paths(&third), vec!["docs/.docs-manifest.json"]);
+    }
+
+    #[test]
+    fn test_docusaurus_front_matter() {
+        let generator = DocsGenerator::new("/tmp/project", DocsLayout::Docusaurus);
+        let model = DocsModel {
+            modules: vec![module("net/http", "1:1:0")],
+            decisions: Vec::new(),
+        };
+        let update = generator.plan(&model, &DocsManifest::default()).unwrap();
+        assert_eq!(update.pages[0].path, PathBuf::from("website/docs/api/net-http.md"));
+        assert!(update.pages[0].content.starts_with("---\ntitle: \"net/http\"\n---\n"));
+        assert!(paths(&update).contains(&"website/docs/adr/_category_.json".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_model_from_graph() {
+        use zed42_memory::knowledge_graph::KnowledgeEdge;
+
+        let dir = tempfile::tempdir().unwrap();
+        let graph = KnowledgeGraphMemory::new(dir.path(), "docs", None).await.unwrap();
+        for (id, node_type, updated_at) in [
+            ("module:api", "module", 1),
+            ("module:db", "module", 1),
+            ("fn:serve", "function", 7),
+            ("dec:1", "decision", 1),
+            ("dec:2", "decision", 2),
+        ] {
+            graph
+                .insert_node(KnowledgeNode {
+                    id: id.to_string(),
+                    node_type: node_type.to_string(),
+                    name: id.to_string(),
+                    content: String::new(),
+                    embedding: None,
+                    metadata: "{}".to_string(),
+                    created_at: updated_at,
+                    updated_at,
+                })
+                .await
+                .unwrap();
+        }
+        for (edge_type, from, to) in [
+            ("contains", "module:api", "fn:serve"),
+            ("depends_on", "module:api", "module:db"),
+            ("supersedes", "dec:2", "dec:1"),
+        ] {
+            graph
+                .insert_edge(KnowledgeEdge {
+                    id: format!("{}-{}", from, to),
+                    edge_type: edge_type.to_string(),
+                    from_id: from.to_string(),
+                    to_id: to.to_string(),
+                    metadata: None,
+                    created_at: 0,
+                })
+                .await
+                .unwrap();
+        }
+
+        let model = DocsModel::load(&graph).await.unwrap();
+        let api = &model.modules[0];
+        assert_eq!(api.name, "module:api");
+        assert_eq!(api.items.len(), 1);
+        assert_eq!(api.depends_on, vec!["module:db"]);
+        assert_eq!(api.fingerprint, "7:1:1");
+        assert_eq!(model.decisions[0].superseded_by.as_deref(), Some("dec:2"));
+    }
+}
@@ -0,0 +1,165 @@
+//! Structured tool failures
+//!
+//! Agents react differently to a bad call (fix the parameters), a broken
+//! environment (retry or escalate) and a policy denial (don't retry, ask).
+//! [`ToolError`] carries that category and whether a retry can help, and
+//! [`ToolError::to_json`] gives the model the same shape for every tool.
+
+use serde_json::{json, Value};
+use std::time::Duration;
+
+/// Why a tool call failed
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum ToolError {
+    /// The parameters were malformed or name something the tool can't handle
+    #[error("Invalid parameters: {0}")]
+    InvalidParams(String),
+    /// A file, directory or record the call refers to does not exist
+    #[error("Not found: {0}")]
+    NotFound(String),
+    /// The sandbox, a guardrail or a policy refused the call
+    #[error("Denied by policy rule '{rule}': {reason}")]
+    PolicyDenied { rule: String, reason: String },
+    /// The local environment failed (IO error, disk full, missing binary)
+    #[error("{0}")]
+    Environment(String),
+    /// A remote service (forge, model provider) failed
+    #[error("External service error: {0}")]
+    External(String),
+    #[error("Timed out after {}s", .0.as_secs_f64())]
+    TimedOut(Duration),
+    #[error("Cancelled")]
+    Cancelled,
+    /// A bug in the tool itself
+    #[error("Internal tool error: {0}")]
+    Internal(String),
+}
+
+impl ToolError {
+    pub fn invalid_params(e: impl std::fmt::Display) -> Self {
+        Self::InvalidParams(e.to_string())
+    }
+
+    /// Denial by the path sandbox
+    pub fn sandbox(reason: impl std::fmt::Display) -> Self {
+        Self::PolicyDenied { rule: "sandbox".to_string(), reason: reason.to_string() }
+    }
+
+    /// Classify an IO error, prefixed with what the tool was doing
+    pub fn io(context: impl std::fmt::Display, e: std::io::Error) -> Self {
+        let message = format!("{}: {}", context, e);
+        match e.kind() {
+            std::io::ErrorKind::NotFound => Self::NotFound(message),
+            _ => Self::Environment(message),
+        }
+    }
+
+    /// Stable category name for the model
+    pub fn category(&self) -> &'static str {
+        match self {
+            Self::InvalidParams(_) => "invalid_params",
+            Self::NotFound(_) => "not_found",
+            Self::PolicyDenied { .. } => "policy_denied",
+            Self::Environment(_) => "environment",
+            Self::External(_) => "external",
+            Self::TimedOut(_) => "timeout",
+            Self::Cancelled => "cancelled",
+            Self::Internal(_) => "internal",
+        }
+    }
+
+    /// Whether repeating the same call could succeed
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Self::Environment(_) | Self::External(_) | Self::TimedOut(_))
+    }
+
+    /// Failure result handed to the model in place of the tool output
+    pub fn to_json(&self, tool: &str) -> Value {
+        let mut error = json!({
+            "category": self.category(),
+            "message": self.to_string(),
+            "retryable": self.is_retryable(),
+        });
+        match self {
+            Self::PolicyDenied { rule, .. } => error["rule"] = json!(rule),
+            Self::TimedOut(after) => error["timeout_secs"] = json!(after.as_secs_f64()),
+            _ => {}
+        }
+        json!({
+            "success": false,
+            "tool": tool,
+            "error": error,
+        })
+    }
+}
+
+impl From<anyhow::Error> for ToolError {
+    /// Helpers below the tool boundary still return anyhow errors; IO
+    /// failures keep their category, anything else counts as environmental.
+    fn from(e: anyhow::Error) -> Self {
+        let e = match e.downcast::<ToolError>() {
+            Ok(tool_error) => return tool_error,
+            Err(e) => e,
+        };
+        let message = format!("{:#}", e);
+        match e.chain().find_map(|cause| cause.downcast_ref::<std::io::Error>()) {
+            Some(io) if io.kind() == std::io::ErrorKind::NotFound => Self::NotFound(message),
+            _ => Self::Environment(message),
+        }
+    }
+}
+
+impl From<std::io::Error> for ToolError {
+    fn from(e: std::io::Error) -> Self {
+        Self::io("IO error", e)
+    }
+}
+
+impl From<tokio::task::JoinError> for ToolError {
+    fn from(e: tokio::task::JoinError) -> Self {
+        Self::Internal(format!("Tool task failed: {}", e))
+    }
+}
+
+impl From<serde_json::Error> for ToolError {
+    /// Parameter parsing maps to [`ToolError::InvalidParams`] explicitly;
+    /// anything else is the tool failing to build its own output.
+    fn from(e: serde_json::Error) -> Self {
+        Self::Internal(format!("Failed to serialize tool output: {}", e))
+    }
+}
+
+/// The output the model sees for a call: the tool's value, or its error as JSON
+pub fn tool_output(tool: &str, result: crate::ToolResult) -> Value {
+    result.unwrap_or_else(|e| e.to_json(tool))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_classification() {
+        let missing = std::fs::read("/definitely/not/here").context("Failed to read file");
+        let err = ToolError::from(missing.unwrap_err());
+        assert_eq!(err.category(), "not_found");
+        assert!(!err.is_retryable());
+
+        let denied = ToolError::sandbox("Path traversal (..) is not allowed");
+        let json = denied.to_json("read_file");
+        assert_eq!(json["success"], false);
+        assert_eq!(json["error"]["category"], "policy_denied");
+        assert_eq!(json["error"]["rule"], "sandbox");
+        assert_eq!(json["error"]["retryable"], false);
+
+        let timeout = ToolError::TimedOut(Duration::from_secs(2));
+        assert_eq!(timeout.to_json("run_tests")["error"]["timeout_secs"], 2.0);
+        assert!(timeout.is_retryable());
+
+        // Structured errors survive a trip through anyhow
+        let wrapped = anyhow::Error::new(ToolError::invalid_params("missing field `path`"));
+        assert_eq!(ToolError::from(wrapped), ToolError::invalid_params("missing field `path`"));
+        assert_eq!(ToolError::from(anyhow::anyhow!("disk full")).category(), "environment");
+    }
+}
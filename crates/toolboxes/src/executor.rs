@@ -0,0 +1,269 @@
+//! Tool dispatch for agents
+//!
+//! [`ToolExecutor`] holds the tools an agent may call, runs each call
+//! through [`run_tool`] (timeouts and cancellation), and runs batches of
+//! independent calls concurrently. A cycle that reads five files and lists a
+//! directory then waits for the slowest call instead of the sum of all six.
+//! With an [`OutputSummarizer`], oversized results are replaced by summaries
+//! before they reach the agent. With an [`Approver`], calls a tool flags as
+//! dangerous wait for a human first (see [`crate::approval`]).
+
+use crate::approval::{request_approval, Approver};
+use crate::summarize::{OutputSummarizer, ReadToolOutput};
+use crate::timeout::run_tool;
+use crate::{Tool, ToolError, ToolResult};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use zed42_core::types::TaskId;
+use zed42_core::{AgentId, CancellationToken, TraceKind, TraceRecord};
+
+/// Calls from one batch allowed to run at the same time
+pub const DEFAULT_MAX_CONCURRENCY: usize = 8;
+
+/// One requested tool call
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolCall {
+    pub tool: String,
+    #[serde(default)]
+    pub params: Value,
+}
+
+impl ToolCall {
+    pub fn new(tool: impl Into<String>, params: Value) -> Self {
+        Self { tool: tool.into(), params }
+    }
+
+    /// Trace record of this call and its result, for the task's transcript
+    pub fn trace(&self, task_id: impl Into<TaskId>, agent_id: AgentId, result: &ToolResult) -> TraceRecord {
+        let outcome = match result {
+            Ok(value) => value.to_string(),
+            Err(e) => format!("error: {}", e),
+        };
+        TraceRecord::new(
+            task_id,
+            agent_id,
+            TraceKind::ToolCall { tool: self.tool.clone(), ok: result.is_ok() },
+            format!("params: {}\nresult: {}", self.params, outcome),
+        )
+    }
+}
+
+/// Runs an agent's tools by name
+pub struct ToolExecutor {
+    tools: HashMap<String, Arc<dyn Tool>>,
+    max_concurrency: usize,
+    cancel: CancellationToken,
+    summarizer: Option<Arc<OutputSummarizer>>,
+    approver: Option<Arc<dyn Approver>>,
+}
+
+impl ToolExecutor {
+    pub fn new() -> Self {
+        Self {
+            tools: HashMap::new(),
+            max_concurrency: DEFAULT_MAX_CONCURRENCY,
+            cancel: CancellationToken::new(),
+            summarizer: None,
+            approver: None,
+        }
+    }
+
+    /// Cap on calls from one batch running at once (at least 1)
+    pub fn with_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = max_concurrency.max(1);
+        self
+    }
+
+    /// Cancel in-flight calls when `token` fires (e.g. agent shutdown)
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancel = token;
+        self
+    }
+
+    /// Summarize large outputs, and register `read_tool_output` for the details
+    pub fn with_summarizer(mut self, summarizer: OutputSummarizer) -> Self {
+        self.register(Arc::new(ReadToolOutput::new(summarizer.clone())));
+        self.summarizer = Some(Arc::new(summarizer));
+        self
+    }
+
+    /// Hold calls that need approval until `approver` answers
+    pub fn with_approver(mut self, approver: Arc<dyn Approver>) -> Self {
+        self.approver = Some(approver);
+        self
+    }
+
+    /// Make `tool` callable by its name, replacing any tool of that name
+    pub fn register(&mut self, tool: Arc<dyn Tool>) {
+        self.tools.insert(tool.name().to_string(), tool);
+    }
+
+    pub fn tool(&self, name: &str) -> Option<&Arc<dyn Tool>> {
+        self.tools.get(name)
+    }
+
+    /// Names of the registered tools, sorted
+    pub fn tool_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.tools.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Run one call
+    pub async fn execute(&self, call: ToolCall) -> ToolResult {
+        let tool = self.lookup(&call.tool)?;
+        if let Some(approver) = &self.approver {
+            request_approval(tool.as_ref(), &call.params, approver.as_ref(), &self.cancel).await?;
+        }
+        let result = run_tool(tool.as_ref(), call.params, &self.cancel).await;
+        summarize(self.summarizer.as_deref(), &call.tool, result)
+    }
+
+    /// Run independent calls concurrently, returning results in call order
+    ///
+    /// At most `max_concurrency` calls run at once. A failing call does not
+    /// stop the others; its error takes its slot in the result.
+    pub async fn execute_batch(&self, calls: Vec<ToolCall>) -> Vec<ToolResult> {
+        let mut results: Vec<Option<ToolResult>> = Vec::with_capacity(calls.len());
+        results.resize_with(calls.len(), || None);
+
+        let permits = Arc::new(Semaphore::new(self.max_concurrency));
+        let mut running = JoinSet::new();
+        for (index, call) in calls.into_iter().enumerate() {
+            let tool = match self.lookup(&call.tool) {
+                Ok(tool) => tool,
+                Err(e) => {
+                    results[index] = Some(Err(e));
+                    continue;
+                }
+            };
+            let permits = permits.clone();
+            let cancel = self.cancel.clone();
+            let summarizer = self.summarizer.clone();
+            let approver = self.approver.clone();
+            running.spawn(async move {
+                // Waiting for a human doesn't hold one of the batch's slots
+                if let Some(approver) = approver {
+                    if let Err(e) = request_approval(tool.as_ref(), &call.params, approver.as_ref(), &cancel).await {
+                        return (index, Err(e));
+                    }
+                }
+                let _permit = permits.acquire_owned().await.expect("batch semaphore is never closed");
+                let result = run_tool(tool.as_ref(), call.params, &cancel).await;
+                (index, summarize(summarizer.as_deref(), &call.tool, result))
+            });
+        }
+
+        while let Some(joined) = running.join_next().await {
+            match joined {
+                Ok((index, result)) => results[index] = Some(result),
+                Err(e) => tracing::error!("Tool call task failed: {}", e),
+            }
+        }
+
+        results
+            .into_iter()
+            .map(|result| result.unwrap_or_else(|| Err(ToolError::Internal("Tool call task failed".to_string()))))
+            .collect()
+    }
+
+    fn lookup(&self, name: &str) -> Result<Arc<dyn Tool>, ToolError> {
+        self.tools
+            .get(name)
+            .cloned()
+            .ok_or_else(|| ToolError::InvalidParams(format!("Unknown tool '{}'", name)))
+    }
+}
+
+/// Apply the summarizer to a successful result
+///
+/// `read_tool_output` pages are already bounded, and summarizing them would
+/// hide the details the agent asked for. A summarizer failure is logged and
+/// the full output returned.
+fn summarize(summarizer: Option<&OutputSummarizer>, tool: &str, result: ToolResult) -> ToolResult {
+    let Some(summarizer) = summarizer else {
+        return result;
+    };
+    if tool == "read_tool_output" {
+        return result;
+    }
+    let output = result?;
+    summarizer.summarize(tool, output.clone()).or_else(|e| {
+        tracing::warn!(tool, "Failed to summarize tool output: {:#}", e);
+        Ok(output)
+    })
+}
+
+impl Default for ToolExecutor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::{Duration, Instant};
+
+    /// Sleeps for `ms` and returns it, tracking how many calls overlap
+    #[derive(Default)]
+    struct Sleeper {
+        running: AtomicUsize,
+        peak: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Tool for Sleeper {
+        fn name(&self) -> &str {
+            "sleep"
+        }
+
+        fn description(&self) -> &str {
+            "Sleeps"
+        }
+
+        fn parameter_schema(&self) -> Value {
+            json!({ "type": "object" })
+        }
+
+        async fn execute(&self, params: Value) -> ToolResult {
+            let now = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(now, Ordering::SeqCst);
+            let ms = params["ms"].as_u64().unwrap_or(0);
+            tokio::time::sleep(Duration::from_millis(ms)).await;
+            self.running.fetch_sub(1, Ordering::SeqCst);
+            Ok(json!(ms))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batch_runs_concurrently_in_order() {
+        let sleeper = Arc::new(Sleeper::default());
+        let mut executor = ToolExecutor::new().with_max_concurrency(3);
+        executor.register(sleeper.clone());
+
+        let mut calls: Vec<ToolCall> = [150, 50, 100, 10, 120, 30]
+            .iter()
+            .map(|ms| ToolCall::new("sleep", json!({ "ms": ms })))
+            .collect();
+        calls.insert(2, ToolCall::new("missing", json!({})));
+
+        let started = Instant::now();
+        let results = executor.execute_batch(calls).await;
+
+        // Sequential would take 460ms
+        assert!(started.elapsed() < Duration::from_millis(400));
+        assert_eq!(sleeper.peak.load(Ordering::SeqCst), 3);
+        assert_eq!(results.len(), 7);
+        assert!(matches!(results[2], Err(ToolError::InvalidParams(_))));
+        let values: Vec<u64> = results.iter().filter_map(|r| r.as_ref().ok()).map(|v| v.as_u64().unwrap()).collect();
+        assert_eq!(values, vec![150, 50, 100, 10, 120, 30]);
+    }
+}
@@ -1 +1,168 @@
 //! External integration tools
+//!
+//! The forge toolbox opens pull requests on the project's code host. Only
+//! the GitHub REST API is spoken today (GitHub Enterprise works through
+//! `api_url`); the token is read from an environment variable so it never
+//! lands in config files.
+
+use anyhow::{bail, Context};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use crate::{ApprovalNeed, Tool, ToolError, ToolResult};
+
+/// Where and how pull requests are opened
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForgeConfig {
+    /// API base URL
+    #[serde(default = "default_api_url")]
+    pub api_url: String,
+    /// `owner/name` of the repository
+    pub repository: String,
+    /// Environment variable holding the access token
+    #[serde(default = "default_token_env")]
+    pub token_env: String,
+    /// Branch pull requests target unless one is given
+    #[serde(default = "default_base")]
+    pub base: String,
+}
+
+fn default_api_url() -> String {
+    "https://api.github.com".to_string()
+}
+
+fn default_token_env() -> String {
+    "GITHUB_TOKEN".to_string()
+}
+
+fn default_base() -> String {
+    "main".to_string()
+}
+
+impl ForgeConfig {
+    pub fn new(repository: impl Into<String>) -> Self {
+        Self {
+            api_url: default_api_url(),
+            repository: repository.into(),
+            token_env: default_token_env(),
+            base: default_base(),
+        }
+    }
+
+    pub fn with_api_url(mut self, api_url: impl Into<String>) -> Self {
+        self.api_url = api_url.into();
+        self
+    }
+}
+
+/// Parameters for CreatePullRequest tool
+#[derive(Debug, Deserialize)]
+pub struct CreatePullRequestParams {
+    pub title: String,
+    /// Markdown description
+    pub body: String,
+    /// Branch with the changes
+    pub head: String,
+    /// Branch to merge into; the configured base when absent
+    #[serde(default)]
+    pub base: Option<String>,
+    #[serde(default)]
+    pub draft: bool,
+}
+
+/// CreatePullRequest tool - opens a pull request on the forge
+pub struct CreatePullRequest {
+    config: ForgeConfig,
+    client: reqwest::Client,
+}
+
+impl CreatePullRequest {
+    pub fn new(config: ForgeConfig) -> Self {
+        Self {
+            config,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Open a pull request and return its web URL
+    pub async fn open(&self, params: &CreatePullRequestParams) -> anyhow::Result<String> {
+        let token = std::env::var(&self.config.token_env)
+            .with_context(|| format!("{} is not set", self.config.token_env))?;
+        let url = format!("{}/repos/{}/pulls", self.config.api_url.trim_end_matches('/'), self.config.repository);
+        let response = self
+            .client
+            .post(&url)
+            .bearer_auth(token)
+            .header("Accept", "application/vnd.github+json")
+            .header("User-Agent", "zed42")
+            .json(&json!({
+                "title": params.title,
+                "body": params.body,
+                "head": params.head,
+                "base": params.base.as_deref().unwrap_or(&self.config.base),
+                "draft": params.draft,
+            }))
+            .send()
+            .await
+            .context("Failed to reach the forge")?;
+
+        let status = response.status();
+        let body: Value = response.json().await.unwrap_or(Value::Null);
+        if !status.is_success() {
+            let message = body.get("message").and_then(|m| m.as_str()).unwrap_or("no details");
+            bail!("Forge rejected the pull request ({}): {}", status, message);
+        }
+        body.get("html_url")
+            .and_then(|u| u.as_str())
+            .map(str::to_string)
+            .context("Forge response has no pull request URL")
+    }
+}
+
+#[async_trait]
+impl Tool for CreatePullRequest {
+    fn name(&self) -> &str {
+        "create_pull_request"
+    }
+
+    fn description(&self) -> &str {
+        "Open a pull request with a title and Markdown description"
+    }
+
+    fn parameter_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "title": { "type": "string", "description": "Pull request title" },
+                "body": { "type": "string", "description": "Markdown description" },
+                "head": { "type": "string", "description": "Branch with the changes" },
+                "base": { "type": "string", "description": "Branch to merge into (default: the configured base)" },
+                "draft": { "type": "boolean", "description": "Open as a draft" }
+            },
+            "required": ["title", "body", "head"]
+        })
+    }
+
+    /// Every pull request, since it is posted to the forge for others to see
+    fn approval_needed(&self, params: &Value) -> Option<ApprovalNeed> {
+        let params: CreatePullRequestParams = serde_json::from_value(params.clone()).ok()?;
+        let base = params.base.as_deref().unwrap_or(&self.config.base);
+        Some(ApprovalNeed::new(
+            format!("Opens pull request \"{}\" from {} into {} on {}", params.title, params.head, base, self.config.repository),
+            format!("create_pull_request {}", self.config.repository),
+        ))
+    }
+
+    /// The description as it will be posted
+    async fn approval_preview(&self, params: &Value) -> Option<String> {
+        params["body"].as_str().map(str::to_string)
+    }
+
+    async fn execute(&self, params: Value) -> ToolResult {
+        let params: CreatePullRequestParams = serde_json::from_value(params)
+            .map_err(ToolError::invalid_params)?;
+        let url = self.open(&params).await.map_err(|e| ToolError::External(format!("{:#}", e)))?;
+        tracing::info!(url = %url, "Opened pull request");
+        Ok(json!({ "success": true, "url": url }))
+    }
+}
@@ -7,7 +7,12 @@ use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::path::{Path, PathBuf};
-use crate::{Tool, ToolResult};
+use crate::diff_guard::{WorkspaceDiffGuard, WriteCheck};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use std::collections::HashMap;
+use zed42_core::page::{Cursor, Page, PageRequest, Position};
+use crate::{approval, platform, ApprovalNeed, Tool, ToolError, ToolResult};
+use zed42_core::types::TaskId;
 
 /// Path sanitizer for securing file operations
 #[derive(Debug, Clone)]
@@ -32,6 +37,10 @@ impl PathSanitizer {
     /// Returns an error if:
     /// - Path contains ".." traversal
     /// - Path resolves outside the sandbox root
+    ///
+    /// Both the path and the sandbox root are resolved before comparing, so
+    /// Windows verbatim (`\\?\`) and UNC paths, symlinked roots and long
+    /// paths are checked against the same spelling of the root.
     pub fn sanitize(&self, path: &str) -> std::result::Result<PathBuf, String> {
         // Check for obvious traversal attempts
         if path.contains("..") {
@@ -45,23 +54,26 @@ impl PathSanitizer {
             self.sandbox_root.join(path)
         };
 
+        let root = platform::resolve(&self.sandbox_root)
+            .map_err(|e| format!("Cannot resolve sandbox root: {}", e))?;
+
         // Canonicalize to resolve symlinks and normalize
-        let canonical = match full_path.canonicalize() {
-            Ok(p) => p,
+        let canonical = match platform::extended_length(&full_path).canonicalize() {
+            Ok(p) => platform::simplify(&p),
             Err(_) => {
                 // For new files that don't exist yet, check parent
                 if let Some(parent) = full_path.parent() {
-                    if parent.exists() {
+                    if platform::extended_length(parent).exists() {
                         // Parent exists, check if it's in sandbox
-                        let canonical_parent = parent.canonicalize()
+                        let canonical_parent = platform::resolve(parent)
                             .map_err(|e| format!("Cannot resolve parent path: {}", e))?;
-                        if !canonical_parent.starts_with(&self.sandbox_root) {
+                        if !platform::starts_with(&canonical_parent, &root) {
                             return Err(format!(
                                 "Path '{}' is outside the sandbox",
                                 path
                             ));
                         }
-                        return Ok(full_path);
+                        return Ok(platform::extended_length(&full_path));
                     }
                 }
                 return Err(format!("Path '{}' does not exist and cannot be verified", path));
@@ -69,7 +81,7 @@ impl PathSanitizer {
         };
 
         // Verify the canonical path is within sandbox
-        if !canonical.starts_with(&self.sandbox_root) {
+        if !platform::starts_with(&canonical, &root) {
             return Err(format!(
                 "Path '{}' resolves outside the sandbox",
                 path
@@ -90,14 +102,23 @@ pub struct ReadFileParams {
 /// ReadFile tool - reads file contents safely
 pub struct ReadFile {
     sanitizer: PathSanitizer,
+    /// Records what the task read, so later writes can detect user edits
+    diff_guard: Option<(WorkspaceDiffGuard, TaskId)>,
 }
 
 impl ReadFile {
     pub fn new(sandbox_root: impl Into<PathBuf>) -> Self {
         Self {
             sanitizer: PathSanitizer::new(sandbox_root),
+            diff_guard: None,
         }
     }
+
+    /// Record each read as the base of `task_id` in `guard`
+    pub fn with_diff_guard(mut self, guard: WorkspaceDiffGuard, task_id: impl Into<TaskId>) -> Self {
+        self.diff_guard = Some((guard, task_id.into()));
+        self
+    }
 }
 
 #[async_trait]
@@ -125,17 +146,22 @@ impl Tool for ReadFile {
 
     async fn execute(&self, params: Value) -> ToolResult {
         let params: ReadFileParams = serde_json::from_value(params)
-            .map_err(|e| anyhow::anyhow!("Invalid parameters: {}", e))?;
+            .map_err(ToolError::invalid_params)?;
 
         let safe_path = self.sanitizer.sanitize(&params.path)
-            .map_err(|e| anyhow::anyhow!("Path security error: {}", e))?;
+            .map_err(ToolError::sandbox)?;
 
         let content = tokio::fs::read_to_string(&safe_path).await
-            .map_err(|e| anyhow::anyhow!("Failed to read file: {}", e))?;
+            .map_err(|e| ToolError::io("Failed to read file", e))?;
+
+        if let Some((ref guard, ref task_id)) = self.diff_guard {
+            guard.record_read(task_id, &safe_path, content.as_bytes())?;
+        }
 
         Ok(json!({
             "success": true,
             "path": safe_path.to_string_lossy(),
+            "hash": crate::diff_guard::content_hash(content.as_bytes())?,
             "content": content,
             "size_bytes": content.len()
         }))
@@ -157,14 +183,23 @@ pub struct WriteFileParams {
 /// WriteFile tool - writes file contents safely
 pub struct WriteFile {
     sanitizer: PathSanitizer,
+    /// Holds writes to files changed since the task read them
+    diff_guard: Option<(WorkspaceDiffGuard, TaskId)>,
 }
 
 impl WriteFile {
     pub fn new(sandbox_root: impl Into<PathBuf>) -> Self {
         Self {
             sanitizer: PathSanitizer::new(sandbox_root),
+            diff_guard: None,
         }
     }
+
+    /// Refuse to overwrite files that diverged from what `task_id` read
+    pub fn with_diff_guard(mut self, guard: WorkspaceDiffGuard, task_id: impl Into<TaskId>) -> Self {
+        self.diff_guard = Some((guard, task_id.into()));
+        self
+    }
 }
 
 #[async_trait]
@@ -199,50 +234,82 @@ impl Tool for WriteFile {
         })
     }
 
+    /// Writes that replace an existing file; new files don't need approval
+    fn approval_needed(&self, params: &Value) -> Option<ApprovalNeed> {
+        let path = params["path"].as_str()?;
+        if !self.sanitizer.sanitize(path).ok()?.is_file() {
+            return None;
+        }
+        Some(ApprovalNeed::new(format!("Overwrites {}", path), "overwrite_file"))
+    }
+
+    /// Diff from the current content to the new one
+    async fn approval_preview(&self, params: &Value) -> Option<String> {
+        let path = params["path"].as_str()?;
+        let content = params["content"].as_str()?;
+        let current = tokio::fs::read(self.sanitizer.sanitize(path).ok()?).await.ok()?;
+        approval::diff_preview(path, &current, content.as_bytes())
+    }
+
     async fn execute(&self, params: Value) -> ToolResult {
         let params: WriteFileParams = serde_json::from_value(params)
-            .map_err(|e| anyhow::anyhow!("Invalid parameters: {}", e))?;
+            .map_err(ToolError::invalid_params)?;
 
         // For write, we need to handle non-existent files
         // First check for path traversal directly
         if params.path.contains("..") {
-            return Err(anyhow::anyhow!("Path traversal (..) is not allowed"));
+            return Err(ToolError::sandbox("Path traversal (..) is not allowed"));
+        }
+
+        let safe_path = self.sanitizer.sanitize(&params.path).map_err(ToolError::sandbox)?;
+
+        // 0. Diff guard: don't overwrite an edit made since the task read the file
+        if let Some((ref guard, ref task_id)) = self.diff_guard {
+            if let WriteCheck::Diverged { base, current } = guard.check_write(task_id, &safe_path)? {
+                tracing::warn!(path = %params.path, task_id = %task_id, "File changed since the agent read it");
+                return Ok(json!({
+                    "path": params.path,
+                    "success": false,
+                    "approval_required": true,
+                    "rule": "diff_guard",
+                    "reason": "The file changed since it was read. Read it again and reapply the change, or wait for approval to overwrite",
+                    "base_hash": base,
+                    "current_hash": current
+                }));
+            }
         }
 
         // Use FileStateGuard for atomic write (Shadow Write pattern)
         // 1. Guard initializes .tmp file
         let guard = crate::fs_guard::FileStateGuard::new(&self.sanitizer, &params.path)
-            .map_err(|e| anyhow::anyhow!("Failed to initialize atomic write guard: {}", e))?;
+            .map_err(|e| ToolError::from(e.context("Failed to initialize atomic write guard")))?;
 
         let full_path = self.sanitizer.sandbox_root.join(&params.path);
 
         // Create parent directories if requested
         if params.create_dirs {
             if let Some(parent) = full_path.parent() {
-                // Verify parent is still in sandbox
-                let sandbox_canon = self.sanitizer.sandbox_root.canonicalize()
-                    .map_err(|e| anyhow::anyhow!("Sandbox path error: {}", e))?;
-                
                 // Create the dirs first, then verify
-                tokio::fs::create_dir_all(parent).await
-                    .map_err(|e| anyhow::anyhow!("Failed to create directories: {}", e))?;
-                
-                let parent_canon = parent.canonicalize()
-                    .map_err(|e| anyhow::anyhow!("Path resolution error: {}", e))?;
-                
-                if !parent_canon.starts_with(&sandbox_canon) {
-                    return Err(anyhow::anyhow!("Path resolves outside sandbox"));
+                tokio::fs::create_dir_all(platform::extended_length(parent)).await
+                    .map_err(|e| ToolError::io("Failed to create directories", e))?;
+
+                if !platform::is_within(parent, &self.sanitizer.sandbox_root) {
+                    return Err(ToolError::sandbox("Path resolves outside sandbox"));
                 }
             }
         }
 
         // 2. Write to the temporary path provided by guard
         tokio::fs::write(guard.path(), &params.content).await
-            .map_err(|e| anyhow::anyhow!("Failed to write to temp file: {}", e))?;
+            .map_err(|e| ToolError::io("Failed to write to temp file", e))?;
 
         // 3. Commit the guard (Atomic update)
         guard.commit()
-            .map_err(|e| anyhow::anyhow!("Failed to commit atomic write: {}", e))?;
+            .map_err(|e| ToolError::from(e.context("Failed to commit atomic write")))?;
+
+        if let Some((ref diff_guard, ref task_id)) = self.diff_guard {
+            diff_guard.record_write(task_id, &safe_path, params.content.as_bytes())?;
+        }
 
         Ok(json!({
             "success": true,
@@ -284,10 +351,23 @@ impl Tool for MoveFile {
         })
     }
 
+    /// Every move, since the source disappears and the target may be replaced
+    fn approval_needed(&self, params: &Value) -> Option<ApprovalNeed> {
+        let source = params["source_path"].as_str()?;
+        let target = params["target_path"].as_str()?;
+        let replaces = self.sanitizer.sanitize(target).is_ok_and(|path| path.exists());
+        let reason = if replaces {
+            format!("Moves {} to {}, replacing it", source, target)
+        } else {
+            format!("Moves {} to {}", source, target)
+        };
+        Some(ApprovalNeed::new(reason, "move_file"))
+    }
+
     async fn execute(&self, params: Value) -> ToolResult {
-        let params: MoveFileParams = serde_json::from_value(params)?;
-        let src = self.sanitizer.sanitize(&params.source_path)?;
-        let dst = self.sanitizer.sanitize(&params.target_path)?;
+        let params: MoveFileParams = serde_json::from_value(params).map_err(ToolError::invalid_params)?;
+        let src = self.sanitizer.sanitize(&params.source_path).map_err(ToolError::sandbox)?;
+        let dst = self.sanitizer.sanitize(&params.target_path).map_err(ToolError::sandbox)?;
 
         // check health before write (rename is a write to dir)
         let guard = crate::fs_guard::FileStateGuard::new(&self.sanitizer, &params.target_path)?;
@@ -326,9 +406,21 @@ impl Tool for DeleteFile {
         })
     }
 
+    fn approval_needed(&self, params: &Value) -> Option<ApprovalNeed> {
+        let path = params["path"].as_str()?;
+        Some(ApprovalNeed::new(format!("Deletes {}", path), "delete_file"))
+    }
+
+    /// The file's content as removed lines
+    async fn approval_preview(&self, params: &Value) -> Option<String> {
+        let path = params["path"].as_str()?;
+        let content = tokio::fs::read(self.sanitizer.sanitize(path).ok()?).await.ok()?;
+        approval::diff_preview(path, &content, b"")
+    }
+
     async fn execute(&self, params: Value) -> ToolResult {
-        let params: DeleteFileParams = serde_json::from_value(params)?;
-        let path = self.sanitizer.sanitize(&params.path)?;
+        let params: DeleteFileParams = serde_json::from_value(params).map_err(ToolError::invalid_params)?;
+        let path = self.sanitizer.sanitize(&params.path).map_err(ToolError::sandbox)?;
         
         // health check and guard
         let guard = crate::fs_guard::FileStateGuard::new(&self.sanitizer, &params.path)?;
@@ -368,7 +460,7 @@ impl Tool for CreateDir {
     }
 
     async fn execute(&self, params: Value) -> ToolResult {
-        let params: CreateDirParams = serde_json::from_value(params)?;
+        let params: CreateDirParams = serde_json::from_value(params).map_err(ToolError::invalid_params)?;
         
         // Use FileStateGuard for atomic dir creation
         let guard = crate::fs_guard::FileStateGuard::new_dir(&self.sanitizer, &params.path)?;
@@ -412,9 +504,18 @@ impl Tool for DeleteDir {
         })
     }
 
+    fn approval_needed(&self, params: &Value) -> Option<ApprovalNeed> {
+        let path = params["path"].as_str()?;
+        let reason = match params["recursive"].as_bool() {
+            Some(true) => format!("Deletes directory {} and everything in it", path),
+            _ => format!("Deletes empty directory {}", path),
+        };
+        Some(ApprovalNeed::new(reason, "delete_dir"))
+    }
+
     async fn execute(&self, params: Value) -> ToolResult {
-        let params: DeleteDirParams = serde_json::from_value(params)?;
-        let path = self.sanitizer.sanitize(&params.path)?;
+        let params: DeleteDirParams = serde_json::from_value(params).map_err(ToolError::invalid_params)?;
+        let path = self.sanitizer.sanitize(&params.path).map_err(ToolError::sandbox)?;
         
         // health check and guard
         let guard = crate::fs_guard::FileStateGuard::new_dir(&self.sanitizer, &params.path)?;
@@ -459,6 +560,28 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_path_sanitizer_normalizes_root_and_long_paths() {
+        let temp = tempdir().unwrap();
+        let outside = tempdir().unwrap();
+        // The root is not canonical: canonicalize() adds `\\?\` on Windows
+        // and `/private` on macOS, which used to fail the starts_with check
+        let sanitizer = PathSanitizer::new(temp.path());
+
+        std::fs::write(temp.path().join("a.txt"), "x").unwrap();
+        assert!(sanitizer.sanitize("a.txt").is_ok());
+        assert!(sanitizer.sanitize("new.txt").is_ok());
+        assert!(sanitizer.sanitize(outside.path().join("b.txt").to_str().unwrap()).is_err());
+
+        // Well past MAX_PATH once joined to the root
+        let deep = (0..30).map(|i| format!("segment_{:02}", i)).collect::<Vec<_>>().join("/");
+        std::fs::create_dir_all(platform::extended_length(&temp.path().join(&deep))).unwrap();
+        let long = format!("{}/file.rs", deep);
+        assert!(sanitizer.sanitize(&long).is_ok());
+        std::fs::write(platform::extended_length(&temp.path().join(&long)), "fn main() {}").unwrap();
+        assert!(sanitizer.sanitize(&long).is_ok());
+    }
+
     #[tokio::test]
     async fn test_read_file_tool() {
         let temp = tempdir().unwrap();
@@ -490,6 +613,26 @@ mod tests {
         assert_eq!(content, "generated code");
     }
 
+    #[tokio::test]
+    async fn test_overwrites_and_moves_need_approval() {
+        let temp = tempdir().unwrap();
+        std::fs::write(temp.path().join("old.txt"), "before\n").unwrap();
+        let write = WriteFile::new(temp.path());
+
+        assert!(write.approval_needed(&json!({ "path": "new.txt", "content": "x" })).is_none());
+        let overwrite = json!({ "path": "old.txt", "content": "after\n" });
+        assert_eq!(write.approval_needed(&overwrite).unwrap().scope, "overwrite_file");
+        let preview = write.approval_preview(&overwrite).await.unwrap();
+        assert!(preview.contains("-before") && preview.contains("+after"));
+
+        let moves = MoveFile::new(temp.path());
+        let need = moves.approval_needed(&json!({ "source_path": "old.txt", "target_path": "kept.txt" })).unwrap();
+        assert_eq!(need.reason, "Moves old.txt to kept.txt");
+        std::fs::write(temp.path().join("kept.txt"), "").unwrap();
+        let need = moves.approval_needed(&json!({ "source_path": "old.txt", "target_path": "kept.txt" })).unwrap();
+        assert!(need.reason.ends_with("replacing it"));
+    }
+
     #[tokio::test]
     async fn test_write_file_rejects_traversal() {
         let temp = tempdir().unwrap();
@@ -521,6 +664,104 @@ mod tests {
         assert!(entries.iter().any(|e| e["name"] == "file1.txt" && e["type"] == "file"));
         assert!(entries.iter().any(|e| e["name"] == "subdir" && e["type"] == "dir"));
     }
+
+    #[tokio::test]
+    async fn test_list_dir_recursive_globs_and_pages() {
+        let temp = tempdir().unwrap();
+        for path in ["src/a.rs", "src/b.rs", "src/nested/c.rs", "src/nested/deep/d.rs", "docs/guide.md"] {
+            let path = temp.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "12345").unwrap();
+        }
+        let tool = ListDir::new(temp.path());
+
+        let top = tool.execute(json!({ "path": "." })).await.unwrap();
+        assert_eq!(top["entries"].as_array().unwrap().len(), 2);
+        assert!(top["next_cursor"].is_null());
+
+        let two = tool.execute(json!({ "path": "src", "max_depth": 2 })).await.unwrap();
+        let paths: Vec<&str> = two["entries"].as_array().unwrap().iter().map(|e| e["path"].as_str().unwrap()).collect();
+        assert_eq!(paths, vec!["a.rs", "b.rs", "nested", "nested/c.rs", "nested/deep"]);
+
+        // Page through every .rs file two at a time
+        let mut seen = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let mut params = json!({ "path": ".", "max_depth": 0, "globs": ["*.rs"], "limit": 2 });
+            if let Some(cursor) = &cursor {
+                params["cursor"] = json!(cursor);
+            }
+            let page = tool.execute(params).await.unwrap();
+            assert_eq!(page["stats"]["files"], 4);
+            assert_eq!(page["stats"]["total_bytes"], 20);
+            assert_eq!(page["stats"]["extensions"][0]["extension"], "rs");
+            seen.extend(page["entries"].as_array().unwrap().iter().map(|e| e["path"].as_str().unwrap().to_string()));
+            match page["next_cursor"].as_str() {
+                Some(next) => cursor = Some(next.to_string()),
+                None => break,
+            }
+        }
+        assert_eq!(seen, vec!["src/a.rs", "src/b.rs", "src/nested/c.rs", "src/nested/deep/d.rs"]);
+
+        assert!(tool.execute(json!({ "path": ".", "cursor": "zz" })).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_stat_files_detects_changes_since_read() {
+        let temp = tempdir().unwrap();
+        std::fs::write(temp.path().join("a.rs"), "fn a() {}").unwrap();
+        std::fs::write(temp.path().join("b.rs"), "fn b() {}").unwrap();
+
+        let read = ReadFile::new(temp.path()).execute(json!({ "path": "a.rs" })).await.unwrap();
+        let hash = read["hash"].as_str().unwrap().to_string();
+        let tool = StatFiles::new(temp.path());
+        let params = json!({ "paths": ["a.rs", "b.rs", "gone/c.rs"], "expected_hashes": { "a.rs": hash } });
+
+        let result = tool.execute(params.clone()).await.unwrap();
+        assert_eq!(result["all_current"], true);
+        assert_eq!(result["files"][0]["size"], 9);
+        assert_eq!(result["files"][0]["current"], true);
+        assert!(result["files"][1]["hash"].is_string());
+        assert!(result["files"][1].get("current").is_none());
+        assert_eq!(result["files"][2]["exists"], false);
+
+        std::fs::write(temp.path().join("a.rs"), "fn a() { changed() }").unwrap();
+        let result = tool.execute(params).await.unwrap();
+        assert_eq!(result["all_current"], false);
+        assert_eq!(result["files"][0]["current"], false);
+        assert!(tool.execute(json!({ "paths": ["../x"] })).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_list_dir_and_read_many_skip_ignored() {
+        let temp = tempdir().unwrap();
+        std::fs::write(temp.path().join(".gitignore"), "*.log\n").unwrap();
+        std::fs::create_dir_all(temp.path().join("target/debug")).unwrap();
+        std::fs::create_dir_all(temp.path().join("src")).unwrap();
+        std::fs::write(temp.path().join("target/debug/out"), "binary").unwrap();
+        std::fs::write(temp.path().join("src/main.rs"), "fn main() {}").unwrap();
+        std::fs::write(temp.path().join("src/trace.log"), "noise").unwrap();
+
+        let list = ListDir::new(temp.path());
+        let names = |value: Value| -> Vec<String> {
+            value["entries"].as_array().unwrap().iter().map(|e| e["name"].as_str().unwrap().to_string()).collect()
+        };
+        assert_eq!(names(list.execute(json!({ "path": "." })).await.unwrap()), vec![".gitignore", "src"]);
+        assert_eq!(
+            names(list.execute(json!({ "path": ".", "include_ignored": true })).await.unwrap()),
+            vec![".gitignore", "src", "target"]
+        );
+
+        let read = ReadMany::new(temp.path());
+        let result = read.execute(json!({ "paths": ["src"] })).await.unwrap();
+        let files = result["files"].as_array().unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0]["path"], "src/main.rs");
+
+        let result = read.execute(json!({ "paths": ["."], "include_ignored": true, "max_files": 2 })).await.unwrap();
+        assert_eq!(result["files"].as_array().unwrap().len(), 2);
+        assert_eq!(result["truncated"], true);
+    }
 }
 
 /// Parameters for ListDir tool
@@ -528,6 +769,35 @@ mod tests {
 pub struct ListDirParams {
     /// Directory path to list (relative to sandbox root)
     pub path: String,
+    /// List entries excluded by .gitignore/.zedignore too
+    #[serde(default)]
+    pub include_ignored: bool,
+    /// Levels to descend; 1 lists the directory itself, 0 means no limit
+    #[serde(default = "default_list_depth")]
+    pub max_depth: usize,
+    /// Only list entries whose path (relative to `path`) matches one of these globs
+    #[serde(default)]
+    pub globs: Vec<String>,
+    /// Entries per page
+    pub limit: Option<usize>,
+    /// `next_cursor` from the previous page
+    pub cursor: Option<String>,
+}
+
+fn default_list_depth() -> usize {
+    1
+}
+
+/// Extensions reported in listing statistics
+const TOP_EXTENSIONS: usize = 10;
+
+/// One listed entry, before paging
+struct ListedEntry {
+    path: String,
+    name: String,
+    kind: &'static str,
+    size: u64,
+    readonly: bool,
 }
 
 /// ListDir tool - lists directory contents safely
@@ -550,7 +820,7 @@ impl Tool for ListDir {
     }
 
     fn description(&self) -> &str {
-        "List contents of a directory within the project sandbox"
+        "List a directory within the project sandbox, optionally recursively and filtered by globs, one page at a time with summary statistics; .gitignore/.zedignore matches are skipped unless include_ignored is set"
     }
 
     fn parameter_schema(&self) -> Value {
@@ -560,6 +830,29 @@ impl Tool for ListDir {
                 "path": {
                     "type": "string",
                     "description": "Path to the directory (relative to project root)"
+                },
+                "include_ignored": {
+                    "type": "boolean",
+                    "description": "Also list ignored entries such as target/ or node_modules/",
+                    "default": false
+                },
+                "max_depth": {
+                    "type": "number",
+                    "description": "Levels to descend: 1 lists only this directory, 0 means unlimited",
+                    "default": 1
+                },
+                "globs": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Only list entries matching one of these globs, e.g. \"**/*.rs\""
+                },
+                "limit": {
+                    "type": "number",
+                    "description": "Entries per page (default 100, max 1000)"
+                },
+                "cursor": {
+                    "type": "string",
+                    "description": "next_cursor from the previous page"
                 }
             },
             "required": ["path"]
@@ -568,44 +861,371 @@ impl Tool for ListDir {
 
     async fn execute(&self, params: Value) -> ToolResult {
         let params: ListDirParams = serde_json::from_value(params)
-            .map_err(|e| anyhow::anyhow!("Invalid parameters: {}", e))?;
+            .map_err(ToolError::invalid_params)?;
 
         let safe_path = self.sanitizer.sanitize(&params.path)
-            .map_err(|e| anyhow::anyhow!("Path security error: {}", e))?;
+            .map_err(ToolError::sandbox)?;
 
         if !safe_path.is_dir() {
-            return Err(anyhow::anyhow!("Path is not a directory: {}", params.path));
+            return Err(ToolError::invalid_params(format!("Path is not a directory: {}", params.path)));
         }
 
-        let mut read_dir = tokio::fs::read_dir(&safe_path).await
-            .map_err(|e| anyhow::anyhow!("Failed to read directory: {}", e))?;
-
-        let mut entries = Vec::new();
-        while let Some(entry) = read_dir.next_entry().await
-            .map_err(|e| anyhow::anyhow!("Failed to iterate entries: {}", e))? 
-        {
-            let metadata = entry.metadata().await
-                .map_err(|e| anyhow::anyhow!("Failed to get metadata: {}", e))?;
-            
-            let file_type = if metadata.is_dir() { "dir" } 
-                else if metadata.is_symlink() { "symlink" } 
-                else { "file" };
-
-            entries.push(json!({
-                "name": entry.file_name().to_string_lossy(),
-                "type": file_type,
-                "size": metadata.len(),
-                "readonly": metadata.permissions().readonly(),
-            }));
-        }
+        let request = PageRequest {
+            cursor: params.cursor.clone().map(Cursor::from),
+            limit: params.limit,
+        };
+        let position: Option<Position<String>> = request.position().map_err(ToolError::invalid_params)?;
 
-        //Sort entries by name for deterministic output
-        entries.sort_by(|a, b| a["name"].as_str().unwrap().cmp(b["name"].as_str().unwrap()));
+        let mut globs = GlobSetBuilder::new();
+        for glob in &params.globs {
+            globs.add(Glob::new(glob).map_err(ToolError::invalid_params)?);
+        }
+        let globs = globs.build().map_err(ToolError::invalid_params)?;
+
+        let root = safe_path.clone();
+        let include_ignored = params.include_ignored;
+        let max_depth = (params.max_depth > 0).then_some(params.max_depth);
+        let mut listed = tokio::task::spawn_blocking(move || list_entries(&root, include_ignored, max_depth, &globs))
+            .await
+            .map_err(|e| ToolError::Internal(e.to_string()))??;
+        listed.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let stats = listing_stats(&listed);
+        // Paths are unique, so the page resumes right after the last one returned
+        let rows: Vec<ListedEntry> = listed
+            .into_iter()
+            .filter(|entry| position.as_ref().is_none_or(|p| entry.path > p.key))
+            .take(request.limit() + 1)
+            .collect();
+        let page = Page::from_rows(rows, request.limit(), position.as_ref(), |entry| entry.path.clone());
+
+        let entries: Vec<Value> = page
+            .items
+            .iter()
+            .map(|entry| {
+                json!({
+                    "path": entry.path,
+                    "name": entry.name,
+                    "type": entry.kind,
+                    "size": entry.size,
+                    "readonly": entry.readonly,
+                })
+            })
+            .collect();
 
         Ok(json!({
             "success": true,
             "path": safe_path.to_string_lossy(),
-            "entries": entries
+            "entries": entries,
+            "next_cursor": page.next_cursor,
+            "stats": stats
+        }))
+    }
+}
+
+/// Files `read_many` returns unless asked for fewer
+const DEFAULT_READ_MANY_LIMIT: usize = 50;
+
+/// Parameters for ReadMany tool
+#[derive(Debug, Deserialize)]
+pub struct ReadManyParams {
+    /// Files and directories to read (relative to sandbox root); directories are read recursively
+    pub paths: Vec<String>,
+    /// Read files excluded by .gitignore/.zedignore too
+    #[serde(default)]
+    pub include_ignored: bool,
+    /// Stop after this many files
+    pub max_files: Option<usize>,
+}
+
+/// ReadMany tool - reads several files, expanding directories without ignored paths
+pub struct ReadMany {
+    sanitizer: PathSanitizer,
+}
+
+impl ReadMany {
+    pub fn new(sandbox_root: impl Into<PathBuf>) -> Self {
+        Self {
+            sanitizer: PathSanitizer::new(sandbox_root),
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for ReadMany {
+    fn name(&self) -> &str {
+        "read_many"
+    }
+
+    fn description(&self) -> &str {
+        "Read several files or whole directories within the project sandbox, skipping .gitignore/.zedignore matches unless include_ignored is set"
+    }
+
+    fn parameter_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "paths": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Files or directories (relative to project root)"
+                },
+                "include_ignored": {
+                    "type": "boolean",
+                    "description": "Also read ignored files such as build output",
+                    "default": false
+                },
+                "max_files": {
+                    "type": "number",
+                    "description": "Maximum number of files to read (default 50)"
+                }
+            },
+            "required": ["paths"]
+        })
+    }
+
+    async fn execute(&self, params: Value) -> ToolResult {
+        let params: ReadManyParams = serde_json::from_value(params)
+            .map_err(ToolError::invalid_params)?;
+        let limit = params.max_files.unwrap_or(DEFAULT_READ_MANY_LIMIT);
+
+        // Named files are read as asked; only directory expansion filters ignored paths
+        let mut targets = Vec::new();
+        for path in &params.paths {
+            let safe_path = self.sanitizer.sanitize(path).map_err(ToolError::sandbox)?;
+            if safe_path.is_dir() {
+                let walk_root = safe_path.clone();
+                let include_ignored = params.include_ignored;
+                let found = tokio::task::spawn_blocking(move || crate::walk::files(&walk_root, include_ignored))
+                    .await
+                    .map_err(|e| ToolError::Internal(e.to_string()))?;
+                targets.extend(found);
+            } else {
+                targets.push(safe_path);
+            }
+        }
+        targets.dedup();
+
+        let root = platform::resolve(&self.sanitizer.sandbox_root)
+            .map_err(|e| ToolError::io("Cannot resolve sandbox root", e))?;
+        let truncated = targets.len() > limit;
+        let mut files = Vec::new();
+        let mut skipped = Vec::new();
+        for path in targets.into_iter().take(limit) {
+            let relative = path.strip_prefix(&root).unwrap_or(&path).to_string_lossy().replace('\\', "/");
+            match tokio::fs::read_to_string(&path).await {
+                Ok(content) => files.push(json!({ "path": relative, "content": content })),
+                // Binary and non-UTF-8 files are listed, not read
+                Err(e) if e.kind() == std::io::ErrorKind::InvalidData => skipped.push(relative),
+                Err(e) => return Err(ToolError::io(format!("Failed to read {}", relative), e)),
+            }
+        }
+
+        Ok(json!({
+            "success": true,
+            "files": files,
+            "skipped": skipped,
+            "truncated": truncated
+        }))
+    }
+}
+
+/// Entries under `root` down to `max_depth`, keeping those matching `globs` (all when empty)
+fn list_entries(
+    root: &Path,
+    include_ignored: bool,
+    max_depth: Option<usize>,
+    globs: &GlobSet,
+) -> std::result::Result<Vec<ListedEntry>, ToolError> {
+    let walk = crate::walk::walker(root, include_ignored).max_depth(max_depth).build();
+    let mut listed = Vec::new();
+    for entry in walk {
+        let entry = entry.map_err(|e| ToolError::Environment(format!("Failed to iterate entries: {}", e)))?;
+        if entry.depth() == 0 {
+            continue;
+        }
+        let relative = entry.path().strip_prefix(root).unwrap_or(entry.path());
+        let path = relative.to_string_lossy().replace('\\', "/");
+        if !globs.is_empty() && !globs.is_match(&path) {
+            continue;
+        }
+        let metadata = entry.metadata()
+            .map_err(|e| ToolError::Environment(format!("Failed to get metadata: {}", e)))?;
+
+        let kind = if entry.path_is_symlink() { "symlink" }
+            else if metadata.is_dir() { "dir" }
+            else { "file" };
+
+        listed.push(ListedEntry {
+            name: entry.file_name().to_string_lossy().to_string(),
+            path,
+            kind,
+            size: metadata.len(),
+            readonly: crate::platform::is_readonly(&metadata),
+        });
+    }
+    Ok(listed)
+}
+
+/// Counts over the whole listing, not just the page returned
+fn listing_stats(listed: &[ListedEntry]) -> Value {
+    let files: Vec<&ListedEntry> = listed.iter().filter(|entry| entry.kind == "file").collect();
+    let mut extensions: HashMap<String, usize> = HashMap::new();
+    for file in &files {
+        let extension = Path::new(&file.name)
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_string())
+            .unwrap_or_default();
+        *extensions.entry(extension).or_default() += 1;
+    }
+    let mut extensions: Vec<(String, usize)> = extensions.into_iter().collect();
+    extensions.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    extensions.truncate(TOP_EXTENSIONS);
+
+    json!({
+        "entries": listed.len(),
+        "files": files.len(),
+        "dirs": listed.iter().filter(|entry| entry.kind == "dir").count(),
+        "total_bytes": files.iter().map(|file| file.size).sum::<u64>(),
+        "extensions": extensions
+            .into_iter()
+            .map(|(extension, count)| json!({ "extension": extension, "files": count }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+/// Size, modification time and content hash of one file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileStat {
+    pub path: String,
+    pub exists: bool,
+    #[serde(default)]
+    pub size: Option<u64>,
+    #[serde(default)]
+    pub modified: Option<chrono::DateTime<chrono::Utc>>,
+    /// Git blob id of the content, as `read_file` reports it
+    #[serde(default)]
+    pub hash: Option<String>,
+}
+
+/// Stat `path`, reported under `label`; a missing file is not an error
+pub fn stat_file(path: &Path, label: &str) -> anyhow::Result<FileStat> {
+    let metadata = match std::fs::metadata(path) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(FileStat { path: label.to_string(), exists: false, size: None, modified: None, hash: None });
+        }
+        Err(e) => return Err(anyhow::Error::new(e).context(format!("Failed to stat {}", label))),
+    };
+    if metadata.is_dir() {
+        anyhow::bail!("{} is a directory", label);
+    }
+    let content = std::fs::read(path).map_err(|e| anyhow::Error::new(e).context(format!("Failed to read {}", label)))?;
+    Ok(FileStat {
+        path: label.to_string(),
+        exists: true,
+        size: Some(metadata.len()),
+        modified: metadata.modified().ok().map(chrono::DateTime::<chrono::Utc>::from),
+        hash: Some(crate::diff_guard::content_hash(&content)?),
+    })
+}
+
+/// Parameters for StatFiles tool
+#[derive(Debug, Deserialize)]
+pub struct StatFilesParams {
+    /// Files to stat (relative to sandbox root)
+    pub paths: Vec<String>,
+    /// Hash each file had when it was read, to check it is still current
+    #[serde(default)]
+    pub expected_hashes: HashMap<String, String>,
+}
+
+/// StatFiles tool - cheap change detection without reading content into the prompt
+pub struct StatFiles {
+    sanitizer: PathSanitizer,
+}
+
+impl StatFiles {
+    pub fn new(sandbox_root: impl Into<PathBuf>) -> Self {
+        Self {
+            sanitizer: PathSanitizer::new(sandbox_root),
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for StatFiles {
+    fn name(&self) -> &str {
+        "stat_files"
+    }
+
+    fn description(&self) -> &str {
+        "Return size, modification time and content hash for files, and whether each still matches the hash it was read with"
+    }
+
+    fn parameter_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "paths": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Files to stat (relative to project root)"
+                },
+                "expected_hashes": {
+                    "type": "object",
+                    "additionalProperties": { "type": "string" },
+                    "description": "Path to the hash read_file returned, to verify the content is still current"
+                }
+            },
+            "required": ["paths"]
+        })
+    }
+
+    async fn execute(&self, params: Value) -> ToolResult {
+        let params: StatFilesParams = serde_json::from_value(params)
+            .map_err(ToolError::invalid_params)?;
+
+        let mut targets = Vec::new();
+        for path in &params.paths {
+            let safe_path = match self.sanitizer.sanitize(path) {
+                Ok(safe_path) => safe_path,
+                // A file under a missing directory is just missing; nothing outside is revealed
+                Err(_) if !path.contains("..") && !self.sanitizer.sandbox_root.join(path).exists() => {
+                    self.sanitizer.sandbox_root.join(path)
+                }
+                Err(e) => return Err(ToolError::sandbox(e)),
+            };
+            targets.push((path.clone(), safe_path));
+        }
+
+        let stats = tokio::task::spawn_blocking(move || {
+            targets
+                .iter()
+                .map(|(label, path)| stat_file(path, label))
+                .collect::<anyhow::Result<Vec<FileStat>>>()
+        })
+        .await
+        .map_err(|e| ToolError::Internal(e.to_string()))??;
+
+        let mut all_current = true;
+        let files: Vec<Value> = stats
+            .iter()
+            .map(|stat| {
+                let mut file = json!(stat);
+                if let Some(expected) = params.expected_hashes.get(&stat.path) {
+                    let current = stat.hash.as_deref() == Some(expected.as_str());
+                    all_current &= current;
+                    file["current"] = json!(current);
+                }
+                file
+            })
+            .collect();
+
+        Ok(json!({
+            "success": true,
+            "files": files,
+            "all_current": all_current
         }))
     }
 }
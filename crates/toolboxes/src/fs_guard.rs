@@ -87,6 +87,9 @@ impl FileStateGuard {
             return Ok(());
         }
 
+        zed42_core::chaos::fail_point(zed42_core::chaos::FaultPoint::ToolIo)
+            .context(format!("Failed to commit file: {:?}", self.target_path))?;
+
         // Rename temp -> target (Atomic on POSIX, mostly atomic on Windows)
         std::fs::rename(&self.temp_path, &self.target_path)
             .context(format!("Failed to commit file: atomic rename failed from {:?} to {:?}", self.temp_path, self.target_path))?;
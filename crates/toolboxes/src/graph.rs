@@ -0,0 +1,313 @@
+//! Knowledge graph query tools
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use zed42_memory::knowledge_graph::{KnowledgeGraphMemory, KnowledgeNode, NodeType, SearchQuery, DEFAULT_IMPACT_DEPTH};
+use crate::{Tool, ToolError, ToolResult};
+
+/// Results returned by semantic_code_search by default
+const DEFAULT_SEARCH_RESULTS: usize = 10;
+
+/// Upper bound on semantic_code_search results
+const MAX_SEARCH_RESULTS: usize = 50;
+
+/// Characters of node content shown per search hit
+const SNIPPET_CHARS: usize = 400;
+
+/// Parameters for AnalyzeImpact tool
+#[derive(Debug, Deserialize)]
+pub struct AnalyzeImpactParams {
+    /// Function or file node being changed
+    pub node_id: String,
+    /// Hops of dependents to follow (default 5)
+    #[serde(default)]
+    pub max_depth: Option<usize>,
+}
+
+/// AnalyzeImpact tool - blast radius of changing a function or file
+///
+/// Returns the dependents, affected files, covering tests and public APIs of
+/// the node, plus a risk level. Agents attach the report to their plans and
+/// reviewers use the risk level when scoring a change.
+pub struct AnalyzeImpact {
+    graph: Arc<KnowledgeGraphMemory>,
+}
+
+impl AnalyzeImpact {
+    pub fn new(graph: Arc<KnowledgeGraphMemory>) -> Self {
+        Self { graph }
+    }
+}
+
+#[async_trait]
+impl Tool for AnalyzeImpact {
+    fn name(&self) -> &str {
+        "analyze_impact"
+    }
+
+    fn description(&self) -> &str {
+        "Compute the blast radius of changing a function or file: dependents, affected files, tests and public APIs, with a risk level"
+    }
+
+    fn parameter_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "node_id": {
+                    "type": "string",
+                    "description": "Knowledge graph id of the function or file being changed"
+                },
+                "max_depth": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "description": "Hops of dependents to follow (default 5)"
+                }
+            },
+            "required": ["node_id"]
+        })
+    }
+
+    async fn execute(&self, params: Value) -> ToolResult {
+        let params: AnalyzeImpactParams = serde_json::from_value(params)
+            .map_err(ToolError::invalid_params)?;
+
+        let report = self
+            .graph
+            .analyze_impact(&params.node_id, params.max_depth.unwrap_or(DEFAULT_IMPACT_DEPTH))
+            .await?;
+        Ok(json!({
+            "success": true,
+            "summary": report.summary(),
+            "report": report
+        }))
+    }
+}
+
+/// Parameters for SemanticCodeSearch tool
+#[derive(Debug, Deserialize)]
+pub struct SemanticCodeSearchParams {
+    /// Natural-language description of the code being looked for
+    pub query: String,
+    /// Node types to return (all when empty)
+    #[serde(default)]
+    pub node_types: Vec<NodeType>,
+    /// Only return nodes whose path starts with this
+    #[serde(default)]
+    pub path_prefix: Option<String>,
+    #[serde(default)]
+    pub top_k: Option<usize>,
+}
+
+/// SemanticCodeSearch tool - finds code by meaning in the knowledge graph
+///
+/// Lets agents ask "where do we validate auth tokens" on their own instead
+/// of relying on memory results injected with the task.
+pub struct SemanticCodeSearch {
+    graph: Arc<KnowledgeGraphMemory>,
+}
+
+impl SemanticCodeSearch {
+    pub fn new(graph: Arc<KnowledgeGraphMemory>) -> Self {
+        Self { graph }
+    }
+}
+
+#[async_trait]
+impl Tool for SemanticCodeSearch {
+    fn name(&self) -> &str {
+        "semantic_code_search"
+    }
+
+    fn description(&self) -> &str {
+        "Search the codebase by meaning (e.g. 'where do we validate auth tokens'), optionally filtered by node type and path prefix"
+    }
+
+    fn parameter_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "query": {
+                    "type": "string",
+                    "description": "What the code does, in plain language"
+                },
+                "node_types": {
+                    "type": "array",
+                    "items": {
+                        "type": "string",
+                        "enum": ["file", "function", "type", "module", "decision", "dependency", "test", "documentation", "package", "person", "feedback"]
+                    },
+                    "description": "Only return these kinds of nodes (optional)"
+                },
+                "path_prefix": {
+                    "type": "string",
+                    "description": "Only return nodes under this path, e.g. 'crates/auth/' (optional)"
+                },
+                "top_k": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": MAX_SEARCH_RESULTS,
+                    "description": "Number of results (default 10)"
+                }
+            },
+            "required": ["query"]
+        })
+    }
+
+    async fn execute(&self, params: Value) -> ToolResult {
+        let params: SemanticCodeSearchParams = serde_json::from_value(params)
+            .map_err(ToolError::invalid_params)?;
+        if params.query.trim().is_empty() {
+            return Err(ToolError::invalid_params("query must not be empty"));
+        }
+        let top_k = params.top_k.unwrap_or(DEFAULT_SEARCH_RESULTS).clamp(1, MAX_SEARCH_RESULTS);
+        let prefix = params.path_prefix.as_deref().map(|p| p.trim_start_matches("./")).filter(|p| !p.is_empty());
+
+        // The path filter runs after the vector search, so over-fetch for it
+        let fetch = if prefix.is_some() { top_k * 4 } else { top_k };
+        let hits = self
+            .graph
+            .search(SearchQuery::Semantic {
+                query_text: params.query.clone(),
+                top_k: fetch,
+                node_types: (!params.node_types.is_empty()).then_some(params.node_types),
+            })
+            .await?;
+
+        let results: Vec<Value> = hits
+            .into_iter()
+            .filter_map(|hit| {
+                let path = node_path(&hit.node);
+                if let Some(prefix) = prefix {
+                    if !path.as_deref().is_some_and(|p| p.trim_start_matches("./").starts_with(prefix)) {
+                        return None;
+                    }
+                }
+                Some(json!({
+                    "id": hit.node.id,
+                    "node_type": hit.node.node_type,
+                    "name": hit.node.name,
+                    "path": path,
+                    "score": hit.relevance_score,
+                    "snippet": hit.node.content.chars().take(SNIPPET_CHARS).collect::<String>()
+                }))
+            })
+            .take(top_k)
+            .collect();
+
+        Ok(json!({
+            "success": true,
+            "query": params.query,
+            "count": results.len(),
+            "results": results
+        }))
+    }
+}
+
+/// Source path of a node: its `path` or `file` metadata, or the id of a file node
+pub fn node_path(node: &KnowledgeNode) -> Option<String> {
+    let metadata: Value = serde_json::from_str(&node.metadata).unwrap_or(Value::Null);
+    ["path", "file", "file_path"]
+        .iter()
+        .find_map(|key| metadata[*key].as_str().map(str::to_string))
+        .or_else(|| (node.node_type == "file").then(|| node.id.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zed42_memory::knowledge_graph::{KnowledgeEdge, KnowledgeNode};
+
+    #[tokio::test]
+    async fn test_analyze_impact_tool() {
+        let dir = tempfile::tempdir().unwrap();
+        let graph = Arc::new(KnowledgeGraphMemory::new(dir.path(), "impact", None).await.unwrap());
+        for (id, node_type) in [("lib.rs", "file"), ("helper", "function"), ("caller", "function")] {
+            graph
+                .insert_node(KnowledgeNode {
+                    id: id.to_string(),
+                    node_type: node_type.to_string(),
+                    name: id.to_string(),
+                    content: String::new(),
+                    embedding: None,
+                    metadata: "{}".to_string(),
+                    created_at: 0,
+                    updated_at: 0,
+                })
+                .await
+                .unwrap();
+        }
+        for (edge_type, from, to) in [("calls", "caller", "helper"), ("contains", "lib.rs", "caller")] {
+            graph
+                .insert_edge(KnowledgeEdge {
+                    id: format!("{}-{}", from, to),
+                    edge_type: edge_type.to_string(),
+                    from_id: from.to_string(),
+                    to_id: to.to_string(),
+                    metadata: None,
+                    created_at: 0,
+                })
+                .await
+                .unwrap();
+        }
+
+        let tool = AnalyzeImpact::new(graph);
+        let result = tool.execute(json!({ "node_id": "helper" })).await.unwrap();
+        assert_eq!(result["report"]["dependents"][0]["name"], "caller");
+        assert_eq!(result["report"]["affected_files"], json!(["lib.rs"]));
+        // No covering tests
+        assert_eq!(result["report"]["risk"], "medium");
+        assert!(result["summary"].as_str().unwrap().starts_with("Changing helper affects 1 dependent(s)"));
+    }
+
+    #[tokio::test]
+    async fn test_semantic_code_search_filters() {
+        let dir = tempfile::tempdir().unwrap();
+        let client: Arc<dyn zed42_llm::LlmClient> = Arc::new(zed42_llm::MockLlmClient::new(String::new()));
+        let graph = Arc::new(KnowledgeGraphMemory::new(dir.path(), "search", Some(client)).await.unwrap());
+        // The mock embeds every query as [0.1; 1536], so `near` vectors rank first
+        let near = vec![0.1; 1536];
+        let far: Vec<f32> = (0..1536).map(|i| if i % 2 == 0 { 1.0 } else { -1.0 }).collect();
+        for (id, node_type, path, embedding) in [
+            ("validate_token", "function", "crates/auth/src/token.rs", near.clone()),
+            ("token_tests", "test", "crates/auth/tests/token.rs", near.clone()),
+            ("render", "function", "crates/ui/src/view.rs", far),
+        ] {
+            graph
+                .insert_node(KnowledgeNode {
+                    id: id.to_string(),
+                    node_type: node_type.to_string(),
+                    name: id.to_string(),
+                    content: format!("fn {}() {{}}", id),
+                    embedding: Some(embedding),
+                    metadata: json!({ "path": path }).to_string(),
+                    created_at: 0,
+                    updated_at: 0,
+                })
+                .await
+                .unwrap();
+        }
+        graph.enable_ann_index(zed42_memory::knowledge_graph::AnnConfig::default()).await.unwrap();
+
+        let tool = SemanticCodeSearch::new(graph);
+        let result = tool
+            .execute(json!({ "query": "where do we validate auth tokens", "node_types": ["function"], "top_k": 1 }))
+            .await
+            .unwrap();
+        assert_eq!(result["results"][0]["id"], "validate_token");
+        assert_eq!(result["results"][0]["path"], "crates/auth/src/token.rs");
+
+        let result = tool
+            .execute(json!({ "query": "rendering", "path_prefix": "crates/ui/" }))
+            .await
+            .unwrap();
+        assert_eq!(result["count"], 1);
+        assert_eq!(result["results"][0]["name"], "render");
+
+        assert!(matches!(
+            tool.execute(json!({ "query": "x", "node_types": ["widget"] })).await,
+            Err(ToolError::InvalidParams(_))
+        ));
+    }
+}
@@ -0,0 +1,329 @@
+//! HTTP API testing tool
+//!
+//! [`HttpRequest`] lets a TestEngineer exercise a running service end to end:
+//! any method, headers and a text or JSON body, with the status, headers,
+//! body and latency returned for assertions. Requests only go to allowed
+//! hosts (local ones by default), redirects are followed only within the
+//! allow-list, and response bodies are cut at a size cap.
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use crate::{Tool, ToolError, ToolResult};
+
+/// Hosts reachable when no allow-list is configured
+pub const LOCAL_HOSTS: &[&str] = &["localhost", "127.0.0.1", "::1"];
+
+/// Default cap on response body bytes returned
+pub const DEFAULT_MAX_RESPONSE_BYTES: usize = 1024 * 1024;
+
+/// Default limit for one request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Redirects followed before giving up
+const MAX_REDIRECTS: usize = 5;
+
+/// Parameters for HttpRequest tool
+#[derive(Debug, Deserialize)]
+pub struct HttpRequestParams {
+    /// HTTP method (default GET)
+    #[serde(default = "default_method")]
+    pub method: String,
+    pub url: String,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// Raw request body
+    #[serde(default)]
+    pub body: Option<String>,
+    /// JSON request body; sets `Content-Type: application/json`
+    #[serde(default)]
+    pub json: Option<Value>,
+}
+
+fn default_method() -> String {
+    "GET".to_string()
+}
+
+/// Hosts requests may go to
+///
+/// Entries are host names or addresses; `*.example.com` also matches any
+/// subdomain of `example.com`.
+#[derive(Debug, Clone)]
+pub struct HostAllowList {
+    hosts: Vec<String>,
+}
+
+impl HostAllowList {
+    pub fn new(hosts: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            hosts: hosts.into_iter().map(|h| h.into().to_ascii_lowercase()).collect(),
+        }
+    }
+
+    /// Only the local machine
+    pub fn local() -> Self {
+        Self::new(LOCAL_HOSTS.iter().copied())
+    }
+
+    pub fn allows(&self, host: &str) -> bool {
+        let host = host.trim_start_matches('[').trim_end_matches(']').to_ascii_lowercase();
+        self.hosts.iter().any(|allowed| match allowed.strip_prefix("*.") {
+            Some(domain) => host == domain || host.ends_with(&format!(".{}", domain)),
+            None => host == *allowed,
+        })
+    }
+
+    /// Whether `url` is http(s) to an allowed host
+    pub fn allows_url(&self, url: &reqwest::Url) -> bool {
+        matches!(url.scheme(), "http" | "https") && url.host_str().is_some_and(|host| self.allows(host))
+    }
+}
+
+impl Default for HostAllowList {
+    fn default() -> Self {
+        Self::local()
+    }
+}
+
+/// HttpRequest tool - sends HTTP requests to allow-listed hosts
+pub struct HttpRequest {
+    client: reqwest::Client,
+    allow_list: Arc<HostAllowList>,
+    max_response_bytes: usize,
+}
+
+impl HttpRequest {
+    pub fn new() -> Self {
+        Self::with_allow_list(HostAllowList::local())
+    }
+
+    pub fn with_allow_list(allow_list: HostAllowList) -> Self {
+        let allow_list = Arc::new(allow_list);
+        let redirects = allow_list.clone();
+        let client = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::custom(move |attempt| {
+                if attempt.previous().len() >= MAX_REDIRECTS {
+                    attempt.error("too many redirects")
+                } else if redirects.allows_url(attempt.url()) {
+                    attempt.follow()
+                } else {
+                    // Hand the redirect itself back rather than leave the allow-list
+                    attempt.stop()
+                }
+            }))
+            .user_agent("zed42")
+            .build()
+            .expect("HTTP client configuration is valid");
+        Self {
+            client,
+            allow_list,
+            max_response_bytes: DEFAULT_MAX_RESPONSE_BYTES,
+        }
+    }
+
+    pub fn with_max_response_bytes(mut self, max_response_bytes: usize) -> Self {
+        self.max_response_bytes = max_response_bytes;
+        self
+    }
+}
+
+impl Default for HttpRequest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Tool for HttpRequest {
+    fn name(&self) -> &str {
+        "http_request"
+    }
+
+    fn description(&self) -> &str {
+        "Send an HTTP request to an allowed host (by default the local machine) and return the status, headers, body and latency"
+    }
+
+    fn default_timeout(&self) -> Duration {
+        REQUEST_TIMEOUT
+    }
+
+    fn parameter_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "method": {
+                    "type": "string",
+                    "description": "HTTP method (default GET)"
+                },
+                "url": {
+                    "type": "string",
+                    "description": "Full URL, e.g. 'http://localhost:8080/api/health'"
+                },
+                "headers": {
+                    "type": "object",
+                    "additionalProperties": { "type": "string" },
+                    "description": "Request headers"
+                },
+                "body": {
+                    "type": "string",
+                    "description": "Raw request body"
+                },
+                "json": {
+                    "description": "JSON request body (sets Content-Type: application/json)"
+                },
+                "timeout_secs": {
+                    "type": "number",
+                    "description": "Give up after this many seconds (optional, default 30)"
+                }
+            },
+            "required": ["url"]
+        })
+    }
+
+    async fn execute(&self, params: Value) -> ToolResult {
+        let params: HttpRequestParams = serde_json::from_value(params)
+            .map_err(ToolError::invalid_params)?;
+        let url = reqwest::Url::parse(&params.url)
+            .map_err(|e| ToolError::invalid_params(format!("Invalid URL '{}': {}", params.url, e)))?;
+        if !self.allow_list.allows_url(&url) {
+            return Err(ToolError::PolicyDenied {
+                rule: "http_allow_list".to_string(),
+                reason: format!("{} is not an allowed http(s) destination", url),
+            });
+        }
+        let method = reqwest::Method::from_bytes(params.method.to_ascii_uppercase().as_bytes())
+            .map_err(|_| ToolError::invalid_params(format!("Invalid HTTP method '{}'", params.method)))?;
+        if params.body.is_some() && params.json.is_some() {
+            return Err(ToolError::invalid_params("Give either body or json, not both"));
+        }
+
+        let mut request = self.client.request(method.clone(), url.clone());
+        for (name, value) in &params.headers {
+            request = request.header(name, value);
+        }
+        if let Some(body) = params.body {
+            request = request.body(body);
+        } else if let Some(ref body) = params.json {
+            request = request.json(body);
+        }
+
+        let started = Instant::now();
+        let mut response = request.send().await.map_err(|e| ToolError::External(format!("Request to {} failed: {}", url, e)))?;
+        let latency = started.elapsed();
+
+        let status = response.status();
+        let final_url = response.url().to_string();
+        let headers: BTreeMap<String, String> = response
+            .headers()
+            .iter()
+            .map(|(name, value)| (name.to_string(), String::from_utf8_lossy(value.as_bytes()).into_owned()))
+            .collect();
+
+        let mut body = Vec::new();
+        let mut truncated = false;
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| ToolError::External(format!("Failed to read response from {}: {}", url, e)))?
+        {
+            let room = self.max_response_bytes - body.len();
+            if chunk.len() > room {
+                body.extend_from_slice(&chunk[..room]);
+                truncated = true;
+                break;
+            }
+            body.extend_from_slice(&chunk);
+        }
+        let duration = started.elapsed();
+
+        let text = String::from_utf8_lossy(&body).into_owned();
+        let is_json = headers.get("content-type").is_some_and(|t| t.contains("json"));
+        let json_body = if is_json && !truncated { serde_json::from_str::<Value>(&text).ok() } else { None };
+
+        Ok(json!({
+            "method": method.as_str(),
+            "url": final_url,
+            "status": status.as_u16(),
+            "ok": status.is_success(),
+            "headers": headers,
+            "body": text,
+            "json": json_body,
+            "body_bytes": body.len(),
+            "truncated": truncated,
+            "latency_ms": latency.as_millis() as u64,
+            "duration_ms": duration.as_millis() as u64
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Serve `response` verbatim to each connection; returns the base URL
+    async fn serve(response: String) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let response = response.clone();
+                tokio::spawn(async move {
+                    let mut buf = [0u8; 4096];
+                    let _ = socket.read(&mut buf).await;
+                    let _ = socket.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_request_to_local_service() {
+        let body = r#"{"status":"healthy"}"#;
+        let base = serve(format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        ))
+        .await;
+        let tool = HttpRequest::new();
+
+        let result = tool
+            .execute(json!({ "method": "post", "url": format!("{}/health", base), "json": { "probe": true } }))
+            .await
+            .unwrap();
+        assert_eq!(result["status"], 200);
+        assert_eq!(result["method"], "POST");
+        assert_eq!(result["json"]["status"], "healthy");
+        assert_eq!(result["truncated"], false);
+        assert!(result["latency_ms"].is_u64());
+
+        let capped = HttpRequest::new().with_max_response_bytes(5);
+        let result = capped.execute(json!({ "url": base })).await.unwrap();
+        assert_eq!(result["body"], r#"{"sta"#);
+        assert_eq!(result["truncated"], true);
+        assert!(result["json"].is_null());
+    }
+
+    #[tokio::test]
+    async fn test_allow_list() {
+        let tool = HttpRequest::new();
+        let err = tool.execute(json!({ "url": "https://example.com/" })).await.unwrap_err();
+        assert!(matches!(err, ToolError::PolicyDenied { ref rule, .. } if rule == "http_allow_list"));
+        let err = tool.execute(json!({ "url": "file:///etc/passwd" })).await.unwrap_err();
+        assert!(matches!(err, ToolError::PolicyDenied { .. }));
+
+        let list = HostAllowList::new(["*.internal.test", "api.example.com"]);
+        assert!(list.allows("svc.internal.test"));
+        assert!(list.allows("internal.test"));
+        assert!(list.allows("API.example.com"));
+        assert!(!list.allows("evilinternal.test"));
+        assert!(!list.allows("example.com"));
+        assert!(HostAllowList::local().allows("[::1]"));
+    }
+}
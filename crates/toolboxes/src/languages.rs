@@ -0,0 +1,315 @@
+//! Language detection, source indexing and build/test adapters
+//!
+//! Files are classified by extension. Each supported language has a
+//! tree-sitter grammar used to index functions, types and modules, plus the
+//! commands that build and test a project written in it (`cargo test`,
+//! `npm test`, `pytest`, `go test`). The detected language is also rendered
+//! as a short prompt hint so agents write idiomatic code for the file.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Languages ZED42 can index and build
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    Rust,
+    TypeScript,
+    JavaScript,
+    Python,
+    Go,
+}
+
+impl Language {
+    /// Language of a file, from its extension
+    pub fn detect(path: impl AsRef<Path>) -> Option<Self> {
+        let ext = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "rs" => Some(Language::Rust),
+            "ts" | "tsx" | "mts" | "cts" => Some(Language::TypeScript),
+            "js" | "jsx" | "mjs" | "cjs" => Some(Language::JavaScript),
+            "py" | "pyi" => Some(Language::Python),
+            "go" => Some(Language::Go),
+            _ => None,
+        }
+    }
+
+    /// First language named by a file path mentioned in `text` (e.g. a task description)
+    pub fn detect_in_text(text: &str) -> Option<Self> {
+        text.split(|c: char| c.is_whitespace() || matches!(c, '`' | '\'' | '"' | ',' | '(' | ')'))
+            .map(|word| word.trim_end_matches(['.', ':', ';']))
+            .filter(|word| word.contains('.'))
+            .find_map(Self::detect)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::Rust => "Rust",
+            Language::TypeScript => "TypeScript",
+            Language::JavaScript => "JavaScript",
+            Language::Python => "Python",
+            Language::Go => "Go",
+        }
+    }
+
+    /// Markdown code fence tag
+    pub fn fence(&self) -> &'static str {
+        match self {
+            Language::Rust => "rust",
+            Language::TypeScript => "typescript",
+            Language::JavaScript => "javascript",
+            Language::Python => "python",
+            Language::Go => "go",
+        }
+    }
+
+    /// Command that builds (or type-checks) a project in this language
+    pub fn build_command(&self) -> Vec<String> {
+        argv(match self {
+            Language::Rust => &["cargo", "build"],
+            Language::TypeScript | Language::JavaScript => &["npm", "run", "build"],
+            Language::Python => &["python", "-m", "compileall", "-q", "."],
+            Language::Go => &["go", "build", "./..."],
+        })
+    }
+
+    /// Command that runs the project's tests
+    pub fn test_command(&self) -> Vec<String> {
+        argv(match self {
+            Language::Rust => &["cargo", "test"],
+            Language::TypeScript | Language::JavaScript => &["npm", "test"],
+            Language::Python => &["pytest"],
+            Language::Go => &["go", "test", "./..."],
+        })
+    }
+
+    /// Prompt hint naming the language and its conventions
+    pub fn prompt_hint(&self) -> String {
+        let conventions = match self {
+            Language::Rust => "Return Result instead of panicking; tests go in a #[cfg(test)] module.",
+            Language::TypeScript => "Use strict types and avoid `any`; tests run with `npm test`.",
+            Language::JavaScript => "Use modern ES modules and const/let; tests run with `npm test`.",
+            Language::Python => "Follow PEP 8 with type hints; tests are pytest functions named test_*.",
+            Language::Go => "Return errors instead of panicking; tests are TestXxx functions in _test.go files.",
+        };
+        format!("Language: {}. {}", self.name(), conventions)
+    }
+
+    fn grammar(&self, path: &Path) -> tree_sitter::Language {
+        match self {
+            Language::Rust => tree_sitter_rust::language(),
+            Language::TypeScript if path.extension().is_some_and(|e| e == "tsx") => {
+                tree_sitter_typescript::language_tsx()
+            }
+            Language::TypeScript => tree_sitter_typescript::language_typescript(),
+            Language::JavaScript => tree_sitter_javascript::language(),
+            Language::Python => tree_sitter_python::language(),
+            Language::Go => tree_sitter_go::language(),
+        }
+    }
+
+    fn symbol_kind(&self, node_kind: &str) -> Option<SymbolKind> {
+        use SymbolKind::*;
+        match (self, node_kind) {
+            (Language::Rust, "function_item") => Some(Function),
+            (Language::Rust, "struct_item" | "enum_item" | "trait_item" | "type_item") => Some(Type),
+            (Language::Rust, "mod_item") => Some(Module),
+            (
+                Language::TypeScript | Language::JavaScript,
+                "function_declaration" | "generator_function_declaration" | "method_definition",
+            ) => Some(Function),
+            (
+                Language::TypeScript | Language::JavaScript,
+                "class_declaration" | "interface_declaration" | "type_alias_declaration" | "enum_declaration",
+            ) => Some(Type),
+            (Language::TypeScript, "internal_module") => Some(Module),
+            (Language::Python, "function_definition") => Some(Function),
+            (Language::Python, "class_definition") => Some(Type),
+            (Language::Go, "function_declaration" | "method_declaration") => Some(Function),
+            (Language::Go, "type_spec") => Some(Type),
+            _ => None,
+        }
+    }
+}
+
+fn argv(args: &[&str]) -> Vec<String> {
+    args.iter().map(|a| a.to_string()).collect()
+}
+
+/// Kind of indexed symbol
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SymbolKind {
+    Function,
+    /// Struct, enum, class, interface, trait or type alias
+    Type,
+    Module,
+}
+
+/// A definition found in a source file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    /// 1-based line of the definition
+    pub line: usize,
+}
+
+/// Index of one source file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileIndex {
+    pub path: PathBuf,
+    pub language: Language,
+    pub symbols: Vec<Symbol>,
+}
+
+/// Parse `source` and list its definitions; `None` for unsupported files
+pub fn index_source(path: impl AsRef<Path>, source: &str) -> Result<Option<FileIndex>> {
+    let path = path.as_ref();
+    let Some(language) = Language::detect(path) else {
+        return Ok(None);
+    };
+
+    let mut parser = tree_sitter::Parser::new();
+    parser
+        .set_language(language.grammar(path))
+        .map_err(|e| anyhow!("Failed to load {} grammar: {}", language.name(), e))?;
+    let tree = parser
+        .parse(source, None)
+        .ok_or_else(|| anyhow!("Failed to parse {}", path.display()))?;
+
+    let mut symbols = Vec::new();
+    collect_symbols(language, tree.root_node(), source.as_bytes(), &mut symbols);
+    Ok(Some(FileIndex {
+        path: path.to_path_buf(),
+        language,
+        symbols,
+    }))
+}
+
+fn collect_symbols(language: Language, node: tree_sitter::Node, source: &[u8], out: &mut Vec<Symbol>) {
+    if let Some(kind) = language.symbol_kind(node.kind()) {
+        if let Some(name) = node.child_by_field_name("name").and_then(|n| n.utf8_text(source).ok()) {
+            out.push(Symbol {
+                name: name.to_string(),
+                kind,
+                line: node.start_position().row + 1,
+            });
+        }
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_symbols(language, child, source, out);
+    }
+}
+
+/// Index every supported file under `root`, skipping ignored paths, build output and dependencies
+pub fn index_tree(root: &Path) -> Result<Vec<FileIndex>> {
+    let mut files = Vec::new();
+    for path in crate::walk::files(root, false) {
+        if Language::detect(&path).is_none() {
+            continue;
+        }
+        let source = match std::fs::read_to_string(&path) {
+            Ok(source) => source,
+            // Binary or non-UTF-8 files are not indexed
+            Err(_) => continue,
+        };
+        let relative = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+        if let Some(index) = index_source(&relative, &source)? {
+            files.push(index);
+        }
+    }
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
+}
+
+/// Languages of the indexed files, most files first
+pub fn detect_languages(files: &[FileIndex]) -> Vec<(Language, usize)> {
+    let mut counts: Vec<(Language, usize)> = Vec::new();
+    for file in files {
+        match counts.iter_mut().find(|(language, _)| *language == file.language) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((file.language, 1)),
+        }
+    }
+    counts.sort_by(|a, b| b.1.cmp(&a.1));
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(index: &FileIndex) -> Vec<(&str, SymbolKind)> {
+        index.symbols.iter().map(|s| (s.name.as_str(), s.kind)).collect()
+    }
+
+    #[test]
+    fn test_detection_and_adapters() {
+        assert_eq!(Language::detect("src/app.tsx"), Some(Language::TypeScript));
+        assert_eq!(Language::detect("pkg/mod.py"), Some(Language::Python));
+        assert_eq!(Language::detect("README.md"), None);
+        assert_eq!(
+            Language::detect_in_text("Add retries to `client/http.py`."),
+            Some(Language::Python)
+        );
+        assert_eq!(Language::detect_in_text("Use e.g. a cache"), None);
+        assert_eq!(Language::Python.test_command(), vec!["pytest"]);
+        assert_eq!(Language::TypeScript.test_command(), vec!["npm", "test"]);
+    }
+
+    #[test]
+    fn test_indexes_typescript_and_python() {
+        let ts = "export interface User { id: number }\n\nexport class Store {\n  get(id: number): User | undefined { return undefined; }\n}\n\nfunction load(): void {}\n";
+        let index = index_source("src/store.ts", ts).unwrap().unwrap();
+        assert_eq!(
+            names(&index),
+            vec![
+                ("User", SymbolKind::Type),
+                ("Store", SymbolKind::Type),
+                ("get", SymbolKind::Function),
+                ("load", SymbolKind::Function),
+            ]
+        );
+
+        let py = "class Parser:\n    def parse(self, text):\n        return text\n\ndef main():\n    pass\n";
+        let index = index_source("tool/parser.py", py).unwrap().unwrap();
+        assert_eq!(index.language, Language::Python);
+        assert_eq!(
+            names(&index),
+            vec![
+                ("Parser", SymbolKind::Type),
+                ("parse", SymbolKind::Function),
+                ("main", SymbolKind::Function),
+            ]
+        );
+        assert_eq!(index.symbols[2].line, 5);
+
+        assert!(index_source("notes.txt", "hello").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_index_tree_skips_dependencies() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for (path, content) in [
+            ("src/lib.rs", "pub fn add() {}\n"),
+            ("web/index.ts", "export function render() {}\n"),
+            ("web/util.ts", "export const x = 1;\n"),
+            ("web/node_modules/dep/index.js", "function vendored() {}\n"),
+            ("web/.gitignore", "gen/\n"),
+            ("web/gen/api.ts", "export function generated() {}\n"),
+        ] {
+            let path = root.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+
+        let files = index_tree(root).unwrap();
+        let paths: Vec<&Path> = files.iter().map(|f| f.path.as_path()).collect();
+        assert_eq!(paths, vec![Path::new("src/lib.rs"), Path::new("web/index.ts"), Path::new("web/util.ts")]);
+        assert_eq!(detect_languages(&files), vec![(Language::TypeScript, 2), (Language::Rust, 1)]);
+    }
+}
@@ -14,14 +14,41 @@ pub mod code;
 pub mod analysis;
 pub mod testing;
 pub mod vcs;
+pub mod commit;
 pub mod external;
 pub mod visualization;
 pub mod file_manipulation;
 pub mod shell;
+pub mod platform;
 pub mod fs_guard;
+pub mod diff_guard;
+pub mod walk;
+pub mod policy;
+pub mod standards;
+pub mod languages;
+pub mod graph;
+pub mod docsite;
+pub mod injection;
+pub mod guardrails;
+pub mod timeout;
+pub mod error;
+pub mod executor;
+pub mod summarize;
+pub mod repl;
+pub mod database;
+pub mod http;
+pub mod approval;
+#[cfg(feature = "browser")]
+pub mod browser;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+pub use approval::{ApprovalNeed, Approver};
+pub use error::ToolError;
+pub use executor::{ToolCall, ToolExecutor};
 
 /// Tool execution result
-pub type ToolResult = anyhow::Result<serde_json::Value>;
+pub type ToolResult = std::result::Result<serde_json::Value, ToolError>;
 
 /// Base trait for all tools
 #[async_trait]
@@ -37,6 +64,21 @@ pub trait Tool: Send + Sync {
 
     /// Execute the tool with given parameters
     async fn execute(&self, params: serde_json::Value) -> ToolResult;
+
+    /// How long a call may run before [`timeout::run_tool`] gives up on it
+    fn default_timeout(&self) -> std::time::Duration {
+        timeout::DEFAULT_TOOL_TIMEOUT
+    }
+
+    /// Whether this call needs a human's approval under the `dangerous_tools` gate
+    fn approval_needed(&self, _params: &serde_json::Value) -> Option<ApprovalNeed> {
+        None
+    }
+
+    /// What the call would change, shown with its approval request (e.g. a diff)
+    async fn approval_preview(&self, _params: &serde_json::Value) -> Option<String> {
+        None
+    }
 }
 
 /// Toolbox containing a set of tools
@@ -80,6 +122,8 @@ impl ToolboxRegistry {
                 "move_file".to_string(),
                 "delete_file".to_string(),
                 "list_dir".to_string(),
+                "read_many".to_string(),
+                "stat_files".to_string(),
             ],
         });
 
@@ -91,6 +135,13 @@ impl ToolboxRegistry {
             ],
         });
 
+        self.register(Toolbox {
+            name: "CodeEvaluation".to_string(),
+            tools: vec![
+                "evaluate_code".to_string(),
+            ],
+        });
+
         self.register(Toolbox {
             name: "Refactoring".to_string(),
             tools: vec![
@@ -111,6 +162,20 @@ impl ToolboxRegistry {
             ],
         });
 
+        self.register(Toolbox {
+            name: "PolicyEnforcement".to_string(),
+            tools: vec![
+                "check_policy".to_string(),
+            ],
+        });
+
+        self.register(Toolbox {
+            name: "InjectionScreening".to_string(),
+            tools: vec![
+                "review_quarantine".to_string(),
+            ],
+        });
+
         self.register(Toolbox {
             name: "DependencyScanning".to_string(),
             tools: vec![
@@ -134,7 +199,9 @@ impl ToolboxRegistry {
             tools: vec![
                 "traverse_dependencies".to_string(),
                 "analyze_impact".to_string(),
+                "semantic_code_search".to_string(),
                 "find_patterns".to_string(),
+                "workspace_packages".to_string(),
             ],
         });
 
@@ -148,6 +215,31 @@ impl ToolboxRegistry {
             ],
         });
 
+        self.register(Toolbox {
+            name: "ApiTesting".to_string(),
+            tools: vec![
+                "http_request".to_string(),
+            ],
+        });
+
+        #[cfg(feature = "browser")]
+        self.register(Toolbox {
+            name: "BrowserAutomation".to_string(),
+            tools: vec![
+                "browser_navigate".to_string(),
+                "browser_screenshot".to_string(),
+                "browser_assert_dom".to_string(),
+            ],
+        });
+
+        #[cfg(feature = "wasm")]
+        self.register(Toolbox {
+            name: "WasmSandbox".to_string(),
+            tools: vec![
+                "run_wasm".to_string(),
+            ],
+        });
+
         self.register(Toolbox {
             name: "Fuzzing".to_string(),
             tools: vec![
@@ -186,6 +278,20 @@ impl ToolboxRegistry {
         });
 
         // External Integration Toolboxes
+        self.register(Toolbox {
+            name: "Forge".to_string(),
+            tools: vec![
+                "create_pull_request".to_string(),
+            ],
+        });
+
+        self.register(Toolbox {
+            name: "DatabaseQuery".to_string(),
+            tools: vec![
+                "query_database".to_string(),
+            ],
+        });
+
         self.register(Toolbox {
             name: "BuildSystem".to_string(),
             tools: vec![
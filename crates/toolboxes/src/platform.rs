@@ -0,0 +1,360 @@
+//! Platform abstraction for paths and processes
+//!
+//! Tools run on Windows, macOS and Linux, which disagree in a few places
+//! that matter to a sandbox:
+//!
+//! - `echo`, `dir`, `type` and friends are `cmd.exe` builtins on Windows, so
+//!   `Command::new("echo")` fails there. [`command`] routes them through the
+//!   platform shell.
+//! - `Permissions::set_readonly(false)` makes a file world-writable on Unix.
+//!   [`set_readonly`] only grants the owner write access back.
+//! - `canonicalize` fails for paths that don't exist yet. [`resolve`]
+//!   canonicalizes the deepest existing ancestor and appends the rest.
+//! - On macOS the temp dir sits behind the `/var` → `/private/var` symlink,
+//!   so a sandbox root has to be resolved the same way as the paths checked
+//!   against it ([`is_within`]).
+//! - On Windows `canonicalize` returns verbatim paths (`\\?\C:\...`,
+//!   `\\?\UNC\server\share\...`) that never `starts_with` a plain root.
+//!   [`simplify`] drops the prefix when the plain form means the same thing,
+//!   and [`is_within`] compares both sides without it, case-insensitively.
+//!   [`extended_length`] adds the prefix back for paths past `MAX_PATH`.
+//!
+//! Everything here behaves the same on all three platforms unless the doc
+//! comment says otherwise, and the tests run unchanged on each of them.
+
+use std::ffi::OsString;
+use std::io;
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+/// Commands that only exist as `cmd.exe` builtins on Windows
+const WINDOWS_BUILTINS: &[&str] = &[
+    "echo", "dir", "type", "copy", "del", "erase", "move", "ren", "rename", "mkdir", "md", "rmdir", "rd", "set",
+    "ver", "vol", "cls", "mklink",
+];
+
+/// Longest path Win32 APIs accept without the `\\?\` prefix
+pub const MAX_PATH: usize = 260;
+
+/// Operating system family the tools are running on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Platform {
+    Windows,
+    MacOs,
+    Linux,
+    /// BSDs and other Unix-likes
+    OtherUnix,
+}
+
+impl Platform {
+    pub fn current() -> Self {
+        if cfg!(windows) {
+            Self::Windows
+        } else if cfg!(target_os = "macos") {
+            Self::MacOs
+        } else if cfg!(target_os = "linux") {
+            Self::Linux
+        } else {
+            Self::OtherUnix
+        }
+    }
+
+    pub fn is_windows(self) -> bool {
+        self == Self::Windows
+    }
+
+    /// Shell and the flag that makes it run one command line
+    pub fn shell(self) -> (&'static str, &'static str) {
+        if self.is_windows() {
+            ("cmd", "/C")
+        } else {
+            ("sh", "-c")
+        }
+    }
+
+    /// Whether `program` only exists inside the shell on this platform
+    pub fn is_shell_builtin(self, program: &str) -> bool {
+        self.is_windows() && WINDOWS_BUILTINS.iter().any(|b| b.eq_ignore_ascii_case(program))
+    }
+
+    /// `name` with the platform's executable suffix (`.exe` on Windows)
+    pub fn executable_name(self, name: &str) -> String {
+        if self.is_windows() && Path::new(name).extension().is_none() {
+            format!("{}.exe", name)
+        } else {
+            name.to_string()
+        }
+    }
+}
+
+/// Build a command for `program`, going through the shell for builtins
+///
+/// Arguments are passed as-is; quoting only happens when `cmd.exe` has to
+/// run a builtin, and then it follows the standard Windows rules.
+pub fn command(program: &str, args: &[String]) -> Command {
+    let platform = Platform::current();
+    if platform.is_shell_builtin(program) {
+        let (shell, flag) = platform.shell();
+        let mut cmd = Command::new(shell);
+        cmd.arg(flag).arg(program).args(args);
+        cmd
+    } else {
+        let mut cmd = Command::new(program);
+        cmd.args(args);
+        cmd
+    }
+}
+
+/// Run `script` through the platform shell (`sh -c` or `cmd /C`)
+pub fn shell_command(script: &str) -> Command {
+    let (shell, flag) = Platform::current().shell();
+    let mut cmd = Command::new(shell);
+    cmd.arg(flag).arg(script);
+    cmd
+}
+
+/// Canonicalize `path`, allowing the trailing components not to exist yet
+///
+/// The deepest existing ancestor is canonicalized and the missing
+/// components are appended unchanged. Missing components may not be `..`,
+/// since there is nothing on disk to resolve them against. The result is
+/// [`simplify`]d.
+pub fn resolve(path: &Path) -> io::Result<PathBuf> {
+    let mut existing = path;
+    let mut missing: Vec<OsString> = Vec::new();
+    loop {
+        match existing.canonicalize() {
+            Ok(mut resolved) => {
+                resolved.extend(missing.iter().rev());
+                return Ok(simplify(&resolved));
+            }
+            Err(e) => match (existing.parent(), existing.file_name()) {
+                (Some(parent), Some(name)) => {
+                    missing.push(name.to_os_string());
+                    existing = if parent.as_os_str().is_empty() { Path::new(".") } else { parent };
+                }
+                _ => return Err(e),
+            },
+        }
+    }
+}
+
+/// Whether `path` resolves to `root` or somewhere below it
+///
+/// Both sides go through [`resolve`], so symlinked roots (macOS temp dirs)
+/// and paths that don't exist yet compare correctly, and through
+/// [`starts_with`], so verbatim and plain Windows paths do too.
+/// Unresolvable paths are never within the root.
+pub fn is_within(path: &Path, root: &Path) -> bool {
+    match (resolve(path), resolve(root)) {
+        (Ok(path), Ok(root)) => starts_with(&path, &root),
+        _ => false,
+    }
+}
+
+/// `Path::starts_with` that ignores how Windows spelled either side
+///
+/// On Windows the `\\?\` prefix is dropped, separators are unified and the
+/// comparison is case-insensitive. Elsewhere this is plain `starts_with`.
+pub fn starts_with(path: &Path, root: &Path) -> bool {
+    if Platform::current().is_windows() {
+        match (path.to_str(), root.to_str()) {
+            (Some(path), Some(root)) => Path::new(&comparable(path)).starts_with(comparable(root)),
+            _ => path.starts_with(root),
+        }
+    } else {
+        path.starts_with(root)
+    }
+}
+
+/// Drop the verbatim prefix `canonicalize` puts on Windows paths
+///
+/// Like the `dunce` crate: the prefix is only removed when the plain path
+/// means the same thing (shorter than [`MAX_PATH`], no reserved device
+/// names, no trailing dots or spaces). Other paths are returned unchanged,
+/// as is everything on non-Windows platforms.
+pub fn simplify(path: &Path) -> PathBuf {
+    if !Platform::current().is_windows() {
+        return path.to_path_buf();
+    }
+    match path.to_str().and_then(simplify_verbatim) {
+        Some(plain) => PathBuf::from(plain),
+        None => path.to_path_buf(),
+    }
+}
+
+/// Add the verbatim prefix to absolute Windows paths past [`MAX_PATH`]
+///
+/// Use this right before handing a long path to the OS. Other paths, and
+/// everything on non-Windows platforms, are returned unchanged.
+pub fn extended_length(path: &Path) -> PathBuf {
+    if !Platform::current().is_windows() {
+        return path.to_path_buf();
+    }
+    match path.to_str() {
+        Some(s) if s.len() >= MAX_PATH => to_verbatim(s).map(PathBuf::from).unwrap_or_else(|| path.to_path_buf()),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// `\\?\C:\x` → `C:\x` and `\\?\UNC\srv\share\x` → `\\srv\share\x`
+fn strip_verbatim(path: &str) -> Option<String> {
+    if let Some(rest) = path.strip_prefix(r"\\?\UNC\") {
+        return Some(format!(r"\\{}", rest));
+    }
+    let rest = path.strip_prefix(r"\\?\")?;
+    let bytes = rest.as_bytes();
+    (bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':').then(|| rest.to_string())
+}
+
+/// [`strip_verbatim`], if the plain path means the same thing
+fn simplify_verbatim(path: &str) -> Option<String> {
+    let plain = strip_verbatim(path)?;
+    let safe = plain.len() < MAX_PATH
+        && !plain.contains('/')
+        && plain.split('\\').skip(1).filter(|c| !c.is_empty()).all(is_plain_component);
+    safe.then_some(plain)
+}
+
+/// Whether a path component survives Win32 path normalization unchanged
+fn is_plain_component(component: &str) -> bool {
+    if component.ends_with('.') || component.ends_with(' ') {
+        return false;
+    }
+    if component.chars().any(|c| matches!(c, '<' | '>' | ':' | '"' | '|' | '?' | '*') || c < ' ') {
+        return false;
+    }
+    let stem = component.split('.').next().unwrap_or_default().to_ascii_uppercase();
+    let numbered_device = stem.len() == 4
+        && (stem.starts_with("COM") || stem.starts_with("LPT"))
+        && stem.as_bytes()[3].is_ascii_digit();
+    !numbered_device && !matches!(stem.as_str(), "CON" | "PRN" | "AUX" | "NUL")
+}
+
+/// `C:\x` → `\\?\C:\x` and `\\srv\share\x` → `\\?\UNC\srv\share\x`
+fn to_verbatim(path: &str) -> Option<String> {
+    let path = path.replace('/', "\\");
+    if path.starts_with(r"\\?\") {
+        return Some(path);
+    }
+    if let Some(unc) = path.strip_prefix(r"\\") {
+        return Some(format!(r"\\?\UNC\{}", unc));
+    }
+    let bytes = path.as_bytes();
+    (bytes.len() >= 3 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' && bytes[2] == b'\\')
+        .then(|| format!(r"\\?\{}", path))
+}
+
+/// Spelling of a Windows path used for prefix comparisons
+fn comparable(path: &str) -> String {
+    let path = path.replace('/', "\\");
+    strip_verbatim(&path).unwrap_or(path).to_lowercase()
+}
+
+/// Make `path` read-only, or writable by its owner again
+///
+/// On Unix this clears every write bit, or sets only the owner's, instead of
+/// the world-writable result of `Permissions::set_readonly(false)`. On
+/// Windows it toggles the read-only attribute.
+pub fn set_readonly(path: &Path, readonly: bool) -> io::Result<()> {
+    let mut permissions = std::fs::metadata(path)?.permissions();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = permissions.mode();
+        permissions.set_mode(if readonly { mode & !0o222 } else { mode | 0o200 });
+    }
+    #[cfg(not(unix))]
+    permissions.set_readonly(readonly);
+    std::fs::set_permissions(path, permissions)
+}
+
+/// Whether the file behind `metadata` is read-only for its owner
+///
+/// `Permissions::readonly` is only true on Unix when nobody can write, so
+/// this checks the owner's write bit there instead.
+pub fn is_readonly(metadata: &std::fs::Metadata) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        metadata.permissions().mode() & 0o200 == 0
+    }
+    #[cfg(not(unix))]
+    {
+        metadata.permissions().readonly()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_resolve_missing_components() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+
+        let resolved = resolve(&dir.path().join("new").join("file.txt")).unwrap();
+        assert_eq!(resolved, root.join("new").join("file.txt"));
+        assert!(is_within(&dir.path().join("new"), dir.path()));
+        assert!(!is_within(&std::env::temp_dir(), &dir.path().join("new")));
+        assert!(resolve(&dir.path().join("missing").join("..").join("x")).is_err());
+    }
+
+    #[test]
+    fn test_verbatim_prefixes() {
+        assert_eq!(strip_verbatim(r"\\?\C:\work\repo").unwrap(), r"C:\work\repo");
+        assert_eq!(strip_verbatim(r"\\?\UNC\server\share\repo").unwrap(), r"\\server\share\repo");
+        assert!(strip_verbatim(r"\\?\Volume{1234}\repo").is_none());
+        assert!(strip_verbatim(r"C:\work").is_none());
+
+        assert_eq!(simplify_verbatim(r"\\?\C:\work\repo").unwrap(), r"C:\work\repo");
+        assert!(simplify_verbatim(r"\\?\C:\work\con.txt").is_none());
+        assert!(simplify_verbatim(r"\\?\C:\work\trailing.").is_none());
+        assert!(simplify_verbatim(&format!(r"\\?\C:\{}", "a".repeat(MAX_PATH))).is_none());
+
+        assert_eq!(to_verbatim("C:/work/repo").unwrap(), r"\\?\C:\work\repo");
+        assert_eq!(to_verbatim(r"\\server\share\repo").unwrap(), r"\\?\UNC\server\share\repo");
+        assert!(to_verbatim(r"relative\path").is_none());
+
+        // Canonical (verbatim) paths match the plain sandbox root they were configured with
+        assert_eq!(comparable(r"\\?\C:\Work\Repo\src"), comparable(r"c:/work/repo/src"));
+        assert!(comparable(r"\\?\UNC\srv\share\repo\a.rs").starts_with(&comparable(r"\\SRV\share\repo")));
+    }
+
+    #[test]
+    fn test_readonly_round_trip() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("locked.txt");
+        std::fs::write(&file, "v1").unwrap();
+
+        set_readonly(&file, true).unwrap();
+        assert!(is_readonly(&std::fs::metadata(&file).unwrap()));
+
+        set_readonly(&file, false).unwrap();
+        assert!(!is_readonly(&std::fs::metadata(&file).unwrap()));
+        std::fs::write(&file, "v2").unwrap();
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "v2");
+    }
+
+    #[tokio::test]
+    async fn test_echo_runs_on_every_platform() {
+        let output = command("echo", &["hello".to_string()]).output().await.unwrap();
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "hello");
+
+        let output = shell_command("echo scripted").output().await.unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "scripted");
+    }
+
+    #[test]
+    fn test_platform_names() {
+        let platform = Platform::current();
+        assert_eq!(platform.is_shell_builtin("ECHO"), platform.is_windows());
+        assert!(!platform.is_shell_builtin("cargo"));
+        assert_eq!(platform.executable_name("tool.sh"), "tool.sh");
+        let exe = platform.executable_name("cargo");
+        assert_eq!(exe.ends_with(".exe"), platform.is_windows());
+    }
+}
@@ -0,0 +1,503 @@
+//! Project policy enforcement for generated files
+//!
+//! The StandardsEnforcer checks generated files against a configurable policy
+//! (required license headers, forbidden dependencies, banned APIs). Violations
+//! are structured so they can be fed back to the authoring agent, and any
+//! violation blocks artifact approval until it is fixed. Artifacts scored
+//! below the policy's minimum confidence are held the same way.
+//!
+//! A project [`StandardsProfile`] (`.zed42/standards.toml`) layers naming,
+//! documentation and error-handling rules on top of the policy.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use zed42_core::types::Confidence;
+use zed42_core::Artifact;
+use crate::file_manipulation::PathSanitizer;
+use crate::standards::StandardsProfile;
+use crate::{Tool, ToolError, ToolResult};
+
+/// Policy loaded from a TOML file (see `config/policy.toml`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PolicyConfig {
+    /// Required license header, if any
+    #[serde(default)]
+    pub license_header: Option<LicenseHeaderRule>,
+    /// Dependency names that must not appear in manifests or imports
+    #[serde(default)]
+    pub forbidden_dependencies: Vec<String>,
+    /// API usages that are not allowed in generated code
+    #[serde(default)]
+    pub banned_apis: Vec<BannedApi>,
+    /// Artifacts whose confidence is below this are not approved; unscored artifacts pass
+    #[serde(default)]
+    pub min_confidence: Option<Confidence>,
+}
+
+/// License header requirement
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LicenseHeaderRule {
+    /// Text that must appear near the top of the file
+    pub text: String,
+    /// File extensions the rule applies to (empty = all files)
+    #[serde(default)]
+    pub extensions: Vec<String>,
+    /// Number of leading lines searched for the header
+    #[serde(default = "default_scan_lines")]
+    pub scan_lines: usize,
+}
+
+fn default_scan_lines() -> usize {
+    10
+}
+
+/// A banned API pattern
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BannedApi {
+    /// Substring that identifies the banned usage (e.g. `.unwrap()`)
+    pub pattern: String,
+    /// Why the API is banned, surfaced to the agent
+    pub reason: String,
+    /// File extensions the rule applies to (empty = all files)
+    #[serde(default)]
+    pub extensions: Vec<String>,
+}
+
+impl PolicyConfig {
+    /// Parse a policy from TOML text
+    pub fn from_toml_str(content: &str) -> anyhow::Result<Self> {
+        toml::from_str(content).map_err(|e| anyhow::anyhow!("Invalid policy file: {}", e))
+    }
+
+    /// Load a policy from a TOML file
+    pub fn from_file(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path.as_ref())
+            .map_err(|e| anyhow::anyhow!("Failed to read policy file: {}", e))?;
+        Self::from_toml_str(&content)
+    }
+}
+
+/// Category of policy violation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ViolationKind {
+    MissingLicenseHeader,
+    ForbiddenDependency,
+    BannedApi,
+    LowConfidence,
+    NamingConvention,
+    MissingDocs,
+}
+
+/// A single policy violation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicyViolation {
+    pub kind: ViolationKind,
+    pub file: String,
+    /// 1-based line number, when the violation maps to a line
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl std::fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}: {}", self.file, line, self.message),
+            None => write!(f, "{}: {}", self.file, self.message),
+        }
+    }
+}
+
+/// Checks file contents against a [`PolicyConfig`]
+#[derive(Debug, Clone, Default)]
+pub struct PolicyEnforcer {
+    config: PolicyConfig,
+    standards: Option<StandardsProfile>,
+}
+
+impl PolicyEnforcer {
+    pub fn new(config: PolicyConfig) -> Self {
+        Self { config, standards: None }
+    }
+
+    /// Apply a project standards profile on top of the policy
+    pub fn with_standards(mut self, standards: StandardsProfile) -> Self {
+        for name in &standards.forbidden_crates {
+            if !self.config.forbidden_dependencies.contains(name) {
+                self.config.forbidden_dependencies.push(name.clone());
+            }
+        }
+        self.config.banned_apis.extend(standards.banned_apis());
+        self.standards = Some(standards);
+        self
+    }
+
+    pub fn config(&self) -> &PolicyConfig {
+        &self.config
+    }
+
+    pub fn standards(&self) -> Option<&StandardsProfile> {
+        self.standards.as_ref()
+    }
+
+    /// Check a single file and return all violations found
+    pub fn check_file(&self, path: &str, content: &str) -> Vec<PolicyViolation> {
+        let mut violations = Vec::new();
+        self.check_license_header(path, content, &mut violations);
+        self.check_dependencies(path, content, &mut violations);
+        self.check_banned_apis(path, content, &mut violations);
+        if let Some(standards) = &self.standards {
+            violations.extend(standards.check_source(path, content));
+        }
+        violations
+    }
+
+    /// Check a generated artifact; artifacts without a path are checked against every rule
+    pub fn check_artifact(&self, artifact: &Artifact) -> Vec<PolicyViolation> {
+        let path = artifact.file_path.clone().unwrap_or_else(|| artifact.id.clone());
+        let mut violations = self.check_file(&path, &artifact.content);
+        if let (Some(min), Some(confidence)) = (self.config.min_confidence, artifact.confidence) {
+            if confidence < min {
+                violations.push(PolicyViolation {
+                    kind: ViolationKind::LowConfidence,
+                    file: path,
+                    line: None,
+                    message: format!(
+                        "Confidence {:.2} is below the {:.2} required for approval",
+                        confidence, min
+                    ),
+                });
+            }
+        }
+        violations
+    }
+
+    fn check_license_header(&self, path: &str, content: &str, out: &mut Vec<PolicyViolation>) {
+        let Some(rule) = &self.config.license_header else {
+            return;
+        };
+        if !applies_to(path, &rule.extensions) {
+            return;
+        }
+
+        let header: Vec<&str> = content.lines().take(rule.scan_lines).collect();
+        if !header.join("\n").contains(rule.text.trim()) {
+            out.push(PolicyViolation {
+                kind: ViolationKind::MissingLicenseHeader,
+                file: path.to_string(),
+                line: Some(1),
+                message: format!(
+                    "Missing required license header in the first {} lines: \"{}\"",
+                    rule.scan_lines,
+                    rule.text.trim()
+                ),
+            });
+        }
+    }
+
+    fn check_dependencies(&self, path: &str, content: &str, out: &mut Vec<PolicyViolation>) {
+        if self.config.forbidden_dependencies.is_empty() {
+            return;
+        }
+
+        let file_name = Path::new(path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        let declared: Vec<String> = match file_name.as_str() {
+            "Cargo.toml" => cargo_dependencies(content),
+            "package.json" => npm_dependencies(content),
+            _ => Vec::new(),
+        };
+
+        for forbidden in &self.config.forbidden_dependencies {
+            if declared.iter().any(|d| d == forbidden) {
+                out.push(PolicyViolation {
+                    kind: ViolationKind::ForbiddenDependency,
+                    file: path.to_string(),
+                    line: find_line(content, forbidden),
+                    message: format!("Forbidden dependency '{}' declared", forbidden),
+                });
+            }
+        }
+
+        if !applies_to(path, &["rs".to_string()]) {
+            return;
+        }
+
+        // Source-level imports of forbidden crates
+        for (idx, line) in content.lines().enumerate() {
+            let trimmed = line.trim_start();
+            for forbidden in &self.config.forbidden_dependencies {
+                let ident = forbidden.replace('-', "_");
+                if trimmed.starts_with(&format!("use {}::", ident))
+                    || trimmed.starts_with(&format!("extern crate {}", ident))
+                {
+                    out.push(PolicyViolation {
+                        kind: ViolationKind::ForbiddenDependency,
+                        file: path.to_string(),
+                        line: Some(idx + 1),
+                        message: format!("Import of forbidden crate '{}'", forbidden),
+                    });
+                }
+            }
+        }
+    }
+
+    fn check_banned_apis(&self, path: &str, content: &str, out: &mut Vec<PolicyViolation>) {
+        for api in &self.config.banned_apis {
+            if !applies_to(path, &api.extensions) {
+                continue;
+            }
+            for (idx, line) in content.lines().enumerate() {
+                if line.trim_start().starts_with("//") {
+                    continue;
+                }
+                if line.contains(&api.pattern) {
+                    out.push(PolicyViolation {
+                        kind: ViolationKind::BannedApi,
+                        file: path.to_string(),
+                        line: Some(idx + 1),
+                        message: format!("Banned API '{}': {}", api.pattern, api.reason),
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Whether an extension-scoped rule applies to `path`
+fn applies_to(path: &str, extensions: &[String]) -> bool {
+    if extensions.is_empty() {
+        return true;
+    }
+    match Path::new(path).extension() {
+        Some(ext) => {
+            let ext = ext.to_string_lossy();
+            extensions.iter().any(|e| e.trim_start_matches('.') == ext)
+        }
+        // Unnamed content (e.g. an artifact without a path) is checked conservatively
+        None => true,
+    }
+}
+
+fn find_line(content: &str, needle: &str) -> Option<usize> {
+    content.lines().position(|l| l.contains(needle)).map(|i| i + 1)
+}
+
+fn cargo_dependencies(content: &str) -> Vec<String> {
+    let Ok(manifest) = content.parse::<toml::Table>() else {
+        return Vec::new();
+    };
+
+    let sections = ["dependencies", "dev-dependencies", "build-dependencies"];
+    let mut names = Vec::new();
+    let mut collect = |table: &toml::Table| {
+        for section in sections {
+            if let Some(deps) = table.get(section).and_then(|v| v.as_table()) {
+                names.extend(deps.keys().cloned());
+            }
+        }
+    };
+
+    collect(&manifest);
+    if let Some(workspace) = manifest.get("workspace").and_then(|v| v.as_table()) {
+        collect(workspace);
+    }
+    names
+}
+
+fn npm_dependencies(content: &str) -> Vec<String> {
+    let Ok(manifest) = serde_json::from_str::<Value>(content) else {
+        return Vec::new();
+    };
+
+    ["dependencies", "devDependencies", "peerDependencies"]
+        .iter()
+        .filter_map(|section| manifest.get(section).and_then(|v| v.as_object()))
+        .flat_map(|deps| deps.keys().cloned())
+        .collect()
+}
+
+/// Parameters for CheckPolicy tool
+#[derive(Debug, Deserialize)]
+pub struct CheckPolicyParams {
+    /// Files to check (relative to sandbox root)
+    pub paths: Vec<String>,
+}
+
+/// CheckPolicy tool - validates files against the project policy
+pub struct CheckPolicy {
+    sanitizer: PathSanitizer,
+    enforcer: PolicyEnforcer,
+}
+
+impl CheckPolicy {
+    /// Picks up `.zed42/standards.toml` under the sandbox root when present
+    pub fn new(sandbox_root: impl Into<PathBuf>, policy: PolicyConfig) -> Self {
+        let root = sandbox_root.into();
+        let mut enforcer = PolicyEnforcer::new(policy);
+        match StandardsProfile::load(&root) {
+            Ok(Some(standards)) => enforcer = enforcer.with_standards(standards),
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Ignoring project standards: {}", e),
+        }
+        Self {
+            sanitizer: PathSanitizer::new(root),
+            enforcer,
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for CheckPolicy {
+    fn name(&self) -> &str {
+        "check_policy"
+    }
+
+    fn description(&self) -> &str {
+        "Check files for required license headers, forbidden dependencies, banned APIs, and project standards"
+    }
+
+    fn parameter_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "paths": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Files to check (relative to project root)"
+                }
+            },
+            "required": ["paths"]
+        })
+    }
+
+    async fn execute(&self, params: Value) -> ToolResult {
+        let params: CheckPolicyParams = serde_json::from_value(params)
+            .map_err(ToolError::invalid_params)?;
+
+        let mut violations = Vec::new();
+        for path in &params.paths {
+            let safe_path = self.sanitizer.sanitize(path)
+                .map_err(ToolError::sandbox)?;
+            let content = tokio::fs::read_to_string(&safe_path).await
+                .map_err(|e| ToolError::io(format!("Failed to read file '{}'", path), e))?;
+            violations.extend(self.enforcer.check_file(path, &content));
+        }
+
+        Ok(json!({
+            "success": violations.is_empty(),
+            "files_checked": params.paths.len(),
+            "violations": violations
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const POLICY: &str = r#"
+forbidden_dependencies = ["openssl"]
+
+[license_header]
+text = "Copyright (c) 2025 ZED42 Team"
+extensions = ["rs"]
+
+[[banned_apis]]
+pattern = ".unwrap()"
+reason = "Handle errors explicitly"
+extensions = ["rs"]
+"#;
+
+    fn enforcer() -> PolicyEnforcer {
+        PolicyEnforcer::new(PolicyConfig::from_toml_str(POLICY).unwrap())
+    }
+
+    #[test]
+    fn test_license_header_and_banned_api() {
+        let content = "fn main() {\n    let x = foo().unwrap();\n}\n";
+        let violations = enforcer().check_file("src/main.rs", content);
+
+        assert_eq!(violations.len(), 2);
+        assert_eq!(violations[0].kind, ViolationKind::MissingLicenseHeader);
+        assert_eq!(violations[1].kind, ViolationKind::BannedApi);
+        assert_eq!(violations[1].line, Some(2));
+
+        let clean = "// Copyright (c) 2025 ZED42 Team\nfn main() {}\n";
+        assert!(enforcer().check_file("src/main.rs", clean).is_empty());
+        // Extension-scoped rules skip other files
+        assert!(enforcer().check_file("README.md", content).is_empty());
+    }
+
+    #[test]
+    fn test_forbidden_dependencies() {
+        let manifest = "[package]\nname = \"demo\"\n\n[dependencies]\nopenssl = \"0.10\"\nserde = \"1\"\n";
+        let violations = enforcer().check_file("Cargo.toml", manifest);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].kind, ViolationKind::ForbiddenDependency);
+        assert_eq!(violations[0].line, Some(5));
+
+        let source = "// Copyright (c) 2025 ZED42 Team\nuse openssl::ssl;\n";
+        let violations = enforcer().check_file("src/lib.rs", source);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].kind, ViolationKind::ForbiddenDependency);
+    }
+
+    #[test]
+    fn test_low_confidence_artifacts_are_held() {
+        let enforcer = PolicyEnforcer::new(PolicyConfig {
+            min_confidence: Some(0.6),
+            ..Default::default()
+        });
+        let artifact = Artifact::code("t1".to_string(), "fn f() {}".to_string(), None);
+        assert!(enforcer.check_artifact(&artifact).is_empty());
+
+        let violations = enforcer.check_artifact(&artifact.clone().with_confidence(0.45));
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].kind, ViolationKind::LowConfidence);
+        assert!(enforcer.check_artifact(&artifact.with_confidence(0.8)).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_check_policy_tool() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        std::fs::write(root.join("lib.rs"), "fn f() { g().unwrap(); }\n").unwrap();
+
+        let tool = CheckPolicy::new(&root, PolicyConfig::from_toml_str(POLICY).unwrap());
+        let result = tool.execute(json!({ "paths": ["lib.rs"] })).await.unwrap();
+
+        assert_eq!(result["success"], false);
+        assert_eq!(result["violations"].as_array().unwrap().len(), 2);
+        assert_eq!(result["violations"][0]["kind"], "missing_license_header");
+    }
+
+    #[tokio::test]
+    async fn test_check_policy_applies_project_standards() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        std::fs::create_dir_all(root.join(".zed42")).unwrap();
+        std::fs::write(
+            root.join(crate::standards::STANDARDS_PATH),
+            "forbidden_crates = [\"openssl\"]\n[naming]\nfunctions = \"snake_case\"\n[error_handling]\nforbid_panic = true\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("lib.rs"), "use openssl::ssl;\nfn doWork() { todo!() }\n").unwrap();
+
+        let tool = CheckPolicy::new(&root, PolicyConfig::default());
+        let result = tool.execute(json!({ "paths": ["lib.rs"] })).await.unwrap();
+        let kinds: Vec<&str> = result["violations"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| v["kind"].as_str().unwrap())
+            .collect();
+        assert_eq!(kinds, vec!["forbidden_dependency", "banned_api", "naming_convention"]);
+    }
+}
@@ -0,0 +1,496 @@
+//! Interactive code evaluation
+//!
+//! [`EvaluateCode`] lets an agent try a snippet before writing a full
+//! implementation. Each task gets its own session per language, and state
+//! carries over between calls in that session:
+//!
+//! - Python runs in one long-lived interpreter per session, so variables,
+//!   imports and functions defined by earlier snippets stay available.
+//! - Rust has no persistent interpreter. Snippets that define items (`fn`,
+//!   `struct`, `use`, ...) are kept and prepended to every later program,
+//!   which `rust-script` compiles and runs. Statements run once.
+//!
+//! Sessions start in the project root with a minimal environment. That is
+//! not a sandbox: snippets run as the agent's user, with its file system and
+//! network access and no resource limits, like `execute_command`, so every
+//! snippet needs approval. A call that times out leaves its interpreter
+//! busy, so the next call to that session starts a fresh one.
+//!
+//! Sessions idle for [`SESSION_IDLE`] are stopped, and at most
+//! [`MAX_SESSIONS`] stay open; the least recently used goes first.
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::process::{Child, ChildStdin, ChildStdout};
+use tokio::sync::Mutex;
+use crate::platform::{self, Platform};
+use crate::{ApprovalNeed, Tool, ToolError, ToolResult};
+
+/// Default limit for one evaluation (a cold `rust-script` build is slow)
+const EVAL_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Characters of stdout/stderr returned per evaluation
+const MAX_OUTPUT_CHARS: usize = 16_000;
+
+/// Sessions unused for this long are stopped
+const SESSION_IDLE: Duration = Duration::from_secs(15 * 60);
+
+/// Most sessions kept open at once
+const MAX_SESSIONS: usize = 8;
+
+/// Environment variables passed through to interpreters
+const PASSTHROUGH_ENV: &[&str] = &["PATH", "HOME", "USERPROFILE", "SYSTEMROOT", "TEMP", "TMP", "CARGO_HOME", "RUSTUP_HOME"];
+
+/// Request loop run by the Python interpreter
+///
+/// Reads one JSON request per line, runs the code in a shared namespace and
+/// answers with one JSON line tagged with the request id. A lone expression
+/// prints its repr, like the interactive prompt.
+const PYTHON_DRIVER: &str = r#"
+import sys, json, io, contextlib, traceback
+ns = {"__name__": "__repl__"}
+for line in sys.stdin:
+    req = json.loads(line)
+    out, err = io.StringIO(), io.StringIO()
+    ok = True
+    with contextlib.redirect_stdout(out), contextlib.redirect_stderr(err):
+        try:
+            try:
+                code = compile(req["code"], "<repl>", "eval")
+            except SyntaxError:
+                code = None
+            if code is None:
+                exec(compile(req["code"], "<repl>", "exec"), ns)
+            else:
+                value = eval(code, ns)
+                if value is not None:
+                    print(repr(value))
+        except BaseException:
+            ok = False
+            traceback.print_exc()
+    sys.__stdout__.write(json.dumps({"id": req["id"], "ok": ok, "stdout": out.getvalue(), "stderr": err.getvalue()}) + "\n")
+    sys.__stdout__.flush()
+"#;
+
+/// Languages with an evaluation session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReplLanguage {
+    Python,
+    Rust,
+}
+
+impl ReplLanguage {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Python => "Python",
+            Self::Rust => "Rust",
+        }
+    }
+}
+
+/// Parameters for EvaluateCode tool
+#[derive(Debug, Deserialize)]
+pub struct EvaluateCodeParams {
+    /// Task the session belongs to
+    pub task_id: String,
+    pub language: ReplLanguage,
+    /// Snippet to evaluate
+    pub code: String,
+    /// Discard the session's state before evaluating
+    #[serde(default)]
+    pub reset: bool,
+}
+
+/// Outcome of one snippet
+#[derive(Debug, Clone, PartialEq)]
+pub struct Evaluation {
+    pub success: bool,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// A Python interpreter running [`PYTHON_DRIVER`]
+struct PythonSession {
+    _child: Child,
+    stdin: ChildStdin,
+    stdout: Lines<BufReader<ChildStdout>>,
+    next_id: u64,
+    /// Set while a request is unanswered; still set on entry means the last
+    /// call was abandoned mid-evaluation
+    busy: bool,
+}
+
+impl PythonSession {
+    fn spawn(root: &Path) -> anyhow::Result<Self> {
+        let program = if Platform::current().is_windows() { "python" } else { "python3" };
+        let mut command = interpreter(program, &["-u".to_string(), "-c".to_string(), PYTHON_DRIVER.to_string()], root);
+        command.stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::null());
+        let mut child = command
+            .spawn()
+            .map_err(|e| anyhow::Error::new(e).context(format!("Failed to start {}", program)))?;
+        let stdin = child.stdin.take().ok_or_else(|| anyhow::anyhow!("Interpreter stdin unavailable"))?;
+        let stdout = child.stdout.take().ok_or_else(|| anyhow::anyhow!("Interpreter stdout unavailable"))?;
+        Ok(Self {
+            _child: child,
+            stdin,
+            stdout: BufReader::new(stdout).lines(),
+            next_id: 0,
+            busy: false,
+        })
+    }
+
+    async fn eval(&mut self, code: &str) -> anyhow::Result<Evaluation> {
+        self.next_id += 1;
+        let id = self.next_id;
+        self.busy = true;
+
+        let mut request = serde_json::to_string(&json!({ "id": id, "code": code }))?;
+        request.push('\n');
+        self.stdin.write_all(request.as_bytes()).await?;
+        self.stdin.flush().await?;
+
+        loop {
+            let line = self
+                .stdout
+                .next_line()
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Interpreter exited"))?;
+            let response: Value = serde_json::from_str(&line)?;
+            if response["id"].as_u64() != Some(id) {
+                continue;
+            }
+            self.busy = false;
+            return Ok(Evaluation {
+                success: response["ok"].as_bool().unwrap_or(false),
+                stdout: response["stdout"].as_str().unwrap_or_default().to_string(),
+                stderr: response["stderr"].as_str().unwrap_or_default().to_string(),
+            });
+        }
+    }
+}
+
+/// Item definitions accumulated from earlier Rust snippets
+#[derive(Default)]
+struct RustSession {
+    items: Vec<String>,
+}
+
+impl RustSession {
+    async fn eval(&mut self, root: &Path, code: &str) -> anyhow::Result<Evaluation> {
+        let is_item = is_rust_item(code);
+        let source = rust_program(&self.items, code, is_item);
+
+        let dir = std::env::temp_dir().join(format!("zed42-repl-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir)?;
+        let script = dir.join("snippet.rs");
+        std::fs::write(&script, source)?;
+
+        let output = interpreter("rust-script", &[script.to_string_lossy().into_owned()], root)
+            .stdin(Stdio::null())
+            .output()
+            .await;
+        let _ = std::fs::remove_dir_all(&dir);
+        let output = output.map_err(|e| anyhow::Error::new(e).context("Failed to run rust-script"))?;
+
+        let success = output.status.success();
+        if success && is_item {
+            self.items.push(code.trim().to_string());
+        }
+        Ok(Evaluation {
+            success,
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        })
+    }
+}
+
+enum Session {
+    Python(PythonSession),
+    Rust(RustSession),
+}
+
+/// An open session and when it was last used
+struct SessionSlot {
+    session: Arc<Mutex<Session>>,
+    last_used: Instant,
+}
+
+/// EvaluateCode tool - runs snippets in per-task interpreter sessions
+pub struct EvaluateCode {
+    sandbox_root: PathBuf,
+    sessions: Mutex<HashMap<(String, ReplLanguage), SessionSlot>>,
+}
+
+impl EvaluateCode {
+    pub fn new(sandbox_root: impl Into<PathBuf>) -> Self {
+        Self {
+            sandbox_root: sandbox_root.into(),
+            sessions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Drop every session of `task_id`, stopping its interpreters
+    pub async fn end_task(&self, task_id: &str) {
+        self.sessions.lock().await.retain(|(task, _), _| task != task_id);
+    }
+
+    /// Evaluate `code` in the task's session, creating it if needed
+    pub async fn evaluate(&self, task_id: &str, language: ReplLanguage, code: &str, reset: bool) -> anyhow::Result<Evaluation> {
+        let session = {
+            let mut sessions = self.sessions.lock().await;
+            let key = (task_id.to_string(), language);
+            if reset {
+                sessions.remove(&key);
+            }
+            // Tasks that finished without `end_task` leave their sessions idle
+            let now = Instant::now();
+            sessions.retain(|_, slot| now.duration_since(slot.last_used) < SESSION_IDLE);
+            if !sessions.contains_key(&key) && sessions.len() >= MAX_SESSIONS {
+                let oldest = sessions.iter().min_by_key(|(_, slot)| slot.last_used).map(|(key, _)| key.clone());
+                if let Some(oldest) = oldest {
+                    tracing::debug!(task_id = %oldest.0, "Stopping least recently used evaluation session");
+                    sessions.remove(&oldest);
+                }
+            }
+            match sessions.get_mut(&key) {
+                Some(slot) => {
+                    slot.last_used = now;
+                    slot.session.clone()
+                }
+                None => {
+                    let session = Arc::new(Mutex::new(self.start(language)?));
+                    sessions.insert(key, SessionSlot { session: session.clone(), last_used: now });
+                    session
+                }
+            }
+        };
+
+        let mut session = session.lock().await;
+        if let Session::Python(python) = &*session {
+            if python.busy {
+                tracing::warn!(task_id, "Restarting Python session abandoned mid-evaluation");
+                *session = self.start(language)?;
+            }
+        }
+        match &mut *session {
+            Session::Python(python) => python.eval(code).await,
+            Session::Rust(rust) => rust.eval(&self.sandbox_root, code).await,
+        }
+    }
+
+    fn start(&self, language: ReplLanguage) -> anyhow::Result<Session> {
+        Ok(match language {
+            ReplLanguage::Python => Session::Python(PythonSession::spawn(&self.sandbox_root)?),
+            ReplLanguage::Rust => Session::Rust(RustSession::default()),
+        })
+    }
+}
+
+#[async_trait]
+impl Tool for EvaluateCode {
+    fn name(&self) -> &str {
+        "evaluate_code"
+    }
+
+    fn description(&self) -> &str {
+        "Evaluate a Python or Rust snippet in a session that keeps state across calls for the same task. Use it to test hypotheses before writing a full implementation. Snippets run in the project root with the same file and network access as execute_command; they are not sandboxed."
+    }
+
+    fn default_timeout(&self) -> Duration {
+        EVAL_TIMEOUT
+    }
+
+    /// Every snippet; "always allow" covers only the same code in the same language
+    fn approval_needed(&self, params: &Value) -> Option<ApprovalNeed> {
+        let params: EvaluateCodeParams = serde_json::from_value(params.clone()).ok()?;
+        let language = params.language.label();
+        Some(ApprovalNeed::new(
+            format!("Runs a {} snippet for task {} in the project root", language, params.task_id),
+            format!("{}: {}", language, params.code.trim()),
+        ))
+    }
+
+    /// The snippet itself
+    async fn approval_preview(&self, params: &Value) -> Option<String> {
+        params["code"].as_str().map(str::to_string)
+    }
+
+    fn parameter_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "task_id": {
+                    "type": "string",
+                    "description": "Task the session belongs to; calls with the same task_id share state"
+                },
+                "language": {
+                    "type": "string",
+                    "enum": ["python", "rust"]
+                },
+                "code": {
+                    "type": "string",
+                    "description": "Snippet to run. Python keeps variables between calls; Rust keeps fn/struct/use/impl definitions"
+                },
+                "reset": {
+                    "type": "boolean",
+                    "description": "Discard the session's state first (optional, default false)"
+                },
+                "timeout_secs": {
+                    "type": "number",
+                    "description": "Give up after this many seconds (optional, default 120)"
+                }
+            },
+            "required": ["task_id", "language", "code"]
+        })
+    }
+
+    async fn execute(&self, params: Value) -> ToolResult {
+        let params: EvaluateCodeParams = serde_json::from_value(params)
+            .map_err(ToolError::invalid_params)?;
+        if params.task_id.trim().is_empty() {
+            return Err(ToolError::invalid_params("task_id must not be empty"));
+        }
+
+        let started = Instant::now();
+        let evaluation = self.evaluate(&params.task_id, params.language, &params.code, params.reset).await?;
+
+        Ok(json!({
+            "task_id": params.task_id,
+            "success": evaluation.success,
+            "stdout": truncate(&evaluation.stdout),
+            "stderr": truncate(&evaluation.stderr),
+            "duration_ms": started.elapsed().as_millis() as u64
+        }))
+    }
+}
+
+/// Command for `program` in `root` with only [`PASSTHROUGH_ENV`] set
+///
+/// Only the environment is restricted; see the module docs.
+fn interpreter(program: &str, args: &[String], root: &Path) -> tokio::process::Command {
+    let mut command = platform::command(program, args);
+    command.current_dir(root).env_clear().kill_on_drop(true);
+    for key in PASSTHROUGH_ENV {
+        if let Some(value) = std::env::var_os(key) {
+            command.env(key, value);
+        }
+    }
+    command
+}
+
+/// Whether a Rust snippet defines items rather than running statements
+fn is_rust_item(code: &str) -> bool {
+    const ITEM_KEYWORDS: &[&str] = &[
+        "fn", "pub", "struct", "enum", "impl", "trait", "use", "mod", "const", "static", "type", "extern", "unsafe", "async", "macro_rules!",
+    ];
+    let code = code.trim_start();
+    if code.starts_with("#[") || code.starts_with("//!") {
+        return true;
+    }
+    let first = code.split_whitespace().next().unwrap_or_default();
+    ITEM_KEYWORDS.contains(&first)
+}
+
+/// Full program for a snippet on top of the session's items
+///
+/// Statement snippets become the body of `main`; if the last line is an
+/// expression, its value is printed with `{:?}`.
+fn rust_program(items: &[String], code: &str, is_item: bool) -> String {
+    let mut source = String::from("#![allow(unused)]\n");
+    for item in items {
+        source.push_str(item);
+        source.push_str("\n\n");
+    }
+
+    let code = code.trim();
+    if is_item {
+        source.push_str(code);
+        source.push_str("\n\nfn main() {}\n");
+    } else if code.ends_with(';') || code.ends_with('}') || code.is_empty() {
+        source.push_str(&format!("fn main() {{\n{}\n}}\n", code));
+    } else {
+        source.push_str(&format!("fn main() {{\n    let __value = {{\n{}\n    }};\n    println!(\"{{:?}}\", __value);\n}}\n", code));
+    }
+    source
+}
+
+fn truncate(text: &str) -> String {
+    match text.char_indices().nth(MAX_OUTPUT_CHARS) {
+        Some((cut, _)) => format!("{}\n... [output truncated]", &text[..cut]),
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_rust_program_keeps_items_and_prints_value() {
+        assert!(is_rust_item("#[derive(Debug)]\nstruct Point { x: i32 }"));
+        assert!(is_rust_item("fn double(x: i32) -> i32 { x * 2 }"));
+        assert!(!is_rust_item("let x = double(2);\nx + 1"));
+
+        let items = vec!["fn double(x: i32) -> i32 { x * 2 }".to_string()];
+        let program = rust_program(&items, "let x = double(2);\nx + 1", false);
+        assert!(program.starts_with("#![allow(unused)]\nfn double"));
+        assert!(program.contains("let __value = {\nlet x = double(2);\nx + 1\n    };"));
+
+        let program = rust_program(&items, "println!(\"hi\");", false);
+        assert!(program.ends_with("fn main() {\nprintln!(\"hi\");\n}\n"));
+    }
+
+    #[tokio::test]
+    async fn test_python_session_keeps_state_per_task() {
+        if std::process::Command::new("python3").arg("--version").output().is_err() {
+            return;
+        }
+        let temp = tempdir().unwrap();
+        let tool = EvaluateCode::new(temp.path());
+
+        let first = tool.execute(json!({ "task_id": "t1", "language": "python", "code": "x = 20" })).await.unwrap();
+        assert_eq!(first["success"], true);
+        let second = tool.execute(json!({ "task_id": "t1", "language": "python", "code": "x * 2 + 2" })).await.unwrap();
+        assert_eq!(second["stdout"].as_str().unwrap().trim(), "42");
+
+        // Other tasks don't see the state, and errors are reported in the output
+        let other = tool.execute(json!({ "task_id": "t2", "language": "python", "code": "x" })).await.unwrap();
+        assert_eq!(other["success"], false);
+        assert!(other["stderr"].as_str().unwrap().contains("NameError"));
+
+        // A hung snippet is abandoned and the session restarted
+        let err = crate::timeout::run_tool(
+            &tool,
+            json!({ "task_id": "t1", "language": "python", "code": "while True: pass", "timeout_secs": 0.5 }),
+            &zed42_core::CancellationToken::new(),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, ToolError::TimedOut(_)));
+        let after = tool.execute(json!({ "task_id": "t1", "language": "python", "code": "print('alive')" })).await.unwrap();
+        assert_eq!(after["stdout"].as_str().unwrap().trim(), "alive");
+
+        tool.end_task("t1").await;
+        assert_eq!(tool.sessions.lock().await.len(), 1);
+    }
+
+    #[test]
+    fn test_every_snippet_needs_approval() {
+        let tool = EvaluateCode::new(".");
+        let need = tool
+            .approval_needed(&json!({ "task_id": "t1", "language": "python", "code": " import os\n" }))
+            .unwrap();
+        assert_eq!(need.scope, "Python: import os");
+        assert!(need.reason.contains("task t1"));
+        assert!(tool.approval_needed(&json!({ "language": "python" })).is_none());
+    }
+}
@@ -7,8 +7,29 @@ use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::process::Stdio;
-use tokio::process::Command;
-use crate::{Tool, ToolResult};
+use zed42_core::Team;
+use crate::guardrails::{CommandGuardrail, GuardrailVerdict};
+use crate::platform;
+use crate::{ApprovalNeed, Tool, ToolError, ToolResult};
+
+/// Default limit for a single command
+const COMMAND_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(300);
+
+/// Programs that run whatever their arguments say; "always allow" covers only the exact call
+const INTERPRETERS: &[&str] = &[
+    "sh", "bash", "zsh", "dash", "ksh", "fish", "cmd", "powershell", "pwsh", "env", "sudo", "doas",
+    "xargs", "nohup", "timeout", "nice", "python", "python3", "node", "perl", "ruby", "npx", "deno",
+    "bun", "bunx", "uvx",
+];
+
+/// Subcommands that run code from the project, treated like [`INTERPRETERS`]
+const RUN_SUBCOMMANDS: &[(&str, &str)] = &[("cargo", "run"), ("uv", "run"), ("npm", "exec"), ("npm", "run")];
+
+/// Programs whose arguments are the files or processes they act on, so
+/// approving one call says nothing about the next
+const TARGETED: &[&str] = &[
+    "rm", "rmdir", "mv", "cp", "ln", "chmod", "chown", "dd", "truncate", "shred", "find", "kill", "pkill", "killall",
+];
 
 /// Parameters for ExecuteCommand tool
 #[derive(Debug, Deserialize)]
@@ -25,14 +46,23 @@ pub struct ExecuteCommandParams {
 pub struct ExecuteCommand {
     /// Root directory for execution context
     sandbox_root: std::path::PathBuf,
+    /// Guardrail applied to every command, with the team of the calling agent
+    guardrail: Option<(CommandGuardrail, Team)>,
 }
 
 impl ExecuteCommand {
     pub fn new(sandbox_root: impl Into<std::path::PathBuf>) -> Self {
         Self {
             sandbox_root: sandbox_root.into(),
+            guardrail: None,
         }
     }
+
+    /// Check commands against `guardrail` using the rules for `team`
+    pub fn with_guardrail(mut self, guardrail: CommandGuardrail, team: Team) -> Self {
+        self.guardrail = Some((guardrail, team));
+        self
+    }
 }
 
 #[async_trait]
@@ -45,6 +75,58 @@ impl Tool for ExecuteCommand {
         "Execute a shell command with full output capture (stdout/stderr). Use this for git, cargo, or system tools."
     }
 
+    fn default_timeout(&self) -> std::time::Duration {
+        COMMAND_TIMEOUT
+    }
+
+    /// Every command; "always allow" covers the program and its subcommand, e.g. `git push`
+    ///
+    /// It covers only the exact command line when any argument is an option
+    /// or a `+` refspec (`git push --force`, `find . -delete`), for
+    /// interpreters like `sh -c` or `cargo run`, and for programs like `rm`
+    /// that act on their arguments. So do commands the guardrail wants
+    /// approved, whose reason names the rule.
+    fn approval_needed(&self, params: &Value) -> Option<ApprovalNeed> {
+        let params: ExecuteCommandParams = serde_json::from_value(params.clone()).ok()?;
+        let line = std::iter::once(params.command.as_str())
+            .chain(params.args.iter().map(String::as_str))
+            .map(quote_arg)
+            .collect::<Vec<_>>()
+            .join(" ");
+        if let Some((ref guardrail, team)) = self.guardrail {
+            if let GuardrailVerdict::RequireApproval { rule, reason } = guardrail.check(team, &params.command, &params.args) {
+                return Some(ApprovalNeed::new(format!("Runs `{}` ({}: {})", line, rule, reason), format!("{}: {}", rule, line)));
+            }
+        }
+        let program = std::path::Path::new(&params.command)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let runs_code = INTERPRETERS.contains(&program.as_str())
+            || TARGETED.contains(&program.as_str())
+            || params.args.first().is_some_and(|sub| RUN_SUBCOMMANDS.contains(&(program.as_str(), sub.as_str())));
+        let has_flags = params.args.iter().any(|arg| arg.starts_with('-') || arg.starts_with('+'));
+        let scope = match params.args.first() {
+            _ if runs_code || has_flags => line.clone(),
+            Some(sub) => format!("{} {}", params.command, quote_arg(sub)),
+            None => params.command.clone(),
+        };
+        Some(ApprovalNeed::new(format!("Runs `{}`", line), scope))
+    }
+
+    /// For `git push`, the diff between the upstream branch and HEAD
+    async fn approval_preview(&self, params: &Value) -> Option<String> {
+        let params: ExecuteCommandParams = serde_json::from_value(params.clone()).ok()?;
+        if params.command != "git" || params.args.first().map(String::as_str) != Some("push") {
+            return None;
+        }
+        let dir = match params.cwd {
+            Some(ref cwd) => self.sandbox_root.join(cwd),
+            None => self.sandbox_root.clone(),
+        };
+        tokio::task::spawn_blocking(move || unpushed_diff(&dir)).await.ok().flatten()
+    }
+
     fn parameter_schema(&self) -> Value {
         json!({
             "type": "object",
@@ -61,6 +143,10 @@ impl Tool for ExecuteCommand {
                 "cwd": {
                     "type": "string",
                     "description": "Working directory relative to project root (optional)"
+                },
+                "timeout_secs": {
+                    "type": "number",
+                    "description": "Kill the command after this many seconds (optional, default 300)"
                 }
             },
             "required": ["command", "args"]
@@ -68,46 +154,65 @@ impl Tool for ExecuteCommand {
     }
 
     async fn execute(&self, params: Value) -> ToolResult {
-        let params: ExecuteCommandParams = serde_json::from_value(params)
-            .map_err(|e| anyhow::anyhow!("Invalid parameters: {}", e))?;
+        let mut params: ExecuteCommandParams = serde_json::from_value(params)
+            .map_err(ToolError::invalid_params)?;
+
+        // 0. Guardrails (block / approval / rewrite)
+        let mut rewritten_from: Option<String> = None;
+        if let Some((ref guardrail, team)) = self.guardrail {
+            match guardrail.check(team, &params.command, &params.args) {
+                GuardrailVerdict::Allow => {}
+                GuardrailVerdict::Block { rule, reason } => {
+                    tracing::warn!(rule = %rule, command = %params.command, "Guardrail blocked command");
+                    return Err(ToolError::PolicyDenied { rule, reason });
+                }
+                // Reported by `approval_needed`, so the executor's approver has already answered
+                GuardrailVerdict::RequireApproval { rule, .. } => {
+                    tracing::info!(rule = %rule, command = %params.command, "Running command the guardrail flagged for approval");
+                }
+                GuardrailVerdict::Rewrite { rule, command, args, .. } => {
+                    tracing::info!(rule = %rule, "Guardrail rewrote command");
+                    rewritten_from = Some(format!("{} {}", params.command, params.args.join(" ")));
+                    params.command = command;
+                    params.args = args;
+                }
+            }
+        }
 
         // 1. Resolve Working Directory
         let cwd = if let Some(ref dir) = params.cwd {
             let full_path = self.sandbox_root.join(dir);
-            // Verify path is safe (simple check here, deeper sanitization if FS tool logic was shared/exposed)
-            // ideally we would reuse PathSanitizer but it's currently private to file_manipulation.
-            // For now, we perform a canonicalization check.
             if full_path.to_string_lossy().contains("..") { // Basic pre-check
-                 return Err(anyhow::anyhow!("Path traversal in cwd not allowed"));
+                 return Err(ToolError::sandbox("Path traversal in cwd not allowed"));
             }
-             match full_path.canonicalize() {
-                Ok(p) => {
-                     // Verify prefix
-                    if !p.starts_with(&self.sandbox_root) {
-                        return Err(anyhow::anyhow!("Working directory resolves outside sandbox"));
-                    }
-                    p
-                },
-                Err(e) => return Err(anyhow::anyhow!("Invalid working directory '{}': {}", dir, e)),
+            let resolved = platform::resolve(&full_path)
+                .map_err(|e| ToolError::io(format!("Invalid working directory '{}'", dir), e))?;
+            if !resolved.is_dir() {
+                return Err(ToolError::invalid_params(format!("Invalid working directory '{}': not a directory", dir)));
+            }
+            // Resolve the root too, so symlinked roots (macOS temp dirs) compare equal
+            if !platform::is_within(&resolved, &self.sandbox_root) {
+                return Err(ToolError::sandbox("Working directory resolves outside sandbox"));
             }
+            resolved
         } else {
             self.sandbox_root.clone()
         };
 
-        // 2. Prepare Command
-        let mut child = Command::new(&params.command);
-        child.args(&params.args)
-             .current_dir(&cwd)
+        // 2. Prepare Command (cmd.exe builtins such as `echo` go through the shell on Windows)
+        let mut child = platform::command(&params.command, &params.args);
+        child.current_dir(&cwd)
              .stdout(Stdio::piped())
              .stderr(Stdio::piped())
-             .stdin(Stdio::null()); // No interactive input
+             .stdin(Stdio::null()) // No interactive input
+             .kill_on_drop(true); // Timeouts and cancellation drop the future
 
         // 3. Execute (Hard Fail detection)
         let output = match child.output().await {
             Ok(o) => o,
             Err(e) => {
                 // HARD FAIL: Binary not found, permission denied, etc.
-                return Err(anyhow::anyhow!("Execution failed (Binary not found or IO error): {}", e));
+                return Err(ToolError::io("Execution failed (Binary not found or IO error)", e));
             }
         };
 
@@ -124,16 +229,70 @@ impl Tool for ExecuteCommand {
             "exit_code": exit_code,
             "stdout": stdout,
             "stderr": stderr,
-            "success": output.status.success()
+            "success": output.status.success(),
+            "rewritten_from": rewritten_from
         }))
     }
 }
 
+/// `arg` as typed in a shell: quoted when empty or containing spaces or quotes
+fn quote_arg(arg: &str) -> std::borrow::Cow<'_, str> {
+    if !arg.is_empty() && !arg.contains(|c: char| c.is_whitespace() || c == '"' || c == '\'') {
+        return arg.into();
+    }
+    format!("{:?}", arg).into()
+}
+
+/// Changes on the current branch that its upstream doesn't have
+fn unpushed_diff(dir: &std::path::Path) -> Option<String> {
+    let repo = git2::Repository::discover(dir).ok()?;
+    let head = repo.head().ok()?;
+    let ours = head.peel_to_tree().ok()?;
+    let upstream = git2::Branch::wrap(head).upstream().ok()?;
+    let theirs = upstream.get().peel_to_tree().ok()?;
+    let diff = repo.diff_tree_to_tree(Some(&theirs), Some(&ours), None).ok()?;
+    let mut text = String::new();
+    diff.print(git2::DiffFormat::Patch, |_, _, line| {
+        if matches!(line.origin(), '+' | '-' | ' ') {
+            text.push(line.origin());
+        }
+        text.push_str(&String::from_utf8_lossy(line.content()));
+        true
+    })
+    .ok()?;
+    Some(text)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use tempfile::tempdir;
 
+    #[test]
+    fn test_session_scope_never_covers_other_scripts() {
+        let tool = ExecuteCommand::new(".");
+        let scope = |command: &str, args: &[&str]| {
+            tool.approval_needed(&json!({ "command": command, "args": args })).unwrap().scope
+        };
+
+        assert_eq!(scope("git", &["push", "origin"]), "git push");
+        assert_eq!(scope("sh", &["-c", "cargo test"]), "sh -c \"cargo test\"");
+        assert_ne!(scope("/bin/bash", &["-c", "cargo test"]), scope("/bin/bash", &["-c", "rm -rf ~"]));
+        assert_eq!(scope("python3", &["script.py"]), "python3 script.py");
+        assert_eq!(scope("git", &["-C", "sub", "push"]), "git -C sub push");
+        assert_eq!(scope("cargo", &[]), "cargo");
+
+        // Flags and targets are part of what was approved
+        assert_eq!(scope("git", &["push", "--force"]), "git push --force");
+        assert_eq!(scope("git", &["push", "origin", "+main"]), "git push origin +main");
+        assert_eq!(scope("find", &["."]), "find .");
+        assert_ne!(scope("find", &["."]), scope("find", &[".", "-delete"]));
+        assert_eq!(scope("rm", &["a.txt", "b.txt"]), "rm a.txt b.txt");
+        assert_eq!(scope("cargo", &["run", "--", "x"]), "cargo run -- x");
+        assert_eq!(scope("uv", &["run", "script.py"]), "uv run script.py");
+        assert_eq!(scope("npx", &["prettier", "src"]), "npx prettier src");
+    }
+
     #[tokio::test]
     async fn test_execute_echo_success() {
         let temp = tempdir().unwrap();
@@ -167,6 +326,77 @@ mod tests {
         assert!(result.unwrap_err().to_string().contains("Execution failed"));
     }
 
+    #[tokio::test]
+    async fn test_guardrail_blocks_and_requests_approval() {
+        let temp = tempdir().unwrap();
+        let tool = ExecuteCommand::new(temp.path())
+            .with_guardrail(CommandGuardrail::new(), Team::Blue);
+
+        let result = tool.execute(json!({
+            "command": "rm",
+            "args": ["-rf", "/"]
+        })).await;
+        assert!(matches!(result, Err(ToolError::PolicyDenied { .. })));
+
+        // The guardrail's reason goes to the approver, and only this exact command is covered
+        let need = tool.approval_needed(&json!({ "command": "printenv", "args": [] })).unwrap();
+        assert!(need.reason.starts_with("Runs `printenv` (credential_echo: "));
+        assert_eq!(need.scope, "credential_echo: printenv");
+
+        let mut executor = crate::ToolExecutor::new().with_approver(std::sync::Arc::new(Denier));
+        executor.register(std::sync::Arc::new(tool));
+        let result = executor.execute(crate::ToolCall::new("execute_command", json!({ "command": "printenv", "args": [] }))).await;
+        assert!(matches!(result, Err(ToolError::PolicyDenied { ref rule, .. }) if rule == crate::approval::APPROVAL_RULE));
+    }
+
+    struct Denier;
+
+    #[async_trait]
+    impl crate::approval::Approver for Denier {
+        async fn decide(&self, _request: crate::approval::ApprovalRequest) -> crate::approval::ApprovalDecision {
+            crate::approval::ApprovalDecision::Deny
+        }
+    }
+
+    #[tokio::test]
+    async fn test_builtin_echo_in_subdirectory() {
+        let temp = tempdir().unwrap();
+        std::fs::create_dir(temp.path().join("sub")).unwrap();
+        let tool = ExecuteCommand::new(temp.path());
+
+        let val = tool.execute(json!({
+            "command": "echo",
+            "args": ["hello"],
+            "cwd": "sub"
+        })).await.unwrap();
+
+        assert_eq!(val["exit_code"], 0);
+        assert_eq!(val["stdout"].as_str().unwrap().trim(), "hello");
+    }
+
+    #[tokio::test]
+    async fn test_hung_command_is_killed_on_timeout() {
+        let temp = tempdir().unwrap();
+        let tool = ExecuteCommand::new(temp.path());
+        let (program, args) = if platform::Platform::current().is_windows() {
+            ("ping", vec!["-n", "30", "127.0.0.1"])
+        } else {
+            ("sleep", vec!["30"])
+        };
+
+        let started = std::time::Instant::now();
+        let err = crate::timeout::run_tool(
+            &tool,
+            json!({ "command": program, "args": args, "timeout_secs": 0.2 }),
+            &zed42_core::CancellationToken::new(),
+        )
+        .await
+        .unwrap_err();
+
+        assert!(matches!(err, ToolError::TimedOut(_)));
+        assert!(started.elapsed() < std::time::Duration::from_secs(10));
+    }
+
     #[tokio::test]
     async fn test_cwd_sandboxing() {
         let temp = tempdir().unwrap();
@@ -0,0 +1,379 @@
+//! Project standards profile
+//!
+//! A repository can describe its coding standards in `.zed42/standards.toml`:
+//! naming conventions, forbidden crates, error-handling rules and
+//! documentation requirements. The StandardsEnforcer compiles the profile
+//! into two things applied to every Blue-team artifact:
+//!
+//! - a critique rubric appended to the reviewer prompt of the reflexion loop
+//! - static checks run by the [`PolicyEnforcer`](crate::policy::PolicyEnforcer)
+//!   alongside the project policy (naming, doc comments, banned error
+//!   handling APIs, forbidden crates)
+//!
+//! ```toml
+//! forbidden_crates = ["openssl"]
+//! rubric = ["Prefer iterators over index loops"]
+//!
+//! [naming]
+//! functions = "snake_case"
+//! types = "upper_camel_case"
+//! constants = "screaming_snake_case"
+//!
+//! [error_handling]
+//! forbid_unwrap = true
+//! forbid_expect = true
+//! forbid_panic = true
+//!
+//! [docs]
+//! public_items = true
+//! module = true
+//! ```
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::path::Path;
+
+use crate::policy::{BannedApi, PolicyViolation, ViolationKind};
+
+/// Location of the profile relative to the project root
+pub const STANDARDS_PATH: &str = ".zed42/standards.toml";
+
+/// Identifier case conventions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Case {
+    SnakeCase,
+    UpperCamelCase,
+    ScreamingSnakeCase,
+}
+
+impl Case {
+    pub fn matches(&self, ident: &str) -> bool {
+        let ident = ident.trim_start_matches("r#");
+        let mut chars = ident.chars();
+        let Some(first) = chars.next() else {
+            return true;
+        };
+        match self {
+            Case::SnakeCase => {
+                (first.is_ascii_lowercase() || first == '_')
+                    && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+            }
+            Case::UpperCamelCase => first.is_ascii_uppercase() && chars.all(|c| c.is_ascii_alphanumeric()),
+            Case::ScreamingSnakeCase => {
+                (first.is_ascii_uppercase() || first == '_')
+                    && chars.all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+            }
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Case::SnakeCase => "snake_case",
+            Case::UpperCamelCase => "UpperCamelCase",
+            Case::ScreamingSnakeCase => "SCREAMING_SNAKE_CASE",
+        }
+    }
+}
+
+/// Naming conventions for Rust items; unset kinds are not checked
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NamingRules {
+    #[serde(default)]
+    pub functions: Option<Case>,
+    /// Structs, enums, traits and type aliases
+    #[serde(default)]
+    pub types: Option<Case>,
+    /// Consts and statics
+    #[serde(default)]
+    pub constants: Option<Case>,
+}
+
+/// Error-handling rules
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ErrorHandlingRules {
+    #[serde(default)]
+    pub forbid_unwrap: bool,
+    #[serde(default)]
+    pub forbid_expect: bool,
+    /// `panic!`, `todo!` and `unimplemented!`
+    #[serde(default)]
+    pub forbid_panic: bool,
+    /// Free-form guidance for the reviewer (e.g. "wrap errors with context")
+    #[serde(default)]
+    pub guidance: Vec<String>,
+}
+
+/// Documentation requirements
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DocRules {
+    /// Public functions and types need a `///` comment
+    #[serde(default)]
+    pub public_items: bool,
+    /// Source files start with a `//!` module comment
+    #[serde(default)]
+    pub module: bool,
+}
+
+/// Coding standards loaded from `.zed42/standards.toml`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StandardsProfile {
+    #[serde(default)]
+    pub naming: NamingRules,
+    #[serde(default)]
+    pub forbidden_crates: Vec<String>,
+    #[serde(default)]
+    pub error_handling: ErrorHandlingRules,
+    #[serde(default)]
+    pub docs: DocRules,
+    /// Extra review points passed to the critique as-is
+    #[serde(default)]
+    pub rubric: Vec<String>,
+}
+
+impl StandardsProfile {
+    pub fn from_toml_str(content: &str) -> anyhow::Result<Self> {
+        toml::from_str(content).map_err(|e| anyhow::anyhow!("Invalid standards file: {}", e))
+    }
+
+    /// Load `.zed42/standards.toml` under `project_root`; `None` if the project has none
+    pub fn load(project_root: &Path) -> anyhow::Result<Option<Self>> {
+        let path = project_root.join(STANDARDS_PATH);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = std::fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::from_toml_str(&content).map(Some)
+    }
+
+    /// Banned APIs implied by the error-handling rules
+    pub fn banned_apis(&self) -> Vec<BannedApi> {
+        let rs = vec!["rs".to_string()];
+        let rules = &self.error_handling;
+        let mut banned = Vec::new();
+        let mut ban = |pattern: &str, reason: &str| {
+            banned.push(BannedApi {
+                pattern: pattern.to_string(),
+                reason: reason.to_string(),
+                extensions: rs.clone(),
+            })
+        };
+        if rules.forbid_unwrap {
+            ban(".unwrap()", "Project standards forbid unwrap; propagate the error");
+        }
+        if rules.forbid_expect {
+            ban(".expect(", "Project standards forbid expect; propagate the error");
+        }
+        if rules.forbid_panic {
+            for pattern in ["panic!(", "todo!(", "unimplemented!("] {
+                ban(pattern, "Project standards forbid panicking; return an error");
+            }
+        }
+        banned
+    }
+
+    /// Review points for the critique prompt; empty when the profile sets nothing
+    pub fn critique_rubric(&self) -> String {
+        let mut points = Vec::new();
+        let naming = [
+            ("functions", self.naming.functions),
+            ("types", self.naming.types),
+            ("constants", self.naming.constants),
+        ];
+        for (kind, case) in naming {
+            if let Some(case) = case {
+                points.push(format!("Names of {} are {}", kind, case.label()));
+            }
+        }
+        if !self.forbidden_crates.is_empty() {
+            points.push(format!("Does not use the crates {}", self.forbidden_crates.join(", ")));
+        }
+        let rules = &self.error_handling;
+        let forbidden: Vec<&str> = [
+            (rules.forbid_unwrap, "unwrap()"),
+            (rules.forbid_expect, "expect()"),
+            (rules.forbid_panic, "panic!/todo!/unimplemented!"),
+        ]
+        .into_iter()
+        .filter_map(|(on, name)| on.then_some(name))
+        .collect();
+        if !forbidden.is_empty() {
+            points.push(format!("Never calls {}", forbidden.join(", ")));
+        }
+        points.extend(rules.guidance.iter().cloned());
+        if self.docs.public_items {
+            points.push("Every public function and type has a /// doc comment".to_string());
+        }
+        if self.docs.module {
+            points.push("The file starts with a //! module doc comment".to_string());
+        }
+        points.extend(self.rubric.iter().cloned());
+
+        let mut rubric = String::new();
+        if !points.is_empty() {
+            rubric.push_str("Project standards:");
+            for point in points {
+                let _ = write!(rubric, "\n- {}", point);
+            }
+        }
+        rubric
+    }
+
+    /// Naming and documentation checks for a Rust source file
+    pub fn check_source(&self, path: &str, content: &str) -> Vec<PolicyViolation> {
+        let mut out = Vec::new();
+        if !path.ends_with(".rs") && Path::new(path).extension().is_some() {
+            return out;
+        }
+
+        if self.docs.module && !content.lines().any(|l| l.trim_start().starts_with("//!")) {
+            out.push(PolicyViolation {
+                kind: ViolationKind::MissingDocs,
+                file: path.to_string(),
+                line: Some(1),
+                message: "Missing //! module doc comment".to_string(),
+            });
+        }
+
+        let lines: Vec<&str> = content.lines().collect();
+        for (idx, line) in lines.iter().enumerate() {
+            let Some((keyword, name, public)) = declared_item(line) else {
+                continue;
+            };
+            let (rule, kind) = match keyword {
+                "fn" => (self.naming.functions, "function"),
+                "const" | "static" => (self.naming.constants, "constant"),
+                _ => (self.naming.types, "type"),
+            };
+            if let Some(case) = rule.filter(|case| !case.matches(name)) {
+                out.push(PolicyViolation {
+                    kind: ViolationKind::NamingConvention,
+                    file: path.to_string(),
+                    line: Some(idx + 1),
+                    message: format!("{} '{}' should be {}", kind, name, case.label()),
+                });
+            }
+            if self.docs.public_items && public && keyword != "const" && keyword != "static" && !has_doc(&lines[..idx]) {
+                out.push(PolicyViolation {
+                    kind: ViolationKind::MissingDocs,
+                    file: path.to_string(),
+                    line: Some(idx + 1),
+                    message: format!("Public {} '{}' has no doc comment", kind, name),
+                });
+            }
+        }
+        out
+    }
+}
+
+/// Keyword, name and visibility of an item declared on `line`
+fn declared_item(line: &str) -> Option<(&'static str, &str, bool)> {
+    let trimmed = line.trim_start();
+    if trimmed.starts_with("//") {
+        return None;
+    }
+    let mut words = trimmed.split_whitespace().peekable();
+    let mut public = false;
+    while let Some(&word) = words.peek() {
+        match word {
+            w if w == "pub" || w.starts_with("pub(") => public = true,
+            "async" | "unsafe" | "const" | "extern" | "default" => {
+                // `const` is a qualifier only when another keyword follows
+                if word == "const" {
+                    let mut ahead = words.clone();
+                    ahead.next();
+                    if !matches!(ahead.peek(), Some(&"fn") | Some(&"unsafe") | Some(&"async")) {
+                        break;
+                    }
+                }
+            }
+            _ => break,
+        }
+        words.next();
+    }
+    let keyword = match words.next()? {
+        "fn" => "fn",
+        "struct" => "struct",
+        "enum" => "enum",
+        "trait" => "trait",
+        "type" => "type",
+        "const" => "const",
+        "static" => "static",
+        _ => return None,
+    };
+    let mut rest = words.next()?;
+    if keyword == "static" && rest == "mut" {
+        rest = words.next()?;
+    }
+    let end = rest.find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '#')).unwrap_or(rest.len());
+    let name = &rest[..end];
+    (!name.is_empty() && name != "_").then_some((keyword, name, public))
+}
+
+/// Whether the lines before an item end in a doc comment (attributes skipped)
+fn has_doc(before: &[&str]) -> bool {
+    for line in before.iter().rev() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("#[") {
+            continue;
+        }
+        return trimmed.starts_with("///") || trimmed.starts_with("#[doc");
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STANDARDS: &str = r#"
+forbidden_crates = ["openssl"]
+
+[naming]
+functions = "snake_case"
+types = "upper_camel_case"
+constants = "screaming_snake_case"
+
+[error_handling]
+forbid_unwrap = true
+guidance = ["Wrap errors with context"]
+
+[docs]
+public_items = true
+module = true
+"#;
+
+    #[test]
+    fn test_rubric_lists_every_rule() {
+        let profile = StandardsProfile::from_toml_str(STANDARDS).unwrap();
+        let rubric = profile.critique_rubric();
+        assert!(rubric.starts_with("Project standards:"));
+        assert!(rubric.contains("- Names of types are UpperCamelCase"));
+        assert!(rubric.contains("- Never calls unwrap()"));
+        assert!(rubric.contains("- Wrap errors with context"));
+        assert_eq!(profile.banned_apis().len(), 1);
+        assert!(StandardsProfile::default().critique_rubric().is_empty());
+    }
+
+    #[test]
+    fn test_naming_and_doc_checks() {
+        let profile = StandardsProfile::from_toml_str(STANDARDS).unwrap();
+        let source = "//! Parsing\n\n/// Parses input\npub fn parseInput() {}\n\npub struct raw_token;\nconst max_len: usize = 4;\n#[derive(Debug)]\n/// Documented\npub enum Kind {}\n";
+        let violations = profile.check_source("src/parse.rs", source);
+        let found: Vec<(ViolationKind, Option<usize>)> = violations.iter().map(|v| (v.kind, v.line)).collect();
+        assert_eq!(
+            found,
+            vec![
+                (ViolationKind::NamingConvention, Some(4)),
+                (ViolationKind::NamingConvention, Some(6)),
+                (ViolationKind::MissingDocs, Some(6)),
+                (ViolationKind::NamingConvention, Some(7)),
+            ]
+        );
+
+        let missing_module = profile.check_source("src/lib.rs", "fn ok() {}\n");
+        assert_eq!(missing_module[0].kind, ViolationKind::MissingDocs);
+        assert!(profile.check_source("README.md", "pub fn badName() {}").is_empty());
+    }
+}
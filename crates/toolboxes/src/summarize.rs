@@ -0,0 +1,321 @@
+//! Summaries of large tool outputs for prompts
+//!
+//! A whole file or a full test log can take more of the context window than
+//! the rest of the prompt together. Past a token threshold the executor
+//! stores the full output as a tool-output artifact and hands the agent an
+//! extractive summary instead: the start and end of each long text, plus the
+//! lines that mention errors or failures. The summary names the artifact, and
+//! the agent reads the rest with `read_tool_output` when it needs it.
+
+use crate::{Tool, ToolError, ToolResult};
+use anyhow::Context;
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+
+/// Where tool-output artifacts live, relative to the workspace root
+pub const TOOL_OUTPUT_DIR: &str = ".zed42/tool_outputs";
+
+/// Outputs above this many tokens are summarized
+pub const DEFAULT_SUMMARY_THRESHOLD: usize = 2_000;
+
+/// Lines kept from the start and end of a long text
+const EDGE_LINES: usize = 20;
+
+/// Lines worth keeping from the middle of a long text
+const SALIENT_MARKERS: &[&str] = &[
+    "error", "fail", "panic", "warning", "exception", "traceback", "assert", "denied", "fatal",
+];
+
+/// Rough token estimate (~4 characters per token)
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+/// Replaces oversized tool outputs with summaries backed by artifacts
+#[derive(Debug, Clone)]
+pub struct OutputSummarizer {
+    dir: PathBuf,
+    threshold: usize,
+}
+
+impl OutputSummarizer {
+    /// Store full outputs under `dir` (usually `<workspace>/.zed42/tool_outputs`)
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            threshold: DEFAULT_SUMMARY_THRESHOLD,
+        }
+    }
+
+    /// Token count above which outputs are summarized
+    pub fn with_threshold(mut self, tokens: usize) -> Self {
+        self.threshold = tokens;
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// `output` if it fits, otherwise a summary pointing at the stored original
+    pub fn summarize(&self, tool: &str, output: Value) -> anyhow::Result<Value> {
+        let full = serde_json::to_string_pretty(&output)?;
+        let tokens = estimate_tokens(&full);
+        if tokens <= self.threshold {
+            return Ok(output);
+        }
+
+        let artifact_id = uuid::Uuid::new_v4().to_string();
+        std::fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create {}", self.dir.display()))?;
+        std::fs::write(self.artifact_path(&artifact_id), &full).context("Failed to store tool output")?;
+        tracing::debug!(tool, tokens, %artifact_id, "Summarized large tool output");
+
+        // Share the budget between the long strings, which is where the bulk is
+        let long_strings = count_long_strings(&output, self.string_budget());
+        let budget = (self.threshold / long_strings.max(1)).max(EDGE_LINES * 4);
+        let mut summary = shrink(output, budget);
+        let pointer = json!({
+            "artifact_id": artifact_id,
+            "original_tokens": tokens,
+            "hint": "Output was summarized; call read_tool_output with this artifact_id for the omitted lines"
+        });
+        match summary.as_object_mut() {
+            Some(map) => {
+                map.insert("summarized".to_string(), json!(true));
+                map.insert("full_output".to_string(), pointer);
+            }
+            None => summary = json!({ "summarized": true, "summary": summary, "full_output": pointer }),
+        }
+        Ok(summary)
+    }
+
+    /// Lines `offset..offset + limit` of a stored output, optionally only those containing `pattern`
+    pub fn read(&self, artifact_id: &str, offset: usize, limit: usize, pattern: Option<&str>) -> Result<Value, ToolError> {
+        if uuid::Uuid::parse_str(artifact_id).is_err() {
+            return Err(ToolError::invalid_params(format!("Invalid artifact id: {}", artifact_id)));
+        }
+        let full = std::fs::read_to_string(self.artifact_path(artifact_id))
+            .map_err(|e| ToolError::io(format!("Tool output {}", artifact_id), e))?;
+        // Stored pretty-printed, so long strings are single lines; split those too
+        let lines: Vec<String> = full
+            .lines()
+            .flat_map(|line| line.split("\\n").map(str::to_string).collect::<Vec<_>>())
+            .collect();
+        let selected: Vec<(usize, &String)> = lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| pattern.is_none_or(|p| line.to_lowercase().contains(&p.to_lowercase())))
+            .skip(offset)
+            .take(limit)
+            .collect();
+        Ok(json!({
+            "success": true,
+            "artifact_id": artifact_id,
+            "total_lines": lines.len(),
+            "lines": selected.iter().map(|(n, line)| format!("{:>6}  {}", n + 1, line)).collect::<Vec<_>>(),
+        }))
+    }
+
+    fn artifact_path(&self, artifact_id: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", artifact_id))
+    }
+
+    /// Strings shorter than this are left alone
+    fn string_budget(&self) -> usize {
+        self.threshold / 4
+    }
+}
+
+fn count_long_strings(value: &Value, budget: usize) -> usize {
+    match value {
+        Value::String(s) => usize::from(estimate_tokens(s) > budget),
+        Value::Array(items) => items.iter().map(|v| count_long_strings(v, budget)).sum(),
+        Value::Object(map) => map.values().map(|v| count_long_strings(v, budget)).sum(),
+        _ => 0,
+    }
+}
+
+/// Replace strings over `budget` tokens with extracts; truncate long arrays
+fn shrink(value: Value, budget: usize) -> Value {
+    match value {
+        Value::String(s) if estimate_tokens(&s) > budget => Value::String(extract(&s, budget)),
+        Value::Array(items) if items.len() > EDGE_LINES * 2 => {
+            let omitted = items.len() - EDGE_LINES * 2;
+            let mut kept: Vec<Value> = items[..EDGE_LINES].iter().cloned().map(|v| shrink(v, budget)).collect();
+            kept.push(json!(format!("... {} items omitted ...", omitted)));
+            kept.extend(items[items.len() - EDGE_LINES..].iter().cloned().map(|v| shrink(v, budget)));
+            Value::Array(kept)
+        }
+        Value::Array(items) => Value::Array(items.into_iter().map(|v| shrink(v, budget)).collect()),
+        Value::Object(map) => Value::Object(map.into_iter().map(|(k, v)| (k, shrink(v, budget))).collect()),
+        other => other,
+    }
+}
+
+/// Extractive summary of `text` in roughly `budget` tokens
+///
+/// Keeps the first and last [`EDGE_LINES`] lines and, from the middle, lines
+/// with error/failure markers (with one line of context each) until the
+/// budget runs out. Gaps are marked with the number of omitted lines.
+pub fn extract(text: &str, budget: usize) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let edge = EDGE_LINES.min(lines.len().div_ceil(2));
+    let mut keep = vec![false; lines.len()];
+    keep[..edge].iter_mut().for_each(|k| *k = true);
+    keep[lines.len() - edge..].iter_mut().for_each(|k| *k = true);
+
+    let mut used: usize = lines.iter().zip(&keep).filter(|(_, k)| **k).map(|(l, _)| estimate_tokens(l) + 1).sum();
+    for i in edge..lines.len() - edge {
+        let lower = lines[i].to_lowercase();
+        if !SALIENT_MARKERS.iter().any(|marker| lower.contains(marker)) {
+            continue;
+        }
+        for j in i.saturating_sub(1)..=(i + 1).min(lines.len() - 1) {
+            if !keep[j] {
+                let cost = estimate_tokens(lines[j]) + 1;
+                if used + cost > budget {
+                    break;
+                }
+                keep[j] = true;
+                used += cost;
+            }
+        }
+    }
+
+    let mut out = Vec::new();
+    let mut skipped = 0;
+    for (line, kept) in lines.iter().zip(&keep) {
+        if *kept {
+            if skipped > 0 {
+                out.push(format!("... [{} lines omitted] ...", skipped));
+                skipped = 0;
+            }
+            out.push(truncate_line(line, budget));
+        } else {
+            skipped += 1;
+        }
+    }
+    if skipped > 0 {
+        out.push(format!("... [{} lines omitted] ...", skipped));
+    }
+    out.join("\n")
+}
+
+/// A single line can be huge (minified JS, one-line JSON)
+fn truncate_line(line: &str, budget: usize) -> String {
+    let max_chars = (budget * 4 / 2).max(200);
+    match line.char_indices().nth(max_chars) {
+        Some((idx, _)) => format!("{}... [line truncated]", &line[..idx]),
+        None => line.to_string(),
+    }
+}
+
+/// Parameters for ReadToolOutput tool
+#[derive(Debug, Deserialize)]
+pub struct ReadToolOutputParams {
+    pub artifact_id: String,
+    /// First line to return, counted from 0 (after filtering)
+    #[serde(default)]
+    pub offset: usize,
+    #[serde(default = "default_read_limit")]
+    pub limit: usize,
+    /// Only lines containing this text (case-insensitive)
+    #[serde(default)]
+    pub pattern: Option<String>,
+}
+
+fn default_read_limit() -> usize {
+    200
+}
+
+/// ReadToolOutput tool - pages through a summarized tool output
+pub struct ReadToolOutput {
+    summarizer: OutputSummarizer,
+}
+
+impl ReadToolOutput {
+    pub fn new(summarizer: OutputSummarizer) -> Self {
+        Self { summarizer }
+    }
+}
+
+#[async_trait]
+impl Tool for ReadToolOutput {
+    fn name(&self) -> &str {
+        "read_tool_output"
+    }
+
+    fn description(&self) -> &str {
+        "Read lines from a tool output that was summarized, by artifact_id, optionally filtered by a pattern"
+    }
+
+    fn parameter_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "artifact_id": { "type": "string", "description": "full_output.artifact_id from the summarized result" },
+                "offset": { "type": "integer", "minimum": 0, "description": "First line to return (default 0)" },
+                "limit": { "type": "integer", "minimum": 1, "description": "Lines to return (default 200)" },
+                "pattern": { "type": "string", "description": "Only return lines containing this text" }
+            },
+            "required": ["artifact_id"]
+        })
+    }
+
+    async fn execute(&self, params: Value) -> ToolResult {
+        let params: ReadToolOutputParams = serde_json::from_value(params)
+            .map_err(ToolError::invalid_params)?;
+        self.summarizer
+            .read(&params.artifact_id, params.offset, params.limit, params.pattern.as_deref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn test_log() -> String {
+        let mut lines: Vec<String> = (0..2_000).map(|i| format!("test case_{} ... ok", i)).collect();
+        lines[1_000] = "test case_1000 ... FAILED".to_string();
+        lines[1_001] = "thread 'case_1000' panicked at src/lib.rs:42: assertion failed".to_string();
+        lines.join("\n")
+    }
+
+    #[test]
+    fn test_small_outputs_pass_through() {
+        let dir = tempdir().unwrap();
+        let summarizer = OutputSummarizer::new(dir.path());
+        let output = json!({ "success": true, "stdout": "ok" });
+        assert_eq!(summarizer.summarize("execute_command", output.clone()).unwrap(), output);
+    }
+
+    #[tokio::test]
+    async fn test_large_outputs_are_summarized_and_readable() {
+        let dir = tempdir().unwrap();
+        let summarizer = OutputSummarizer::new(dir.path()).with_threshold(500);
+        let output = json!({ "success": false, "exit_code": 101, "stdout": test_log() });
+
+        let summary = summarizer.summarize("run_tests", output).unwrap();
+        assert_eq!(summary["summarized"], true);
+        assert_eq!(summary["exit_code"], 101);
+        let stdout = summary["stdout"].as_str().unwrap();
+        assert!(stdout.starts_with("test case_0 ... ok"));
+        assert!(stdout.contains("case_1000 ... FAILED"));
+        assert!(stdout.contains("panicked at src/lib.rs:42"));
+        assert!(stdout.contains("lines omitted"));
+        assert!(estimate_tokens(&summary.to_string()) < 1_000);
+
+        let artifact_id = summary["full_output"]["artifact_id"].as_str().unwrap();
+        let tool = ReadToolOutput::new(summarizer);
+        let page = tool
+            .execute(json!({ "artifact_id": artifact_id, "pattern": "case_1500 " }))
+            .await
+            .unwrap();
+        assert_eq!(page["lines"].as_array().unwrap().len(), 1);
+        assert!(tool.execute(json!({ "artifact_id": "../../etc/passwd" })).await.is_err());
+    }
+}
@@ -1 +1,170 @@
 //! Testing and validation tools
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use zed42_core::workspace::{PackageKind, ProjectWorkspace};
+use zed42_core::Team;
+use crate::guardrails::CommandGuardrail;
+use crate::languages::{detect_languages, index_tree, Language};
+use crate::shell::ExecuteCommand;
+use crate::{Tool, ToolError, ToolResult};
+
+/// Parameters for RunTests tool
+#[derive(Debug, Default, Deserialize)]
+pub struct RunTestsParams {
+    /// File the change touched; picks the language and owning package
+    #[serde(default)]
+    pub path: Option<String>,
+    /// Language to test when no path is given
+    #[serde(default)]
+    pub language: Option<Language>,
+}
+
+/// RunTests tool - runs the test adapter for a file's language
+///
+/// Cargo and npm workspace members are tested on their own (`cargo test -p`,
+/// `npm test --workspace`); other files use the language's default runner
+/// (`pytest`, `go test ./...`). Without a path or language, the repository's
+/// most common language is tested.
+pub struct RunTests {
+    sandbox_root: PathBuf,
+    shell: ExecuteCommand,
+}
+
+impl RunTests {
+    pub fn new(sandbox_root: impl Into<PathBuf>) -> Self {
+        let sandbox_root = sandbox_root.into();
+        Self {
+            shell: ExecuteCommand::new(sandbox_root.clone()),
+            sandbox_root,
+        }
+    }
+
+    /// Check the test command against `guardrail` using the rules for `team`
+    pub fn with_guardrail(mut self, guardrail: CommandGuardrail, team: Team) -> Self {
+        self.shell = self.shell.with_guardrail(guardrail, team);
+        self
+    }
+
+    /// (language, package, argv) to run for `params`
+    fn resolve(root: PathBuf, params: RunTestsParams) -> anyhow::Result<(Language, Option<String>, Vec<String>)> {
+        let language = match (&params.path, params.language) {
+            (Some(path), _) => Language::detect(path)
+                .ok_or_else(|| anyhow::anyhow!("No test adapter for '{}'", path))?,
+            (None, Some(language)) => language,
+            (None, None) => detect_languages(&index_tree(&root)?)
+                .first()
+                .map(|(language, _)| *language)
+                .ok_or_else(|| anyhow::anyhow!("No supported source files found"))?,
+        };
+
+        if let Some(path) = &params.path {
+            let workspace = ProjectWorkspace::discover(&root)?;
+            let kind = match language {
+                Language::Rust => Some(PackageKind::Cargo),
+                Language::TypeScript | Language::JavaScript => Some(PackageKind::Npm),
+                Language::Python | Language::Go => None,
+            };
+            if let Some(package) = workspace
+                .package_for_path(path)
+                .filter(|p| Some(p.kind) == kind)
+            {
+                return Ok((language, Some(package.name.clone()), package.test_command.clone()));
+            }
+        }
+        Ok((language, None, language.test_command()))
+    }
+}
+
+#[async_trait]
+impl Tool for RunTests {
+    fn name(&self) -> &str {
+        "run_tests"
+    }
+
+    fn description(&self) -> &str {
+        "Run the tests for a file or language (cargo test, npm test, pytest, go test), scoped to the owning workspace package"
+    }
+
+    /// Test suites outlast ordinary commands
+    fn default_timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(600)
+    }
+
+    fn parameter_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "File the change touched (relative to project root)"
+                },
+                "language": {
+                    "type": "string",
+                    "enum": ["rust", "typescript", "javascript", "python", "go"],
+                    "description": "Language to test when no path is given"
+                }
+            }
+        })
+    }
+
+    async fn execute(&self, params: Value) -> ToolResult {
+        let params: RunTestsParams = if params.is_null() {
+            RunTestsParams::default()
+        } else {
+            serde_json::from_value(params).map_err(ToolError::invalid_params)?
+        };
+
+        let root = self.sandbox_root.clone();
+        let (language, package, argv) = tokio::task::spawn_blocking(move || Self::resolve(root, params)).await??;
+        let (command, args) = argv
+            .split_first()
+            .ok_or_else(|| ToolError::Internal("Empty test command".to_string()))?;
+
+        let mut result = self.shell.execute(json!({ "command": command, "args": args })).await?;
+        result["language"] = json!(language);
+        result["package"] = json!(package);
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_resolves_adapter_per_file() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("crates/core")).unwrap();
+        std::fs::write(root.join("Cargo.toml"), "[workspace]\nmembers = [\"crates/*\"]\n").unwrap();
+        std::fs::write(root.join("crates/core/Cargo.toml"), "[package]\nname = \"core\"\n").unwrap();
+
+        let resolve = |path: &str| {
+            RunTests::resolve(
+                root.to_path_buf(),
+                RunTestsParams { path: Some(path.to_string()), language: None },
+            )
+            .unwrap()
+        };
+
+        let (language, package, argv) = resolve("crates/core/src/lib.rs");
+        assert_eq!(language, Language::Rust);
+        assert_eq!(package.as_deref(), Some("core"));
+        assert_eq!(argv, vec!["cargo", "test", "-p", "core"]);
+
+        let (language, package, argv) = resolve("scripts/check.py");
+        assert_eq!(language, Language::Python);
+        assert!(package.is_none());
+        assert_eq!(argv, vec!["pytest"]);
+
+        assert!(RunTests::resolve(
+            root.to_path_buf(),
+            RunTestsParams { path: Some("notes.txt".to_string()), language: None },
+        )
+        .is_err());
+    }
+}
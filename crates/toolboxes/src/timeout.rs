@@ -0,0 +1,124 @@
+//! Tool timeouts and cancellation
+//!
+//! `Tool::execute` can hang on an external process or a stuck network call.
+//! [`run_tool`] bounds every call by the tool's [`Tool::default_timeout`],
+//! or by a `timeout_secs` parameter on the call itself, and gives up early
+//! when the agent's cancellation token fires. Giving up drops the tool
+//! future; tools that spawn processes set `kill_on_drop`, so the child dies
+//! with it.
+//!
+//! Interrupted calls fail with [`ToolError::TimedOut`] or
+//! [`ToolError::Cancelled`], so agents can tell them from ordinary failures.
+
+use crate::{Tool, ToolError, ToolResult};
+use serde_json::Value;
+use std::time::Duration;
+use zed42_core::CancellationToken;
+
+/// Per-call parameter overriding the tool's default timeout
+pub const TIMEOUT_PARAM: &str = "timeout_secs";
+
+/// Timeout for tools that don't set their own
+pub const DEFAULT_TOOL_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Run `tool` with its timeout, stopping early if `cancel` fires
+///
+/// A `timeout_secs` number in `params` overrides the tool's default and is
+/// removed before the tool sees the parameters.
+pub async fn run_tool(tool: &dyn Tool, mut params: Value, cancel: &CancellationToken) -> ToolResult {
+    let timeout = take_timeout(&mut params)?.unwrap_or_else(|| tool.default_timeout());
+    let interrupted = |error: ToolError| {
+        tracing::warn!(tool = tool.name(), %error, "Tool call interrupted");
+        Err(error)
+    };
+
+    tokio::select! {
+        biased;
+        _ = cancel.cancelled() => interrupted(ToolError::Cancelled),
+        result = tokio::time::timeout(timeout, tool.execute(params)) => {
+            result.unwrap_or_else(|_| interrupted(ToolError::TimedOut(timeout)))
+        }
+    }
+}
+
+/// Remove and parse the per-call timeout
+fn take_timeout(params: &mut Value) -> Result<Option<Duration>, ToolError> {
+    let Some(value) = params.as_object_mut().and_then(|map| map.remove(TIMEOUT_PARAM)) else {
+        return Ok(None);
+    };
+    match value.as_f64() {
+        Some(secs) if secs.is_finite() && secs > 0.0 => Ok(Some(Duration::from_secs_f64(secs))),
+        _ => Err(ToolError::invalid_params(format!("{} must be a positive number of seconds", TIMEOUT_PARAM))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use serde_json::json;
+
+    /// Sleeps for `sleep_ms` and echoes its parameters
+    struct Sleeper;
+
+    #[async_trait]
+    impl Tool for Sleeper {
+        fn name(&self) -> &str {
+            "sleeper"
+        }
+
+        fn description(&self) -> &str {
+            "Sleeps"
+        }
+
+        fn parameter_schema(&self) -> Value {
+            json!({ "type": "object" })
+        }
+
+        fn default_timeout(&self) -> Duration {
+            Duration::from_millis(50)
+        }
+
+        async fn execute(&self, params: Value) -> ToolResult {
+            let ms = params["sleep_ms"].as_u64().unwrap_or(0);
+            tokio::time::sleep(Duration::from_millis(ms)).await;
+            Ok(params)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_timeouts() {
+        let cancel = CancellationToken::new();
+
+        let ok = run_tool(&Sleeper, json!({ "sleep_ms": 0 }), &cancel).await.unwrap();
+        assert_eq!(ok, json!({ "sleep_ms": 0 }));
+
+        let err = run_tool(&Sleeper, json!({ "sleep_ms": 5_000 }), &cancel).await.unwrap_err();
+        assert_eq!(err, ToolError::TimedOut(Duration::from_millis(50)));
+        assert_eq!(err.to_json("sleeper")["error"]["category"], "timeout");
+
+        // The per-call timeout wins over the default and is not passed on
+        let ok = run_tool(&Sleeper, json!({ "sleep_ms": 100, "timeout_secs": 5 }), &cancel).await.unwrap();
+        assert_eq!(ok, json!({ "sleep_ms": 100 }));
+        assert!(matches!(
+            run_tool(&Sleeper, json!({ "timeout_secs": -1 }), &cancel).await,
+            Err(ToolError::InvalidParams(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_cancellation() {
+        let cancel = CancellationToken::new();
+        let trigger = cancel.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            trigger.cancel();
+        });
+
+        let err = run_tool(&Sleeper, json!({ "sleep_ms": 5_000, "timeout_secs": 60 }), &cancel)
+            .await
+            .unwrap_err();
+        assert_eq!(err, ToolError::Cancelled);
+        assert!(!err.is_retryable());
+    }
+}
@@ -1 +1,490 @@
 //! Version control system tools
+//!
+//! Git history tools (`git_blame`, `find_author`) and the ownership map built
+//! from them: for every directory, which authors' commits touch it and what
+//! share of its commits each one has. The map is stored in the knowledge
+//! graph as `person` nodes with `owns` edges to `module` nodes, suggests
+//! CODEOWNERS entries, and names the owners whose review a change needs.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use zed42_memory::knowledge_graph::{EdgeType, KnowledgeEdge, KnowledgeGraphMemory, KnowledgeNode, NodeType};
+use crate::file_manipulation::PathSanitizer;
+use crate::{Tool, ToolError, ToolResult};
+
+/// Commits walked when no limit is given
+pub const DEFAULT_HISTORY_DEPTH: usize = 1000;
+
+/// Module key of the repository root
+const ROOT_MODULE: &str = ".";
+
+/// Lines of a file last changed by one commit
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlameHunk {
+    pub author: String,
+    pub email: String,
+    pub commit: String,
+    /// 1-based first line
+    pub start_line: usize,
+    pub lines: usize,
+}
+
+/// Who last changed each line of `path` (relative to the repository root)
+pub fn blame(repo_root: &Path, path: &str) -> Result<Vec<BlameHunk>> {
+    let repo = git2::Repository::open(repo_root).context("Not a git repository")?;
+    let blame = repo
+        .blame_file(Path::new(path), None)
+        .with_context(|| format!("Failed to blame '{}'", path))?;
+    Ok(blame
+        .iter()
+        .map(|hunk| {
+            let signature = hunk.final_signature();
+            BlameHunk {
+                author: signature.name().unwrap_or("unknown").to_string(),
+                email: signature.email().unwrap_or_default().to_string(),
+                commit: hunk.final_commit_id().to_string(),
+                start_line: hunk.final_start_line(),
+                lines: hunk.lines_in_hunk(),
+            }
+        })
+        .collect())
+}
+
+/// An author's share of a module's history
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OwnerShare {
+    pub author: String,
+    pub email: String,
+    pub commits: usize,
+    /// Fraction of the module's commits (0.0-1.0)
+    pub share: f64,
+}
+
+/// Authors per directory, largest share first
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OwnershipMap {
+    /// Directory (relative, `.` for the root) to its owners
+    pub modules: BTreeMap<String, Vec<OwnerShare>>,
+}
+
+/// Directory key for a file path and each of its ancestors, deepest first
+fn module_chain(path: &Path) -> Vec<String> {
+    let mut chain: Vec<String> = path
+        .ancestors()
+        .skip(1)
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .map(|dir| dir.to_string_lossy().replace('\\', "/"))
+        .collect();
+    chain.push(ROOT_MODULE.to_string());
+    chain
+}
+
+impl OwnershipMap {
+    /// Walk up to `max_commits` commits from HEAD; each commit counts once per directory it touches
+    pub fn from_history(repo_root: &Path, max_commits: usize) -> Result<Self> {
+        let repo = git2::Repository::open(repo_root).context("Not a git repository")?;
+        let mut revwalk = repo.revwalk()?;
+        revwalk.push_head().context("Repository has no commits")?;
+        revwalk.set_sorting(git2::Sort::TIME)?;
+
+        // module -> email -> (name, commits)
+        let mut counts: BTreeMap<String, HashMap<String, (String, usize)>> = BTreeMap::new();
+        for oid in revwalk.take(max_commits) {
+            let commit = repo.find_commit(oid?)?;
+            let tree = commit.tree()?;
+            let parent_tree = match commit.parents().next() {
+                Some(parent) => Some(parent.tree()?),
+                None => None,
+            };
+            let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)?;
+
+            let mut modules = std::collections::BTreeSet::new();
+            for delta in diff.deltas() {
+                if let Some(path) = delta.new_file().path().or_else(|| delta.old_file().path()) {
+                    modules.extend(module_chain(path));
+                }
+            }
+            let author = commit.author();
+            let name = author.name().unwrap_or("unknown").to_string();
+            let email = author.email().unwrap_or_default().to_string();
+            for module in modules {
+                let entry = counts
+                    .entry(module)
+                    .or_default()
+                    .entry(email.clone())
+                    .or_insert_with(|| (name.clone(), 0));
+                entry.1 += 1;
+            }
+        }
+
+        let modules = counts
+            .into_iter()
+            .map(|(module, authors)| {
+                let total: usize = authors.values().map(|(_, n)| n).sum();
+                let mut owners: Vec<OwnerShare> = authors
+                    .into_iter()
+                    .map(|(email, (author, commits))| OwnerShare {
+                        author,
+                        email,
+                        commits,
+                        share: commits as f64 / total.max(1) as f64,
+                    })
+                    .collect();
+                owners.sort_by(|a, b| b.commits.cmp(&a.commits).then_with(|| a.email.cmp(&b.email)));
+                (module, owners)
+            })
+            .collect();
+        Ok(Self { modules })
+    }
+
+    /// Owners of the deepest known directory containing `path`
+    pub fn owners_of(&self, path: &str) -> &[OwnerShare] {
+        let mut candidates = vec![path.trim_end_matches('/').to_string()];
+        candidates.extend(module_chain(Path::new(path)));
+        candidates
+            .iter()
+            .find_map(|module| self.modules.get(module))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Owners of `path` with at least `min_share` of its history
+    pub fn reviewers_for(&self, path: &str, min_share: f64) -> Vec<&OwnerShare> {
+        self.owners_of(path).iter().filter(|o| o.share >= min_share).collect()
+    }
+
+    /// CODEOWNERS lines for directories with a clear owner (`min_share`)
+    ///
+    /// A directory is listed only when its owners differ from its parent's,
+    /// which keeps the file to the boundaries where ownership changes.
+    pub fn suggest_codeowners(&self, min_share: f64) -> String {
+        let owners_at = |module: &str| -> Vec<&str> {
+            self.modules
+                .get(module)
+                .map(|owners| {
+                    owners
+                        .iter()
+                        .filter(|o| o.share >= min_share && !o.email.is_empty())
+                        .map(|o| o.email.as_str())
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
+        let mut out = String::from("# Suggested from git history\n");
+        for module in self.modules.keys() {
+            let owners = owners_at(module);
+            if owners.is_empty() {
+                continue;
+            }
+            let parent = module_chain(Path::new(module)).into_iter().find(|m| self.modules.contains_key(m));
+            if module != ROOT_MODULE && parent.is_some_and(|p| owners_at(&p) == owners) {
+                continue;
+            }
+            let pattern = if module == ROOT_MODULE { "*".to_string() } else { format!("/{}/", module) };
+            let _ = writeln!(out, "{} {}", pattern, owners.join(" "));
+        }
+        out
+    }
+
+    /// Replace the ownership stored in the knowledge graph
+    pub async fn store(&self, graph: &KnowledgeGraphMemory) -> Result<()> {
+        for stale in graph.get_edges_by_type(EdgeType::Owns).await? {
+            graph.delete_node(&stale.from_id).await?;
+        }
+
+        let now = chrono::Utc::now().timestamp();
+        let mut people = BTreeMap::new();
+        for owners in self.modules.values() {
+            for owner in owners {
+                people.entry(owner.email.clone()).or_insert_with(|| owner.author.clone());
+            }
+        }
+        for (email, author) in &people {
+            graph
+                .insert_node(KnowledgeNode {
+                    id: person_node_id(email),
+                    node_type: "person".to_string(),
+                    name: author.clone(),
+                    content: json!({ "email": email }).to_string(),
+                    embedding: None,
+                    metadata: "{}".to_string(),
+                    created_at: now,
+                    updated_at: now,
+                })
+                .await?;
+        }
+        for (module, owners) in &self.modules {
+            let module_id = format!("module:{}", module);
+            if graph.get_node(&module_id).await?.is_none() {
+                graph
+                    .insert_node(KnowledgeNode {
+                        id: module_id.clone(),
+                        node_type: "module".to_string(),
+                        name: module.clone(),
+                        content: String::new(),
+                        embedding: None,
+                        metadata: "{}".to_string(),
+                        created_at: now,
+                        updated_at: now,
+                    })
+                    .await?;
+            }
+            for owner in owners {
+                graph
+                    .insert_edge(KnowledgeEdge {
+                        id: format!("{}->{}", person_node_id(&owner.email), module_id),
+                        edge_type: "owns".to_string(),
+                        from_id: person_node_id(&owner.email),
+                        to_id: module_id.clone(),
+                        metadata: Some(json!({ "commits": owner.commits, "share": owner.share }).to_string()),
+                        created_at: now,
+                    })
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Ownership previously stored with [`OwnershipMap::store`]
+    pub async fn load(graph: &KnowledgeGraphMemory) -> Result<Self> {
+        let people: HashMap<String, KnowledgeNode> = graph
+            .get_nodes_by_type(NodeType::Person)
+            .await?
+            .into_iter()
+            .map(|node| (node.id.clone(), node))
+            .collect();
+
+        let mut map = Self::default();
+        for edge in graph.get_edges_by_type(EdgeType::Owns).await? {
+            let Some(module) = edge.to_id.strip_prefix("module:") else {
+                continue;
+            };
+            let stats: Value = edge.metadata.as_deref().and_then(|m| serde_json::from_str(m).ok()).unwrap_or_default();
+            let email = edge.from_id.strip_prefix("person:").unwrap_or(&edge.from_id).to_string();
+            map.modules.entry(module.to_string()).or_default().push(OwnerShare {
+                author: people.get(&edge.from_id).map(|p| p.name.clone()).unwrap_or_else(|| email.clone()),
+                email,
+                commits: stats["commits"].as_u64().unwrap_or(0) as usize,
+                share: stats["share"].as_f64().unwrap_or(0.0),
+            });
+        }
+        for owners in map.modules.values_mut() {
+            owners.sort_by(|a, b| b.commits.cmp(&a.commits).then_with(|| a.email.cmp(&b.email)));
+        }
+        Ok(map)
+    }
+}
+
+fn person_node_id(email: &str) -> String {
+    format!("person:{}", email)
+}
+
+/// Parameters for GitBlame tool
+#[derive(Debug, Deserialize)]
+pub struct GitBlameParams {
+    /// File to blame (relative to the repository root)
+    pub path: String,
+}
+
+/// GitBlame tool - who last changed each line of a file
+pub struct GitBlame {
+    repo_root: PathBuf,
+    sanitizer: PathSanitizer,
+}
+
+impl GitBlame {
+    pub fn new(repo_root: impl Into<PathBuf>) -> Self {
+        let repo_root = repo_root.into();
+        Self {
+            sanitizer: PathSanitizer::new(repo_root.clone()),
+            repo_root,
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for GitBlame {
+    fn name(&self) -> &str {
+        "git_blame"
+    }
+
+    fn description(&self) -> &str {
+        "Show which author and commit last changed each line range of a file"
+    }
+
+    fn parameter_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "File to blame (relative to project root)"
+                }
+            },
+            "required": ["path"]
+        })
+    }
+
+    async fn execute(&self, params: Value) -> ToolResult {
+        let params: GitBlameParams = serde_json::from_value(params)
+            .map_err(ToolError::invalid_params)?;
+        self.sanitizer.sanitize(&params.path)
+            .map_err(ToolError::sandbox)?;
+
+        let root = self.repo_root.clone();
+        let path = params.path.clone();
+        let hunks = tokio::task::spawn_blocking(move || blame(&root, &path)).await??;
+
+        let mut lines_by_author: BTreeMap<&str, usize> = BTreeMap::new();
+        for hunk in &hunks {
+            *lines_by_author.entry(hunk.email.as_str()).or_default() += hunk.lines;
+        }
+        Ok(json!({
+            "success": true,
+            "path": params.path,
+            "hunks": hunks,
+            "lines_by_author": lines_by_author
+        }))
+    }
+}
+
+/// Parameters for FindAuthor tool
+#[derive(Debug, Deserialize)]
+pub struct FindAuthorParams {
+    /// File or directory (relative to the repository root)
+    pub path: String,
+    /// Commits of history to consider
+    #[serde(default)]
+    pub max_commits: Option<usize>,
+}
+
+/// FindAuthor tool - the people who know a file or directory best
+pub struct FindAuthor {
+    repo_root: PathBuf,
+}
+
+impl FindAuthor {
+    pub fn new(repo_root: impl Into<PathBuf>) -> Self {
+        Self {
+            repo_root: repo_root.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for FindAuthor {
+    fn name(&self) -> &str {
+        "find_author"
+    }
+
+    fn description(&self) -> &str {
+        "Rank the authors of a file or directory by their share of its commit history"
+    }
+
+    fn parameter_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "File or directory (relative to project root)"
+                },
+                "max_commits": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "description": "Commits of history to consider (default 1000)"
+                }
+            },
+            "required": ["path"]
+        })
+    }
+
+    async fn execute(&self, params: Value) -> ToolResult {
+        let params: FindAuthorParams = serde_json::from_value(params)
+            .map_err(ToolError::invalid_params)?;
+        if params.path.contains("..") {
+            return Err(ToolError::sandbox("Path traversal not allowed"));
+        }
+
+        let root = self.repo_root.clone();
+        let max_commits = params.max_commits.unwrap_or(DEFAULT_HISTORY_DEPTH);
+        let map = tokio::task::spawn_blocking(move || OwnershipMap::from_history(&root, max_commits)).await??;
+        Ok(json!({
+            "success": true,
+            "path": params.path,
+            "authors": map.owners_of(&params.path)
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit(repo: &git2::Repository, root: &Path, files: &[(&str, &str)], author: &str) {
+        for (path, content) in files {
+            let full = root.join(path);
+            std::fs::create_dir_all(full.parent().unwrap()).unwrap();
+            std::fs::write(full, content).unwrap();
+        }
+        let mut index = repo.index().unwrap();
+        index.add_all(["*"], git2::IndexAddOption::DEFAULT, None).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::now(author, &format!("{}@example.com", author)).unwrap();
+        let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        repo.commit(Some("HEAD"), &signature, &signature, "change", &tree, &parents).unwrap();
+    }
+
+    fn sample_repo() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = git2::Repository::init(dir.path()).unwrap();
+        commit(&repo, dir.path(), &[("src/db/pool.rs", "a\n"), ("README.md", "r\n")], "alice");
+        commit(&repo, dir.path(), &[("src/db/pool.rs", "a\nb\n")], "alice");
+        commit(&repo, dir.path(), &[("src/web/routes.rs", "w\n")], "bob");
+        dir
+    }
+
+    #[test]
+    fn test_ownership_from_history() {
+        let dir = sample_repo();
+        let map = OwnershipMap::from_history(dir.path(), DEFAULT_HISTORY_DEPTH).unwrap();
+
+        let db = map.owners_of("src/db/pool.rs");
+        assert_eq!(db.len(), 1);
+        assert_eq!((db[0].author.as_str(), db[0].commits), ("alice", 2));
+        let src = map.owners_of("src");
+        assert_eq!(src[0].email, "alice@example.com");
+        assert!((src[0].share - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(map.reviewers_for("src/web/new.rs", 0.5)[0].author, "bob");
+
+        let codeowners = map.suggest_codeowners(0.5);
+        assert!(codeowners.contains("* alice@example.com\n"));
+        assert!(codeowners.contains("/src/web/ bob@example.com\n"));
+        // Same owner as the parent directory
+        assert!(!codeowners.contains("/src/db/"));
+
+        let hunks = blame(dir.path(), "src/db/pool.rs").unwrap();
+        assert_eq!(hunks.iter().map(|h| h.lines).sum::<usize>(), 2);
+        assert!(hunks.iter().all(|h| h.author == "alice"));
+    }
+
+    #[tokio::test]
+    async fn test_ownership_round_trips_through_graph() {
+        let dir = sample_repo();
+        let map = OwnershipMap::from_history(dir.path(), DEFAULT_HISTORY_DEPTH).unwrap();
+        let kg_dir = tempfile::tempdir().unwrap();
+        let graph = KnowledgeGraphMemory::new(kg_dir.path(), "owners", None).await.unwrap();
+
+        map.store(&graph).await.unwrap();
+        map.store(&graph).await.unwrap();
+        assert_eq!(OwnershipMap::load(&graph).await.unwrap(), map);
+    }
+}
@@ -0,0 +1,86 @@
+//! Ignore-aware directory walks
+//!
+//! Listing, bulk reads and indexing walk the project the way git sees it:
+//! `.gitignore` (with or without a repository), `.git/info/exclude` and a
+//! project-level `.zedignore` for paths that are tracked but useless to
+//! agents. Build output and dependency directories are skipped even when no
+//! ignore file mentions them. Tools that genuinely need ignored paths pass
+//! `include_ignored` to walk everything but `.git`.
+
+use ignore::{DirEntry, WalkBuilder};
+use std::path::{Path, PathBuf};
+
+/// Project ignore file with gitignore syntax, read alongside `.gitignore`
+pub const ZEDIGNORE_FILE: &str = ".zedignore";
+
+/// Directories skipped without an ignore rule (build output, dependencies, VCS data)
+pub const SKIPPED_DIRS: &[&str] = &[
+    ".git", "target", "node_modules", "dist", "build", "__pycache__", ".venv", "venv", ".mypy_cache", ".zed42",
+];
+
+/// Walker over `root` honouring ignore files unless `include_ignored`
+pub fn walker(root: &Path, include_ignored: bool) -> WalkBuilder {
+    let mut builder = WalkBuilder::new(root);
+    builder.sort_by_file_name(|a, b| a.cmp(b));
+    if include_ignored {
+        builder.standard_filters(false);
+        builder.filter_entry(|entry| entry.file_name() != ".git");
+    } else {
+        builder
+            .hidden(false)
+            .require_git(false)
+            .add_custom_ignore_filename(ZEDIGNORE_FILE);
+        builder.filter_entry(|entry| !is_skipped_dir(entry));
+    }
+    builder
+}
+
+/// Files under `root`, sorted, skipping ignored paths unless `include_ignored`
+pub fn files(root: &Path, include_ignored: bool) -> Vec<PathBuf> {
+    walker(root, include_ignored)
+        .build()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+        .map(|entry| entry.into_path())
+        .collect()
+}
+
+fn is_skipped_dir(entry: &DirEntry) -> bool {
+    // The walk root itself is never skipped, so a tool can still look inside target/ on request
+    entry.depth() > 0
+        && entry.file_type().is_some_and(|t| t.is_dir())
+        && SKIPPED_DIRS.iter().any(|skip| entry.file_name() == *skip)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_walk_honours_gitignore_zedignore_and_override() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for (path, content) in [
+            (".gitignore", "*.log\ngenerated/\n"),
+            (".zedignore", "fixtures/\n"),
+            ("src/lib.rs", ""),
+            ("debug.log", ""),
+            ("generated/out.rs", ""),
+            ("fixtures/big.json", ""),
+            ("node_modules/dep/index.js", ""),
+        ] {
+            let path = root.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+
+        let relative = |paths: Vec<PathBuf>| -> Vec<String> {
+            paths
+                .iter()
+                .map(|p| p.strip_prefix(root).unwrap().to_string_lossy().replace('\\', "/"))
+                .collect()
+        };
+        assert_eq!(relative(files(root, false)), vec![".gitignore", ".zedignore", "src/lib.rs"]);
+        assert_eq!(relative(files(root, true)).len(), 7);
+    }
+}
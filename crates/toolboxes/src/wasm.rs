@@ -0,0 +1,600 @@
+//! WASM sandbox for untrusted snippets
+//!
+//! [`RunWasm`] runs small generated programs inside wasmtime instead of on
+//! the host, so an EdgeCaseMiner or TestEngineer can execute code it does
+//! not trust (a crash reproducer, a fuzz harness, an input generator)
+//! without touching the project sandbox.
+//!
+//! Programs arrive as WebAssembly text or as Rust source, which `rustc`
+//! compiles to `wasm32-wasip1` in a scratch directory outside the project.
+//! The compiler runs on the host, so Rust sources that could read host files
+//! or environment variables at compile time (`include_str!`, `include_bytes!`,
+//! `include!`, `env!`, `option_env!`, `#[path]` modules, and `macro_rules!`,
+//! which could assemble those invocations) are refused before compiling.
+//! The guest gets WASI with only stdin, stdout, stderr and its arguments:
+//! no preopened directories, no environment and no sockets. Execution is
+//! metered with fuel and linear memory is capped, so a runaway loop or
+//! allocation ends in a trap rather than a hung or swapping host.
+//!
+//! Built with the `wasm` feature.
+
+use anyhow::Context;
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::path::Path;
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+use wasmtime::{Config, Engine, Linker, Module, Store, StoreLimits, StoreLimitsBuilder, Trap};
+use wasmtime_wasi::pipe::{MemoryInputPipe, MemoryOutputPipe};
+use wasmtime_wasi::preview1::{self, WasiP1Ctx};
+use wasmtime_wasi::{I32Exit, WasiCtxBuilder};
+use crate::platform;
+use crate::{Tool, ToolError, ToolResult};
+
+/// Default fuel per run, roughly one unit per executed instruction
+pub const DEFAULT_FUEL: u64 = 500_000_000;
+
+/// Upper bound on the fuel a call may ask for
+pub const MAX_FUEL: u64 = 10_000_000_000;
+
+/// Default cap on the guest's linear memory
+pub const DEFAULT_MEMORY_BYTES: usize = 64 * 1024 * 1024;
+
+/// Default limit for compile plus run
+const WASM_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Bytes of stdout/stderr kept per run
+const MAX_OUTPUT_BYTES: usize = 64 * 1024;
+
+/// Target Rust snippets are compiled for
+const RUST_TARGET: &str = "wasm32-wasip1";
+
+/// Environment variables `rustc` needs to find its toolchain
+/// Bytes of compiler diagnostics returned to the caller
+const MAX_DIAGNOSTIC_BYTES: usize = 8 * 1024;
+
+const RUSTC_ENV: &[&str] = &["PATH", "HOME", "USERPROFILE", "SYSTEMROOT", "TEMP", "TMP", "CARGO_HOME", "RUSTUP_HOME", "RUSTUP_TOOLCHAIN"];
+
+/// Source language of a program
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WasmSource {
+    /// WebAssembly text format exporting `_start` or `main`
+    Wat,
+    /// A Rust program with `fn main`, compiled to `wasm32-wasip1`
+    Rust,
+}
+
+/// Parameters for RunWasm tool
+#[derive(Debug, Deserialize)]
+pub struct RunWasmParams {
+    pub language: WasmSource,
+    pub code: String,
+    /// Arguments after the program name
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub stdin: String,
+    #[serde(default)]
+    pub fuel: Option<u64>,
+}
+
+/// Resource limits of a run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WasmLimits {
+    pub fuel: u64,
+    pub memory_bytes: usize,
+}
+
+impl Default for WasmLimits {
+    fn default() -> Self {
+        Self { fuel: DEFAULT_FUEL, memory_bytes: DEFAULT_MEMORY_BYTES }
+    }
+}
+
+/// Outcome of one run
+#[derive(Debug, Clone, PartialEq)]
+pub struct WasmRun {
+    /// Exit status; 0 for a normal return, `None` after a trap
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+    pub fuel_consumed: u64,
+    pub out_of_fuel: bool,
+    /// Trap or link error that ended the run
+    pub trap: Option<String>,
+}
+
+impl WasmRun {
+    pub fn success(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+}
+
+struct GuestState {
+    wasi: WasiP1Ctx,
+    limits: StoreLimits,
+}
+
+/// Run a compiled module (binary or text) under `limits`
+pub fn run_module(bytes: &[u8], args: &[String], stdin: &str, limits: WasmLimits) -> anyhow::Result<WasmRun> {
+    let mut config = Config::new();
+    config.consume_fuel(true);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(&engine, bytes).context("Invalid WebAssembly module")?;
+
+    let stdout = MemoryOutputPipe::new(MAX_OUTPUT_BYTES);
+    let stderr = MemoryOutputPipe::new(MAX_OUTPUT_BYTES);
+    let mut argv = vec!["snippet".to_string()];
+    argv.extend(args.iter().cloned());
+    // Nothing but the standard streams and arguments: no preopens, env or sockets
+    let wasi = WasiCtxBuilder::new()
+        .stdin(MemoryInputPipe::new(stdin.to_string()))
+        .stdout(stdout.clone())
+        .stderr(stderr.clone())
+        .args(&argv)
+        .build_p1();
+
+    let state = GuestState {
+        wasi,
+        limits: StoreLimitsBuilder::new().memory_size(limits.memory_bytes).instances(1).build(),
+    };
+    let mut store = Store::new(&engine, state);
+    store.limiter(|state| &mut state.limits);
+    store.set_fuel(limits.fuel)?;
+
+    let mut linker: Linker<GuestState> = Linker::new(&engine);
+    preview1::add_to_linker_sync(&mut linker, |state| &mut state.wasi)?;
+
+    let outcome = linker.instantiate(&mut store, &module).and_then(|instance| {
+        let entry = instance
+            .get_func(&mut store, "_start")
+            .or_else(|| instance.get_func(&mut store, "main"))
+            .context("Module exports neither _start nor main")?;
+        entry.typed::<(), ()>(&store)?.call(&mut store, ())
+    });
+
+    let fuel_consumed = limits.fuel.saturating_sub(store.get_fuel().unwrap_or(0));
+    let (exit_code, out_of_fuel, trap) = match outcome {
+        Ok(()) => (Some(0), false, None),
+        Err(e) => match e.downcast_ref::<I32Exit>() {
+            Some(exit) => (Some(exit.0), false, None),
+            None => {
+                let out_of_fuel = e.downcast_ref::<Trap>() == Some(&Trap::OutOfFuel);
+                (None, out_of_fuel, Some(format!("{:#}", e)))
+            }
+        },
+    };
+    drop(store);
+
+    Ok(WasmRun {
+        exit_code,
+        stdout: String::from_utf8_lossy(&stdout.contents()).into_owned(),
+        stderr: String::from_utf8_lossy(&stderr.contents()).into_owned(),
+        fuel_consumed,
+        out_of_fuel,
+        trap,
+    })
+}
+
+/// Macros that read host files or environment variables at compile time
+const HOST_MACROS: &[&str] = &["include_str", "include_bytes", "include", "env", "option_env"];
+
+/// Refuse Rust sources that would make host `rustc` read host files or environment
+///
+/// Works on tokens, so the names may appear in strings and comments.
+pub fn check_rust_source(code: &str) -> Result<(), String> {
+    let tokens = rust_tokens(code);
+    // Bracket depth inside the current `#[...]`/`#![...]`, 0 outside attributes
+    let mut attribute_depth = 0usize;
+    for (i, token) in tokens.iter().enumerate() {
+        match token {
+            RustToken::Punct('[') if attribute_depth > 0 => attribute_depth += 1,
+            RustToken::Punct('[') => {
+                let opens_attribute = i >= 1
+                    && (tokens[i - 1] == RustToken::Punct('#')
+                        || (i >= 2 && tokens[i - 1] == RustToken::Punct('!') && tokens[i - 2] == RustToken::Punct('#')));
+                if opens_attribute {
+                    attribute_depth = 1;
+                }
+            }
+            RustToken::Punct(']') if attribute_depth > 0 => attribute_depth -= 1,
+            _ => {}
+        }
+        let RustToken::Ident(ident) = token else { continue };
+        let next = tokens.get(i + 1);
+        if HOST_MACROS.contains(&ident.as_str()) && next == Some(&RustToken::Punct('!')) {
+            return Err(format!("`{}!` reads the host at compile time and is not allowed in the sandbox", ident));
+        }
+        if ident == "macro_rules" {
+            return Err("`macro_rules!` is not allowed in the sandbox".to_string());
+        }
+        // Anywhere in an attribute, so `cfg_attr(..., path = "...")` is caught too
+        if attribute_depth > 0 && ident == "path" {
+            return Err("`#[path]` modules read host files and are not allowed in the sandbox".to_string());
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, PartialEq)]
+enum RustToken {
+    Ident(String),
+    Punct(char),
+}
+
+/// Identifiers and punctuation of a Rust source, skipping comments, strings and chars
+fn rust_tokens(code: &str) -> Vec<RustToken> {
+    let chars: Vec<char> = code.chars().collect();
+    let is_ident_start = |c: char| c.is_alphabetic() || c == '_';
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '/' && chars.get(i + 1) == Some(&'/') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c == '/' && chars.get(i + 1) == Some(&'*') {
+            let mut depth = 0;
+            while i < chars.len() {
+                if chars[i] == '/' && chars.get(i + 1) == Some(&'*') {
+                    depth += 1;
+                    i += 2;
+                } else if chars[i] == '*' && chars.get(i + 1) == Some(&'/') {
+                    depth -= 1;
+                    i += 2;
+                    if depth == 0 {
+                        break;
+                    }
+                } else {
+                    i += 1;
+                }
+            }
+        } else if c == '"' {
+            i = skip_string(&chars, i + 1);
+        } else if c == '\'' {
+            if chars.get(i + 1) == Some(&'\\') {
+                // Escaped char, e.g. '\'' or '\u{1F600}'
+                i += 3;
+                while i < chars.len() && chars[i] != '\'' {
+                    i += 1;
+                }
+                i += 1;
+            } else if chars.get(i + 2) == Some(&'\'') {
+                i += 3;
+            } else {
+                // A lifetime; its name is read as an identifier next
+                i += 1;
+            }
+        } else if is_ident_start(c) {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let ident: String = chars[start..i].iter().collect();
+            let raw_prefix = matches!(ident.as_str(), "r" | "br" | "cr");
+            if raw_prefix && chars.get(i) == Some(&'#') && chars.get(i + 1).is_some_and(|c| is_ident_start(*c)) {
+                // Raw identifier: r#name is the identifier `name`
+                i += 1;
+                continue;
+            }
+            if raw_prefix && matches!(chars.get(i), Some('"') | Some('#')) {
+                let mut hashes = 0;
+                while chars.get(i) == Some(&'#') {
+                    hashes += 1;
+                    i += 1;
+                }
+                i = skip_raw_string(&chars, i + 1, hashes);
+                continue;
+            }
+            if matches!(ident.as_str(), "b" | "c") && chars.get(i) == Some(&'"') {
+                i = skip_string(&chars, i + 1);
+                continue;
+            }
+            tokens.push(RustToken::Ident(ident));
+        } else {
+            tokens.push(RustToken::Punct(c));
+            i += 1;
+        }
+    }
+    tokens
+}
+
+/// Index after the closing quote of an escaped string starting at `i`
+fn skip_string(chars: &[char], mut i: usize) -> usize {
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 2,
+            '"' => return i + 1,
+            _ => i += 1,
+        }
+    }
+    i
+}
+
+/// Index after the closing `"` and `hashes` hashes of a raw string starting at `i`
+fn skip_raw_string(chars: &[char], mut i: usize, hashes: usize) -> usize {
+    while i < chars.len() {
+        if chars[i] == '"' && (1..=hashes).all(|k| chars.get(i + k) == Some(&'#')) {
+            return i + 1 + hashes;
+        }
+        i += 1;
+    }
+    i
+}
+
+/// Compile a Rust program to `wasm32-wasip1` in a scratch directory
+///
+/// Returns the module, or `Err(diagnostics)` when the program does not compile.
+async fn compile_rust(code: &str) -> anyhow::Result<Result<Vec<u8>, String>> {
+    let dir = std::env::temp_dir().join(format!("zed42-wasm-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir)?;
+    let result = compile_rust_in(&dir, code).await;
+    let _ = std::fs::remove_dir_all(&dir);
+    result
+}
+
+async fn compile_rust_in(dir: &Path, code: &str) -> anyhow::Result<Result<Vec<u8>, String>> {
+    let source = dir.join("snippet.rs");
+    let module = dir.join("snippet.wasm");
+    std::fs::write(&source, code)?;
+
+    let args: Vec<String> = [
+        "--edition", "2021", "-O", "--target", RUST_TARGET, "--crate-name", "snippet", "--error-format=short", "-o",
+    ]
+        .iter()
+        .map(|arg| arg.to_string())
+        // Relative, so diagnostics name the snippet as `snippet.rs`
+        .chain([module.to_string_lossy().into_owned(), "snippet.rs".to_string()])
+        .collect();
+    let mut command = platform::command("rustc", &args);
+    command.current_dir(dir).env_clear().kill_on_drop(true).stdin(Stdio::null());
+    for key in RUSTC_ENV {
+        if let Some(value) = std::env::var_os(key) {
+            command.env(key, value);
+        }
+    }
+    let output = command.output().await.context("Failed to run rustc")?;
+
+    let diagnostics = String::from_utf8_lossy(&output.stderr).into_owned();
+    if output.status.success() {
+        return Ok(Ok(std::fs::read(&module)?));
+    }
+    if diagnostics.contains("may not be installed") || diagnostics.contains("can't find crate for `std`") {
+        anyhow::bail!("Rust target {} is not installed (rustup target add {})", RUST_TARGET, RUST_TARGET);
+    }
+    Ok(Err(snippet_diagnostics(&diagnostics)))
+}
+
+/// Diagnostics located in the snippet itself, capped at [`MAX_DIAGNOSTIC_BYTES`]
+///
+/// Anything pointing into another file is dropped, so a source that still
+/// gets rustc to open a host file can't read it back through the errors.
+fn snippet_diagnostics(diagnostics: &str) -> String {
+    let mut kept = String::new();
+    for line in diagnostics.lines().filter(|line| line.starts_with("snippet.rs:") || line.starts_with("error: aborting")) {
+        if kept.len() + line.len() + 1 > MAX_DIAGNOSTIC_BYTES {
+            kept.push_str("... more diagnostics omitted\n");
+            break;
+        }
+        kept.push_str(line);
+        kept.push('\n');
+    }
+    kept
+}
+
+/// RunWasm tool - executes untrusted snippets in a fuel-limited WASM sandbox
+pub struct RunWasm {
+    limits: WasmLimits,
+}
+
+impl RunWasm {
+    pub fn new() -> Self {
+        Self { limits: WasmLimits::default() }
+    }
+
+    pub fn with_limits(mut self, limits: WasmLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+}
+
+impl Default for RunWasm {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Tool for RunWasm {
+    fn name(&self) -> &str {
+        "run_wasm"
+    }
+
+    fn description(&self) -> &str {
+        "Run an untrusted Rust or WebAssembly-text program in an isolated WASM sandbox with no filesystem or network access and a fuel (instruction) limit. Use it to execute generated reproducers and harnesses safely."
+    }
+
+    fn default_timeout(&self) -> Duration {
+        WASM_TIMEOUT
+    }
+
+    fn parameter_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "language": {
+                    "type": "string",
+                    "enum": ["rust", "wat"],
+                    "description": "rust: a program with fn main (std only, no crates); wat: WebAssembly text exporting _start or main"
+                },
+                "code": {
+                    "type": "string",
+                    "description": "Program source"
+                },
+                "args": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Command line arguments (optional)"
+                },
+                "stdin": {
+                    "type": "string",
+                    "description": "Standard input (optional)"
+                },
+                "fuel": {
+                    "type": "integer",
+                    "description": "Instruction budget (optional, default 500000000)"
+                },
+                "timeout_secs": {
+                    "type": "number",
+                    "description": "Give up after this many seconds (optional, default 120)"
+                }
+            },
+            "required": ["language", "code"]
+        })
+    }
+
+    async fn execute(&self, params: Value) -> ToolResult {
+        let params: RunWasmParams = serde_json::from_value(params)
+            .map_err(ToolError::invalid_params)?;
+        let mut limits = self.limits;
+        if let Some(fuel) = params.fuel {
+            if fuel == 0 || fuel > MAX_FUEL {
+                return Err(ToolError::invalid_params(format!("fuel must be between 1 and {}", MAX_FUEL)));
+            }
+            limits.fuel = fuel;
+        }
+
+        if params.language == WasmSource::Rust {
+            check_rust_source(&params.code)
+                .map_err(|reason| ToolError::PolicyDenied { rule: "wasm_sandbox".to_string(), reason })?;
+        }
+
+        let started = Instant::now();
+        let bytes = match params.language {
+            WasmSource::Wat => params.code.into_bytes(),
+            WasmSource::Rust => match compile_rust(&params.code).await.map_err(|e| ToolError::Environment(format!("{:#}", e)))? {
+                Ok(bytes) => bytes,
+                Err(diagnostics) => {
+                    return Ok(json!({
+                        "success": false,
+                        "stage": "compile",
+                        "stderr": diagnostics,
+                        "duration_ms": started.elapsed().as_millis() as u64
+                    }));
+                }
+            },
+        };
+
+        let run = tokio::task::spawn_blocking(move || run_module(&bytes, &params.args, &params.stdin, limits))
+            .await
+            .map_err(|e| ToolError::Internal(format!("WASM run panicked: {}", e)))?
+            .map_err(|e| ToolError::invalid_params(format!("{:#}", e)))?;
+
+        Ok(json!({
+            "success": run.success(),
+            "stage": "run",
+            "exit_code": run.exit_code,
+            "stdout": run.stdout,
+            "stderr": run.stderr,
+            "fuel_consumed": run.fuel_consumed,
+            "out_of_fuel": run.out_of_fuel,
+            "trap": run.trap,
+            "duration_ms": started.elapsed().as_millis() as u64
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HELLO: &str = r#"(module
+        (import "wasi_snapshot_preview1" "fd_write" (func $fd_write (param i32 i32 i32 i32) (result i32)))
+        (memory (export "memory") 1)
+        (data (i32.const 16) "hi\n")
+        (func (export "_start")
+            (i32.store (i32.const 0) (i32.const 16))
+            (i32.store (i32.const 4) (i32.const 3))
+            (drop (call $fd_write (i32.const 1) (i32.const 0) (i32.const 1) (i32.const 8)))))"#;
+
+    const SPIN: &str = r#"(module (func (export "_start") (loop $l (br $l))))"#;
+
+    const OPEN_FILE: &str = r#"(module
+        (import "wasi_snapshot_preview1" "path_open"
+            (func $path_open (param i32 i32 i32 i32 i32 i64 i64 i32 i32) (result i32)))
+        (import "wasi_snapshot_preview1" "proc_exit" (func $exit (param i32)))
+        (memory (export "memory") 1)
+        (data (i32.const 64) "/etc/passwd")
+        (func (export "_start")
+            (call $exit (call $path_open (i32.const 3) (i32.const 0) (i32.const 64) (i32.const 11)
+                (i32.const 0) (i64.const 2) (i64.const 0) (i32.const 0) (i32.const 0)))))"#;
+
+    #[tokio::test]
+    async fn test_run_wasm_output_fuel_and_isolation() {
+        let tool = RunWasm::new();
+
+        let hello = tool.execute(json!({ "language": "wat", "code": HELLO })).await.unwrap();
+        assert_eq!(hello["success"], true);
+        assert_eq!(hello["stdout"], "hi\n");
+
+        let spin = tool.execute(json!({ "language": "wat", "code": SPIN, "fuel": 10_000 })).await.unwrap();
+        assert_eq!(spin["success"], false);
+        assert_eq!(spin["out_of_fuel"], true);
+
+        // Fd 3 would be the first preopened directory; there is none
+        let open = run_module(OPEN_FILE.as_bytes(), &[], "", WasmLimits::default()).unwrap();
+        assert!(open.exit_code.is_some_and(|errno| errno != 0));
+
+        assert!(tool.execute(json!({ "language": "wat", "code": "(module" })).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rust_sources_reading_the_host_are_refused() {
+        let tool = RunWasm::new();
+        let leak = r#"fn main() { print!("{}", include_str!("/etc/passwd")); }"#;
+        let refused = tool.execute(json!({ "language": "rust", "code": leak })).await;
+        assert!(matches!(refused, Err(ToolError::PolicyDenied { ref rule, .. }) if rule == "wasm_sandbox"));
+
+        for code in [
+            "fn main() { let _ = include_bytes ! (\"/root/.ssh/id_rsa\"); }",
+            "fn main() { println!(\"{}\", env!(\"HOME\")); }",
+            "fn main() { let _ = r#option_env!(\"TOKEN\"); }",
+            "include!(\"/etc/hosts\"); fn main() {}",
+            "macro_rules! m { ($i:ident) => { $i!(\"/etc/passwd\") } } fn main() { m!(include_str); }",
+            "#[path = \"/etc/passwd\"] mod secrets; fn main() {}",
+            "#[cfg_attr(all(), path = \"/etc/passwd\")] mod secrets; fn main() {}",
+            "#![cfg_attr(any(unix, windows), allow(dead_code), path = \"/etc\")] fn main() {}",
+        ] {
+            assert!(check_rust_source(code).is_err(), "{}", code);
+        }
+
+        // The names alone, in strings, comments or paths, are fine
+        let fine = r##"
+            // include_str!("x")
+            fn main() {
+                let quote = '\'';
+                let args: Vec<String> = std::env::args().collect();
+                println!("include_str!(\"{}\") {}", args.len(), r#"env!("HOME")"#);
+                let _ = quote;
+            }"##;
+        assert_eq!(check_rust_source(fine), Ok(()));
+    }
+
+    #[test]
+    fn test_diagnostics_only_cover_the_snippet() {
+        let diagnostics = "/etc/passwd:1:5: error: expected one of `!` or `::`, found `x`\n\
+                           snippet.rs:1:1: error[E0425]: cannot find value `y` in this scope\n\
+                           error: aborting due to 2 previous errors\n";
+        assert_eq!(
+            snippet_diagnostics(diagnostics),
+            "snippet.rs:1:1: error[E0425]: cannot find value `y` in this scope\nerror: aborting due to 2 previous errors\n"
+        );
+        let flood = "snippet.rs:1:1: error: x\n".repeat(MAX_DIAGNOSTIC_BYTES);
+        assert!(snippet_diagnostics(&flood).len() <= MAX_DIAGNOSTIC_BYTES + 40);
+    }
+}
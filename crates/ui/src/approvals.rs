@@ -0,0 +1,136 @@
+//! Native approval dialogs for dangerous tool calls
+//!
+//! [`prompt`] pops a native dialog for every request on the session's
+//! [`ApprovalDesk`], showing the call's full parameters and diff preview,
+//! and answers with the user's choice. The window can also answer from its
+//! own approvals panel (requests arrive as `approval_requested` events) via
+//! [`resolve_approval`]; whichever answers first wins. The app registers
+//! `tauri_plugin_dialog::init()` and manages an `Arc<ApprovalDesk>` as state.
+
+use std::sync::Arc;
+use tauri::{AppHandle, Runtime, State};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind, MessageDialogResult};
+use tokio::sync::broadcast;
+use zed42_core::CancellationToken;
+use zed42_cortex::approvals::{ApprovalDecision, ApprovalDesk, ApprovalRecord, ApprovalRequest, SessionRule};
+
+/// Actor recorded for answers given in the desktop app
+const DEFAULT_ACTOR: &str = "desktop";
+
+/// Preview lines shown in the dialog; the approvals panel shows the rest
+const DIALOG_PREVIEW_LINES: usize = 40;
+
+const ALLOW: &str = "Allow";
+const ALLOW_FOR_SESSION: &str = "Always allow this session";
+const DENY: &str = "Deny";
+
+/// Ask the user about every approval request until `shutdown`
+pub async fn prompt<R: Runtime>(app: AppHandle<R>, desk: Arc<ApprovalDesk>, shutdown: CancellationToken) {
+    let mut requests = desk.subscribe();
+    loop {
+        let request = tokio::select! {
+            _ = shutdown.cancelled() => break,
+            request = requests.recv() => match request {
+                Ok(request) => request,
+                Err(broadcast::error::RecvError::Lagged(missed)) => {
+                    tracing::warn!(missed, "Approval dialogs fell behind; the approvals panel lists the rest");
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+        };
+        let app = app.clone();
+        let desk = desk.clone();
+        // One dialog per request, so a slow answer doesn't hold up the others
+        tokio::spawn(async move {
+            let id = request.id.clone();
+            let decision = match tokio::task::spawn_blocking(move || ask(&app, &request)).await {
+                Ok(decision) => decision,
+                Err(e) => {
+                    tracing::warn!(error = %e, "Approval dialog failed");
+                    return;
+                }
+            };
+            // Already answered from the window, or timed out
+            if let Err(e) = desk.resolve(&id, decision, DEFAULT_ACTOR).await {
+                tracing::debug!(request = %id, "Dialog answer not used: {:#}", e);
+            }
+        });
+    }
+}
+
+/// Show the dialog and wait for the user; closing it denies the call
+fn ask<R: Runtime>(app: &AppHandle<R>, request: &ApprovalRequest) -> ApprovalDecision {
+    let result = app
+        .dialog()
+        .message(dialog_text(request))
+        .title(format!("Approve {}?", request.tool))
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::YesNoCancelCustom(
+            ALLOW.to_string(),
+            ALLOW_FOR_SESSION.to_string(),
+            DENY.to_string(),
+        ))
+        .blocking_show_with_result();
+    match result {
+        MessageDialogResult::Yes | MessageDialogResult::Ok => ApprovalDecision::Allow,
+        MessageDialogResult::No => ApprovalDecision::AllowForSession,
+        MessageDialogResult::Custom(label) if label == ALLOW => ApprovalDecision::Allow,
+        MessageDialogResult::Custom(label) if label == ALLOW_FOR_SESSION => ApprovalDecision::AllowForSession,
+        _ => ApprovalDecision::Deny,
+    }
+}
+
+/// Reason, full parameters and the start of the preview
+fn dialog_text(request: &ApprovalRequest) -> String {
+    let params = serde_json::to_string_pretty(&request.params).unwrap_or_else(|_| request.params.to_string());
+    let mut text = format!("{}\n\nParameters:\n{}", request.reason, params);
+    if let Some(preview) = &request.preview {
+        text.push_str("\n\nChanges:\n");
+        let lines: Vec<&str> = preview.lines().collect();
+        text.push_str(&lines[..lines.len().min(DIALOG_PREVIEW_LINES)].join("\n"));
+        if lines.len() > DIALOG_PREVIEW_LINES {
+            text.push_str(&format!(
+                "\n... {} more line(s) in the approvals panel",
+                lines.len() - DIALOG_PREVIEW_LINES
+            ));
+        }
+    }
+    text.push_str(&format!("\n\n\"{}\" allows every {} call for this session.", ALLOW_FOR_SESSION, request.scope));
+    text
+}
+
+/// Requests waiting for an answer, oldest first
+#[tauri::command]
+pub fn pending_approvals(desk: State<'_, Arc<ApprovalDesk>>) -> Vec<ApprovalRequest> {
+    desk.pending()
+}
+
+#[tauri::command]
+pub async fn resolve_approval(
+    desk: State<'_, Arc<ApprovalDesk>>,
+    request_id: String,
+    decision: ApprovalDecision,
+    actor_name: Option<String>,
+) -> Result<ApprovalRecord, String> {
+    let actor = actor_name.as_deref().unwrap_or(DEFAULT_ACTOR);
+    desk.resolve(&request_id, decision, actor).await.map_err(|e| e.to_string())
+}
+
+/// "Always allow" rules in effect this session
+#[tauri::command]
+pub fn approval_rules(desk: State<'_, Arc<ApprovalDesk>>) -> Vec<SessionRule> {
+    desk.rules()
+}
+
+/// Ask again for calls `rule` allowed; false if there was no such rule
+#[tauri::command]
+pub fn revoke_approval_rule(desk: State<'_, Arc<ApprovalDesk>>, rule: SessionRule) -> bool {
+    desk.revoke_rule(&rule)
+}
+
+/// Decisions made this session, oldest first
+#[tauri::command]
+pub fn approval_history(desk: State<'_, Arc<ApprovalDesk>>) -> Vec<ApprovalRecord> {
+    desk.history()
+}
@@ -0,0 +1,48 @@
+//! Event bus bridge to the desktop window
+//!
+//! The window listens for [`EVENT_NAME`] and receives the same versioned
+//! envelopes `zed42 top` and WebSocket viewers get. On load, and again
+//! after a reload, it calls [`event_catch_up`] with the last sequence
+//! number it rendered (none on first load) and applies the result before
+//! live events.
+
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Runtime, State};
+use tokio::sync::broadcast;
+use zed42_core::CancellationToken;
+use zed42_cortex::dashboard::{EventBus, EventEnvelope};
+
+/// Tauri event carrying each envelope
+pub const EVENT_NAME: &str = "zed42://event";
+
+/// Emit every event published on `bus` to the app's windows until `shutdown`
+pub async fn forward<R: Runtime>(app: AppHandle<R>, bus: Arc<EventBus>, shutdown: CancellationToken) {
+    let (_, mut events) = bus.subscribe();
+    loop {
+        let envelope = tokio::select! {
+            _ = shutdown.cancelled() => break,
+            event = events.recv() => match event {
+                Ok(envelope) => envelope,
+                Err(broadcast::error::RecvError::Lagged(_)) => bus.snapshot_envelope(),
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+        };
+        if let Err(e) = app.emit(EVENT_NAME, &envelope) {
+            tracing::warn!(error = %e, "Failed to emit dashboard event to the window");
+        }
+    }
+}
+
+/// Events after `since`, or a snapshot when there is no `since` or it is too old
+#[tauri::command]
+pub fn event_catch_up(bus: State<'_, Arc<EventBus>>, since: Option<u64>) -> Vec<EventEnvelope> {
+    since
+        .and_then(|seq| bus.since(seq))
+        .unwrap_or_else(|| vec![bus.snapshot_envelope()])
+}
+
+/// The last `last` events, oldest first
+#[tauri::command]
+pub fn event_backfill(bus: State<'_, Arc<EventBus>>, last: usize) -> Vec<EventEnvelope> {
+    bus.backfill(last)
+}
@@ -5,6 +5,11 @@
 //!
 //! Desktop-native UI using Tauri 2.0 and SolidJS
 
+pub mod approvals;
+pub mod events;
+pub mod notifications;
+pub mod settings;
+
 // Placeholder for UI implementation
 // This will be developed after core functionality is established
 
@@ -0,0 +1,36 @@
+//! Desktop notifications
+//!
+//! The app builds its [`Notifier`](zed42_notify::Notifier) from
+//! `config/notify.toml` with a shared [`DesktopChannel`], hands it to
+//! `Cortex::start_notifications`, and runs [`forward`] so notifications
+//! routed to the `desktop` channel reach the window as [`EVENT_NAME`].
+
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Runtime};
+use tokio::sync::broadcast;
+use zed42_core::CancellationToken;
+use zed42_notify::channels::DesktopChannel;
+
+/// Tauri event carrying each rendered notification
+pub const EVENT_NAME: &str = "zed42://notification";
+
+/// Emit every notification delivered to `desktop` to the app's windows until `shutdown`
+pub async fn forward<R: Runtime>(app: AppHandle<R>, desktop: Arc<DesktopChannel>, shutdown: CancellationToken) {
+    let mut notifications = desktop.subscribe();
+    loop {
+        let notification = tokio::select! {
+            _ = shutdown.cancelled() => break,
+            notification = notifications.recv() => match notification {
+                Ok(notification) => notification,
+                Err(broadcast::error::RecvError::Lagged(missed)) => {
+                    tracing::warn!(missed, "Desktop notifications fell behind");
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+        };
+        if let Err(e) = app.emit(EVENT_NAME, &notification) {
+            tracing::warn!(error = %e, "Failed to emit notification to the window");
+        }
+    }
+}
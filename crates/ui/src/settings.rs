@@ -0,0 +1,74 @@
+//! Settings commands for the desktop window
+//!
+//! Budgets, execution profiles and approval gates are edited through the
+//! app's [`SettingsService`]; invalid values come back as the command's
+//! error string and every accepted change lands in the ledger's audit log.
+//! The app manages an `Arc<SettingsService>` and the running
+//! `Arc<Mutex<Cortex>>` as state.
+
+use std::sync::Arc;
+use tauri::State;
+use tokio::sync::Mutex;
+use zed42_core::ledger::{Budget, LedgerEntry};
+use zed42_cortex::presets::ApprovalGate;
+use zed42_cortex::settings::{BudgetEdit, SettingsService};
+use zed42_cortex::Cortex;
+use zed42_mom::types::ExecutionProfile;
+
+/// Actor recorded when the window doesn't name one
+const DEFAULT_ACTOR: &str = "desktop";
+
+fn actor(actor: &Option<String>) -> &str {
+    actor.as_deref().unwrap_or(DEFAULT_ACTOR)
+}
+
+#[tauri::command]
+pub async fn update_budget(
+    settings: State<'_, Arc<SettingsService>>,
+    edit: BudgetEdit,
+    actor_name: Option<String>,
+) -> Result<Budget, String> {
+    settings.update_budget(edit, actor(&actor_name)).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn execution_profile(
+    settings: State<'_, Arc<SettingsService>>,
+    agent_id: String,
+) -> Result<Option<ExecutionProfile>, String> {
+    settings.execution_profile(&agent_id).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_execution_profile(
+    settings: State<'_, Arc<SettingsService>>,
+    profile: ExecutionProfile,
+    actor_name: Option<String>,
+) -> Result<(), String> {
+    settings.set_execution_profile(profile, actor(&actor_name)).await.map_err(|e| e.to_string())
+}
+
+/// Replace the gates of a queued run or preset; returns the gates now in effect
+#[tauri::command]
+pub async fn set_approval_gates(
+    settings: State<'_, Arc<SettingsService>>,
+    cortex: State<'_, Arc<Mutex<Cortex>>>,
+    target: String,
+    gates: Vec<ApprovalGate>,
+    actor_name: Option<String>,
+) -> Result<Vec<ApprovalGate>, String> {
+    let mut cortex = cortex.lock().await;
+    settings
+        .set_approval_gates(&mut cortex, &target, gates, actor(&actor_name))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Audit entries, oldest first, optionally for one entity
+#[tauri::command]
+pub async fn audit_log(
+    settings: State<'_, Arc<SettingsService>>,
+    entity_id: Option<String>,
+) -> Result<Vec<LedgerEntry>, String> {
+    settings.audit_log(entity_id.as_deref()).await.map_err(|e| e.to_string())
+}